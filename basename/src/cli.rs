@@ -14,6 +14,10 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
         .arg(
             Arg::with_name("NAME")
                 .help("Name of the file(s) to use.")
+                .long_help(
+                    "Name of the file(s) to use.\n\nWithout --multiple or --suffix, a second NAME \
+                     is the suffix to remove.",
+                )
                 .required(true)
                 .multiple(true),
         )
@@ -1,26 +1,37 @@
+use std::process;
+
 mod cli;
 
 fn main() {
     let matches = cli::create_app().get_matches();
 
+    // We know that NAME is required, so it's ok to unwrap
+    let mut names: Vec<_> = matches.values_of("NAME").unwrap().collect();
+
     let multiple_paths = matches.is_present("multiple") || matches.is_present("suffix");
 
-    let suffix = matches.value_of("suffix").unwrap_or("");
+    let mut suffix = matches.value_of("suffix").unwrap_or("");
 
-    let line_ending = if matches.is_present("zero") { '\0' } else { '\n' };
+    // Without multiple names, the second one is the suffix
+    if !multiple_paths {
+        if names.len() > 2 {
+            eprintln!("basename: extra operand '{}'", names[2]);
+            process::exit(1);
+        }
 
-    if multiple_paths {
-        let paths = matches.values_of("NAME").unwrap();
-        for path in paths {
-            print!("{} {}", basename(path, suffix), line_ending);
+        if names.len() == 2 {
+            suffix = names.pop().unwrap_or_default();
         }
-    } else {
-        let path = matches.value_of("NAME").unwrap();
-        print!("{} {}", basename(path, suffix), line_ending);
+    }
+
+    let line_ending = if matches.is_present("zero") { '\0' } else { '\n' };
+
+    for path in names {
+        print!("{}{}", basename(path, suffix), line_ending);
     }
 }
 
-/// Get `full_path` basename, removing the given `suffix`.
+/// Get `full_path` basename, without the trailing slashes, removing the given `suffix`.
 ///
 /// ## Examples:
 /// ```rust
@@ -37,10 +48,19 @@ fn main() {
 /// # }
 /// ```
 fn basename(full_path: &str, suffix: &str) -> String {
-    let split_full_path: Vec<&str> = full_path.split('/').collect();
-    match split_full_path.last() {
-        Some(name) => name.strip_suffix(suffix).unwrap_or(name).to_string(),
-        None => "".to_string(),
+    let trimmed = full_path.trim_end_matches('/');
+
+    // A path of only slashes is the root
+    if trimmed.is_empty() {
+        return if full_path.is_empty() { String::new() } else { "/".to_string() };
+    }
+
+    let name = trimmed.rsplit('/').next().unwrap_or(trimmed);
+
+    // The suffix is not removed when it is the whole name
+    match name.strip_suffix(suffix) {
+        Some(stripped) if !stripped.is_empty() => stripped.to_string(),
+        _ => name.to_string(),
     }
 }
 
@@ -71,4 +91,19 @@ mod tests {
         assert_eq!("b", basename("/usr/bin", "in"));
         assert_eq!("Doc", basename("~/Documents", "uments"));
     }
+
+    #[test]
+    fn basename_trailing_slashes() {
+        assert_eq!("bin", basename("/usr/bin//", ""));
+        assert_eq!("b", basename("a//b/", ""));
+        assert_eq!("/", basename("/", ""));
+        assert_eq!("/", basename("//", ""));
+        assert_eq!("", basename("", ""));
+    }
+
+    #[test]
+    fn basename_whole_suffix() {
+        assert_eq!(".jpg", basename("~/Pictures/.jpg", ".jpg"));
+        assert_eq!("image", basename("~/Pictures/image.jpg/", ".jpg"));
+    }
 }
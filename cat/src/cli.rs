@@ -34,6 +34,27 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .long("show-ends")
                 .short("E"),
         )
+        .arg(
+            Arg::with_name("show_tabs")
+                .help("Display TAB characters as ^I.")
+                .long("show-tabs")
+                .short("T"),
+        )
+        .arg(
+            Arg::with_name("show_nonprinting")
+                .help("Use ^ and M- notation, except for LFD and TAB.")
+                .long("show-nonprinting")
+                .short("v"),
+        )
+        .arg(Arg::with_name("show_all").help("Equivalent to -vET.").long("show-all").short("A"))
+        .arg(Arg::with_name("e").help("Equivalent to -vE.").short("e"))
+        .arg(Arg::with_name("t").help("Equivalent to -vT.").short("t"))
+        .arg(
+            Arg::with_name("unbuffered")
+                .help("Write without delay, this is always the case. (ignored)")
+                .long("unbuffered")
+                .short("u"),
+        )
         .arg(
             Arg::with_name("squeeze_blank")
                 .help(
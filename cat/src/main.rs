@@ -1,12 +1,19 @@
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
 use std::{
     fs::File,
-    io::{prelude::*, stdin, BufReader, ErrorKind},
+    io::{self, prelude::*, stdin, stdout, BufReader, BufWriter, ErrorKind},
 };
 
 use clap::ArgMatches;
+#[cfg(unix)]
+use coreutils_core::copy::copy;
 
 mod cli;
 
+const NEW_LINE: u8 = b'\n';
+const TAB: u8 = b'\t';
+
 fn main() {
     let matches = cli::create_app().get_matches();
 
@@ -19,132 +26,332 @@ fn main() {
 
     let mut exit_code = 0;
 
-    let mut line_number = 1;
-    let mut last_line_empty = false;
+    let stdout = stdout();
+    let mut output = BufWriter::new(stdout.lock());
+    let mut state = CatState::new();
+
     for filename in files.iter() {
-        if filename == "-" {
-            loop {
-                let mut line = String::new();
-
-                match stdin().read_line(&mut line) {
-                    Ok(0) => break, // EOF
-                    Ok(_) => {
-                        line.pop();
-                        print_line(line, flags, &mut line_number, &mut last_line_empty)
-                    },
-                    Err(error) => eprintln!("cat: stdin: {}", error),
-                }
+        let result = if filename == "-" {
+            let stdin = stdin();
+            let input = stdin.lock();
+
+            if flags.is_plain() {
+                cat_plain(input, &mut output)
+            } else {
+                cat(input, &mut output, flags, &mut state)
             }
         } else {
             match File::open(filename) {
-                Ok(file) => {
-                    for line in BufReader::new(file).lines() {
-                        let line = match line {
-                            Ok(line) => line,
-                            Err(error) => {
-                                eprintln!("cat: {}: I/O error: {}", filename, error);
-                                exit_code = 1;
-                                break;
-                            },
-                        };
-                        print_line(line, flags, &mut line_number, &mut last_line_empty);
-                    }
-                },
-                Err(e) => {
-                    exit_code = 1;
-                    match e.kind() {
-                        ErrorKind::NotFound => {
-                            eprintln!("cat: {}: No such file or directory", filename)
-                        },
-                        ErrorKind::PermissionDenied => {
-                            eprintln!("cat: {}: Permission denied", filename)
-                        },
-                        _ => eprintln!("cat: {}: Unknown error", filename),
-                    }
-                },
-            };
+                Ok(file) if flags.is_plain() => cat_plain(file, &mut output),
+                Ok(file) => cat(BufReader::new(file), &mut output, flags, &mut state),
+                Err(err) => Err(err),
+            }
+        };
+
+        if let Err(err) = result {
+            exit_code = 1;
+
+            // Write what was already processed before the error message
+            output.flush().ok();
+
+            let filename = if filename == "-" { "stdin" } else { filename };
+            match err.kind() {
+                ErrorKind::NotFound => eprintln!("cat: {}: No such file or directory", filename),
+                ErrorKind::PermissionDenied => eprintln!("cat: {}: Permission denied", filename),
+                ErrorKind::BrokenPipe => break,
+                _ => eprintln!("cat: {}: {}", filename, err),
+            }
+        }
+    }
+
+    let flushed = finish(&mut output, &mut state).and_then(|()| output.flush());
+
+    if let Err(err) = flushed {
+        if err.kind() != ErrorKind::BrokenPipe {
+            eprintln!("cat: write error: {}", err);
+            exit_code = 1;
         }
     }
 
     std::process::exit(exit_code);
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 struct CatFlags {
     pub number: bool,
     pub number_nonblank: bool,
     pub show_ends: bool,
+    pub show_tabs: bool,
+    pub show_nonprinting: bool,
     pub squeeze_blank: bool,
 }
 
 impl CatFlags {
     pub fn from_matches(matches: &ArgMatches) -> Self {
+        let show_all = matches.is_present("show_all");
+        let e = matches.is_present("e");
+        let t = matches.is_present("t");
+
         CatFlags {
             number: matches.is_present("number"),
             number_nonblank: matches.is_present("number_nonblank"),
-            show_ends: matches.is_present("show_ends"),
+            show_ends: matches.is_present("show_ends") || show_all || e,
+            show_tabs: matches.is_present("show_tabs") || show_all || t,
+            show_nonprinting: matches.is_present("show_nonprinting") || show_all || e || t,
             squeeze_blank: matches.is_present("squeeze_blank"),
         }
     }
+
+    /// Returns `true` if no formatting is applied to the output.
+    pub fn is_plain(&self) -> bool {
+        !(self.number
+            || self.number_nonblank
+            || self.show_ends
+            || self.show_tabs
+            || self.show_nonprinting
+            || self.squeeze_blank)
+    }
 }
 
-fn print_line(line: String, flags: CatFlags, line_number: &mut usize, last_line_empty: &mut bool) {
-    if flags.squeeze_blank {
-        if line.is_empty() {
-            if !*last_line_empty {
-                if !flags.number_nonblank && flags.number {
-                    if flags.show_ends {
-                        println!("{:6}  $", line_number);
-                    } else {
-                        println!("{:6}  ", line_number);
-                    }
-
-                    *line_number += 1;
-                } else if flags.show_ends {
-                    println!("$");
-                } else {
-                    println!();
+/// State kept between the input files, so the line numbering and blank lines squeezing
+/// continue across them.
+#[derive(Debug, Clone, Copy)]
+struct CatState {
+    line_number: usize,
+    at_line_start: bool,
+    last_line_empty: bool,
+    /// Whether the last input ended in a carriage return, written as `^M` with `-E` if
+    /// the next one starts with the end of its line.
+    pending_cr: bool,
+}
+
+impl CatState {
+    fn new() -> Self {
+        CatState { line_number: 1, at_line_start: true, last_line_empty: false, pending_cr: false }
+    }
+}
+
+/// Copy `input` to `output` as is, moving the data kernel-side when possible.
+#[cfg(unix)]
+fn cat_plain<R: AsRawFd, W: Write + AsRawFd>(
+    input: R, output: &mut BufWriter<W>,
+) -> io::Result<()> {
+    output.flush()?;
+    copy(input.as_raw_fd(), output.get_ref().as_raw_fd())?;
+    Ok(())
+}
+
+/// Copy `input` to `output` as is.
+#[cfg(not(unix))]
+fn cat_plain<R: Read, W: Write>(mut input: R, output: &mut W) -> io::Result<()> {
+    io::copy(&mut input, output)?;
+    Ok(())
+}
+
+/// Copy `input` to `output`, applying the formatting of `flags`.
+fn cat<R: BufRead, W: Write>(
+    mut input: R, output: &mut W, flags: CatFlags, state: &mut CatState,
+) -> io::Result<()> {
+    loop {
+        let buffer = match input.fill_buf() {
+            Ok([]) => break, // EOF
+            Ok(buffer) => buffer,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+
+        let len = buffer.len();
+        write_formatted(buffer, output, flags, state)?;
+        input.consume(len);
+
+        // Keep interactive inputs responsive
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Write `buffer` to `output` applying the formatting of `flags`. The `buffer` may end in
+/// the middle of a line.
+fn write_formatted<W: Write>(
+    mut buffer: &[u8], output: &mut W, flags: CatFlags, state: &mut CatState,
+) -> io::Result<()> {
+    while !buffer.is_empty() {
+        if state.pending_cr {
+            state.pending_cr = false;
+            output.write_all(if buffer[0] == NEW_LINE { b"^M" } else { b"\r" })?;
+        }
+
+        if state.at_line_start {
+            if buffer[0] == NEW_LINE {
+                if flags.squeeze_blank && state.last_line_empty {
+                    buffer = &buffer[1..];
+                    continue;
                 }
-            }
-            *last_line_empty = true;
-        } else {
-            if flags.number || flags.number_nonblank {
-                if flags.show_ends {
-                    println!("{:6}  {}$", line_number, line);
-                } else {
-                    println!("{:6}  {}", line_number, line);
+                state.last_line_empty = true;
+
+                if flags.number && !flags.number_nonblank {
+                    write!(output, "{:6}\t", state.line_number)?;
+                    state.line_number += 1;
                 }
-                *line_number += 1;
-            } else if flags.show_ends {
-                println!("{}$", line);
             } else {
-                println!("{}", line);
+                state.last_line_empty = false;
+
+                if flags.number || flags.number_nonblank {
+                    write!(output, "{:6}\t", state.line_number)?;
+                    state.line_number += 1;
+                }
             }
-            *last_line_empty = false;
         }
-    } else if flags.number_nonblank {
-        if !line.is_empty() {
+
+        let (line, ends) = match buffer.iter().position(|&b| b == NEW_LINE) {
+            Some(pos) => (&buffer[..pos], true),
+            None => (buffer, false),
+        };
+
+        // Like GNU, the carriage return at the end of a line is shown with -E
+        let cr = flags.show_ends && !flags.show_nonprinting && line.last() == Some(&b'\r');
+        let shown = if cr { &line[..line.len() - 1] } else { line };
+
+        if flags.show_nonprinting || flags.show_tabs {
+            write_visible(shown, output, flags)?;
+        } else {
+            output.write_all(shown)?;
+        }
+
+        if cr && ends {
+            output.write_all(b"^M")?;
+        }
+        state.pending_cr = cr && !ends;
+
+        if ends {
             if flags.show_ends {
-                println!("{:6}  {}$", line_number, line);
-            } else {
-                println!("{:6}  {}", line_number, line);
+                output.write_all(b"$")?;
             }
-            *line_number += 1;
-        } else if flags.show_ends {
-            println!("$");
+            output.write_all(b"\n")?;
+            buffer = &buffer[line.len() + 1..];
         } else {
-            println!();
+            buffer = &[];
         }
-    } else if flags.number {
-        if flags.show_ends {
-            println!("{:6}  {}$", line_number, line);
+        state.at_line_start = ends;
+    }
+
+    Ok(())
+}
+
+/// Write the carriage return that ended the last input, if it wasn't written.
+fn finish<W: Write>(output: &mut W, state: &mut CatState) -> io::Result<()> {
+    if state.pending_cr {
+        state.pending_cr = false;
+        output.write_all(b"\r")?;
+    }
+
+    Ok(())
+}
+
+/// Write `line` using the `^` and `M-` notation for the non printing characters and TAB
+/// characters as indicated by `flags`.
+fn write_visible<W: Write>(line: &[u8], output: &mut W, flags: CatFlags) -> io::Result<()> {
+    for &byte in line {
+        if byte == TAB {
+            if flags.show_tabs {
+                output.write_all(b"^I")?;
+            } else {
+                output.write_all(&[TAB])?;
+            }
+            continue;
+        }
+
+        if !flags.show_nonprinting {
+            output.write_all(&[byte])?;
+            continue;
+        }
+
+        let byte = if byte >= 128 {
+            output.write_all(b"M-")?;
+            byte - 128
         } else {
-            println!("{:6}  {}", line_number, line);
+            byte
+        };
+
+        match byte {
+            0..=31 => output.write_all(&[b'^', byte + 64])?,
+            127 => output.write_all(b"^?")?,
+            _ => output.write_all(&[byte])?,
         }
-        *line_number += 1;
-    } else if flags.show_ends {
-        println!("{}$", line);
-    } else {
-        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(input: &[&[u8]], flags: CatFlags) -> String {
+        let mut out = Vec::new();
+        let mut state = CatState::new();
+
+        for buffer in input {
+            cat(BufReader::new(*buffer), &mut out, flags, &mut state).unwrap();
+        }
+        finish(&mut out, &mut state).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn cat_plain() {
+        let flags = CatFlags::default();
+        assert_eq!(run(&[b"foo\nbar", b"baz\n"], flags), "foo\nbarbaz\n");
+    }
+
+    #[test]
+    fn cat_number() {
+        let flags = CatFlags { number: true, ..CatFlags::default() };
+        assert_eq!(run(&[b"foo\n\nbar\n"], flags), "     1\tfoo\n     2\t\n     3\tbar\n");
+    }
+
+    #[test]
+    fn cat_number_across_files() {
+        let flags = CatFlags { number: true, ..CatFlags::default() };
+        assert_eq!(
+            run(&[b"foo\nba", b"r\nbaz\n"], flags),
+            "     1\tfoo\n     2\tbar\n     3\tbaz\n"
+        );
+    }
+
+    #[test]
+    fn cat_number_nonblank() {
+        let flags = CatFlags { number: true, number_nonblank: true, ..CatFlags::default() };
+        assert_eq!(run(&[b"foo\n\nbar\n"], flags), "     1\tfoo\n\n     2\tbar\n");
+    }
+
+    #[test]
+    fn cat_squeeze_blank() {
+        let flags = CatFlags { squeeze_blank: true, ..CatFlags::default() };
+        assert_eq!(run(&[b"\n\nfoo\n\n\n", b"\nbar\n"], flags), "\nfoo\n\nbar\n");
+    }
+
+    #[test]
+    fn cat_show_ends_and_tabs() {
+        let flags = CatFlags { show_ends: true, show_tabs: true, ..CatFlags::default() };
+        assert_eq!(run(&[b"a\tb\n\n"], flags), "a^Ib$\n$\n");
+    }
+
+    #[test]
+    fn cat_show_ends_crlf() {
+        let flags = CatFlags { show_ends: true, ..CatFlags::default() };
+        assert_eq!(run(&[b"a\r\nb\r\r\nc\r"], flags), "a^M$\nb\r^M$\nc\r");
+        assert_eq!(run(&[b"a\r", b"\nb\r", b"c\n"], flags), "a^M$\nb\rc$\n");
+
+        let flags = CatFlags { show_ends: true, show_nonprinting: true, ..CatFlags::default() };
+        assert_eq!(run(&[b"a\r\n"], flags), "a^M$\n");
+    }
+
+    #[test]
+    fn cat_show_nonprinting() {
+        let flags = CatFlags { show_nonprinting: true, ..CatFlags::default() };
+        assert_eq!(run(&[b"\x00\x1b\r\t\x7f\xe9\xff\xa0\n"], flags), "^@^[^M\t^?M-iM-^?M- \n");
     }
 }
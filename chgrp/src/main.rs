@@ -0,0 +1,102 @@
+use std::{fs, os::unix::ffi::OsStrExt, path::Path, process};
+
+use clap::ArgMatches;
+use coreutils_core::{
+    chowner::{ChownOptions, Chowner, Ownership, Report},
+    walk::Follow,
+};
+
+mod cli;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let reference = matches.value_of_os("reference");
+    let mut operands = matches.values_of_os("FILE").map_or_else(Vec::new, Iterator::collect);
+
+    let mut options = chgrp_options(&matches);
+
+    let group =
+        if reference.is_none() && !operands.is_empty() { Some(operands.remove(0)) } else { None };
+
+    if operands.is_empty() {
+        match group {
+            Some(group) => eprintln!("chgrp: missing operand after '{}'", group.to_string_lossy()),
+            None => eprintln!("chgrp: missing operand"),
+        }
+        process::exit(1);
+    }
+
+    options.ownership = match (reference, group) {
+        (Some(reference), _) => match fs::metadata(reference) {
+            // Only the group of the reference is set
+            Ok(metadata) => Ownership { uid: None, user_name: None, ..Ownership::of(&metadata) },
+            Err(err) => {
+                eprintln!(
+                    "chgrp: failed to get attributes of '{}': {}",
+                    Path::new(reference).display(),
+                    err
+                );
+                process::exit(1);
+            },
+        },
+        // Without a group nothing is changed
+        (None, Some(group)) if group.is_empty() => Ownership::default(),
+        (None, Some(group)) => Ownership::parse_group(&String::from_utf8_lossy(group.as_bytes()))
+            .unwrap_or_else(|err| {
+                eprintln!("chgrp: {}", err);
+                process::exit(1);
+            }),
+        (None, None) => unreachable!("there is a group if there are files"),
+    };
+
+    let mut chowner = Chowner::new("chgrp", &options);
+    let mut success = true;
+
+    for file in operands {
+        success &= chowner.change(Path::new(file));
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// Get the options of the changes from the `matches`, without the group set, exiting if
+/// they don't go together.
+fn chgrp_options(matches: &ArgMatches) -> ChownOptions {
+    let follow = if matches.is_present("H") {
+        Follow::CommandLine
+    } else if matches.is_present("L") {
+        Follow::Always
+    } else {
+        Follow::Never
+    };
+
+    let dereference = if matches.is_present("noDereference") {
+        Some(false)
+    } else if matches.is_present("dereference") {
+        Some(true)
+    } else {
+        None
+    };
+
+    let mut options = ChownOptions::new(matches.is_present("recursive"), follow, dereference)
+        .unwrap_or_else(|err| {
+            eprintln!("chgrp: {}", err);
+            process::exit(1);
+        });
+
+    options.report = if matches.is_present("verbose") {
+        Report::All
+    } else if matches.is_present("changes") {
+        Report::Changes
+    } else {
+        Report::Nothing
+    };
+
+    options.preserve_root = !matches.is_present("noPreserveRoot");
+    options.quiet = matches.is_present("quiet");
+
+    options
+}
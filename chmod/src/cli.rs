@@ -0,0 +1,98 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("Mode to set, and files to change.")
+                .long_help(
+                    "Mode to set, and files to change.\n\nMODE is a octal number, or a list of \
+                     symbolic changes separated by commas like 'u+x,go=r', where the users whose \
+                     permissions are changed are 'u', 'g', 'o' and 'a', the operations are '+', \
+                     '-' and '=', and the permissions are 'r', 'w', 'x', 'X', 's' and 't', or \
+                     'u', 'g' or 'o' to copy the ones of those users. Without the users, the \
+                     permissions of all of them but the ones in the umask are changed.\n\nThe \
+                     modes that start with '-', like '-w', can be given as options. With \
+                     --reference there is no MODE.",
+                )
+                .value_names(&["MODE", "FILE"])
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("changes")
+                .help("Like --verbose, but only tell the changes made.")
+                .long("changes")
+                .short("c")
+                .overrides_with("verbose"),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .help("Do not print most of the error messages.")
+                .long("silent")
+                .visible_alias("quiet")
+                .short("f"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .help("Tell the mode of each file processed, and whether it is changed.")
+                .long("verbose")
+                .short("v")
+                .overrides_with("changes"),
+        )
+        .arg(
+            Arg::with_name("noPreserveRoot")
+                .help("Do not treat '/' specially.")
+                .long("no-preserve-root")
+                .overrides_with("preserveRoot"),
+        )
+        .arg(
+            Arg::with_name("preserveRoot")
+                .help("Fail to operate recursively on '/'. (default)")
+                .long("preserve-root")
+                .overrides_with("noPreserveRoot"),
+        )
+        .arg(
+            Arg::with_name("reference")
+                .help("Set the mode of RFILE instead of MODE.")
+                .long("reference")
+                .value_name("RFILE")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("recursive")
+                .help("Change the files and directories recursively.")
+                .long("recursive")
+                .short("R"),
+        )
+        .arg(
+            Arg::with_name("H")
+                .help("With -R, follow the symbolic links to directories given. (default)")
+                .short("H")
+                .overrides_with_all(&["L", "P"]),
+        )
+        .arg(
+            Arg::with_name("L")
+                .help("With -R, follow all the symbolic links to directories.")
+                .short("L")
+                .overrides_with_all(&["H", "P"]),
+        )
+        .arg(
+            Arg::with_name("P")
+                .help("With -R, do not follow any symbolic link.")
+                .long_help(
+                    "With -R, do not follow any symbolic link.\n\nThe files not followed are left \
+                     as they are, since the modes of the symbolic links are not used.",
+                )
+                .short("P")
+                .overrides_with_all(&["H", "L"]),
+        )
+}
@@ -0,0 +1,321 @@
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    fs::{self, Metadata, Permissions},
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{MetadataExt, PermissionsExt},
+    },
+    path::Path,
+    process,
+};
+
+use coreutils_core::{
+    mode::{self, Mode},
+    walk::{walk, Follow, Visitor, WalkError},
+};
+
+mod cli;
+
+fn main() {
+    let (args, dash_mode) = take_dash_modes(env::args_os());
+    let matches = cli::create_app().get_matches_from(args);
+
+    let reference = matches.value_of_os("reference");
+    let mut operands = matches.values_of_os("FILE").map_or_else(Vec::new, Iterator::collect);
+
+    if reference.is_some() && dash_mode.is_some() {
+        eprintln!("chmod: cannot combine mode and --reference options");
+        process::exit(1);
+    }
+
+    let text_mode = match &dash_mode {
+        Some(mode) => Some(OsStr::new(mode)),
+        None if reference.is_none() && !operands.is_empty() => Some(operands.remove(0)),
+        None => None,
+    };
+
+    if operands.is_empty() {
+        match text_mode {
+            Some(mode) if dash_mode.is_none() => {
+                eprintln!("chmod: missing operand after '{}'", mode.to_string_lossy())
+            },
+            _ => eprintln!("chmod: missing operand"),
+        }
+        process::exit(1);
+    }
+
+    let mode = match (reference, text_mode) {
+        (Some(reference), _) => match fs::metadata(reference) {
+            Ok(metadata) => Mode::from_bits(metadata.mode()),
+            Err(err) => {
+                eprintln!(
+                    "chmod: failed to get attributes of '{}': {}",
+                    Path::new(reference).display(),
+                    err
+                );
+                process::exit(1);
+            },
+        },
+        (None, Some(text)) => match text.to_str().map(str::parse::<Mode>) {
+            Some(Ok(mode)) => mode,
+            _ => {
+                eprintln!("chmod: invalid mode: '{}'", text.to_string_lossy());
+                process::exit(1);
+            },
+        },
+        (None, None) => unreachable!("there is a mode if there are files"),
+    };
+
+    let recursive = matches.is_present("recursive");
+    let follow = if recursive && matches.is_present("L") {
+        Follow::Always
+    } else if recursive && matches.is_present("P") {
+        Follow::Never
+    } else {
+        Follow::CommandLine
+    };
+
+    let root = if recursive && !matches.is_present("noPreserveRoot") {
+        fs::metadata("/").ok().map(|root| (root.dev(), root.ino()))
+    } else {
+        None
+    };
+
+    let report = if matches.is_present("verbose") {
+        Report::All
+    } else if matches.is_present("changes") {
+        Report::Changes
+    } else {
+        Report::Nothing
+    };
+
+    let mut changer = Changer {
+        mode,
+        umask: mode::umask(),
+        diagnose_surprises: dash_mode.is_some(),
+        root,
+        report,
+        quiet: matches.is_present("quiet"),
+        success: true,
+    };
+
+    for file in operands {
+        walk(Path::new(file), recursive, follow, &mut changer);
+    }
+
+    if !changer.success {
+        process::exit(1);
+    }
+}
+
+/// Take the modes given like options, as `-w`, out of the `args`, returning the other
+/// arguments and the modes joined with commas.
+///
+/// They are the arguments before `--` that start with `-` and a character that can start
+/// a mode but not a option.
+fn take_dash_modes(args: impl Iterator<Item = OsString>) -> (Vec<OsString>, Option<String>) {
+    let mut others = Vec::new();
+    let mut modes: Vec<String> = Vec::new();
+    let mut options_end = false;
+
+    for arg in args {
+        let bytes = arg.as_bytes();
+        options_end |= bytes == b"--";
+
+        let is_mode = !options_end
+            && bytes.len() > 1
+            && bytes[0] == b'-'
+            && b"rwxXstugoa,+=01234567".contains(&bytes[1]);
+
+        if is_mode {
+            modes.push(arg.to_string_lossy().into_owned());
+        } else {
+            others.push(arg);
+        }
+    }
+
+    let mode = if modes.is_empty() { None } else { Some(modes.join(",")) };
+
+    (others, mode)
+}
+
+/// Which of the files processed are told.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Report {
+    Nothing,
+    /// The ones whose mode is changed, with `-c`.
+    Changes,
+    /// All of them, with `-v`.
+    All,
+}
+
+/// The changer of the modes of the files walked.
+struct Changer {
+    mode: Mode,
+    umask: u32,
+    /// Whether the new modes not allowed by the umask are errors, with the modes given as
+    /// options.
+    diagnose_surprises: bool,
+    /// The device and inode numbers of `/`, when it is not changed recursively.
+    root: Option<(u64, u64)>,
+    report: Report,
+    quiet: bool,
+    success: bool,
+}
+
+impl Visitor for Changer {
+    fn visit(&mut self, path: &Path, metadata: &Metadata, _given: bool) -> bool {
+        if self.root == Some((metadata.dev(), metadata.ino())) {
+            dangerous(path);
+            self.success = false;
+            return false;
+        }
+
+        // The modes of the symbolic links are not used, so they are not changed
+        if metadata.file_type().is_symlink() {
+            if self.report == Report::All {
+                println!(
+                    "neither symbolic link '{}' nor referent has been changed",
+                    path.display()
+                );
+            }
+            return false;
+        }
+
+        let is_dir = metadata.is_dir();
+        let old_mode = metadata.mode() & 0o7777;
+        let new_mode = self.mode.apply(metadata.mode(), is_dir, self.umask);
+
+        if let Err(err) = fs::set_permissions(path, Permissions::from_mode(new_mode)) {
+            if !self.quiet {
+                eprintln!("chmod: changing permissions of '{}': {}", path.display(), err);
+            }
+            if self.report == Report::All {
+                println!(
+                    "failed to change mode of '{}' from {} to {}",
+                    path.display(),
+                    describe(old_mode),
+                    describe(new_mode)
+                );
+            }
+            self.success = false;
+            return true;
+        }
+
+        if self.report != Report::Nothing {
+            // The special bits may be dropped by the system
+            let set_mode = if new_mode & 0o7000 == 0 {
+                new_mode
+            } else {
+                fs::metadata(path).map_or(new_mode, |metadata| metadata.mode() & 0o7777)
+            };
+
+            if set_mode != old_mode {
+                println!(
+                    "mode of '{}' changed from {} to {}",
+                    path.display(),
+                    describe(old_mode),
+                    describe(new_mode)
+                );
+            } else if self.report == Report::All {
+                println!("mode of '{}' retained as {}", path.display(), describe(old_mode));
+            }
+        }
+
+        if self.diagnose_surprises {
+            let expected = self.mode.apply(metadata.mode(), is_dir, 0);
+
+            if new_mode & !expected != 0 {
+                eprintln!(
+                    "chmod: {}: new permissions are {}, not {}",
+                    path.display(),
+                    permissions(new_mode),
+                    permissions(expected)
+                );
+                self.success = false;
+            }
+        }
+
+        true
+    }
+
+    fn error(&mut self, path: &Path, error: WalkError) {
+        self.success = false;
+
+        if !self.quiet {
+            match error {
+                WalkError::Access(err) => {
+                    eprintln!("chmod: cannot access '{}': {}", path.display(), err)
+                },
+                WalkError::Dangling(_) => {
+                    eprintln!("chmod: cannot operate on dangling symlink '{}'", path.display())
+                },
+                WalkError::ReadDir(err) => {
+                    eprintln!("chmod: cannot read directory '{}': {}", path.display(), err)
+                },
+            }
+        }
+
+        if self.report == Report::All {
+            println!("'{}' could not be accessed", path.display());
+        }
+    }
+}
+
+/// Tell that the root directory in `path` is not changed with `--preserve-root`.
+fn dangerous(path: &Path) {
+    if path.as_os_str() == "/" {
+        eprintln!("chmod: it is dangerous to operate recursively on '/'");
+    } else {
+        eprintln!(
+            "chmod: it is dangerous to operate recursively on '{}' (same as '/')",
+            path.display()
+        );
+    }
+    eprintln!("chmod: use --no-preserve-root to override this failsafe");
+}
+
+/// The bits of `mode` in octal with its permissions, like `0755 (rwxr-xr-x)`.
+fn describe(mode: u32) -> String {
+    format!("{:04o} ({})", mode, permissions(mode))
+}
+
+/// The permissions of `mode` as a string, like `rwxr-xr-x`.
+fn permissions(mode: u32) -> String {
+    // Without the type of the file
+    unix_mode::to_string(mode).chars().skip(1).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take(args: &[&str]) -> (Vec<OsString>, Option<String>) {
+        take_dash_modes(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn dash_modes() {
+        let (args, mode) = take(&["chmod", "-R", "-w", "file", "-x,u+r", "-v"]);
+        assert_eq!(args, vec!["chmod", "-R", "file", "-v"]);
+        assert_eq!(mode, Some(String::from("-w,-x,u+r")));
+
+        let (args, mode) = take(&["chmod", "-f", "--", "-w", "file"]);
+        assert_eq!(args, vec!["chmod", "-f", "--", "-w", "file"]);
+        assert_eq!(mode, None);
+
+        let (args, mode) = take(&["chmod", "-=", "-", "--reference=file"]);
+        assert_eq!(args, vec!["chmod", "-", "--reference=file"]);
+        assert_eq!(mode, Some(String::from("-=")));
+    }
+
+    #[test]
+    fn permission_strings() {
+        assert_eq!(permissions(0o100644), "rw-r--r--");
+        assert_eq!(permissions(0o40755), "rwxr-xr-x");
+        assert_eq!(permissions(0o107610), "rwS--s--T");
+        assert_eq!(describe(0o4755), "4755 (rwsr-xr-x)");
+        assert_eq!(describe(0o600), "0600 (rw-------)");
+    }
+}
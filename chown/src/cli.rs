@@ -0,0 +1,123 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("Owner and group to set, and files to change.")
+                .long_help(
+                    "Owner and group to set, and files to change.\n\nOWNER is 'USER' to only \
+                     change the owner, 'USER:GROUP' to change both, ':GROUP' to only change the \
+                     group, or 'USER:' to change the group to the login group of USER. The users \
+                     and groups are names or IDs, that are not looked up as names with a leading \
+                     '+'.\n\nWith --reference there is no OWNER.",
+                )
+                .value_names(&["OWNER", "FILE"])
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("changes")
+                .help("Like --verbose, but only tell the changes made.")
+                .long("changes")
+                .short("c")
+                .overrides_with("verbose"),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .help("Do not print most of the error messages.")
+                .long("silent")
+                .visible_alias("quiet")
+                .short("f"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .help("Tell the ownership of each file processed, and whether it is changed.")
+                .long("verbose")
+                .short("v")
+                .overrides_with("changes"),
+        )
+        .arg(
+            Arg::with_name("dereference")
+                .help("Change the referents of the symbolic links, instead of the links. (default)")
+                .long_help(
+                    "Change the referents of the symbolic links, instead of the links. \
+                     (default)\n\nWith -R it requires -H or -L.",
+                )
+                .long("dereference")
+                .overrides_with("noDereference"),
+        )
+        .arg(
+            Arg::with_name("noDereference")
+                .help("Change the symbolic links, instead of their referents.")
+                .long("no-dereference")
+                .short("h")
+                .overrides_with("dereference"),
+        )
+        .arg(
+            Arg::with_name("from")
+                .help("Only change the files with the current owner and group given.")
+                .long_help(
+                    "Only change the files with the current owner and group given.\n\nThey are \
+                     given like OWNER, and the ones omitted are not checked.",
+                )
+                .long("from")
+                .value_name("CURRENT_OWNER:CURRENT_GROUP")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("noPreserveRoot")
+                .help("Do not treat '/' specially.")
+                .long("no-preserve-root")
+                .overrides_with("preserveRoot"),
+        )
+        .arg(
+            Arg::with_name("preserveRoot")
+                .help("Fail to operate recursively on '/'. (default)")
+                .long("preserve-root")
+                .overrides_with("noPreserveRoot"),
+        )
+        .arg(
+            Arg::with_name("reference")
+                .help("Set the owner and group of RFILE instead of OWNER.")
+                .long("reference")
+                .value_name("RFILE")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("recursive")
+                .help("Change the files and directories recursively.")
+                .long("recursive")
+                .short("R"),
+        )
+        .arg(
+            Arg::with_name("H")
+                .help("With -R, follow the symbolic links to directories given.")
+                .short("H")
+                .overrides_with_all(&["L", "P"]),
+        )
+        .arg(
+            Arg::with_name("L")
+                .help("With -R, follow all the symbolic links to directories.")
+                .short("L")
+                .overrides_with_all(&["H", "P"]),
+        )
+        .arg(
+            Arg::with_name("P")
+                .help("With -R, do not follow any symbolic link. (default)")
+                .long_help(
+                    "With -R, do not follow any symbolic link. (default)\n\nThe symbolic links \
+                     are changed instead of their referents, like with -h.",
+                )
+                .short("P")
+                .overrides_with_all(&["H", "L"]),
+        )
+}
@@ -0,0 +1,146 @@
+use std::{fs, os::unix::ffi::OsStrExt, path::Path, process};
+
+use clap::ArgMatches;
+use coreutils_core::{
+    chowner::{ChownOptions, Chowner, Ownership, Report},
+    walk::Follow,
+};
+
+mod cli;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let reference = matches.value_of_os("reference");
+    let mut operands = matches.values_of_os("FILE").map_or_else(Vec::new, Iterator::collect);
+
+    let mut options = chown_options(&matches);
+
+    let spec =
+        if reference.is_none() && !operands.is_empty() { Some(operands.remove(0)) } else { None };
+
+    if operands.is_empty() {
+        match spec {
+            Some(spec) => eprintln!("chown: missing operand after '{}'", spec.to_string_lossy()),
+            None => eprintln!("chown: missing operand"),
+        }
+        process::exit(1);
+    }
+
+    options.ownership = match (reference, spec) {
+        (Some(reference), _) => match fs::metadata(reference) {
+            Ok(metadata) => Ownership::of(&metadata),
+            Err(err) => {
+                eprintln!(
+                    "chown: failed to get attributes of '{}': {}",
+                    Path::new(reference).display(),
+                    err
+                );
+                process::exit(1);
+            },
+        },
+        (None, Some(spec)) => parse_spec(&String::from_utf8_lossy(spec.as_bytes())),
+        (None, None) => unreachable!("there is a owner if there are files"),
+    };
+
+    // With a group alone, the changes are told as changes of ownership
+    if options.ownership.user_name.is_none() && options.ownership.group_name.is_some() {
+        options.ownership.user_name = Some(String::new());
+    }
+
+    let mut chowner = Chowner::new("chown", &options);
+    let mut success = true;
+
+    for file in operands {
+        success &= chowner.change(Path::new(file));
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// Get the options of the changes from the `matches`, without the ownership set, exiting
+/// if they don't go together.
+fn chown_options(matches: &ArgMatches) -> ChownOptions {
+    let follow = if matches.is_present("H") {
+        Follow::CommandLine
+    } else if matches.is_present("L") {
+        Follow::Always
+    } else {
+        Follow::Never
+    };
+
+    let dereference = if matches.is_present("noDereference") {
+        Some(false)
+    } else if matches.is_present("dereference") {
+        Some(true)
+    } else {
+        None
+    };
+
+    let mut options = ChownOptions::new(matches.is_present("recursive"), follow, dereference)
+        .unwrap_or_else(|err| {
+            eprintln!("chown: {}", err);
+            process::exit(1);
+        });
+
+    options.report = if matches.is_present("verbose") {
+        Report::All
+    } else if matches.is_present("changes") {
+        Report::Changes
+    } else {
+        Report::Nothing
+    };
+
+    options.required = matches.value_of("from").map(parse_spec).unwrap_or_default();
+    options.preserve_root = !matches.is_present("noPreserveRoot");
+    options.quiet = matches.is_present("quiet");
+
+    options
+}
+
+/// Parse the `spec` of a owner and group, exiting if it is invalid.
+///
+/// Without `:`, the first `.` is taken as the separator when the `spec` is not a user.
+fn parse_spec(spec: &str) -> Ownership {
+    let parsed = Ownership::parse(spec).or_else(|err| match spec.find('.') {
+        Some(_) if !spec.contains(':') => {
+            let ownership = Ownership::parse(&spec.replacen('.', ":", 1)).map_err(|_| err)?;
+            eprintln!("chown: warning: '.' should be ':': '{}'", spec);
+            Ok(ownership)
+        },
+        _ => Err(err),
+    });
+
+    parsed.unwrap_or_else(|err| {
+        eprintln!("chown: {}", err);
+        process::exit(1);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(args: &[&str]) -> ChownOptions {
+        let args = ["chown"].iter().chain(args).chain(&["root", "file"]);
+        chown_options(&cli::create_app().get_matches_from(args))
+    }
+
+    #[test]
+    fn symbolic_links() {
+        let recursive = options(&["-R", "-L", "-h"]);
+        assert_eq!((recursive.follow, recursive.dereference), (Follow::Always, false));
+
+        let dereference = options(&["-h", "--dereference"]);
+        assert_eq!((dereference.follow, dereference.dereference), (Follow::CommandLine, true));
+    }
+
+    #[test]
+    fn specs() {
+        assert_eq!(parse_spec("root.root"), Ownership::parse("root:root").unwrap());
+        assert_eq!(parse_spec("0.1").gid, Some(1));
+        assert_eq!(options(&["--from=:0"]).required.gid, Some(0));
+    }
+}
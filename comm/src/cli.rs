@@ -0,0 +1,74 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("The two files to compare, where '-' is standard input.")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("suppress1")
+                .help("Suppress the column of the lines only in FILE1.")
+                .short("1"),
+        )
+        .arg(
+            Arg::with_name("suppress2")
+                .help("Suppress the column of the lines only in FILE2.")
+                .short("2"),
+        )
+        .arg(
+            Arg::with_name("suppress3")
+                .help("Suppress the column of the lines in both files.")
+                .short("3"),
+        )
+        .arg(
+            Arg::with_name("checkOrder")
+                .help("Fail when the files are not sorted.")
+                .long_help(
+                    "Fail when the files are not sorted.\n\nBy default, it is only reported once \
+                     some line is not in both files, and the exit status is then 1.",
+                )
+                .long("check-order")
+                .overrides_with("nocheckOrder"),
+        )
+        .arg(
+            Arg::with_name("nocheckOrder")
+                .help("Do not check whether the files are sorted.")
+                .long("nocheck-order")
+                .overrides_with("checkOrder"),
+        )
+        .arg(
+            Arg::with_name("outputDelimiter")
+                .help("Separate the columns with STR, instead of a tab.")
+                .long_help(
+                    "Separate the columns with STR, instead of a tab.\n\nWhen STR is empty, the \
+                     columns are separated by NUL characters.",
+                )
+                .long("output-delimiter")
+                .value_name("STR")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("total")
+                .help("Write a last line with the count of the lines of each column.")
+                .long("total"),
+        )
+        .arg(
+            Arg::with_name("zeroTerminated")
+                .help("End the lines with NUL characters, instead of newlines.")
+                .long("zero-terminated")
+                .short("z"),
+        )
+}
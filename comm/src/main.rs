@@ -0,0 +1,360 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    fs::File,
+    io::{self, stdin, stdout, BufRead, BufReader, BufWriter, Write},
+    mem, process,
+};
+
+use clap::{ArgMatches, Values};
+use coreutils_core::collate::Collator;
+
+mod cli;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let options = Options::from_matches(&matches).unwrap_or_else(|err| fail(&err));
+
+    let files: Vec<_> = matches.values_of("FILE").map_or_else(Vec::new, Values::collect);
+    match files.len() {
+        0 => fail("missing operand"),
+        1 => fail(&format!("missing operand after '{}'", files[0])),
+        2 => {},
+        _ => fail(&format!("extra operand '{}'", files[2])),
+    }
+
+    let open = |file: &str| -> Box<dyn BufRead> {
+        if file == "-" {
+            return Box::new(BufReader::new(stdin()));
+        }
+        match File::open(file) {
+            Ok(opened) => Box::new(BufReader::new(opened)),
+            Err(err) => fail(&format!("{}: {}", file, err)),
+        }
+    };
+    let inputs = [Input::new(open(files[0]), files[0]), Input::new(open(files[1]), files[1])];
+
+    let stdout = stdout();
+    let mut comm = Comm::new(&options, inputs, BufWriter::new(stdout.lock()));
+    let result = comm.compare();
+
+    // Like GNU, the errors follow the lines written before them
+    let flushed = comm.output.flush();
+    if let Err(err) = result {
+        fail(&err.to_string());
+    }
+    if let Err(err) = flushed {
+        fail(&format!("write error: {}", err));
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("comm: {}", message);
+    process::exit(1);
+}
+
+#[derive(Debug)]
+enum Error {
+    Read(String, io::Error),
+    Write(io::Error),
+    /// The files are not sorted, with the message describing it.
+    Unsorted(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Read(name, err) => write!(f, "{}: {}", name, err),
+            Error::Write(err) => write!(f, "write error: {}", err),
+            Error::Unsorted(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Write(err)
+    }
+}
+
+/// How the input order is checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Check {
+    /// Checked once some line is not in both files, with a warning that fails at the end.
+    Default,
+    /// Checked from the start, failing at the first disorder.
+    Enabled,
+    Disabled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Options {
+    /// Whether the columns of the lines only in the first file, only in the second one,
+    /// and in both are written.
+    columns: [bool; 3],
+    check: Check,
+    /// The delimiter of the columns, where an empty one is a NUL character, but nothing
+    /// in the line of the totals, like GNU.
+    delimiter: Vec<u8>,
+    total: bool,
+    line_terminator: u8,
+    collator: Collator,
+}
+
+impl Options {
+    fn from_matches(matches: &ArgMatches) -> Result<Self, String> {
+        let mut delimiter = None;
+        for value in matches.values_of("outputDelimiter").into_iter().flatten() {
+            if matches!(delimiter, Some(delimiter) if delimiter != value) {
+                return Err("multiple output delimiters specified".to_string());
+            }
+            delimiter = Some(value);
+        }
+
+        let check = if matches.is_present("checkOrder") {
+            Check::Enabled
+        } else if matches.is_present("nocheckOrder") {
+            Check::Disabled
+        } else {
+            Check::Default
+        };
+
+        Ok(Options {
+            columns: [
+                !matches.is_present("suppress1"),
+                !matches.is_present("suppress2"),
+                !matches.is_present("suppress3"),
+            ],
+            check,
+            delimiter: delimiter.unwrap_or("\t").as_bytes().to_vec(),
+            total: matches.is_present("total"),
+            line_terminator: if matches.is_present("zeroTerminated") { b'\0' } else { b'\n' },
+            collator: Collator::from_env(),
+        })
+    }
+}
+
+/// An input file.
+struct Input<R> {
+    reader: R,
+    name: String,
+    /// The line to compare, without its terminator, or `None` at the end of the file.
+    line: Option<Vec<u8>>,
+    /// The line read before it.
+    previous: Option<Vec<u8>>,
+    /// Whether its disorder was reported.
+    warned: bool,
+}
+
+impl<R: BufRead> Input<R> {
+    fn new(reader: R, name: &str) -> Self {
+        Input { reader, name: name.to_string(), line: None, previous: None, warned: false }
+    }
+}
+
+/// The comparer of the lines of two inputs.
+struct Comm<'a, R, W> {
+    options: &'a Options,
+    inputs: [Input<R>; 2],
+    output: W,
+    /// Whether some line was not in both files, which starts the default order check.
+    seen_unpaired: bool,
+}
+
+impl<'a, R: BufRead, W: Write> Comm<'a, R, W> {
+    fn new(options: &'a Options, inputs: [Input<R>; 2], output: W) -> Self {
+        Comm { options, inputs, output, seen_unpaired: false }
+    }
+
+    /// Write the lines of the inputs in their columns to the output, like GNU.
+    fn compare(&mut self) -> Result<(), Error> {
+        let mut totals = [0u64; 3];
+        for which in 0..2 {
+            let line = self.read_line(which)?;
+            self.inputs[which].line = line;
+        }
+
+        loop {
+            let order = match (&self.inputs[0].line, &self.inputs[1].line) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(line1), Some(line2)) => self.options.collator.compare(line1, line2),
+            };
+
+            let column = match order {
+                Ordering::Less => 0,
+                Ordering::Greater => 1,
+                Ordering::Equal => 2,
+            };
+            if order != Ordering::Equal {
+                self.seen_unpaired = true;
+            }
+            totals[column] += 1;
+            self.write_line(column)?;
+
+            if order != Ordering::Greater {
+                self.advance(0)?;
+            }
+            if order != Ordering::Less {
+                self.advance(1)?;
+            }
+        }
+
+        if self.options.total {
+            let delimiter = &self.options.delimiter;
+            for total in &totals {
+                write!(self.output, "{}", total)?;
+                self.output.write_all(delimiter)?;
+            }
+            self.output.write_all(b"total")?;
+            self.output.write_all(&[self.options.line_terminator])?;
+        }
+
+        if self.inputs.iter().any(|input| input.warned) {
+            return Err(Error::Unsorted("input is not in sorted order".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Read the next line of the input `which`, checking its order.
+    ///
+    /// Like GNU, at the end of the file, the last line is checked again, since the check
+    /// may have started after it was read.
+    fn advance(&mut self, which: usize) -> Result<(), Error> {
+        let options = self.options;
+        let line = self.read_line(which)?;
+        let input = &mut self.inputs[which];
+        let earlier = mem::replace(&mut input.previous, input.line.take());
+
+        let check = match options.check {
+            Check::Default => self.seen_unpaired,
+            Check::Enabled => true,
+            Check::Disabled => false,
+        };
+        let pair = match (&input.previous, &line) {
+            (Some(previous), Some(line)) => Some((previous, line)),
+            (Some(last), None) => earlier.as_ref().map(|previous| (previous, last)),
+            _ => None,
+        };
+        let disordered = matches!(pair, Some((previous, line))
+            if options.collator.compare(previous, line) == Ordering::Greater);
+        input.line = line;
+
+        if check && disordered && !input.warned {
+            let message = format!("file {} is not in sorted order", which + 1);
+            if options.check == Check::Enabled {
+                return Err(Error::Unsorted(message));
+            }
+
+            input.warned = true;
+            self.output.flush()?;
+            eprintln!("comm: {}", message);
+        }
+        Ok(())
+    }
+
+    /// Read a line of the input `which`, without its terminator.
+    fn read_line(&mut self, which: usize) -> Result<Option<Vec<u8>>, Error> {
+        let terminator = self.options.line_terminator;
+        let input = &mut self.inputs[which];
+
+        let mut line = Vec::new();
+        match input.reader.read_until(terminator, &mut line) {
+            Ok(0) => Ok(None),
+            Ok(_) => {
+                if line.last() == Some(&terminator) {
+                    line.pop();
+                }
+                Ok(Some(line))
+            },
+            Err(err) => Err(Error::Read(input.name.clone(), err)),
+        }
+    }
+
+    /// Write the current line of the first input in the `column` 0, of the second one in
+    /// the `column` 1, and of either in the `column` 2, after the delimiters of the
+    /// columns before it.
+    fn write_line(&mut self, column: usize) -> io::Result<()> {
+        let options = self.options;
+        if !options.columns[column] {
+            return Ok(());
+        }
+
+        let delimiter: &[u8] =
+            if options.delimiter.is_empty() { b"\0" } else { &options.delimiter };
+        for _ in options.columns[..column].iter().filter(|&&written| written) {
+            self.output.write_all(delimiter)?;
+        }
+
+        let line = if column == 0 { &self.inputs[0].line } else { &self.inputs[1].line };
+        self.output.write_all(line.as_deref().unwrap_or_default())?;
+        self.output.write_all(&[options.line_terminator])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comm(args: &[&str], file1: &str, file2: &str) -> Result<String, String> {
+        let matches = cli::create_app().get_matches_from(["comm"].iter().chain(args));
+        let mut options = Options::from_matches(&matches)?;
+        options.collator = Collator::bytes();
+
+        let inputs = [Input::new(file1.as_bytes(), "a"), Input::new(file2.as_bytes(), "b")];
+        let mut comm = Comm::new(&options, inputs, Vec::new());
+        comm.compare().map_err(|err| err.to_string())?;
+
+        Ok(String::from_utf8(comm.output).unwrap())
+    }
+
+    #[test]
+    fn columns() {
+        let file1 = "a\nb\nd\n";
+        let file2 = "b\nc\nd\ne";
+        assert_eq!(comm(&[], file1, file2).unwrap(), "a\n\t\tb\n\tc\n\t\td\n\te\n");
+        assert_eq!(comm(&["-1"], file1, file2).unwrap(), "\tb\nc\n\td\ne\n");
+        assert_eq!(comm(&["-2"], file1, file2).unwrap(), "a\n\tb\n\td\n");
+        assert_eq!(comm(&["-12"], file1, file2).unwrap(), "b\nd\n");
+        assert_eq!(comm(&["-3"], file1, file2).unwrap(), "a\n\tc\n\te\n");
+        assert_eq!(comm(&["-123"], file1, file2).unwrap(), "");
+        assert_eq!(comm(&[], "", "").unwrap(), "");
+        assert_eq!(comm(&["-z"], "a\0b", "a\nb\0").unwrap(), "a\0\ta\nb\0b\0");
+    }
+
+    #[test]
+    fn delimiters() {
+        let file1 = "a\nb\n";
+        let file2 = "b\nc\n";
+        assert_eq!(comm(&["--output-delimiter", "::"], file1, file2).unwrap(), "a\n::::b\n::c\n");
+        assert_eq!(comm(&["--output-delimiter="], file1, file2).unwrap(), "a\n\0\0b\n\0c\n");
+        assert_eq!(
+            comm(&["--output-delimiter=", "--total", "-123"], "a", "b").unwrap(),
+            "110total\n"
+        );
+        assert_eq!(comm(&["--total", "-3"], file1, file2).unwrap(), "a\n\tc\n1\t1\t1\ttotal\n");
+        assert_eq!(
+            comm(&["--output-delimiter=,", "--output-delimiter=,", "--total", "-123"], "", "x")
+                .unwrap(),
+            "0,1,0,total\n"
+        );
+        assert_eq!(
+            comm(&["--output-delimiter=,", "--output-delimiter=;"], file1, file2).unwrap_err(),
+            "multiple output delimiters specified"
+        );
+    }
+
+    #[test]
+    fn order() {
+        assert_eq!(comm(&[], "b\na\n", "b\na\n").unwrap(), "\t\tb\n\t\ta\n");
+        assert_eq!(comm(&[], "b\na\n", "a\n").unwrap_err(), "input is not in sorted order");
+        assert_eq!(comm(&[], "a\nc\nb\n", "a\nd\n").unwrap_err(), "input is not in sorted order");
+        assert_eq!(
+            comm(&["--check-order"], "a\n", "b\na\n").unwrap_err(),
+            "file 2 is not in sorted order"
+        );
+        assert_eq!(comm(&["--nocheck-order"], "b\na\n", "a\n").unwrap(), "\ta\nb\na\n");
+    }
+}
@@ -20,10 +20,11 @@
 //! ### Numbered
 //! Can be specified by either supplying the strings `numbered` or `t`.
 //! This mode always makes numbered backups. This means that a backup of a file `a.txt`
-//! will be backed up to `a.txt~X~` where `X` is the next number backup.
+//! will be backed up to `a.txt.~X~` where `X` is the next number backup.
 //!
 //! For example, if we create a file named `main.rs` and then back it up in this mode, we
-//! will get `main.rs~1~`. If we back the file up a second time, we will get `main.rs~2~`.
+//! will get `main.rs.~1~`. If we back the file up a second time, we will get
+//! `main.rs.~2~`.
 //!
 //! ### Simple
 //! Can be specified by either supplying the strings `simple` or `never` (not to be
@@ -54,7 +55,7 @@ use regex::Regex;
 pub enum BackupMode {
     /// No backups will be made.
     None,
-    /// Backups will be made of the form `<filename>~<X>~` where `X` is the next backup
+    /// Backups will be made of the form `<filename>.~<X>~` where `X` is the next backup
     /// number.
     Numbered,
     /// The backup method will be consistant with what already exists for the current
@@ -90,9 +91,9 @@ impl From<&str> for BackupMode {
 }
 
 /// Creates a numbered backup. Does so by taking the input `file` and poking the parent
-/// directory to find a file of the form `<file>~<X>~` where `X` is a number. If none can
-/// be found, a backup file is created where `X` is `1`. Else, it creates a backup file
-/// where `X` is `X + 1`.
+/// directory to find the files of the form `<file>.~<X>~` where `X` is a number. If none
+/// can be found, a backup file is created where `X` is `1`. Else, it creates a backup
+/// file where `X` is the highest `X + 1`.
 ///
 /// # Errors
 /// If this function encounters any kind of I/O error, an error variant will be returned.
@@ -103,31 +104,29 @@ impl From<&str> for BackupMode {
 // `io::Error`
 #[inline]
 pub fn create_numbered_backup(file: &Path) -> Result<PathBuf, Error> {
-    let mut index = 1_u64;
-    loop {
-        if index == std::u64::MAX {
+    let index = match last_backup_number(file)? {
+        Some(std::u64::MAX) => {
             return Err(Error::new(
                 ErrorKind::AlreadyExists,
                 "Cannot create backup: too many backup files",
             ));
-        }
+        },
+        Some(index) => index + 1,
+        None => 1,
+    };
 
-        let new = file.with_extension(format!("~{}~", index));
-        if !new.exists() {
-            match fs::rename(file, &new) {
-                Ok(()) => return Ok(new),
-                Err(err) => return Err(err),
-            };
-        }
+    let new = PathBuf::from(format!("{}.~{}~", file.display(), index));
 
-        index += 1;
+    match fs::rename(file, &new) {
+        Ok(()) => Ok(new),
+        Err(err) => Err(err),
     }
 }
 
 /// Creates a backup in-keeping with previous backups. Pokes the directory to see whether
-/// there are any numbered or simple backups of the input `file`. If numbered backups are
-/// found, a numbered backup will be created. Else, a simple backup is created using the
-/// input `suffix`
+/// there are any numbered backups of the input `file`. If numbered backups are found, a
+/// numbered backup will be created. Else, a simple backup is created using the input
+/// `suffix`
 ///
 /// # Errors
 /// If this function encounters any kind of I/O error, an error variant will be returned.
@@ -139,25 +138,58 @@ pub fn create_numbered_backup(file: &Path) -> Result<PathBuf, Error> {
 // `io::Error`
 #[inline]
 pub fn create_existing_backup(file: &Path, suffix: &str) -> Result<PathBuf, Error> {
-    let mut has_numbered_backup = false;
-    let regex = Regex::new(r"~\d+~").unwrap();
-    let parent = file.parent().unwrap();
-    for entry in parent.read_dir()?.flatten() {
-        if let Some(ext) = entry.path().extension() {
-            if regex.is_match(ext.to_str().unwrap()) {
-                has_numbered_backup = true;
-                break;
-            }
-        }
-    }
-
-    if has_numbered_backup {
+    if last_backup_number(file)?.is_some() {
         create_numbered_backup(file)
     } else {
         create_simple_backup(file, suffix)
     }
 }
 
+/// Gets the highest number `X` of the numbered backups `<file>.~<X>~` in the directory of
+/// `file`, or [`None`] if it has none.
+fn last_backup_number(file: &Path) -> Result<Option<u64>, Error> {
+    let name = match file.file_name() {
+        Some(name) => format!("{}.~", name.to_string_lossy()),
+        None => return Ok(None),
+    };
+
+    let parent = match file.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let regex = Regex::new(r"^(\d+)~$").unwrap();
+    let mut last = None;
+
+    for entry in parent.read_dir()?.flatten() {
+        let entry_name = entry.file_name();
+        let entry_name = entry_name.to_string_lossy();
+
+        let number = entry_name
+            .strip_prefix(name.as_str())
+            .and_then(|rest| regex.captures(rest))
+            .and_then(|captures| captures[1].parse::<u64>().ok());
+
+        if let Some(number) = number {
+            last = last.max(Some(number));
+        }
+    }
+
+    Ok(last)
+}
+
+/// Returns `true` if `file` and `other` are the same entry of the same directory, so
+/// moving one of them away for the backup would also move the other.
+pub fn is_same_entry(file: &Path, other: &Path) -> bool {
+    let parent = |path: &Path| match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => fs::canonicalize(parent),
+        _ => fs::canonicalize("."),
+    };
+
+    file.file_name() == other.file_name()
+        && matches!((parent(file), parent(other)), (Ok(parent), Ok(other)) if parent == other)
+}
+
 /// Creates a simple backup. Creates a backup of the form `<file><suffix>`. Overwrites any
 /// previous backup files with that same suffix.
 ///
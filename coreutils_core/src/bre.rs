@@ -0,0 +1,321 @@
+//! Translation of the POSIX basic regular expressions (BRE) to the syntax of the `regex`
+//! crate.
+use std::fmt;
+
+use regex::Regex;
+
+/// The error of a invalid basic regular expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BreError {
+    UnmatchedParen,
+    UnmatchedCloseParen,
+    UnmatchedBracket,
+    UnmatchedBrace,
+    InvalidBraceContent,
+    InvalidPreceding,
+    TrailingBackslash,
+    InvalidClass,
+    BackReference,
+    Invalid,
+}
+
+impl fmt::Display for BreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BreError::UnmatchedParen => write!(f, "Unmatched ( or \\("),
+            BreError::UnmatchedCloseParen => write!(f, "Unmatched ) or \\)"),
+            BreError::UnmatchedBracket => write!(f, "Unmatched [, [^, [:, [., or [="),
+            BreError::UnmatchedBrace => write!(f, "Unmatched \\{{"),
+            BreError::InvalidBraceContent => write!(f, "Invalid content of \\{{\\}}"),
+            BreError::InvalidPreceding => write!(f, "Invalid preceding regular expression"),
+            BreError::TrailingBackslash => write!(f, "Trailing backslash"),
+            BreError::InvalidClass => write!(f, "Invalid character class name"),
+            BreError::BackReference => write!(f, "Back references are not supported"),
+            BreError::Invalid => write!(f, "Invalid regular expression"),
+        }
+    }
+}
+
+/// The names of the character classes, like `[:alpha:]`.
+const CLASSES: [&str; 12] = [
+    "alnum", "alpha", "blank", "cntrl", "digit", "graph", "lower", "print", "punct", "space",
+    "upper", "xdigit",
+];
+
+/// Compile the basic regular expression `bre`, anchored at the start of the text.
+pub fn compile(bre: &str) -> Result<Regex, BreError> {
+    let translated = translate(bre)?;
+    Regex::new(&format!("(?s)\\A(?:{})", translated)).map_err(|_| BreError::Invalid)
+}
+
+/// Translate the basic regular expression `bre`.
+///
+/// Besides the POSIX syntax, the GNU extensions `\+`, `\?`, `\|`, `\w`, `\W`, `\s`, `\S`,
+/// `\b`, `\B`, `\<`, `\>`, `` \` `` and `\'` are supported.
+pub fn translate(bre: &str) -> Result<String, BreError> {
+    let chars: Vec<char> = bre.chars().collect();
+    let mut output = String::with_capacity(bre.len());
+    let mut depth = 0;
+    // Whether the position is the start of a expression, where `*` is a literal and `^`
+    // a anchor
+    let mut at_start = true;
+    // Whether the last item can be repeated
+    let mut repeatable = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+
+        match c {
+            '\\' => {
+                let escaped = *chars.get(i).ok_or(BreError::TrailingBackslash)?;
+                i += 1;
+
+                match escaped {
+                    '(' => {
+                        output.push('(');
+                        depth += 1;
+                        at_start = true;
+                        repeatable = false;
+                        continue;
+                    },
+                    ')' => {
+                        if depth == 0 {
+                            return Err(BreError::UnmatchedCloseParen);
+                        }
+                        output.push(')');
+                        depth -= 1;
+                    },
+                    '|' => {
+                        output.push('|');
+                        at_start = true;
+                        repeatable = false;
+                        continue;
+                    },
+                    '{' => {
+                        if at_start {
+                            return Err(BreError::InvalidPreceding);
+                        }
+                        i = translate_interval(&chars, i, &mut output)?;
+                        repeatable = false;
+                        continue;
+                    },
+                    '+' | '?' if !at_start && repeatable => {
+                        output.push(escaped);
+                        repeatable = false;
+                        continue;
+                    },
+                    '1'..='9' => return Err(BreError::BackReference),
+                    'w' | 'W' | 's' | 'S' => output.extend(&['\\', escaped]),
+                    'b' | 'B' => {
+                        output.extend(&['\\', escaped]);
+                        at_start = false;
+                        repeatable = false;
+                        continue;
+                    },
+                    '<' | '>' | '`' | '\'' => {
+                        output.push_str(match escaped {
+                            '<' => "\\b{start}",
+                            '>' => "\\b{end}",
+                            '`' => "\\A",
+                            _ => "\\z",
+                        });
+                        at_start = false;
+                        repeatable = false;
+                        continue;
+                    },
+                    _ => output.push_str(&regex::escape(&escaped.to_string())),
+                }
+            },
+            '[' => i = translate_bracket(&chars, i, &mut output)?,
+            '*' if !at_start => {
+                // A repeated repetition is the same repetition
+                if repeatable {
+                    output.push('*');
+                }
+                repeatable = false;
+                continue;
+            },
+            '^' if at_start => {
+                output.push('^');
+                continue;
+            },
+            '$' if i == chars.len()
+                || chars[i] == '\\' && matches!(chars.get(i + 1), Some(')') | Some('|')) =>
+            {
+                output.push('$');
+                at_start = false;
+                repeatable = false;
+                continue;
+            },
+            '.' => output.push('.'),
+            _ => output.push_str(&regex::escape(&c.to_string())),
+        }
+
+        at_start = false;
+        repeatable = true;
+    }
+
+    if depth > 0 {
+        return Err(BreError::UnmatchedParen);
+    }
+    Ok(output)
+}
+
+/// Translate the interval after the `\{` at `start`, returning the position after its
+/// `\}`.
+fn translate_interval(
+    chars: &[char], start: usize, output: &mut String,
+) -> Result<usize, BreError> {
+    let mut end = start;
+    while end + 1 < chars.len() && !(chars[end] == '\\' && chars[end + 1] == '}') {
+        end += 1;
+    }
+    if end + 1 >= chars.len() {
+        return Err(BreError::UnmatchedBrace);
+    }
+
+    let content: String = chars[start..end].iter().collect();
+    let mut bounds = content.splitn(2, ',');
+    let min = bounds.next().unwrap();
+    let max = bounds.next();
+
+    let valid = |bound: &str| bound.bytes().all(|b| b.is_ascii_digit());
+    if min.is_empty() && max.is_none() || !valid(min) || !max.into_iter().all(valid) {
+        return Err(BreError::InvalidBraceContent);
+    }
+    if let (Ok(min), Some(Ok(max))) = (min.parse::<u32>(), max.map(str::parse::<u32>)) {
+        if min > max {
+            return Err(BreError::InvalidBraceContent);
+        }
+    }
+
+    output.push('{');
+    output.push_str(if min.is_empty() { "0" } else { min });
+    if let Some(max) = max {
+        output.push(',');
+        output.push_str(max);
+    }
+    output.push('}');
+    Ok(end + 2)
+}
+
+/// Translate the bracket expression after the `[` at `start`, returning the position
+/// after its `]`.
+fn translate_bracket(chars: &[char], start: usize, output: &mut String) -> Result<usize, BreError> {
+    let mut i = start;
+    output.push('[');
+
+    if chars.get(i) == Some(&'^') {
+        output.push('^');
+        i += 1;
+    }
+    if i == chars.len() {
+        return Err(BreError::Invalid);
+    }
+    // A `]` first is a literal
+    if chars.get(i) == Some(&']') {
+        output.push_str("\\]");
+        i += 1;
+    }
+
+    while let Some(&c) = chars.get(i) {
+        i += 1;
+
+        match c {
+            ']' => {
+                output.push(']');
+                return Ok(i);
+            },
+            '[' if matches!(chars.get(i), Some(':') | Some('.') | Some('=')) => {
+                let kind = chars[i];
+                let name_start = i + 1;
+                let mut end = name_start;
+                while end + 1 < chars.len() && !(chars[end] == kind && chars[end + 1] == ']') {
+                    end += 1;
+                }
+                if end + 1 >= chars.len() {
+                    return Err(BreError::UnmatchedBracket);
+                }
+
+                let name: String = chars[name_start..end].iter().collect();
+                if kind == ':' {
+                    if !CLASSES.contains(&name.as_str()) {
+                        return Err(BreError::InvalidClass);
+                    }
+                    output.push_str(&format!("[:{}:]", name));
+                } else {
+                    // Only the single characters are supported as collating elements
+                    let mut name_chars = name.chars();
+                    match (name_chars.next(), name_chars.next()) {
+                        (Some(c), None) => output.push_str(&escape_class_char(c)),
+                        _ => return Err(BreError::Invalid),
+                    }
+                }
+                i = end + 2;
+            },
+            _ => output.push_str(&escape_class_char(c)),
+        }
+    }
+
+    Err(BreError::UnmatchedBracket)
+}
+
+/// Escape the characters that are special inside the classes of the `regex` crate, but
+/// not in the POSIX bracket expressions.
+fn escape_class_char(c: char) -> String {
+    match c {
+        '\\' | '[' | '&' | '~' | '^' => format!("\\{}", c),
+        _ => c.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translate_basic() {
+        assert_eq!(translate("a.b*").unwrap(), "a.b*");
+        assert_eq!(translate("*a").unwrap(), "\\*a");
+        assert_eq!(translate("\\(*a\\)").unwrap(), "(\\*a)");
+        assert_eq!(translate("^a$").unwrap(), "^a$");
+        assert_eq!(translate("a^b$c").unwrap(), "a\\^b\\$c");
+        assert_eq!(translate("a+b?(c){2}|d").unwrap(), "a\\+b\\?\\(c\\)\\{2\\}\\|d");
+        assert_eq!(translate("a\\+b\\?\\|c").unwrap(), "a+b?|c");
+        assert_eq!(translate("a\\{2,\\}b\\{,3\\}").unwrap(), "a{2,}b{0,3}");
+        assert_eq!(translate("a**").unwrap(), "a*");
+    }
+
+    #[test]
+    fn translate_brackets() {
+        assert_eq!(translate("[]a]").unwrap(), "[\\]a]");
+        assert_eq!(translate("[^]a-z]").unwrap(), "[^\\]a-z]");
+        assert_eq!(translate("[[:alpha:]_]").unwrap(), "[[:alpha:]_]");
+        assert_eq!(translate("[\\[&]").unwrap(), "[\\\\\\[\\&]");
+        assert_eq!(translate("[[.-.]]").unwrap(), "[-]");
+    }
+
+    #[test]
+    fn translate_errors() {
+        assert_eq!(translate("\\(a"), Err(BreError::UnmatchedParen));
+        assert_eq!(translate("a\\)"), Err(BreError::UnmatchedCloseParen));
+        assert_eq!(translate("[a"), Err(BreError::UnmatchedBracket));
+        assert_eq!(translate("[[:foo:]]"), Err(BreError::InvalidClass));
+        assert_eq!(translate("a\\{1"), Err(BreError::UnmatchedBrace));
+        assert_eq!(translate("a\\{x\\}"), Err(BreError::InvalidBraceContent));
+        assert_eq!(translate("a\\{2,1\\}"), Err(BreError::InvalidBraceContent));
+        assert_eq!(translate("\\{1\\}"), Err(BreError::InvalidPreceding));
+        assert_eq!(translate("a\\"), Err(BreError::TrailingBackslash));
+        assert_eq!(translate("\\(a\\)\\1"), Err(BreError::BackReference));
+    }
+
+    #[test]
+    fn compile_anchored() {
+        let regex = compile("b").unwrap();
+        assert!(!regex.is_match("ab"));
+        assert!(regex.is_match("ba"));
+        assert!(compile("a.b").unwrap().is_match("a\nb"));
+    }
+}
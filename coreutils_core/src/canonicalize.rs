@@ -0,0 +1,287 @@
+//! The Canonicalize module resolves the paths to absolute paths without symbolic links,
+//! `.` or `..` components, like the `canonicalize_filename_mode` function of GNU.
+//!
+//! ## About
+//! Unlike [`std::fs::canonicalize`], the paths can have components that don't exist,
+//! depending on the [`Missing`] mode:
+//! - [`Missing::None`]: all the components must exist.
+//! - [`Missing::Last`]: all the components but the last must exist.
+//! - [`Missing::Any`]: any component can be missing, and the ones after the first missing
+//!   one are added as they are, with `..` removing the previous one. The components that
+//!   can't be resolved, like the ones in loops of symbolic links, are taken as missing.
+//!
+//! A path that ends with `/` has to be a directory, when it exists or [`Missing::None`]
+//! is used.
+//!
+//! The paths can also be resolved without following the symbolic links with
+//! [`normalize`], where the `..` components remove the previous one as it is written.
+
+use std::{
+    env,
+    ffi::OsString,
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+/// The most symbolic links followed to resolve a path, like the `MAXSYMLINKS` of Linux.
+const MAX_LINKS: usize = 40;
+
+/// Which components of a path can be missing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Missing {
+    /// All the components must exist.
+    None,
+    /// All the components but the last must exist.
+    Last,
+    /// Any component can be missing.
+    Any,
+}
+
+/// Resolve the `path` to a absolute path without symbolic links, `.` or `..` components,
+/// with the components that can be `missing`. The relative paths are resolved from the
+/// current directory.
+///
+/// # Errors
+/// If a component that has to exist is missing, one that is not a directory has more
+/// components after it, there are too many symbolic links or a internal call set a errno
+/// (I/O OS error), an error variant will be returned.
+pub fn canonicalize(path: impl AsRef<Path>, missing: Missing) -> io::Result<PathBuf> {
+    let path = path.as_ref();
+
+    if path.as_os_str().is_empty() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "No such file or directory"));
+    }
+
+    let mut resolved = if path.is_absolute() { PathBuf::from("/") } else { env::current_dir()? };
+
+    // The components left to resolve, with the next one last
+    let mut left = components(path);
+    let directory = path.to_string_lossy().ends_with('/');
+    let mut links = 0;
+    let mut exists = true;
+
+    while let Some(component) = left.pop() {
+        if component == ".." {
+            resolved.pop();
+            continue;
+        }
+
+        resolved.push(&component);
+
+        if !exists {
+            continue;
+        }
+
+        let metadata = match fs::symlink_metadata(&resolved) {
+            Ok(metadata) => metadata,
+            Err(_) if missing == Missing::Any => {
+                exists = false;
+                continue;
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                match missing {
+                    Missing::Last if left.is_empty() => {},
+                    _ => return Err(err),
+                }
+
+                exists = false;
+                continue;
+            },
+            Err(err) => return Err(err),
+        };
+
+        if metadata.file_type().is_symlink() {
+            links += 1;
+            if links > MAX_LINKS {
+                if missing == Missing::Any {
+                    exists = false;
+                    continue;
+                }
+
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Too many levels of symbolic links",
+                ));
+            }
+
+            let target = fs::read_link(&resolved)?;
+            resolved.pop();
+
+            if target.is_absolute() {
+                resolved = PathBuf::from("/");
+            }
+
+            left.extend(components(&target));
+        } else if !metadata.is_dir() && !left.is_empty() && missing != Missing::Any {
+            return Err(not_a_directory());
+        }
+    }
+
+    if directory && exists && missing != Missing::Any && !resolved.is_dir() {
+        return Err(not_a_directory());
+    }
+
+    Ok(resolved)
+}
+
+/// Resolve the `path` to a absolute path without `.` or `..` components, with the
+/// components that can be `missing`, keeping the symbolic links. The relative paths are
+/// resolved from the current directory.
+///
+/// Only the whole path is checked to exist, but what is before a `..` component has to
+/// be a directory unless any component can be missing.
+///
+/// # Errors
+/// If what has to exist is missing or is not a directory, or a internal call set a errno
+/// (I/O OS error), an error variant will be returned.
+pub fn normalize(path: impl AsRef<Path>, missing: Missing) -> io::Result<PathBuf> {
+    let path = path.as_ref();
+
+    if path.as_os_str().is_empty() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "No such file or directory"));
+    }
+
+    let mut resolved = if path.is_absolute() { PathBuf::from("/") } else { env::current_dir()? };
+    let directory = path.to_string_lossy().ends_with('/');
+
+    for component in components(path).into_iter().rev() {
+        if component != ".." {
+            resolved.push(component);
+            continue;
+        }
+
+        if missing != Missing::Any && !fs::metadata(&resolved)?.is_dir() {
+            return Err(not_a_directory());
+        }
+
+        resolved.pop();
+    }
+
+    if missing != Missing::Any {
+        match fs::metadata(&resolved) {
+            Ok(metadata) if directory && !metadata.is_dir() => return Err(not_a_directory()),
+            Err(err) if err.kind() != io::ErrorKind::NotFound || missing == Missing::None => {
+                return Err(err);
+            },
+            _ => {},
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Get the relative path from the directory `base` to `path`, that are both absolute
+/// paths without `.` or `..` components, like the ones returned by [`canonicalize`].
+pub fn relative_path(path: impl AsRef<Path>, base: impl AsRef<Path>) -> PathBuf {
+    let mut path = path.as_ref().components().peekable();
+    let mut base = base.as_ref().components().peekable();
+
+    while let (Some(a), Some(b)) = (path.peek(), base.peek()) {
+        if a != b {
+            break;
+        }
+        path.next();
+        base.next();
+    }
+
+    let relative: PathBuf = base.map(|_| Component::ParentDir).chain(path).collect();
+
+    if relative.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        relative
+    }
+}
+
+/// The error of the components that have to be directories.
+fn not_a_directory() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "Not a directory")
+}
+
+/// The normal and `..` components of the `path`, with the first one last.
+fn components(path: &Path) -> Vec<OsString> {
+    path.components()
+        .rev()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_os_string()),
+            Component::ParentDir => Some(OsString::from("..")),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let src = Path::new(dir).join("src");
+
+        assert_eq!(
+            canonicalize(format!("{}/src/../src/./lib.rs", dir), Missing::None).unwrap(),
+            src.join("lib.rs")
+        );
+        assert!(canonicalize(src.join("nowhere"), Missing::None).is_err());
+        assert_eq!(canonicalize(src.join("nowhere"), Missing::Last).unwrap(), src.join("nowhere"));
+        assert!(canonicalize(src.join("nowhere/file"), Missing::Last).is_err());
+        assert_eq!(
+            canonicalize(src.join("nowhere/../a/b"), Missing::Any).unwrap(),
+            src.join("a/b")
+        );
+        assert!(canonicalize(src.join("lib.rs/a"), Missing::Last).is_err());
+        assert!(canonicalize(format!("{}/src/lib.rs/", dir), Missing::Last).is_err());
+        assert_eq!(
+            canonicalize(format!("{}/src/nowhere/", dir), Missing::Last).unwrap(),
+            src.join("nowhere")
+        );
+        assert_eq!(
+            canonicalize(format!("{}/src/lib.rs/", dir), Missing::Any).unwrap(),
+            src.join("lib.rs")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn loops() {
+        let root = env::temp_dir().join(format!("core.canonicalize.{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        std::os::unix::fs::symlink("b", root.join("a")).unwrap();
+        std::os::unix::fs::symlink("a", root.join("b")).unwrap();
+
+        let missing = canonicalize(root.join("a/file"), Missing::Any);
+        let last = canonicalize(root.join("a"), Missing::Last);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(missing.unwrap(), root.join("a/file"));
+        assert!(last.is_err());
+    }
+
+    #[test]
+    fn symbolic_links_kept() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let src = Path::new(dir).join("src");
+
+        assert_eq!(
+            normalize(src.join("nowhere/file"), Missing::Last).unwrap(),
+            src.join("nowhere/file")
+        );
+        assert!(normalize(src.join("nowhere/file"), Missing::None).is_err());
+        assert!(normalize(src.join("nowhere/../lib.rs"), Missing::Last).is_err());
+        assert_eq!(
+            normalize(src.join("nowhere/../lib.rs"), Missing::Any).unwrap(),
+            src.join("lib.rs")
+        );
+        assert!(normalize(format!("{}/src/lib.rs/", dir), Missing::Last).is_err());
+    }
+
+    #[test]
+    fn relative() {
+        assert_eq!(relative_path("/a/b/c", "/a"), Path::new("b/c"));
+        assert_eq!(relative_path("/a", "/a/b/c"), Path::new("../.."));
+        assert_eq!(relative_path("/a/x", "/a/b/c"), Path::new("../../x"));
+        assert_eq!(relative_path("/a", "/a"), Path::new("."));
+        assert_eq!(relative_path("/a", "/"), Path::new("a"));
+    }
+}
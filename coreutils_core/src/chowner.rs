@@ -0,0 +1,499 @@
+//! Module to change the owners and groups of files, and with the recursion the ones of
+//! the trees of the directories, as `chown` and `chgrp` do.
+//!
+//! The changes are told as chosen in [`ChownOptions`], and the errors are written on the
+//! standard error with the name of the utility. The directories walked recursively are
+//! changed after their contents.
+
+use std::{
+    ffi::CString,
+    fs::{self, Metadata},
+    io,
+    os::unix::{ffi::OsStrExt, fs::MetadataExt},
+    path::Path,
+};
+
+use crate::{
+    os::{group::Group, passwd::Passwd, Gid, Uid},
+    walk::{walk, Follow, Visitor, WalkError},
+};
+
+/// A owner and group of files, with the names they are told with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ownership {
+    pub uid: Option<Uid>,
+    pub gid: Option<Gid>,
+    /// The name of the owner, that is told as changed with the group when it is empty.
+    pub user_name: Option<String>,
+    pub group_name: Option<String>,
+}
+
+impl Ownership {
+    /// Parse the `spec` of a owner and group, as `USER`, `USER:GROUP`, `:GROUP`, or
+    /// `USER:` for the login group of the user.
+    ///
+    /// The users and groups are names or IDs, that are not looked up as names with a
+    /// leading `+`.
+    ///
+    /// # Errors
+    /// If the user or group doesn't exist, the message of the error is returned.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (user, group, separator) = match spec.find(':') {
+            Some(index) => (&spec[..index], &spec[index + 1..], true),
+            None => (spec, "", false),
+        };
+
+        let mut ownership = Ownership::default();
+
+        if !user.is_empty() {
+            let passwd = if user.starts_with('+') { None } else { Passwd::from_name(user).ok() };
+
+            match passwd {
+                Some(passwd) => {
+                    ownership.uid = Some(passwd.uid());
+                    ownership.user_name = Some(user.to_string());
+
+                    if separator && group.is_empty() {
+                        ownership.gid = Some(passwd.gid());
+                        ownership.group_name = Some(group_name(passwd.gid()));
+                    }
+                },
+                None if separator && group.is_empty() => {
+                    return Err(format!("invalid spec: '{}'", spec));
+                },
+                None => {
+                    let uid = parse_id(user)
+                        .filter(|&uid| uid != Uid::MAX)
+                        .ok_or_else(|| format!("invalid user: '{}'", spec))?;
+                    ownership.uid = Some(uid);
+                    ownership.user_name = Some(uid.to_string());
+                },
+            }
+        }
+
+        if !group.is_empty() {
+            match lookup_group(group) {
+                Some((gid, named)) if named || gid != Gid::MAX => {
+                    ownership.gid = Some(gid);
+                    ownership.group_name =
+                        Some(if named { group.to_string() } else { gid.to_string() });
+                },
+                _ => return Err(format!("invalid group: '{}'", spec)),
+            }
+        }
+
+        Ok(ownership)
+    }
+
+    /// Parse the name or ID of a `group`, that is not looked up as a name with a leading
+    /// `+`.
+    ///
+    /// # Errors
+    /// If the group doesn't exist, the message of the error is returned.
+    pub fn parse_group(group: &str) -> Result<Self, String> {
+        let (gid, _) = lookup_group(group).ok_or_else(|| format!("invalid group: '{}'", group))?;
+
+        // The maximum leaves the group as it is
+        let gid = Some(gid).filter(|&gid| gid != Gid::MAX);
+
+        Ok(Ownership { gid, group_name: Some(group.to_string()), ..Ownership::default() })
+    }
+
+    /// The owner and group of the file with `metadata`, like a reference file.
+    pub fn of(metadata: &Metadata) -> Self {
+        Ownership {
+            uid: Some(metadata.uid()),
+            gid: Some(metadata.gid()),
+            user_name: Some(user_name(metadata.uid())),
+            group_name: Some(group_name(metadata.gid())),
+        }
+    }
+
+    /// Whether the file with `metadata` has the owner and group that are not `None`.
+    fn matches(&self, metadata: &Metadata) -> bool {
+        (self.uid.is_none() || self.uid == Some(metadata.uid()))
+            && (self.gid.is_none() || self.gid == Some(metadata.gid()))
+    }
+}
+
+/// Look up the name or ID of a `group`, returning the ID and whether it is a name.
+fn lookup_group(group: &str) -> Option<(Gid, bool)> {
+    let found = if group.starts_with('+') { None } else { Group::from_name(group).ok() };
+
+    match found {
+        Some(found) => Some((found.id(), true)),
+        None => parse_id(group).map(|gid| (gid, false)),
+    }
+}
+
+/// Parse a user or group ID, with a optional leading `+`.
+///
+/// The maximum is the ID the system takes as the one not changed.
+fn parse_id(id: &str) -> Option<u32> {
+    let digits = id.strip_prefix('+').unwrap_or(id);
+
+    match digits.parse() {
+        Ok(id) if digits.bytes().all(|b| b.is_ascii_digit()) => Some(id),
+        _ => None,
+    }
+}
+
+/// The name of the user with `uid`, or the ID if it has no name.
+fn user_name(uid: Uid) -> String {
+    Passwd::from_uid(uid).map_or_else(|_| uid.to_string(), |passwd| passwd.name().to_string())
+}
+
+/// The name of the group with `gid`, or the ID if it has no name.
+fn group_name(gid: Gid) -> String {
+    Group::from_gid(gid).map_or_else(|_| gid.to_string(), |group| group.name().to_string())
+}
+
+/// Which of the files processed are told.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Report {
+    Nothing,
+    /// The ones whose ownership is changed, with `-c`.
+    Changes,
+    /// All of them, with `-v`.
+    All,
+}
+
+/// The options of the changes of ownership.
+#[derive(Debug, Clone)]
+pub struct ChownOptions {
+    /// The owner and group set.
+    pub ownership: Ownership,
+    /// The owner and group the files must have to be changed, with `--from`.
+    pub required: Ownership,
+    pub recursive: bool,
+    pub follow: Follow,
+    /// Whether the referents of the symbolic links are changed, instead of the links.
+    pub dereference: bool,
+    /// Whether `/` is not changed recursively.
+    pub preserve_root: bool,
+    pub report: Report,
+    /// Whether most of the error messages are not written.
+    pub quiet: bool,
+}
+
+impl ChownOptions {
+    /// Creates the options of the changes, without the owner and group set, with the
+    /// symbolic links walked as chosen by `follow` with `-H`, `-L` and `-P`, and the
+    /// links changed instead of their referents if `dereference` is `Some(false)`
+    /// with `-h`.
+    ///
+    /// With the recursion and without following any link the links are changed, like
+    /// `-h`.
+    ///
+    /// # Errors
+    /// If the recursion follows no link and `dereference` is `Some(true)` with
+    /// `--dereference`, the message of the error is returned.
+    pub fn new(recursive: bool, follow: Follow, dereference: Option<bool>) -> Result<Self, String> {
+        let dereference = match (recursive, follow, dereference) {
+            (true, Follow::Never, Some(true)) => {
+                return Err(String::from("-R --dereference requires either -H or -L"));
+            },
+            (true, Follow::Never, _) => false,
+            (_, _, dereference) => dereference.unwrap_or(true),
+        };
+
+        // Without the recursion only the links given can be followed
+        let follow = match (recursive, dereference) {
+            (true, _) => follow,
+            (false, true) => Follow::CommandLine,
+            (false, false) => Follow::Never,
+        };
+
+        Ok(ChownOptions {
+            ownership: Ownership::default(),
+            required: Ownership::default(),
+            recursive,
+            follow,
+            dereference,
+            preserve_root: true,
+            report: Report::Nothing,
+            quiet: false,
+        })
+    }
+}
+
+/// The outcome of the change of a file, as it is told.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Changed,
+    Retained,
+    Failed,
+}
+
+/// The changer of the ownership of files, for the utility with `name`.
+#[derive(Debug)]
+pub struct Chowner<'a> {
+    name: &'a str,
+    options: &'a ChownOptions,
+    /// The device and inode numbers of `/`, with `--preserve-root`.
+    root: Option<(u64, u64)>,
+    success: bool,
+}
+
+impl<'a> Chowner<'a> {
+    /// Creates a changer for the utility with `name`, with `options`.
+    pub fn new(name: &'a str, options: &'a ChownOptions) -> Self {
+        let root = if options.recursive && options.preserve_root {
+            fs::metadata("/").ok().map(|root| (root.dev(), root.ino()))
+        } else {
+            None
+        };
+
+        Chowner { name, options, root, success: true }
+    }
+
+    /// Change the ownership of `path`, and with the recursion the one of its contents,
+    /// returning `false` if any of them failed.
+    pub fn change(&mut self, path: &Path) -> bool {
+        self.success = true;
+        walk(path, self.options.recursive, self.options.follow, self);
+        self.success
+    }
+
+    /// Change the ownership of the file at `path`, walked with `metadata`.
+    fn change_file(&mut self, path: &Path, metadata: &Metadata) {
+        let current = if !self.options.dereference {
+            fs::symlink_metadata(path)
+        } else if metadata.file_type().is_symlink() {
+            fs::metadata(path)
+        } else {
+            Ok(metadata.clone())
+        };
+
+        let current = match current {
+            Ok(current) => current,
+            Err(err) => {
+                if !self.options.quiet {
+                    eprintln!("{}: cannot dereference '{}': {}", self.name, path.display(), err);
+                }
+                self.tell(path, Status::Failed, Some(metadata));
+                self.success = false;
+                return;
+            },
+        };
+
+        if !self.options.required.matches(&current) {
+            self.tell(path, Status::Retained, Some(&current));
+            return;
+        }
+
+        let Ownership { uid, gid, .. } = self.options.ownership;
+
+        match change_owner(path, uid, gid, self.options.dereference) {
+            Ok(()) => {
+                let changed = (uid.is_some() && uid != Some(current.uid()))
+                    || (gid.is_some() && gid != Some(current.gid()));
+
+                self.tell(
+                    path,
+                    if changed { Status::Changed } else { Status::Retained },
+                    Some(&current),
+                );
+            },
+            Err(err) => {
+                if !self.options.quiet {
+                    eprintln!(
+                        "{}: changing {} of '{}': {}",
+                        self.name,
+                        self.what(),
+                        path.display(),
+                        err
+                    );
+                }
+                self.tell(path, Status::Failed, Some(&current));
+                self.success = false;
+            },
+        }
+    }
+
+    /// What is changed, as told in the messages.
+    fn what(&self) -> &str {
+        if self.options.ownership.user_name.is_none() && self.options.ownership.group_name.is_some()
+        {
+            "group"
+        } else {
+            "ownership"
+        }
+    }
+
+    /// Tell the `status` of the change of `path`, that had `old` metadata if it could be
+    /// accessed.
+    fn tell(&self, path: &Path, status: Status, old: Option<&Metadata>) {
+        let told = match self.options.report {
+            Report::Nothing => false,
+            Report::Changes => status == Status::Changed,
+            Report::All => true,
+        };
+
+        if !told {
+            return;
+        }
+
+        let ownership = &self.options.ownership;
+        let spec = names(ownership.user_name.clone(), ownership.group_name.clone());
+        let old_spec = old.and_then(|old| {
+            names(
+                ownership.user_name.as_ref().map(|_| user_name(old.uid())),
+                ownership.group_name.as_ref().map(|_| group_name(old.gid())),
+            )
+        });
+
+        let (what, path) = (self.what(), path.display());
+
+        match (status, spec, old_spec) {
+            (Status::Changed, Some(spec), Some(old_spec)) => {
+                println!("changed {} of '{}' from {} to {}", what, path, old_spec, spec)
+            },
+            (Status::Changed, ..) => println!("no change to ownership of '{}'", path),
+            (Status::Retained, _, Some(old_spec)) => {
+                println!("{} of '{}' retained as {}", what, path, old_spec)
+            },
+            (Status::Retained, ..) => println!("ownership of '{}' retained", path),
+            (Status::Failed, Some(spec), Some(old_spec)) => {
+                println!("failed to change {} of '{}' from {} to {}", what, path, old_spec, spec)
+            },
+            (Status::Failed, Some(spec), None) => {
+                println!("failed to change {} of '{}' to {}", what, path, spec)
+            },
+            (Status::Failed, None, _) => println!("failed to change ownership of '{}'", path),
+        }
+    }
+}
+
+impl Visitor for Chowner<'_> {
+    fn visit(&mut self, path: &Path, metadata: &Metadata, _given: bool) -> bool {
+        if self.root == Some((metadata.dev(), metadata.ino())) {
+            if path.as_os_str() == "/" {
+                eprintln!("{}: it is dangerous to operate recursively on '/'", self.name);
+            } else {
+                eprintln!(
+                    "{}: it is dangerous to operate recursively on '{}' (same as '/')",
+                    self.name,
+                    path.display()
+                );
+            }
+            eprintln!("{}: use --no-preserve-root to override this failsafe", self.name);
+            self.success = false;
+            return false;
+        }
+
+        // The directories walked are changed when they are left
+        if !self.options.recursive || !metadata.is_dir() {
+            self.change_file(path, metadata);
+        }
+
+        true
+    }
+
+    fn leave(&mut self, path: &Path, metadata: &Metadata, _given: bool) {
+        self.change_file(path, metadata);
+    }
+
+    fn error(&mut self, path: &Path, error: WalkError) {
+        self.success = false;
+
+        let (message, err, old) = match error {
+            WalkError::Access(err) => ("cannot access", err, None),
+            WalkError::Dangling(err) => {
+                ("cannot dereference", err, fs::symlink_metadata(path).ok())
+            },
+            WalkError::ReadDir(err) => ("cannot read directory", err, None),
+        };
+
+        if !self.options.quiet {
+            eprintln!("{}: {} '{}': {}", self.name, message, path.display(), err);
+        }
+        self.tell(path, Status::Failed, old.as_ref());
+    }
+}
+
+/// The `user` and `group` names as they are told, like `user:group`.
+fn names(user: Option<String>, group: Option<String>) -> Option<String> {
+    match (user, group) {
+        (Some(user), Some(group)) => Some(format!("{}:{}", user, group)),
+        (user, None) => user,
+        (None, group) => group,
+    }
+}
+
+/// Change the owner and group of the file in `path`, or of the symbolic link in it if not
+/// `dereference`, leaving the ones that are `None`.
+fn change_owner(
+    path: &Path, owner: Option<Uid>, group: Option<Gid>, dereference: bool,
+) -> io::Result<()> {
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    let (owner, group) = (owner.unwrap_or(Uid::MAX), group.unwrap_or(Gid::MAX));
+
+    let result = if dereference {
+        unsafe { libc::chown(path.as_ptr(), owner, group) }
+    } else {
+        unsafe { libc::lchown(path.as_ptr(), owner, group) }
+    };
+
+    match result {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ownership(uid: Option<u32>, gid: Option<u32>, user: &str, group: &str) -> Ownership {
+        let name = |name: &str| if name.is_empty() { None } else { Some(name.to_string()) };
+        Ownership { uid, gid, user_name: name(user), group_name: name(group) }
+    }
+
+    #[test]
+    fn specs() {
+        assert_eq!(Ownership::parse("root"), Ok(ownership(Some(0), None, "root", "")));
+        assert_eq!(Ownership::parse("root:"), Ok(ownership(Some(0), Some(0), "root", "root")));
+        assert_eq!(Ownership::parse("0:+1"), Ok(ownership(Some(0), Some(1), "0", "1")));
+        assert_eq!(Ownership::parse("+0:root"), Ok(ownership(Some(0), Some(0), "0", "root")));
+        assert_eq!(Ownership::parse(":99999"), Ok(ownership(None, Some(99999), "", "99999")));
+        assert_eq!(Ownership::parse(""), Ok(Ownership::default()));
+        assert_eq!(Ownership::parse(":"), Ok(Ownership::default()));
+
+        assert_eq!(Ownership::parse("+root"), Err(String::from("invalid user: '+root'")));
+        assert_eq!(Ownership::parse("4294967295"), Err(String::from("invalid user: '4294967295'")));
+        assert_eq!(Ownership::parse("0:-1"), Err(String::from("invalid group: '0:-1'")));
+        assert_eq!(Ownership::parse("12345:"), Err(String::from("invalid spec: '12345:'")));
+        assert_eq!(Ownership::parse("x:y:z"), Err(String::from("invalid user: 'x:y:z'")));
+
+        assert_eq!(Ownership::parse_group("root"), Ok(ownership(None, Some(0), "", "root")));
+        assert_eq!(Ownership::parse_group("+7"), Ok(ownership(None, Some(7), "", "+7")));
+        let unchanged = ownership(None, None, "", "4294967295");
+        assert_eq!(Ownership::parse_group("4294967295"), Ok(unchanged));
+        assert!(Ownership::parse_group(":0").is_err());
+    }
+
+    #[test]
+    fn symbolic_links() {
+        let options = |recursive, follow, dereference| {
+            ChownOptions::new(recursive, follow, dereference)
+                .map(|options| (options.follow, options.dereference))
+        };
+
+        assert_eq!(options(false, Follow::Never, None), Ok((Follow::CommandLine, true)));
+        assert_eq!(options(false, Follow::Always, Some(false)), Ok((Follow::Never, false)));
+        assert_eq!(options(true, Follow::Never, None), Ok((Follow::Never, false)));
+        assert_eq!(options(true, Follow::CommandLine, None), Ok((Follow::CommandLine, true)));
+        assert_eq!(options(true, Follow::Always, Some(false)), Ok((Follow::Always, false)));
+        assert!(options(true, Follow::Never, Some(true)).is_err());
+    }
+
+    #[test]
+    fn told_names() {
+        let both = names(Some(String::from("a")), Some(String::from("b")));
+        assert_eq!(both.as_deref(), Some("a:b"));
+        assert_eq!(names(Some(String::new()), Some(String::from("b"))).as_deref(), Some(":b"));
+        assert_eq!(names(None, Some(String::from("b"))).as_deref(), Some("b"));
+        assert_eq!(names(None, None), None);
+    }
+}
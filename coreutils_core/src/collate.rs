@@ -0,0 +1,127 @@
+//! Module for the comparison of strings by the collation of the locale.
+
+use std::cmp::Ordering;
+
+/// Compares strings by the `LC_COLLATE` category of the locale set on the environment.
+///
+/// With the "C" and "POSIX" locales, or when the locale can't be set, the strings are
+/// compared by their bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Collator {
+    bytes: bool,
+}
+
+impl Collator {
+    /// Creates a [`Collator`] comparing the strings by their bytes.
+    #[inline]
+    pub const fn bytes() -> Self {
+        Collator { bytes: true }
+    }
+
+    /// Creates a [`Collator`] for the locale set on the environment.
+    ///
+    /// It sets the `LC_COLLATE` category of the locale of the process from the `LC_ALL`,
+    /// `LC_COLLATE` and `LANG` variables, as `setlocale(LC_COLLATE, "")` does.
+    #[cfg(unix)]
+    pub fn from_env() -> Self {
+        use std::ffi::CStr;
+
+        let name = unsafe { libc::setlocale(libc::LC_COLLATE, "\0".as_ptr() as *const _) };
+        if name.is_null() {
+            return Self::bytes();
+        }
+
+        let name = unsafe { CStr::from_ptr(name) }.to_bytes();
+        Collator { bytes: name == b"C" || name == b"POSIX" }
+    }
+
+    /// Creates a [`Collator`] for the locale set on the environment.
+    ///
+    /// The locales are not supported on this platform, so the strings are compared by
+    /// their bytes.
+    #[cfg(not(unix))]
+    #[inline]
+    pub fn from_env() -> Self {
+        Self::bytes()
+    }
+
+    /// Whether the strings are compared by their bytes.
+    #[inline]
+    pub fn is_bytes(&self) -> bool {
+        self.bytes
+    }
+
+    /// Compare the strings `a` and `b`.
+    ///
+    /// Like `memcoll` of gnulib, the strings may contain NUL characters, where each of
+    /// their parts between them is compared in turn.
+    pub fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        if self.bytes || a == b {
+            return a.cmp(b);
+        }
+
+        compare_parts(a, b)
+    }
+}
+
+#[cfg(unix)]
+fn compare_parts(a: &[u8], b: &[u8]) -> Ordering {
+    let mut a = a.split(|&byte| byte == b'\0');
+    let mut b = b.split(|&byte| byte == b'\0');
+    // Allocated once for the terminated copies of the parts
+    let mut part_a = Vec::new();
+    let mut part_b = Vec::new();
+
+    loop {
+        match (a.next(), b.next()) {
+            (Some(next_a), Some(next_b)) => {
+                part_a.clear();
+                part_a.extend_from_slice(next_a);
+                part_a.push(b'\0');
+                part_b.clear();
+                part_b.extend_from_slice(next_b);
+                part_b.push(b'\0');
+
+                let diff = unsafe {
+                    libc::strcoll(part_a.as_ptr() as *const _, part_b.as_ptr() as *const _)
+                };
+                if diff != 0 {
+                    return diff.cmp(&0);
+                }
+            },
+            (Some(_), None) => return Ordering::Greater,
+            (None, Some(_)) => return Ordering::Less,
+            (None, None) => return Ordering::Equal,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn compare_parts(a: &[u8], b: &[u8]) -> Ordering {
+    a.cmp(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes() {
+        let collator = Collator::bytes();
+        assert!(collator.is_bytes());
+        assert_eq!(collator.compare(b"a", b"b"), Ordering::Less);
+        assert_eq!(collator.compare(b"B", b"a"), Ordering::Less);
+        assert_eq!(collator.compare(b"ab", b"a"), Ordering::Greater);
+        assert_eq!(collator.compare(b"a\0b", b"a\0b"), Ordering::Equal);
+        assert_eq!(collator.compare("é".as_bytes(), b"z"), Ordering::Greater);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn parts() {
+        assert_eq!(compare_parts(b"a\0b", b"a\0c"), Ordering::Less);
+        assert_eq!(compare_parts(b"a\0b", b"a"), Ordering::Greater);
+        assert_eq!(compare_parts(b"a", b"a\0"), Ordering::Less);
+        assert_eq!(compare_parts(b"b", b"a\0c"), Ordering::Greater);
+    }
+}
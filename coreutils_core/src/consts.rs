@@ -22,6 +22,8 @@ pub const HOST_OS: &str = "Haiku";
 pub const HOST_OS: &str = "Fuchsia";
 #[cfg(target_os = "redox")]
 pub const HOST_OS: &str = "Redox";
+#[cfg(target_os = "windows")]
+pub const HOST_OS: &str = "Windows";
 
 #[cfg(target_arch = "aarch64")]
 pub const MACHINE_ARCH: &str = "aarch64";
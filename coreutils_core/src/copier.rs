@@ -0,0 +1,1098 @@
+//! Module to copy files, and directories with their contents, keeping the attributes of
+//! the sources chosen in [`CopyOptions`], as `cp` does and `mv` does between file
+//! systems.
+//!
+//! The contents are copied with the [`copy`](crate::copy) module, with a progress bar for
+//! the big files when asked, and the errors are written on the standard error with the
+//! name of the utility.
+use std::{
+    collections::HashMap,
+    ffi::CString,
+    fs::{self, DirBuilder, File, Metadata, OpenOptions},
+    io,
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{symlink, DirBuilderExt, FileTypeExt, MetadataExt, OpenOptionsExt, PermissionsExt},
+        io::AsRawFd,
+    },
+    path::{Path, PathBuf},
+};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use crate::os::{acl, selinux, xattr};
+use crate::{
+    backup::{
+        create_existing_backup, create_numbered_backup, create_simple_backup, is_same_entry,
+        BackupMode,
+    },
+    copy,
+    error::strerror,
+    input::Input,
+    mode,
+    os::{time::set_file_times, TimeSpec},
+};
+
+mod progress;
+
+use self::progress::{Progress, PROGRESS_THRESHOLD};
+
+/// What is done with the destination files that exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overwrite {
+    /// They are replaced.
+    Always,
+    /// The user is asked whether to replace each of them.
+    Ask,
+    /// They are kept.
+    Never,
+}
+
+/// When the symbolic links of the sources are followed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dereference {
+    /// Never, copying them as links.
+    Never,
+    /// Only the ones of the sources given to [`Copier::copy`].
+    CommandLine,
+    /// Always.
+    Always,
+}
+
+/// When the data of the regular files is cloned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reflink {
+    /// Never, always copying it.
+    Never,
+    /// When the file system supports it, copying it otherwise.
+    Auto,
+    /// Always, failing if it can't be cloned.
+    Always,
+}
+
+/// When the copies of the regular files are made sparse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sparse {
+    /// Never, writing the zeros of the holes.
+    Never,
+    /// When the source has holes, leaving them in the same places.
+    Auto,
+    /// Always, leaving holes also where the source has blocks of zeros.
+    Always,
+}
+
+/// The attributes of the sources that are copied to the destinations.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Preserve {
+    pub mode: bool,
+    pub ownership: bool,
+    pub timestamps: bool,
+    /// The hard links between the sources.
+    pub links: bool,
+    /// The security context of SELinux.
+    pub context: bool,
+    /// The extended attributes.
+    pub xattr: bool,
+}
+
+impl Preserve {
+    /// Set the attributes named like in the `--preserve` list of `cp` to `value`, with
+    /// `all` for all of them.
+    pub fn set<'a>(&mut self, attributes: impl IntoIterator<Item = &'a str>, value: bool) {
+        for attribute in attributes {
+            match attribute {
+                "mode" => self.mode = value,
+                "ownership" => self.ownership = value,
+                "timestamps" => self.timestamps = value,
+                "links" => self.links = value,
+                "context" => self.context = value,
+                "xattr" => self.xattr = value,
+                "all" => {
+                    *self = Preserve {
+                        mode: value,
+                        ownership: value,
+                        timestamps: value,
+                        links: value,
+                        context: value,
+                        xattr: value,
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+/// The options of the copies.
+#[derive(Debug, Clone)]
+pub struct CopyOptions {
+    pub overwrite: Overwrite,
+    /// Only the destination files older than their sources are replaced.
+    pub update: bool,
+    /// The destination files that can't be opened are removed.
+    pub force: bool,
+    /// The copies of the big files show a progress bar.
+    pub progress: bool,
+    pub backup: BackupMode,
+    pub suffix: String,
+    /// The directories are copied with their contents, and the special files are created
+    /// again instead of reading from them.
+    pub recursive: bool,
+    pub dereference: Dereference,
+    pub reflink: Reflink,
+    pub sparse: Sparse,
+    pub preserve: Preserve,
+    /// The attributes that make the copy fail if they can't be preserved, that are
+    /// otherwise not preserved silently when the destinations can't have them.
+    pub required: Preserve,
+    /// The files are created with the default permissions instead of the ones of their
+    /// sources.
+    pub default_mode: bool,
+}
+
+impl CopyOptions {
+    /// The options to copy the files as they are, recursively, without following the
+    /// symbolic links and preserving all the attributes that can be, like `cp -a`.
+    pub fn archive() -> Self {
+        let mut preserve = Preserve::default();
+        preserve.set(Some("all"), true);
+        preserve.context = selinux_enabled();
+
+        CopyOptions {
+            overwrite: Overwrite::Always,
+            update: false,
+            force: false,
+            progress: false,
+            backup: BackupMode::None,
+            suffix: "~".to_string(),
+            recursive: true,
+            dereference: Dereference::Never,
+            reflink: Reflink::Auto,
+            sparse: Sparse::Auto,
+            preserve,
+            required: Preserve::default(),
+            default_mode: false,
+        }
+    }
+}
+
+/// Copies the sources with the options, keeping the state shared between them.
+#[derive(Debug)]
+pub struct Copier<'a> {
+    /// The name of the utility, that starts the error messages.
+    name: &'a str,
+    options: &'a CopyOptions,
+    /// The destinations of the files with many hard links that were copied, by their
+    /// device and inode numbers, to link them again when preserving the links.
+    links: HashMap<(u64, u64), PathBuf>,
+    /// The permissions removed from the files created.
+    umask: u32,
+}
+
+impl<'a> Copier<'a> {
+    /// Make a copier with the `options` for the utility of the `name`.
+    pub fn new(name: &'a str, options: &'a CopyOptions) -> Self {
+        Copier { name, options, links: HashMap::new(), umask: mode::umask() }
+    }
+
+    /// Copy the `source` of the command line to `dest`, returning `true` if everything
+    /// was copied.
+    pub fn copy(&mut self, source: &Path, dest: &Path) -> bool {
+        self.copy_file(source, dest, true)
+    }
+
+    /// Copy the `source` to `dest` by its type, and preserve its attributes.
+    fn copy_file(&mut self, source: &Path, dest: &Path, command_line: bool) -> bool {
+        let follow = match self.options.dereference {
+            Dereference::Always => true,
+            Dereference::CommandLine => command_line,
+            Dereference::Never => false,
+        };
+
+        let metadata = if follow { fs::metadata(source) } else { fs::symlink_metadata(source) };
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                eprintln!("{}: cannot stat '{}': {}", self.name, source.display(), strerror(&err));
+                return false;
+            },
+        };
+
+        if let Ok(dest_metadata) = fs::metadata(dest) {
+            let same =
+                dest_metadata.dev() == metadata.dev() && dest_metadata.ino() == metadata.ino();
+
+            // Like GNU, nothing is done with -n, and the backup moves the destination away
+            if same && self.options.overwrite == Overwrite::Never && !metadata.is_dir() {
+                return true;
+            }
+
+            if same && !self.backs_up_same(source, dest, &metadata, follow) {
+                eprintln!(
+                    "{}: '{}' and '{}' are the same file",
+                    self.name,
+                    source.display(),
+                    dest.display()
+                );
+                return false;
+            }
+        }
+
+        let file_type = metadata.file_type();
+
+        if file_type.is_dir() {
+            return self.copy_directory(source, dest, &metadata);
+        }
+
+        if let Ok(dest_metadata) = fs::symlink_metadata(dest) {
+            if dest_metadata.is_dir() {
+                eprintln!(
+                    "{}: cannot overwrite directory '{}' with non-directory",
+                    self.name,
+                    dest.display()
+                );
+                return false;
+            }
+
+            if !self.replaces(dest, &metadata, &dest_metadata) {
+                return true;
+            }
+
+            if !self.backup(dest) {
+                return false;
+            }
+        }
+
+        let id = (metadata.dev(), metadata.ino());
+        let hard_linked = self.options.preserve.links && metadata.nlink() > 1;
+
+        if hard_linked {
+            if let Some(first) = self.links.get(&id) {
+                return self.link(first, dest);
+            }
+        }
+
+        let copied = if file_type.is_symlink() {
+            self.copy_symlink(source, dest)
+        } else if file_type.is_file() || !self.options.recursive {
+            // Without `-R` the contents of the special files are read
+            self.copy_contents(source, dest, &metadata)
+        } else {
+            self.create_special(dest, &metadata)
+        };
+
+        if !copied {
+            return false;
+        }
+
+        if hard_linked {
+            self.links.insert(id, dest.to_path_buf());
+        }
+
+        self.preserve(source, dest, &metadata, false)
+    }
+
+    /// Returns `true` if the existing `dest`, the same file as the `source` of the
+    /// `metadata`, can be copied to after moving it away with `--backup`. It can't if
+    /// they are the same entry, or if the `source` is a symbolic link `follow`ed to
+    /// `dest`, that would be left dangling.
+    fn backs_up_same(&self, source: &Path, dest: &Path, metadata: &Metadata, follow: bool) -> bool {
+        if self.options.backup == BackupMode::None || metadata.is_dir() {
+            return false;
+        }
+
+        let is_symlink =
+            |path| matches!(fs::symlink_metadata(path), Ok(m) if m.file_type().is_symlink());
+
+        if follow && is_symlink(source) && !is_symlink(dest) {
+            return false;
+        }
+
+        !is_same_entry(source, dest)
+    }
+
+    /// Returns `true` if the existing `dest` is replaced by the copy of the source of the
+    /// `metadata`, by `--update` and asking the user with `-i`.
+    fn replaces(&self, dest: &Path, metadata: &Metadata, dest_metadata: &Metadata) -> bool {
+        if self.options.update {
+            if let (Ok(modified), Ok(dest_modified)) =
+                (metadata.modified(), dest_metadata.modified())
+            {
+                if dest_modified >= modified {
+                    return false;
+                }
+            }
+        }
+
+        match self.options.overwrite {
+            Overwrite::Always => true,
+            Overwrite::Never => false,
+            Overwrite::Ask => Input::new()
+                .with_msg(&format!("{}: overwrite '{}'? ", self.name, dest.display()))
+                .with_err_msg(&format!("{}: could not read user input", self.name))
+                .is_affirmative(),
+        }
+    }
+
+    /// Make the backup of the existing `dest` with the `--backup` method, moving it away,
+    /// returning `true` if it was made or was not needed.
+    fn backup(&self, dest: &Path) -> bool {
+        let suffix = &self.options.suffix;
+
+        let backup = match self.options.backup {
+            BackupMode::None => return true,
+            BackupMode::Numbered => create_numbered_backup(dest),
+            BackupMode::Existing => create_existing_backup(dest, suffix),
+            BackupMode::Simple => create_simple_backup(dest, suffix),
+        };
+
+        match backup {
+            Ok(_) => true,
+            Err(err) => {
+                eprintln!("{}: cannot backup '{}': {}", self.name, dest.display(), strerror(&err));
+                false
+            },
+        }
+    }
+
+    /// Copy the `source` directory and its contents to `dest`, creating it if needed.
+    fn copy_directory(&mut self, source: &Path, dest: &Path, metadata: &Metadata) -> bool {
+        if !self.options.recursive {
+            eprintln!("{}: -r not specified; omitting directory '{}'", self.name, source.display());
+            return false;
+        }
+
+        if is_inside(source, dest) {
+            eprintln!(
+                "{}: cannot copy a directory, '{}', into itself, '{}'",
+                self.name,
+                source.display(),
+                dest.display()
+            );
+            return false;
+        }
+
+        let created = match fs::symlink_metadata(dest) {
+            Ok(dest_metadata) if dest_metadata.is_dir() => false,
+            Ok(_) => {
+                eprintln!(
+                    "{}: cannot overwrite non-directory '{}' with directory '{}'",
+                    self.name,
+                    dest.display(),
+                    source.display()
+                );
+                return false;
+            },
+            // The owner can write the contents until its mode is set
+            Err(_) => match DirBuilder::new().mode(self.new_mode(metadata) | 0o700).create(dest) {
+                Ok(_) => true,
+                Err(err) => {
+                    eprintln!(
+                        "{}: cannot create directory '{}': {}",
+                        self.name,
+                        dest.display(),
+                        strerror(&err)
+                    );
+                    return false;
+                },
+            },
+        };
+
+        let entries = match fs::read_dir(source) {
+            Ok(entries) => entries,
+            Err(err) => {
+                eprintln!(
+                    "{}: cannot access '{}': {}",
+                    self.name,
+                    source.display(),
+                    strerror(&err)
+                );
+                return false;
+            },
+        };
+
+        let mut success = true;
+
+        for entry in entries {
+            match entry {
+                Ok(entry) => {
+                    success &= self.copy_file(&entry.path(), &dest.join(entry.file_name()), false)
+                },
+                Err(err) => {
+                    eprintln!(
+                        "{}: cannot access '{}': {}",
+                        self.name,
+                        source.display(),
+                        strerror(&err)
+                    );
+                    success = false;
+                },
+            }
+        }
+
+        success & self.preserve(source, dest, metadata, created)
+    }
+
+    /// Copy the contents of the `source` to `dest`, creating it with the permissions of
+    /// the `metadata` if it doesn't exist, or clone them with `--reflink`.
+    fn copy_contents(&self, source: &Path, dest: &Path, metadata: &Metadata) -> bool {
+        let mode = self.new_mode(metadata);
+        let reflink = self.options.reflink;
+
+        // APFS can only clone to a new file
+        #[cfg(target_os = "macos")]
+        {
+            if reflink != Reflink::Never && fs::symlink_metadata(dest).is_err() {
+                match self.clone_file(source, dest, mode) {
+                    Ok(_) => return true,
+                    Err(err) if reflink == Reflink::Always => {
+                        eprintln!(
+                            "{}: failed to clone '{}' from '{}': {}",
+                            self.name,
+                            dest.display(),
+                            source.display(),
+                            strerror(&err)
+                        );
+                        return false;
+                    },
+                    Err(_) => {},
+                }
+            }
+        }
+
+        let input = match File::open(source) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!(
+                    "{}: cannot open '{}' for reading: {}",
+                    self.name,
+                    source.display(),
+                    strerror(&err)
+                );
+                return false;
+            },
+        };
+
+        let open =
+            || OpenOptions::new().write(true).create(true).truncate(true).mode(mode).open(dest);
+
+        // With `-f` a file that can't be opened is replaced
+        let output = match open() {
+            Err(_) if self.options.force && fs::remove_file(dest).is_ok() => open(),
+            output => output,
+        };
+
+        let output = match output {
+            Ok(output) => output,
+            Err(err) => {
+                eprintln!(
+                    "{}: cannot create regular file '{}': {}",
+                    self.name,
+                    dest.display(),
+                    strerror(&err)
+                );
+                return false;
+            },
+        };
+
+        if reflink != Reflink::Never {
+            let cloned = clone_contents(&input, &output);
+
+            match cloned {
+                Ok(_) => return true,
+                Err(err) if reflink == Reflink::Always => {
+                    eprintln!(
+                        "{}: failed to clone '{}' from '{}': {}",
+                        self.name,
+                        dest.display(),
+                        source.display(),
+                        strerror(&err)
+                    );
+                    return false;
+                },
+                Err(_) => {},
+            }
+        }
+
+        // Only the copies to new or truncated regular files can have holes, that with
+        // `--sparse=always` are also made from the zeros of the inputs that aren't files
+        let sparse = matches!(output.metadata(), Ok(output) if output.is_file())
+            && match self.options.sparse {
+                Sparse::Never => false,
+                Sparse::Auto => metadata.is_file() && is_sparse(metadata),
+                Sparse::Always => true,
+            };
+
+        let mut progress = if self.options.progress && metadata.len() >= PROGRESS_THRESHOLD {
+            Some(Progress::new(source, metadata.len()))
+        } else {
+            None
+        };
+        let shows_progress = progress.is_some();
+        let mut update = |copied| {
+            if let Some(progress) = &mut progress {
+                progress.update(copied);
+            }
+        };
+
+        let copied = if sparse {
+            let zeros = self.options.sparse == Sparse::Always;
+            copy::copy_sparse(input.as_raw_fd(), output.as_raw_fd(), zeros, &mut update)
+        } else if shows_progress {
+            copy::copy_with_progress(input.as_raw_fd(), output.as_raw_fd(), &mut update)
+        } else {
+            copy::copy(input.as_raw_fd(), output.as_raw_fd())
+        };
+
+        if let (Some(progress), Ok(copied)) = (&mut progress, &copied) {
+            progress.finish(*copied);
+        }
+
+        match copied {
+            Ok(_) => true,
+            Err(err) => {
+                eprintln!(
+                    "{}: error copying '{}' to '{}': {}",
+                    self.name,
+                    source.display(),
+                    dest.display(),
+                    strerror(&err)
+                );
+                false
+            },
+        }
+    }
+
+    /// Clone the `source` file to the new file `dest`, that gets the permissions of the
+    /// `mode` and the current times as a copy would, before its attributes are preserved.
+    #[cfg(target_os = "macos")]
+    fn clone_file(&self, source: &Path, dest: &Path, mode: u32) -> io::Result<()> {
+        copy::clone_file(source, dest)?;
+
+        let now = TimeSpec { tv_sec: 0, tv_nsec: libc::UTIME_NOW as _ };
+        fs::set_permissions(dest, PermissionsExt::from_mode(mode & !self.umask))?;
+        set_file_times(dest, Some(now), Some(now), false)
+    }
+
+    /// Create a named pipe, device or socket like the one of the `metadata` in `dest`.
+    fn create_special(&self, dest: &Path, metadata: &Metadata) -> bool {
+        // A link from the destination is replaced
+        if let Ok(dest_metadata) = fs::symlink_metadata(dest) {
+            if !dest_metadata.is_dir() && fs::remove_file(dest).is_err() {
+                eprintln!("{}: cannot remove '{}'", self.name, dest.display());
+                return false;
+            }
+        }
+
+        let file_type = metadata.file_type();
+        let mode = self.new_mode(metadata);
+
+        let result = if file_type.is_fifo() {
+            make_fifo(dest, mode)
+        } else {
+            // The `mode_t` isn't of 32 bits on all the systems
+            #[allow(clippy::unnecessary_cast)]
+            let node_type = metadata.mode() & libc::S_IFMT as u32;
+
+            make_node(dest, node_type | mode, metadata.rdev())
+        };
+
+        match result {
+            Ok(_) => true,
+            Err(err) if file_type.is_fifo() => {
+                eprintln!(
+                    "{}: cannot create fifo '{}': {}",
+                    self.name,
+                    dest.display(),
+                    strerror(&err)
+                );
+                false
+            },
+            Err(err) => {
+                eprintln!(
+                    "{}: cannot create special file '{}': {}",
+                    self.name,
+                    dest.display(),
+                    strerror(&err)
+                );
+                false
+            },
+        }
+    }
+
+    /// Get the permissions of a file created as a copy of the one of the `metadata`,
+    /// before the umask is applied. They are the default ones with
+    /// `--no-preserve=mode`, and the set user ID, set group ID and sticky bits are
+    /// only copied when preserving the mode.
+    fn new_mode(&self, metadata: &Metadata) -> u32 {
+        match (self.options.default_mode, metadata.is_dir()) {
+            (true, true) => 0o777,
+            (true, false) => 0o666,
+            (false, _) => metadata.mode() & 0o777,
+        }
+    }
+
+    /// Set the attributes of the `source` and its `metadata` that are preserved on
+    /// `dest`. The mode of a directory that was `created` is set even if it is not
+    /// preserved, as it was created writable.
+    fn preserve(&self, source: &Path, dest: &Path, metadata: &Metadata, created: bool) -> bool {
+        let preserve = self.options.preserve;
+        let is_symlink = metadata.file_type().is_symlink();
+
+        // The owner first, as changing it may clear the set user ID and set group ID bits
+        if preserve.ownership {
+            if let Err(err) = change_owner(dest, metadata.uid(), metadata.gid()) {
+                eprintln!(
+                    "{}: failed to preserve ownership for '{}': {}",
+                    self.name,
+                    dest.display(),
+                    strerror(&err)
+                );
+                return false;
+            }
+        }
+
+        // Before the mode, as the attributes can only be set on the files that can be
+        // written
+        if !self.preserve_extended(source, dest, is_symlink) {
+            return false;
+        }
+
+        let mode = if preserve.mode {
+            Some(metadata.mode() & 0o7777)
+        } else if created {
+            Some(self.new_mode(metadata) & !self.umask)
+        } else {
+            None
+        };
+
+        if let (Some(mode), false) = (mode, is_symlink) {
+            if let Err(err) = fs::set_permissions(dest, PermissionsExt::from_mode(mode)) {
+                eprintln!(
+                    "{}: preserving permissions for '{}': {}",
+                    self.name,
+                    dest.display(),
+                    strerror(&err)
+                );
+                return false;
+            }
+
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            {
+                if preserve.mode {
+                    if let Err(err) = acl::copy_acl(source, dest) {
+                        let message = format!("preserving permissions for '{}'", dest.display());
+                        if !self.attribute_failed(&message, &err, self.options.required.mode) {
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+
+        if preserve.timestamps {
+            let atime =
+                TimeSpec { tv_sec: metadata.atime() as _, tv_nsec: metadata.atime_nsec() as _ };
+            let mtime =
+                TimeSpec { tv_sec: metadata.mtime() as _, tv_nsec: metadata.mtime_nsec() as _ };
+
+            if let Err(err) = set_file_times(dest, Some(atime), Some(mtime), is_symlink) {
+                eprintln!(
+                    "{}: preserving times for '{}': {}",
+                    self.name,
+                    dest.display(),
+                    strerror(&err)
+                );
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Copy the extended attributes and the security context of the `source` to `dest`
+    /// if they are preserved, returning `false` if the copy failed. The symbolic links
+    /// only get their context, as the other attributes are only for the files.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn preserve_extended(&self, source: &Path, dest: &Path, is_symlink: bool) -> bool {
+        let (preserve, required) = (self.options.preserve, self.options.required);
+
+        if preserve.xattr && !is_symlink {
+            if let Err(err) = copy_xattrs(source, dest) {
+                let message = format!("setting attributes for '{}'", dest.display());
+                if !self.attribute_failed(&message, &err, required.xattr) {
+                    return false;
+                }
+            }
+        }
+
+        if preserve.context {
+            let copied = selinux::file_context(source, !is_symlink)
+                .and_then(|context| selinux::set_file_context(dest, &context, !is_symlink));
+
+            if let Err(err) = copied {
+                let message = format!("failed to set the security context of '{}'", dest.display());
+                if !self.attribute_failed(&message, &err, required.context) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// The extended attributes can't be preserved on this system.
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn preserve_extended(&self, _source: &Path, _dest: &Path, _is_symlink: bool) -> bool {
+        true
+    }
+
+    /// Report the `err` of preserving a attribute of a file with the `message`, returning
+    /// `false` if it makes the copy fail. Only the attributes `required` by name make it
+    /// fail, and when the destination can't have them it is only a warning, that is not
+    /// even reported if the attribute is not required.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn attribute_failed(&self, message: &str, err: &io::Error, required: bool) -> bool {
+        let unsupported = err.raw_os_error() == Some(libc::ENOTSUP);
+
+        if required || !unsupported {
+            eprintln!("{}: {}: {}", self.name, message, strerror(err));
+        }
+
+        !required || unsupported
+    }
+
+    /// Create a symbolic link in `dest` with the target of the `source` link, replacing
+    /// the file in `dest`.
+    fn copy_symlink(&self, source: &Path, dest: &Path) -> bool {
+        let target = match fs::read_link(source) {
+            Ok(target) => target,
+            Err(err) => {
+                eprintln!(
+                    "{}: cannot read symbolic link '{}': {}",
+                    self.name,
+                    source.display(),
+                    strerror(&err)
+                );
+                return false;
+            },
+        };
+
+        if fs::symlink_metadata(dest).is_ok() && fs::remove_file(dest).is_err() {
+            eprintln!("{}: cannot remove '{}'", self.name, dest.display());
+            return false;
+        }
+
+        match symlink(target, dest) {
+            Ok(_) => true,
+            Err(err) => {
+                eprintln!(
+                    "{}: cannot create symbolic link '{}': {}",
+                    self.name,
+                    dest.display(),
+                    strerror(&err)
+                );
+                false
+            },
+        }
+    }
+
+    /// Create `dest` as a hard link to the `first` copy of a file, replacing the file in
+    /// `dest`.
+    fn link(&self, first: &Path, dest: &Path) -> bool {
+        if fs::symlink_metadata(dest).is_ok() && fs::remove_file(dest).is_err() {
+            eprintln!("{}: cannot remove '{}'", self.name, dest.display());
+            return false;
+        }
+
+        match fs::hard_link(first, dest) {
+            Ok(_) => true,
+            Err(err) => {
+                eprintln!(
+                    "{}: cannot create hard link '{}' to '{}': {}",
+                    self.name,
+                    dest.display(),
+                    first.display(),
+                    strerror(&err)
+                );
+                false
+            },
+        }
+    }
+}
+
+/// Copy the extended attributes of the file in `source` to `dest`, but the ACLs and the
+/// security context, that are preserved with the mode and the context.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn copy_xattrs(source: &Path, dest: &Path) -> io::Result<()> {
+    let names = match xattr::list(source, true) {
+        Ok(names) => names,
+        // The file system of the source has no attributes
+        Err(err) if err.raw_os_error() == Some(libc::ENOTSUP) => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    for name in names {
+        if name.starts_with(b"system.posix_acl_") || name == "security.selinux" {
+            continue;
+        }
+
+        let value = xattr::get(source, &name, true)?;
+        xattr::set(dest, &name, &value, true)?;
+    }
+
+    Ok(())
+}
+
+/// Returns `true` if the file of the `metadata` uses less blocks than its size needs, so
+/// it has holes.
+fn is_sparse(metadata: &Metadata) -> bool {
+    metadata.blocks().saturating_mul(512) < metadata.size()
+}
+
+/// Make `output` share the data of `input` on the file systems with copy-on-write.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn clone_contents(input: &File, output: &File) -> io::Result<()> {
+    copy::reflink(input.as_raw_fd(), output.as_raw_fd())
+}
+
+/// The files are cloned to new destinations on macOS, and can't be on the other systems.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn clone_contents(_input: &File, _output: &File) -> io::Result<()> {
+    Err(io::Error::from_raw_os_error(libc::EOPNOTSUPP))
+}
+
+/// Returns `true` if `dest` is the `source` directory or is inside it.
+fn is_inside(source: &Path, dest: &Path) -> bool {
+    let source = match fs::canonicalize(source) {
+        Ok(source) => source,
+        Err(_) => return false,
+    };
+
+    // The destination may not exist yet, but its directory must
+    let dest = match fs::canonicalize(dest) {
+        Ok(dest) => dest,
+        Err(_) => match (dest.parent(), dest.file_name()) {
+            (Some(parent), Some(name)) => {
+                let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+
+                match fs::canonicalize(parent) {
+                    Ok(parent) => parent.join(name),
+                    Err(_) => return false,
+                }
+            },
+            _ => return false,
+        },
+    };
+
+    dest.starts_with(source)
+}
+
+/// Change the owner and group of the file in `path`, or of the link itself if it is a
+/// symbolic link. If the user can't give the file away, only the group is changed, and it
+/// is not an error if that fails too.
+fn change_owner(path: &Path, uid: u32, gid: u32) -> io::Result<()> {
+    let path = CString::new(path.as_os_str().as_bytes())?;
+
+    if unsafe { libc::lchown(path.as_ptr(), uid, gid) } == 0 {
+        return Ok(());
+    }
+
+    let err = io::Error::last_os_error();
+
+    match err.raw_os_error() {
+        Some(libc::EPERM) | Some(libc::EINVAL) => {
+            unsafe { libc::lchown(path.as_ptr(), u32::MAX, gid) };
+            Ok(())
+        },
+        _ => Err(err),
+    }
+}
+
+/// Create a named pipe in `path` with the permissions of the `mode`.
+fn make_fifo(path: &Path, mode: u32) -> io::Result<()> {
+    let path = CString::new(path.as_os_str().as_bytes())?;
+
+    match unsafe { libc::mkfifo(path.as_ptr(), mode as libc::mode_t) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Create a device or socket in `path` with the type and permissions of the `mode`, and
+/// the device number `rdev`.
+fn make_node(path: &Path, mode: u32, rdev: u64) -> io::Result<()> {
+    let path = CString::new(path.as_os_str().as_bytes())?;
+
+    match unsafe { libc::mknod(path.as_ptr(), mode as libc::mode_t, rdev as libc::dev_t) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Returns `true` if the security contexts of SELinux can be preserved.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn selinux_enabled() -> bool {
+    selinux::is_enabled()
+}
+
+/// SELinux is only on Linux.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn selinux_enabled() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, io::Write};
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("core.copier.{}.{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// The options of `cp -a` without cloning, so the contents are always copied.
+    fn archive() -> CopyOptions {
+        CopyOptions { reflink: Reflink::Never, ..CopyOptions::archive() }
+    }
+
+    #[test]
+    fn preserve_mode() {
+        let dir = temp_dir("mode");
+        let source = dir.join("source");
+        fs::write(&source, b"mode").unwrap();
+        fs::set_permissions(&source, PermissionsExt::from_mode(0o2751)).unwrap();
+
+        let options = archive();
+        assert!(Copier::new("cp", &options).copy(&source, &dir.join("kept")));
+        assert_eq!(fs::metadata(dir.join("kept")).unwrap().mode() & 0o7777, 0o2751);
+
+        // The special bits are only copied with the mode
+        let options = CopyOptions { preserve: Preserve::default(), ..archive() };
+        let mut copier = Copier::new("cp", &options);
+        let umask = copier.umask;
+        assert!(copier.copy(&source, &dir.join("new")));
+        assert_eq!(fs::metadata(dir.join("new")).unwrap().mode() & 0o7777, 0o751 & !umask);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn preserve_timestamps() {
+        let dir = temp_dir("timestamps");
+        let source = dir.join("source");
+        fs::write(&source, b"times").unwrap();
+        let atime = TimeSpec { tv_sec: 1_500_000_000, tv_nsec: 123_456_789 };
+        let mtime = TimeSpec { tv_sec: 1_600_000_000, tv_nsec: 987_654_321 };
+        set_file_times(&source, Some(atime), Some(mtime), false).unwrap();
+
+        let options = archive();
+        assert!(Copier::new("cp", &options).copy(&source, &dir.join("dest")));
+
+        let metadata = fs::metadata(dir.join("dest")).unwrap();
+        assert_eq!((metadata.atime(), metadata.atime_nsec()), (1_500_000_000, 123_456_789));
+        assert_eq!((metadata.mtime(), metadata.mtime_nsec()), (1_600_000_000, 987_654_321));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn preserve_ownership() {
+        let dir = temp_dir("ownership");
+        let source = dir.join("source");
+        fs::write(&source, b"owner").unwrap();
+
+        // Only the superuser can give the source away, the others keep their own
+        if unsafe { libc::geteuid() } == 0 {
+            change_owner(&source, 1, 1).unwrap();
+        }
+        let metadata = fs::metadata(&source).unwrap();
+
+        let options = archive();
+        assert!(Copier::new("cp", &options).copy(&source, &dir.join("dest")));
+
+        let dest = fs::metadata(dir.join("dest")).unwrap();
+        assert_eq!((dest.uid(), dest.gid()), (metadata.uid(), metadata.gid()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn create_special_files() {
+        let dir = temp_dir("special");
+        let fifo = dir.join("fifo");
+        make_fifo(&fifo, 0o640).unwrap();
+        symlink("fifo", dir.join("link")).unwrap();
+
+        // The pipe is created again instead of being read, that would block
+        let options = archive();
+        let mut copier = Copier::new("cp", &options);
+        assert!(copier.copy(&fifo, &dir.join("fifo.copy")));
+        assert!(copier.copy(&dir.join("link"), &dir.join("link.copy")));
+
+        let metadata = fs::symlink_metadata(dir.join("fifo.copy")).unwrap();
+        assert!(metadata.file_type().is_fifo());
+        assert_eq!(metadata.mode() & 0o777, 0o640);
+        assert_eq!(fs::read_link(dir.join("link.copy")).unwrap(), Path::new("fifo"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn copy_sparse() {
+        let dir = temp_dir("sparse");
+        let source = dir.join("source");
+        let mut file = File::create(&source).unwrap();
+        file.write_all(b"start").unwrap();
+        file.set_len(16 * 1024 * 1024).unwrap();
+        drop(file);
+
+        let options = archive();
+        assert!(Copier::new("cp", &options).copy(&source, &dir.join("auto")));
+        let options = CopyOptions { sparse: Sparse::Never, ..archive() };
+        assert!(Copier::new("cp", &options).copy(&source, &dir.join("never")));
+
+        let content = fs::read(&source).unwrap();
+        assert_eq!(fs::read(dir.join("auto")).unwrap(), content);
+        assert_eq!(fs::read(dir.join("never")).unwrap(), content);
+
+        // Only on the file systems with holes
+        if is_sparse(&fs::metadata(&source).unwrap()) {
+            assert!(is_sparse(&fs::metadata(dir.join("auto")).unwrap()));
+            assert!(!is_sparse(&fs::metadata(dir.join("never")).unwrap()));
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn same_file() {
+        let dir = temp_dir("same");
+        let source = dir.join("source");
+        let link = dir.join("link");
+        fs::write(&source, b"same").unwrap();
+        fs::hard_link(&source, &link).unwrap();
+
+        let options = archive();
+        assert!(!Copier::new("cp", &options).copy(&source, &link));
+
+        // Nothing is done with -n
+        let options = CopyOptions { overwrite: Overwrite::Never, ..archive() };
+        assert!(Copier::new("cp", &options).copy(&source, &link));
+        assert_eq!(fs::metadata(&link).unwrap().nlink(), 2);
+
+        // The backup moves the link away, but not the same entry
+        let options = CopyOptions { backup: BackupMode::Simple, ..archive() };
+        assert!(Copier::new("cp", &options).copy(&source, &link));
+        assert_eq!(fs::read(&link).unwrap(), b"same");
+        assert_eq!(
+            fs::metadata(dir.join("link~")).unwrap().ino(),
+            fs::metadata(&source).unwrap().ino()
+        );
+        assert!(!Copier::new("cp", &options).copy(&source, &dir.join(".").join("source")));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,164 @@
+//! The progress bar of the copies of the big files.
+use std::{
+    io::{self, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::os::tty::tty_dimensions;
+
+/// The size of the smallest files whose copies show a progress bar.
+pub(crate) const PROGRESS_THRESHOLD: u64 = 16 << 20;
+
+/// The time between the updates of the bar.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The progress bar of the copy of a file, written on a single line of the standard
+/// error, that is written again as the data is copied.
+pub(crate) struct Progress {
+    name: String,
+    total: u64,
+    start: Instant,
+    last_update: Option<Instant>,
+    width: usize,
+    /// The length of the last line written, to clear what is left of it.
+    last_len: usize,
+}
+
+impl Progress {
+    /// Make the bar of the copy of the file `name` of `total` bytes.
+    pub(crate) fn new(name: &Path, total: u64) -> Self {
+        let width = match tty_dimensions(&io::stderr()) {
+            Some((columns, _)) if columns > 0 => columns as usize,
+            _ => 80,
+        };
+
+        Progress {
+            name: name.display().to_string(),
+            total,
+            start: Instant::now(),
+            last_update: None,
+            width,
+            last_len: 0,
+        }
+    }
+
+    /// Show that `copied` bytes were copied, if the bar was not written too recently.
+    pub(crate) fn update(&mut self, copied: u64) {
+        let now = Instant::now();
+
+        if let Some(last_update) = self.last_update {
+            if now.duration_since(last_update) < UPDATE_INTERVAL {
+                return;
+            }
+        }
+
+        self.last_update = Some(now);
+        self.write(copied, now);
+    }
+
+    /// Write the bar of the finished copy of `copied` bytes and leave its line.
+    pub(crate) fn finish(&mut self, copied: u64) {
+        self.write(copied, Instant::now());
+        eprintln!();
+    }
+
+    fn write(&mut self, copied: u64, now: Instant) {
+        let elapsed = now.duration_since(self.start).as_secs_f64();
+        let rate = if elapsed > 0.0 { copied as f64 / elapsed } else { 0.0 };
+        let percent = (copied.min(self.total) * 100).checked_div(self.total).unwrap_or(100);
+
+        let eta = if rate > 0.0 && copied < self.total {
+            format_duration((self.total - copied) as f64 / rate)
+        } else {
+            format_duration(0.0)
+        };
+
+        // The numbers are padded so the bar keeps its width
+        let stats = format!(
+            "{:>3}% {:>9}/{:<9} {:>9}/s ETA {}",
+            percent,
+            format_size(copied as f64),
+            format_size(self.total as f64),
+            format_size(rate),
+            eta
+        );
+
+        // The name is shortened to leave room for a bar of at least 10 characters
+        let room = self.width.saturating_sub(stats.len() + 4);
+        let name_width = room.saturating_sub(10).min(self.width / 3);
+        let name: String = if self.name.chars().count() > name_width {
+            let skip = self.name.chars().count() - name_width.saturating_sub(3);
+            format!("...{}", self.name.chars().skip(skip).collect::<String>())
+        } else {
+            self.name.clone()
+        };
+
+        let bar_width = room.saturating_sub(name.chars().count()).max(10);
+        let filled = (percent as usize * bar_width / 100).min(bar_width);
+        let bar = match filled {
+            0 => " ".repeat(bar_width),
+            _ if filled == bar_width => "=".repeat(bar_width),
+            _ => format!("{}>{}", "=".repeat(filled - 1), " ".repeat(bar_width - filled)),
+        };
+
+        let line = format!("{} [{}] {}", name, bar, stats);
+        let len = line.chars().count();
+        let clear = " ".repeat(self.last_len.saturating_sub(len));
+        self.last_len = len;
+
+        let mut stderr = io::stderr();
+        let _ = write!(stderr, "\r{}{}", line, clear);
+        let _ = stderr.flush();
+    }
+}
+
+/// Display the `bytes` with the largest binary suffix that leaves them under 1024, with a
+/// decimal digit.
+fn format_size(bytes: f64) -> String {
+    let suffixes = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+    let mut size = bytes;
+    let mut suffix = 0;
+    while size >= 1024.0 && suffix < suffixes.len() - 1 {
+        size /= 1024.0;
+        suffix += 1;
+    }
+
+    if suffix == 0 {
+        format!("{}{}", size as u64, suffixes[suffix])
+    } else {
+        format!("{:.1}{}", size, suffixes[suffix])
+    }
+}
+
+/// Display the `seconds` as minutes and seconds, with the hours if there are.
+fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(format_size(10.0), "10B");
+        assert_eq!(format_size(1536.0), "1.5KiB");
+        assert_eq!(format_size(16.0 * 1024.0 * 1024.0), "16.0MiB");
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(0.0), "00:00");
+        assert_eq!(format_duration(75.4), "01:15");
+        assert_eq!(format_duration(3725.0), "1:02:05");
+    }
+}
@@ -0,0 +1,611 @@
+//! Module to copy data between file descriptors, moving it kernel-side when the system
+//! supports it.
+//!
+//! On Linux `copy_file_range(2)`, `sendfile(2)` and `splice(2)` are tried in that order,
+//! on the other systems, or when none of them can be used with the given file
+//! descriptors, a buffered read/write loop is used.
+//!
+//! The files can also be cloned without copying the data on the file systems with
+//! copy-on-write, with [`reflink`] on Linux and [`clone_file`] on macOS, and copied
+//! keeping their holes with [`copy_sparse`].
+//!
+//! The progress of the copies can be followed with [`copy_with_progress`] and
+//! [`copy_sparse`], that call a function with the number of bytes copied after each part
+//! of the data.
+
+#[cfg(target_os = "macos")]
+use std::{ffi::CString, os::unix::ffi::OsStrExt, path::Path};
+use std::{io, mem::MaybeUninit, os::unix::io::RawFd};
+
+/// The buffer size used by the read/write fallback.
+pub const COPY_BUFFER_SIZE: usize = 128 * 1024;
+
+/// The maximum size of a kernel-side copy call.
+#[cfg(target_os = "linux")]
+const KERNEL_COPY_SIZE: usize = 1 << 30;
+
+/// The maximum size of a kernel-side copy call when the progress is followed, so it is
+/// reported often enough.
+#[cfg(target_os = "linux")]
+const PROGRESS_COPY_SIZE: usize = 8 << 20;
+
+/// Copy all the data from `input` to `output` until `input` reaches EOF, returning the
+/// number of bytes copied.
+///
+/// The data is copied from and to the current offsets of the file descriptors.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned. An
+/// [`InvalidInput`](io::ErrorKind::InvalidInput) error is returned if `input` and
+/// `output` are the same regular file with data left to read, that would be copied after
+/// itself without end.
+#[cfg_attr(feature = "inline-more", inline)]
+pub fn copy(input: RawFd, output: RawFd) -> io::Result<u64> {
+    #[cfg(target_os = "linux")]
+    let size = KERNEL_COPY_SIZE;
+    #[cfg(not(target_os = "linux"))]
+    let size = 0;
+
+    copy_parts(input, output, size, &mut |_| {})
+}
+
+/// Copy all the data from `input` to `output` like [`copy`], calling `progress` with the
+/// number of bytes copied so far after each part of it.
+///
+/// # Errors
+/// The same as [`copy`].
+#[cfg_attr(feature = "inline-more", inline)]
+pub fn copy_with_progress(
+    input: RawFd, output: RawFd, progress: &mut dyn FnMut(u64),
+) -> io::Result<u64> {
+    #[cfg(target_os = "linux")]
+    let size = PROGRESS_COPY_SIZE;
+    #[cfg(not(target_os = "linux"))]
+    let size = 0;
+
+    copy_parts(input, output, size, progress)
+}
+
+/// Copy the data with kernel-side calls of up to `size` bytes if the system supports
+/// them, or with the read/write loop.
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn copy_parts(
+    input: RawFd, output: RawFd, size: usize, progress: &mut dyn FnMut(u64),
+) -> io::Result<u64> {
+    check_same_file(input, output)?;
+
+    let mut copied = 0;
+
+    #[cfg(target_os = "linux")]
+    {
+        for kernel_copy in &[linux::copy_file_range, linux::sendfile, linux::splice] {
+            match kernel_copy(input, output, size, &mut copied, progress)? {
+                KernelCopy::Done => return Ok(copied),
+                KernelCopy::Unsupported => continue,
+            }
+        }
+    }
+
+    copy_buffered(input, output, &mut copied, progress)?;
+
+    Ok(copied)
+}
+
+/// Returns a error if `input` is the regular file of `output` and isn't read to its end.
+fn check_same_file(input: RawFd, output: RawFd) -> io::Result<()> {
+    let (input_stat, output_stat) = (fstat(input)?, fstat(output)?);
+
+    let same = output_stat.st_mode & libc::S_IFMT == libc::S_IFREG
+        && input_stat.st_dev == output_stat.st_dev
+        && input_stat.st_ino == output_stat.st_ino;
+
+    if same && unsafe { libc::lseek(input, 0, libc::SEEK_CUR) } < input_stat.st_size {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "input file is output file"));
+    }
+
+    Ok(())
+}
+
+/// Copy all the data from `input` to `output` like [`copy`], but leaving holes in
+/// `output` where `input` has them, found with `SEEK_DATA` and `SEEK_HOLE` on the systems
+/// that have them, and also where it has blocks of zeros if `zeros`. Returns the size of
+/// `input`, and calls `progress` with the offset reached after each part of the data.
+///
+/// The data is copied from the start of `input`, and `output` must be a empty regular
+/// file: the holes are left by seeking over them, and its size is set at the end. If
+/// `input` isn't a regular file, like a pipe, it is read until its end and only the
+/// blocks of zeros are left as holes.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+pub fn copy_sparse(
+    input: RawFd, output: RawFd, zeros: bool, progress: &mut dyn FnMut(u64),
+) -> io::Result<u64> {
+    let input_stat = fstat(input)?;
+    // The holes can't be smaller than the blocks of the file system
+    let block = if zeros { fstat(output)?.st_blksize.max(512) as usize } else { 0 };
+
+    if input_stat.st_mode & libc::S_IFMT != libc::S_IFREG {
+        let copied = copy_extent(input, output, 0, u64::MAX, block, progress)?;
+
+        if unsafe { libc::ftruncate(output, copied as libc::off_t) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        return Ok(copied);
+    }
+
+    let size = input_stat.st_size;
+
+    let mut offset = 0;
+    while offset < size {
+        let (start, end) = match data_extent(input, offset, size)? {
+            Some(extent) => extent,
+            None => break,
+        };
+
+        seek(input, start)?;
+        seek(output, start)?;
+        copy_extent(input, output, start as u64, (end - start) as u64, block, progress)?;
+
+        offset = end;
+    }
+
+    // The holes at the end
+    if unsafe { libc::ftruncate(output, size) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    progress(size as u64);
+
+    Ok(size as u64)
+}
+
+/// Get the start and end of the first range of data of `input` from `offset`, or
+/// [`None`] if there is only a hole until the end.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "solaris",
+    target_os = "illumos"
+))]
+fn data_extent(
+    input: RawFd, offset: libc::off_t, size: libc::off_t,
+) -> io::Result<Option<(libc::off_t, libc::off_t)>> {
+    let start = unsafe { libc::lseek(input, offset, libc::SEEK_DATA) };
+    if start < 0 {
+        let err = io::Error::last_os_error();
+
+        return match err.raw_os_error() {
+            // There is no data after the offset
+            Some(libc::ENXIO) => Ok(None),
+            // The file system doesn't report the holes
+            Some(libc::EINVAL) | Some(libc::ENOTSUP) => Ok(Some((offset, size))),
+            _ => Err(err),
+        };
+    }
+
+    let end = unsafe { libc::lseek(input, start, libc::SEEK_HOLE) };
+    if end < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(Some((start, end.min(size))))
+}
+
+/// The holes can't be found on this system, so the whole file is data.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "solaris",
+    target_os = "illumos"
+)))]
+fn data_extent(
+    _input: RawFd, offset: libc::off_t, size: libc::off_t,
+) -> io::Result<Option<(libc::off_t, libc::off_t)>> {
+    Ok(Some((offset, size)))
+}
+
+/// Copy `len` bytes from `input` to `output` from the `start` offset, or less if it ends
+/// before, seeking over the blocks of `block` bytes that are only zeros instead of
+/// writing them if `block` is not 0. Returns the number of bytes copied.
+fn copy_extent(
+    input: RawFd, output: RawFd, start: u64, len: u64, block: usize, progress: &mut dyn FnMut(u64),
+) -> io::Result<u64> {
+    let mut copied = 0;
+    let mut buff = vec![0u8; COPY_BUFFER_SIZE];
+
+    while copied < len {
+        let max = (buff.len() as u64).min(len - copied) as usize;
+        let read = unsafe { libc::read(input, buff.as_mut_ptr() as *mut libc::c_void, max) };
+
+        match read {
+            // The file was truncated while being copied, or the pipe ended
+            0 => return Ok(copied),
+            -1 => {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(err);
+            },
+            _ => {},
+        }
+
+        let data = &buff[..read as usize];
+
+        if block == 0 {
+            write_all(output, data)?;
+        } else {
+            for chunk in data.chunks(block) {
+                if chunk.iter().all(|&byte| byte == 0) {
+                    if unsafe { libc::lseek(output, chunk.len() as _, libc::SEEK_CUR) } < 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                } else {
+                    write_all(output, chunk)?;
+                }
+            }
+        }
+
+        copied += read as u64;
+        progress(start + copied);
+    }
+
+    Ok(copied)
+}
+
+/// Get the status of the file of `fd`.
+fn fstat(fd: RawFd) -> io::Result<libc::stat> {
+    let mut stat = MaybeUninit::uninit();
+
+    match unsafe { libc::fstat(fd, stat.as_mut_ptr()) } {
+        0 => Ok(unsafe { stat.assume_init() }),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Set the offset of `fd` to `offset`.
+fn seek(fd: RawFd, offset: libc::off_t) -> io::Result<()> {
+    match unsafe { libc::lseek(fd, offset, libc::SEEK_SET) } {
+        -1 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+/// Make `output` share the data of `input` with the `FICLONE` `ioctl(2)`, so it is not
+/// copied until one of them is modified. It is supported by the file systems with
+/// copy-on-write, like Btrfs and XFS, when both files are on the same one.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg_attr(feature = "inline-more", inline)]
+pub fn reflink(input: RawFd, output: RawFd) -> io::Result<()> {
+    // `_IOW(0x94, 9, int)`, defined here as older versions of libc lack it
+    const FICLONE: libc::c_ulong = 0x4004_9409;
+
+    match unsafe { libc::ioctl(output, FICLONE as _, input) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Create `dest` as a clone of the file in `source` with `clonefile(2)`, sharing its data
+/// until one of them is modified. It is supported by APFS, `dest` must not exist and
+/// gets the permissions and times of `source`.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[cfg(target_os = "macos")]
+#[cfg_attr(feature = "inline-more", inline)]
+pub fn clone_file(source: impl AsRef<Path>, dest: impl AsRef<Path>) -> io::Result<()> {
+    let source = CString::new(source.as_ref().as_os_str().as_bytes())?;
+    let dest = CString::new(dest.as_ref().as_os_str().as_bytes())?;
+
+    match unsafe { libc::clonefile(source.as_ptr(), dest.as_ptr(), 0) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Result of a kernel-side copy attempt.
+#[cfg(target_os = "linux")]
+enum KernelCopy {
+    /// The input reached EOF.
+    Done,
+    /// The system call can't be used with the file descriptors, the next method should be
+    /// tried.
+    Unsupported,
+}
+
+/// Copy using a read/write loop, adding the number of bytes copied to `copied`.
+fn copy_buffered(
+    input: RawFd, output: RawFd, copied: &mut u64, progress: &mut dyn FnMut(u64),
+) -> io::Result<()> {
+    let mut buff = vec![0u8; COPY_BUFFER_SIZE];
+
+    loop {
+        let read = unsafe { libc::read(input, buff.as_mut_ptr() as *mut libc::c_void, buff.len()) };
+
+        match read {
+            0 => return Ok(()),
+            -1 => {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(err);
+            },
+            _ => {},
+        }
+
+        write_all(output, &buff[..read as usize])?;
+        *copied += read as u64;
+        progress(*copied);
+    }
+}
+
+/// Write all `buff` on `output`.
+fn write_all(output: RawFd, mut buff: &[u8]) -> io::Result<()> {
+    while !buff.is_empty() {
+        let written =
+            unsafe { libc::write(output, buff.as_ptr() as *const libc::c_void, buff.len()) };
+
+        match written {
+            -1 => {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(err);
+            },
+            0 => return Err(io::ErrorKind::WriteZero.into()),
+            _ => buff = &buff[written as usize..],
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{io, os::unix::io::RawFd, ptr};
+
+    use libc::{c_int, ssize_t, EINTR, EINVAL, ENOSYS, EOPNOTSUPP, EXDEV, F_GETFL, O_APPEND};
+
+    use super::KernelCopy;
+
+    /// Calls `syscall` until it returns 0 (EOF). If it fails with a error that tells that
+    /// the file descriptors are not supported, [`KernelCopy::Unsupported`] is returned so
+    /// the next copy method can be tried from the current offsets.
+    fn kernel_loop<F>(
+        copied: &mut u64, progress: &mut dyn FnMut(u64), mut syscall: F,
+    ) -> io::Result<KernelCopy>
+    where
+        F: FnMut() -> ssize_t,
+    {
+        loop {
+            match syscall() {
+                0 => return Ok(KernelCopy::Done),
+                -1 => {
+                    let err = io::Error::last_os_error();
+                    let errno = err.raw_os_error().unwrap_or(0);
+
+                    if errno == EINTR {
+                        continue;
+                    }
+
+                    if is_unsupported(errno) {
+                        return Ok(KernelCopy::Unsupported);
+                    }
+
+                    return Err(err);
+                },
+                n => {
+                    *copied += n as u64;
+                    progress(*copied);
+                },
+            }
+        }
+    }
+
+    fn is_unsupported(errno: c_int) -> bool {
+        errno == EINVAL || errno == ENOSYS || errno == EXDEV || errno == EOPNOTSUPP
+    }
+
+    pub(super) fn copy_file_range(
+        input: RawFd, output: RawFd, size: usize, copied: &mut u64, progress: &mut dyn FnMut(u64),
+    ) -> io::Result<KernelCopy> {
+        // It fails with `EBADF` on the files opened for appending, that the other methods
+        // support
+        if unsafe { libc::fcntl(output, F_GETFL) } & O_APPEND != 0 {
+            return Ok(KernelCopy::Unsupported);
+        }
+
+        kernel_loop(copied, progress, || unsafe {
+            libc::copy_file_range(input, ptr::null_mut(), output, ptr::null_mut(), size, 0)
+        })
+    }
+
+    pub(super) fn sendfile(
+        input: RawFd, output: RawFd, size: usize, copied: &mut u64, progress: &mut dyn FnMut(u64),
+    ) -> io::Result<KernelCopy> {
+        kernel_loop(copied, progress, || unsafe {
+            libc::sendfile(output, input, ptr::null_mut(), size)
+        })
+    }
+
+    pub(super) fn splice(
+        input: RawFd, output: RawFd, size: usize, copied: &mut u64, progress: &mut dyn FnMut(u64),
+    ) -> io::Result<KernelCopy> {
+        kernel_loop(copied, progress, || unsafe {
+            libc::splice(input, ptr::null_mut(), output, ptr::null_mut(), size, libc::SPLICE_F_MOVE)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env,
+        fs::{self, File, OpenOptions},
+        io::{Read, Seek, SeekFrom, Write},
+        os::unix::{
+            fs::MetadataExt,
+            io::{AsRawFd, FromRawFd},
+        },
+        path::PathBuf,
+    };
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("core.copy.{}.{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn copy_files() {
+        let dir = temp_dir("files");
+        let content = data(3 * COPY_BUFFER_SIZE + 7);
+        fs::write(dir.join("input"), &content).unwrap();
+
+        let input = File::open(dir.join("input")).unwrap();
+        let output = File::create(dir.join("output")).unwrap();
+        assert_eq!(copy(input.as_raw_fd(), output.as_raw_fd()).unwrap(), content.len() as u64);
+        assert_eq!(fs::read(dir.join("output")).unwrap(), content);
+
+        // From the current offsets, and appending
+        let mut input = File::open(dir.join("input")).unwrap();
+        input.seek(SeekFrom::Start(10)).unwrap();
+        let output = OpenOptions::new().append(true).open(dir.join("output")).unwrap();
+        let mut reports = Vec::new();
+        let copied =
+            copy_with_progress(input.as_raw_fd(), output.as_raw_fd(), &mut |n| reports.push(n))
+                .unwrap();
+
+        assert_eq!(copied, content.len() as u64 - 10);
+        assert_eq!(reports.last(), Some(&copied));
+        assert_eq!(fs::read(dir.join("output")).unwrap(), [&content[..], &content[10..]].concat());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn copy_pipe() {
+        let dir = temp_dir("pipe");
+        let content = data(1000);
+
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        let input = unsafe { File::from_raw_fd(fds[0]) };
+        let mut writer = unsafe { File::from_raw_fd(fds[1]) };
+        writer.write_all(&content).unwrap();
+        drop(writer);
+
+        let output = File::create(dir.join("output")).unwrap();
+        assert_eq!(copy(input.as_raw_fd(), output.as_raw_fd()).unwrap(), 1000);
+        assert_eq!(fs::read(dir.join("output")).unwrap(), content);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn copy_same_file() {
+        let dir = temp_dir("same");
+        let path = dir.join("file");
+        fs::write(&path, "data\n").unwrap();
+
+        let mut input = File::open(&path).unwrap();
+        let output = OpenOptions::new().append(true).open(&path).unwrap();
+        let err = copy(input.as_raw_fd(), output.as_raw_fd()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(err.to_string(), "input file is output file");
+        assert_eq!(fs::read(&path).unwrap(), b"data\n");
+
+        // Nothing is left to read
+        input.read_to_end(&mut Vec::new()).unwrap();
+        assert_eq!(copy(input.as_raw_fd(), output.as_raw_fd()).unwrap(), 0);
+
+        // An empty file
+        let empty = File::create(dir.join("empty")).unwrap();
+        let input = File::open(dir.join("empty")).unwrap();
+        assert_eq!(copy(input.as_raw_fd(), empty.as_raw_fd()).unwrap(), 0);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn copy_holes() {
+        let dir = temp_dir("holes");
+        let len = 4 << 20;
+        let mut file = File::create(dir.join("input")).unwrap();
+        file.set_len(len).unwrap();
+        file.seek(SeekFrom::Start(1 << 20)).unwrap();
+        file.write_all(&data(5000)).unwrap();
+        drop(file);
+        let content = fs::read(dir.join("input")).unwrap();
+
+        for &zeros in &[false, true] {
+            let input = File::open(dir.join("input")).unwrap();
+            let output = File::create(dir.join("output")).unwrap();
+            let mut reached = 0;
+            let copied =
+                copy_sparse(input.as_raw_fd(), output.as_raw_fd(), zeros, &mut |n| reached = n)
+                    .unwrap();
+
+            assert_eq!(copied, len);
+            assert_eq!(reached, len);
+            assert_eq!(fs::read(dir.join("output")).unwrap(), content);
+            // Only the blocks of the data are written
+            assert!(output.metadata().unwrap().blocks() * 512 < len / 2);
+        }
+
+        // The zeros written in the input are left as holes
+        fs::write(dir.join("input"), [vec![0; 1 << 20], data(10), vec![0; 1 << 20]].concat())
+            .unwrap();
+        let input = File::open(dir.join("input")).unwrap();
+        let output = File::create(dir.join("output")).unwrap();
+        copy_sparse(input.as_raw_fd(), output.as_raw_fd(), true, &mut |_| {}).unwrap();
+
+        assert_eq!(fs::read(dir.join("output")).unwrap(), fs::read(dir.join("input")).unwrap());
+        assert!(output.metadata().unwrap().blocks() * 512 < 1 << 20);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn copy_holes_from_pipe() {
+        let dir = temp_dir("pipe_holes");
+        let content = [vec![0; 1 << 20], data(10), vec![0; 1 << 20]].concat();
+
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        let input = unsafe { File::from_raw_fd(fds[0]) };
+        let mut writer = unsafe { File::from_raw_fd(fds[1]) };
+        let written = content.clone();
+        let writing = std::thread::spawn(move || writer.write_all(&written).unwrap());
+
+        let output = File::create(dir.join("output")).unwrap();
+        let copied = copy_sparse(input.as_raw_fd(), output.as_raw_fd(), true, &mut |_| {});
+        writing.join().unwrap();
+
+        // The zeros read are left as holes, the ones at the end too
+        assert_eq!(copied.unwrap(), content.len() as u64);
+        assert_eq!(fs::read(dir.join("output")).unwrap(), content);
+        assert!(output.metadata().unwrap().blocks() * 512 < 1 << 20);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,8 @@
+//! Module for the date and time abstractions of the utilities that take dates, like the
+//! free-form dates of `date --date` and `touch --date`.
+pub mod calendar;
+pub mod parse;
+pub mod zone;
+
+pub use parse::{parse_date, Timestamp};
+pub use zone::Zone;
@@ -0,0 +1,182 @@
+//! The arithmetic of the proleptic Gregorian calendar, on the seconds since the Epoch.
+use std::fmt;
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+pub const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// The names of the days of the week, starting on Sunday.
+pub const WEEKDAY_NAMES: [&str; 7] =
+    ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+
+/// The broken down date and time of a number of seconds since the Epoch, without any
+/// time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fields {
+    pub year: i64,
+    pub month: i64,
+    pub day: i64,
+    pub hour: i64,
+    pub minute: i64,
+    pub second: i64,
+}
+
+impl Fields {
+    pub fn from_seconds(seconds: i64) -> Self {
+        let days = seconds.div_euclid(SECONDS_PER_DAY);
+        let time = seconds.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+
+        Fields { year, month, day, hour: time / 3600, minute: time / 60 % 60, second: time % 60 }
+    }
+
+    /// The seconds since the Epoch, where the fields out of their ranges carry to the
+    /// next ones, like the 32nd of January is the 1st of February.
+    pub fn to_seconds(self) -> i64 {
+        // Normalize the month first, so the days of the month can be added
+        let months = self.year * 12 + self.month - 1;
+        let (year, month) = (months.div_euclid(12), months.rem_euclid(12) + 1);
+        let days = days_from_civil(year, month, 1) + self.day - 1;
+
+        days * SECONDS_PER_DAY + self.hour * 3600 + self.minute * 60 + self.second
+    }
+
+    /// The fields with the fields out of their ranges carried to the next ones.
+    pub fn normalized(self) -> Self {
+        Fields::from_seconds(self.to_seconds())
+    }
+
+    /// The day of the week, with 0 as Sunday.
+    pub fn weekday(self) -> i64 {
+        (days_from_civil(self.year, self.month, self.day) + 4).rem_euclid(7)
+    }
+
+    /// The day of the year, with 0 as the 1st of January.
+    pub fn year_day(self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) - days_from_civil(self.year, 1, 1)
+    }
+
+    /// The ISO 8601 week-based year and week number, where the weeks start on Monday and
+    /// the first week of the year is the one with its Thursday.
+    pub fn iso_week(self) -> (i64, i64) {
+        let monday_weekday = (self.weekday() + 6) % 7;
+        let week = (self.year_day() - monday_weekday + 10) / 7;
+
+        if week < 1 {
+            (self.year - 1, iso_weeks_in_year(self.year - 1))
+        } else if week > iso_weeks_in_year(self.year) {
+            (self.year + 1, 1)
+        } else {
+            (self.year, week)
+        }
+    }
+}
+
+impl fmt::Display for Fields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "(Y-M-D) {:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+pub fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// The number of ISO 8601 weeks of `year`, that has 53 if it starts or ends on Thursday.
+pub fn iso_weeks_in_year(year: i64) -> i64 {
+    let december_31 = |year: i64| (days_from_civil(year, 12, 31) + 4).rem_euclid(7);
+    if december_31(year) == 4 || december_31(year - 1) == 3 {
+        53
+    } else {
+        52
+    }
+}
+
+pub fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The days since the Epoch of a date, from Howard Hinnant's algorithms.
+pub fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The year, month and day of the days since the Epoch.
+pub fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+
+    (year_of_era + era * 400 + (month <= 2) as i64, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_days() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        for &days in &[-800_000, -1, 0, 59, 11016, 11017, 20_000, 2_932_896] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn fields() {
+        let fields = Fields::from_seconds(1_600_000_000);
+        assert_eq!(fields, Fields {
+            year: 2020,
+            month: 9,
+            day: 13,
+            hour: 12,
+            minute: 26,
+            second: 40
+        });
+        assert_eq!(fields.to_seconds(), 1_600_000_000);
+        assert_eq!(fields.weekday(), 0);
+        assert_eq!(Fields::from_seconds(-1).to_string(), "(Y-M-D) 1969-12-31 23:59:59");
+
+        let overflowed = Fields { year: 2026, month: 2, day: 31, hour: 0, minute: 0, second: 0 };
+        assert_eq!((overflowed.normalized().month, overflowed.normalized().day), (3, 3));
+        let overflowed = Fields { month: 14, ..overflowed };
+        assert_eq!((overflowed.normalized().year, overflowed.normalized().month), (2027, 3));
+    }
+}
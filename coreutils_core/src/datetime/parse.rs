@@ -0,0 +1,1400 @@
+//! The parser of the free-form date strings of `--date`, like `2 weeks ago`, `next
+//! monday`, `@1600000000` or `2020-01-02T03:04:05+05:00`, with the syntax of the GNU
+//! implementation.
+//!
+//! The string is a sequence of items, each one setting a part of the date: a calendar
+//! date, a time of the day, a time zone, a day of the week or a relative amount of time.
+//! The parts that are not set are taken from the current date and time.
+use super::{
+    calendar::{days_in_month, Fields, MONTH_NAMES, WEEKDAY_NAMES},
+    zone::{format_offset, Zone},
+};
+
+/// A instant, as the seconds and nanoseconds since the Epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamp {
+    pub seconds: i64,
+    pub nanos: u32,
+}
+
+/// The years that can be displayed.
+const YEARS: std::ops::RangeInclusive<i64> = -99_999..=99_999;
+
+/// Parse `input` relative to `now`, where the dates without a time zone are in `zone`.
+///
+/// If `debug`, how the date was parsed and the possible mistakes are written to the
+/// standard error.
+pub fn parse_date(
+    input: &str, now: Timestamp, zone: Zone, debug: bool,
+) -> Result<Timestamp, String> {
+    let invalid = || format!("invalid date '{}'", input);
+
+    let tokens = match tokenize(input) {
+        Ok(tokens) => tokens,
+        Err(word) => {
+            if debug {
+                eprintln!("date: error: unknown word '{}'", word);
+                eprintln!("date: error: parsing failed");
+            }
+            return Err(invalid());
+        },
+    };
+
+    let mut parser = Parser { tokens, pos: 0, debug, now, zone, items: Items::default() };
+    if parser.parse().is_err() {
+        parser.debug("error: parsing failed");
+        return Err(invalid());
+    }
+
+    parser.resolve().ok_or_else(invalid)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    /// A integer, `signed` if it has a explicit sign
+    Number {
+        value: i64,
+        digits: usize,
+        signed: bool,
+    },
+    /// A number with a fraction, as the seconds rounded down and the nanoseconds
+    Decimal {
+        seconds: i64,
+        nanos: u32,
+        signed: bool,
+    },
+    Meridian(Meridian),
+    Month(i64),
+    Weekday(i64),
+    Ordinal(i64),
+    Unit(Unit),
+    Ago,
+    DayShift(i64),
+    /// A time zone, with its offset in seconds
+    Zone(i64),
+    Dst,
+    /// The `T` between the date and the time, or the military time zone
+    T,
+    Char(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Meridian {
+    Am,
+    Pm,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Year,
+    Month,
+    Fortnight,
+    Week,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+const ORDINALS: [(&str, i64); 14] = [
+    ("LAST", -1),
+    ("THIS", 0),
+    ("NEXT", 1),
+    ("FIRST", 1),
+    ("THIRD", 3),
+    ("FOURTH", 4),
+    ("FIFTH", 5),
+    ("SIXTH", 6),
+    ("SEVENTH", 7),
+    ("EIGHTH", 8),
+    ("NINTH", 9),
+    ("TENTH", 10),
+    ("ELEVENTH", 11),
+    ("TWELFTH", 12),
+];
+
+/// The time zone abbreviations, with their offsets in minutes and whether they are
+/// daylight saving time.
+const ZONES: [(&str, i64); 51] = [
+    ("GMT", 0),
+    ("UT", 0),
+    ("UTC", 0),
+    ("WET", 0),
+    ("WEST", 60),
+    ("BST", 60),
+    ("ART", -180),
+    ("BRT", -180),
+    ("BRST", -120),
+    ("NST", -210),
+    ("NDT", -150),
+    ("AST", -240),
+    ("ADT", -180),
+    ("CLT", -240),
+    ("CLST", -180),
+    ("EST", -300),
+    ("EDT", -240),
+    ("CST", -360),
+    ("CDT", -300),
+    ("MST", -420),
+    ("MDT", -360),
+    ("PST", -480),
+    ("PDT", -420),
+    ("AKST", -540),
+    ("AKDT", -480),
+    ("HST", -600),
+    ("HAST", -600),
+    ("HADT", -540),
+    ("SST", -720),
+    ("WAT", 60),
+    ("CET", 60),
+    ("CEST", 120),
+    ("MET", 60),
+    ("MEZ", 60),
+    ("MEST", 120),
+    ("MESZ", 120),
+    ("EET", 120),
+    ("EEST", 180),
+    ("CAT", 120),
+    ("SAST", 120),
+    ("EAT", 180),
+    ("MSK", 180),
+    ("MSD", 240),
+    ("IST", 330),
+    ("SGT", 480),
+    ("KST", 540),
+    ("JST", 540),
+    ("GST", 600),
+    ("NZST", 720),
+    ("NZDT", 780),
+    ("Z", 0),
+];
+
+/// Split `input` in tokens, or return the unknown word that failed.
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+
+        if c.is_ascii_whitespace() {
+            i += 1;
+        } else if c == b'(' {
+            // Comments, that may be nested
+            let mut depth = 0;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'(' => depth += 1,
+                    b')' => depth -= 1,
+                    _ => {},
+                }
+                i += 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+        } else if c.is_ascii_digit() || c == b'+' || c == b'-' {
+            let signed = !c.is_ascii_digit();
+            let negative = c == b'-';
+            if signed {
+                i += 1;
+                while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                // A sign that is not of a number is ignored
+                if i == bytes.len() || !bytes[i].is_ascii_digit() {
+                    continue;
+                }
+            }
+
+            let start = i;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            let digits = i - start;
+            let value: i64 = input[start..i].parse().map_err(|_| input[start..i].to_string())?;
+
+            let is_fraction = |i: usize| {
+                i + 1 < bytes.len()
+                    && (bytes[i] == b'.' || bytes[i] == b',')
+                    && bytes[i + 1].is_ascii_digit()
+            };
+            if is_fraction(i) {
+                i += 1;
+                let fraction_start = i;
+                while i < bytes.len() && bytes[i].is_ascii_digit() {
+                    i += 1;
+                }
+                // Only the nanoseconds are kept
+                let fraction = &input[fraction_start..i.min(fraction_start + 9)];
+                let mut nanos: u32 = fraction.parse().unwrap_or(0);
+                for _ in fraction.len()..9 {
+                    nanos *= 10;
+                }
+
+                let (seconds, nanos) = match (negative, nanos) {
+                    (false, _) => (value, nanos),
+                    (true, 0) => (-value, 0),
+                    (true, _) => (-value - 1, 1_000_000_000 - nanos),
+                };
+                tokens.push(Token::Decimal { seconds, nanos, signed });
+            } else {
+                let value = if negative { -value } else { value };
+                tokens.push(Token::Number { value, digits, signed });
+            }
+        } else if c.is_ascii_alphabetic() {
+            let start = i;
+            while i < bytes.len() && (bytes[i].is_ascii_alphabetic() || bytes[i] == b'.') {
+                i += 1;
+            }
+            let word = input[start..i].to_ascii_uppercase();
+            tokens.push(lookup_word(&word).ok_or(word)?);
+        } else {
+            tokens.push(Token::Char(c));
+            // Keep the index at a character boundary
+            i += input[i..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn lookup_word(word: &str) -> Option<Token> {
+    match word {
+        "AM" | "A.M." => return Some(Token::Meridian(Meridian::Am)),
+        "PM" | "P.M." => return Some(Token::Meridian(Meridian::Pm)),
+        "DST" => return Some(Token::Dst),
+        "AGO" => return Some(Token::Ago),
+        "TOMORROW" => return Some(Token::DayShift(1)),
+        "YESTERDAY" => return Some(Token::DayShift(-1)),
+        "TODAY" | "NOW" => return Some(Token::DayShift(0)),
+        "T" => return Some(Token::T),
+        _ => {},
+    }
+
+    // The names of the months and days, that may be abbreviated to 3 letters with an
+    // optional dot
+    let abbreviation = match word.len() {
+        3 => Some(word),
+        4 if word.ends_with('.') => Some(&word[..3]),
+        _ => None,
+    };
+    let matches = |name: &str| {
+        name.eq_ignore_ascii_case(word)
+            || matches!(abbreviation, Some(a) if name[..3].eq_ignore_ascii_case(a))
+    };
+
+    if let Some(month) = MONTH_NAMES.iter().position(|&name| matches(name)) {
+        return Some(Token::Month(month as i64 + 1));
+    }
+    if word == "SEPT" {
+        return Some(Token::Month(9));
+    }
+    if let Some(day) = WEEKDAY_NAMES.iter().position(|&name| matches(name)) {
+        return Some(Token::Weekday(day as i64));
+    }
+    match word {
+        "TUES" => return Some(Token::Weekday(2)),
+        "WEDNES" => return Some(Token::Weekday(3)),
+        "THUR" | "THURS" => return Some(Token::Weekday(4)),
+        _ => {},
+    }
+
+    if let Some(unit) = lookup_unit(word).or_else(|| lookup_unit(word.strip_suffix('S')?)) {
+        return Some(Token::Unit(unit));
+    }
+    if let Some(&(_, ordinal)) = ORDINALS.iter().find(|(name, _)| *name == word) {
+        return Some(Token::Ordinal(ordinal));
+    }
+
+    // The time zones may have dots, like U.T.C.
+    let zone = word.replace('.', "");
+    if let Some(&(_, minutes)) = ZONES.iter().find(|(name, _)| *name == zone) {
+        return Some(Token::Zone(minutes * 60));
+    }
+
+    // The military time zones, of a letter besides J
+    match *zone.as_bytes() {
+        [letter @ b'A'..=b'I'] => Some(Token::Zone(i64::from(letter - b'A' + 1) * 3600)),
+        [letter @ b'K'..=b'M'] => Some(Token::Zone(i64::from(letter - b'A') * 3600)),
+        [letter @ b'N'..=b'Y'] => Some(Token::Zone(-i64::from(letter - b'N' + 1) * 3600)),
+        _ => None,
+    }
+}
+
+fn lookup_unit(word: &str) -> Option<Unit> {
+    match word {
+        "YEAR" => Some(Unit::Year),
+        "MONTH" => Some(Unit::Month),
+        "FORTNIGHT" => Some(Unit::Fortnight),
+        "WEEK" => Some(Unit::Week),
+        "DAY" => Some(Unit::Day),
+        "HOUR" => Some(Unit::Hour),
+        "MINUTE" | "MIN" => Some(Unit::Minute),
+        "SECOND" | "SEC" => Some(Unit::Second),
+        _ => None,
+    }
+}
+
+/// A year, with the number of its digits, since the years of 2 digits are in the 20th or
+/// 21st centuries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Year {
+    value: i64,
+    digits: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Date {
+    year: Option<Year>,
+    month: i64,
+    day: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Time {
+    hour: i64,
+    minute: i64,
+    second: i64,
+    nanos: u32,
+    meridian: Option<Meridian>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Weekday {
+    /// The `ordinal` occurrence of the day, like `next` or `last`
+    ordinal: i64,
+    number: i64,
+    has_ordinal: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Relative {
+    years: i64,
+    months: i64,
+    days: i64,
+    hours: i64,
+    minutes: i64,
+    seconds: i64,
+    nanos: i64,
+}
+
+impl Relative {
+    /// Add `count` of `unit`, failing on overflow.
+    fn add(&mut self, unit: Unit, count: i64) -> Option<()> {
+        let (field, count) = match unit {
+            Unit::Year => (&mut self.years, count),
+            Unit::Month => (&mut self.months, count),
+            Unit::Fortnight => (&mut self.days, count.checked_mul(14)?),
+            Unit::Week => (&mut self.days, count.checked_mul(7)?),
+            Unit::Day => (&mut self.days, count),
+            Unit::Hour => (&mut self.hours, count),
+            Unit::Minute => (&mut self.minutes, count),
+            Unit::Second => (&mut self.seconds, count),
+        };
+        *field = field.checked_add(count)?;
+        Some(())
+    }
+
+    fn has_date(&self) -> bool {
+        self.years != 0 || self.months != 0 || self.days != 0
+    }
+
+    fn has_time(&self) -> bool {
+        self.hours != 0 || self.minutes != 0 || self.seconds != 0 || self.nanos != 0
+    }
+
+    /// Describe the amounts that are not zero, like ` +1 day(s) -2 hour(s)`.
+    fn describe(&self) -> String {
+        let amounts = [
+            (self.years, " year(s)"),
+            (self.months, " month(s)"),
+            (self.days, " day(s)"),
+            (self.hours, " hour(s)"),
+            (self.minutes, " minutes"),
+            (self.seconds, " seconds"),
+            (self.nanos, " nanoseconds"),
+        ];
+
+        let description: String = amounts
+            .iter()
+            .filter(|(amount, _)| *amount != 0)
+            .map(|(amount, name)| format!(" {:+}{}", amount, name))
+            .collect();
+        if description.is_empty() {
+            " today/this/now".to_string()
+        } else {
+            description
+        }
+    }
+}
+
+/// The parts of the date set by the items.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Items {
+    timestamp: Option<Timestamp>,
+    date: Option<Date>,
+    time: Option<Time>,
+    /// The offset of the time zone, in seconds
+    zone: Option<i64>,
+    weekday: Option<Weekday>,
+    relative: Relative,
+    has_relative: bool,
+    /// The number of times each part was set, since a repeated part is an error
+    times_seen: usize,
+    dates_seen: usize,
+    days_seen: usize,
+    zones_seen: usize,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    debug: bool,
+    now: Timestamp,
+    zone: Zone,
+    items: Items,
+}
+
+/// The error of a string that doesn't follow the syntax.
+struct SyntaxError;
+
+type ParseResult = Result<(), SyntaxError>;
+
+impl Parser {
+    fn debug(&self, message: impl std::fmt::Display) {
+        if self.debug {
+            eprintln!("date: {}", message);
+        }
+    }
+
+    fn peek(&self, pos: usize) -> Option<Token> {
+        self.tokens.get(self.pos + pos).copied()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek(0);
+        self.pos += 1;
+        token
+    }
+
+    /// Advance if the next token is `token`.
+    fn next_is(&mut self, token: Token) -> bool {
+        if self.peek(0) == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The next token if it's a unsigned number, as the value and the digits.
+    fn next_unsigned(&mut self) -> Option<(i64, usize)> {
+        match self.peek(0) {
+            Some(Token::Number { value, digits, signed: false }) => {
+                self.pos += 1;
+                Some((value, digits))
+            },
+            _ => None,
+        }
+    }
+
+    /// The next token if it's a signed number, as the value and the digits.
+    fn next_signed(&mut self) -> Option<(i64, usize)> {
+        match self.peek(0) {
+            Some(Token::Number { value, digits, signed: true }) => {
+                self.pos += 1;
+                Some((value, digits))
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether the token at `pos` is a signed number not followed by a unit, like the
+    /// offsets of the time zones.
+    fn is_offset(&self, pos: usize) -> bool {
+        matches!(self.peek(pos), Some(Token::Number { signed: true, .. }))
+            && !matches!(self.peek(pos + 1), Some(Token::Unit(_)))
+    }
+
+    fn parse(&mut self) -> ParseResult {
+        // A timestamp is the whole string
+        if self.next_is(Token::Char(b'@')) {
+            let (seconds, nanos) = match self.next() {
+                Some(Token::Number { value, .. }) => (value, 0),
+                Some(Token::Decimal { seconds, nanos, .. }) => (seconds, nanos),
+                _ => return Err(SyntaxError),
+            };
+            self.debug(format_args!(
+                "parsed number of seconds part: number of seconds: {}",
+                seconds
+            ));
+            if self.peek(0).is_some() {
+                return Err(SyntaxError);
+            }
+
+            self.items.timestamp = Some(Timestamp { seconds, nanos });
+            return Ok(());
+        }
+
+        while let Some(token) = self.next() {
+            match token {
+                Token::Number { value, digits, signed: false } => self.number(value, digits)?,
+                Token::Number { value, signed: true, .. } => self.relative(value)?,
+                Token::Decimal { seconds, nanos, .. } => {
+                    if !self.next_is(Token::Unit(Unit::Second)) {
+                        return Err(SyntaxError);
+                    }
+                    let negate = self.next_is(Token::Ago);
+                    let mut relative =
+                        Relative { seconds, nanos: i64::from(nanos), ..Relative::default() };
+                    if negate {
+                        relative.seconds = -relative.seconds;
+                        relative.nanos = -relative.nanos;
+                    }
+                    self.add_relative(relative, "relative")?;
+                },
+                Token::Month(month) => self.month_date(month)?,
+                Token::Weekday(number) => {
+                    self.next_is(Token::Char(b','));
+                    self.weekday(Weekday { ordinal: 0, number, has_ordinal: false });
+                },
+                Token::Ordinal(ordinal) => match self.next() {
+                    Some(Token::Weekday(number)) => {
+                        self.weekday(Weekday { ordinal, number, has_ordinal: true });
+                    },
+                    Some(Token::Unit(unit)) => self.unit(unit, ordinal)?,
+                    _ => return Err(SyntaxError),
+                },
+                Token::Unit(unit) => self.unit(unit, 1)?,
+                Token::DayShift(days) => {
+                    self.add_relative(Relative { days, ..Relative::default() }, "relative")?;
+                },
+                Token::Zone(offset) => {
+                    let offset = if self.next_is(Token::Dst) {
+                        offset + 3600
+                    } else if self.is_offset(0) {
+                        let (value, digits) = self.next_signed().unwrap();
+                        offset + self.offset(value, digits)?
+                    } else {
+                        offset
+                    };
+                    self.zone_item(offset);
+                },
+                // The military time zone T
+                Token::T => self.zone_item(-7 * 3600),
+                _ => return Err(SyntaxError),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The items that start with the unsigned number `value`.
+    fn number(&mut self, value: i64, digits: usize) -> ParseResult {
+        match self.peek(0) {
+            // HH:MM[:SS] [am|pm|OFFSET]
+            Some(Token::Char(b':')) => {
+                self.pos += 1;
+                let time = self.time(value)?;
+                let zone = self.time_zone()?;
+                self.set_time(None, time, zone);
+            },
+            // HH am
+            Some(Token::Meridian(meridian)) => {
+                self.pos += 1;
+                let time =
+                    Time { hour: value, minute: 0, second: 0, nanos: 0, meridian: Some(meridian) };
+                self.set_time(None, time, None);
+            },
+            // MM/DD, MM/DD/YY and YYYY/MM/DD
+            Some(Token::Char(b'/')) => {
+                self.pos += 1;
+                let (second, _) = self.next_unsigned().ok_or(SyntaxError)?;
+                if !self.next_is(Token::Char(b'/')) {
+                    self.set_date(Date { year: None, month: value, day: second });
+                    return Ok(());
+                }
+
+                let (third, third_digits) = self.next_unsigned().ok_or(SyntaxError)?;
+                if digits >= 4 {
+                    self.debug(format_args!(
+                        "warning: value {} has {} digits. Assuming YYYY/MM/DD",
+                        value, digits
+                    ));
+                    self.set_date(Date {
+                        year: Some(Year { value, digits }),
+                        month: second,
+                        day: third,
+                    });
+                } else {
+                    self.debug(format_args!(
+                        "warning: value {} has less than 4 digits. Assuming MM/DD/YY[YY]",
+                        value
+                    ));
+                    let year = Year { value: third, digits: third_digits };
+                    self.set_date(Date { year: Some(year), month: value, day: second });
+                }
+            },
+            // YYYY-MM-DD, with an optional THH:MM[:SS][OFFSET]
+            Some(Token::Number { value: month, signed: true, .. })
+                if matches!(self.peek(1), Some(Token::Number { signed: true, .. })) =>
+            {
+                let day = match self.peek(1) {
+                    Some(Token::Number { value, .. }) => value,
+                    _ => unreachable!(),
+                };
+                self.pos += 2;
+                let date = Date { year: Some(Year { value, digits }), month: -month, day: -day };
+
+                if self.peek(0) == Some(Token::T)
+                    && matches!(self.peek(1), Some(Token::Number { signed: false, .. }))
+                {
+                    self.pos += 1;
+                    let (hour, _) = self.next_unsigned().unwrap();
+                    let time = if self.next_is(Token::Char(b':')) {
+                        self.time(hour)?
+                    } else if self.is_offset(0) {
+                        Time { hour, minute: 0, second: 0, nanos: 0, meridian: None }
+                    } else {
+                        return Err(SyntaxError);
+                    };
+                    let zone = self.time_zone()?;
+                    self.set_time(Some(date), time, zone);
+                } else {
+                    self.set_date(date);
+                }
+            },
+            // A number and a relative item, like `2020 +1 month`
+            Some(Token::Number { value: count, signed: true, .. })
+                if matches!(self.peek(1), Some(Token::Unit(_))) =>
+            {
+                let unit = match self.peek(1) {
+                    Some(Token::Unit(unit)) => unit,
+                    _ => unreachable!(),
+                };
+                self.pos += 2;
+                self.digits_to_date_time(value, digits);
+
+                let mut relative = Relative::default();
+                relative.add(unit, count).ok_or(SyntaxError)?;
+                self.add_relative(relative, "hybrid")?;
+            },
+            // HH OFFSET
+            Some(Token::Number { signed: true, .. }) => {
+                let zone = self.time_zone()?;
+                let time = Time { hour: value, minute: 0, second: 0, nanos: 0, meridian: None };
+                self.set_time(None, time, zone);
+            },
+            // DD MONTH [YYYY]
+            Some(Token::Month(month)) => {
+                self.pos += 1;
+                let year = match self.peek(0) {
+                    Some(Token::Number { value, digits, .. }) => {
+                        self.pos += 1;
+                        Some(Year { value: value.abs(), digits })
+                    },
+                    _ => None,
+                };
+                self.set_date(Date { year, month, day: value });
+            },
+            Some(Token::Weekday(number)) => {
+                self.pos += 1;
+                self.weekday(Weekday { ordinal: value, number, has_ordinal: true });
+            },
+            Some(Token::Unit(unit)) => {
+                self.pos += 1;
+                self.unit(unit, value)?;
+            },
+            _ => {
+                let description = self.digits_to_date_time(value, digits);
+                self.debug(format_args!("parsed number part: {}", description));
+            },
+        }
+        Ok(())
+    }
+
+    /// Set the part of a number alone, that is the year after a date, a date like
+    /// `YYYYMMDD` or a time like `HHMM`. Returns the description of the part.
+    fn digits_to_date_time(&mut self, value: i64, digits: usize) -> String {
+        let is_year = !self.items.has_relative && (self.items.time.is_some() || digits > 2);
+        match &mut self.items.date {
+            Some(date) if date.year.is_none() && is_year => {
+                date.year = Some(Year { value, digits });
+                return format!("year: {}", value);
+            },
+            _ => {},
+        }
+
+        if digits > 4 {
+            let year = Year { value: value / 10000, digits: digits - 4 };
+            let date = Date { year: Some(year), month: value / 100 % 100, day: value % 100 };
+            self.items.date = Some(date);
+            self.items.dates_seen += 1;
+            self.describe_first(self.items.dates_seen, describe_date(&date, self.now_fields()))
+        } else {
+            let (hour, minute) = if digits <= 2 { (value, 0) } else { (value / 100, value % 100) };
+            let time = Time { hour, minute, second: 0, nanos: 0, meridian: None };
+            self.items.time = Some(time);
+            self.items.times_seen += 1;
+            self.describe_first(self.items.times_seen, describe_time(&time))
+        }
+    }
+
+    /// The rest of a time after `HH:`, as `MM[:SS[.NNN]]` and an optional meridian.
+    fn time(&mut self, hour: i64) -> Result<Time, SyntaxError> {
+        let (minute, _) = self.next_unsigned().ok_or(SyntaxError)?;
+        let (second, nanos) = if self.next_is(Token::Char(b':')) {
+            match self.next() {
+                Some(Token::Number { value, signed: false, .. }) => (value, 0),
+                Some(Token::Decimal { seconds, nanos, signed: false }) => (seconds, nanos),
+                _ => return Err(SyntaxError),
+            }
+        } else {
+            (0, 0)
+        };
+
+        let meridian = match self.peek(0) {
+            Some(Token::Meridian(meridian)) => {
+                self.pos += 1;
+                Some(meridian)
+            },
+            _ => None,
+        };
+        Ok(Time { hour, minute, second, nanos, meridian })
+    }
+
+    /// The optional offset after a time, like `+05:00`. Like the other implementations, a
+    /// signed number after a time is always an offset, even if a unit follows it.
+    fn time_zone(&mut self) -> Result<Option<i64>, SyntaxError> {
+        match self.next_signed() {
+            Some((value, digits)) => self.offset(value, digits).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// The offset in seconds of `value`, the hours or `HHMM`, with any `:MM` after it.
+    fn offset(&mut self, value: i64, digits: usize) -> Result<i64, SyntaxError> {
+        let minutes = if self.peek(0) == Some(Token::Char(b':')) {
+            self.pos += 1;
+            let (minutes, _) = self.next_unsigned().ok_or(SyntaxError)?;
+            let sign = if value < 0 { -1 } else { 1 };
+            value * 60 + sign * minutes
+        } else if digits <= 2 {
+            value * 60
+        } else {
+            value / 100 * 60 + value % 100
+        };
+
+        if minutes.abs() > 24 * 60 {
+            return Err(SyntaxError);
+        }
+        Ok(minutes * 60)
+    }
+
+    /// The dates that start with `month`: `MONTH DD`, `MONTH DD, YYYY` and
+    /// `MONTH-DD-YYYY`.
+    fn month_date(&mut self, month: i64) -> ParseResult {
+        match self.next() {
+            Some(Token::Number { value: day, signed: false, .. }) => {
+                let year = if self.peek(0) == Some(Token::Char(b','))
+                    && matches!(self.peek(1), Some(Token::Number { signed: false, .. }))
+                {
+                    self.pos += 1;
+                    let (value, digits) = self.next_unsigned().unwrap();
+                    Some(Year { value, digits })
+                } else {
+                    None
+                };
+                self.set_date(Date { year, month, day });
+            },
+            Some(Token::Number { value: day, signed: true, .. }) => {
+                let (year, digits) = self.next_signed().ok_or(SyntaxError)?;
+                self.set_date(Date { year: Some(Year { value: -year, digits }), month, day: -day });
+            },
+            _ => return Err(SyntaxError),
+        }
+        Ok(())
+    }
+
+    /// A relative item that starts with the signed number `count`.
+    fn relative(&mut self, count: i64) -> ParseResult {
+        match self.next() {
+            Some(Token::Unit(unit)) => self.unit(unit, count),
+            _ => Err(SyntaxError),
+        }
+    }
+
+    /// `count` of `unit`, with an optional `ago` after it.
+    fn unit(&mut self, unit: Unit, count: i64) -> ParseResult {
+        let count =
+            if self.next_is(Token::Ago) { count.checked_neg().ok_or(SyntaxError)? } else { count };
+
+        let mut relative = Relative::default();
+        relative.add(unit, count).ok_or(SyntaxError)?;
+        self.add_relative(relative, "relative")
+    }
+
+    /// Add the `relative` item, where the `part` is `relative` or `hybrid`.
+    fn add_relative(&mut self, relative: Relative, part: &str) -> ParseResult {
+        let sum = &mut self.items.relative;
+        let add = |a: i64, b: i64| a.checked_add(b).ok_or(SyntaxError);
+        *sum = Relative {
+            years: add(sum.years, relative.years)?,
+            months: add(sum.months, relative.months)?,
+            days: add(sum.days, relative.days)?,
+            hours: add(sum.hours, relative.hours)?,
+            minutes: add(sum.minutes, relative.minutes)?,
+            seconds: add(sum.seconds, relative.seconds)?,
+            nanos: add(sum.nanos, relative.nanos)?,
+        };
+        self.items.has_relative = true;
+
+        self.debug(format_args!("parsed {} part:{}", part, self.items.relative.describe()));
+        Ok(())
+    }
+
+    fn weekday(&mut self, weekday: Weekday) {
+        self.items.weekday = Some(weekday);
+        self.items.days_seen += 1;
+        let description = format!(
+            "{} (day ordinal={} number={})",
+            describe_weekday(&weekday),
+            weekday.ordinal,
+            weekday.number
+        );
+        self.debug(format_args!(
+            "parsed day part: {}",
+            self.describe_first(self.items.days_seen, description)
+        ));
+    }
+
+    fn set_date(&mut self, date: Date) {
+        self.items.date = Some(date);
+        self.items.dates_seen += 1;
+        let description = describe_date(&date, self.now_fields());
+        self.debug(format_args!(
+            "parsed date part: {}",
+            self.describe_first(self.items.dates_seen, description)
+        ));
+    }
+
+    /// Set the `time` with the `date` of the same item if any, like in
+    /// `2020-01-02T03:04:05`, and the `zone` if any.
+    fn set_time(&mut self, date: Option<Date>, time: Time, zone: Option<i64>) {
+        let mut parts = Vec::new();
+        if let Some(date) = date {
+            self.items.date = Some(date);
+            self.items.dates_seen += 1;
+            if self.items.dates_seen == 1 {
+                parts.push(describe_date(&date, self.now_fields()));
+            }
+        }
+
+        self.items.time = Some(time);
+        self.items.times_seen += 1;
+        if self.items.times_seen == 1 {
+            parts.push(describe_time(&time));
+            if let Some(zone) = zone {
+                parts.push(format!("UTC{}", format_offset(zone)));
+            }
+        }
+        if let Some(zone) = zone {
+            self.items.zone = Some(zone);
+            self.items.zones_seen += 1;
+        }
+
+        let part = if date.is_some() { "datetime" } else { "time" };
+        self.debug(format_args!("parsed {} part: {}", part, parts.join(" ")));
+    }
+
+    /// Set the time zone of a zone item, like `EST` or `UTC+05`.
+    fn zone_item(&mut self, offset: i64) {
+        self.items.zone = Some(offset);
+        self.items.zones_seen += 1;
+        let description = format!("UTC{}", format_offset(offset));
+        self.debug(format_args!(
+            "parsed zone part: {}",
+            self.describe_first(self.items.zones_seen, description)
+        ));
+    }
+
+    /// The `description` of a part, if it was `seen` for the first time. The repeated
+    /// parts are not described, since they fail later.
+    fn describe_first(&self, seen: usize, description: String) -> String {
+        if seen == 1 {
+            description
+        } else {
+            String::new()
+        }
+    }
+
+    /// The local date and time of now.
+    fn now_fields(&self) -> Fields {
+        Fields::from_seconds(self.now.seconds + self.zone.offset_at(self.now.seconds))
+    }
+
+    /// The instant of the parsed items. Returns `None` if the date is invalid.
+    fn resolve(&self) -> Option<Timestamp> {
+        let items = &self.items;
+
+        if let Some(timestamp) = items.timestamp {
+            self.debug("input timezone: '@timespec' - always UTC");
+            self.debug_final(timestamp);
+            return Some(timestamp);
+        }
+
+        match items.zone {
+            Some(offset) => self.debug(format_args!(
+                "input timezone: parsed date/time string ({})",
+                format_offset(offset)
+            )),
+            None => self.debug(format_args!("input timezone: {}", self.zone.describe())),
+        }
+
+        let repeated = [
+            (items.times_seen, "time"),
+            (items.dates_seen, "date"),
+            (items.days_seen, "days"),
+            (items.zones_seen, "time-zone"),
+        ];
+        let mut is_repeated = false;
+        for &(seen, part) in repeated.iter() {
+            if seen > 1 {
+                self.debug(format_args!("error: seen multiple {} parts", part));
+                is_repeated = true;
+            }
+        }
+        if is_repeated {
+            return None;
+        }
+
+        let now = self.now_fields();
+        let mut fields = now;
+        let mut nanos = self.now.nanos;
+
+        if let Some(date) = items.date {
+            fields.month = date.month;
+            fields.day = date.day;
+            if let Some(year) = date.year {
+                fields.year = year.value;
+                if year.digits == 2 {
+                    fields.year += if year.value < 69 { 2000 } else { 1900 };
+                    self.debug(format_args!(
+                        "warning: adjusting year value {} to {}",
+                        year.value, fields.year
+                    ));
+                }
+            }
+        }
+
+        if let Some(time) = items.time {
+            fields.hour = match to_hour(time.hour, time.meridian) {
+                Some(hour) => hour,
+                None => {
+                    self.debug(format_args!(
+                        "error: invalid hour {}{}",
+                        time.hour,
+                        describe_meridian(time.meridian)
+                    ));
+                    return None;
+                },
+            };
+            fields.minute = time.minute;
+            fields.second = time.second;
+            nanos = time.nanos;
+            self.debug(format_args!(
+                "using specified time as starting value: '{:02}:{:02}:{:02}'",
+                fields.hour, fields.minute, fields.second
+            ));
+        } else if items.has_relative && items.date.is_none() && items.weekday.is_none() {
+            self.debug(format_args!(
+                "using current time as starting value: '{:02}:{:02}:{:02}'",
+                fields.hour, fields.minute, fields.second
+            ));
+        } else {
+            fields.hour = 0;
+            fields.minute = 0;
+            fields.second = 0;
+            nanos = 0;
+            self.debug("warning: using midnight as starting time: 00:00:00");
+        }
+
+        // The years that can't be displayed are invalid
+        if !YEARS.contains(&fields.year) || !self.check_fields(fields) {
+            return None;
+        }
+
+        if items.date.is_none() && items.weekday.is_none() {
+            self.debug(format_args!(
+                "using current date as starting value: '(Y-M-D) {:04}-{:02}-{:02}'",
+                now.year, now.month, now.day
+            ));
+        }
+
+        if let Some(weekday) = items.weekday {
+            if items.date.is_some() {
+                self.debug(format_args!(
+                    "warning: day ({}) ignored when explicit dates are given",
+                    &WEEKDAY_NAMES[weekday.number as usize][..3]
+                ));
+            } else {
+                let current = fields.weekday();
+                let later = (weekday.number - current + 7) % 7;
+                let weeks =
+                    weekday.ordinal - (weekday.ordinal > 0 && current != weekday.number) as i64;
+                fields.day += later + 7 * weeks;
+                fields = fields.normalized();
+                self.debug(format_args!(
+                    "new start date: '{}' is '{}'",
+                    describe_weekday(&weekday),
+                    fields
+                ));
+            }
+        }
+
+        let zone_suffix =
+            items.zone.map_or_else(String::new, |zone| format!(" TZ={}", format_offset(zone)));
+        self.debug(format_args!("starting date/time: '{}{}'", fields, zone_suffix));
+
+        let relative = items.relative;
+        if relative.has_date() {
+            if (relative.years != 0 || relative.months != 0) && fields.day != 15 {
+                self.debug(
+                    "warning: when adding relative months/years, it is recommended to specify the \
+                     15th of the months",
+                );
+            }
+            if relative.days != 0 && fields.hour != 12 {
+                self.debug("warning: when adding relative days, it is recommended to specify noon");
+            }
+
+            let adjusted = Fields {
+                year: fields.year.checked_add(relative.years)?,
+                month: fields.month.checked_add(relative.months)?,
+                ..fields
+            };
+            let months = adjusted.year.checked_mul(12)?.checked_add(adjusted.month - 1)?;
+            let adjusted = Fields {
+                year: months.div_euclid(12),
+                month: months.rem_euclid(12) + 1,
+                ..adjusted
+            };
+            if !YEARS.contains(&adjusted.year) {
+                return None;
+            }
+            let normalized = Fields { day: adjusted.day.checked_add(relative.days)?, ..adjusted };
+            let normalized = Fields::from_seconds(normalized.to_seconds().checked_add(0)?);
+
+            self.debug(format_args!(
+                "after date adjustment ({:+} years, {:+} months, {:+} days),",
+                relative.years, relative.months, relative.days
+            ));
+            self.debug(format_args!("    new date/time = '{}{}'", normalized, zone_suffix));
+
+            // The days moved by the relative days are not shifts
+            if relative.days == 0 && adjusted.day > days_in_month(adjusted.year, adjusted.month) {
+                let shifted = adjusted.normalized();
+                self.debug("warning: month/year adjustment resulted in shifted dates:");
+                self.debug(format_args!(
+                    "     adjusted Y M D: {:04} {:02} {:02}",
+                    adjusted.year, adjusted.month, adjusted.day
+                ));
+                self.debug(format_args!(
+                    "   normalized Y M D: {:04} {:02} {:02}",
+                    shifted.year, shifted.month, shifted.day
+                ));
+            }
+            fields = normalized;
+        }
+
+        if !YEARS.contains(&fields.year) {
+            return None;
+        }
+        let local = fields.to_seconds();
+        let mut seconds = match items.zone {
+            Some(offset) => local - offset,
+            None => self.zone.local_to_utc(local),
+        };
+        self.debug(format_args!("'{}{}' = {} epoch-seconds", fields, zone_suffix, seconds));
+
+        let mut nanos = i64::from(nanos);
+        if relative.has_time() {
+            let total = relative
+                .hours
+                .checked_mul(3600)?
+                .checked_add(relative.minutes.checked_mul(60)?)?
+                .checked_add(relative.seconds)?;
+            nanos += relative.nanos;
+            seconds = seconds.checked_add(total)?.checked_add(nanos.div_euclid(1_000_000_000))?;
+            nanos = nanos.rem_euclid(1_000_000_000);
+
+            self.debug(format_args!(
+                "after time adjustment ({:+} hours, {:+} minutes, {:+} seconds, {:+} ns),",
+                relative.hours, relative.minutes, relative.seconds, relative.nanos
+            ));
+            self.debug(format_args!("    new time = {} epoch-seconds", seconds));
+        }
+
+        let timestamp = Timestamp { seconds, nanos: nanos as u32 };
+        if !YEARS.contains(&Fields::from_seconds(seconds).year) {
+            return None;
+        }
+        self.debug_final(timestamp);
+        Some(timestamp)
+    }
+
+    /// Check that the `fields` are in their ranges and exist in the zone, writing the
+    /// differences with the normalized ones if not.
+    fn check_fields(&self, fields: Fields) -> bool {
+        let valid_date = (1..=12).contains(&fields.month)
+            && (1..=days_in_month(fields.year, fields.month)).contains(&fields.day);
+        let valid_time = (0..24).contains(&fields.hour)
+            && (0..60).contains(&fields.minute)
+            && (0..60).contains(&fields.second);
+
+        // The local times skipped by the daylight saving time changes don't exist either
+        let normalized = match self.items.zone {
+            Some(_) => fields.normalized(),
+            None => {
+                let seconds = self.zone.local_to_utc(fields.to_seconds());
+                Fields::from_seconds(seconds + self.zone.offset_at(seconds))
+            },
+        };
+        if normalized == fields {
+            return true;
+        }
+        if !self.debug {
+            return false;
+        }
+
+        let marks: Vec<String> = [
+            (fields.year, normalized.year, 4),
+            (fields.month, normalized.month, 2),
+            (fields.day, normalized.day, 2),
+            (fields.hour, normalized.hour, 2),
+            (fields.minute, normalized.minute, 2),
+            (fields.second, normalized.second, 2),
+        ]
+        .iter()
+        .map(|&(field, normalized, width)| {
+            (if field == normalized { " " } else { "-" }).repeat(width)
+        })
+        .collect();
+
+        self.debug("error: invalid date/time value:");
+        self.debug(format_args!("    user provided time: '{}'", fields));
+        self.debug(format_args!("       normalized time: '{}'", normalized));
+        self.debug(format_args!("{:33}{}", "", marks.join(" ").trim_end()));
+        self.debug("     possible reasons:");
+        if valid_date && valid_time {
+            self.debug("       non-existing due to daylight-saving time;");
+        }
+        if fields.day != normalized.day && fields.month != normalized.month {
+            self.debug("       invalid day/month combination;");
+        }
+        self.debug("       numeric values overflow;");
+        self.debug(if self.items.zone.is_some() {
+            "       incorrect timezone"
+        } else {
+            "       missing timezone"
+        });
+        false
+    }
+
+    fn debug_final(&self, timestamp: Timestamp) {
+        if !self.debug {
+            return;
+        }
+
+        let offset = self.zone.offset_at(timestamp.seconds);
+        self.debug(format_args!("timezone: {}", self.zone.describe()));
+        self.debug(format_args!(
+            "final: {}.{:09} (epoch-seconds)",
+            timestamp.seconds, timestamp.nanos
+        ));
+        self.debug(format_args!("final: {} (UTC)", Fields::from_seconds(timestamp.seconds)));
+        self.debug(format_args!(
+            "final: {} (UTC{})",
+            Fields::from_seconds(timestamp.seconds + offset),
+            format_offset(offset)
+        ));
+    }
+}
+
+/// The hour of the day of `hour` with `meridian`. Returns `None` if it's out of range.
+fn to_hour(hour: i64, meridian: Option<Meridian>) -> Option<i64> {
+    match meridian {
+        None if (0..24).contains(&hour) => Some(hour),
+        Some(Meridian::Am) if (1..=12).contains(&hour) => Some(hour % 12),
+        Some(Meridian::Pm) if (1..=12).contains(&hour) => Some(hour % 12 + 12),
+        _ => None,
+    }
+}
+
+fn describe_meridian(meridian: Option<Meridian>) -> &'static str {
+    match meridian {
+        Some(Meridian::Am) => "am",
+        Some(Meridian::Pm) => "pm",
+        None => "",
+    }
+}
+
+fn describe_date(date: &Date, now: Fields) -> String {
+    let year = date.year.map_or(now.year, |year| year.value);
+    format!("(Y-M-D) {:04}-{:02}-{:02}", year, date.month, date.day)
+}
+
+fn describe_time(time: &Time) -> String {
+    let mut description = format!("{:02}:{:02}:{:02}", time.hour, time.minute, time.second);
+    if time.nanos != 0 {
+        description.push_str(&format!(".{:09}", time.nanos));
+    }
+    // Like the other implementations, only the afternoon is annotated
+    if time.meridian == Some(Meridian::Pm) {
+        description.push_str("pm");
+    }
+    description
+}
+
+fn describe_weekday(weekday: &Weekday) -> String {
+    let name = &WEEKDAY_NAMES[weekday.number as usize][..3];
+    if !weekday.has_ordinal {
+        return name.to_string();
+    }
+
+    let ordinal = match weekday.ordinal {
+        -1 => "last".to_string(),
+        0 => "this".to_string(),
+        1 => "next/first".to_string(),
+        2 => "(SECOND)".to_string(),
+        ordinal => match ORDINALS.iter().find(|&&(_, value)| value == ordinal) {
+            Some((word, _)) => word.to_ascii_lowercase(),
+            None => ordinal.to_string(),
+        },
+    };
+    format!("{} {}", ordinal, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Wednesday 14 October 2026, 08:49:24.5 UTC
+    const NOW: Timestamp = Timestamp { seconds: 1_791_967_764, nanos: 500_000_000 };
+
+    fn parse(input: &str) -> Option<i64> {
+        parse_date(input, NOW, Zone::Utc, false).ok().map(|timestamp| timestamp.seconds)
+    }
+
+    fn utc(year: i64, month: i64, day: i64, hour: i64, minute: i64, second: i64) -> Option<i64> {
+        Some(Fields { year, month, day, hour, minute, second }.to_seconds())
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(
+            parse_date("@1600000000", NOW, Zone::Utc, false),
+            Ok(Timestamp { seconds: 1_600_000_000, nanos: 0 })
+        );
+        assert_eq!(
+            parse_date("@-1.25", NOW, Zone::Utc, false),
+            Ok(Timestamp { seconds: -2, nanos: 750_000_000 })
+        );
+        assert_eq!(parse("@ 5"), Some(5));
+        assert_eq!(parse("@5 days"), None);
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(parse("2020-01-05"), utc(2020, 1, 5, 0, 0, 0));
+        assert_eq!(parse("2020/01/05"), utc(2020, 1, 5, 0, 0, 0));
+        assert_eq!(parse("12/11/10"), utc(2010, 12, 11, 0, 0, 0));
+        assert_eq!(parse("1/5"), utc(2026, 1, 5, 0, 0, 0));
+        assert_eq!(parse("jan 5"), utc(2026, 1, 5, 0, 0, 0));
+        assert_eq!(parse("Jan 5, 2020"), utc(2020, 1, 5, 0, 0, 0));
+        assert_eq!(parse("jan 5 2020"), utc(2020, 1, 5, 0, 0, 0));
+        assert_eq!(parse("5 jan 2020"), utc(2020, 1, 5, 0, 0, 0));
+        assert_eq!(parse("17-JUN-1992"), utc(1992, 6, 17, 0, 0, 0));
+        assert_eq!(parse("JUN-17-1992"), utc(1992, 6, 17, 0, 0, 0));
+        assert_eq!(parse("sep. 3"), utc(2026, 9, 3, 0, 0, 0));
+        assert_eq!(parse("sept 3"), utc(2026, 9, 3, 0, 0, 0));
+        assert_eq!(parse("20201010"), utc(2020, 10, 10, 0, 0, 0));
+        assert_eq!(parse("2020-13-01"), None);
+        assert_eq!(parse("2021-02-29"), None);
+        assert_eq!(parse("jan"), None);
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(parse("10:00"), utc(2026, 10, 14, 10, 0, 0));
+        assert_eq!(parse("3pm"), utc(2026, 10, 14, 15, 0, 0));
+        assert_eq!(parse("12 am"), utc(2026, 10, 14, 0, 0, 0));
+        assert_eq!(parse("1230"), utc(2026, 10, 14, 12, 30, 0));
+        assert_eq!(parse("2020"), utc(2026, 10, 14, 20, 20, 0));
+        assert_eq!(parse("10:00:05.5pm"), utc(2026, 10, 14, 22, 0, 5));
+        assert_eq!(parse(""), utc(2026, 10, 14, 0, 0, 0));
+        assert_eq!(parse("25:00"), None);
+        assert_eq!(parse("0am"), None);
+        assert_eq!(parse("20:00 20:00"), None);
+    }
+
+    #[test]
+    fn zones() {
+        assert_eq!(parse("2020-01-02T03:04:05+05:00"), utc(2020, 1, 1, 22, 4, 5));
+        assert_eq!(parse("2020-01-02T03:04:05Z"), utc(2020, 1, 2, 3, 4, 5));
+        assert_eq!(parse("10:00 EST"), utc(2026, 10, 14, 15, 0, 0));
+        assert_eq!(parse("10:00 CET DST"), utc(2026, 10, 14, 8, 0, 0));
+        assert_eq!(parse("10:00 UTC+0530"), utc(2026, 10, 14, 4, 30, 0));
+        assert_eq!(parse("10:00 -3"), utc(2026, 10, 14, 13, 0, 0));
+        assert_eq!(parse("10:00 A"), utc(2026, 10, 14, 9, 0, 0));
+        assert_eq!(parse("10:00 N"), utc(2026, 10, 14, 11, 0, 0));
+        assert_eq!(parse("10:00 T"), utc(2026, 10, 14, 17, 0, 0));
+        assert_eq!(parse("fri, 13 jun 2025 10:00:00 +0200"), utc(2025, 6, 13, 8, 0, 0));
+        assert_eq!(parse("10:00 UTC EST"), None);
+    }
+
+    #[test]
+    fn relative() {
+        assert_eq!(parse("2 weeks ago"), utc(2026, 9, 30, 8, 49, 24));
+        assert_eq!(parse("yesterday"), utc(2026, 10, 13, 8, 49, 24));
+        assert_eq!(parse("tomorrow 3pm"), utc(2026, 10, 15, 15, 0, 0));
+        assert_eq!(parse("3 hours ago"), utc(2026, 10, 14, 5, 49, 24));
+        assert_eq!(parse("last year"), utc(2025, 10, 14, 8, 49, 24));
+        assert_eq!(parse("next fortnight"), utc(2026, 10, 28, 8, 49, 24));
+        assert_eq!(parse("-3 days"), utc(2026, 10, 11, 8, 49, 24));
+        assert_eq!(parse("1 day ago 2 hours"), utc(2026, 10, 13, 10, 49, 24));
+        assert_eq!(parse("jan 31 + 1 month"), utc(2026, 3, 3, 0, 0, 0));
+        assert_eq!(parse("2020-01-01 10:00 +3 hours"), utc(2020, 1, 1, 8, 0, 0));
+        assert_eq!(parse("10:00 UTC +3 hours"), utc(2026, 10, 14, 13, 0, 0));
+        assert_eq!(parse("20 +3 hours"), utc(2026, 10, 14, 23, 0, 0));
+        assert_eq!(parse("now"), NOW.seconds.into());
+        assert_eq!(parse("1.5 days"), None);
+        assert_eq!(parse("1 ago"), None);
+        assert_eq!(parse_date("1.5 sec", NOW, Zone::Utc, false).map(|t| t.nanos), Ok(0));
+    }
+
+    #[test]
+    fn weekdays() {
+        assert_eq!(parse("monday"), utc(2026, 10, 19, 0, 0, 0));
+        assert_eq!(parse("wednesday"), utc(2026, 10, 14, 0, 0, 0));
+        assert_eq!(parse("next wednesday"), utc(2026, 10, 21, 0, 0, 0));
+        assert_eq!(parse("last monday"), utc(2026, 10, 12, 0, 0, 0));
+        assert_eq!(parse("2 monday"), utc(2026, 10, 26, 0, 0, 0));
+        assert_eq!(parse("tue. 10:00"), utc(2026, 10, 20, 10, 0, 0));
+        assert_eq!(parse("monday 2020-01-01"), utc(2020, 1, 1, 0, 0, 0));
+        assert_eq!(parse("friday ago"), None);
+    }
+
+    #[test]
+    fn invalid_words() {
+        assert_eq!(
+            parse_date("garbage", NOW, Zone::Utc, false),
+            Err("invalid date 'garbage'".to_string())
+        );
+        assert_eq!(parse("noon"), None);
+        assert_eq!(parse("next"), None);
+        assert_eq!(parse("+3"), None);
+    }
+}
@@ -0,0 +1,96 @@
+//! The time zone of the dates, the UTC or the local time of the `TZ` environment
+//! variable.
+use std::env;
+#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+use std::ffi::CStr;
+
+use time::{OffsetDateTime as DateTime, UtcOffset};
+
+#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+use crate::os::time::local_time;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zone {
+    Utc,
+    Local,
+}
+
+impl Zone {
+    /// The offset from UTC in seconds at `seconds` since the Epoch.
+    pub fn offset_at(self, seconds: i64) -> i64 {
+        match self {
+            Zone::Utc => 0,
+            Zone::Local => UtcOffset::try_local_offset_at(DateTime::from_unix_timestamp(seconds))
+                .map_or(0, |offset| offset.as_seconds().into()),
+        }
+    }
+
+    /// The seconds since the Epoch of the `local` seconds of the zone, the seconds since
+    /// the Epoch if the zone were UTC.
+    ///
+    /// The offset depends on the instant, so it's guessed from the offset at `local` and
+    /// fixed with the offset at the guess. The local times skipped by the daylight saving
+    /// time changes are taken with the offset before the change, so they give an instant
+    /// after it.
+    pub fn local_to_utc(self, local: i64) -> i64 {
+        let offset = self.offset_at(local - self.offset_at(local));
+        let seconds = local - offset;
+        let fixed = self.offset_at(seconds);
+
+        if fixed == offset {
+            seconds
+        } else {
+            local - offset.min(fixed)
+        }
+    }
+
+    /// The abbreviation of the zone at `seconds` since the Epoch, like `CET` or `CEST`,
+    /// or its offset if it has none.
+    pub fn name_at(self, seconds: i64) -> String {
+        match self {
+            Zone::Utc => "UTC".to_string(),
+            Zone::Local => {
+                local_name_at(seconds).unwrap_or_else(|| format_offset(self.offset_at(seconds)))
+            },
+        }
+    }
+
+    /// Describe the source of the zone, for the `--debug` messages.
+    pub fn describe(self) -> String {
+        match (self, env::var("TZ")) {
+            (Zone::Utc, _) => "TZ=\"UTC0\" environment value".to_string(),
+            (Zone::Local, Ok(tz)) => format!("TZ=\"{}\" environment value", tz),
+            (Zone::Local, Err(_)) => "system default".to_string(),
+        }
+    }
+}
+
+#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+fn local_name_at(seconds: i64) -> Option<String> {
+    let tm = local_time(seconds).ok()?;
+    if tm.tm_zone.is_null() {
+        return None;
+    }
+
+    // SAFETY: The name is a C string of the zone data, not freed while the data is loaded
+    Some(unsafe { CStr::from_ptr(tm.tm_zone) }.to_string_lossy().into_owned())
+}
+
+// The platforms without the name in the time structure
+#[cfg(any(not(unix), target_os = "solaris", target_os = "illumos"))]
+fn local_name_at(_seconds: i64) -> Option<String> {
+    None
+}
+
+/// Format `offset` seconds from UTC like `+05` or `-03:30`.
+pub fn format_offset(offset: i64) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+    let (hours, minutes, seconds) = (offset / 3600, offset / 60 % 60, offset % 60);
+
+    match (minutes, seconds) {
+        (0, 0) => format!("{}{:02}", sign, hours),
+        (_, 0) => format!("{}{:02}:{:02}", sign, hours, minutes),
+        _ => format!("{}{:02}:{:02}:{:02}", sign, hours, minutes, seconds),
+    }
+}
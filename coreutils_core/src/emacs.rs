@@ -0,0 +1,255 @@
+//! Translation of the GNU Emacs regular expressions, the default syntax of the GNU regex
+//! functions that ptx and tac use, to the syntax of the `regex` crate.
+use regex::bytes::Regex;
+
+use crate::bre::BreError;
+
+/// Compile the regular expression `pattern` on bytes, ignoring the case of the letters if
+/// `ignore_case`.
+///
+/// Like GNU, `^` and `$` also match after and before the newlines.
+pub fn compile(pattern: &[u8], ignore_case: bool) -> Result<Regex, BreError> {
+    let translated = translate(pattern)?;
+    let flags = if ignore_case { "(?-u)(?mi)" } else { "(?-u)(?m)" };
+    Regex::new(&format!("{}{}", flags, translated)).map_err(|_| BreError::Invalid)
+}
+
+/// Translate the regular expression `pattern`.
+///
+/// Unlike the basic regular expressions, `+` and `?` repeat, `\+`, `\?`, `\{` and `\}`
+/// are literals, and there are no classes in the brackets. The GNU extensions `\w`, `\W`,
+/// `\s`, `\S`, `\b`, `\B`, `\<`, `\>`, `` \` `` and `\'` are supported.
+pub fn translate(pattern: &[u8]) -> Result<String, BreError> {
+    let mut output = String::with_capacity(pattern.len());
+    let mut depth = 0;
+    // Whether the position is the start of a expression, where the repetitions are
+    // literals and `^` a anchor
+    let mut at_start = true;
+    // Whether the last item can be repeated
+    let mut repeatable = false;
+    // The repetition of the last item, if any
+    let mut repetition = None;
+    let mut i = 0;
+
+    while i < pattern.len() {
+        let c = pattern[i];
+        i += 1;
+
+        match c {
+            b'\\' => {
+                let escaped = *pattern.get(i).ok_or(BreError::TrailingBackslash)?;
+                i += 1;
+
+                match escaped {
+                    b'(' => {
+                        output.push('(');
+                        depth += 1;
+                        at_start = true;
+                        repeatable = false;
+                        repetition = None;
+                        continue;
+                    },
+                    b')' => {
+                        if depth == 0 {
+                            return Err(BreError::UnmatchedCloseParen);
+                        }
+                        output.push(')');
+                        depth -= 1;
+                    },
+                    b'|' => {
+                        output.push('|');
+                        at_start = true;
+                        repeatable = false;
+                        repetition = None;
+                        continue;
+                    },
+                    b'1'..=b'9' => return Err(BreError::BackReference),
+                    b'w' | b'W' | b's' | b'S' => {
+                        output.push('\\');
+                        output.push(char::from(escaped));
+                    },
+                    b'b' | b'B' | b'<' | b'>' | b'`' | b'\'' => {
+                        output.push_str(match escaped {
+                            b'b' => "\\b",
+                            b'B' => "\\B",
+                            b'<' => "\\b{start}",
+                            b'>' => "\\b{end}",
+                            b'`' => "\\A",
+                            _ => "\\z",
+                        });
+                        at_start = false;
+                        repeatable = false;
+                        repetition = None;
+                        continue;
+                    },
+                    _ => push_literal(&mut output, escaped),
+                }
+            },
+            b'[' => i = translate_bracket(pattern, i, &mut output)?,
+            b'*' | b'+' | b'?' if !at_start && repeatable => {
+                // A repeated repetition is the same one, or `*` for different ones
+                match repetition {
+                    Some(previous) if previous != c => {
+                        output.pop();
+                        output.push('*');
+                        repetition = Some(b'*');
+                    },
+                    Some(_) => {},
+                    None => {
+                        output.push(char::from(c));
+                        repetition = Some(c);
+                    },
+                }
+                continue;
+            },
+            b'^' if at_start => {
+                output.push('^');
+                continue;
+            },
+            b'$' if i == pattern.len()
+                || pattern[i] == b'\\' && matches!(pattern.get(i + 1), Some(b')') | Some(b'|')) =>
+            {
+                output.push('$');
+                at_start = false;
+                repeatable = false;
+                repetition = None;
+                continue;
+            },
+            b'.' => output.push('.'),
+            _ => push_literal(&mut output, c),
+        }
+
+        at_start = false;
+        repeatable = true;
+        repetition = None;
+    }
+
+    if depth > 0 {
+        return Err(BreError::UnmatchedParen);
+    }
+    Ok(output)
+}
+
+/// Translate the bracket expression after the `[` at `start`, returning the position
+/// after its `]`.
+fn translate_bracket(pattern: &[u8], start: usize, output: &mut String) -> Result<usize, BreError> {
+    let mut i = start;
+    let negated = pattern.get(i) == Some(&b'^');
+    if negated {
+        i += 1;
+    }
+
+    let mut ranges = Vec::new();
+    // A `]` first is a literal
+    let mut first = true;
+    loop {
+        let c = *pattern.get(i).ok_or(BreError::UnmatchedBracket)?;
+        if c == b']' && !first {
+            i += 1;
+            break;
+        }
+        first = false;
+
+        let c = match (c, pattern.get(i + 1)) {
+            // Only the single characters are supported as collating elements
+            (b'[', Some(&kind)) if kind == b'.' || kind == b'=' => {
+                let end = pattern.get(i + 3..i + 5).ok_or(BreError::UnmatchedBracket)?;
+                if end != [kind, b']'] {
+                    return Err(BreError::Invalid);
+                }
+                i += 5;
+                pattern[i - 3]
+            },
+            _ => {
+                i += 1;
+                c
+            },
+        };
+
+        match pattern.get(i..i + 2) {
+            Some(&[b'-', last]) if last != b']' => {
+                // Like GNU, the reversed ranges are empty
+                if c <= last {
+                    ranges.push((c, last));
+                }
+                i += 2;
+            },
+            _ => ranges.push((c, c)),
+        }
+    }
+
+    if ranges.is_empty() {
+        output.push_str(if negated { "[\\x00-\\xFF]" } else { "[^\\x00-\\xFF]" });
+        return Ok(i);
+    }
+
+    output.push('[');
+    if negated {
+        output.push('^');
+    }
+    for (first, last) in ranges {
+        output.push_str(&format!("\\x{:02X}", first));
+        if last != first {
+            output.push_str(&format!("-\\x{:02X}", last));
+        }
+    }
+    output.push(']');
+    Ok(i)
+}
+
+/// Push the byte `c` matching itself.
+fn push_literal(output: &mut String, c: u8) {
+    if c.is_ascii_alphanumeric() || c == b' ' {
+        output.push(char::from(c));
+    } else {
+        output.push_str(&format!("\\x{:02X}", c));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translate_basic() {
+        assert_eq!(translate(b"a.b*").unwrap(), "a.b*");
+        assert_eq!(translate(b"*a+").unwrap(), "\\x2Aa+");
+        assert_eq!(translate(b"\\(?a\\|b\\)").unwrap(), "(\\x3Fa|b)");
+        assert_eq!(translate(b"^a$").unwrap(), "^a$");
+        assert_eq!(translate(b"a^b$c").unwrap(), "a\\x5Eb\\x24c");
+        assert_eq!(translate(b"a\\+{2}").unwrap(), "a\\x2B\\x7B2\\x7D");
+        assert_eq!(translate(b"a**b+?c??").unwrap(), "a*b*c?");
+        assert_eq!(translate(b"\\<\\w\\>\\'").unwrap(), "\\b{start}\\w\\b{end}\\z");
+    }
+
+    #[test]
+    fn translate_brackets() {
+        assert_eq!(translate(b"[]a]").unwrap(), "[\\x5D\\x61]");
+        assert_eq!(translate(b"[^a-c\\]").unwrap(), "[^\\x61-\\x63\\x5C]");
+        assert_eq!(translate(b"[-z-a]").unwrap(), "[\\x2D]");
+        assert_eq!(translate(b"[[.-.]x]").unwrap(), "[\\x2D\\x78]");
+        assert_eq!(
+            translate(b"[[:alpha:]]").unwrap(),
+            "[\\x5B\\x3A\\x61\\x6C\\x70\\x68\\x61\\x3A]\\x5D"
+        );
+    }
+
+    #[test]
+    fn translate_errors() {
+        assert_eq!(translate(b"\\(a"), Err(BreError::UnmatchedParen));
+        assert_eq!(translate(b"a\\)"), Err(BreError::UnmatchedCloseParen));
+        assert_eq!(translate(b"[a"), Err(BreError::UnmatchedBracket));
+        assert_eq!(translate(b"a\\"), Err(BreError::TrailingBackslash));
+        assert_eq!(translate(b"\\(a\\)\\1"), Err(BreError::BackReference));
+    }
+
+    #[test]
+    fn compile_bytes() {
+        let regex = compile(b"a.c", false).unwrap();
+        assert!(regex.is_match(b"xa\xffc"));
+        assert!(!regex.is_match(b"a\nc"));
+        assert!(compile(b"b$", false).unwrap().is_match(b"ab\nc"));
+        assert!(compile(b"ab", true).unwrap().is_match(b"AB"));
+        assert!(!compile(b"[z-a]", false).unwrap().is_match(b"za"));
+    }
+}
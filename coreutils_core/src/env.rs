@@ -89,3 +89,30 @@ pub fn current_dir_logical() -> Result<PathBuf> {
     }
     Err(Error::Io(IoError::last_os_error()))
 }
+
+/// Check if the character encoding of the locale set on the environment is UTF-8.
+///
+/// The locale is taken from the first non empty of the `LC_ALL`, `LC_CTYPE` and `LANG`
+/// variables, as `setlocale(3)` does for the character classification. When none is set,
+/// the locale is the "C" one, that is not UTF-8.
+#[inline]
+pub fn is_utf8_locale() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty());
+
+    match locale {
+        Some(locale) => {
+            // The locale format is language[_territory][.codeset][@modifier]
+            let locale = locale.split('@').next().unwrap_or("");
+            let codeset = locale.find('.').map(|i| &locale[i + 1..]);
+            codeset
+                .map(|codeset| {
+                    codeset.eq_ignore_ascii_case("UTF-8") || codeset.eq_ignore_ascii_case("utf8")
+                })
+                .unwrap_or(false)
+        },
+        None => false,
+    }
+}
@@ -0,0 +1,33 @@
+//! The messages of the I/O errors, written like GNU writes them.
+use std::io;
+
+/// The message of `err`, that for the errors of the system is the one of `strerror(3)`,
+/// without the `(os error N)` that [`io::Error`] adds to it.
+pub fn strerror(err: &io::Error) -> String {
+    let msg = err.to_string();
+
+    match err.raw_os_error() {
+        Some(code) => match msg.strip_suffix(&format!(" (os error {})", code)) {
+            Some(strerror) => strerror.to_string(),
+            None => msg,
+        },
+        None => msg,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages() {
+        #[cfg(unix)]
+        {
+            let err = io::Error::from_raw_os_error(libc::ENOENT);
+            assert_eq!(strerror(&err), "No such file or directory");
+        }
+
+        let err = io::Error::new(io::ErrorKind::InvalidInput, "input file is output file");
+        assert_eq!(strerror(&err), "input file is output file");
+    }
+}
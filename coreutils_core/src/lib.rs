@@ -5,9 +5,31 @@ pub use time;
 
 // Agnostic Modules
 pub mod backup;
+pub mod bre;
+pub mod canonicalize;
+pub mod collate;
 pub mod consts;
+#[cfg(unix)]
+pub mod copier;
+#[cfg(unix)]
+pub mod chowner;
+#[cfg(unix)]
+pub mod copy;
+pub mod datetime;
+pub mod emacs;
 pub mod env;
+pub mod error;
 pub mod input;
+pub mod ls_colors;
+#[cfg(unix)]
 pub mod mkfifo;
+#[cfg(unix)]
 pub mod mktemp;
+pub mod mode;
 pub mod os;
+pub mod random;
+pub mod size;
+pub mod target;
+pub mod version_sort;
+#[cfg(unix)]
+pub mod walk;
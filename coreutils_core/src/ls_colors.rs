@@ -0,0 +1,382 @@
+//! Module for the colors of the file names given by the `LS_COLORS` environment variable,
+//! in the format of `dircolors(1)`.
+//!
+//! The variable is a list of entries separated by `:`, each one a two letters indicator
+//! or a `*` followed by a suffix of the file names, a `=` and the SGR parameters of the
+//! color, like `di=01;34:*.tar=01;31`. The values may have the escapes of `\` and `^`.
+use std::{cell::Cell, env, error::Error as StdError, fmt};
+
+use self::Indicator::*;
+
+/// The kinds of files, and the parts of the escape sequences, that have a color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Indicator {
+    /// The start of the escape sequences, `lc`.
+    LeftCode,
+    /// The end of the escape sequences, `rc`.
+    RightCode,
+    /// The sequence that replaces the reset after the file names, `ec`.
+    EndCode,
+    /// The parameters of the reset, `rs`.
+    Reset,
+    /// The text that is not a file name, `no`.
+    Normal,
+    /// The regular files, `fi`.
+    File,
+    /// The directories, `di`.
+    Directory,
+    /// The symbolic links, `ln`.
+    Link,
+    /// The named pipes, `pi`.
+    Fifo,
+    /// The sockets, `so`.
+    Socket,
+    /// The block devices, `bd`.
+    BlockDevice,
+    /// The character devices, `cd`.
+    CharDevice,
+    /// The missing targets of the symbolic links, `mi`.
+    MissingFile,
+    /// The symbolic links to missing files, `or`.
+    Orphan,
+    /// The executable files, `ex`.
+    Executable,
+    /// The doors, `do`.
+    Door,
+    /// The files that are set user ID, `su`.
+    Setuid,
+    /// The files that are set group ID, `sg`.
+    Setgid,
+    /// The directories with the sticky bit, `st`.
+    Sticky,
+    /// The directories writable by others, `ow`.
+    OtherWritable,
+    /// The directories with the sticky bit and writable by others, `tw`.
+    StickyOtherWritable,
+    /// The files with capabilities, `ca`.
+    Capability,
+    /// The files with multiple hard links, `mh`.
+    MultiHardLink,
+    /// The sequence that clears the rest of the line, `cl`.
+    ClearLine,
+}
+
+/// The indicators in the order of their discriminants, with their names.
+const INDICATORS: [(Indicator, &str); 24] = [
+    (LeftCode, "lc"),
+    (RightCode, "rc"),
+    (EndCode, "ec"),
+    (Reset, "rs"),
+    (Normal, "no"),
+    (File, "fi"),
+    (Directory, "di"),
+    (Link, "ln"),
+    (Fifo, "pi"),
+    (Socket, "so"),
+    (BlockDevice, "bd"),
+    (CharDevice, "cd"),
+    (MissingFile, "mi"),
+    (Orphan, "or"),
+    (Executable, "ex"),
+    (Door, "do"),
+    (Setuid, "su"),
+    (Setgid, "sg"),
+    (Sticky, "st"),
+    (OtherWritable, "ow"),
+    (StickyOtherWritable, "tw"),
+    (Capability, "ca"),
+    (MultiHardLink, "mh"),
+    (ClearLine, "cl"),
+];
+
+/// The values of the indicators when the variable doesn't set them, like GNU.
+const DEFAULTS: [&str; 24] = [
+    "\x1b[", "m", "", "0", "", "", "01;34", "01;36", "33", "01;35", "01;33", "01;33", "", "",
+    "01;32", "01;35", "37;41", "30;43", "37;44", "34;42", "30;42", "", "", "\x1b[K",
+];
+
+/// The error of a `LS_COLORS` that can't be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError;
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unparsable value for LS_COLORS environment variable")
+    }
+}
+
+impl StdError for ParseError {}
+
+/// The colors of the file names.
+#[derive(Debug, Clone)]
+pub struct LsColors {
+    /// The values of the indicators, by their discriminants.
+    indicators: Vec<String>,
+    /// The suffixes of the file names with their colors, in the order they were given.
+    extensions: Vec<(String, String)>,
+    /// If the symbolic links take the color of their targets, with `ln=target`.
+    link_as_target: bool,
+    /// If a color was used, so the terminal was reset before it.
+    used: Cell<bool>,
+}
+
+impl LsColors {
+    /// Creates the default colors.
+    pub fn new() -> Self {
+        LsColors {
+            indicators: DEFAULTS.iter().map(|value| value.to_string()).collect(),
+            extensions: Vec::new(),
+            link_as_target: false,
+            used: Cell::new(false),
+        }
+    }
+
+    /// Creates the colors of the `LS_COLORS` environment variable, or the default ones if
+    /// it is unset or empty.
+    ///
+    /// # Errors
+    /// If the variable can't be parsed, an error variant will be returned.
+    pub fn from_env() -> Result<Self, ParseError> {
+        match env::var("LS_COLORS") {
+            Ok(spec) => LsColors::parse(&spec),
+            Err(_) => Ok(LsColors::new()),
+        }
+    }
+
+    /// Creates the colors of `spec`, in the format of `LS_COLORS`, over the default ones.
+    ///
+    /// # Errors
+    /// If `spec` has a unknown indicator or a entry without value, an error variant will
+    /// be returned.
+    pub fn parse(spec: &str) -> Result<Self, ParseError> {
+        let mut colors = LsColors::new();
+        let mut rest = spec.as_bytes();
+
+        while let Some(&first) = rest.first() {
+            if first == b':' {
+                rest = &rest[1..];
+                continue;
+            }
+
+            let key = if first == b'*' {
+                let (suffix, after) = unescape(&rest[1..], true)?;
+                rest = after;
+                Key::Suffix(suffix)
+            } else {
+                let name = rest.get(..2).ok_or(ParseError)?;
+                let indicator = INDICATORS
+                    .iter()
+                    .find(|(_, key)| key.as_bytes() == name)
+                    .map(|&(indicator, _)| indicator)
+                    .ok_or(ParseError)?;
+                rest = &rest[2..];
+                Key::Indicator(indicator)
+            };
+
+            if rest.first() != Some(&b'=') {
+                return Err(ParseError);
+            }
+
+            let (value, after) = unescape(&rest[1..], false)?;
+            rest = after;
+
+            match key {
+                Key::Indicator(Link) if value == "target" => colors.link_as_target = true,
+                Key::Indicator(indicator) => colors.indicators[indicator as usize] = value,
+                Key::Suffix(suffix) => colors.extensions.push((suffix, value)),
+            }
+        }
+
+        Ok(colors)
+    }
+
+    /// Get the value of the `indicator` if it sets a color.
+    pub fn indicator(&self, indicator: Indicator) -> Option<&str> {
+        let value = self.indicators[indicator as usize].as_str();
+
+        if is_colored(value) {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// Get the color of the suffix of the file `name`, with a exact match preferred over
+    /// one ignoring the case.
+    pub fn extension(&self, name: &str) -> Option<&str> {
+        let lowercase_name = name.to_lowercase();
+
+        // The later entries override the former ones
+        let exact =
+            self.extensions.iter().rev().find(|(suffix, _)| name.ends_with(suffix.as_str()));
+        let ignoring_case = || {
+            self.extensions
+                .iter()
+                .rev()
+                .find(|(suffix, _)| lowercase_name.ends_with(&suffix.to_lowercase()))
+        };
+
+        exact
+            .or_else(ignoring_case)
+            .map(|(_, value)| value.as_str())
+            .filter(|&value| is_colored(value))
+    }
+
+    /// Returns `true` if the symbolic links take the color of their targets.
+    #[inline]
+    pub fn link_as_target(&self) -> bool {
+        self.link_as_target
+    }
+
+    /// Returns `true` if the targets of the symbolic links must be checked to color them.
+    pub fn checks_link_targets(&self) -> bool {
+        self.link_as_target
+            || self.indicator(Orphan).is_some()
+            || self.indicator(MissingFile).is_some()
+    }
+
+    /// Paint the `text` with the `color` parameters, resetting the terminal before the
+    /// first one.
+    pub fn paint(&self, color: &str, text: &str) -> String {
+        let value = |indicator: Indicator| self.indicators[indicator as usize].as_str();
+        let (left, right) = (value(LeftCode), value(RightCode));
+
+        let end = if value(EndCode).is_empty() {
+            format!("{}{}{}", left, value(Reset), right)
+        } else {
+            value(EndCode).to_string()
+        };
+
+        let reset = if self.used.replace(true) { "" } else { end.as_str() };
+
+        format!("{}{}{}{}{}{}", reset, left, color, right, text, end)
+    }
+}
+
+impl Default for LsColors {
+    fn default() -> Self {
+        LsColors::new()
+    }
+}
+
+/// The key of a entry of `LS_COLORS`.
+enum Key {
+    Indicator(Indicator),
+    Suffix(String),
+}
+
+/// Returns `true` if the parameters of the `value` are not empty or a reset.
+fn is_colored(value: &str) -> bool {
+    !matches!(value, "" | "0" | "00")
+}
+
+/// Replace the escapes of the value at the start of `input`, until the end of the entry
+/// or a `=` if it is the `key`, returning the rest after it.
+fn unescape(input: &[u8], key: bool) -> Result<(String, &[u8]), ParseError> {
+    let mut value = Vec::new();
+    let mut i = 0;
+
+    while let Some(&byte) = input.get(i) {
+        i += 1;
+
+        match byte {
+            b':' => {
+                i -= 1;
+                break;
+            },
+            b'=' if key => {
+                i -= 1;
+                break;
+            },
+            b'\\' => {
+                let escaped = *input.get(i).ok_or(ParseError)?;
+                i += 1;
+
+                let code = match escaped {
+                    b'0'..=b'7' => {
+                        let mut code = u32::from(escaped - b'0');
+                        for _ in 0..2 {
+                            match input.get(i) {
+                                Some(digit @ b'0'..=b'7') => {
+                                    code = code * 8 + u32::from(digit - b'0');
+                                    i += 1;
+                                },
+                                _ => break,
+                            }
+                        }
+                        code as u8
+                    },
+                    b'x' => {
+                        let mut code = 0;
+                        for _ in 0..2 {
+                            match input.get(i).and_then(|&digit| (digit as char).to_digit(16)) {
+                                Some(digit) => {
+                                    code = code * 16 + digit;
+                                    i += 1;
+                                },
+                                None => break,
+                            }
+                        }
+                        code as u8
+                    },
+                    b'a' => 0x07,
+                    b'b' => 0x08,
+                    b'e' => 0x1b,
+                    b'f' => 0x0c,
+                    b'n' => b'\n',
+                    b'r' => b'\r',
+                    b't' => b'\t',
+                    b'v' => 0x0b,
+                    b'?' => 0x7f,
+                    b'_' => b' ',
+                    other => other,
+                };
+                value.push(code);
+            },
+            b'^' => {
+                let code = match *input.get(i).ok_or(ParseError)? {
+                    b'?' => 0x7f,
+                    control @ b'@'..=b'~' => control & 0x1f,
+                    _ => return Err(ParseError),
+                };
+                i += 1;
+                value.push(code);
+            },
+            _ => value.push(byte),
+        }
+    }
+
+    Ok((String::from_utf8_lossy(&value).into_owned(), &input[i..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let colors = LsColors::parse("di=01;31:*.tar=04:ln=target:ex=00:mi=\\e^[").unwrap();
+
+        assert_eq!(colors.indicator(Directory), Some("01;31"));
+        assert_eq!(colors.indicator(Fifo), Some("33"));
+        assert_eq!(colors.indicator(Executable), None);
+        assert_eq!(colors.indicator(MissingFile), Some("\x1b\x1b"));
+        assert_eq!(colors.extension("a.tar"), Some("04"));
+        assert_eq!(colors.extension("a.TAR"), Some("04"));
+        assert_eq!(colors.extension("a.zip"), None);
+        assert!(colors.link_as_target());
+
+        assert_eq!(LsColors::parse("xx=1").unwrap_err(), ParseError);
+        assert_eq!(LsColors::parse("di").unwrap_err(), ParseError);
+    }
+
+    #[test]
+    fn paint() {
+        let colors = LsColors::new();
+        assert_eq!(colors.paint("01;34", "dir"), "\x1b[0m\x1b[01;34mdir\x1b[0m");
+        assert_eq!(colors.paint("33", "fifo"), "\x1b[33mfifo\x1b[0m");
+
+        let colors = LsColors::parse("lc=<:rc=>:ec=E").unwrap();
+        assert_eq!(colors.paint("1", "a"), "E<1>aE");
+    }
+}
@@ -1,8 +1,8 @@
-//! Helper functions to deal with FIFO special file.
+//! Helper functions to deal with FIFO and other special files.
 
-use std::{ffi::CString, io, io::Error, path::Path};
+use std::{ffi::CString, io, io::Error, os::unix::ffi::OsStrExt, path::Path};
 
-use libc::{self, mode_t};
+use libc::{self, dev_t, mode_t};
 
 /// Creates FIFO special file with name `filepath` with `mode` permissions.
 /// Inspired by crate `unix_named_pipe`
@@ -10,9 +10,8 @@ use libc::{self, mode_t};
 /// # Errors
 /// If a internal call set a errno (I/O OS error), an error variant will be returned.
 #[inline]
-pub fn mkfifo(filepath: &str, mode: u32) -> io::Result<()> {
-    let path: &Path = Path::new(filepath);
-    let path = CString::new(path.to_str().unwrap())?;
+pub fn mkfifo(filepath: impl AsRef<Path>, mode: u32) -> io::Result<()> {
+    let path = CString::new(filepath.as_ref().as_os_str().as_bytes())?;
     let result = unsafe { libc::mkfifo(path.as_ptr(), mode as mode_t) };
 
     match result {
@@ -20,3 +19,19 @@ pub fn mkfifo(filepath: &str, mode: u32) -> io::Result<()> {
         _ => Err(Error::last_os_error()),
     }
 }
+
+/// Creates special file with name `filepath` with the type and permissions of `mode`, and
+/// the device number `device` if it is a block or character special file.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[inline]
+pub fn mknod(filepath: impl AsRef<Path>, mode: u32, device: u64) -> io::Result<()> {
+    let path = CString::new(filepath.as_ref().as_os_str().as_bytes())?;
+    let result = unsafe { libc::mknod(path.as_ptr(), mode as mode_t, device as dev_t) };
+
+    match result {
+        0 => Ok(()),
+        _ => Err(Error::last_os_error()),
+    }
+}
@@ -1,82 +1,184 @@
-//! Module with wrappers for libc mkstemp(3), mkdtemp(3).
+//! Module to create temporary files and directories with the names of a template, like
+//! mkstemp(3) and mkdtemp(3).
+//!
+//! The random names are tried until one that doesn't exist is found, and the files are
+//! created with `O_EXCL`, so an existing file is never used as the temporary one.
 
 use std::{
-    fmt::{self, Display},
-    fs::File,
-    io::{self, Error as IOError},
-    os::unix::io::FromRawFd,
+    ffi::{OsStr, OsString},
+    fs::{self, DirBuilder, File, OpenOptions},
+    io,
+    os::unix::{
+        ffi::{OsStrExt, OsStringExt},
+        fs::{DirBuilderExt, OpenOptionsExt},
+    },
+    path::{Path, PathBuf},
 };
 
-/// A struct that represents a mktemp(3) result.
-/// This includes the file created, and the path to that file.
-#[derive(Debug)]
-pub struct Mktemp {
-    pub file: File,
-    pub path: String,
+use crate::random::RandomSource;
+
+/// The characters of the random part of the names.
+const CHARACTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// The number of names tried before giving up, like `TMP_MAX` of glibc.
+const ATTEMPTS: usize = 62 * 62 * 62;
+
+/// The minimum number of `X` characters of a template.
+pub const MIN_X: usize = 3;
+
+/// A template of the names of temporary files, with a run of `X` characters that is
+/// replaced by random characters in each name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    bytes: Vec<u8>,
+    start: usize,
+    end: usize,
 }
 
-impl Display for Mktemp {
-    #[inline]
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.pad(&self.path)
+impl Template {
+    /// Creates a template from `template`, where the run of `X` characters right before
+    /// the last `suffix_len` bytes is replaced.
+    ///
+    /// Returns `None` if that run has less than [`MIN_X`] characters.
+    pub fn new(template: impl AsRef<OsStr>, suffix_len: usize) -> Option<Self> {
+        let bytes = template.as_ref().as_bytes().to_vec();
+        let end = bytes.len().checked_sub(suffix_len)?;
+        let start = bytes[..end].iter().rposition(|&b| b != b'X').map_or(0, |i| i + 1);
+
+        if end - start < MIN_X {
+            return None;
+        }
+
+        Some(Template { bytes, start, end })
+    }
+
+    /// The template relative to `dir`.
+    pub fn in_dir(self, dir: impl AsRef<Path>) -> Self {
+        let bytes = dir.as_ref().join(OsStr::from_bytes(&self.bytes)).into_os_string().into_vec();
+        let offset = bytes.len() - self.bytes.len();
+
+        Template { bytes, start: self.start + offset, end: self.end + offset }
+    }
+
+    /// The path of the template, with the `X` characters.
+    pub fn path(&self) -> &Path {
+        Path::new(OsStr::from_bytes(&self.bytes))
+    }
+
+    /// Get a name of the template, with random characters from `random`.
+    pub fn name(&self, random: &mut RandomSource) -> PathBuf {
+        let mut bytes = self.bytes.clone();
+
+        for byte in &mut bytes[self.start..self.end] {
+            // Only the generator can fail, on reading
+            let index = random.up_to(CHARACTERS.len() as u64 - 1).unwrap_or(0);
+            *byte = CHARACTERS[index as usize];
+        }
+
+        PathBuf::from(OsString::from_vec(bytes))
+    }
+
+    /// Creates a file with a name of the template, readable and writable only by its
+    /// owner.
+    ///
+    /// # Errors
+    /// If the file can't be created, or all the names tried exist, an error variant will
+    /// be returned.
+    pub fn create_file(&self) -> io::Result<(File, PathBuf)> {
+        self.try_names(|path| {
+            OpenOptions::new().read(true).write(true).create_new(true).mode(0o600).open(path)
+        })
     }
-}
 
-/// Creates a temporary file based on the given `template`.
-///
-/// The `template` should end with a number of `X` characters.
-///
-/// Some libc implementations requires the `template` to end with a minimum number of X
-/// characters (for example, 6 in glibc as of version 2.29).
-///
-/// # Errors
-/// If a internal call set a errno (I/O OS error), an error variant will be returned.
-#[inline]
-pub fn mkstemp(template: &str) -> io::Result<Mktemp> {
-    let mut template_cstr = {
-        let mut t = String::new();
-        t.push_str(template);
-        t.push('\0');
-        t
-    };
-
-    let fd = unsafe { libc::mkstemp(template_cstr.as_mut_ptr() as *mut libc::c_char) };
-
-    match fd {
-        -1 => Err(IOError::last_os_error()),
-        _ => {
-            template_cstr.pop();
-            Ok(Mktemp { file: unsafe { File::from_raw_fd(fd) }, path: template_cstr })
-        },
+    /// Creates a directory with a name of the template, only accessible by its owner.
+    ///
+    /// # Errors
+    /// If the directory can't be created, or all the names tried exist, an error variant
+    /// will be returned.
+    pub fn create_dir(&self) -> io::Result<PathBuf> {
+        self.try_names(|path| DirBuilder::new().mode(0o700).create(path)).map(|((), path)| path)
+    }
+
+    /// Get a name of the template that no file has, without creating it.
+    ///
+    /// This is unsafe, since another file may be created with that name before it is
+    /// used.
+    ///
+    /// # Errors
+    /// If the existence of the files can't be known, or all the names tried exist, an
+    /// error variant will be returned.
+    pub fn unused_name(&self) -> io::Result<PathBuf> {
+        self.try_names(|path| match fs::symlink_metadata(path) {
+            Ok(_) => Err(io::ErrorKind::AlreadyExists.into()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+        })
+        .map(|((), path)| path)
+    }
+
+    /// Try `create` with random names until one doesn't exist.
+    fn try_names<T>(
+        &self, mut create: impl FnMut(&Path) -> io::Result<T>,
+    ) -> io::Result<(T, PathBuf)> {
+        let mut random = RandomSource::new();
+
+        for _ in 0..ATTEMPTS {
+            let path = self.name(&mut random);
+
+            match create(&path) {
+                Ok(created) => return Ok((created, path)),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(io::ErrorKind::AlreadyExists.into())
     }
 }
 
-/// Creates a temporary directory based on the given `template`.
-///
-/// The `template` should end with a number of `X` characters.
-///
-/// Some libc implementations requires the `template` to end with a minimum number of X
-/// characters (for example, 6 in glibc as of version 2.29).
-///
-/// # Errors
-/// If a internal call set a errno (I/O OS error), an error variant will be returned.
-#[inline]
-pub fn mkdtemp(template: &str) -> io::Result<String> {
-    let mut template_cstr = {
-        let mut t = String::new();
-        t.push_str(template);
-        t.push('\0');
-        t
-    };
-
-    let ptr = unsafe {
-        libc::mkdtemp(template_cstr.as_mut_ptr() as *mut libc::c_char) as *const libc::c_char
-    };
-
-    if ptr.is_null() {
-        Err(IOError::last_os_error())
-    } else {
-        template_cstr.pop();
-        Ok(template_cstr)
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    #[test]
+    fn template_names() {
+        let template = Template::new("a.XXXXbX.XXXXXX.txt", 4).unwrap();
+        let mut random = RandomSource::new();
+        let name = template.name(&mut random);
+        let name = name.as_os_str().as_bytes();
+
+        assert_eq!(&name[..9], b"a.XXXXbX.");
+        assert_eq!(&name[15..], b".txt");
+        assert!(name[9..15].iter().all(|b| b.is_ascii_alphanumeric()));
+
+        assert!(Template::new("aXX", 0).is_none());
+        assert!(Template::new("aXXXb", 0).is_none());
+        assert!(Template::new("aXXX", 4).is_none());
+        assert!(Template::new("XXX", 0).is_some());
+
+        let template = Template::new("bXXX", 0).unwrap().in_dir("/tmp");
+        assert_eq!(template.path(), Path::new("/tmp/bXXX"));
+        assert_eq!(&template.name(&mut random).as_os_str().as_bytes()[..6], b"/tmp/b");
+    }
+
+    #[test]
+    fn create_temporary() {
+        let template = Template::new(std::env::temp_dir().join("core.mktemp.XXXXXX"), 0).unwrap();
+
+        let dir = template.create_dir().unwrap();
+        assert!(dir.is_dir());
+        assert_eq!(fs::metadata(&dir).unwrap().permissions().mode() & 0o777, 0o700);
+
+        let template = Template::new(dir.join("XXXXXX"), 0).unwrap();
+        let (_, file) = template.create_file().unwrap();
+        assert!(file.is_file());
+        assert_eq!(fs::metadata(&file).unwrap().permissions().mode() & 0o777, 0o600);
+
+        let name = template.unused_name().unwrap();
+        assert!(fs::symlink_metadata(&name).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,325 @@
+//! The Mode module parses the modes of files given like to `chmod`, as octal numbers or
+//! symbolic modes, and applies them to the modes the files have.
+//!
+//! ## About
+//! A symbolic mode is a list of clauses separated by commas, each with the users whose
+//! permissions are changed (`u`, `g`, `o` or `a`) and one or more operations (`+`, `-` or
+//! `=`) with the permissions (`rwxXst`) or the user whose permissions are copied (`u`,
+//! `g` or `o`). Without users the permissions are changed for all of them but the ones
+//! set in the umask.
+//!
+//! Like GNU, the set-user-ID and set-group-ID bits of directories are kept unless the
+//! mode mentions them, and the octal numbers with fewer than five digits can set them but
+//! not clear them.
+
+use std::{
+    error::Error as StdError,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+const SET_UID: u32 = 0o4000;
+const SET_GID: u32 = 0o2000;
+const STICKY: u32 = 0o1000;
+const USER: u32 = 0o700;
+const GROUP: u32 = 0o070;
+const OTHERS: u32 = 0o007;
+const READ: u32 = 0o444;
+const WRITE: u32 = 0o222;
+const EXECUTE: u32 = 0o111;
+/// All the bits that can be changed.
+const ALL: u32 = 0o7777;
+
+/// A mode, to apply to the modes of files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mode {
+    changes: Vec<Change>,
+}
+
+/// A operation of a mode, on the bits of the users it affects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Change {
+    op: Op,
+    kind: Kind,
+    /// The bits of the users, or 0 for all of them but the ones in the umask.
+    affected: u32,
+    value: u32,
+    /// The bits the mode mentions, that are changed in directories.
+    mentioned: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    /// `=`
+    Set,
+    /// `+`
+    Add,
+    /// `-`
+    Remove,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// The bits in the value.
+    Bits,
+    /// The bits in the value, and the execute ones if the file is a directory or anyone
+    /// can execute it, with `X`.
+    ExecuteIf,
+    /// The bits the file has in the value, for all the users, with `u`, `g` or `o`.
+    Copy,
+}
+
+/// Error of a mode that can't be parsed, it holds the mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidMode(pub String);
+
+impl Display for InvalidMode {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid mode '{}'", self.0)
+    }
+}
+
+impl StdError for InvalidMode {}
+
+impl FromStr for Mode {
+    type Err = InvalidMode;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidMode(mode.to_string());
+        let bytes = mode.as_bytes();
+
+        if matches!(bytes.first(), Some(b'0'..=b'7')) {
+            if !bytes.iter().all(|byte| (b'0'..=b'7').contains(byte)) {
+                return Err(invalid());
+            }
+
+            let value = match u32::from_str_radix(mode, 8) {
+                Ok(value) if value <= ALL => value,
+                _ => return Err(invalid()),
+            };
+
+            let mentioned = if bytes.len() < 5 {
+                (value & (SET_UID | SET_GID)) | STICKY | USER | GROUP | OTHERS
+            } else {
+                ALL
+            };
+
+            let change = Change { op: Op::Set, kind: Kind::Bits, affected: ALL, value, mentioned };
+            return Ok(Mode { changes: vec![change] });
+        }
+
+        let mut changes = Vec::new();
+        let mut i = 0;
+
+        loop {
+            let mut affected = 0;
+
+            while let Some(byte) = bytes.get(i) {
+                affected |= match byte {
+                    b'u' => SET_UID | USER,
+                    b'g' => SET_GID | GROUP,
+                    b'o' => STICKY | OTHERS,
+                    b'a' => ALL,
+                    _ => break,
+                };
+                i += 1;
+            }
+
+            loop {
+                let op = match bytes.get(i) {
+                    Some(b'=') => Op::Set,
+                    Some(b'+') => Op::Add,
+                    Some(b'-') => Op::Remove,
+                    _ => return Err(invalid()),
+                };
+                i += 1;
+
+                let copied = match bytes.get(i) {
+                    Some(b'u') => Some(USER),
+                    Some(b'g') => Some(GROUP),
+                    Some(b'o') => Some(OTHERS),
+                    _ => None,
+                };
+
+                let (kind, value) = match copied {
+                    Some(value) => {
+                        i += 1;
+                        (Kind::Copy, value)
+                    },
+                    None => {
+                        let (mut kind, mut value) = (Kind::Bits, 0);
+
+                        while let Some(byte) = bytes.get(i) {
+                            match byte {
+                                b'r' => value |= READ,
+                                b'w' => value |= WRITE,
+                                b'x' => value |= EXECUTE,
+                                b'X' => kind = Kind::ExecuteIf,
+                                b's' => value |= SET_UID | SET_GID,
+                                b't' => value |= STICKY,
+                                _ => break,
+                            }
+                            i += 1;
+                        }
+
+                        (kind, value)
+                    },
+                };
+
+                let mentioned = if affected == 0 { value } else { affected & value };
+                changes.push(Change { op, kind, affected, value, mentioned });
+
+                if !matches!(bytes.get(i), Some(b'=') | Some(b'+') | Some(b'-')) {
+                    break;
+                }
+            }
+
+            match bytes.get(i) {
+                None => break,
+                Some(b',') => i += 1,
+                Some(_) => return Err(invalid()),
+            }
+        }
+
+        Ok(Mode { changes })
+    }
+}
+
+impl Mode {
+    /// A mode that sets all the bits to the ones in `bits`, the set-user-ID and
+    /// set-group-ID bits of directories included, like the mode of a reference file.
+    pub fn from_bits(bits: u32) -> Self {
+        let value = bits & ALL;
+        let change = Change { op: Op::Set, kind: Kind::Bits, affected: ALL, value, mentioned: ALL };
+
+        Mode { changes: vec![change] }
+    }
+
+    /// Apply the mode to the `mode` of a file, that is a directory if `is_dir`, with the
+    /// `umask` of the process, returning the new mode.
+    pub fn apply(&self, mode: u32, is_dir: bool, umask: u32) -> u32 {
+        self.apply_with_bits(mode, is_dir, umask).0
+    }
+
+    /// Like [`Mode::apply`], but also returning the bits the mode sets or clears, that
+    /// are the ones of the new mode that don't come from `mode`.
+    pub fn apply_with_bits(&self, mode: u32, is_dir: bool, umask: u32) -> (u32, u32) {
+        let mut new = mode & ALL;
+        let mut bits = 0;
+
+        for change in &self.changes {
+            let omitted = if is_dir { (SET_UID | SET_GID) & !change.mentioned } else { 0 };
+            let mut value = change.value;
+
+            match change.kind {
+                Kind::Bits => {},
+                Kind::ExecuteIf => {
+                    if is_dir || new & EXECUTE != 0 {
+                        value |= EXECUTE;
+                    }
+                },
+                Kind::Copy => {
+                    value &= new;
+
+                    for &bits in &[READ, WRITE, EXECUTE] {
+                        if value & bits != 0 {
+                            value |= bits;
+                        }
+                    }
+                },
+            }
+
+            let affected = if change.affected == 0 { !umask } else { change.affected };
+            value &= affected & !omitted;
+
+            new = match change.op {
+                Op::Set => {
+                    let kept = (if change.affected == 0 { 0 } else { !change.affected }) | omitted;
+                    bits |= ALL & !kept;
+                    (new & kept) | value
+                },
+                Op::Add => {
+                    bits |= value;
+                    new | value
+                },
+                Op::Remove => {
+                    bits |= value;
+                    new & !value
+                },
+            };
+        }
+
+        (new, bits)
+    }
+}
+
+/// Get the umask of the process.
+#[cfg(unix)]
+pub fn umask() -> u32 {
+    // The umask can only be get by setting it
+    let umask = unsafe {
+        let umask = libc::umask(0);
+        libc::umask(umask);
+        umask
+    };
+
+    // The `mode_t` isn't of 32 bits on all the systems
+    #[allow(clippy::useless_conversion)]
+    let umask = u32::from(umask);
+
+    umask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(mode: &str, old: u32, is_dir: bool) -> u32 {
+        mode.parse::<Mode>().unwrap().apply(old, is_dir, 0o022)
+    }
+
+    #[test]
+    fn octal() {
+        assert_eq!(apply("700", 0o777, false), 0o700);
+        assert_eq!(apply("4755", 0o777, false), 0o4755);
+        assert_eq!(apply("755", 0o2777, true), 0o2755);
+        assert_eq!(apply("00755", 0o2777, true), 0o755);
+        assert_eq!(apply("0", 0o777, false), 0);
+        assert_eq!(Mode::from_bits(0o100644).apply(0o2777, true, 0o022), 0o644);
+    }
+
+    #[test]
+    fn symbolic() {
+        assert_eq!(apply("u=rwx,go=rx,+t", 0o777, true), 0o1755);
+        assert_eq!(apply("+w", 0o555, false), 0o755);
+        assert_eq!(apply("a+w", 0o555, false), 0o777);
+        assert_eq!(apply("g+s", 0o777, true), 0o2777);
+        assert_eq!(apply("u=rw,g=u,o=g-w", 0o777, true), 0o664);
+        assert_eq!(apply("a=r,u+X", 0o777, true), 0o544);
+        assert_eq!(apply("a=r,u+X", 0o777, false), 0o444);
+        assert_eq!(apply("u+X", 0o610, false), 0o710);
+        assert_eq!(apply("=", 0o777, true), 0);
+        assert_eq!(apply("o=rwx,-x", 0o777, true), 0o666);
+        assert_eq!(apply("+rwx-w,u=", 0o777, true), 0o077);
+        assert_eq!(apply("u-s", 0o4755, true), 0o755);
+    }
+
+    #[test]
+    fn changed_bits() {
+        let with_bits =
+            |mode: &str| mode.parse::<Mode>().unwrap().apply_with_bits(0o777, true, 0o022);
+
+        assert_eq!(with_bits("+t"), (0o1777, 0o1000));
+        assert_eq!(with_bits("go-w"), (0o755, 0o022));
+        assert_eq!(with_bits("o=t"), (0o1770, 0o1007));
+        assert_eq!(with_bits("755"), (0o755, 0o1777));
+    }
+
+    #[test]
+    fn invalid() {
+        for mode in &["", "8", "77777", "u", "u+z", "a+w,", "u+x,g"] {
+            assert_eq!(mode.parse::<Mode>(), Err(InvalidMode(mode.to_string())));
+        }
+    }
+}
@@ -1,76 +1,176 @@
 //! Module for OS processes and types abstractions.
+pub mod hostname;
+
+#[cfg(unix)]
+pub mod dir;
+#[cfg(unix)]
+pub mod fs_usage;
+#[cfg(unix)]
 pub mod group;
+#[cfg(unix)]
+pub mod limits;
+#[cfg(unix)]
 pub mod login_name;
+#[cfg(unix)]
+pub mod mount_table;
+#[cfg(unix)]
 pub mod passwd;
+#[cfg(unix)]
 pub mod process;
+#[cfg(unix)]
 pub mod resource;
+#[cfg(unix)]
 pub mod time;
+#[cfg(unix)]
 pub mod tty;
+#[cfg(unix)]
 pub mod utsname;
 
 // Specific Modules
-#[cfg(not(any(target_os = "fuchsia", target_os = "haiku")))]
+#[cfg(windows)]
+#[path = "os/group_windows.rs"]
+pub mod group;
+
+#[cfg(windows)]
+#[path = "os/passwd_windows.rs"]
+pub mod passwd;
+
+#[cfg(windows)]
+#[path = "os/sid_windows.rs"]
+mod sid_windows;
+
+#[cfg(all(unix, not(any(target_os = "fuchsia", target_os = "haiku"))))]
 pub mod load;
 
 #[cfg(any(target_os = "netbsd", target_os = "openbsd", target_os = "solaris"))]
 pub mod utmp;
 
-#[cfg(not(any(target_os = "fuchsia", target_os = "haiku", target_os = "openbsd")))]
+#[cfg(all(unix, not(any(target_os = "fuchsia", target_os = "haiku", target_os = "openbsd"))))]
 pub mod utmpx;
 
 #[cfg(any(target_os = "freebsd", target_os = "macos"))]
 pub mod audit;
 
+#[cfg(target_os = "linux")]
+#[path = "os/audit_linux.rs"]
+pub mod audit;
+
 #[cfg(target_os = "openbsd")]
 pub mod routing_table;
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod acl;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod selinux;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod xattr;
+
+#[cfg(unix)]
 use libc::{
-    c_int, getegid, geteuid, getgid, getuid, gid_t, pid_t, suseconds_t, time_t, timeval, tm, uid_t,
+    c_int, getegid, geteuid, getgid, getuid, gid_t, pid_t, suseconds_t, time_t, timespec, timeval,
+    tm, uid_t,
 };
 
+#[cfg(unix)]
 pub type Tm = tm;
 
 /// Time stamp type used on system structures.
+#[cfg(unix)]
 pub type TimeVal = timeval;
 
+/// Time stamp type with nanoseconds used on system structures.
+#[cfg(unix)]
+pub type TimeSpec = timespec;
+
 /// Group ID type.
+#[cfg(unix)]
 pub type Gid = gid_t;
 
 /// User ID type.
+#[cfg(unix)]
 pub type Uid = uid_t;
 
+/// Group ID type.
+///
+/// On Windows it is the relative ID (RID) of the group SID.
+#[cfg(windows)]
+pub type Gid = u32;
+
+/// User ID type.
+///
+/// On Windows it is the relative ID (RID) of the user SID.
+#[cfg(windows)]
+pub type Uid = u32;
+
 /// Process ID Type.
+#[cfg(unix)]
 pub type Pid = pid_t;
 
 /// Passwd time type.
+#[cfg(unix)]
 pub type Time = time_t;
 
 /// Passwd field type.
+#[cfg(unix)]
 pub type Fields = c_int;
 
 /// Field for [`TimeVal`] in microseconds.
+#[cfg(unix)]
 pub type Susec = suseconds_t;
 
 /// Get the current running process user effective group id.
+#[cfg(unix)]
 #[inline]
 pub fn get_effective_gid() -> Uid {
     unsafe { getegid() }
 }
 
 /// Get the current running process user real group id.
+#[cfg(unix)]
 #[inline]
 pub fn get_real_gid() -> Uid {
     unsafe { getgid() }
 }
 
 /// Get the current running process user effective user id.
+#[cfg(unix)]
 #[inline]
 pub fn get_effective_uid() -> Uid {
     unsafe { geteuid() }
 }
 
 /// Get the current running process user real user id.
+#[cfg(unix)]
 #[inline]
 pub fn get_real_uid() -> Uid {
     unsafe { getuid() }
 }
+
+// The errno of the calling thread, set to 0 before the calls that only tell apart their
+// errors by it
+#[cfg(any(target_os = "linux", target_os = "fuchsia"))]
+pub(crate) unsafe fn errno() -> *mut c_int {
+    libc::__errno_location()
+}
+
+#[cfg(any(target_os = "android", target_os = "netbsd", target_os = "openbsd"))]
+pub(crate) unsafe fn errno() -> *mut c_int {
+    libc::__errno()
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "dragonfly"))]
+pub(crate) unsafe fn errno() -> *mut c_int {
+    libc::__error()
+}
+
+#[cfg(any(target_os = "solaris", target_os = "illumos"))]
+pub(crate) unsafe fn errno() -> *mut c_int {
+    libc::___errno()
+}
+
+#[cfg(target_os = "haiku")]
+pub(crate) unsafe fn errno() -> *mut c_int {
+    libc::_errnop()
+}
@@ -0,0 +1,51 @@
+//! Module for the POSIX access control lists (ACLs) of the files.
+//!
+//! On Linux the ACL of a file is kept on its `system.posix_acl_access` extended
+//! attribute, and the default ACL a directory gives to the files created in it on its
+//! `system.posix_acl_default` one, as described on the `acl(5)` man page. Their values
+//! have the same binary form on all the file systems, so the ACLs are copied as them.
+
+use std::{io, path::Path};
+
+use super::xattr;
+
+/// The name of the extended attribute of the access ACLs.
+pub const ACCESS_ATTRIBUTE: &[u8] = b"system.posix_acl_access";
+
+/// The name of the extended attribute of the default ACLs of the directories.
+pub const DEFAULT_ATTRIBUTE: &[u8] = b"system.posix_acl_default";
+
+/// Copy the ACLs of the file in `source` to `dest`, removing the ones `dest` has that
+/// `source` doesn't, like the ones it got from the default ACL of its directory.
+///
+/// The permissions of the owner, group and others are part of the ACLs, so they are set
+/// as the ones of `source` too.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+pub fn copy_acl(source: impl AsRef<Path>, dest: impl AsRef<Path>) -> io::Result<()> {
+    let (source, dest) = (source.as_ref(), dest.as_ref());
+
+    for &name in &[ACCESS_ATTRIBUTE, DEFAULT_ATTRIBUTE] {
+        match xattr::get(source, name, true) {
+            Ok(acl) => xattr::set(dest, name, &acl, true)?,
+            Err(err) if !is_missing(&err) => return Err(err),
+            Err(_) => match xattr::remove(dest, name, true) {
+                Ok(_) => {},
+                Err(err) if is_missing(&err) => {},
+                Err(err) => return Err(err),
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns `true` if the `err` is because the file has no ACL, or its file system has no
+/// ACLs.
+fn is_missing(err: &io::Error) -> bool {
+    match err.raw_os_error() {
+        Some(errno) => errno == libc::ENODATA || errno == libc::ENOTSUP,
+        None => false,
+    }
+}
@@ -4,14 +4,17 @@
 //! the names defined on `GETAUDIT(2)` will have a '¹' on them.
 
 use std::{
+    convert::TryFrom,
+    error::Error as StdError,
     fmt::{self, Display},
     io,
-    mem::MaybeUninit,
+    mem::{self, MaybeUninit},
+    ops::{BitAnd, BitOr},
 };
 
 #[cfg(target_os = "macos")]
 use libc::dev_t;
-use libc::{c_int, c_uint, pid_t, uid_t};
+use libc::{c_int, c_uint, c_void, pid_t, uid_t};
 
 /// This type contains the audit identifier which is recorded in the audit log for each
 /// event the process caused.
@@ -25,6 +28,27 @@ pub type AuditUserId = uid_t;
 /// ¹Same as `au_asid_t`
 pub type AuditSessionId = pid_t;
 
+/// Error type for [`AuditCommand`] and [`AuditCondition`] conversion.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub enum Error {
+    /// When there is no [`AuditCommand`] related to this number.
+    NoCommand(c_int),
+    /// When there is no [`AuditCondition`] related to this number.
+    NoCondition(c_int),
+}
+
+impl Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoCommand(num) => write!(f, "There is no auditon command related to {}", num),
+            Self::NoCondition(num) => write!(f, "There is no audit condition related to {}", num),
+        }
+    }
+}
+
+impl StdError for Error {}
+
 pub type AuditEvent = u16;
 pub type AuditEmod = u16;
 pub type AuditClass = u32;
@@ -127,7 +151,285 @@ impl Display for AuditInfoAddr {
     }
 }
 
-// TODO: Add other system calls related to audit
+/// Commands accepted by `auditon(2)`.
+///
+/// Only the non-legacy commands are listed, the `A_OLD*` variants were superseded by
+/// these.
+#[repr(i32)]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub enum AuditCommand {
+    /// Get the kernel preselection mask.
+    ///
+    /// ¹Same as `A_GETKMASK`
+    GetKernelMask = 4,
+    /// Set the kernel preselection mask.
+    ///
+    /// ¹Same as `A_SETKMASK`
+    SetKernelMask = 5,
+    /// Get the audit statistics.
+    ///
+    /// ¹Same as `A_GETSTAT`
+    GetStatistics = 12,
+    /// Set the audit statistics.
+    ///
+    /// ¹Same as `A_SETSTAT`
+    SetStatistics = 13,
+    /// Set the maximum audit trail file size.
+    ///
+    /// ¹Same as `A_SETFSIZE`
+    SetFileSize = 26,
+    /// Get the current audit trail file size and the maximum.
+    ///
+    /// ¹Same as `A_GETFSIZE`
+    GetFileSize = 27,
+    /// Get the audit policy flags.
+    ///
+    /// ¹Same as `A_GETPOLICY`
+    GetPolicy = 33,
+    /// Set the audit policy flags.
+    ///
+    /// ¹Same as `A_SETPOLICY`
+    SetPolicy = 34,
+    /// Get the kernel audit queue parameters.
+    ///
+    /// ¹Same as `A_GETQCTRL`
+    GetQueueControl = 35,
+    /// Set the kernel audit queue parameters.
+    ///
+    /// ¹Same as `A_SETQCTRL`
+    SetQueueControl = 36,
+    /// Get the current audit condition.
+    ///
+    /// ¹Same as `A_GETCOND`
+    GetCondition = 37,
+    /// Set the current audit condition.
+    ///
+    /// ¹Same as `A_SETCOND`
+    SetCondition = 38,
+}
+
+impl TryFrom<c_int> for AuditCommand {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(num: c_int) -> Result<Self, Error> {
+        match num {
+            4 => Ok(Self::GetKernelMask),
+            5 => Ok(Self::SetKernelMask),
+            12 => Ok(Self::GetStatistics),
+            13 => Ok(Self::SetStatistics),
+            26 => Ok(Self::SetFileSize),
+            27 => Ok(Self::GetFileSize),
+            33 => Ok(Self::GetPolicy),
+            34 => Ok(Self::SetPolicy),
+            35 => Ok(Self::GetQueueControl),
+            36 => Ok(Self::SetQueueControl),
+            37 => Ok(Self::GetCondition),
+            38 => Ok(Self::SetCondition),
+            _ => Err(Error::NoCommand(num)),
+        }
+    }
+}
+
+impl From<AuditCommand> for c_int {
+    #[inline]
+    fn from(cmd: AuditCommand) -> Self {
+        cmd as c_int
+    }
+}
+
+/// The audit condition, which says if the audit system is auditing events or not.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub enum AuditCondition {
+    /// Auditing was never configured.
+    ///
+    /// ¹Same as `AUC_UNSET`
+    Unset,
+    /// Auditing is enabled and events are being recorded.
+    ///
+    /// ¹Same as `AUC_AUDITING`
+    Auditing,
+    /// Auditing is enabled but no events are being recorded.
+    ///
+    /// ¹Same as `AUC_NOAUDIT`
+    NoAudit,
+    /// Auditing is disabled.
+    ///
+    /// ¹Same as `AUC_DISABLED`
+    Disabled,
+}
+
+impl AuditCondition {
+    /// Returns `true` if the audit system is recording events.
+    #[inline]
+    pub fn is_auditing(self) -> bool {
+        matches!(self, Self::Auditing)
+    }
+}
+
+impl TryFrom<c_int> for AuditCondition {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(num: c_int) -> Result<Self, Error> {
+        match num {
+            0 => Ok(Self::Unset),
+            1 => Ok(Self::Auditing),
+            2 => Ok(Self::NoAudit),
+            -1 => Ok(Self::Disabled),
+            _ => Err(Error::NoCondition(num)),
+        }
+    }
+}
+
+impl From<AuditCondition> for c_int {
+    #[inline]
+    fn from(cond: AuditCondition) -> Self {
+        match cond {
+            AuditCondition::Unset => 0,
+            AuditCondition::Auditing => 1,
+            AuditCondition::NoAudit => 2,
+            AuditCondition::Disabled => -1,
+        }
+    }
+}
+
+impl Display for AuditCondition {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unset => write!(f, "unset"),
+            Self::Auditing => write!(f, "auditing"),
+            Self::NoAudit => write!(f, "noaudit"),
+            Self::Disabled => write!(f, "disabled"),
+        }
+    }
+}
+
+/// A set of audit policy flags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct AuditPolicy(c_int);
+
+impl AuditPolicy {
+    /// Halt the system if an audit record can not be written. (`AUDIT_AHLT`)
+    pub const AHLT: Self = Self(0x0002);
+    /// Add the environment variables to `execve(2)` records. (`AUDIT_ARGE`)
+    pub const ARGE: Self = Self(0x0008);
+    /// Add the command line arguments to `execve(2)` records. (`AUDIT_ARGV`)
+    pub const ARGV: Self = Self(0x0004);
+    /// Suspend processes when the audit storage is full instead of dropping records.
+    /// (`AUDIT_CNT`)
+    pub const CNT: Self = Self(0x0001);
+    /// Add the group list to each record. (`AUDIT_GROUP`)
+    pub const GROUP: Self = Self(0x0080);
+    /// Add the path of the file backing the process. (`AUDIT_PATH`)
+    pub const PATH: Self = Self(0x0200);
+    /// Add a sequence token to each record. (`AUDIT_SEQ`)
+    pub const SEQ: Self = Self(0x0010);
+    /// Add a trailer token to each record. (`AUDIT_TRAIL`)
+    pub const TRAIL: Self = Self(0x0100);
+
+    /// Creates a empty policy set.
+    #[inline]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Creates a policy set from the raw `auditon(2)` bits.
+    #[inline]
+    pub const fn from_bits(bits: c_int) -> Self {
+        Self(bits)
+    }
+
+    /// Get the raw `auditon(2)` bits of the policy set.
+    #[inline]
+    pub const fn bits(self) -> c_int {
+        self.0
+    }
+
+    /// Returns `true` if all flags in `other` are set on `self`.
+    #[inline]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if no flag is set.
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for AuditPolicy {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for AuditPolicy {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl Display for AuditPolicy {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = [
+            (Self::CNT, "cnt"),
+            (Self::AHLT, "ahlt"),
+            (Self::ARGV, "argv"),
+            (Self::ARGE, "arge"),
+            (Self::SEQ, "seq"),
+            (Self::GROUP, "group"),
+            (Self::TRAIL, "trail"),
+            (Self::PATH, "path"),
+        ];
+
+        let mut first = true;
+        for (flag, name) in names.iter() {
+            if self.contains(*flag) {
+                if !first {
+                    write!(f, ",")?;
+                }
+                write!(f, "{}", name)?;
+                first = false;
+            }
+        }
+
+        if first {
+            write!(f, "none")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// This struct holds the parameters of the kernel audit record queue.
+///
+/// ¹Same as `au_qctrl_t`
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Eq, Ord, Hash)]
+#[repr(C)]
+pub struct AuditQueueControl {
+    /// Maximum number of records in the queue.
+    pub aq_hiwater: c_int,
+    /// Queue size to resume processes waiting on a full queue.
+    pub aq_lowater: c_int,
+    /// Maximum size of a audit record, in bytes.
+    pub aq_bufsz: c_int,
+    /// Unused.
+    pub aq_delay: c_int,
+    /// Minimum percentage of free disk space before a warning is triggered.
+    pub aq_minfree: c_int,
+}
+
 extern "C" {
     /// This system call retrieves the active audit session state for the current
     /// process via the `AuditInfo` pointed to by `auditinfo`.
@@ -140,6 +442,12 @@ extern "C" {
     ///
     /// Returns `0` is successful, `-1` otherwise.
     pub fn getaudit_addr(auditinfo_addr: *mut AuditInfoAddr, length: c_int) -> c_int;
+
+    /// This system call manipulates various audit control operations. The type of
+    /// `data` depends on `cmd`, and `length` must be the size of `data`.
+    ///
+    /// Returns `0` is successful, `-1` otherwise.
+    pub fn auditon(cmd: c_int, data: *mut c_void, length: c_uint) -> c_int;
 }
 
 /// Returns the `AuditInfo`.
@@ -159,3 +467,93 @@ pub fn audit_info() -> io::Result<AuditInfo> {
 
     Ok(auditinfo)
 }
+
+/// Calls `auditon(2)` with the `cmd` command, using `data` as the input or output of the
+/// command.
+///
+/// # Safety
+/// The type `T` must be the type `auditon(2)` expects for `cmd`.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[inline]
+pub unsafe fn audit_control<T>(cmd: AuditCommand, data: &mut T) -> io::Result<()> {
+    let res = auditon(cmd.into(), data as *mut T as *mut c_void, mem::size_of::<T>() as c_uint);
+
+    if res == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Returns the current [`AuditPolicy`].
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[inline]
+pub fn audit_policy() -> io::Result<AuditPolicy> {
+    let mut policy: c_int = 0;
+    unsafe { audit_control(AuditCommand::GetPolicy, &mut policy)? };
+    Ok(AuditPolicy::from_bits(policy))
+}
+
+/// Sets the current [`AuditPolicy`].
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[inline]
+pub fn set_audit_policy(policy: AuditPolicy) -> io::Result<()> {
+    let mut policy = policy.bits();
+    unsafe { audit_control(AuditCommand::SetPolicy, &mut policy) }
+}
+
+/// Returns the current [`AuditCondition`].
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[inline]
+pub fn audit_condition() -> io::Result<AuditCondition> {
+    let mut cond: c_int = 0;
+    unsafe { audit_control(AuditCommand::GetCondition, &mut cond)? };
+    AuditCondition::try_from(cond).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Sets the current [`AuditCondition`].
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[inline]
+pub fn set_audit_condition(cond: AuditCondition) -> io::Result<()> {
+    let mut cond = c_int::from(cond);
+    unsafe { audit_control(AuditCommand::SetCondition, &mut cond) }
+}
+
+/// Returns the current [`AuditQueueControl`].
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[inline]
+pub fn audit_queue_control() -> io::Result<AuditQueueControl> {
+    let mut qctrl = AuditQueueControl::default();
+    unsafe { audit_control(AuditCommand::GetQueueControl, &mut qctrl)? };
+    Ok(qctrl)
+}
+
+/// Sets the current [`AuditQueueControl`].
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[inline]
+pub fn set_audit_queue_control(mut qctrl: AuditQueueControl) -> io::Result<()> {
+    unsafe { audit_control(AuditCommand::SetQueueControl, &mut qctrl) }
+}
+
+/// Returns `true` if the audit system is enabled and recording events.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[inline]
+pub fn is_auditing() -> io::Result<bool> {
+    audit_condition().map(AuditCondition::is_auditing)
+}
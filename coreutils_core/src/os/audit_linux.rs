@@ -0,0 +1,326 @@
+//! Module of audit session compability for Linux.
+//!
+//! The audit identifiers of a process are exposed by the kernel on `/proc/self/loginuid`
+//! and `/proc/self/sessionid`, and the audit system status is queried with the
+//! `NETLINK_AUDIT` socket, as described on the `NETLINK(7)` man page.
+
+use std::{
+    fmt::{self, Display},
+    fs, io,
+    mem::{self, MaybeUninit},
+    ptr,
+};
+
+use libc::{
+    c_int, c_void, close, nlmsghdr, recv, sendto, sockaddr, sockaddr_nl, socket, socklen_t, uid_t,
+    AF_NETLINK, NETLINK_AUDIT, NLMSG_ERROR, NLM_F_REQUEST, SOCK_CLOEXEC, SOCK_RAW,
+};
+
+/// This type contains the audit identifier (login UID) which is recorded in the audit
+/// log for each event the process caused.
+pub type AuditUserId = uid_t;
+
+/// This type contains the audit session ID which is recorded with every event caused
+/// by the process.
+pub type AuditSessionId = u32;
+
+/// Value of [`AuditUserId`] and [`AuditSessionId`] when the process has no login UID or
+/// session ID assigned.
+pub const AUDIT_UNSET: u32 = u32::MAX;
+
+/// Netlink message type to get the audit system status.
+const AUDIT_GET: u16 = 1000;
+
+/// This struct represents a active audit session.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct AuditInfo {
+    /// Audit user ID
+    pub ai_auid: AuditUserId,
+    /// Audit session ID.
+    pub ai_asid: AuditSessionId,
+}
+
+impl Display for AuditInfo {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.ai_auid == AUDIT_UNSET {
+            writeln!(f, "auid=unset")?;
+        } else {
+            writeln!(f, "auid={}", self.ai_auid)?;
+        }
+
+        if self.ai_asid == AUDIT_UNSET {
+            write!(f, "asid=unset")
+        } else {
+            write!(f, "asid={}", self.ai_asid)
+        }
+    }
+}
+
+/// The state of the kernel audit system.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub enum AuditEnabled {
+    /// Auditing is disabled.
+    Disabled,
+    /// Auditing is enabled.
+    Enabled,
+    /// Auditing is enabled and the configuration can not be changed until reboot.
+    Locked,
+    /// Unknown state reported by the kernel.
+    Unknown(u32),
+}
+
+impl From<u32> for AuditEnabled {
+    #[inline]
+    fn from(num: u32) -> Self {
+        match num {
+            0 => Self::Disabled,
+            1 => Self::Enabled,
+            2 => Self::Locked,
+            _ => Self::Unknown(num),
+        }
+    }
+}
+
+impl Display for AuditEnabled {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Disabled => write!(f, "disabled"),
+            Self::Enabled => write!(f, "enabled"),
+            Self::Locked => write!(f, "locked"),
+            Self::Unknown(num) => write!(f, "unknown ({})", num),
+        }
+    }
+}
+
+/// This struct holds the status of the kernel audit system.
+///
+/// ¹Same as `struct audit_status`
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Eq, Ord, Hash)]
+#[repr(C)]
+pub struct AuditStatus {
+    /// Bit mask for valid entries.
+    pub mask: u32,
+    /// `1` = enabled, `0` = disabled, `2` = locked.
+    pub enabled: u32,
+    /// Failure-to-log action.
+    pub failure: u32,
+    /// PID of the audit daemon.
+    pub pid: u32,
+    /// Message rate limit (per second).
+    pub rate_limit: u32,
+    /// Waiting messages limit.
+    pub backlog_limit: u32,
+    /// Messages lost.
+    pub lost: u32,
+    /// Messages waiting in queue.
+    pub backlog: u32,
+    /// Bitmap of the audit features supported by the kernel.
+    pub feature_bitmap: u32,
+    /// Message queue wait timeout.
+    pub backlog_wait_time: u32,
+}
+
+impl AuditStatus {
+    /// Get the state of the audit system.
+    #[inline]
+    pub fn state(&self) -> AuditEnabled {
+        AuditEnabled::from(self.enabled)
+    }
+
+    /// Returns `true` if the audit system is recording events.
+    #[inline]
+    pub fn is_auditing(&self) -> bool {
+        matches!(self.state(), AuditEnabled::Enabled | AuditEnabled::Locked)
+    }
+}
+
+impl Display for AuditStatus {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "enabled={}", self.state())?;
+        writeln!(f, "failure={}", self.failure)?;
+        writeln!(f, "pid={}", self.pid)?;
+        writeln!(f, "rate_limit={}", self.rate_limit)?;
+        writeln!(f, "backlog_limit={}", self.backlog_limit)?;
+        writeln!(f, "lost={}", self.lost)?;
+        write!(f, "backlog={}", self.backlog)
+    }
+}
+
+/// Reads a unsigned number from a `/proc` file.
+fn read_proc_number(path: &str) -> io::Result<u32> {
+    let content = fs::read_to_string(path)?;
+
+    content.trim().parse().map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Returns the audit user ID (login UID) of the current process.
+///
+/// If the process has no login UID, [`AUDIT_UNSET`] is returned.
+///
+/// # Errors
+/// If it fails to read or parse `/proc/self/loginuid`, an error variant will be
+/// returned.
+#[inline]
+pub fn login_uid() -> io::Result<AuditUserId> {
+    read_proc_number("/proc/self/loginuid")
+}
+
+/// Returns the audit session ID of the current process.
+///
+/// If the process has no session ID, [`AUDIT_UNSET`] is returned.
+///
+/// # Errors
+/// If it fails to read or parse `/proc/self/sessionid`, an error variant will be
+/// returned.
+#[inline]
+pub fn session_id() -> io::Result<AuditSessionId> {
+    read_proc_number("/proc/self/sessionid")
+}
+
+/// Returns the `AuditInfo`.
+///
+/// # Errors
+/// If it fails to read or parse the `/proc` files, an error variant will be returned.
+#[inline]
+pub fn audit_info() -> io::Result<AuditInfo> {
+    Ok(AuditInfo { ai_auid: login_uid()?, ai_asid: session_id()? })
+}
+
+/// A `NETLINK_AUDIT` socket. It's closed on drop.
+struct AuditSocket(c_int);
+
+impl AuditSocket {
+    fn open() -> io::Result<Self> {
+        let fd = unsafe { socket(AF_NETLINK, SOCK_RAW | SOCK_CLOEXEC, NETLINK_AUDIT) };
+
+        if fd == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self(fd))
+    }
+
+    /// Sends a request message of `msg_type` without payload to the kernel.
+    fn request(&self, msg_type: u16, seq: u32) -> io::Result<()> {
+        let header = nlmsghdr {
+            nlmsg_len: mem::size_of::<nlmsghdr>() as u32,
+            nlmsg_type: msg_type,
+            nlmsg_flags: NLM_F_REQUEST as u16,
+            nlmsg_seq: seq,
+            nlmsg_pid: 0,
+        };
+
+        let mut addr: sockaddr_nl = unsafe { mem::zeroed() };
+        addr.nl_family = AF_NETLINK as u16;
+
+        let res = unsafe {
+            sendto(
+                self.0,
+                &header as *const nlmsghdr as *const c_void,
+                mem::size_of::<nlmsghdr>(),
+                0,
+                &addr as *const sockaddr_nl as *const sockaddr,
+                mem::size_of::<sockaddr_nl>() as socklen_t,
+            )
+        };
+
+        if res == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    /// Receives a message from the kernel, returning its header and payload.
+    fn receive(&self, buff: &mut [u8]) -> io::Result<(nlmsghdr, usize)> {
+        let len = unsafe { recv(self.0, buff.as_mut_ptr() as *mut c_void, buff.len(), 0) };
+
+        if len == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let len = len as usize;
+        if len < mem::size_of::<nlmsghdr>() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated netlink message"));
+        }
+
+        let header = unsafe { ptr::read_unaligned(buff.as_ptr() as *const nlmsghdr) };
+        let msg_len = (header.nlmsg_len as usize).min(len);
+
+        Ok((header, msg_len))
+    }
+}
+
+impl Drop for AuditSocket {
+    fn drop(&mut self) {
+        unsafe { close(self.0) };
+    }
+}
+
+/// Returns the status of the kernel audit system.
+///
+/// This requires the `CAP_AUDIT_CONTROL` capability.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error) or the kernel replies with an error, an
+/// error variant will be returned.
+#[cfg_attr(feature = "inline-more", inline)]
+pub fn audit_status() -> io::Result<AuditStatus> {
+    let sock = AuditSocket::open()?;
+    let seq = 1;
+    sock.request(AUDIT_GET, seq)?;
+
+    let header_size = mem::size_of::<nlmsghdr>();
+    let mut buff = [0u8; 8192];
+
+    loop {
+        let (header, msg_len) = sock.receive(&mut buff)?;
+
+        if header.nlmsg_seq != seq {
+            continue;
+        }
+
+        let payload = &buff[header_size..msg_len];
+
+        if header.nlmsg_type == NLMSG_ERROR as u16 {
+            if payload.len() < mem::size_of::<c_int>() {
+                break Err(io::Error::new(io::ErrorKind::InvalidData, "truncated netlink error"));
+            }
+
+            // The payload starts with the `error` field of `struct nlmsgerr`
+            let error = unsafe { ptr::read_unaligned(payload.as_ptr() as *const c_int) };
+
+            // An error code of 0 is an acknowledgement, the status comes next
+            if error == 0 {
+                continue;
+            }
+
+            break Err(io::Error::from_raw_os_error(-error));
+        }
+
+        if header.nlmsg_type == AUDIT_GET {
+            // Older kernels send a smaller struct, the fields not sent are left as zero
+            let mut status: MaybeUninit<AuditStatus> = MaybeUninit::zeroed();
+            let size = payload.len().min(mem::size_of::<AuditStatus>());
+
+            let status = unsafe {
+                ptr::copy_nonoverlapping(payload.as_ptr(), status.as_mut_ptr() as *mut u8, size);
+                status.assume_init()
+            };
+
+            break Ok(status);
+        }
+    }
+}
+
+/// Returns `true` if the audit system is enabled and recording events.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[inline]
+pub fn is_auditing() -> io::Result<bool> {
+    audit_status().map(|status| status.is_auditing())
+}
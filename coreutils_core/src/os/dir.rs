@@ -0,0 +1,263 @@
+//! Module for the directories opened as file descriptors, to work on their entries
+//! relative to them with the `*at` functions, as described on the `openat(2)` man page.
+//!
+//! Unlike a path, a open directory stays the same directory when its path is renamed or
+//! replaced, and the directories opened from it never follow symbolic links, so a tree
+//! can be walked without leaving it when a directory is swapped for a link in the
+//! meantime.
+//!
+//! The entries are read from the directory as they are needed, so the directories with a
+//! lot of them don't have to be read at once.
+
+use std::{
+    ffi::{CStr, CString, OsStr, OsString},
+    io,
+    mem::MaybeUninit,
+    os::unix::{
+        ffi::{OsStrExt, OsStringExt},
+        io::{AsRawFd, RawFd},
+    },
+    path::Path,
+    ptr::NonNull,
+};
+
+use libc::{c_int, DIR};
+
+use super::errno;
+
+/// A open directory, closed when dropped.
+#[derive(Debug)]
+pub struct Dir {
+    dir: NonNull<DIR>,
+}
+
+// The stream is owned by the `Dir`, so it is only used by the thread that has it
+unsafe impl Send for Dir {}
+
+impl Dir {
+    /// Open the directory in `path`, following it if it is a symbolic link.
+    ///
+    /// # Errors
+    /// If `path` is not a directory or a internal call set a errno (I/O OS error), an
+    /// error variant will be returned.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes())?;
+
+        let fd = unsafe { libc::open(path.as_ptr(), libc::O_RDONLY | DIRECTORY_FLAGS) };
+        Self::from_fd(fd)
+    }
+
+    /// Open the directory `name` of this directory, without following it if it is a
+    /// symbolic link.
+    ///
+    /// # Errors
+    /// If `name` is not a directory, it is a symbolic link or a internal call set a errno
+    /// (I/O OS error), an error variant will be returned.
+    pub fn open_dir(&self, name: impl AsRef<OsStr>) -> io::Result<Self> {
+        let name = CString::new(name.as_ref().as_bytes())?;
+        let flags = libc::O_RDONLY | libc::O_NOFOLLOW | DIRECTORY_FLAGS;
+
+        let fd = unsafe { libc::openat(self.as_raw_fd(), name.as_ptr(), flags) };
+        Self::from_fd(fd)
+    }
+
+    /// Get the status of the directory itself.
+    ///
+    /// # Errors
+    /// If a internal call set a errno (I/O OS error), an error variant will be returned.
+    pub fn stat(&self) -> io::Result<libc::stat> {
+        let mut stat = MaybeUninit::uninit();
+
+        match unsafe { libc::fstat(self.as_raw_fd(), stat.as_mut_ptr()) } {
+            0 => Ok(unsafe { stat.assume_init() }),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    /// Get the status of the entry `name`, or of the file it points to if it is a
+    /// symbolic link and `follow`.
+    ///
+    /// # Errors
+    /// If a internal call set a errno (I/O OS error), an error variant will be returned.
+    pub fn stat_at(&self, name: impl AsRef<OsStr>, follow: bool) -> io::Result<libc::stat> {
+        let name = CString::new(name.as_ref().as_bytes())?;
+        let flags = if follow { 0 } else { libc::AT_SYMLINK_NOFOLLOW };
+        let mut stat = MaybeUninit::uninit();
+
+        match unsafe { libc::fstatat(self.as_raw_fd(), name.as_ptr(), stat.as_mut_ptr(), flags) } {
+            0 => Ok(unsafe { stat.assume_init() }),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    /// Check if the entry `name` can be written by the effective user and group.
+    pub fn writable(&self, name: impl AsRef<OsStr>) -> bool {
+        let name = match CString::new(name.as_ref().as_bytes()) {
+            Ok(name) => name,
+            Err(_) => return false,
+        };
+
+        unsafe {
+            libc::faccessat(self.as_raw_fd(), name.as_ptr(), libc::W_OK, libc::AT_EACCESS) == 0
+        }
+    }
+
+    /// Check if the directory has no entries other than `.` and `..`, reading them
+    /// without moving the entries read by [`read`](Dir::read).
+    ///
+    /// # Errors
+    /// If a internal call set a errno (I/O OS error), an error variant will be returned.
+    pub fn is_empty(&self) -> io::Result<bool> {
+        let mut dir = self.open_dir(".")?;
+
+        dir.read().transpose().map(|entry| entry.is_none())
+    }
+
+    /// Get the identifier of the mount of the directory, that tells apart the bind mounts
+    /// of the same file system, or `None` if the system doesn't give it.
+    #[cfg(all(target_os = "linux", any(target_env = "gnu", target_env = "musl")))]
+    pub fn mount_id(&self) -> Option<u64> {
+        let mut stat = MaybeUninit::<libc::statx>::uninit();
+
+        let res = unsafe {
+            libc::statx(
+                self.as_raw_fd(),
+                b"\0".as_ptr().cast(),
+                libc::AT_EMPTY_PATH,
+                libc::STATX_MNT_ID,
+                stat.as_mut_ptr(),
+            )
+        };
+
+        if res != 0 {
+            return None;
+        }
+
+        let stat = unsafe { stat.assume_init() };
+
+        if stat.stx_mask & libc::STATX_MNT_ID == 0 {
+            None
+        } else {
+            Some(stat.stx_mnt_id)
+        }
+    }
+
+    /// Get the identifier of the mount of the directory, that tells apart the bind mounts
+    /// of the same file system, or `None` if the system doesn't give it.
+    #[cfg(not(all(target_os = "linux", any(target_env = "gnu", target_env = "musl"))))]
+    pub fn mount_id(&self) -> Option<u64> {
+        None
+    }
+
+    /// Remove the entry `name`, that is not a directory.
+    ///
+    /// # Errors
+    /// If a internal call set a errno (I/O OS error), an error variant will be returned.
+    pub fn remove_file(&self, name: impl AsRef<OsStr>) -> io::Result<()> {
+        self.unlink(name.as_ref(), 0)
+    }
+
+    /// Remove the entry `name`, that is a empty directory.
+    ///
+    /// # Errors
+    /// If a internal call set a errno (I/O OS error), an error variant will be returned.
+    pub fn remove_dir(&self, name: impl AsRef<OsStr>) -> io::Result<()> {
+        self.unlink(name.as_ref(), libc::AT_REMOVEDIR)
+    }
+
+    /// Read the name of the next entry of the directory, without `.` and `..`, or `None`
+    /// when all of them were read.
+    ///
+    /// The entries added or removed while the directory is read may or may not be read.
+    ///
+    /// # Errors
+    /// If a internal call set a errno (I/O OS error), an error variant will be returned.
+    pub fn read(&mut self) -> Option<io::Result<OsString>> {
+        loop {
+            // The end of the entries is only told apart from the errors by errno
+            unsafe { *errno() = 0 };
+
+            let entry = unsafe { libc::readdir(self.dir.as_ptr()) };
+
+            if entry.is_null() {
+                return match io::Error::last_os_error() {
+                    err if err.raw_os_error() == Some(0) => None,
+                    err => Some(Err(err)),
+                };
+            }
+
+            let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) }.to_bytes();
+
+            if name != b"." && name != b".." {
+                return Some(Ok(OsString::from_vec(name.to_vec())));
+            }
+        }
+    }
+
+    fn from_fd(fd: c_int) -> io::Result<Self> {
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        match NonNull::new(unsafe { libc::fdopendir(fd) }) {
+            Some(dir) => Ok(Dir { dir }),
+            None => {
+                let err = io::Error::last_os_error();
+                unsafe { libc::close(fd) };
+                Err(err)
+            },
+        }
+    }
+
+    fn unlink(&self, name: &OsStr, flags: c_int) -> io::Result<()> {
+        let name = CString::new(name.as_bytes())?;
+
+        match unsafe { libc::unlinkat(self.as_raw_fd(), name.as_ptr(), flags) } {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+}
+
+impl AsRawFd for Dir {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        unsafe { libc::dirfd(self.dir.as_ptr()) }
+    }
+}
+
+impl Drop for Dir {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { libc::closedir(self.dir.as_ptr()) };
+    }
+}
+
+/// The flags of the file descriptors of the directories.
+const DIRECTORY_FLAGS: c_int = libc::O_DIRECTORY | libc::O_CLOEXEC;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries() {
+        let mut dir = Dir::open(env!("CARGO_MANIFEST_DIR")).unwrap();
+
+        let mut names = Vec::new();
+        while let Some(name) = dir.read() {
+            names.push(name.unwrap());
+        }
+
+        assert!(names.contains(&OsString::from("Cargo.toml")));
+        assert!(names.contains(&OsString::from("src")));
+        assert!(!names.contains(&OsString::from(".")));
+
+        assert!(!dir.is_empty().unwrap());
+        assert!(dir.read().is_none());
+
+        let src = dir.open_dir("src").unwrap();
+        assert_eq!(src.stat_at("lib.rs", false).unwrap().st_mode & libc::S_IFMT, libc::S_IFREG);
+        assert_eq!(dir.open_dir("Cargo.toml").unwrap_err().raw_os_error(), Some(libc::ENOTDIR));
+    }
+}
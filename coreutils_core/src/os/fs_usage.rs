@@ -0,0 +1,106 @@
+//! Module for the usage of the space and the file nodes of the file systems.
+use std::{ffi::CString, io, mem::MaybeUninit, os::unix::ffi::OsStrExt, path::Path};
+
+use libc::statvfs;
+
+/// The usage of a file system, with the size of the blocks of the counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FsUsage {
+    /// The size in bytes of the blocks.
+    block_size: u64,
+    /// The total of data blocks.
+    blocks: u64,
+    /// The free blocks.
+    free_blocks: u64,
+    /// The free blocks available to the users that are not the superuser.
+    available_blocks: u64,
+    /// The total of file nodes.
+    files: u64,
+    /// The free file nodes.
+    free_files: u64,
+}
+
+impl FsUsage {
+    /// Get the usage of the file system of the file in `path`.
+    ///
+    /// # Errors
+    /// If a internal call set a errno (I/O OS error), an error variant will be returned.
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes())?;
+        let mut status: MaybeUninit<statvfs> = MaybeUninit::zeroed();
+
+        let res = unsafe { libc::statvfs(path.as_ptr(), status.as_mut_ptr()) };
+        if res != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let status = unsafe { status.assume_init() };
+
+        // The counts are of the fundamental blocks, that some systems don't give
+        let block_size = match status.f_frsize {
+            0 => status.f_bsize,
+            size => size,
+        };
+
+        // The fields are of 64 bits or less, depending on the system
+        #[allow(clippy::useless_conversion)]
+        let usage = FsUsage {
+            block_size: u64::from(block_size),
+            blocks: u64::from(status.f_blocks),
+            free_blocks: u64::from(status.f_bfree),
+            available_blocks: u64::from(status.f_bavail),
+            files: u64::from(status.f_files),
+            free_files: u64::from(status.f_ffree),
+        };
+
+        Ok(usage)
+    }
+
+    /// Get the size in bytes of the blocks of the counts.
+    #[inline]
+    pub fn block_size(&self) -> u64 {
+        self.block_size
+    }
+
+    /// Get the total of data blocks.
+    #[inline]
+    pub fn blocks(&self) -> u64 {
+        self.blocks
+    }
+
+    /// Get the free blocks.
+    #[inline]
+    pub fn free_blocks(&self) -> u64 {
+        self.free_blocks
+    }
+
+    /// Get the blocks in use.
+    #[inline]
+    pub fn used_blocks(&self) -> u64 {
+        self.blocks.saturating_sub(self.free_blocks)
+    }
+
+    /// Get the free blocks available to the users that are not the superuser.
+    #[inline]
+    pub fn available_blocks(&self) -> u64 {
+        self.available_blocks
+    }
+
+    /// Get the total of file nodes.
+    #[inline]
+    pub fn files(&self) -> u64 {
+        self.files
+    }
+
+    /// Get the free file nodes.
+    #[inline]
+    pub fn free_files(&self) -> u64 {
+        self.free_files
+    }
+
+    /// Get the file nodes in use.
+    #[inline]
+    pub fn used_files(&self) -> u64 {
+        self.files.saturating_sub(self.free_files)
+    }
+}
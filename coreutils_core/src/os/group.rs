@@ -221,8 +221,8 @@ impl Group {
                 getgrnam_r(
                     name.as_ptr() as *const c_char,
                     gr.as_mut_ptr(),
-                    &mut buff[0],
-                    buff.len(),
+                    buff.as_mut_ptr(),
+                    buff.capacity(),
                     &mut result,
                 )
             };
@@ -0,0 +1,313 @@
+//! Module to deal more easily with Windows groups, with a API close to the UNIX group
+//! one.
+//!
+//! The group IDs are the relative IDs (RID) of the group SIDs.
+
+use std::{
+    error::Error as StdError,
+    fmt::{self, Display},
+    io::{self, Error as IoError},
+    ptr, slice,
+    slice::Iter,
+};
+
+use bstr::{BStr, BString, ByteSlice};
+use winapi::{
+    shared::{
+        lmcons::MAX_PREFERRED_LENGTH,
+        minwindef::{DWORD, LPBYTE},
+    },
+    um::{
+        lmaccess::{NetUserGetLocalGroups, LG_INCLUDE_INDIRECT, LOCALGROUP_USERS_INFO_0},
+        lmapibuf::NetApiBufferFree,
+        winnt::{SidTypeAlias, SidTypeGroup, SidTypeWellKnownGroup},
+    },
+};
+
+use self::Error::*;
+use super::{
+    passwd::{Error as PwError, Passwd},
+    sid_windows::{
+        from_wide_ptr, lookup_account_name, lookup_account_sid, sid_from_rid, to_wide, NERR_SUCCESS,
+    },
+    Gid,
+};
+
+/// A iterator of group members.
+pub type Members = Vec<BString>;
+
+/// Enum that holds possible errors while creating [`Group`] type.
+#[derive(Debug)]
+pub enum Error {
+    /// Happens when a Windows API call fails.
+    ///
+    /// It holds the the function that was used and a error code of the function return.
+    GetGroupFailed(&'static str, i32),
+    /// Happens when the account is not found or is not a group account.
+    GroupNotFound,
+    /// Happens when creating a [`Passwd`] fails.
+    Passwd(Box<PwError>),
+}
+
+impl Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GetGroupFailed(fn_name, err_code) => write!(
+                f,
+                "Failed to get group with the following error code: {}. For more info search for \
+                 the {} documentation",
+                err_code, fn_name
+            ),
+            GroupNotFound => write!(f, "Group was not found in the system"),
+            Passwd(err) => write!(f, "Passwd error: {}", err),
+        }
+    }
+}
+
+impl StdError for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Passwd(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<PwError> for Error {
+    #[inline]
+    fn from(err: PwError) -> Error {
+        Passwd(Box::new(err))
+    }
+}
+
+impl From<Error> for IoError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        Self::new(io::ErrorKind::Other, err)
+    }
+}
+
+/// This struct holds information about a group of Windows systems.
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct Group {
+    /// Group name.
+    name: BString,
+    /// Group ID. (The relative ID of the group SID)
+    id: Gid,
+    /// Group encrypted password. Always empty, Windows does not have it.
+    passwd: BString,
+    /// Group list of members. Always empty, listing members requires privilege.
+    mem: Members,
+    /// Group SID in the string format.
+    sid: BString,
+}
+
+impl Group {
+    /// Creates a new [`Group`] getting the user primary group as default.
+    ///
+    /// # Errors
+    /// If there is a error ocurrence when getting the account information, an error
+    /// variant is returned.
+    #[inline]
+    pub fn new() -> io::Result<Self> {
+        Self::from_gid(Passwd::effective()?.gid())
+    }
+
+    /// Creates a [`Group`] using a `id` to get all attributes.
+    ///
+    /// # Errors
+    /// If there is a error ocurrence when getting the account information, an error
+    /// variant is returned.
+    #[inline]
+    pub fn from_gid(id: Gid) -> io::Result<Self> {
+        let sid = sid_from_rid(id)?;
+        Self::from_name(&lookup_account_sid(&sid)?)
+    }
+
+    /// Creates a [`Group`] using a `name` to get all attributes.
+    ///
+    /// # Errors
+    /// If there is a error ocurrence when getting the account information, an error
+    /// variant is returned.
+    #[inline]
+    pub fn from_name(name: &str) -> io::Result<Self> {
+        let name = name.trim_end_matches('\0');
+        let (sid, name_use) = lookup_account_name(name)?;
+
+        if name_use != SidTypeGroup && name_use != SidTypeAlias && name_use != SidTypeWellKnownGroup
+        {
+            return Err(GroupNotFound.into());
+        }
+
+        Ok(Group {
+            name: BString::from(name),
+            id: sid.rid(),
+            passwd: BString::from(""),
+            mem: Members::new(),
+            sid: BString::from(sid.to_string_sid()?),
+        })
+    }
+
+    /// Returns the `Group` name.
+    #[inline]
+    pub fn name(&self) -> &BStr {
+        self.name.as_bstr()
+    }
+
+    /// Returns the `Group` ID.
+    #[inline]
+    pub fn id(&self) -> Gid {
+        self.id
+    }
+
+    /// Returns the `Group` encrypted password.
+    #[inline]
+    pub fn passwd(&self) -> &BStr {
+        self.passwd.as_bstr()
+    }
+
+    /// Returns the `Group` list of members.
+    #[inline]
+    pub fn mem(&self) -> &Members {
+        &self.mem
+    }
+
+    /// Returns the `Group` SID in the string format. (`S-1-5-...`)
+    #[inline]
+    pub fn sid(&self) -> &BStr {
+        self.sid.as_bstr()
+    }
+}
+
+/// A collection of [`Group`].
+#[derive(Debug, Clone, Default)]
+pub struct Groups {
+    inner: Vec<Group>,
+}
+
+impl Groups {
+    /// Creates a empty new [`Groups`].
+    #[inline]
+    pub const fn new() -> Self {
+        Groups { inner: Vec::new() }
+    }
+
+    /// Get all the process caller groups.
+    ///
+    /// # Errors
+    /// If it fails to get a [`Group`], an error variant will be returned.
+    #[inline]
+    pub fn caller() -> io::Result<Self> {
+        Self::from_username(&Passwd::effective()?.name().to_string())
+    }
+
+    /// Get all groups that `username` belongs.
+    ///
+    /// The primary group of the user comes first, followed by the local groups the user
+    /// is a member of, directly or indirectly.
+    ///
+    /// # Errors
+    /// If it fails to get a [`Group`], an error variant will be returned.
+    #[cfg_attr(feature = "inline-more", inline)]
+    pub fn from_username(username: &str) -> io::Result<Self> {
+        let username = username.trim_end_matches('\0');
+        let mut groups = Groups::new();
+
+        if let Ok(gr) = Group::from_gid(Passwd::from_name(username)?.gid()) {
+            groups.push(gr);
+        }
+
+        let wide_name = to_wide(username);
+        let mut buff: LPBYTE = ptr::null_mut();
+        let mut entries_read: DWORD = 0;
+        let mut total_entries: DWORD = 0;
+
+        let res = unsafe {
+            NetUserGetLocalGroups(
+                ptr::null(),
+                wide_name.as_ptr(),
+                0,
+                LG_INCLUDE_INDIRECT,
+                &mut buff,
+                MAX_PREFERRED_LENGTH,
+                &mut entries_read,
+                &mut total_entries,
+            )
+        };
+
+        if res != NERR_SUCCESS {
+            if !buff.is_null() {
+                unsafe { NetApiBufferFree(buff as _) };
+            }
+            return Err(GetGroupFailed("NetUserGetLocalGroups", res as i32).into());
+        }
+
+        if !buff.is_null() {
+            let infos = unsafe {
+                slice::from_raw_parts(buff as *const LOCALGROUP_USERS_INFO_0, entries_read as usize)
+            };
+
+            for info in infos {
+                let name = unsafe { from_wide_ptr(info.lgrui0_name) };
+                if let Ok(gr) = Group::from_name(&name) {
+                    if !groups.inner.contains(&gr) {
+                        groups.push(gr);
+                    }
+                }
+            }
+
+            unsafe { NetApiBufferFree(buff as _) };
+        }
+
+        Ok(groups)
+    }
+
+    /// Creates a [`Groups`] from a list of group names.
+    ///
+    /// # Errors
+    /// If it fails to get a [`Group`], an error variant will be returned.
+    #[inline]
+    pub fn from_group_list(group_list: &[&str]) -> io::Result<Self> {
+        let mut groups = Groups::new();
+        for group in group_list {
+            groups.push(Group::from_name(group)?);
+        }
+        Ok(groups)
+    }
+
+    /// Insert a [`Group`] on [`Groups`].
+    #[inline]
+    pub fn push(&mut self, value: Group) {
+        self.inner.push(value);
+    }
+
+    /// Return `true` if [`Groups`] contains 0 elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Transform [`Groups`] into a [`Vec`] of [`Group`].
+    #[inline]
+    pub fn into_vec(self) -> Vec<Group> {
+        self.inner
+    }
+
+    /// Creates a iterator over it's entries.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, Group> {
+        self.inner.iter()
+    }
+}
+
+impl IntoIterator for Groups {
+    type IntoIter = std::vec::IntoIter<Group>;
+    type Item = Group;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
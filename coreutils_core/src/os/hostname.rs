@@ -0,0 +1,71 @@
+//! Module to get the host name of the system.
+
+use std::io;
+
+use bstr::BString;
+
+/// Get the host name of the system.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[cfg(unix)]
+#[cfg_attr(feature = "inline-more", inline)]
+pub fn hostname() -> io::Result<BString> {
+    let mut buff = vec![0u8; 256];
+
+    loop {
+        let res = unsafe { libc::gethostname(buff.as_mut_ptr() as *mut libc::c_char, buff.len()) };
+
+        if res == 0 {
+            // The name may be truncated without a nul byte if the buffer is not big enough
+            if let Some(len) = buff.iter().position(|&b| b == b'\0') {
+                buff.truncate(len);
+                break;
+            }
+        } else {
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::ENAMETOOLONG) {
+                return Err(err);
+            }
+        }
+
+        let new_len = buff.len() * 2;
+        buff.resize(new_len, 0);
+    }
+
+    Ok(BString::from(buff))
+}
+
+/// Get the host name of the system.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[cfg(windows)]
+#[cfg_attr(feature = "inline-more", inline)]
+pub fn hostname() -> io::Result<BString> {
+    use winapi::{
+        shared::winerror::ERROR_MORE_DATA,
+        um::sysinfoapi::{ComputerNameDnsHostname, GetComputerNameExW},
+    };
+
+    use super::sid_windows::from_wide;
+
+    let mut size: u32 = 256;
+    let mut buff: Vec<u16> = vec![0; size as usize];
+
+    loop {
+        if unsafe { GetComputerNameExW(ComputerNameDnsHostname, buff.as_mut_ptr(), &mut size) } != 0
+        {
+            break;
+        }
+
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() == Some(ERROR_MORE_DATA as i32) {
+            buff.resize(size as usize, 0);
+        } else {
+            return Err(err);
+        }
+    }
+
+    Ok(BString::from(from_wide(&buff)))
+}
@@ -0,0 +1,58 @@
+//! Module for the limits of the names of files, that depend on the file systems.
+
+use std::{ffi::CString, io, os::unix::ffi::OsStrExt, path::Path};
+
+use libc::c_int;
+
+use super::errno;
+
+/// Get the most bytes of the components of the names of files in the directory `dir`, or
+/// `None` if there is no limit.
+///
+/// # Errors
+/// If `dir` has a zero byte or a internal call set a errno (I/O OS error), an error
+/// variant will be returned.
+#[inline]
+pub fn name_max(dir: impl AsRef<Path>) -> io::Result<Option<usize>> {
+    pathconf(dir.as_ref(), libc::_PC_NAME_MAX)
+}
+
+/// Get the most bytes of the relative names of files from the directory `dir`, with the
+/// terminating zero byte, or `None` if there is no limit.
+///
+/// # Errors
+/// If `dir` has a zero byte or a internal call set a errno (I/O OS error), an error
+/// variant will be returned.
+#[inline]
+pub fn path_max(dir: impl AsRef<Path>) -> io::Result<Option<usize>> {
+    pathconf(dir.as_ref(), libc::_PC_PATH_MAX)
+}
+
+fn pathconf(dir: &Path, name: c_int) -> io::Result<Option<usize>> {
+    let dir = CString::new(dir.as_os_str().as_bytes())?;
+
+    // The missing limits are only told apart from the errors by errno
+    unsafe { *errno() = 0 };
+
+    match unsafe { libc::pathconf(dir.as_ptr(), name) } {
+        -1 => match io::Error::last_os_error() {
+            err if err.raw_os_error() == Some(0) => Ok(None),
+            err => Err(err),
+        },
+        limit => Ok(Some(limit as usize)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+
+        assert!(name_max(dir).unwrap().unwrap_or(14) >= 14);
+        assert!(path_max(dir).unwrap().unwrap_or(256) >= 256);
+        assert!(name_max(format!("{}/nowhere", dir)).is_err());
+    }
+}
@@ -0,0 +1,364 @@
+//! Module for the table of the mounted file systems.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "solaris",
+    target_os = "illumos"
+))]
+use std::{fs, path::Path};
+use std::{io, slice, vec};
+
+use bstr::{BStr, BString, ByteSlice};
+
+/// A mounted file system of the mount table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MountEntry {
+    /// The device or the name of the file system, like `/dev/sda1` or `tmpfs`.
+    source: BString,
+    /// The directory where it is mounted.
+    target: BString,
+    /// The type of the file system.
+    fs_type: BString,
+    /// The options of the mount, empty if the platform doesn't give them.
+    options: BString,
+    /// The directory of the file system that is the root of the mount, for bind mounts.
+    root: Option<BString>,
+    /// The device ID of the file system.
+    dev: Option<u64>,
+}
+
+impl MountEntry {
+    /// Get the device or the name of the file system.
+    #[inline]
+    pub fn source(&self) -> &BStr {
+        self.source.as_bstr()
+    }
+
+    /// Get the directory where the file system is mounted.
+    #[inline]
+    pub fn target(&self) -> &BStr {
+        self.target.as_bstr()
+    }
+
+    /// Get the type of the file system.
+    #[inline]
+    pub fn fs_type(&self) -> &BStr {
+        self.fs_type.as_bstr()
+    }
+
+    /// Get the options of the mount, separated by commas.
+    #[inline]
+    pub fn options(&self) -> &BStr {
+        self.options.as_bstr()
+    }
+
+    /// Get the directory of the file system mounted on the target, if the platform
+    /// gives it.
+    #[inline]
+    pub fn root(&self) -> Option<&BStr> {
+        self.root.as_ref().map(|root| root.as_bstr())
+    }
+
+    /// Get the device ID of the file system, if the platform gives it.
+    #[inline]
+    pub fn dev(&self) -> Option<u64> {
+        self.dev
+    }
+
+    /// Returns `true` if the file system is a pseudo file system without storage, like
+    /// `proc` and `sysfs`.
+    pub fn is_dummy(&self) -> bool {
+        let dummy_types: &[&[u8]] = &[
+            b"autofs",
+            b"proc",
+            b"subfs",
+            b"debugfs",
+            b"devpts",
+            b"fusectl",
+            b"fuse.portal",
+            b"mqueue",
+            b"rpc_pipefs",
+            b"sysfs",
+            b"devfs",
+            b"kernfs",
+            b"ignore",
+        ];
+
+        dummy_types.contains(&self.fs_type.as_bytes())
+            || (self.fs_type == "none" && !self.source.contains(&b'/'))
+    }
+
+    /// Returns `true` if the file system is in other machine, like the ones of NFS and
+    /// SMB.
+    pub fn is_remote(&self) -> bool {
+        self.source.contains(&b':')
+            || (self.source.starts_with(b"//")
+                && matches!(self.fs_type.as_bytes(), b"smbfs" | b"smb3" | b"cifs"))
+            || self.source == "-hosts"
+    }
+}
+
+/// The table of the mounted file systems.
+#[derive(Debug, Clone)]
+pub struct MountTable(Vec<MountEntry>);
+
+impl MountTable {
+    /// Creates the table of the file systems mounted on the running system, in the order
+    /// they were mounted.
+    ///
+    /// # Errors
+    /// If the platform has no mount table or it can't be read, an error variant will be
+    /// returned.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn system() -> io::Result<Self> {
+        match fs::read("/proc/self/mountinfo") {
+            Ok(content) => Ok(MountTable(content.lines().filter_map(parse_mountinfo).collect())),
+            Err(_) => MountTable::from_file("/proc/mounts")
+                .or_else(|_| MountTable::from_file("/etc/mtab")),
+        }
+    }
+
+    /// Creates the table of the file systems mounted on the running system, in the order
+    /// they were mounted.
+    ///
+    /// # Errors
+    /// If the platform has no mount table or it can't be read, an error variant will be
+    /// returned.
+    #[cfg(any(target_os = "solaris", target_os = "illumos"))]
+    pub fn system() -> io::Result<Self> {
+        MountTable::from_file("/etc/mnttab")
+    }
+
+    /// Creates the table of the file systems mounted on the running system.
+    ///
+    /// # Errors
+    /// If the platform has no mount table or it can't be read, an error variant will be
+    /// returned.
+    #[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "openbsd"))]
+    pub fn system() -> io::Result<Self> {
+        use std::{ffi::CStr, ptr};
+
+        use libc::{getmntinfo, statfs, MNT_NOWAIT};
+
+        let mut buffer: *mut statfs = ptr::null_mut();
+        let len = unsafe { getmntinfo(&mut buffer, MNT_NOWAIT) };
+        if len <= 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // SAFETY: getmntinfo gives `len` entries in the buffer it owns
+        let mounts = unsafe { slice::from_raw_parts(buffer, len as usize) };
+        let string = |name: &[libc::c_char]| {
+            BString::from(unsafe { CStr::from_ptr(name.as_ptr()) }.to_bytes())
+        };
+
+        let entries = mounts
+            .iter()
+            .map(|mount| MountEntry {
+                source: string(&mount.f_mntfromname),
+                target: string(&mount.f_mntonname),
+                fs_type: string(&mount.f_fstypename),
+                options: BString::from(""),
+                root: None,
+                dev: None,
+            })
+            .collect();
+
+        Ok(MountTable(entries))
+    }
+
+    /// Creates the table of the file systems mounted on the running system.
+    ///
+    /// # Errors
+    /// This platform has no mount table, so an error variant is always returned.
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "macos",
+        target_os = "freebsd",
+        target_os = "openbsd"
+    )))]
+    pub fn system() -> io::Result<Self> {
+        Err(io::Error::new(io::ErrorKind::Other, "cannot read table of mounted file systems"))
+    }
+
+    /// Creates the table of a file in the format of `fstab(5)`, like `/etc/mtab`, where
+    /// each line has the source, target, type and options of a file system.
+    ///
+    /// # Errors
+    /// If the file can't be read, an error variant will be returned.
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "solaris",
+        target_os = "illumos"
+    ))]
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let content = fs::read(path)?;
+
+        let entries = content
+            .lines()
+            .filter(|line| !line.trim_start().starts_with(b"#"))
+            .filter_map(|line| {
+                let mut fields = line.fields();
+
+                Some(MountEntry {
+                    source: unescape(fields.next()?),
+                    target: unescape(fields.next()?),
+                    fs_type: unescape(fields.next()?),
+                    options: fields.next().map(unescape).unwrap_or_default(),
+                    root: None,
+                    dev: None,
+                })
+            })
+            .collect();
+
+        Ok(MountTable(entries))
+    }
+
+    /// Returns `true` if the table has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Creates a iterator over it's entries.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, MountEntry> {
+        self.0.iter()
+    }
+
+    /// Size of the table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl IntoIterator for MountTable {
+    type IntoIter = vec::IntoIter<MountEntry>;
+    type Item = MountEntry;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a MountTable {
+    type IntoIter = slice::Iter<'a, MountEntry>;
+    type Item = &'a MountEntry;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// Parse a line of `/proc/self/mountinfo`, like
+/// `28 1 254:0 / / rw,relatime shared:1 - ext4 /dev/vda rw`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn parse_mountinfo(line: &[u8]) -> Option<MountEntry> {
+    let mut fields = line.fields();
+
+    // The IDs of the mount and its parent
+    fields.nth(1)?;
+    let mut dev = fields.next()?.splitn_str(2, ":");
+    let major = dev.next()?.to_str().ok()?.parse().ok()?;
+    let minor = dev.next()?.to_str().ok()?.parse().ok()?;
+    let root = unescape(fields.next()?);
+    let target = unescape(fields.next()?);
+    let options = unescape(fields.next()?);
+
+    // The optional fields end with a `-`
+    fields.find(|&field| field == b"-")?;
+    let fs_type = unescape(fields.next()?);
+    let source = unescape(fields.next()?);
+
+    Some(MountEntry {
+        source,
+        target,
+        fs_type,
+        options,
+        root: Some(root),
+        dev: Some(libc::makedev(major, minor) as u64),
+    })
+}
+
+/// Replace the escapes of the octal codes of the bytes, like `\040` of the space.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "solaris",
+    target_os = "illumos"
+))]
+fn unescape(field: &[u8]) -> BString {
+    let mut unescaped = Vec::with_capacity(field.len());
+    let mut i = 0;
+
+    while i < field.len() {
+        let code =
+            field.get(i + 1..i + 4).filter(|code| code.iter().all(|b| matches!(b, b'0'..=b'7')));
+
+        match code {
+            Some(code) if field[i] == b'\\' => {
+                let byte = code.iter().fold(0u32, |byte, digit| byte * 8 + u32::from(digit - b'0'));
+                unescaped.push(byte as u8);
+                i += 4;
+            },
+            _ => {
+                unescaped.push(field[i]);
+                i += 1;
+            },
+        }
+    }
+
+    BString::from(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bstr::B;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn mountinfo() {
+        let entry = parse_mountinfo(
+            b"36 35 98:0 /mnt1 /mnt/my\\040disk rw,noatime master:1 - ext3 /dev/root rw,errors=continue",
+        )
+        .unwrap();
+
+        assert_eq!(entry.source(), "/dev/root");
+        assert_eq!(entry.target(), "/mnt/my disk");
+        assert_eq!(entry.fs_type(), "ext3");
+        assert_eq!(entry.options(), "rw,noatime");
+        assert_eq!(entry.root(), Some(B("/mnt1").as_bstr()));
+        assert_eq!(entry.dev(), Some(libc::makedev(98, 0) as u64));
+        assert!(!entry.is_dummy());
+        assert!(!entry.is_remote());
+
+        assert_eq!(parse_mountinfo(b"36 35 98:0 /mnt1 /mnt2 rw"), None);
+    }
+
+    #[test]
+    fn dummy_and_remote() {
+        let entry = |source: &str, fs_type: &str| MountEntry {
+            source: BString::from(source),
+            target: BString::from("/mnt"),
+            fs_type: BString::from(fs_type),
+            options: BString::from(""),
+            root: None,
+            dev: None,
+        };
+
+        assert!(entry("proc", "proc").is_dummy());
+        assert!(entry("none", "none").is_dummy());
+        assert!(!entry("/dev/sda1", "none").is_dummy());
+        assert!(entry("server:/export", "nfs").is_remote());
+        assert!(entry("//server/share", "cifs").is_remote());
+        assert!(!entry("//server/share", "ext4").is_remote());
+    }
+}
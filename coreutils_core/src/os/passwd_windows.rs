@@ -0,0 +1,262 @@
+//! Module to deal more easily with Windows user accounts, with a API close to the UNIX
+//! passwd one.
+//!
+//! The user and group IDs are the relative IDs (RID) of the account SIDs.
+
+use std::{
+    error::Error as StdError,
+    fmt::{self, Display},
+    io::{self, Error as IoError},
+    ptr,
+};
+
+use bstr::{BStr, BString, ByteSlice};
+use winapi::{
+    shared::minwindef::LPBYTE,
+    um::{
+        lmaccess::{NetUserGetInfo, USER_INFO_3},
+        lmapibuf::NetApiBufferFree,
+        winnt::SidTypeUser,
+    },
+};
+
+use self::Error::*;
+use super::{
+    group::{Error as GrError, Groups},
+    sid_windows::{
+        current_user_name, from_wide_ptr, lookup_account_name, lookup_account_sid, sid_from_rid,
+        to_wide, NERR_SUCCESS,
+    },
+    Gid, Uid,
+};
+
+/// Relative ID of the default primary group of the users. (`DOMAIN_GROUP_RID_USERS`)
+const DEFAULT_PRIMARY_GROUP: Gid = 513;
+
+/// Enum that holds possible errors while creating [`Passwd`] type.
+#[derive(Debug)]
+pub enum Error {
+    /// Happens when a Windows API call fails.
+    ///
+    /// It holds the the function that was used and a error code of the function return.
+    GetPasswdFailed(&'static str, i32),
+    /// Happens when the account is not found or is not a user account.
+    PasswdNotFound,
+    /// Happens when something happens when finding what [`Group`] a [`Passwd`] belongs
+    Group(Box<GrError>),
+}
+
+impl Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GetPasswdFailed(fn_name, err_code) => write!(
+                f,
+                "Failed to get passwd with the following error code: {}. For more info search for \
+                 the {} documentation",
+                err_code, fn_name
+            ),
+            PasswdNotFound => write!(f, "Passwd was not found in the system"),
+            Group(err) => write!(f, "Group error: {}", err),
+        }
+    }
+}
+
+impl StdError for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Group(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<GrError> for Error {
+    #[inline]
+    fn from(err: GrError) -> Error {
+        Group(Box::new(err))
+    }
+}
+
+impl From<Error> for IoError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        Self::new(io::ErrorKind::Other, err)
+    }
+}
+
+/// This struct holds the information of a user in Windows systems.
+#[derive(Clone, Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+pub struct Passwd {
+    /// User login name.
+    name: BString,
+    /// User encrypted password. Always empty, Windows does not expose it.
+    passwd: BString,
+    /// User ID. (The relative ID of the user SID)
+    user_id: Uid,
+    /// User primary Group ID. (The relative ID of the group SID)
+    group_id: Gid,
+    /// User full name.
+    gecos: BString,
+    /// User directory.
+    dir: BString,
+    /// User login shell. Always empty, Windows does not have it.
+    shell: BString,
+    /// User SID in the string format.
+    sid: BString,
+}
+
+impl Passwd {
+    /// Creates a new [`Passwd`] getting the current process user passwd.
+    ///
+    /// # Errors
+    /// If there is a error ocurrence when getting the account information, an error
+    /// variant is returned.
+    #[inline]
+    pub fn effective() -> io::Result<Self> {
+        Self::from_name(&current_user_name()?)
+    }
+
+    /// Creates a new [`Passwd`] getting the current process user passwd.
+    ///
+    /// Windows has no distinction of real and effective user, so this is the same as
+    /// [`Passwd::effective`].
+    ///
+    /// # Errors
+    /// If there is a error ocurrence when getting the account information, an error
+    /// variant is returned.
+    #[inline]
+    pub fn real() -> io::Result<Self> {
+        Self::effective()
+    }
+
+    /// Creates a new [`Passwd`] using a `id` to get all attributes.
+    ///
+    /// # Errors
+    /// If there is a error ocurrence when getting the account information, an error
+    /// variant is returned.
+    #[inline]
+    pub fn from_uid(id: Uid) -> io::Result<Self> {
+        let sid = sid_from_rid(id)?;
+        Self::from_name(&lookup_account_sid(&sid)?)
+    }
+
+    /// Creates a new [`Passwd`] using a `name` to get all attributes.
+    ///
+    /// # Errors
+    /// If there is a error ocurrence when getting the account information, an error
+    /// variant is returned.
+    #[cfg_attr(feature = "inline-more", inline)]
+    pub fn from_name(name: &str) -> io::Result<Self> {
+        let name = name.trim_end_matches('\0');
+        let (sid, name_use) = lookup_account_name(name)?;
+
+        if name_use != SidTypeUser {
+            return Err(PasswdNotFound.into());
+        }
+
+        let mut pw = Passwd {
+            name: BString::from(name),
+            passwd: BString::from(""),
+            user_id: sid.rid(),
+            group_id: DEFAULT_PRIMARY_GROUP,
+            gecos: BString::from(""),
+            dir: BString::from(""),
+            shell: BString::from(""),
+            sid: BString::from(sid.to_string_sid()?),
+        };
+
+        // The extra information requires `NetUserGetInfo`, that may fail for domain users
+        // or restricted callers, in that case we keep the defaults
+        let wide_name = to_wide(name);
+        let mut buff: LPBYTE = ptr::null_mut();
+        let res = unsafe { NetUserGetInfo(ptr::null(), wide_name.as_ptr(), 3, &mut buff) };
+
+        if res == NERR_SUCCESS && !buff.is_null() {
+            unsafe {
+                let info = &*(buff as *const USER_INFO_3);
+                pw.group_id = info.usri3_primary_group_id;
+                pw.gecos = BString::from(from_wide_ptr(info.usri3_full_name));
+                pw.dir = BString::from(from_wide_ptr(info.usri3_home_dir));
+            }
+        }
+
+        if !buff.is_null() {
+            unsafe { NetApiBufferFree(buff as _) };
+        }
+
+        Ok(pw)
+    }
+
+    /// Get [`Passwd`] login name.
+    #[inline]
+    pub fn name(&self) -> &BStr {
+        self.name.as_bstr()
+    }
+
+    /// Get [`Passwd`] encrypted password.
+    #[inline]
+    pub fn passwd(&self) -> &BStr {
+        self.passwd.as_bstr()
+    }
+
+    /// Get [`Passwd`] user ID.
+    #[inline]
+    pub fn uid(&self) -> Uid {
+        self.user_id
+    }
+
+    /// Get [`Passwd`] group ID.
+    #[inline]
+    pub fn gid(&self) -> Gid {
+        self.group_id
+    }
+
+    /// Get [`Passwd`] full name.
+    #[inline]
+    pub fn gecos(&self) -> &BStr {
+        self.gecos.as_bstr()
+    }
+
+    /// Get [`Passwd`] home directory.
+    #[inline]
+    pub fn dir(&self) -> &BStr {
+        self.dir.as_bstr()
+    }
+
+    /// Get [`Passwd`] login shell.
+    #[inline]
+    pub fn shell(&self) -> &BStr {
+        self.shell.as_bstr()
+    }
+
+    /// Get [`Passwd`] SID in the string format. (`S-1-5-...`)
+    #[inline]
+    pub fn sid(&self) -> &BStr {
+        self.sid.as_bstr()
+    }
+
+    /// Get the groups that [`Passwd`] belongs to.
+    ///
+    /// # Errors
+    /// If it fails to get the [`Groups`] where [`Passwd`] (user) belongs, an error
+    /// variant is returned.
+    #[inline]
+    pub fn belongs_to(&self) -> io::Result<Groups> {
+        let name = self.name.to_string();
+        let gr = Groups::from_username(&name)?;
+        Ok(gr)
+    }
+}
+
+impl Display for Passwd {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}:{}:{}:{}",
+            self.name, self.passwd, self.user_id, self.group_id, self.gecos, self.dir, self.shell
+        )
+    }
+}
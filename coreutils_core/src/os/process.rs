@@ -1,6 +1,8 @@
 //! Module with abstractions to safely deal with processes.
 #[cfg(not(any(target_os = "fuchsia")))]
 use std::ffi::CString;
+#[cfg(target_os = "linux")]
+use std::os::unix::io::RawFd;
 use std::{convert::TryInto, io};
 
 use crate::{
@@ -8,7 +10,7 @@ use crate::{
     os::{
         group::{Group, Groups},
         passwd::Passwd,
-        Gid,
+        Gid, Pid,
     },
 };
 
@@ -84,3 +86,31 @@ pub fn set_group(group: &str) -> io::Result<()> {
         _ => Err(io::Error::last_os_error()),
     }
 }
+
+/// Check if a process with the ID `pid` exists.
+///
+/// A process that exists but that the caller has no permission to send signals to is
+/// also reported as existing.
+#[inline]
+pub fn exists(pid: Pid) -> bool {
+    match unsafe { libc::kill(pid, 0) } {
+        0 => true,
+        _ => io::Error::last_os_error().raw_os_error() != Some(libc::ESRCH),
+    }
+}
+
+/// Get a file descriptor that refers to the process with the ID `pid`.
+///
+/// The file descriptor becomes readable when the process terminates, so it can be used
+/// with `poll(2)` to wait for it. The caller is responsible for closing it.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[cfg(target_os = "linux")]
+#[inline]
+pub fn pidfd_open(pid: Pid) -> io::Result<RawFd> {
+    match unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) } {
+        -1 => Err(io::Error::last_os_error()),
+        fd => Ok(fd as RawFd),
+    }
+}
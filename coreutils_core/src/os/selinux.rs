@@ -0,0 +1,78 @@
+//! Module for the security contexts of SELinux.
+//!
+//! The context of a file is kept on its `security.selinux` extended attribute, like
+//! `system_u:object_r:etc_t:s0`, as described on the `xattr(7)` man page. The context of
+//! the files created by a thread is the one of its `attr/fscreate` file, as described on
+//! the `proc(5)` man page.
+
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::Path,
+};
+
+use bstr::BString;
+
+use super::xattr;
+
+/// The name of the extended attribute of the security contexts.
+const CONTEXT_ATTRIBUTE: &[u8] = b"security.selinux";
+
+/// The file of the security context of the files created by the thread.
+const CREATE_CONTEXT_FILE: &str = "/proc/thread-self/attr/fscreate";
+
+/// Returns `true` if SELinux is enabled on the running system, with its file system
+/// mounted.
+pub fn is_enabled() -> bool {
+    Path::new("/sys/fs/selinux/enforce").exists()
+}
+
+/// Get the security context of the file in `path`, or of the symbolic link itself if it
+/// is one and not `follow`.
+///
+/// # Errors
+/// If the file has no security context or a internal call set a errno (I/O OS error),
+/// an error variant will be returned.
+pub fn file_context(path: impl AsRef<Path>, follow: bool) -> io::Result<BString> {
+    let mut context = xattr::get(path, CONTEXT_ATTRIBUTE, follow)?;
+
+    // The context is stored with its nul terminator
+    if context.last() == Some(&0) {
+        context.pop();
+    }
+
+    Ok(context)
+}
+
+/// Set the security context of the file in `path`, or of the symbolic link itself if it
+/// is one and not `follow`, to `context`.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+pub fn set_file_context(path: impl AsRef<Path>, context: &[u8], follow: bool) -> io::Result<()> {
+    let mut value = context.to_vec();
+    value.push(0);
+
+    xattr::set(path, CONTEXT_ATTRIBUTE, &value, follow)
+}
+
+/// Set the security context of the files created by the thread from now on to `context`,
+/// or to the default one of the policy for each of them if `None`.
+///
+/// # Errors
+/// If the context is not valid or a internal call set a errno (I/O OS error), an error
+/// variant will be returned.
+pub fn set_create_context(context: Option<&[u8]>) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).open(CREATE_CONTEXT_FILE)?;
+
+    match context {
+        Some(context) => {
+            let mut value = context.to_vec();
+            value.push(0);
+
+            file.write_all(&value)
+        },
+        // The default context is set by writing nothing
+        None => file.write(&[]).map(|_| ()),
+    }
+}
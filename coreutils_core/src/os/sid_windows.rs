@@ -0,0 +1,274 @@
+//! Module with helpers to deal with Windows security identifiers (SID) and account names.
+//!
+//! Windows has no numeric user and group IDs, so the relative ID (RID) of the account
+//! SID, its last sub-authority, is used in place of them.
+
+use std::{ffi::OsStr, io, iter, os::windows::ffi::OsStrExt, ptr, slice};
+
+use winapi::{
+    shared::{
+        minwindef::{DWORD, FALSE, PULONG},
+        ntdef::{BOOLEAN, LPWSTR, PVOID},
+        sddl::{ConvertSidToStringSidW, ConvertStringSidToSidW},
+        winerror::ERROR_INSUFFICIENT_BUFFER,
+    },
+    um::{
+        securitybaseapi::{GetLengthSid, GetSidSubAuthority, GetSidSubAuthorityCount, IsValidSid},
+        winbase::{LocalFree, LookupAccountNameW, LookupAccountSidW},
+        winnt::{PSID, SID_NAME_USE},
+    },
+};
+
+/// The string SID prefix of the builtin domain, where local groups like `Administrators`
+/// and `Users` live.
+pub(crate) const BUILTIN_DOMAIN: &str = "S-1-5-32";
+
+/// Return code of the `Net*` functions on success.
+pub(crate) const NERR_SUCCESS: DWORD = 0;
+
+/// The `DOMAIN\\user` name format of `EXTENDED_NAME_FORMAT`.
+const NAME_SAM_COMPATIBLE: u32 = 2;
+
+#[link(name = "secur32")]
+extern "system" {
+    // `winapi` does not have the `secext.h` functions
+    fn GetUserNameExW(name_format: u32, name_buffer: LPWSTR, size: PULONG) -> BOOLEAN;
+}
+
+/// A owned security identifier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Sid(Vec<u8>);
+
+impl Sid {
+    /// Copies a `PSID` into a owned [`Sid`].
+    ///
+    /// # Safety
+    /// `psid` must point to a valid SID.
+    unsafe fn from_psid(psid: PSID) -> Self {
+        let len = GetLengthSid(psid) as usize;
+        Sid(slice::from_raw_parts(psid as *const u8, len).to_vec())
+    }
+
+    /// Creates a [`Sid`] from a string SID. (`S-1-5-...`)
+    pub(crate) fn from_string_sid(string_sid: &str) -> io::Result<Self> {
+        let wide = to_wide(string_sid);
+        let mut psid: PSID = ptr::null_mut();
+
+        if unsafe { ConvertStringSidToSidW(wide.as_ptr(), &mut psid) } == FALSE {
+            return Err(io::Error::last_os_error());
+        }
+
+        let sid = unsafe { Self::from_psid(psid) };
+        unsafe { LocalFree(psid as _) };
+
+        Ok(sid)
+    }
+
+    pub(crate) fn as_psid(&self) -> PSID {
+        self.0.as_ptr() as PSID
+    }
+
+    /// Get the relative ID of the SID, the last sub-authority.
+    pub(crate) fn rid(&self) -> u32 {
+        unsafe {
+            let psid = self.as_psid();
+            if IsValidSid(psid) == FALSE {
+                return 0;
+            }
+
+            let count = *GetSidSubAuthorityCount(psid);
+            if count == 0 {
+                return 0;
+            }
+
+            *GetSidSubAuthority(psid, DWORD::from(count) - 1)
+        }
+    }
+
+    /// Get the string representation of the SID. (`S-1-5-...`)
+    pub(crate) fn to_string_sid(&self) -> io::Result<String> {
+        let mut wide: LPWSTR = ptr::null_mut();
+
+        if unsafe { ConvertSidToStringSidW(self.as_psid(), &mut wide) } == FALSE {
+            return Err(io::Error::last_os_error());
+        }
+
+        let string_sid = unsafe { from_wide_ptr(wide) };
+        unsafe { LocalFree(wide as PVOID) };
+
+        Ok(string_sid)
+    }
+
+    /// Get the string SID of the domain of this SID, that is, without the relative ID.
+    pub(crate) fn domain_string_sid(&self) -> io::Result<String> {
+        let string_sid = self.to_string_sid()?;
+
+        match string_sid.rfind('-') {
+            Some(index) => Ok(string_sid[..index].to_string()),
+            None => Ok(string_sid),
+        }
+    }
+}
+
+/// Converts a `&str` into a nul terminated wide string.
+pub(crate) fn to_wide(s: &str) -> Vec<u16> {
+    OsStr::new(s).encode_wide().chain(iter::once(0)).collect()
+}
+
+/// Converts a nul terminated wide string into a [`String`].
+///
+/// # Safety
+/// `ptr` must be null or point to a nul terminated wide string.
+pub(crate) unsafe fn from_wide_ptr(ptr: *const u16) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+
+    let mut len = 0;
+    while *ptr.add(len) != 0 {
+        len += 1;
+    }
+
+    String::from_utf16_lossy(slice::from_raw_parts(ptr, len))
+}
+
+/// Converts a wide string buffer, nul terminated or not, into a [`String`].
+pub(crate) fn from_wide(buff: &[u16]) -> String {
+    let len = buff.iter().position(|&c| c == 0).unwrap_or(buff.len());
+    String::from_utf16_lossy(&buff[..len])
+}
+
+/// Get the name of the user of the current process, without the domain.
+pub(crate) fn current_user_name() -> io::Result<String> {
+    let mut size: u32 = 256;
+    let mut buff: Vec<u16> = vec![0; size as usize];
+
+    loop {
+        if unsafe { GetUserNameExW(NAME_SAM_COMPATIBLE, buff.as_mut_ptr(), &mut size) } != 0 {
+            break;
+        }
+
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() == Some(ERROR_INSUFFICIENT_BUFFER as i32) {
+            buff.resize(size as usize, 0);
+        } else {
+            return Err(err);
+        }
+    }
+
+    // The name is in the `DOMAIN\user` format
+    let name = from_wide(&buff);
+    match name.rfind('\\') {
+        Some(index) => Ok(name[index + 1..].to_string()),
+        None => Ok(name),
+    }
+}
+
+/// Get the [`Sid`] and the account type of the account `name`.
+pub(crate) fn lookup_account_name(name: &str) -> io::Result<(Sid, SID_NAME_USE)> {
+    let wide_name = to_wide(name);
+    let mut sid_size: DWORD = 0;
+    let mut domain_size: DWORD = 0;
+    let mut name_use: SID_NAME_USE = 0;
+
+    // The first call gets the size of the buffers
+    unsafe {
+        LookupAccountNameW(
+            ptr::null(),
+            wide_name.as_ptr(),
+            ptr::null_mut(),
+            &mut sid_size,
+            ptr::null_mut(),
+            &mut domain_size,
+            &mut name_use,
+        )
+    };
+
+    if sid_size == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut sid: Vec<u8> = vec![0; sid_size as usize];
+    let mut domain: Vec<u16> = vec![0; domain_size as usize];
+
+    let res = unsafe {
+        LookupAccountNameW(
+            ptr::null(),
+            wide_name.as_ptr(),
+            sid.as_mut_ptr() as PSID,
+            &mut sid_size,
+            domain.as_mut_ptr(),
+            &mut domain_size,
+            &mut name_use,
+        )
+    };
+
+    if res == FALSE {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok((Sid(sid), name_use))
+}
+
+/// Get the account name of the [`Sid`], without the domain.
+pub(crate) fn lookup_account_sid(sid: &Sid) -> io::Result<String> {
+    let mut name_size: DWORD = 0;
+    let mut domain_size: DWORD = 0;
+    let mut name_use: SID_NAME_USE = 0;
+
+    // The first call gets the size of the buffers
+    unsafe {
+        LookupAccountSidW(
+            ptr::null(),
+            sid.as_psid(),
+            ptr::null_mut(),
+            &mut name_size,
+            ptr::null_mut(),
+            &mut domain_size,
+            &mut name_use,
+        )
+    };
+
+    if name_size == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut name: Vec<u16> = vec![0; name_size as usize];
+    let mut domain: Vec<u16> = vec![0; domain_size as usize];
+
+    let res = unsafe {
+        LookupAccountSidW(
+            ptr::null(),
+            sid.as_psid(),
+            name.as_mut_ptr(),
+            &mut name_size,
+            domain.as_mut_ptr(),
+            &mut domain_size,
+            &mut name_use,
+        )
+    };
+
+    if res == FALSE {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(from_wide(&name))
+}
+
+/// Get the string SID of the domain of the user of the current process.
+pub(crate) fn current_domain_string_sid() -> io::Result<String> {
+    let (sid, _) = lookup_account_name(&current_user_name()?)?;
+    sid.domain_string_sid()
+}
+
+/// Find the [`Sid`] of a account by its relative ID. The builtin domain is tried first,
+/// then the domain of the user of the current process.
+pub(crate) fn sid_from_rid(rid: u32) -> io::Result<Sid> {
+    let builtin = Sid::from_string_sid(&format!("{}-{}", BUILTIN_DOMAIN, rid))?;
+    if lookup_account_sid(&builtin).is_ok() {
+        return Ok(builtin);
+    }
+
+    let domain = current_domain_string_sid()?;
+    Sid::from_string_sid(&format!("{}-{}", domain, rid))
+}
@@ -1,9 +1,9 @@
 //! Module for time related abstractions more close to the OS.
-use std::{io, mem::MaybeUninit, ptr};
+use std::{ffi::CString, io, mem::MaybeUninit, os::unix::ffi::OsStrExt, path::Path, ptr};
 
 use libc::localtime_r;
 
-use super::{Time, TimeVal, Tm};
+use super::{Time, TimeSpec, TimeVal, Tm};
 
 /// Set the system time as `timeval`
 ///
@@ -18,6 +18,58 @@ pub fn set_time_of_day(timeval: TimeVal) -> io::Result<()> {
     }
 }
 
+/// Set the system realtime clock as `timespec`
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[inline]
+pub fn set_clock_time(timespec: TimeSpec) -> io::Result<()> {
+    let result = unsafe { libc::clock_settime(libc::CLOCK_REALTIME, &timespec as *const TimeSpec) };
+    match result {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Get the resolution of the system realtime clock.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[inline]
+pub fn clock_resolution() -> io::Result<TimeSpec> {
+    let mut timespec = TimeSpec { tv_sec: 0, tv_nsec: 0 };
+    let result =
+        unsafe { libc::clock_getres(libc::CLOCK_REALTIME, &mut timespec as *mut TimeSpec) };
+    match result {
+        0 => Ok(timespec),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Set the access and modification times of the file in `path` as `atime` and `mtime`,
+/// where the times that are [`None`] are left unchanged.
+///
+/// If `no_deref` and `path` is a symbolic link, the times of the link itself are set
+/// instead of the file it points to.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+#[inline]
+pub fn set_file_times(
+    path: impl AsRef<Path>, atime: Option<TimeSpec>, mtime: Option<TimeSpec>, no_deref: bool,
+) -> io::Result<()> {
+    let path = CString::new(path.as_ref().as_os_str().as_bytes())?;
+    let omit = TimeSpec { tv_sec: 0, tv_nsec: libc::UTIME_OMIT as _ };
+    let times = [atime.unwrap_or(omit), mtime.unwrap_or(omit)];
+    let flags = if no_deref { libc::AT_SYMLINK_NOFOLLOW } else { 0 };
+
+    let result = unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), times.as_ptr(), flags) };
+    match result {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
 /// Get the time structure with local time offset.
 ///
 /// # Errors
@@ -37,7 +89,11 @@ pub fn local_time(timestamp: i64) -> io::Result<Tm> {
 
     let tm_ptr = unsafe { localtime_r(&(timestamp as Time), tm.as_mut_ptr()) };
 
-    if tm_ptr.is_null() { Err(io::Error::last_os_error()) } else { Ok(unsafe { tm.assume_init() }) }
+    if tm_ptr.is_null() {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(unsafe { tm.assume_init() })
+    }
 }
 
 #[derive(Debug)]
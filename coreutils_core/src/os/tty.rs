@@ -4,18 +4,28 @@ use std::{
     error::Error as StdError,
     ffi::CStr,
     fmt::{self, Display},
-    os::unix::io::AsRawFd,
+    os::{
+        raw::c_char,
+        unix::io::{AsRawFd, RawFd},
+    },
 };
 
 // use crate::file_descriptor::FileDescriptor;
 use bstr::{BStr, BString, ByteSlice};
-use libc::{ioctl, ttyname, winsize, TIOCGWINSZ};
+use libc::{
+    close, getsid, ioctl, open, tcgetsid, ttyname_r, winsize, O_CLOEXEC, O_NOCTTY, O_RDONLY,
+    TIOCGWINSZ,
+};
+#[cfg(not(any(target_os = "fuchsia", target_os = "haiku")))]
+use libc::{dev_t, fstat, S_IFCHR, S_IFMT};
 
 /// Possible errors while trying to get a TTY name
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub enum Error {
     /// Not a TTY error
     NotTty,
+    /// The process has no controlling terminal
+    NoControllingTty,
     /// Any other error
     LibcCall(String, i32),
 }
@@ -25,6 +35,7 @@ impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::NotTty => write!(f, "Not a TTY"),
+            Self::NoControllingTty => write!(f, "No controlling terminal"),
             Self::LibcCall(fn_name, err_code) => {
                 write!(f, "Failed calling {} with this error code: {}", fn_name, err_code)
             },
@@ -40,22 +51,73 @@ impl StdError for Error {}
 pub struct TtyName(BString);
 
 impl TtyName {
-    /// Create a [`TTYName`] from a `file_descriptor`
+    /// Create a [`TtyName`] from a `file_descriptor`
     ///
     /// # Errors
     /// It returns a error variant when `file_descriptor` is not a TTY.
     #[inline]
     pub fn new(file_descriptor: &impl AsRawFd) -> Result<Self, Error> {
-        let name = unsafe { ttyname(file_descriptor.as_raw_fd()) };
+        Self::from_raw_fd(file_descriptor.as_raw_fd())
+    }
 
-        let name = if name.is_null() {
-            return Err(Error::NotTty);
-        } else {
-            let name_cstr = unsafe { CStr::from_ptr(name) };
-            BString::from(name_cstr.to_bytes())
-        };
+    /// Create a [`TtyName`] from a raw file descriptor `fd`.
+    ///
+    /// # Errors
+    /// It returns a error variant when `fd` is not a TTY.
+    #[cfg_attr(feature = "inline-more", inline)]
+    pub fn from_raw_fd(fd: RawFd) -> Result<Self, Error> {
+        // Got this size from `TTY_NAME_MAX` on most systems, we grow it if needed
+        let mut buff: Vec<c_char> = vec![0; 128];
 
-        Ok(TtyName(name))
+        loop {
+            let res = unsafe { ttyname_r(fd, buff.as_mut_ptr(), buff.len()) };
+
+            match res {
+                0 => break,
+                libc::ERANGE => buff.resize(buff.len() * 2, 0),
+                libc::ENOTTY | libc::EBADF => return Err(Error::NotTty),
+                _ => return Err(Error::LibcCall(String::from("ttyname_r"), res)),
+            }
+        }
+
+        let name_cstr = unsafe { CStr::from_ptr(buff.as_ptr()) };
+
+        Ok(TtyName(BString::from(name_cstr.to_bytes())))
+    }
+
+    /// Create a [`TtyName`] of the controlling terminal of the current process.
+    ///
+    /// The standard streams are checked first, since the name of `/dev/tty` itself is
+    /// not the name of the terminal on some systems, where the terminal is found by its
+    /// device number instead.
+    ///
+    /// # Errors
+    /// It returns a error variant when the process has no controlling terminal.
+    #[cfg_attr(feature = "inline-more", inline)]
+    pub fn controlling() -> Result<Self, Error> {
+        let session = unsafe { getsid(0) };
+
+        for fd in 0..=2 {
+            if is_tty_raw(fd) && unsafe { tcgetsid(fd) } == session {
+                return Self::from_raw_fd(fd);
+            }
+        }
+
+        let controlling = ControllingTty::open().ok_or(Error::NoControllingTty)?;
+        let name = Self::from_raw_fd(controlling.0)?;
+
+        if name.0 != "/dev/tty" {
+            return Ok(name);
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            if let Some(name) = controlling.device().and_then(find_tty) {
+                return Ok(name);
+            }
+        }
+
+        Err(Error::NoControllingTty)
     }
 
     /// Extracts a bstring slice containing the entire [`BString`].
@@ -72,6 +134,144 @@ impl Display for TtyName {
     }
 }
 
+/// The controlling terminal opened without becoming the controlling terminal of the
+/// process. It's closed on drop.
+struct ControllingTty(RawFd);
+
+impl ControllingTty {
+    fn open() -> Option<Self> {
+        let fd = unsafe {
+            open(b"/dev/tty\0".as_ptr() as *const c_char, O_RDONLY | O_NOCTTY | O_CLOEXEC)
+        };
+
+        if fd == -1 {
+            None
+        } else {
+            Some(Self(fd))
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl ControllingTty {
+    /// The device number of the terminal, that isn't the one of `/dev/tty`.
+    fn device(&self) -> Option<dev_t> {
+        let mut device: libc::c_uint = 0;
+
+        if unsafe { ioctl(self.0, libc::TIOCGDEV, &mut device) } == -1 {
+            None
+        } else {
+            Some(device.into())
+        }
+    }
+}
+
+/// Find the name of the terminal with the `device` number in `/dev/pts` or `/dev`.
+#[cfg(target_os = "linux")]
+fn find_tty(device: dev_t) -> Option<TtyName> {
+    use std::os::unix::{
+        ffi::OsStringExt,
+        fs::{FileTypeExt, MetadataExt},
+    };
+
+    ["/dev/pts", "/dev"]
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(Result::ok)
+        .find(|entry| {
+            matches!(entry.metadata(), Ok(metadata)
+                if metadata.file_type().is_char_device() && metadata.rdev() == device)
+        })
+        .map(|entry| TtyName(BString::from(entry.path().into_os_string().into_vec())))
+}
+
+impl Drop for ControllingTty {
+    fn drop(&mut self) {
+        unsafe { close(self.0) };
+    }
+}
+
+/// Check if the current process has a controlling terminal.
+///
+/// ## Example
+/// ```rust
+/// use coreutils_core::os::tty::has_controlling_tty;
+/// let has_ctty = has_controlling_tty();
+/// ```
+#[inline]
+pub fn has_controlling_tty() -> bool {
+    ControllingTty::open().is_some()
+}
+
+/// The device number of a TTY.
+#[cfg(not(any(target_os = "fuchsia", target_os = "haiku")))]
+#[derive(Clone, Copy, Debug, PartialOrd, PartialEq, Ord, Eq, Hash)]
+pub struct TtyDevice(dev_t);
+
+#[cfg(not(any(target_os = "fuchsia", target_os = "haiku")))]
+impl TtyDevice {
+    /// Get the device number of the TTY of the `file_descriptor`.
+    ///
+    /// # Errors
+    /// It returns a error variant when `file_descriptor` is not a TTY.
+    #[inline]
+    pub fn new(file_descriptor: &impl AsRawFd) -> Result<Self, Error> {
+        Self::from_raw_fd(file_descriptor.as_raw_fd())
+    }
+
+    /// Get the device number of the TTY of the raw file descriptor `fd`.
+    ///
+    /// # Errors
+    /// It returns a error variant when `fd` is not a TTY.
+    #[inline]
+    pub fn from_raw_fd(fd: RawFd) -> Result<Self, Error> {
+        if !is_tty_raw(fd) {
+            return Err(Error::NotTty);
+        }
+
+        let mut stat = unsafe { std::mem::zeroed() };
+        if unsafe { fstat(fd, &mut stat) } == -1 {
+            let code = std::io::Error::last_os_error().raw_os_error().unwrap_or(0);
+            return Err(Error::LibcCall(String::from("fstat"), code));
+        }
+
+        if stat.st_mode & S_IFMT != S_IFCHR {
+            return Err(Error::NotTty);
+        }
+
+        Ok(Self(stat.st_rdev))
+    }
+
+    /// Get the full device number.
+    #[inline]
+    pub const fn raw(self) -> dev_t {
+        self.0
+    }
+
+    /// Get the major device number.
+    #[allow(unused_unsafe)]
+    #[inline]
+    pub fn major(self) -> u32 {
+        unsafe { libc::major(self.0) as u32 }
+    }
+
+    /// Get the minor device number.
+    #[allow(unused_unsafe)]
+    #[inline]
+    pub fn minor(self) -> u32 {
+        unsafe { libc::minor(self.0) as u32 }
+    }
+}
+
+#[cfg(not(any(target_os = "fuchsia", target_os = "haiku")))]
+impl Display for TtyDevice {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.major(), self.minor())
+    }
+}
+
 /// Convenience trait to use [`is_tty`] function as method
 pub trait IsTty: AsRawFd {
     /// Check if caller is a TTY.
@@ -101,7 +301,19 @@ impl<T: AsRawFd> IsTty for T {
 /// ```
 #[inline]
 pub fn is_tty(file_descriptor: &impl AsRawFd) -> bool {
-    unsafe { libc::isatty(file_descriptor.as_raw_fd()) == 1 }
+    is_tty_raw(file_descriptor.as_raw_fd())
+}
+
+/// Check if the given raw file descriptor `fd` is a TTY.
+///
+/// ## Example
+/// ```rust
+/// use coreutils_core::os::tty::is_tty_raw;
+/// let istty = is_tty_raw(0);
+/// ```
+#[inline]
+pub fn is_tty_raw(fd: RawFd) -> bool {
+    unsafe { libc::isatty(fd) == 1 }
 }
 
 /// Gets the width and height of a TTY.
@@ -130,3 +342,17 @@ pub fn tty_dimensions(file_descriptor: &impl AsRawFd) -> Option<(u16, u16)> {
 
     Some((size.ws_col, size.ws_row))
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::{fs::File, os::unix::fs::MetadataExt};
+
+    use super::*;
+
+    #[test]
+    fn find_tty_device() {
+        let device = File::open("/dev/null").unwrap().metadata().unwrap().rdev();
+
+        assert_eq!(find_tty(device).unwrap().as_bstr(), "/dev/null");
+    }
+}
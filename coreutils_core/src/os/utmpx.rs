@@ -266,6 +266,128 @@ impl Utmpx {
     pub const fn exit_status(&self) -> ExitStatus {
         self.exit
     }
+
+    /// Returns `true` if the entry is the record of the system boot.
+    #[inline]
+    pub fn is_boot_time(&self) -> bool {
+        self.ut_type == UtmpxKind::BootTime
+    }
+
+    /// Returns `true` if the entry is a run level change record.
+    #[inline]
+    pub fn is_run_level(&self) -> bool {
+        self.ut_type == UtmpxKind::RunLevel
+    }
+
+    /// Returns `true` if the entry is the record of a session leader that exited.
+    #[inline]
+    pub fn is_dead_process(&self) -> bool {
+        self.ut_type == UtmpxKind::DeadProcess
+    }
+
+    /// Returns `true` if the entry is the record of a system login process (like
+    /// getty(8)) waiting for a user to log in.
+    #[inline]
+    pub fn is_login_process(&self) -> bool {
+        self.ut_type == UtmpxKind::LoginProcess
+    }
+
+    /// Returns `true` if the entry is the record of a logged-in user.
+    #[inline]
+    pub fn is_user_process(&self) -> bool {
+        self.ut_type == UtmpxKind::UserProcess
+    }
+
+    /// Get the run level information if the entry is a run level record.
+    ///
+    /// The run level is encoded in the process ID field of the record, where the lower byte
+    /// is the current run level and the next byte is the previous run level.
+    #[inline]
+    pub fn run_level(&self) -> Option<RunLevel> {
+        if !self.is_run_level() {
+            return None;
+        }
+
+        let current = (self.pid % 256) as u8;
+        let previous = (self.pid / 256) as u8;
+
+        Some(RunLevel {
+            current: current as char,
+            previous: if previous.is_ascii_graphic() { Some(previous as char) } else { None },
+        })
+    }
+
+    /// Get the process exit information if the entry is a dead process record.
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "solaris",
+        target_os = "illumos"
+    ))]
+    #[inline]
+    pub fn process_exit(&self) -> Option<ProcessExit> {
+        if !self.is_dead_process() {
+            return None;
+        }
+
+        Some(ProcessExit {
+            pid: self.pid,
+            termination: i32::from(self.exit.e_termination),
+            exit: i32::from(self.exit.e_exit),
+        })
+    }
+}
+
+/// The run level information of a [`Utmpx`] run level record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RunLevel {
+    /// The current run level.
+    pub current: char,
+    /// The previous run level, if any.
+    pub previous: Option<char>,
+}
+
+impl Display for RunLevel {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "run-level {}", self.current)?;
+
+        if let Some(previous) = self.previous {
+            write!(f, " last={}", previous)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The exit information of a [`Utmpx`] dead process record.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "netbsd",
+    target_os = "solaris",
+    target_os = "illumos"
+))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProcessExit {
+    /// The process ID of the session leader that exited.
+    pub pid: Pid,
+    /// Process termination status.
+    pub termination: i32,
+    /// Process exit status.
+    pub exit: i32,
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "netbsd",
+    target_os = "solaris",
+    target_os = "illumos"
+))]
+impl Display for ProcessExit {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "id={} term={} exit={}", self.pid, self.termination, self.exit)
+    }
 }
 
 impl From<utmpx> for Utmpx {
@@ -484,6 +606,42 @@ impl UtmpxSet {
         UtmpxSet(set)
     }
 
+    /// Get the most recent system boot record of the collection, if any.
+    #[inline]
+    pub fn boot_time(&self) -> Option<&Utmpx> {
+        self.0.iter().filter(|u| u.is_boot_time()).max_by_key(|u| u.login_time())
+    }
+
+    /// Get the most recent run level record of the collection, if any.
+    #[inline]
+    pub fn run_level(&self) -> Option<&Utmpx> {
+        self.0.iter().filter(|u| u.is_run_level()).max_by_key(|u| u.login_time())
+    }
+
+    /// Creates a iterator over the dead process records of the collection.
+    #[inline]
+    pub fn dead_processes(&self) -> impl Iterator<Item = &Utmpx> {
+        self.kind(UtmpxKind::DeadProcess)
+    }
+
+    /// Creates a iterator over the login process records of the collection.
+    #[inline]
+    pub fn login_processes(&self) -> impl Iterator<Item = &Utmpx> {
+        self.kind(UtmpxKind::LoginProcess)
+    }
+
+    /// Creates a iterator over the user process records of the collection.
+    #[inline]
+    pub fn user_processes(&self) -> impl Iterator<Item = &Utmpx> {
+        self.kind(UtmpxKind::UserProcess)
+    }
+
+    /// Creates a iterator over the entries of the collection of the given `kind`.
+    #[inline]
+    pub fn kind(&self, kind: UtmpxKind) -> impl Iterator<Item = &Utmpx> {
+        self.0.iter().filter(move |u| u.entry_type() == kind)
+    }
+
     /// Returns `true` if collection nas no elements.
     #[inline]
     pub fn is_empty(&self) -> bool {
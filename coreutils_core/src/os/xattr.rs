@@ -0,0 +1,131 @@
+//! Module for the extended attributes of the files.
+//!
+//! The extended attributes are name and value pairs kept with a file, with the names in
+//! namespaces like `user.mime_type` and `security.selinux`, as described on the
+//! `xattr(7)` man page.
+//!
+//! All the functions take a `follow` argument: if the file is a symbolic link, the
+//! attributes of the file it points to are used if it is `true`, and the ones of the link
+//! itself if not.
+
+use std::{ffi::CString, io, os::unix::ffi::OsStrExt, path::Path, ptr};
+
+use bstr::{BString, ByteSlice};
+use libc::{c_char, c_void, ssize_t};
+
+/// Get the names of the extended attributes of the file in `path`.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+pub fn list(path: impl AsRef<Path>, follow: bool) -> io::Result<Vec<BString>> {
+    let path = c_path(path.as_ref())?;
+
+    let names = read_sized(|buff, size| unsafe {
+        if follow {
+            libc::listxattr(path.as_ptr(), buff as *mut c_char, size)
+        } else {
+            libc::llistxattr(path.as_ptr(), buff as *mut c_char, size)
+        }
+    })?;
+
+    // The names are separated by their nul terminators
+    Ok(names.split_str("\0").filter(|name| !name.is_empty()).map(BString::from).collect())
+}
+
+/// Get the value of the extended attribute `name` of the file in `path`.
+///
+/// # Errors
+/// If the file has no attribute `name` or a internal call set a errno (I/O OS error), an
+/// error variant will be returned.
+pub fn get(path: impl AsRef<Path>, name: &[u8], follow: bool) -> io::Result<BString> {
+    let path = c_path(path.as_ref())?;
+    let name = CString::new(name)?;
+
+    let value = read_sized(|buff, size| unsafe {
+        if follow {
+            libc::getxattr(path.as_ptr(), name.as_ptr(), buff, size)
+        } else {
+            libc::lgetxattr(path.as_ptr(), name.as_ptr(), buff, size)
+        }
+    })?;
+
+    Ok(BString::from(value))
+}
+
+/// Set the extended attribute `name` of the file in `path` to `value`, creating it if it
+/// doesn't exist.
+///
+/// # Errors
+/// If a internal call set a errno (I/O OS error), an error variant will be returned.
+pub fn set(path: impl AsRef<Path>, name: &[u8], value: &[u8], follow: bool) -> io::Result<()> {
+    let path = c_path(path.as_ref())?;
+    let name = CString::new(name)?;
+    let value_ptr = value.as_ptr() as *const c_void;
+
+    let result = unsafe {
+        if follow {
+            libc::setxattr(path.as_ptr(), name.as_ptr(), value_ptr, value.len(), 0)
+        } else {
+            libc::lsetxattr(path.as_ptr(), name.as_ptr(), value_ptr, value.len(), 0)
+        }
+    };
+
+    match result {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Remove the extended attribute `name` of the file in `path`.
+///
+/// # Errors
+/// If the file has no attribute `name` or a internal call set a errno (I/O OS error), an
+/// error variant will be returned.
+pub fn remove(path: impl AsRef<Path>, name: &[u8], follow: bool) -> io::Result<()> {
+    let path = c_path(path.as_ref())?;
+    let name = CString::new(name)?;
+
+    let result = unsafe {
+        if follow {
+            libc::removexattr(path.as_ptr(), name.as_ptr())
+        } else {
+            libc::lremovexattr(path.as_ptr(), name.as_ptr())
+        }
+    };
+
+    match result {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+fn c_path(path: &Path) -> io::Result<CString> {
+    Ok(CString::new(path.as_os_str().as_bytes())?)
+}
+
+/// Read the data of a call that returns its size when given a empty buffer, trying again
+/// if it grew between the calls.
+fn read_sized<F>(mut call: F) -> io::Result<Vec<u8>>
+where
+    F: FnMut(*mut c_void, usize) -> ssize_t,
+{
+    loop {
+        let size = call(ptr::null_mut(), 0);
+        if size < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut buff = vec![0u8; size as usize];
+        let size = call(buff.as_mut_ptr() as *mut c_void, buff.len());
+
+        if size >= 0 {
+            buff.truncate(size as usize);
+            return Ok(buff);
+        }
+
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::ERANGE) {
+            return Err(err);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! The Random module has the sources of the random numbers of the utilities, like the
+//! ones used to shuffle or to name the temporary files.
+
+use std::{
+    collections::hash_map::RandomState,
+    fs::File,
+    hash::{BuildHasher, Hasher},
+    io::{self, BufReader, Read},
+};
+
+/// A source of uniformly distributed random numbers.
+#[derive(Debug)]
+pub enum RandomSource {
+    /// A pseudo random generator (splitmix64) seeded differently on each run.
+    Generator(u64),
+    /// The bytes of a file, like `/dev/urandom`.
+    File(BufReader<File>),
+}
+
+impl RandomSource {
+    /// Creates a pseudo random generator with a random seed.
+    pub fn new() -> Self {
+        RandomSource::Generator(RandomState::new().build_hasher().finish())
+    }
+
+    /// Creates a source that reads the random bytes of `file`.
+    pub fn from_file(file: File) -> Self {
+        RandomSource::File(BufReader::new(file))
+    }
+
+    /// Returns a random number.
+    ///
+    /// # Errors
+    /// If the random bytes can't be read from the file, or it ended, an error variant
+    /// will be returned.
+    pub fn next_u64(&mut self) -> io::Result<u64> {
+        match self {
+            RandomSource::Generator(state) => {
+                *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                let mut z = *state;
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+                Ok(z ^ (z >> 31))
+            },
+            RandomSource::File(file) => {
+                let mut bytes = [0; 8];
+                file.read_exact(&mut bytes).map_err(|err| match err.kind() {
+                    io::ErrorKind::UnexpectedEof => {
+                        io::Error::new(io::ErrorKind::UnexpectedEof, "end of file")
+                    },
+                    _ => err,
+                })?;
+                Ok(u64::from_le_bytes(bytes))
+            },
+        }
+    }
+
+    /// Returns a random number from 0 to `n`, both included.
+    ///
+    /// # Errors
+    /// If the random bytes can't be read from the file, or it ended, an error variant
+    /// will be returned.
+    pub fn up_to(&mut self, n: u64) -> io::Result<u64> {
+        let range = match n.checked_add(1) {
+            Some(range) => range,
+            None => return self.next_u64(),
+        };
+
+        // Reject the numbers of the last incomplete range, so all the results are equally
+        // probable
+        let zone = u64::MAX - (u64::MAX - range + 1) % range;
+        loop {
+            let random = self.next_u64()?;
+            if random <= zone {
+                return Ok(random % range);
+            }
+        }
+    }
+}
+
+impl Default for RandomSource {
+    fn default() -> Self {
+        RandomSource::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn up_to_in_range() {
+        let mut random = RandomSource::Generator(42);
+
+        for n in &[0, 1, 2, 9, 1000, u64::MAX] {
+            for _ in 0..100 {
+                assert!(random.up_to(*n).unwrap() <= *n);
+            }
+        }
+    }
+
+    #[test]
+    fn up_to_covers_range() {
+        let mut random = RandomSource::Generator(7);
+        let mut seen = [false; 6];
+
+        for _ in 0..200 {
+            seen[random.up_to(5).unwrap() as usize] = true;
+        }
+        assert!(seen.iter().all(|&seen| seen));
+    }
+}
@@ -0,0 +1,118 @@
+//! The Size module parses the sizes given to the utilities, that are numbers of bytes or
+//! units with a suffix of the unit, like the `xstrtoumax` function of GNU.
+//!
+//! ## About
+//! The suffixes of the units the utilities take are:
+//! - `c`: 1 byte, `w`: 2 bytes and `b`: 512 bytes.
+//! - `K` or `k`, `M` or `m`, `G` or `g`, `T` or `t`, `P`, `E`, `Z` and `Y`: the powers of
+//!   1024, that can also be written as `KiB`, `MiB` and so on, or the powers of 1000 when
+//!   followed by `B`, like `KB`.
+//!
+//! A size with a suffix but without a number is one of the unit, like `K` for 1024.
+
+use std::{
+    error::Error as StdError,
+    fmt::{self, Display},
+};
+
+/// Enum that holds the errors of the sizes that can't be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeError {
+    /// It is not a size, or has a suffix not taken.
+    Invalid,
+    /// It doesn't fit in 64 bits.
+    TooLarge,
+}
+
+impl Display for SizeError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SizeError::Invalid => write!(f, "invalid size"),
+            SizeError::TooLarge => write!(f, "Value too large for defined data type"),
+        }
+    }
+}
+
+impl StdError for SizeError {}
+
+/// Parse the `size` in bytes, with the suffix of the unit being one of the letters in
+/// `suffixes`, or none.
+///
+/// # Errors
+/// If `size` is not a number with one of the `suffixes` or it doesn't fit in 64 bits, an
+/// error variant will be returned.
+pub fn parse_size(size: &str, suffixes: &str) -> Result<u64, SizeError> {
+    let digits_end = size.find(|c: char| !c.is_ascii_digit()).unwrap_or(size.len());
+    let (number, suffix) = size.split_at(digits_end);
+
+    if number.is_empty() && suffix.is_empty() {
+        return Err(SizeError::Invalid);
+    }
+
+    let mut chars = suffix.chars();
+
+    let (base, power) = match chars.next() {
+        None => (1, 0),
+        Some(letter) if suffixes.contains(letter) => match letter {
+            'c' => (1, 1),
+            'w' => (2, 1),
+            'b' => (512, 1),
+            'K' | 'k' => (1024, 1),
+            'M' | 'm' => (1024, 2),
+            'G' | 'g' => (1024, 3),
+            'T' | 't' => (1024, 4),
+            'P' => (1024, 5),
+            'E' => (1024, 6),
+            'Z' => (1024, 7),
+            'Y' => (1024, 8),
+            _ => return Err(SizeError::Invalid),
+        },
+        Some(_) => return Err(SizeError::Invalid),
+    };
+
+    // Only the powers of 1024 can be written as the powers of 1000
+    let base = match chars.as_str() {
+        "" => base,
+        "iB" if base == 1024 => 1024,
+        "B" if base == 1024 => 1000,
+        _ => return Err(SizeError::Invalid),
+    };
+
+    let number = if number.is_empty() {
+        1
+    } else {
+        number.parse::<u64>().map_err(|_| SizeError::TooLarge)?
+    };
+
+    (0..power).try_fold(number, |bytes, _| bytes.checked_mul(base).ok_or(SizeError::TooLarge))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn units() {
+        assert_eq!(parse_size("10", ""), Ok(10));
+        assert_eq!(parse_size("5K", "K"), Ok(5 * 1024));
+        assert_eq!(parse_size("2KB", "K"), Ok(2000));
+        assert_eq!(parse_size("1MiB", "M"), Ok(1024 * 1024));
+        assert_eq!(parse_size("3b", "bcw"), Ok(3 * 512));
+        assert_eq!(parse_size("3w", "bcw"), Ok(6));
+        assert_eq!(parse_size("K", "K"), Ok(1024));
+        assert_eq!(parse_size("0Y", "Y"), Ok(0));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse_size("", "K"), Err(SizeError::Invalid));
+        assert_eq!(parse_size("5b", "K"), Err(SizeError::Invalid));
+        assert_eq!(parse_size("5kb", "k"), Err(SizeError::Invalid));
+        assert_eq!(parse_size("5bB", "b"), Err(SizeError::Invalid));
+        assert_eq!(parse_size("1.5K", "K"), Err(SizeError::Invalid));
+        assert_eq!(parse_size("-5", ""), Err(SizeError::Invalid));
+        assert_eq!(parse_size("1Z", "Z"), Err(SizeError::TooLarge));
+        assert_eq!(parse_size("99999999999999999999", ""), Err(SizeError::TooLarge));
+    }
+}
@@ -0,0 +1,202 @@
+//! The Target module finds where the utilities that copy or move files, like `cp` and
+//! `mv`, put their sources.
+//!
+//! ## About
+//! Like GNU, the operands are classified with the `-t`/`--target-directory` and
+//! `-T`/`--no-target-directory` options:
+//! - With `-t DIRECTORY`, all the operands are sources put in `DIRECTORY`.
+//! - With `-T`, there must be two operands, and the source is put as the destination even
+//!   if it is a directory.
+//! - Without them, the sources are put in the last operand if it is a directory, and a
+//!   single source is put as the last operand if it is not.
+
+use std::{
+    error::Error as StdError,
+    fmt::{self, Display},
+    fs, io,
+    path::Path,
+};
+
+use crate::error::strerror;
+
+/// Where the sources are put.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target<'a> {
+    /// Each source is put in the directory, with its name.
+    Directory { sources: Vec<&'a Path>, directory: &'a Path },
+    /// The source is put as the destination.
+    File { source: &'a Path, dest: &'a Path },
+}
+
+/// Enum that holds the errors of operands that have no target.
+#[derive(Debug)]
+pub enum Error {
+    /// There are no operands.
+    MissingOperand,
+    /// There is only a operand, the source, without `-t`.
+    MissingDestination(String),
+    /// There are more than two operands with `-T`, it holds the first extra one.
+    ExtraOperand(String),
+    /// Both `-t` and `-T` were given.
+    ConflictingOptions,
+    /// The target doesn't exist, it holds if it was given with `-t`.
+    TargetNotFound(String, bool),
+    /// The target is not a directory, it holds if it was given with `-t`.
+    NotADirectory(String, bool),
+    /// The target can't be accessed, it holds if it was given with `-t`.
+    Io(String, bool, io::Error),
+}
+
+impl Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = |directory: &bool| if *directory { "target directory" } else { "target" };
+
+        match self {
+            Error::MissingOperand => write!(f, "missing file operand"),
+            Error::MissingDestination(source) => {
+                write!(f, "missing destination file operand after '{}'", source)
+            },
+            Error::ExtraOperand(operand) => write!(f, "extra operand '{}'", operand),
+            Error::ConflictingOptions => {
+                write!(f, "cannot combine --target-directory (-t) and --no-target-directory (-T)")
+            },
+            Error::TargetNotFound(target, directory) => {
+                write!(f, "{} '{}': No such file or directory", kind(directory), target)
+            },
+            Error::NotADirectory(target, directory) => {
+                write!(f, "{} '{}': Not a directory", kind(directory), target)
+            },
+            Error::Io(target, directory, err) => {
+                write!(f, "{} '{}': {}", kind(directory), target, strerror(err))
+            },
+        }
+    }
+}
+
+impl StdError for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Io(_, _, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Find where the sources of the `operands` are put, with the `target_directory` of `-t`
+/// and if `no_target_directory` with `-T`.
+///
+/// # Errors
+/// If the operands have no target, or the directory they are put in is not one, an error
+/// variant will be returned.
+pub fn find_target<'a>(
+    operands: &[&'a Path], target_directory: Option<&'a Path>, no_target_directory: bool,
+) -> Result<Target<'a>, Error> {
+    if no_target_directory {
+        if target_directory.is_some() {
+            return Err(Error::ConflictingOptions);
+        }
+
+        return match *operands {
+            [] => Err(Error::MissingOperand),
+            [source] => Err(Error::MissingDestination(source.display().to_string())),
+            [source, dest] => Ok(Target::File { source, dest }),
+            [_, _, extra, ..] => Err(Error::ExtraOperand(extra.display().to_string())),
+        };
+    }
+
+    if let Some(directory) = target_directory {
+        if operands.is_empty() {
+            return Err(Error::MissingOperand);
+        }
+
+        check_directory(directory, true)?;
+
+        return Ok(Target::Directory { sources: operands.to_vec(), directory });
+    }
+
+    match *operands {
+        [] => Err(Error::MissingOperand),
+        [source] => Err(Error::MissingDestination(source.display().to_string())),
+        [source, dest] if !dest.is_dir() => Ok(Target::File { source, dest }),
+        [ref sources @ .., directory] => {
+            check_directory(directory, false)?;
+
+            Ok(Target::Directory { sources: sources.to_vec(), directory })
+        },
+    }
+}
+
+/// Check that the `target` is a directory, given with `-t` if `directory`.
+fn check_directory(target: &Path, directory: bool) -> Result<(), Error> {
+    let name = || target.display().to_string();
+
+    match fs::metadata(target) {
+        Ok(metadata) if metadata.is_dir() => Ok(()),
+        Ok(_) => Err(Error::NotADirectory(name(), directory)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            Err(Error::TargetNotFound(name(), directory))
+        },
+        Err(err) => Err(Error::Io(name(), directory, err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths<'a>(operands: &[&'a str]) -> Vec<&'a Path> {
+        operands.iter().map(|operand| Path::new(*operand)).collect()
+    }
+
+    #[test]
+    fn targets() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+
+        assert_eq!(find_target(&paths(&["a", "b"]), None, false).unwrap(), Target::File {
+            source: Path::new("a"),
+            dest: Path::new("b")
+        });
+        assert_eq!(find_target(&paths(&["a", dir]), None, true).unwrap(), Target::File {
+            source: Path::new("a"),
+            dest: Path::new(dir)
+        });
+        assert_eq!(
+            find_target(&paths(&["a", "b", dir]), None, false).unwrap(),
+            Target::Directory {
+                sources: vec![Path::new("a"), Path::new("b")],
+                directory: Path::new(dir)
+            }
+        );
+        assert_eq!(
+            find_target(&paths(&["a"]), Some(Path::new(dir)), false).unwrap(),
+            Target::Directory { sources: vec![Path::new("a")], directory: Path::new(dir) }
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = |operands: &[&str], target_directory: Option<&str>, no_target_directory| {
+            find_target(&paths(operands), target_directory.map(Path::new), no_target_directory)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(error(&[], None, false), "missing file operand");
+        assert_eq!(error(&["a"], None, false), "missing destination file operand after 'a'");
+        assert_eq!(error(&["a", "b", "c"], None, true), "extra operand 'c'");
+        assert_eq!(
+            error(&["a"], Some("b"), true),
+            "cannot combine --target-directory (-t) and --no-target-directory (-T)"
+        );
+        assert_eq!(
+            error(&["a"], Some("/nowhere"), false),
+            "target directory '/nowhere': No such file or directory"
+        );
+        assert_eq!(
+            error(&["a", "b", "Cargo.toml"], None, false),
+            "target 'Cargo.toml': Not a directory"
+        );
+    }
+}
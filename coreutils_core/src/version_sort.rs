@@ -0,0 +1,142 @@
+//! Module for the comparison of the version numbers in texts, like the names of the files
+//! of the releases of a program, in the order of GNU `filevercmp`.
+use std::cmp::Ordering;
+
+/// Compare `a` and `b` as version numbers, where the digit sequences are compared by
+/// their numerical value and the file suffixes, like `.tar.gz`, only if the rest is the
+/// same.
+pub fn compare(a: &[u8], b: &[u8]) -> Ordering {
+    if a.is_empty() || b.is_empty() {
+        return (!a.is_empty()).cmp(&(!b.is_empty()));
+    }
+
+    // ".", then "..", then the hidden files come first
+    match (a[0] == b'.', b[0] == b'.') {
+        (true, false) => return Ordering::Less,
+        (false, true) => return Ordering::Greater,
+        (true, true) => {
+            for special in &[&b"."[..], b".."] {
+                match (a == *special, b == *special) {
+                    (true, true) => return Ordering::Equal,
+                    (true, false) => return Ordering::Less,
+                    (false, true) => return Ordering::Greater,
+                    _ => {},
+                }
+            }
+        },
+        _ => {},
+    }
+
+    let (a_prefix, b_prefix) = (version_prefix(a), version_prefix(b));
+
+    match compare_version_parts(a_prefix, b_prefix) {
+        Ordering::Equal if a_prefix.len() != a.len() || b_prefix.len() != b.len() => {
+            compare_version_parts(a, b)
+        },
+        ord => ord,
+    }
+}
+
+/// Get `s` without the file suffix, the trailing sequence of dots followed by a letter or
+/// `~` and then letters, digits or `~`, like `.tar.gz`.
+fn version_prefix(s: &[u8]) -> &[u8] {
+    let is_suffix_start = |b: u8| b.is_ascii_alphabetic() || b == b'~';
+    let is_suffix_char = |b: u8| b.is_ascii_alphanumeric() || b == b'~';
+
+    let mut prefix_len = 0;
+    let mut i = 0;
+    loop {
+        while i + 1 < s.len() && s[i] == b'.' && is_suffix_start(s[i + 1]) {
+            i += 2;
+            while i < s.len() && is_suffix_char(s[i]) {
+                i += 1;
+            }
+        }
+
+        if i >= s.len() {
+            break;
+        }
+        i += 1;
+        prefix_len = i;
+    }
+
+    &s[..prefix_len]
+}
+
+/// The order of the non digit characters on the versions: `~`, then the end, then the
+/// letters and then the other characters.
+fn version_char_order(s: &[u8], pos: usize) -> i32 {
+    match s.get(pos) {
+        None => -1,
+        Some(b) if b.is_ascii_digit() => 0,
+        Some(b) if b.is_ascii_alphabetic() => *b as i32,
+        Some(b'~') => -2,
+        Some(b) => *b as i32 + 256,
+    }
+}
+
+/// Compare the alternating sequences of non digits and digits of `a` and `b`.
+fn compare_version_parts(a: &[u8], b: &[u8]) -> Ordering {
+    let is_digit = |s: &[u8], pos: usize| s.get(pos).map(u8::is_ascii_digit).unwrap_or(false);
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() || j < b.len() {
+        while (i < a.len() && !is_digit(a, i)) || (j < b.len() && !is_digit(b, j)) {
+            let ord = version_char_order(a, i).cmp(&version_char_order(b, j));
+            if ord != Ordering::Equal {
+                return ord;
+            }
+            i += 1;
+            j += 1;
+        }
+
+        while a.get(i) == Some(&b'0') {
+            i += 1;
+        }
+        while b.get(j) == Some(&b'0') {
+            j += 1;
+        }
+
+        let mut first_diff = Ordering::Equal;
+        while is_digit(a, i) && is_digit(b, j) {
+            if first_diff == Ordering::Equal {
+                first_diff = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+
+        // The longer number is the greater
+        if is_digit(a, i) {
+            return Ordering::Greater;
+        }
+        if is_digit(b, j) {
+            return Ordering::Less;
+        }
+        if first_diff != Ordering::Equal {
+            return first_diff;
+        }
+    }
+
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_order() {
+        let cmp = |a: &str, b: &str| compare(a.as_bytes(), b.as_bytes());
+
+        assert_eq!(cmp("1.9", "1.10"), Ordering::Less);
+        assert_eq!(cmp("a-1.0~rc1", "a-1.0"), Ordering::Less);
+        assert_eq!(cmp("file-01", "file-1"), Ordering::Equal);
+        assert_eq!(cmp("pkg-1.2.tar.gz", "pkg-1.10.tar.bz2"), Ordering::Less);
+        assert_eq!(cmp("pkg-1.tar.gz", "pkg-1.tar.bz2"), Ordering::Greater);
+        assert_eq!(cmp("", "."), Ordering::Less);
+        assert_eq!(cmp("..", ".a"), Ordering::Less);
+        assert_eq!(cmp(".z", "a"), Ordering::Less);
+        assert_eq!(cmp(".b", ".a_"), Ordering::Less);
+    }
+}
@@ -0,0 +1,200 @@
+//! Module to walk the files given to the utilities that change their attributes, like
+//! `chmod` and `chown`, and with the recursion the trees of the directories under them,
+//! following the symbolic links chosen with `-H`, `-L` or `-P`.
+//!
+//! The directories are visited before their contents, so a mode that allows to read them
+//! is set before they are read, and left after them, so they can be changed once their
+//! contents are. The directories that are already being walked are visited but not walked
+//! again when a symbolic link leads to them.
+
+use std::{
+    fs::{self, Metadata},
+    io,
+    os::unix::fs::MetadataExt,
+    path::Path,
+};
+
+/// Which symbolic links are followed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Follow {
+    /// None of them, with `-P`.
+    Never,
+    /// Only the ones given to [`walk`], with `-H`.
+    CommandLine,
+    /// All of them, with `-L`.
+    Always,
+}
+
+/// Error walking a file.
+#[derive(Debug)]
+pub enum WalkError {
+    /// The file can't be accessed.
+    Access(io::Error),
+    /// The file is a symbolic link to follow whose referent can't be accessed.
+    Dangling(io::Error),
+    /// The contents of the directory can't be read.
+    ReadDir(io::Error),
+}
+
+/// A visitor of the files walked.
+pub trait Visitor {
+    /// Visit the file at `path`, with its `metadata`, that is the one of the symbolic
+    /// link if it is not followed. `given` is whether the file was given to [`walk`].
+    ///
+    /// Returns whether the contents are walked, if it is a directory walked recursively.
+    fn visit(&mut self, path: &Path, metadata: &Metadata, given: bool) -> bool;
+
+    /// Leave the directory at `path`, visited with `metadata`, after its contents.
+    ///
+    /// It is only called for the directories walked recursively whose contents could be
+    /// read.
+    fn leave(&mut self, _path: &Path, _metadata: &Metadata, _given: bool) {}
+
+    /// Handle the `error` that happened walking `path`.
+    fn error(&mut self, path: &Path, error: WalkError);
+}
+
+/// Walk `path`, and its contents if `recursive`, with the symbolic links chosen by
+/// `follow` followed.
+pub fn walk(path: &Path, recursive: bool, follow: Follow, visitor: &mut impl Visitor) {
+    walk_file(path, true, recursive, follow, visitor, &mut Vec::new());
+}
+
+/// Walk `path` inside of the directories with the devices and inodes in `ancestors`.
+fn walk_file(
+    path: &Path, given: bool, recursive: bool, follow: Follow, visitor: &mut impl Visitor,
+    ancestors: &mut Vec<(u64, u64)>,
+) {
+    let followed = match follow {
+        Follow::Never => false,
+        Follow::CommandLine => given,
+        Follow::Always => true,
+    };
+
+    let metadata = if followed { fs::metadata(path) } else { fs::symlink_metadata(path) };
+    let metadata = match metadata {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            let is_symlink = followed
+                && matches!(fs::symlink_metadata(path), Ok(meta) if meta.file_type().is_symlink());
+            visitor.error(
+                path,
+                if is_symlink { WalkError::Dangling(err) } else { WalkError::Access(err) },
+            );
+            return;
+        },
+    };
+
+    if !visitor.visit(path, &metadata, given) || !recursive || !metadata.is_dir() {
+        return;
+    }
+
+    let id = (metadata.dev(), metadata.ino());
+    if ancestors.contains(&id) {
+        visitor.leave(path, &metadata, given);
+        return;
+    }
+
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(err) => {
+            visitor.error(path, WalkError::ReadDir(err));
+            return;
+        },
+    };
+
+    ancestors.push(id);
+
+    for entry in entries {
+        match entry {
+            Ok(entry) => walk_file(&entry.path(), false, recursive, follow, visitor, ancestors),
+            Err(err) => visitor.error(path, WalkError::ReadDir(err)),
+        }
+    }
+
+    ancestors.pop();
+    visitor.leave(path, &metadata, given);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, os::unix::fs::symlink, path::PathBuf};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Names {
+        visited: Vec<(PathBuf, bool)>,
+        dangling: Vec<PathBuf>,
+        left: Vec<PathBuf>,
+    }
+
+    impl Visitor for Names {
+        fn visit(&mut self, path: &Path, metadata: &Metadata, _given: bool) -> bool {
+            self.visited.push((path.to_path_buf(), metadata.file_type().is_symlink()));
+            true
+        }
+
+        fn leave(&mut self, path: &Path, _metadata: &Metadata, _given: bool) {
+            self.left.push(path.to_path_buf());
+        }
+
+        fn error(&mut self, path: &Path, error: WalkError) {
+            match error {
+                WalkError::Dangling(_) => self.dangling.push(path.to_path_buf()),
+                err => panic!("{}: {:?}", path.display(), err),
+            }
+        }
+    }
+
+    fn walked(path: &Path, recursive: bool, follow: Follow) -> Names {
+        let mut names = Names::default();
+        walk(path, recursive, follow, &mut names);
+        names.visited.sort();
+        names
+    }
+
+    #[test]
+    fn follow() {
+        let root = env::temp_dir().join(format!("core.walk.{}", std::process::id()));
+        let dir = root.join("dir");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub/file"), "").unwrap();
+        symlink("..", dir.join("sub/up")).unwrap();
+        symlink("nothing", dir.join("dangling")).unwrap();
+        symlink("dir", root.join("link")).unwrap();
+
+        let link = root.join("link");
+
+        let names = walked(&link, false, Follow::CommandLine);
+        assert_eq!(names.visited, vec![(link.clone(), false)]);
+        assert!(names.left.is_empty());
+
+        let names = walked(&link, true, Follow::Never);
+        assert_eq!(names.visited, vec![(link.clone(), true)]);
+
+        let names = walked(&link, true, Follow::CommandLine);
+        assert_eq!(names.visited, vec![
+            (link.clone(), false),
+            (link.join("dangling"), true),
+            (link.join("sub"), false),
+            (link.join("sub/file"), false),
+            (link.join("sub/up"), true),
+        ]);
+        assert!(names.dangling.is_empty());
+        assert_eq!(names.left, vec![link.join("sub"), link.clone()]);
+
+        // The link to the parent is visited, but not walked again
+        let names = walked(&link, true, Follow::Always);
+        assert_eq!(names.visited, vec![
+            (link.clone(), false),
+            (link.join("sub"), false),
+            (link.join("sub/file"), false),
+            (link.join("sub/up"), false),
+        ]);
+        assert_eq!(names.dangling, vec![link.join("dangling")]);
+        assert_eq!(names.left, vec![link.join("sub/up"), link.join("sub"), link.clone()]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,254 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("SOURCE")
+                .help("Source files and destination file or directory.")
+                .value_names(&["SOURCE", "DEST"])
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("archive")
+                .help("Same as -dR --preserve=all.")
+                .long("archive")
+                .short("a"),
+        )
+        .arg(
+            Arg::with_name("backup")
+                .help("Make a backup of each existing destination file.")
+                .long_help(
+                    "Make a backup of each existing destination file.\n\nCONTROL is 'none' or \
+                     'off' to never make backups, 'numbered' or 't' to make numbered backups, \
+                     'existing' or 'nil' to make numbered backups of the files that have them and \
+                     simple backups of the others, or 'simple' or 'never' to make simple backups. \
+                     Without it the VERSION_CONTROL environment variable is used, and 'existing' \
+                     by default.",
+                )
+                .long("backup")
+                .value_name("CONTROL")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&[
+                    "none", "off", "numbered", "t", "existing", "nil", "simple", "never",
+                ]),
+        )
+        .arg(
+            Arg::with_name("backup_default")
+                .help("Same as --backup but does not accept a argument.")
+                .short("b"),
+        )
+        .arg(
+            Arg::with_name("suffix")
+                .help("Override the usual backup suffix, '~' by default.")
+                .long_help(
+                    "Override the usual backup suffix. Without it the SIMPLE_BACKUP_SUFFIX \
+                     environment variable is used, and '~' by default.\n\nIt also makes the \
+                     backups like --backup.",
+                )
+                .long("suffix")
+                .short("S")
+                .value_name("SUFFIX")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("no_dereference_preserve_links")
+                .help("Same as --no-dereference --preserve=links.")
+                .short("d"),
+        )
+        .arg(
+            Arg::with_name("dereference_command_line")
+                .help("Follow the symbolic links of the SOURCE arguments.")
+                .short("H"),
+        )
+        .arg(
+            Arg::with_name("force")
+                .help("Remove the destination files that can't be opened and try again.")
+                .long("force")
+                .short("f"),
+        )
+        .arg(
+            Arg::with_name("progress")
+                .help("Show a progress bar of the copies of the big files.")
+                .long_help(
+                    "Show a progress bar of the copies of the files of 16 MiB or more, with the \
+                     bytes copied, the throughput and the estimated time left.",
+                )
+                .long("progress")
+                .short("g"),
+        )
+        .arg(
+            Arg::with_name("interactive")
+                .help("Prompt before overwriting the destination files.")
+                .long_help(
+                    "Prompt before overwriting the destination files.\n\nThis option overrides \
+                     any previous -n option.",
+                )
+                .long("interactive")
+                .short("i"),
+        )
+        .arg(
+            Arg::with_name("dereference")
+                .help("Always follow the symbolic links in SOURCE.")
+                .long("dereference")
+                .short("L"),
+        )
+        .arg(
+            Arg::with_name("no_clobber")
+                .help("Do not overwrite the destination files.")
+                .long_help(
+                    "Do not overwrite the destination files.\n\nThis option overrides any \
+                     previous -i option, and any later --update option.",
+                )
+                .long("no-clobber")
+                .short("n"),
+        )
+        .arg(
+            Arg::with_name("no_dereference")
+                .help("Never follow the symbolic links in SOURCE.")
+                .long_help(
+                    "Never follow the symbolic links in SOURCE, copying them as symbolic \
+                     links.\n\nIt is the default with -R, without it the links are followed.",
+                )
+                .long("no-dereference")
+                .short("P"),
+        )
+        .arg(
+            Arg::with_name("preserve_default")
+                .help("Same as --preserve=mode,ownership,timestamps.")
+                .short("p"),
+        )
+        .arg(
+            Arg::with_name("preserve")
+                .help(
+                    "Preserve the attributes of the ATTR_LIST, separated by commas: mode, \
+                     ownership, timestamps, links, context, xattr and all. By default \
+                     mode,ownership,timestamps.",
+                )
+                .long("preserve")
+                .value_name("ATTR_LIST")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .use_delimiter(true)
+                .multiple(true)
+                .possible_values(&[
+                    "mode",
+                    "ownership",
+                    "timestamps",
+                    "links",
+                    "context",
+                    "xattr",
+                    "all",
+                ]),
+        )
+        .arg(
+            Arg::with_name("no_preserve")
+                .help("Do not preserve the attributes of the ATTR_LIST.")
+                .long("no-preserve")
+                .value_name("ATTR_LIST")
+                .takes_value(true)
+                .require_equals(true)
+                .use_delimiter(true)
+                .multiple(true)
+                .possible_values(&[
+                    "mode",
+                    "ownership",
+                    "timestamps",
+                    "links",
+                    "context",
+                    "xattr",
+                    "all",
+                ]),
+        )
+        .arg(
+            Arg::with_name("reflink")
+                .help("Control the clones of the data with copy-on-write.")
+                .long_help(
+                    "Control the clones of the data with copy-on-write, that share it with the \
+                     source until one of them is modified.\n\nWHEN is 'always' to fail if the \
+                     file can't be cloned, 'auto' to copy the data if it can't, or 'never'. It is \
+                     'auto' by default, and 'always' without WHEN.",
+                )
+                .long("reflink")
+                .value_name("WHEN")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&["always", "auto", "never"]),
+        )
+        .arg(
+            Arg::with_name("sparse")
+                .help("Control the holes of the copies of the sparse files.")
+                .long_help(
+                    "Control the holes of the copies of the sparse files.\n\nWHEN is 'auto' (the \
+                     default) to leave holes where the source has them, 'always' to leave them \
+                     also where it has blocks of zeros, or 'never'.",
+                )
+                .long("sparse")
+                .value_name("WHEN")
+                .takes_value(true)
+                .require_equals(true)
+                .possible_values(&["always", "auto", "never"]),
+        )
+        .arg(
+            Arg::with_name("recursive")
+                .help("Copy the directories recursively.")
+                .long_help(
+                    "Copy the directories recursively.\n\nThe named pipes and the device files \
+                     are created again instead of reading from them.",
+                )
+                .long("recursive")
+                .short("R"),
+        )
+        .arg(Arg::with_name("recursive_compat").help("Same as -R.").short("r"))
+        .arg(
+            Arg::with_name("target_directory")
+                .help("Copy all the SOURCE arguments into DIRECTORY.")
+                .long("target-directory")
+                .short("t")
+                .value_name("DIRECTORY")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("no_target_directory")
+                .help("Treat DEST as a normal file, even if it is a directory.")
+                .long_help(
+                    "Treat DEST as a normal file, even if it is a directory.\n\nA directory \
+                     SOURCE is then copied as DEST instead of inside it.",
+                )
+                .long("no-target-directory")
+                .short("T"),
+        )
+        .arg(
+            Arg::with_name("update")
+                .help("Control which existing destination files are replaced.")
+                .long_help(
+                    "Control which existing destination files are replaced.\n\nMODE is 'all' to \
+                     replace them all, as without this option, 'none' to replace none of them, or \
+                     'older' to only replace the ones older than the source file. It is 'older' \
+                     without MODE.\n\nThis option overrides any previous -i option.",
+                )
+                .long("update")
+                .value_name("MODE")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&["all", "none", "older"]),
+        )
+        .arg(
+            Arg::with_name("update_older")
+                .help("Only replace the destination files older than the source files.")
+                .short("u"),
+        )
+}
@@ -0,0 +1,361 @@
+use std::{
+    env,
+    ffi::OsStr,
+    path::{Component, Path},
+    process,
+};
+
+use clap::ArgMatches;
+use coreutils_core::{
+    backup::BackupMode,
+    copier::{
+        selinux_enabled, Copier, CopyOptions, Dereference, Overwrite, Preserve, Reflink, Sparse,
+    },
+    target::{find_target, Target},
+};
+
+mod cli;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let options = copy_options(&matches);
+
+    let operands: Vec<&Path> = matches
+        .values_of("SOURCE")
+        .map(|values| values.map(Path::new).collect())
+        .unwrap_or_default();
+
+    let target = find_target(
+        &operands,
+        matches.value_of("target_directory").map(Path::new),
+        matches.is_present("no_target_directory"),
+    );
+    let target = match target {
+        Ok(target) => target,
+        Err(err) => {
+            eprintln!("cp: {}", err);
+            process::exit(1);
+        },
+    };
+
+    let mut copier = Copier::new("cp", &options);
+    let mut success = true;
+
+    match target {
+        Target::Directory { sources, directory } => {
+            for source in sources {
+                let dest = directory.join(last_component(source));
+
+                success &= copier.copy(source, &dest);
+            }
+        },
+        Target::File { source, dest } => success = copier.copy(source, dest),
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// Get the options of the copies from the `matches`, exiting if they don't go together.
+fn copy_options(matches: &ArgMatches) -> CopyOptions {
+    let archive = matches.is_present("archive");
+    let no_dereference_preserve_links = matches.is_present("no_dereference_preserve_links");
+    let recursive =
+        archive || matches.is_present("recursive") || matches.is_present("recursive_compat");
+
+    // The last of the options of the symbolic links wins, `-a` and `-d` being `-P`
+    let index = |name: &str| matches.index_of(name).unwrap_or(0);
+    let last_dereference = [
+        (index("dereference"), Dereference::Always),
+        (index("dereference_command_line"), Dereference::CommandLine),
+        (index("no_dereference"), Dereference::Never),
+        (index("no_dereference_preserve_links"), Dereference::Never),
+        (index("archive"), Dereference::Never),
+    ]
+    .iter()
+    .filter(|(index, _)| *index > 0)
+    .max_by_key(|(index, _)| *index)
+    .map(|&(_, dereference)| dereference);
+
+    let dereference = match last_dereference {
+        Some(dereference) => dereference,
+        None if recursive => Dereference::Never,
+        None => Dereference::Always,
+    };
+
+    // The options of the existing destinations apply in order, but `-n` is not
+    // overridden by the later `--update`
+    let (mut overwrite, mut update, mut no_clobber) = (Overwrite::Always, false, false);
+
+    let mut overwrite_options: Vec<(usize, &str)> =
+        ["interactive", "no_clobber", "update", "update_older"]
+            .iter()
+            .filter_map(|&name| matches.index_of(name).map(|index| (index, name)))
+            .collect();
+    overwrite_options.sort_unstable();
+
+    for (_, name) in overwrite_options {
+        match (name, matches.value_of("update")) {
+            ("interactive", _) => {
+                overwrite = Overwrite::Ask;
+                no_clobber = false;
+            },
+            ("no_clobber", _) => {
+                overwrite = Overwrite::Never;
+                no_clobber = true;
+            },
+            ("update_older", _) => update = true,
+            ("update", Some(mode)) if !no_clobber => {
+                update = mode == "older";
+                overwrite = if mode == "none" { Overwrite::Never } else { Overwrite::Always };
+            },
+            _ => {},
+        }
+    }
+
+    // Without a value `--update` has no index, but only sets what `-u` does
+    if matches.is_present("update") && matches.value_of("update").is_none() {
+        update = true;
+    }
+
+    let backup = if matches.is_present("backup")
+        || matches.is_present("backup_default")
+        || matches.is_present("suffix")
+    {
+        match matches.value_of("backup") {
+            Some(control) => BackupMode::from(control),
+            None => match env::var("VERSION_CONTROL") {
+                Ok(control) if control.is_empty() => BackupMode::Existing,
+                Ok(control) if BACKUP_CONTROLS.contains(&control.as_str()) => {
+                    BackupMode::from(control.as_str())
+                },
+                Ok(control) => {
+                    eprintln!("cp: invalid argument '{}' for '$VERSION_CONTROL'", control);
+                    process::exit(1);
+                },
+                Err(_) => BackupMode::Existing,
+            },
+        }
+    } else {
+        BackupMode::None
+    };
+
+    if backup != BackupMode::None && overwrite == Overwrite::Never {
+        eprintln!("cp: options --backup and --no-clobber are mutually exclusive");
+        process::exit(1);
+    }
+
+    // A suffix with a slash would put the backups in other directory
+    let suffix = match matches.value_of("suffix") {
+        Some(suffix) => suffix.to_string(),
+        None => env::var("SIMPLE_BACKUP_SUFFIX").unwrap_or_default(),
+    };
+    let suffix = if suffix.is_empty() || suffix.contains('/') { "~".to_string() } else { suffix };
+
+    let reflink = match matches.value_of("reflink") {
+        Some("never") => Reflink::Never,
+        Some("auto") => Reflink::Auto,
+        Some(_) => Reflink::Always,
+        None if matches.is_present("reflink") => Reflink::Always,
+        None => Reflink::Auto,
+    };
+
+    let sparse = match matches.value_of("sparse") {
+        Some("never") => Sparse::Never,
+        Some("always") => Sparse::Always,
+        _ => Sparse::Auto,
+    };
+
+    // A clone has the holes of the source
+    if reflink == Reflink::Always && sparse != Sparse::Auto {
+        eprintln!("cp: --reflink can be used only with --sparse=auto");
+        process::exit(1);
+    }
+
+    // The `--no-preserve` applies after all the options that preserve attributes
+    let mut preserve = Preserve::default();
+    let mut required = Preserve::default();
+    if matches.is_present("preserve_default") {
+        preserve.set(DEFAULT_PRESERVE, true);
+    }
+    if archive {
+        preserve.set(Some("all"), true);
+    }
+    if no_dereference_preserve_links {
+        preserve.links = true;
+    }
+    if matches.is_present("preserve") {
+        let attributes: Vec<&str> =
+            matches.values_of("preserve").map(Iterator::collect).unwrap_or_default();
+
+        if attributes.is_empty() {
+            preserve.set(DEFAULT_PRESERVE, true);
+        } else {
+            preserve.set(attributes.iter().copied(), true);
+            required.set(attributes.into_iter().filter(|&attribute| attribute != "all"), true);
+        }
+    }
+    let mut default_mode = false;
+    if let Some(attributes) = matches.values_of("no_preserve") {
+        let attributes: Vec<&str> = attributes.collect();
+
+        default_mode = attributes.contains(&"mode") || attributes.contains(&"all");
+        preserve.set(attributes.iter().copied(), false);
+        required.set(attributes, false);
+    }
+
+    // The contexts are only preserved by `-a` and `all` when SELinux is enabled
+    if !selinux_enabled() {
+        if required.context {
+            eprintln!("cp: cannot preserve security context without an SELinux-enabled kernel");
+            process::exit(1);
+        }
+        preserve.context = false;
+    }
+
+    if cfg!(not(any(target_os = "linux", target_os = "android"))) && required.xattr {
+        eprintln!("cp: cannot preserve extended attributes, cp is built without xattr support");
+        process::exit(1);
+    }
+
+    CopyOptions {
+        overwrite,
+        update,
+        force: matches.is_present("force"),
+        progress: matches.is_present("progress"),
+        backup,
+        suffix,
+        recursive,
+        dereference,
+        reflink,
+        sparse,
+        preserve,
+        required,
+        default_mode,
+    }
+}
+
+/// The values of `--backup` and the `VERSION_CONTROL` environment variable.
+const BACKUP_CONTROLS: [&str; 8] =
+    ["none", "off", "numbered", "t", "existing", "nil", "simple", "never"];
+
+/// The attributes of `-p` and `--preserve` without a list.
+const DEFAULT_PRESERVE: [&str; 3] = ["mode", "ownership", "timestamps"];
+
+/// Get the last component of the `path`, the name it has in the target directory.
+fn last_component(path: &Path) -> &OsStr {
+    match path.components().next_back() {
+        Some(Component::Normal(name)) => name,
+        Some(component) => component.as_os_str(),
+        None => path.as_os_str(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(args: &[&str]) -> CopyOptions {
+        let args = ["cp"].iter().chain(args).chain(&["a", "b"]);
+        copy_options(&cli::create_app().get_matches_from(args))
+    }
+
+    #[test]
+    fn overwrite() {
+        let overwrite = |args| {
+            let options = options(args);
+            (options.overwrite, options.update)
+        };
+
+        assert_eq!(overwrite(&[]), (Overwrite::Always, false));
+        assert_eq!(overwrite(&["-n", "-i"]), (Overwrite::Ask, false));
+        assert_eq!(overwrite(&["-i", "-n"]), (Overwrite::Never, false));
+        assert_eq!(overwrite(&["-i", "-u"]), (Overwrite::Ask, true));
+        assert_eq!(overwrite(&["-i", "--update"]), (Overwrite::Ask, true));
+        assert_eq!(overwrite(&["-i", "--update=older"]), (Overwrite::Always, true));
+        assert_eq!(overwrite(&["--update=none", "-i"]), (Overwrite::Ask, false));
+        assert_eq!(overwrite(&["-n", "--update=all"]), (Overwrite::Never, false));
+        assert_eq!(overwrite(&["-u", "--update=all"]), (Overwrite::Always, false));
+    }
+
+    #[test]
+    fn backup() {
+        assert_eq!(options(&[]).backup, BackupMode::None);
+        assert_eq!(options(&["--backup=numbered"]).backup, BackupMode::Numbered);
+        assert_eq!(options(&["--backup=off"]).backup, BackupMode::None);
+        assert_eq!(options(&["-S", ".bak"]).suffix, ".bak");
+        assert_eq!(options(&["--suffix=a/b", "-b"]).suffix, "~");
+    }
+
+    #[test]
+    fn dereference() {
+        assert_eq!(options(&[]).dereference, Dereference::Always);
+        assert_eq!(options(&["-R"]).dereference, Dereference::Never);
+        assert_eq!(options(&["-R", "-H"]).dereference, Dereference::CommandLine);
+        assert_eq!(options(&["-a", "-L"]).dereference, Dereference::Always);
+        assert_eq!(options(&["-L", "-d"]).dereference, Dereference::Never);
+    }
+
+    #[test]
+    fn reflink() {
+        assert_eq!(options(&[]).reflink, Reflink::Auto);
+        assert_eq!(options(&["--reflink"]).reflink, Reflink::Always);
+        assert_eq!(options(&["--reflink=never"]).reflink, Reflink::Never);
+        assert_eq!(options(&["--reflink=auto"]).reflink, Reflink::Auto);
+    }
+
+    #[test]
+    fn sparse() {
+        assert_eq!(options(&[]).sparse, Sparse::Auto);
+        assert_eq!(options(&["--sparse=never"]).sparse, Sparse::Never);
+        assert_eq!(options(&["--sparse=always", "--reflink=auto"]).sparse, Sparse::Always);
+    }
+
+    #[test]
+    fn preserve() {
+        let all = Preserve {
+            mode: true,
+            ownership: true,
+            timestamps: true,
+            links: true,
+            context: selinux_enabled(),
+            xattr: true,
+        };
+        let default =
+            Preserve { mode: true, ownership: true, timestamps: true, ..Preserve::default() };
+
+        assert_eq!(options(&[]).preserve, Preserve::default());
+        assert_eq!(options(&["-a"]).preserve, all);
+        assert_eq!(options(&["-p"]).preserve, default);
+        assert_eq!(options(&["--preserve"]).preserve, default);
+        assert_eq!(options(&["--preserve=all"]).preserve, all);
+        assert_eq!(options(&["--preserve=all"]).required, Preserve::default());
+        assert_eq!(options(&["-p", "--preserve=xattr"]).preserve, Preserve {
+            xattr: true,
+            ..default
+        });
+        assert_eq!(options(&["--preserve=xattr"]).required, Preserve {
+            xattr: true,
+            ..Preserve::default()
+        });
+        assert_eq!(options(&["-a", "--no-preserve=mode,links"]).preserve, Preserve {
+            mode: false,
+            links: false,
+            ..all
+        });
+        assert_eq!(options(&["--preserve=links,timestamps"]).preserve, Preserve {
+            timestamps: true,
+            links: true,
+            ..Preserve::default()
+        });
+    }
+
+    #[test]
+    fn last_components() {
+        assert_eq!(last_component(Path::new("a/b")), "b");
+        assert_eq!(last_component(Path::new("a/b/")), "b");
+        assert_eq!(last_component(Path::new("..")), "..");
+    }
+}
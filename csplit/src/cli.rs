@@ -19,15 +19,18 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
         .arg(
             Arg::with_name("PATTERN")
                 .help("Patterns to use when splitting file.")
-                .long_help("Patterns to use when splitting file.\n\n\
-                    PATTERN can be any of:\n\t\
-                    INTEGER            copy lines up to line number\n\t\
-                    /REGEXP/[OFFSET]   copy lines up to line matching REGEXP\n\t\
-                    %REGEXP%[OFFSET]   skip lines up to line matching REGEXP\n\t\
-                    {INTEGER}          repeat preceeding pattern INTEGER times\n\t\
-                    {*}                repeat preceeding pattern indefinitely\n\n\
-                    If an OFFSET is given it should be an integer, either positive or negative. \
-                    An offset without sign is assumed to be positive.")
+                .long_help(
+                    "Patterns to use when splitting file.\n\nPATTERN can be any of:\n  \
+                     INTEGER           copy lines up to line number INTEGER\n  \
+                     /REGEXP/[OFFSET]  copy lines up to line matching REGEXP\n  \
+                     %REGEXP%[OFFSET]  skip lines up to line matching REGEXP\n  \
+                     {INTEGER}         repeat preceding pattern INTEGER times\n  \
+                     {*}               repeat preceding pattern as many times as \
+                     possible\n\nREGEXP is a basic regular expression, and OFFSET an integer \
+                     that moves the line where the file is split, either positive or negative. \
+                     An offset without sign is assumed to be positive.",
+                )
+                .allow_hyphen_values(true)
                 .multiple(true),
         )
         .arg(
@@ -50,7 +53,21 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .long("digits")
                 .short("n")
                 .value_name("DIGITS")
-                .default_value("2"),
+                .default_value("2")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("suffixFormat")
+                .help("Use the printf FORMAT instead of %02d for the suffixes of the files.")
+                .long_help(
+                    "Use the printf FORMAT instead of %02d for the suffixes of the files.\n\n\
+                     FORMAT has one conversion of d, i, u, o, x or X, with the optional flags -, \
+                     0, ' and #, width and precision. It overrides --digits.",
+                )
+                .long("suffix-format")
+                .short("b")
+                .value_name("FORMAT")
+                .allow_hyphen_values(true),
         )
         .arg(
             Arg::with_name("silent")
@@ -59,24 +76,15 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .visible_alias("quiet")
                 .short("s"),
         )
-    // .arg(
-    //     Arg::with_name("elide-empty")
-    //         .help("Remove empty output files.")
-    //         .long("elide-empty-files")
-    //         .short("z"),
-    // )
-    // .arg(
-    //     Arg::with_name("suppress")
-    //         .help("Suppress lines that match a PATTERN.")
-    //         .long("suppress-matched")
-    //         .short("x"),
-    // )
-    // .arg(
-    //     Arg::with_name("suffix-format")
-    //         .help("Format to use for the file suffix")
-    //         .long("suffix-format")
-    //         .short("b")
-    //         .value_name("FORMAT")
-    //         .default_value("%02d")
-    // )
+        .arg(
+            Arg::with_name("elideEmpty")
+                .help("Remove empty output files.")
+                .long("elide-empty-files")
+                .short("z"),
+        )
+        .arg(
+            Arg::with_name("suppressMatched")
+                .help("Suppress the lines where the file is split by a PATTERN.")
+                .long("suppress-matched"),
+        )
 }
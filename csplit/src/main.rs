@@ -1,95 +1,94 @@
 use std::{
+    cmp,
+    collections::VecDeque,
+    ffi::{OsStr, OsString},
     fmt,
-    fs::{remove_file, File},
-    io::{self, stdin, BufRead, BufReader, Read, Write},
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
     process, result,
 };
 
 use clap::ArgMatches;
-use regex::Regex;
 
 mod cli;
+mod pattern;
+mod suffix;
+
+use pattern::{Kind, Pattern, Repeat};
+use suffix::Suffix;
 
 fn main() {
     let matches = cli::create_app().get_matches();
 
-    let mut created = Vec::new();
+    let mut pieces = None;
+
+    if let Err(err) = csplit(&matches, &mut pieces) {
+        eprintln!("csplit: {}", err);
+
+        // The pieces are removed after a failure, once the current one is closed
+        if let Some(pieces) = &mut pieces {
+            if let Err(err) = pieces.close() {
+                eprintln!("csplit: {}", err);
+            }
 
-    match csplit(&matches, &mut created) {
-        Ok(_) => (),
-        Err(err) => {
-            eprintln!("csplit: {}", err);
             if !matches.is_present("keep") {
-                for fname in created {
-                    match remove_file(&fname) {
-                        Ok(_) => (),
-                        Err(err) => eprintln!("csplit: remove file {} failed: {}", fname, err),
-                    }
-                }
+                pieces.remove();
             }
-            process::exit(1);
-        },
-    };
+        }
+
+        process::exit(1);
+    }
 }
 
-fn csplit(args: &ArgMatches, created: &mut Vec<String>) -> Result<()> {
-    // Ok to unwrap because it has default value
-    let prefix = args.value_of("prefix").unwrap();
-    let silent = args.is_present("silent");
+/// Split the input as the `args` say, into the `pieces` that are created once the input
+/// is open.
+///
+/// # Errors
+/// If the arguments are not valid, or the input can't be split as the patterns say, an
+/// error variant will be returned.
+fn csplit<'a>(args: &'a ArgMatches, pieces: &mut Option<Pieces<'a>>) -> Result<()> {
     // Ok to unwrap because it is required
     let filename = args.value_of("FILE").unwrap();
-    // Ok to unwrap because it has default value
-    let digits = parse_number(args.value_of("digits").unwrap())?;
-    let mut reader = open_input(filename)?;
-    let mut filer = Filer::new(prefix, digits, created, silent)?;
-
-    if let Some(patterns) = args.values_of("PATTERN") {
-        let mut patterns = build_patterns(patterns.collect::<Vec<_>>())?;
-        patterns.push(Pattern::new(Box::new(NeverMatcher::new())));
-        patterns.reverse();
-
-        // We know that there is at least one pattern in the list so
-        // we can use unwrap.
-        let mut pattern = patterns.pop().unwrap();
-        let mut lineno = 0;
-        loop {
-            let mut buffer = String::new();
-            match reader.read_line(&mut buffer) {
-                Ok(0) => break,
-                Ok(_) => (),
-                Err(err) => return Err(Error::ReadFailed(filename.to_string(), err)),
-            }
 
-            lineno += 1;
+    let arguments: Vec<_> = match args.values_of("PATTERN") {
+        Some(patterns) => patterns.collect(),
+        None => return Err(Error::MissingOperand(filename.to_string())),
+    };
 
-            let (rotate_file, rotate_pattern) = pattern.match_line(&mut filer, lineno, &buffer);
+    // Ok to unwrap because it has default value
+    let digits = parse_number(args.value_of("digits").unwrap())?;
 
-            if rotate_pattern {
-                // We know that there is at least one more pattern
-                // in the list so we can use unwrap.
-                pattern = patterns.pop().unwrap();
-            }
+    let suffix = match args.value_of("suffixFormat") {
+        Some(format) => Suffix::parse(format)?,
+        None => Suffix::digits(digits),
+    };
 
-            if rotate_file {
-                filer.rotate()?;
-                let lines: Vec<_> = filer.buffer.drain(..).collect();
-                for line in lines {
-                    filer.write_line(&line)?;
-                }
-            }
+    let input = open_input(filename)?;
+    let patterns = pattern::parse_patterns(&arguments)?;
+
+    let pieces = pieces.get_or_insert(Pieces {
+        // Ok to unwrap because it has default value
+        prefix: args.value_of_os("prefix").unwrap(),
+        suffix,
+        elide_empty: args.is_present("elideEmpty"),
+        silent: args.is_present("silent"),
+        created: Vec::new(),
+        current: None,
+    });
+
+    let mut splitter = Splitter {
+        input: Input { reader: input, lines: VecDeque::new(), first: 1, ended: false },
+        pieces,
+        current: 0,
+        suppress: args.is_present("suppressMatched"),
+    };
 
-            pattern.process_line(&mut filer, lineno, &buffer)?;
-        }
-        filer.flush();
-        Ok(())
-    } else {
-        Err(Error::MissingOperand(filename.to_string()))
-    }
+    splitter.split(&patterns)
 }
 
 fn open_input(filename: &str) -> Result<BufReader<Box<dyn Read>>> {
     let input: Box<dyn Read> = if filename == "-" {
-        Box::new(stdin())
+        Box::new(io::stdin())
     } else {
         Box::new(File::open(filename).map_err(|err| Error::OpenFailed(filename.to_string(), err))?)
     };
@@ -97,341 +96,404 @@ fn open_input(filename: &str) -> Result<BufReader<Box<dyn Read>>> {
     Ok(BufReader::new(input))
 }
 
-fn build_patterns(patterns: Vec<&str>) -> Result<Vec<Pattern>> {
-    let mut result = Vec::new();
-    for pattern in patterns {
-        match pattern.chars().clone().next() {
-            Some('0'..='9') => result.push(Pattern::new(LineMatcher::parse(&pattern)?)),
-            Some(ch @ '/') | Some(ch @ '%') => {
-                result.push(Pattern::new(RegexMatcher::parse(&pattern, ch)?))
-            },
+/// The splitter of the input into the pieces.
+struct Splitter<'a, 'b> {
+    input: Input,
+    pieces: &'a mut Pieces<'b>,
+    /// The number of the last line that matched a regular expression, or the one where
+    /// the input was split after it, so the next search starts after it.
+    current: u64,
+    /// Whether the lines where the input is split are removed.
+    suppress: bool,
+}
 
-            Some('{') => {
-                if let Some(pat) = result.last_mut() {
-                    pat.repeat = parse_repeat(&pattern)?;
-                } else {
-                    return Err(Error::InvalidPattern(pattern.to_string()));
+impl Splitter<'_, '_> {
+    /// Split the input with each of the `patterns`, writing the rest of it to the last
+    /// piece.
+    fn split(&mut self, patterns: &[Pattern]) -> Result<()> {
+        for pattern in patterns {
+            let mut repetition = 0;
+
+            while pattern.repeat.includes(repetition) {
+                match &pattern.kind {
+                    Kind::Line(line) => self.split_at_line(*line, repetition)?,
+                    Kind::Regex { .. } => {
+                        if !self.split_at_match(pattern, repetition)? {
+                            // The input ended while the pattern was repeated forever
+                            return Ok(());
+                        }
+                    },
                 }
-            },
 
-            _ => {
-                return Err(Error::InvalidPattern(pattern.to_string()));
-            },
+                repetition += 1;
+            }
         }
+
+        self.pieces.create()?;
+        self.write_rest()?;
+        self.pieces.close()
     }
-    Ok(result)
-}
 
-/// Output files handler.
-///
-/// The filer will rotate the files on request, write lines to the
-/// current file, and keep track of created output files.
-struct Filer<'a> {
-    silent: bool,
-    prefix: &'a str,
-    digits: i32,
-    file_counter: i32,
-    created: &'a mut Vec<String>,
-    writer: File,
-    bytes: usize,
-    current: String,
-    pub buffer: Vec<String>,
-}
+    /// Write the lines to a piece up to the one of the multiple of the `line` by the
+    /// `repetition` counted from 1.
+    fn split_at_line(&mut self, line: u64, repetition: u64) -> Result<()> {
+        let end = line.saturating_mul(repetition + 1);
+        let out_of_range = || Error::OutOfRange(line, repetition);
 
-fn create_file(current: &str, created: &mut Vec<String>) -> Result<File> {
-    match File::create(current) {
-        Ok(file) => {
-            created.push(current.to_string());
-            Ok(file)
-        },
-        Err(err) => Err(Error::CreateFailed(current.to_string(), err)),
-    }
-}
+        self.pieces.create()?;
 
-impl<'a> Filer<'a> {
-    fn new(
-        prefix: &'a str, digits: i32, created: &'a mut Vec<String>, silent: bool,
-    ) -> Result<Filer<'a>> {
-        let current = format!("{0}{2:01$}", prefix, digits as usize, 0);
-        let writer = create_file(&current, created)?;
-        Ok(Filer {
-            current,
-            writer,
-            prefix,
-            digits,
-            created,
-            silent,
-            bytes: 0,
-            file_counter: 1,
-            buffer: Vec::new(),
-        })
-    }
+        if self.suppress && self.input.ended()? {
+            return Err(out_of_range());
+        }
 
-    fn rotate(&mut self) -> Result<()> {
-        self.current = format!("{0}{2:01$}", self.prefix, self.digits as usize, self.file_counter);
-        if !self.silent {
-            println!("{}", self.bytes);
+        for _ in self.input.first..end {
+            match self.input.next()? {
+                Some(line) => self.pieces.write(&line)?,
+                None => return Err(out_of_range()),
+            }
         }
-        self.bytes = 0;
-        self.file_counter += 1;
-        create_file(&self.current, self.created).map(|file| self.writer = file)
-    }
 
-    fn write_line(&mut self, line: &str) -> Result<()> {
-        self.bytes += line.len();
-        self.writer
-            .write_all(line.as_bytes())
-            .map_err(|err| Error::WriteFailed(self.current.clone(), err))
-    }
+        self.pieces.close()?;
 
-    fn flush(&mut self) {
-        if !self.silent {
-            println!("{}", self.bytes);
+        // The line where the input is split must exist
+        if self.suppress {
+            self.input.next()?;
+        } else if self.input.ended()? {
+            return Err(out_of_range());
         }
+
+        Ok(())
     }
-}
 
-#[derive(Debug)]
-enum Error {
-    OutOfRange(usize, Option<i32>),
-    CreateFailed(String, io::Error),
-    OpenFailed(String, io::Error),
-    ReadFailed(String, io::Error),
-    WriteFailed(String, io::Error),
-    InvalidNumber(String),
-    InvalidPattern(String),
-    MissingOperand(String),
-}
+    /// Write the lines to a piece, or skip them, up to the one that matches the regular
+    /// expression of the `pattern`, moved by its offset.
+    ///
+    /// Returns false if the input ended while the pattern is repeated forever.
+    fn split_at_match(&mut self, pattern: &Pattern, repetition: u64) -> Result<bool> {
+        let (regex, offset, skip) = match &pattern.kind {
+            Kind::Regex { regex, offset, skip } => (regex, *offset, *skip),
+            Kind::Line(_) => unreachable!(),
+        };
 
-impl fmt::Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Error::OutOfRange(line, None) => write!(f, "'{}': line number out of range", line),
-            Error::OutOfRange(line, Some(rep)) => {
-                write!(f, "'{}': line number out of range on repetition {}", line, rep)
-            },
-            Error::CreateFailed(file, err) => {
-                write!(f, "cannot open '{}' for writing: {}", file, err)
-            },
-            Error::OpenFailed(file, err) => {
-                write!(f, "cannot open '{}' for reading: {}", file, err)
-            },
-            Error::ReadFailed(file, err) => write!(f, "cannot read '{}': {}", file, err),
-            Error::WriteFailed(file, err) => write!(f, "cannot write '{}': {}", file, err),
-            Error::InvalidNumber(val) => write!(f, "invalid number '{}'", val),
-            Error::InvalidPattern(pat) => write!(f, "{}: invalid pattern", pat),
-            Error::MissingOperand(arg) => write!(f, "missing operand after '{}'", arg),
+        if !skip {
+            self.pieces.create()?;
         }
-    }
-}
 
-type Result<T> = result::Result<T, Error>;
+        let mut number = cmp::max(self.current + 1, self.input.first);
 
-/// Pattern with a matcher and a repeat.
-struct Pattern {
-    /// Number of repetitions. Negative or zero repeat means infinite
-    /// repetition
-    repeat: i32,
+        loop {
+            let line = match self.input.line(number)? {
+                Some(line) => line,
+                None => {
+                    if !skip {
+                        self.write_rest()?;
+                    }
 
-    /// Count of number of matches.
-    count: i32,
+                    return match pattern.repeat {
+                        Repeat::Forever if !skip => self.pieces.close().map(|_| false),
+                        Repeat::Forever => Ok(false),
+                        Repeat::Times(_) => Err(Error::NotFound(pattern.text.clone(), repetition)),
+                    };
+                },
+            };
 
-    matcher: Box<dyn Matcher>,
-}
+            if regex.is_match(line.strip_suffix(b"\n").unwrap_or(line)) {
+                break;
+            }
 
-impl Pattern {
-    fn new(matcher: Box<dyn Matcher>) -> Pattern {
-        Pattern { repeat: 1, count: 1, matcher }
-    }
+            // The lines before a match are before the split when the offset is not negative
+            if offset >= 0 {
+                while self.input.first <= number {
+                    self.move_line(skip)?;
+                }
+            }
 
-    /// Check if line matches.
-    ///
-    /// Returns a pair of booleans. First boolean indicate the the
-    /// file should be rotated, second one if the pattern should be
-    /// rotated.
-    fn match_line(&mut self, _filer: &mut Filer, lineno: usize, line: &str) -> (bool, bool) {
-        let (rotate_file, pattern_matched) = self.matcher.match_line(lineno, line);
-
-        if pattern_matched {
-            self.repeat -= 1;
-            self.count += 1;
-            (rotate_file, self.repeat == 0)
-        } else {
-            (false, false)
+            number += 1;
         }
-    }
 
-    /// Process a line of input.
-    ///
-    /// Return `true` if the pattern is exhausted, `false` otherwise.
-    fn process_line(&mut self, filer: &mut Filer, lineno: usize, line: &str) -> Result<()> {
-        match self.matcher.process_line(filer, lineno, line) {
-            Err(Error::OutOfRange(l, None)) => Err(Error::OutOfRange(l, Some(self.count))),
-            err => err,
+        self.current = number;
+
+        let end = i128::from(number) + i128::from(offset);
+        let first = self.input.first;
+
+        if end < i128::from(first) {
+            return Err(Error::OffsetOutOfRange(pattern.text.clone()));
         }
-    }
-}
 
-/// Matcher trait used to match lines.
-trait Matcher: fmt::Debug {
-    /// Check if a line matches.
-    ///
-    /// Return a pair of booleans:
-    /// - First boolean is `true` if the file rotation should take place.
-    /// - Second boolean is `true` if the line matches.
-    fn match_line(&mut self, lineno: usize, line: &str) -> (bool, bool);
+        for _ in first..end as u64 {
+            if !self.move_line(skip)? {
+                return Err(Error::OffsetOutOfRange(pattern.text.clone()));
+            }
+        }
 
-    /// Process an input line.
-    ///
-    /// Return `true` if the matcher matched the line, `false`
-    /// otherwise.
-    fn process_line(&mut self, filer: &mut Filer, lineno: usize, line: &str) -> Result<()>;
-}
+        if !skip {
+            self.pieces.close()?;
+        }
 
-/// Never match a line.
-///
-/// Used as last pattern when reading to write the rest of the file to
-/// a separate file. It will just write lines to the output file,
-/// never rotate, and never exhaust.
-#[derive(Debug)]
-struct NeverMatcher;
+        if offset > 0 {
+            self.current = end as u64;
+        }
+
+        if self.suppress {
+            self.input.next()?;
+        }
 
-impl NeverMatcher {
-    fn new() -> NeverMatcher {
-        NeverMatcher {}
+        Ok(true)
     }
-}
 
-impl Matcher for NeverMatcher {
-    fn match_line(&mut self, _lineno: usize, _line: &str) -> (bool, bool) {
-        (false, false)
+    /// Move the next line to the current piece, or skip it if `skip`.
+    ///
+    /// Returns false if the input ended.
+    fn move_line(&mut self, skip: bool) -> Result<bool> {
+        match self.input.next()? {
+            Some(line) if !skip => self.pieces.write(&line).map(|_| true),
+            Some(_) => Ok(true),
+            None => Ok(false),
+        }
     }
 
-    fn process_line(&mut self, filer: &mut Filer, _lineno: usize, line: &str) -> Result<()> {
-        filer.write_line(line)
+    /// Write the rest of the input to the current piece.
+    fn write_rest(&mut self) -> Result<()> {
+        while self.move_line(false)? {}
+        Ok(())
     }
 }
 
-/// Line matcher.
-///
-/// Match a specific line count relative to the start of the section.
-#[derive(Debug)]
-struct LineMatcher {
-    lineno: usize,
+/// The lines of the input, that are read as they are needed.
+struct Input {
+    reader: BufReader<Box<dyn Read>>,
+    /// The lines read that are not moved out yet, from the one of the number `first`.
+    lines: VecDeque<Vec<u8>>,
+    first: u64,
+    ended: bool,
 }
 
-impl LineMatcher {
-    fn parse(pattern: &str) -> Result<Box<dyn Matcher>> {
-        let num =
-            pattern.parse::<usize>().map_err(|_| Error::InvalidPattern(pattern.to_string()))?;
-        Ok(Box::new(LineMatcher { lineno: num }))
+impl Input {
+    /// Read a line after the ones read, returning false if the input ended.
+    fn read(&mut self) -> Result<bool> {
+        if self.ended {
+            return Ok(false);
+        }
+
+        let mut line = Vec::new();
+
+        match self.reader.read_until(b'\n', &mut line) {
+            Ok(0) => {
+                self.ended = true;
+                Ok(false)
+            },
+            Ok(_) => {
+                self.lines.push_back(line);
+                Ok(true)
+            },
+            Err(err) => Err(Error::ReadFailed(err)),
+        }
     }
-}
 
-impl Matcher for LineMatcher {
-    fn match_line(&mut self, lineno: usize, _line: &str) -> (bool, bool) {
-        if self.lineno == lineno { (true, true) } else { (false, false) }
+    /// The line of the `number`, that must not be moved out, or none if the input ends
+    /// before it.
+    fn line(&mut self, number: u64) -> Result<Option<&[u8]>> {
+        let index = (number - self.first) as usize;
+
+        while self.lines.len() <= index {
+            if !self.read()? {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(&self.lines[index]))
     }
 
-    fn process_line(&mut self, filer: &mut Filer, lineno: usize, line: &str) -> Result<()> {
-        if self.lineno < lineno {
-            Err(Error::OutOfRange(self.lineno, None))
-        } else {
-            filer.write_line(line)
+    /// Move out the next line, if the input didn't end.
+    fn next(&mut self) -> Result<Option<Vec<u8>>> {
+        if self.lines.is_empty() && !self.read()? {
+            return Ok(None);
         }
+
+        self.first += 1;
+        Ok(self.lines.pop_front())
+    }
+
+    /// Whether there are no lines left.
+    fn ended(&mut self) -> Result<bool> {
+        Ok(self.lines.is_empty() && !self.read()?)
     }
 }
 
-/// Regexp matcher.
-///
-/// Match a line if it matches the regular expression.
-#[derive(Debug)]
-struct RegexMatcher {
-    regex: Regex,
-    skip: bool,
-    offset: i32,
-    line_match: Option<usize>,
+/// The pieces of the input, created one after the other.
+struct Pieces<'a> {
+    prefix: &'a OsStr,
+    suffix: Suffix,
+    /// Whether the empty pieces are removed.
+    elide_empty: bool,
+    /// Whether the sizes of the pieces are not printed.
+    silent: bool,
+    /// The names of the pieces created.
+    created: Vec<OsString>,
+    /// The current piece, and the number of bytes written to it.
+    current: Option<(BufWriter<File>, u64)>,
 }
 
-// /REGEXP/[OFFSET]
-// %REGEXP%[OFFSET]
-impl RegexMatcher {
-    fn new(regex: &str, skip: bool, offset: i32) -> Result<RegexMatcher> {
-        let regex = Regex::new(regex).map_err(|_| Error::InvalidPattern(regex.to_string()))?;
-        Ok(RegexMatcher { skip, regex, offset, line_match: None })
+impl Pieces<'_> {
+    /// Create the next piece, that is written from now on.
+    fn create(&mut self) -> Result<()> {
+        let mut name = self.prefix.to_os_string();
+        name.push(self.suffix.format(self.created.len() as u64));
+
+        let file = File::create(&name).map_err(|err| Error::CreateFailed(name.clone(), err))?;
+
+        self.created.push(name);
+        self.current = Some((BufWriter::new(file), 0));
+        Ok(())
     }
 
-    fn parse(pattern: &str, first: char) -> Result<Box<dyn Matcher>> {
-        let mut chars = pattern.chars().enumerate();
-        let pat_end = match chars.by_ref().skip(1).find(move |&(_, c)| c == first) {
-            Some((pos, _)) => pos,
-            None => {
-                return Err(Error::InvalidPattern(pattern.to_string()));
+    /// Write the `line` to the current piece.
+    fn write(&mut self, line: &[u8]) -> Result<()> {
+        match &mut self.current {
+            Some((writer, bytes)) => {
+                *bytes += line.len() as u64;
+                writer.write_all(line).map_err(|err| Error::WriteFailed(self.name(), err))
             },
-        };
+            None => Ok(()),
+        }
+    }
 
-        let slice = &pattern[pat_end + 1..];
-        let offset = if !slice.is_empty() { parse_number(slice)? } else { 0 };
+    /// Close the current piece, printing its size, or removing it if it is empty and the
+    /// empty pieces are removed.
+    fn close(&mut self) -> Result<()> {
+        let (mut writer, bytes) = match self.current.take() {
+            Some(current) => current,
+            None => return Ok(()),
+        };
 
-        Ok(Box::new(RegexMatcher::new(&pattern[1..pat_end], first == '%', offset)?))
-    }
-}
+        writer.flush().map_err(|err| Error::WriteFailed(self.name(), err))?;
 
-impl Matcher for RegexMatcher {
-    fn match_line(&mut self, lineno: usize, line: &str) -> (bool, bool) {
-        if let Some(the_line) = self.line_match {
-            return (!self.skip, the_line == lineno);
+        if bytes == 0 && self.elide_empty {
+            // So the name is used again by the next piece
+            if let Some(name) = self.created.pop() {
+                fs::remove_file(&name).map_err(|err| Error::RemoveFailed(name, err))?;
+            }
+        } else if !self.silent {
+            println!("{}", bytes);
         }
 
-        if self.regex.is_match(line) {
-            if self.offset > 0 {
-                self.line_match = Some(lineno + self.offset as usize);
-                (!self.skip, false)
-            } else {
-                (!self.skip, true)
+        Ok(())
+    }
+
+    /// Remove the pieces created.
+    fn remove(&mut self) {
+        for name in self.created.drain(..) {
+            if let Err(err) = fs::remove_file(&name) {
+                eprintln!("csplit: {}: {}", name.to_string_lossy(), err);
             }
-        } else {
-            (!self.skip, false)
         }
     }
 
-    fn process_line(&mut self, filer: &mut Filer, _lineno: usize, line: &str) -> Result<()> {
-        if self.offset < 0 {
-            filer.buffer.push(line.to_string());
-            let count = filer.buffer.len() as i32 + self.offset;
-            if count > 0 {
-                let lines: Vec<_> = filer.buffer.drain(0..count as usize).collect();
-                for line in lines {
-                    filer.write_line(&line)?;
-                }
-            }
-        } else if !self.skip {
-            filer.write_line(line)?;
+    /// The name of the current piece.
+    fn name(&self) -> OsString {
+        self.created.last().cloned().unwrap_or_default()
+    }
+}
+
+#[derive(Debug)]
+enum Error {
+    OutOfRange(u64, u64),
+    OffsetOutOfRange(String),
+    NotFound(String, u64),
+    CreateFailed(OsString, io::Error),
+    OpenFailed(String, io::Error),
+    ReadFailed(io::Error),
+    RemoveFailed(OsString, io::Error),
+    WriteFailed(OsString, io::Error),
+    InvalidNumber(String),
+    InvalidPattern(String),
+    ZeroLine(String),
+    SmallerLine(String, u64),
+    MissingDelimiter(String, char),
+    InvalidOffset(String),
+    InvalidRegex(String, String),
+    MissingBrace(String),
+    InvalidRepeat(String),
+    TooManyConversions,
+    MissingConversion,
+    MissingSpecifier,
+    InvalidConversion(String),
+    InvalidFlags(char, char),
+    MemoryExhausted,
+    MissingOperand(String),
+}
+
+/// The part of the messages of the errors of the repeated patterns.
+struct Repetition(u64);
+
+impl fmt::Display for Repetition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0 > 0 {
+            write!(f, " on repetition {}", self.0)
+        } else {
+            Ok(())
         }
-        Ok(())
     }
 }
 
-// {INTEGER}
-// {*}
-fn parse_repeat(pattern: &str) -> Result<i32> {
-    if pattern == "{*}" {
-        Ok(-1)
-    } else {
-        let len = pattern.len() - 1;
-        match &pattern[1..len].parse() {
-            Ok(value) => Ok(*value),
-            Err(_) => Err(Error::InvalidPattern(pattern.to_string())),
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::OutOfRange(line, rep) => {
+                write!(f, "'{}': line number out of range{}", line, Repetition(*rep))
+            },
+            Error::OffsetOutOfRange(pat) => write!(f, "'{}': line number out of range", pat),
+            Error::NotFound(pat, rep) => {
+                write!(f, "'{}': match not found{}", pat, Repetition(*rep))
+            },
+            Error::CreateFailed(file, err)
+            | Error::RemoveFailed(file, err)
+            | Error::WriteFailed(file, err) => write!(f, "{}: {}", file.to_string_lossy(), err),
+            Error::OpenFailed(file, err) => {
+                write!(f, "cannot open '{}' for reading: {}", file, err)
+            },
+            Error::ReadFailed(err) => write!(f, "read error: {}", err),
+            Error::InvalidNumber(val) => write!(f, "invalid number: '{}'", val),
+            Error::InvalidPattern(pat) => write!(f, "'{}': invalid pattern", pat),
+            Error::ZeroLine(pat) => write!(f, "{}: line number must be greater than zero", pat),
+            Error::SmallerLine(pat, last) => {
+                write!(f, "line number '{}' is smaller than preceding line number, {}", pat, last)
+            },
+            Error::MissingDelimiter(pat, delim) => {
+                write!(f, "{}: closing delimiter '{}' missing", pat, delim)
+            },
+            Error::InvalidOffset(pat) => write!(f, "'{}': integer expected after delimiter", pat),
+            Error::InvalidRegex(pat, err) => {
+                write!(f, "'{}': invalid regular expression: {}", pat, err)
+            },
+            Error::MissingBrace(rep) => write!(f, "'{}': '}}' is required in repeat count", rep),
+            Error::InvalidRepeat(rep) => {
+                write!(f, "'{}'}}: integer required between '{{' and '}}'", rep)
+            },
+            Error::TooManyConversions => {
+                write!(f, "too many % conversion specifications in suffix")
+            },
+            Error::MissingConversion => write!(f, "missing % conversion specification in suffix"),
+            Error::MissingSpecifier => write!(f, "missing conversion specifier in suffix"),
+            Error::InvalidConversion(conv) => {
+                write!(f, "invalid conversion specifier in suffix: {}", conv)
+            },
+            Error::InvalidFlags(flag, conv) => {
+                write!(f, "invalid flags in conversion specification: %{}{}", flag, conv)
+            },
+            Error::MemoryExhausted => write!(f, "memory exhausted"),
+            Error::MissingOperand(arg) => write!(f, "missing operand after '{}'", arg),
         }
     }
 }
 
-/// Parse a string slice as a number, or return error.
-fn parse_number(slice: &str) -> Result<i32> {
-    if slice.is_empty() {
-        slice.parse().map_err(|_| Error::InvalidNumber(slice.to_string()))
-    } else {
-        Ok(0)
+type Result<T> = result::Result<T, Error>;
+
+/// Parse the number of digits of the suffixes.
+fn parse_number(slice: &str) -> Result<usize> {
+    match slice.parse::<i32>() {
+        Ok(number) if number >= 0 => Ok(number as usize),
+        _ => Err(Error::InvalidNumber(slice.to_string())),
     }
 }
 
@@ -439,37 +501,11 @@ fn parse_number(slice: &str) -> Result<i32> {
 mod tests {
     use super::*;
 
-    // Macro to assert that an expression matches a pattern.
-    macro_rules! assert_matches {
-        ($xpr:expr, $pat:pat) => {
-            match $xpr {
-                $pat => true,
-                ref xpr => {
-                    panic!("assert_matches: '{:?}' doesn't match '{}'", xpr, stringify!($pat))
-                },
-            }
-        };
-        ($xpr:expr, $pat:pat if $cond:expr) => {
-            match $xpr {
-                $pat if $cond => true,
-                ref xpr => panic!(
-                    "assert_matches: '{:?} if {}' doesn't match '{}'",
-                    xpr,
-                    stringify!($cond),
-                    stringify!($pat)
-                ),
-            }
-        };
-    }
-
     #[test]
-    fn regex_matcher() {
-        assert_matches!(RegexMatcher::parse("/foo/", '/'), Ok(_));
-        assert_matches!(RegexMatcher::parse("/foo%", '/'), Err(_));
-        assert_matches!(RegexMatcher::parse("%foo%", '%'), Ok(_));
-        assert_matches!(RegexMatcher::parse("%foo/", '%'), Err(_));
-        assert_matches!(RegexMatcher::parse("/foo/0", '/'), Ok(_));
-        assert_matches!(RegexMatcher::parse("/foo/1", '/'), Ok(_));
-        assert_matches!(RegexMatcher::parse("/foo/-1", '/'), Ok(_));
+    fn numbers() {
+        assert_eq!(parse_number("3").unwrap(), 3);
+        assert_eq!(parse_number("0").unwrap(), 0);
+        assert_eq!(parse_number("-1").unwrap_err().to_string(), "invalid number: '-1'");
+        assert_eq!(parse_number("x").unwrap_err().to_string(), "invalid number: 'x'");
     }
 }
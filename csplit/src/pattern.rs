@@ -0,0 +1,181 @@
+//! The patterns that say where the input is split.
+use coreutils_core::bre;
+use regex::bytes::Regex;
+
+use crate::{Error, Result};
+
+/// Where a pattern splits the input.
+#[derive(Debug, Clone)]
+pub(crate) enum Kind {
+    /// Before the line of the number, or of a multiple of it on the repetitions.
+    Line(u64),
+    /// Before the line that matches the regular expression, moved by the offset, where
+    /// the lines before it are skipped instead of written to a piece if `skip`.
+    Regex { regex: Regex, offset: i64, skip: bool },
+}
+
+/// How many times a pattern is repeated after its first use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Repeat {
+    Times(u64),
+    /// Until the input ends.
+    Forever,
+}
+
+/// A pattern of the command line.
+#[derive(Debug, Clone)]
+pub(crate) struct Pattern {
+    pub(crate) kind: Kind,
+    pub(crate) repeat: Repeat,
+    /// The argument of the pattern, for the error messages.
+    pub(crate) text: String,
+}
+
+impl Repeat {
+    /// Whether the pattern is used on the `repetition`, where the first use is 0.
+    pub(crate) fn includes(self, repetition: u64) -> bool {
+        match self {
+            Repeat::Times(times) => repetition <= times,
+            Repeat::Forever => true,
+        }
+    }
+}
+
+/// Parse the `arguments` of the patterns, where each pattern may be followed by its
+/// repeat count, printing a warning for each line number that is the same as the one
+/// before it.
+///
+/// # Errors
+/// If a pattern or a repeat count is not valid, or the line numbers are not in order, an
+/// error variant will be returned.
+pub(crate) fn parse_patterns(arguments: &[&str]) -> Result<Vec<Pattern>> {
+    let mut patterns = Vec::with_capacity(arguments.len());
+    let mut last_line = 0;
+    let mut arguments = arguments.iter().peekable();
+
+    while let Some(&text) = arguments.next() {
+        let kind = match text.chars().next() {
+            Some(delimiter @ '/') | Some(delimiter @ '%') => parse_regex(text, delimiter)?,
+            _ => {
+                let line = parse_line(text)?;
+
+                if line < last_line {
+                    return Err(Error::SmallerLine(text.to_string(), last_line));
+                } else if line == last_line {
+                    eprintln!(
+                        "csplit: warning: line number '{}' is the same as preceding line number",
+                        text
+                    );
+                }
+
+                last_line = line;
+                Kind::Line(line)
+            },
+        };
+
+        let repeat = match arguments.peek() {
+            Some(count) if count.starts_with('{') => parse_repeat(arguments.next().unwrap())?,
+            _ => Repeat::Times(0),
+        };
+
+        patterns.push(Pattern { kind, repeat, text: text.to_string() });
+    }
+
+    Ok(patterns)
+}
+
+/// Parse the positive line number of the pattern `text`.
+fn parse_line(text: &str) -> Result<u64> {
+    match text.parse() {
+        Ok(0) => Err(Error::ZeroLine(text.to_string())),
+        Ok(line) => Ok(line),
+        Err(_) => Err(Error::InvalidPattern(text.to_string())),
+    }
+}
+
+/// Parse the pattern `text` of a regular expression between the `delimiter`s, that is
+/// the last one, followed by its optional offset.
+fn parse_regex(text: &str, delimiter: char) -> Result<Kind> {
+    let end = match text[1..].rfind(delimiter) {
+        Some(end) => end + 1,
+        None => return Err(Error::MissingDelimiter(text.to_string(), delimiter)),
+    };
+
+    let translated = bre::translate(&text[1..end])
+        .map_err(|err| Error::InvalidRegex(text.to_string(), err.to_string()))?;
+    let regex = Regex::new(&translated)
+        .map_err(|_| Error::InvalidRegex(text.to_string(), bre::BreError::Invalid.to_string()))?;
+
+    let offset = match &text[end + 1..] {
+        "" => 0,
+        offset => {
+            offset.trim_start().parse().map_err(|_| Error::InvalidOffset(text.to_string()))?
+        },
+    };
+
+    Ok(Kind::Regex { regex, offset, skip: delimiter == '%' })
+}
+
+/// Parse the repeat `count`, that is `{N}` or `{*}`.
+fn parse_repeat(count: &str) -> Result<Repeat> {
+    let inside = match count.strip_suffix('}') {
+        Some(inside) => &inside[1..],
+        None => return Err(Error::MissingBrace(count.to_string())),
+    };
+
+    match inside {
+        "*" => Ok(Repeat::Forever),
+        times => times
+            .parse()
+            .map(Repeat::Times)
+            .map_err(|_| Error::InvalidRepeat(count[..count.len() - 1].to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(arguments: &[&str]) -> Result<Vec<(String, Repeat)>> {
+        parse_patterns(arguments).map(|patterns| {
+            patterns.into_iter().map(|pattern| (pattern.text, pattern.repeat)).collect()
+        })
+    }
+
+    #[test]
+    fn patterns() {
+        let parsed = parse(&["3", "{2}", "/a/+1", "%b%-2", "{*}"]).unwrap();
+
+        assert_eq!(parsed, [
+            ("3".to_string(), Repeat::Times(2)),
+            ("/a/+1".to_string(), Repeat::Times(0)),
+            ("%b%-2".to_string(), Repeat::Forever),
+        ]);
+
+        match &parse_patterns(&["/a/b/ 1"]).unwrap()[0].kind {
+            Kind::Regex { regex, offset, skip } => {
+                assert_eq!(regex.as_str(), "a/b");
+                assert_eq!((*offset, *skip), (1, false));
+            },
+            kind => panic!("not a regular expression: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let message = |arguments: &[&str]| parse(arguments).unwrap_err().to_string();
+
+        assert_eq!(message(&["x"]), "'x': invalid pattern");
+        assert_eq!(message(&["{3}"]), "'{3}': invalid pattern");
+        assert_eq!(message(&["0"]), "0: line number must be greater than zero");
+        assert_eq!(
+            message(&["5", "4"]),
+            "line number '4' is smaller than preceding line number, 5"
+        );
+        assert_eq!(message(&["/a"]), "/a: closing delimiter '/' missing");
+        assert_eq!(message(&["%a%x"]), "'%a%x': integer expected after delimiter");
+        assert_eq!(message(&["/\\(/"]), "'/\\(/': invalid regular expression: Unmatched ( or \\(");
+        assert_eq!(message(&["1", "{2"]), "'{2': '}' is required in repeat count");
+        assert_eq!(message(&["1", "{x}"]), "'{x'}: integer required between '{' and '}'");
+    }
+}
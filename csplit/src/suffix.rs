@@ -0,0 +1,235 @@
+//! The format of the suffixes of the names of the pieces, that is like the one of
+//! `printf` with a conversion of an integer.
+use crate::{Error, Result};
+
+/// The format of a suffix, with the text around its conversion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Suffix {
+    before: String,
+    after: String,
+    /// Whether the number is aligned to the left of the width, instead of the right.
+    left: bool,
+    /// Whether the width is padded with zeros instead of spaces.
+    zero: bool,
+    /// Whether the octal numbers start with a zero, and the hexadecimal ones with `0x`.
+    alternative: bool,
+    width: usize,
+    /// The minimum number of digits.
+    precision: Option<usize>,
+    conversion: Conversion,
+}
+
+/// The conversion of the number of a suffix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Conversion {
+    Decimal,
+    Octal,
+    Hexadecimal,
+    UpperHexadecimal,
+}
+
+impl Suffix {
+    /// The suffix of a decimal number padded with zeros to the `digits`.
+    pub(crate) fn digits(digits: usize) -> Self {
+        Suffix {
+            before: String::new(),
+            after: String::new(),
+            left: false,
+            zero: true,
+            alternative: false,
+            width: digits,
+            precision: None,
+            conversion: Conversion::Decimal,
+        }
+    }
+
+    /// Parse the `format`, that has a conversion of `d`, `i`, `u`, `o`, `x` or `X`, with
+    /// its optional flags `-`, `0`, `'` and `#`, width and precision, where `%%` is a
+    /// `%`.
+    ///
+    /// # Errors
+    /// If there is not exactly one conversion, or it is not valid, an error variant will
+    /// be returned.
+    pub(crate) fn parse(format: &str) -> Result<Self> {
+        let bytes = format.as_bytes();
+        let mut text = [String::new(), String::new()];
+        let mut conversion = None;
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let start = i;
+            let part = conversion.is_some() as usize;
+
+            while i < bytes.len() && bytes[i] != b'%' {
+                i += 1;
+            }
+            text[part].push_str(&format[start..i]);
+
+            if i + 1 < bytes.len() && bytes[i + 1] == b'%' {
+                text[part].push('%');
+                i += 2;
+            } else if i < bytes.len() {
+                if conversion.is_some() {
+                    return Err(Error::TooManyConversions);
+                }
+
+                let (parsed, end) = parse_conversion(bytes, i + 1)?;
+                conversion = Some(parsed);
+                i = end;
+            }
+        }
+
+        let [before, after] = text;
+
+        match conversion {
+            Some(suffix) => Ok(Suffix { before, after, ..suffix }),
+            None => Err(Error::MissingConversion),
+        }
+    }
+
+    /// Format the `number` in the suffix.
+    pub(crate) fn format(&self, number: u64) -> String {
+        let mut digits = match self.conversion {
+            Conversion::Decimal => number.to_string(),
+            Conversion::Octal => format!("{:o}", number),
+            Conversion::Hexadecimal => format!("{:x}", number),
+            Conversion::UpperHexadecimal => format!("{:X}", number),
+        };
+
+        match self.precision {
+            // Like in C, no digits are written for a zero without precision
+            Some(0) if number == 0 => digits.clear(),
+            Some(precision) if precision > digits.len() => {
+                digits.insert_str(0, &"0".repeat(precision - digits.len()));
+            },
+            _ => {},
+        }
+
+        let prefix = match self.conversion {
+            Conversion::Octal if self.alternative && !digits.starts_with('0') => "0",
+            Conversion::Hexadecimal if self.alternative && number != 0 => "0x",
+            Conversion::UpperHexadecimal if self.alternative && number != 0 => "0X",
+            _ => "",
+        };
+
+        let padding = self.width.saturating_sub(prefix.len() + digits.len());
+        let number = if self.left {
+            format!("{}{}{}", prefix, digits, " ".repeat(padding))
+        } else if self.zero && self.precision.is_none() {
+            format!("{}{}{}", prefix, "0".repeat(padding), digits)
+        } else {
+            format!("{}{}{}", " ".repeat(padding), prefix, digits)
+        };
+
+        format!("{}{}{}", self.before, number, self.after)
+    }
+}
+
+/// Parse the conversion of the `format` that starts at `start`, after its `%`, returning
+/// its suffix without the text around it and the position after it.
+fn parse_conversion(format: &[u8], start: usize) -> Result<(Suffix, usize)> {
+    let mut suffix = Suffix::digits(0);
+    suffix.zero = false;
+
+    let mut i = start;
+    // The flags that are not valid with every conversion
+    let mut thousands = false;
+
+    while let Some(&flag) = format.get(i) {
+        match flag {
+            b'-' => suffix.left = true,
+            b'0' => suffix.zero = true,
+            b'\'' => thousands = true,
+            b'#' => suffix.alternative = true,
+            _ => break,
+        }
+        i += 1;
+    }
+
+    suffix.width = parse_digits(format, &mut i)?;
+
+    if format.get(i) == Some(&b'.') {
+        i += 1;
+        suffix.precision = Some(parse_digits(format, &mut i)?);
+    }
+
+    suffix.conversion = match format.get(i) {
+        Some(b'd') | Some(b'i') | Some(b'u') => Conversion::Decimal,
+        Some(b'o') => Conversion::Octal,
+        Some(b'x') => Conversion::Hexadecimal,
+        Some(b'X') => Conversion::UpperHexadecimal,
+        Some(&byte) if byte == b' ' || byte.is_ascii_graphic() => {
+            return Err(Error::InvalidConversion(char::from(byte).to_string()));
+        },
+        Some(byte) => return Err(Error::InvalidConversion(format!("\\{:03o}", byte))),
+        None => return Err(Error::MissingSpecifier),
+    };
+
+    let conversion = char::from(format[i]);
+
+    if suffix.conversion == Conversion::Decimal && suffix.alternative {
+        return Err(Error::InvalidFlags('#', conversion));
+    } else if suffix.conversion != Conversion::Decimal && thousands {
+        return Err(Error::InvalidFlags('\'', conversion));
+    }
+
+    Ok((suffix, i + 1))
+}
+
+/// Parse the digits of the `format` at the position `i`, moving it after them.
+///
+/// # Errors
+/// If the number doesn't fit in an `int` of C, so the suffixes can't be written, an
+/// error variant will be returned.
+fn parse_digits(format: &[u8], i: &mut usize) -> Result<usize> {
+    let start = *i;
+
+    while matches!(format.get(*i), Some(byte) if byte.is_ascii_digit()) {
+        *i += 1;
+    }
+
+    match std::str::from_utf8(&format[start..*i]).map(str::parse::<i32>) {
+        _ if start == *i => Ok(0),
+        Ok(Ok(number)) => Ok(number as usize),
+        _ => Err(Error::MemoryExhausted),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(format: &str, number: u64) -> String {
+        Suffix::parse(format).unwrap().format(number)
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(Suffix::digits(2).format(7), "07");
+        assert_eq!(Suffix::digits(2).format(123), "123");
+        assert_eq!(format("%02d", 3), "03");
+        assert_eq!(format("a%03xb", 255), "a0ffb");
+        assert_eq!(format("%#x", 0), "0");
+        assert_eq!(format("%#06X", 10), "0X000A");
+        assert_eq!(format("%#o", 8), "010");
+        assert_eq!(format("%-4d|", 5), "5   |");
+        assert_eq!(format("%5.3i", 5), "  005");
+        assert_eq!(format("%.0u", 0), "");
+        assert_eq!(format("%%%'u%%", 1), "%1%");
+    }
+
+    #[test]
+    fn errors() {
+        let message = |format: &str| Suffix::parse(format).unwrap_err().to_string();
+
+        assert_eq!(message("%d%d"), "too many % conversion specifications in suffix");
+        assert_eq!(message("x%%"), "missing % conversion specification in suffix");
+        assert_eq!(message("%"), "missing conversion specifier in suffix");
+        assert_eq!(message("%+d"), "invalid conversion specifier in suffix: +");
+        assert_eq!(message("%\u{1}"), "invalid conversion specifier in suffix: \\001");
+        assert_eq!(message("%é"), "invalid conversion specifier in suffix: \\303");
+        assert_eq!(message("%#d"), "invalid flags in conversion specification: %#d");
+        assert_eq!(message("%'x"), "invalid flags in conversion specification: %'x");
+        assert_eq!(message("%9999999999d"), "memory exhausted");
+    }
+}
@@ -20,30 +20,47 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
         .arg(
             Arg::with_name("bytes")
                 .help("Select only these bytes.")
-                .long_help("Select only these bytes.\n\nThe LIST specifies byte positions.")
+                .long_help(
+                    "Select only these bytes.\n\nThe LIST specifies byte positions, which may \
+                     split the multibyte characters unless the -n option is specified.",
+                )
                 .long("bytes")
                 .short("b")
                 .value_name("LIST")
+                .allow_hyphen_values(true)
                 .conflicts_with_all(&["chars", "fields"]),
         )
         .arg(
             Arg::with_name("chars")
                 .help("Select only these characters.")
                 .long_help(
-                    "Select only these characters.\n\nThe LIST specifies character positions.",
+                    "Select only these characters.\n\nThe LIST specifies character positions. \
+                     Characters are decoded as UTF-8, where each byte of an invalid sequence is a \
+                     character by itself.",
                 )
                 .long("characters")
                 .visible_alias("chars")
                 .short("c")
                 .value_name("LIST")
+                .allow_hyphen_values(true)
                 .conflicts_with_all(&["bytes", "fields"]),
         )
+        .arg(
+            Arg::with_name("no-split")
+                .help("Do not split the multibyte characters selected with the -b option.")
+                .long_help(
+                    "Do not split the multibyte characters selected with the -b option.\n\nA \
+                     range starting inside a character is extended to its first byte, and one \
+                     ending inside a character stops before it.",
+                )
+                .short("n"),
+        )
         .arg(
             Arg::with_name("input-delimiter")
-                .help("Use DELIM instead of TAB for field delimiter.")
+                .help("Use the character DELIM instead of TAB for field delimiter.")
                 .long("delimiter")
                 .short("d")
-                .requires("field")
+                .requires("fields")
                 .value_name("DELIM"),
         )
         .arg(
@@ -62,7 +79,8 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .long("fields")
                 .short("f")
                 .conflicts_with_all(&["bytes", "chars"])
-                .value_name("LIST"),
+                .value_name("LIST")
+                .allow_hyphen_values(true),
         )
         .arg(
             Arg::with_name("complement")
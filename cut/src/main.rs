@@ -1,10 +1,9 @@
 use std::{
-    cmp::min,
+    cmp::{max, min},
     fmt,
     fs::File,
-    io::{self, BufRead, BufReader, Write},
-    num::ParseIntError,
-    process, result, string,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    process, result, str,
 };
 
 use clap::ArgMatches;
@@ -26,15 +25,35 @@ fn main() {
     let complement = matches.is_present("complement");
     let options = Options { line_terminator, complement };
 
-    let result = make_cutter(&matches, &options).and_then(|cutter| {
-        filenames
-            .iter()
-            .map(|filename| cutter.process_file(filename, &options))
-            .collect::<Result<Vec<_>>>()
+    let cutter = make_cutter(&matches, &options).unwrap_or_else(|err| {
+        eprintln!("cut: {}", err);
+        process::exit(1);
     });
 
-    if let Err(err) = result {
-        eprintln!("cut: {}", err);
+    let stdout = io::stdout();
+    let mut output = BufWriter::new(stdout.lock());
+    let mut success = true;
+
+    for filename in filenames {
+        match cutter.process_file(filename, &options, &mut output) {
+            Ok(()) => {},
+            Err(IoError::Read(err)) => {
+                eprintln!("cut: {}: {}", filename, err);
+                success = false;
+            },
+            Err(IoError::Write(err)) => {
+                eprintln!("cut: write error: {}", err);
+                process::exit(1);
+            },
+        }
+    }
+
+    if let Err(err) = output.flush() {
+        eprintln!("cut: write error: {}", err);
+        process::exit(1);
+    }
+
+    if !success {
         process::exit(1);
     }
 }
@@ -47,38 +66,71 @@ struct Options {
 #[derive(PartialEq, Debug)]
 struct Error(String, i32);
 
-impl From<ParseIntError> for Error {
-    fn from(_err: ParseIntError) -> Self {
-        Error("not an integer".to_string(), 2)
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
-impl From<io::Error> for Error {
-    fn from(err: io::Error) -> Self {
-        Error(format!("{}", err), 1)
-    }
+type Result<T> = result::Result<T, Error>;
+
+/// Failure while cutting a file, either reading it or writing the output.
+#[derive(Debug)]
+enum IoError {
+    Read(io::Error),
+    Write(io::Error),
 }
 
-impl From<string::FromUtf8Error> for Error {
-    fn from(err: string::FromUtf8Error) -> Self {
-        Error(format!("{}", err), 1)
-    }
+/// What the positions of a list are counting, which the errors of the list name.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Unit {
+    /// Positions of bytes or characters.
+    Position,
+    /// Numbers of fields.
+    Field,
 }
 
-impl fmt::Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0)
+impl Unit {
+    fn numbered_from_one(self) -> Error {
+        match self {
+            Unit::Position => Error("byte/character positions are numbered from 1".to_string(), 2),
+            Unit::Field => Error("fields are numbered from 1".to_string(), 2),
+        }
+    }
+
+    fn invalid_value(self, value: &str) -> Error {
+        match self {
+            Unit::Position => Error(format!("invalid byte/character position '{}'", value), 2),
+            Unit::Field => Error(format!("invalid field value '{}'", value), 2),
+        }
+    }
+
+    fn too_large(self, value: &str) -> Error {
+        match self {
+            Unit::Position => Error(format!("byte/character offset '{}' is too large", value), 2),
+            Unit::Field => Error(format!("field number '{}' is too large", value), 2),
+        }
+    }
+
+    fn invalid_range(self) -> Error {
+        match self {
+            Unit::Position => Error("invalid byte or character range".to_string(), 2),
+            Unit::Field => Error("invalid field range".to_string(), 2),
+        }
     }
 }
 
-type Result<T> = result::Result<T, Error>;
+/// Whether the character separates the ranges of a list.
+fn is_separator(c: char) -> bool {
+    c == ',' || c == ' ' || c == '\t'
+}
 
 /// Range of the form [start, one-after-end).
 #[derive(Debug, PartialEq, Clone, Copy)]
 struct Range(usize, usize);
 
 impl Range {
-    /// Parse a range into optional beginning and optional end.
+    /// Parse the range at the start of the string, up to the first separator.
     ///
     /// Accepted formats are:
     /// - <number>
@@ -86,31 +138,57 @@ impl Range {
     /// - <number> "-"
     /// - <number> "-" <number>
     /// # Errors
-    fn from_string(string: &str) -> Result<Self> {
-        let v: Vec<&str> = string.split('-').collect();
-        if string.is_empty() || v.is_empty() || v.len() > 2 {
-            return Err(Error("invalid byte or character range".to_string(), 2));
-        }
-
-        // An interval with no endpoints at all should give an error.
-        if v.len() == 2 && v[0].is_empty() && v[1].is_empty() {
-            return Err(Error("invalid range with no endpoint".to_string(), 2));
+    /// Like GNU, an invalid character is reported with the rest of the string after it.
+    fn from_string(string: &str, unit: Unit) -> Result<Self> {
+        let mut lower = None;
+        let mut upper = None;
+        let mut dash = false;
+        let mut rest = string;
+
+        while let Some(c) = rest.chars().next() {
+            if is_separator(c) {
+                break;
+            } else if c == '-' {
+                if dash {
+                    return Err(unit.invalid_range());
+                }
+                if lower == Some(0) {
+                    return Err(unit.numbered_from_one());
+                }
+                dash = true;
+                rest = &rest[1..];
+            } else if c.is_ascii_digit() {
+                let len = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+                // The largest value stands for the end of the line.
+                let number = match rest[..len].parse::<usize>() {
+                    Ok(number) if number < usize::MAX => number,
+                    _ => return Err(unit.too_large(&rest[..len])),
+                };
+                if dash {
+                    upper = Some(number);
+                } else {
+                    lower = Some(number);
+                }
+                rest = &rest[len..];
+            } else {
+                return Err(unit.invalid_value(rest));
+            }
         }
 
-        let lower = if v[0].is_empty() { usize::min_value() } else { v[0].parse::<usize>()? - 1 };
-        let upper = if v.len() == 1 {
-            lower + 1
-        } else if v[1].is_empty() {
-            usize::max_value()
-        } else {
-            v[1].parse::<usize>()?
-        };
-
-        if lower >= upper {
-            return Err(Error(format!("invalid range {} ({} >= {})", string, lower + 1, upper), 2));
+        match (lower, upper) {
+            // An interval with no endpoints at all should give an error.
+            (None, None) if dash => Err(Error("invalid range with no endpoint: -".to_string(), 2)),
+            (lower, None) if dash => Ok(Range(lower.unwrap_or(1) - 1, usize::MAX)),
+            (lower, Some(upper)) => {
+                let lower = lower.unwrap_or(1);
+                if upper < lower {
+                    return Err(Error("invalid decreasing range".to_string(), 2));
+                }
+                Ok(Range(lower - 1, upper))
+            },
+            (Some(number), None) if number > 0 => Ok(Range(number - 1, number)),
+            _ => Err(unit.numbered_from_one()),
         }
-
-        Ok(Range(lower, upper))
     }
 }
 
@@ -121,10 +199,17 @@ struct RangeSet {
 }
 
 impl RangeSet {
-    fn from_string(string: &str) -> Result<Self> {
-        // Split the string at commas and parse the pieces as ranges.
-        let ranges =
-            string.split(',').map(|rng| Range::from_string(rng)).collect::<Result<Vec<Range>>>()?;
+    fn from_string(string: &str, unit: Unit) -> Result<Self> {
+        // Split the string at the separators and parse the pieces as ranges.
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        loop {
+            ranges.push(Range::from_string(&string[start..], unit)?);
+            match string[start..].find(is_separator) {
+                Some(len) => start += len + 1,
+                None => break,
+            }
+        }
         Ok(Self::from_vec(ranges))
     }
 
@@ -134,14 +219,15 @@ impl RangeSet {
         // below.
         ranges.sort_unstable_by_key(|rng| rng.0);
 
-        // Iterate over the ranges and merge ranges if there are
-        // any overlaps.
+        // Iterate over the ranges and merge ranges if there are any
+        // overlaps. Like GNU, ranges that are only adjacent are kept
+        // apart, since an output delimiter is written between them.
         let mut current: Option<Range> = None;
         let mut points = Vec::new();
         for range in &ranges {
             if let Some(rng) = current {
-                if range.0 <= rng.1 {
-                    current = Some(Range(rng.0, range.1));
+                if range.0 < rng.1 {
+                    current = Some(Range(rng.0, max(rng.1, range.1)));
                 } else {
                     points.push(rng);
                     current = Some(*range);
@@ -167,61 +253,171 @@ impl RangeSet {
             }
             carry = range.1;
         }
-        if carry < usize::max_value() {
-            points.push(Range(carry, usize::max_value()));
+        if carry < usize::MAX {
+            points.push(Range(carry, usize::MAX));
         }
         self.points = points;
     }
+
+    /// Whether the position is in one of the ranges.
+    fn contains(&self, position: usize) -> bool {
+        self.points.iter().any(|range| range.0 <= position && position < range.1)
+    }
+
+    /// The position after the end of the last range.
+    fn end(&self) -> usize {
+        self.points.last().map_or(0, |range| range.1)
+    }
 }
 
 // Trait that is used to implement line cutting traits.
 trait Cutter {
-    fn process_line(&self, line: Vec<u8>) -> Result<()>;
+    // Write the selected parts of a line, given without its
+    // terminator, followed by the terminator.
+    fn process_line(
+        &self, line: &[u8], options: &Options, output: &mut dyn Write,
+    ) -> io::Result<()>;
 
     // Process an entire file. The special file name "-" will be
     // reading from standard input.
-    fn process_file(&self, filename: &str, options: &Options) -> Result<()> {
-        let mut reader: Box<dyn io::Read> =
-            if filename == "-" { Box::new(io::stdin()) } else { Box::new(File::open(filename)?) };
-        self.process_input(&mut reader, options)
+    fn process_file(
+        &self, filename: &str, options: &Options, output: &mut dyn Write,
+    ) -> result::Result<(), IoError> {
+        if filename == "-" {
+            let stdin = io::stdin();
+            let mut reader = stdin.lock();
+            self.process_input(&mut reader, options, output)
+        } else {
+            let file = File::open(filename).map_err(IoError::Read)?;
+            self.process_input(&mut BufReader::new(file), options, output)
+        }
     }
 
     // Process input from an already opened reader.
-    fn process_input(&self, reader: &mut dyn io::Read, options: &Options) -> Result<()> {
-        let mut reader = BufReader::new(reader);
-        loop {
-            let mut line = Vec::new();
-            match reader.read_until(options.line_terminator, &mut line) {
-                Ok(count) if count > 0 => self.process_line(line)?,
-                Ok(_) => return Ok(()),
-                Err(err) => return Err(Error(format!("I/O error: {}", err), 1)),
-            }
+    fn process_input(
+        &self, reader: &mut dyn BufRead, options: &Options, output: &mut dyn Write,
+    ) -> result::Result<(), IoError> {
+        process_lines(self, reader, options, output)
+    }
+}
+
+// Process the lines of the input one by one.
+fn process_lines<C: Cutter + ?Sized>(
+    cutter: &C, reader: &mut dyn BufRead, options: &Options, output: &mut dyn Write,
+) -> result::Result<(), IoError> {
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(options.line_terminator, &mut line).map_err(IoError::Read)? == 0 {
+            return Ok(());
+        }
+        if line.last() == Some(&options.line_terminator) {
+            line.pop();
+        }
+        cutter.process_line(&line, options, output).map_err(IoError::Write)?;
+    }
+}
+
+// Write the bytes of the line in the ranges, with the delimiter, if
+// any, between the ranges.
+fn write_positions(
+    bytes: &[u8], ranges: &[Range], delimiter: Option<&str>, output: &mut dyn Write,
+) -> io::Result<()> {
+    // If line is shorter than range give, only print the parts of
+    // the line that are in range.
+    let mut written = false;
+    for range in ranges {
+        if bytes.len() <= range.0 {
+            break;
+        }
+        if let (true, Some(delimiter)) = (written, delimiter) {
+            output.write_all(delimiter.as_bytes())?;
+        }
+        output.write_all(&bytes[range.0..min(bytes.len(), range.1)])?;
+        written = true;
+    }
+    Ok(())
+}
+
+// The positions of the starts of the characters of the line, followed
+// by the length of the line.
+//
+// The bytes that are not part of a valid UTF-8 sequence are
+// characters by themselves.
+fn char_boundaries(bytes: &[u8]) -> Vec<usize> {
+    let mut boundaries = Vec::with_capacity(bytes.len() + 1);
+    let mut start = 0;
+    while start < bytes.len() {
+        let (valid, invalid) = match str::from_utf8(&bytes[start..]) {
+            Ok(valid) => (valid, false),
+            Err(err) => (str::from_utf8(&bytes[start..start + err.valid_up_to()]).unwrap(), true),
+        };
+        boundaries.extend(valid.char_indices().map(|(index, _)| start + index));
+        start += valid.len();
+        if invalid {
+            boundaries.push(start);
+            start += 1;
         }
     }
+    boundaries.push(bytes.len());
+    boundaries
+}
+
+// The start of the character containing the byte at the position.
+fn char_start(boundaries: &[usize], position: usize) -> usize {
+    match boundaries.binary_search(&position) {
+        Ok(_) => position,
+        Err(index) => boundaries[index - 1],
+    }
 }
 
 // A byte cutter that will cut out bytes by position in the line.
 struct Bytes {
     range_set: RangeSet,
+    output_delimiter: Option<String>,
+    /// Whether the ranges are narrowed to keep the multibyte characters whole.
+    no_split: bool,
 }
 
 impl Bytes {
-    fn new(range_set: RangeSet, _matches: &ArgMatches) -> Self {
-        Bytes { range_set }
+    fn new(range_set: RangeSet, matches: &ArgMatches) -> Self {
+        Bytes {
+            range_set,
+            output_delimiter: matches.value_of("output-delimiter").map(String::from),
+            no_split: matches.is_present("no-split"),
+        }
     }
 }
 
 impl Cutter for Bytes {
-    fn process_line(&self, bytes: Vec<u8>) -> Result<()> {
-        // If line is shorter than range give, only print the parts of
-        // the line that are in range.
-        for range in &self.range_set.points {
-            if bytes.len() > range.0 {
-                io::stdout().write_all(&bytes[range.0..min(bytes.len(), range.1)])?;
-            }
+    fn process_line(
+        &self, bytes: &[u8], options: &Options, output: &mut dyn Write,
+    ) -> io::Result<()> {
+        let delimiter = self.output_delimiter.as_deref();
+        if !self.no_split || bytes.is_ascii() {
+            write_positions(bytes, &self.range_set.points, delimiter, output)?;
+            return output.write_all(&[options.line_terminator]);
         }
-        io::stdout().write_all(b"\n")?;
-        Ok(())
+
+        // Like POSIX, a range starting inside a character is extended
+        // to its first byte, and one ending inside a character stops
+        // before it, or is dropped when nothing is left.
+        let boundaries = char_boundaries(bytes);
+        let ranges: Vec<_> = self
+            .range_set
+            .points
+            .iter()
+            .take_while(|range| range.0 < bytes.len())
+            .map(|range| {
+                Range(
+                    char_start(&boundaries, range.0),
+                    char_start(&boundaries, min(bytes.len(), range.1)),
+                )
+            })
+            .filter(|range| range.0 < range.1)
+            .collect();
+        write_positions(bytes, &ranges, delimiter, output)?;
+        output.write_all(&[options.line_terminator])
     }
 }
 
@@ -229,29 +425,53 @@ impl Cutter for Bytes {
 // line.
 struct Chars {
     range_set: RangeSet,
+    output_delimiter: Option<String>,
 }
 
 impl Chars {
-    fn new(range_set: RangeSet, _matches: &ArgMatches) -> Self {
-        Chars { range_set }
+    fn new(range_set: RangeSet, matches: &ArgMatches) -> Self {
+        Chars {
+            range_set,
+            output_delimiter: matches.value_of("output-delimiter").map(String::from),
+        }
     }
 }
 
 impl Cutter for Chars {
-    fn process_line(&self, bytes: Vec<u8>) -> Result<()> {
-        let line: String = String::from_utf8(bytes)?;
-        let pieces: Vec<&str> = self
+    fn process_line(
+        &self, bytes: &[u8], options: &Options, output: &mut dyn Write,
+    ) -> io::Result<()> {
+        let delimiter = self.output_delimiter.as_deref();
+        if bytes.is_ascii() {
+            write_positions(bytes, &self.range_set.points, delimiter, output)?;
+            return output.write_all(&[options.line_terminator]);
+        }
+
+        // Translate the positions of the characters into the
+        // positions of their bytes.
+        let boundaries = char_boundaries(bytes);
+        let count = boundaries.len() - 1;
+        let ranges: Vec<_> = self
             .range_set
             .points
             .iter()
-            .map(|range| {
-                // If line is shorter than range give, only print the
-                // parts of the line that are in range.
-                if line.len() > range.0 { &line[range.0..min(line.len(), range.1)] } else { "" }
-            })
+            .take_while(|range| range.0 < count)
+            .map(|range| Range(boundaries[range.0], boundaries[min(count, range.1)]))
             .collect();
-        println!("{}", pieces.join(""));
-        Ok(())
+        write_positions(bytes, &ranges, delimiter, output)?;
+        output.write_all(&[options.line_terminator])
+    }
+}
+
+// The position of the first occurrence of the delimiter in the
+// bytes.
+//
+// Since a valid UTF-8 sequence never starts inside another one, an
+// occurrence is always a whole character of the line.
+fn find(bytes: &[u8], delimiter: &[u8]) -> Option<usize> {
+    match delimiter {
+        [byte] => bytes.iter().position(|b| b == byte),
+        _ => bytes.windows(delimiter.len()).position(|window| window == delimiter),
     }
 }
 
@@ -259,81 +479,115 @@ impl Cutter for Chars {
 struct Fields {
     range_set: RangeSet,
     only_delimited: bool,
-    input_delimiter: String,
-    output_delimiter: String,
+    /// The encoding of the single character of the delimiter.
+    input_delimiter: Vec<u8>,
+    output_delimiter: Vec<u8>,
 }
 
 impl Fields {
     fn new(range_set: RangeSet, matches: &ArgMatches) -> Result<Self> {
+        // Like GNU, an empty delimiter is the NUL character.
         let idelim = matches.value_of("input-delimiter").unwrap_or("\t");
-        if idelim.len() != 1 {
-            return Err(Error("single character for delimiter".to_string(), 2));
-        }
+        let input_delimiter = match idelim.chars().count() {
+            0 => vec![b'\0'],
+            1 => idelim.as_bytes().to_vec(),
+            _ => return Err(Error("the delimiter must be a single character".to_string(), 2)),
+        };
 
-        let odelim = matches.value_of("output-delimiter").unwrap_or(idelim);
-        if odelim.len() != 1 {
-            return Err(Error("single character for delimiter".to_string(), 2));
-        }
+        let output_delimiter = match matches.value_of("output-delimiter") {
+            Some(delimiter) => delimiter.as_bytes().to_vec(),
+            None => input_delimiter.clone(),
+        };
 
         Ok(Fields {
             range_set,
             only_delimited: matches.is_present("only-delimited"),
-            input_delimiter: idelim.to_string(),
-            output_delimiter: odelim.to_string(),
+            input_delimiter,
+            output_delimiter,
         })
     }
 }
 
 impl Cutter for Fields {
-    fn process_line(&self, bytes: Vec<u8>) -> Result<()> {
-        let line: String = String::from_utf8(bytes)?;
-        let fields: Vec<&str> = line.split(&self.input_delimiter).collect();
-        if !self.only_delimited || fields.len() > 1 {
-            let pieces: Vec<_> = self
-                .range_set
-                .points
-                .iter()
-                .map(|range| {
-                    // If there are fewer fields than what the range
-                    // denotes, we print those fields that are in the
-                    // range.
-                    if fields.len() > range.0 {
-                        fields[range.0..min(fields.len(), range.1)].join(&self.output_delimiter)
-                    } else {
-                        "".to_string()
-                    }
-                })
-                .collect();
-            println!("{}", pieces.join(&self.output_delimiter));
+    fn process_line(
+        &self, bytes: &[u8], options: &Options, output: &mut dyn Write,
+    ) -> io::Result<()> {
+        // Lines without any delimiter are written whole.
+        let mut next = find(bytes, &self.input_delimiter);
+        if next.is_none() {
+            if self.only_delimited {
+                return Ok(());
+            }
+            output.write_all(bytes)?;
+            return output.write_all(&[options.line_terminator]);
+        }
+
+        let end = self.range_set.end();
+        let mut written = false;
+        let mut rest = bytes;
+        for index in 0..end {
+            let field = next.map_or(rest, |position| &rest[..position]);
+            if self.range_set.contains(index) {
+                if written {
+                    output.write_all(&self.output_delimiter)?;
+                }
+                output.write_all(field)?;
+                written = true;
+            }
+
+            match next {
+                Some(position) => rest = &rest[position + self.input_delimiter.len()..],
+                None => break,
+            }
+            next = find(rest, &self.input_delimiter);
+        }
+        output.write_all(&[options.line_terminator])
+    }
+
+    // When the delimiter is the line terminator, the whole input is
+    // a single line of which the lines are the fields.
+    fn process_input(
+        &self, reader: &mut dyn BufRead, options: &Options, output: &mut dyn Write,
+    ) -> result::Result<(), IoError> {
+        if self.input_delimiter != [options.line_terminator] {
+            return process_lines(self, reader, options, output);
+        }
+
+        let mut input = Vec::new();
+        if reader.read_to_end(&mut input).map_err(IoError::Read)? == 0 {
+            return Ok(());
+        }
+        if input.last() == Some(&options.line_terminator) {
+            input.pop();
         }
-        Ok(())
+        self.process_line(&input, options, output).map_err(IoError::Write)
     }
 }
 
 // Factory function to create a cutter from command-line arguments.
 fn make_cutter(matches: &ArgMatches, options: &Options) -> Result<Box<dyn Cutter>> {
     if let Some(rng) = matches.value_of("bytes") {
-        let mut range_set = RangeSet::from_string(rng)?;
+        let mut range_set = RangeSet::from_string(rng, Unit::Position)?;
         if options.complement {
             range_set.complement();
         }
         let cutter = Bytes::new(range_set, matches);
         Ok(Box::new(cutter))
     } else if let Some(rng) = matches.value_of("chars") {
-        let mut range_set = RangeSet::from_string(rng)?;
+        let mut range_set = RangeSet::from_string(rng, Unit::Position)?;
         if options.complement {
             range_set.complement();
         }
         let cutter = Chars::new(range_set, matches);
         Ok(Box::new(cutter))
     } else if let Some(rng) = matches.value_of("fields") {
-        let mut range_set = RangeSet::from_string(rng)?;
+        let mut range_set = RangeSet::from_string(rng, Unit::Field)?;
         if options.complement {
             range_set.complement();
         }
         let cutter = Fields::new(range_set, matches)?;
         Ok(Box::new(cutter))
     } else {
-        Err(Error("not possible to select cutter".to_string(), 1))
+        Err(Error("you must specify a list of bytes, characters, or fields".to_string(), 1))
     }
 }
@@ -12,40 +12,55 @@ macro_rules! assert_matches {
 
 #[test]
 fn range_from_string() {
-    assert_eq!(Range::from_string("2"), Ok(Range(1, 2)));
-    assert_eq!(Range::from_string("-2"), Ok(Range(usize::min_value(), 2)));
-    assert_eq!(Range::from_string("2-"), Ok(Range(1, usize::max_value())));
-    assert_eq!(Range::from_string("2-5"), Ok(Range(1, 5)));
-
-    assert_matches!(Range::from_string(""), Err(Error(_, _)));
-    assert_matches!(Range::from_string("5-2"), Err(Error(_, _)));
-    assert_matches!(Range::from_string("foo"), Err(Error(_, _)));
-    assert_matches!(Range::from_string("2-0x12"), Err(Error(_, _)));
-    assert_matches!(Range::from_string("-"), Err(Error(_, _)));
+    assert_eq!(Range::from_string("2", Unit::Position), Ok(Range(1, 2)));
+    assert_eq!(Range::from_string("-2", Unit::Position), Ok(Range(usize::MIN, 2)));
+    assert_eq!(Range::from_string("2-", Unit::Position), Ok(Range(1, usize::MAX)));
+    assert_eq!(Range::from_string("2-5", Unit::Position), Ok(Range(1, 5)));
+
+    assert_matches!(Range::from_string("", Unit::Position), Err(Error(_, _)));
+    assert_matches!(Range::from_string("5-2", Unit::Position), Err(Error(_, _)));
+    assert_matches!(Range::from_string("foo", Unit::Position), Err(Error(_, _)));
+    assert_matches!(Range::from_string("2-0x12", Unit::Position), Err(Error(_, _)));
+    assert_matches!(Range::from_string("-", Unit::Position), Err(Error(_, _)));
 }
 
 #[test]
 fn rangeset_from_string() {
-    assert_eq!(RangeSet::from_string("2"), Ok(RangeSet { points: vec![Range(1, 2)] }));
     assert_eq!(
-        RangeSet::from_string("-2"),
-        Ok(RangeSet { points: vec![Range(usize::min_value(), 2)] })
+        RangeSet::from_string("2", Unit::Position),
+        Ok(RangeSet { points: vec![Range(1, 2)] })
+    );
+    assert_eq!(
+        RangeSet::from_string("-2", Unit::Position),
+        Ok(RangeSet { points: vec![Range(usize::MIN, 2)] })
+    );
+    assert_eq!(
+        RangeSet::from_string("2,3", Unit::Position),
+        Ok(RangeSet { points: vec![Range(1, 2), Range(2, 3)] })
+    );
+    assert_eq!(
+        RangeSet::from_string("2-3", Unit::Position),
+        Ok(RangeSet { points: vec![Range(1, 3)] })
     );
-    assert_eq!(RangeSet::from_string("2,3"), Ok(RangeSet { points: vec![Range(1, 3)] }));
-    assert_eq!(RangeSet::from_string("2-3"), Ok(RangeSet { points: vec![Range(1, 3)] }));
-    assert_eq!(RangeSet::from_string("2-3,3-5,4-6"), Ok(RangeSet { points: vec![Range(1, 6)] }));
-    assert_eq!(RangeSet::from_string("4-6,3-5,2-3"), Ok(RangeSet { points: vec![Range(1, 6)] }));
     assert_eq!(
-        RangeSet::from_string("2,5-10"),
+        RangeSet::from_string("2-3,3-5,4-6", Unit::Position),
+        Ok(RangeSet { points: vec![Range(1, 6)] })
+    );
+    assert_eq!(
+        RangeSet::from_string("4-6,3-5,2-3", Unit::Position),
+        Ok(RangeSet { points: vec![Range(1, 6)] })
+    );
+    assert_eq!(
+        RangeSet::from_string("2,5-10", Unit::Position),
         Ok(RangeSet { points: vec![Range(1, 2), Range(4, 10)] })
     );
     assert_eq!(
-        RangeSet::from_string("2,5-"),
-        Ok(RangeSet::from_vec(vec![Range(1, 2), Range(4, usize::max_value())]))
+        RangeSet::from_string("2,5-", Unit::Position),
+        Ok(RangeSet::from_vec(vec![Range(1, 2), Range(4, usize::MAX)]))
     );
     assert_eq!(
-        RangeSet::from_string("-2,5-"),
-        Ok(RangeSet { points: vec![Range(usize::min_value(), 2), Range(4, usize::max_value())] })
+        RangeSet::from_string("-2,5-", Unit::Position),
+        Ok(RangeSet { points: vec![Range(usize::MIN, 2), Range(4, usize::MAX)] })
     );
 }
 
@@ -57,30 +72,149 @@ fn complement_rangeset_helper(ranges: Vec<Range>, expected: Vec<Range>) {
 
 #[test]
 fn completment_rangeset() {
-    complement_rangeset_helper(vec![Range(usize::min_value(), usize::max_value())], vec![]);
-    complement_rangeset_helper(vec![Range(usize::min_value(), 5)], vec![Range(
-        5,
-        usize::max_value(),
-    )]);
-    complement_rangeset_helper(vec![Range(5, usize::max_value())], vec![Range(
-        usize::min_value(),
-        5,
-    )]);
-    complement_rangeset_helper(vec![Range(1, 5)], vec![
-        Range(usize::min_value(), 1),
-        Range(5, usize::max_value()),
-    ]);
+    complement_rangeset_helper(vec![Range(usize::MIN, usize::MAX)], vec![]);
+    complement_rangeset_helper(vec![Range(usize::MIN, 5)], vec![Range(5, usize::MAX)]);
+    complement_rangeset_helper(vec![Range(5, usize::MAX)], vec![Range(usize::MIN, 5)]);
+    complement_rangeset_helper(vec![Range(1, 5)], vec![Range(usize::MIN, 1), Range(5, usize::MAX)]);
     complement_rangeset_helper(vec![Range(1, 5), Range(8, 12)], vec![
-        Range(usize::min_value(), 1),
+        Range(usize::MIN, 1),
         Range(5, 8),
-        Range(12, usize::max_value()),
+        Range(12, usize::MAX),
     ]);
-    complement_rangeset_helper(vec![Range(usize::min_value(), 5), Range(8, 12)], vec![
+    complement_rangeset_helper(vec![Range(usize::MIN, 5), Range(8, 12)], vec![
         Range(5, 8),
-        Range(12, usize::max_value()),
+        Range(12, usize::MAX),
     ]);
-    complement_rangeset_helper(vec![Range(5, 8), Range(12, usize::max_value())], vec![
+    complement_rangeset_helper(vec![Range(5, 8), Range(12, usize::MAX)], vec![
         Range(0, 5),
         Range(8, 12),
     ]);
 }
+
+#[test]
+fn range_errors() {
+    let error = |string, unit| Range::from_string(string, unit).unwrap_err().0;
+
+    assert_eq!(error("0", Unit::Field), "fields are numbered from 1");
+    assert_eq!(error("0-", Unit::Position), "byte/character positions are numbered from 1");
+    assert_eq!(error("", Unit::Field), "fields are numbered from 1");
+    assert_eq!(error("-", Unit::Field), "invalid range with no endpoint: -");
+    assert_eq!(error("3-2", Unit::Field), "invalid decreasing range");
+    assert_eq!(error("1--2", Unit::Field), "invalid field range");
+    assert_eq!(error("1--2", Unit::Position), "invalid byte or character range");
+    assert_eq!(error("1x-3,4", Unit::Field), "invalid field value 'x-3,4'");
+    assert_eq!(error("x", Unit::Position), "invalid byte/character position 'x'");
+    assert_eq!(
+        error("99999999999999999999-", Unit::Field),
+        "field number '99999999999999999999' is too large"
+    );
+    assert_eq!(
+        error("-18446744073709551615", Unit::Position),
+        "byte/character offset '18446744073709551615' is too large"
+    );
+}
+
+#[test]
+fn rangeset_overlaps() {
+    assert_eq!(
+        RangeSet::from_string("2-,3,-5", Unit::Field),
+        Ok(RangeSet { points: vec![Range(0, usize::MAX)] })
+    );
+    assert_eq!(
+        RangeSet::from_string("7 1-3\t2", Unit::Field),
+        Ok(RangeSet { points: vec![Range(0, 3), Range(6, 7)] })
+    );
+    assert_eq!(
+        RangeSet::from_string("1,,2", Unit::Field).unwrap_err().0,
+        "fields are numbered from 1"
+    );
+    assert_eq!(
+        RangeSet::from_string("1,x,2", Unit::Field).unwrap_err().0,
+        "invalid field value 'x,2'"
+    );
+}
+
+// Cut the input with the command-line arguments.
+fn cut_bytes(args: &[&str], input: &[u8]) -> Vec<u8> {
+    let matches = cli::create_app().get_matches_from(["cut"].iter().chain(args));
+    let line_terminator = if matches.is_present("zero-terminated") { b'\0' } else { b'\n' };
+    let options = Options { line_terminator, complement: matches.is_present("complement") };
+
+    let mut output = Vec::new();
+    let cutter = make_cutter(&matches, &options).unwrap();
+    cutter.process_input(&mut &input[..], &options, &mut output).unwrap();
+    output
+}
+
+fn cut(args: &[&str], input: &[u8]) -> String {
+    String::from_utf8(cut_bytes(args, input)).unwrap()
+}
+
+#[test]
+fn fields() {
+    let input = b"a:b:c:d:e\nno delimiter\n:\nf:g";
+    assert_eq!(cut(&["-f2", "-d:"], input), "b\nno delimiter\n\ng\n");
+    assert_eq!(cut(&["-f", "4-,2", "-d:"], input), "b:d:e\nno delimiter\n\ng\n");
+    assert_eq!(cut(&["-f-2", "-d:", "-s"], input), "a:b\n:\nf:g\n");
+    assert_eq!(cut(&["-f2,4", "-d:", "--complement"], input), "a:c:e\nno delimiter\n\nf\n");
+    assert_eq!(
+        cut(&["-f1,3-", "-d:", "--output-delimiter=, "], input),
+        "a, c, d, e\nno delimiter\n\nf\n"
+    );
+    assert_eq!(cut(&["-f2"], b"a\tb\tc\nd\n"), "b\nd\n");
+    assert_eq!(cut(&["-f2", "-d", ""], b"a\0b\n"), "b\n");
+    assert_eq!(cut(&["-f2", "-d", "\n"], b"a\nb\nc\n"), "b\n");
+    assert_eq!(cut(&["-f2", "-d:", "-z"], b"a:b\0c\nd:e\0f"), "b\0e\0f\0");
+}
+
+#[test]
+fn positions() {
+    let input = b"abcdef\nab\n";
+    assert_eq!(cut(&["-b2-3,5"], input), "bce\nb\n");
+    assert_eq!(cut(&["-c", "1,2,5-", "--output-delimiter=:"], input), "a:b:ef\na:b\n");
+    assert_eq!(cut(&["-b2,4", "--complement", "--output-delimiter=:"], input), "a:c:ef\na\n");
+    assert_eq!(cut(&["-b-2", "-z"], b"abc\0d"), "ab\0d\0");
+}
+
+#[test]
+fn lists_starting_with_hyphen() {
+    let input = b"a:b:c:d:e:f\nabcdef\n";
+    assert_eq!(cut(&["-f", "-5", "-d:"], input), "a:b:c:d:e\nabcdef\n");
+    assert_eq!(cut(&["-b", "-2,4-", "--complement"], input), "b\nc\n");
+    assert_eq!(cut(&["--characters", "-3"], input), "a:b\nabc\n");
+}
+
+#[test]
+fn char_boundaries_of_lines() {
+    assert_eq!(char_boundaries(b"ab"), vec![0, 1, 2]);
+    assert_eq!(char_boundaries("aé日".as_bytes()), vec![0, 1, 3, 6]);
+    assert_eq!(char_boundaries(b"a\xe6\x97b\xff"), vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(char_boundaries(b""), vec![0]);
+}
+
+#[test]
+fn multibyte() {
+    let input = "aé日本b\n".as_bytes();
+    assert_eq!(cut(&["-c2-3"], input), "é日\n");
+    assert_eq!(cut(&["-c", "1,4-", "--output-delimiter=:"], input), "a:本b\n");
+    assert_eq!(cut(&["-c3", "--complement"], input), "aé本b\n");
+    assert_eq!(cut_bytes(&["-b2"], input), b"\xc3\n");
+    assert_eq!(cut(&["-b2-3"], input), "é\n");
+
+    assert_eq!(cut(&["-b2", "-n"], input), "\n");
+    assert_eq!(cut(&["-b3-5", "-n"], input), "é\n");
+    assert_eq!(cut(&["-b3-6", "-n"], input), "é日\n");
+    assert_eq!(cut(&["-b", "1,5-", "-n", "--output-delimiter=:"], input), "a:日本b\n");
+    assert_eq!(cut(&["-c2", "-n"], input), "é\n");
+
+    // Each byte of an invalid sequence is a character
+    let invalid = b"\xe6\x97a\xffb\n";
+    assert_eq!(cut_bytes(&["-c2-3"], invalid), b"\x97a\n");
+    assert_eq!(cut_bytes(&["-b1-4", "-n"], invalid), b"\xe6\x97a\xff\n");
+
+    assert_eq!(cut(&["-f2", "-d", "é"], "aébéc\nd\n".as_bytes()), "b\nd\n");
+    assert_eq!(
+        cut_bytes(&["-f1,3", "-d", "é", "--output-delimiter=:"], b"\xc3a\xc3\xa9\xa9b\xc3\xa9c"),
+        b"\xc3a:c\n"
+    );
+}
@@ -21,10 +21,13 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                     "An operand with a leading plus sign ('+') signals a user-defined format \
                      string which specifies the format in which to display the date and \
                      time.\n\nThe format string may contain any of the conversion specifications \
-                     described in the time crate documentation, as well as any arbitrary \
-                     text.\n\nA newline ('\\n') character is always output after the characters \
-                     specified by the format string.\n\nIf specified with other arguments that \
-                     also sets an OUTPUT_FMT/FMT, this one will be used.",
+                     of strftime(3), like '%Y-%m-%d %H:%M:%S.%N %:z' with the nanoseconds and the \
+                     offset with a colon or '%s' with the seconds since the Epoch, as well as any \
+                     arbitrary text. A conversion may have the flags '-' to not pad, '_' to pad \
+                     with spaces, '0' to pad with zeros, '^' to use uppercase and '#' to use the \
+                     opposite case, and a width.\n\nA newline ('\\n') character is always output \
+                     after the characters specified by the format string.\n\nIf specified with \
+                     other arguments that also sets an OUTPUT_FMT/FMT, this one will be used.",
                 )
                 .value_name("+OUTPUT_FMT"),
         )
@@ -32,6 +35,29 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
             "Display time described by STRING, not 'now'.\n\nShould be of value \
              [[[[[CC]YY]MM]DD]hh]mm[.SS].",
         ))
+        .arg(
+            Arg::with_name("date")
+                .help("Display time described by STRING, not 'now'.")
+                .long_help(
+                    "Display time described by STRING, not 'now'.\n\nThe STRING is a free-form \
+                     date, like '2 weeks ago', 'next monday', '@1600000000' or \
+                     '2020-01-02T03:04:05+05:00'. The items it doesn't specify are taken from the \
+                     current date and time.",
+                )
+                .long("date")
+                .short("d")
+                .value_name("STRING")
+                .allow_hyphen_values(true)
+                .conflicts_with("reference"),
+        )
+        .arg(
+            Arg::with_name("debug")
+                .help(
+                    "Annotate the parsed date, and warn about questionable usage to standard \
+                     error.",
+                )
+                .long("debug"),
+        )
         .arg(
             Arg::with_name("utc")
                 .help("Display Coordinated Universal Time (UTC).")
@@ -44,15 +70,31 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Do not try to set the date.")
                 .long("no-set")
                 .visible_alias("convert")
-                .short("j")
-                .overrides_with("set"),
+                .short("j"),
         )
         .arg(
             Arg::with_name("set")
-                .help("Try to set the date.")
+                .help("Set time described by STRING.")
+                .long_help(
+                    "Set time described by STRING.\n\nThe STRING has the same syntax of --date. \
+                     Setting the time needs privileges, and the date is displayed even if it's \
+                     not set.",
+                )
                 .long("set")
                 .short("s")
-                .overrides_with("no_set"),
+                .value_name("STRING")
+                .allow_hyphen_values(true)
+                .conflicts_with_all(&["date", "reference", "resolution"]),
+        )
+        .arg(
+            Arg::with_name("resolution")
+                .help("Display the resolution of the timestamps instead of the time.")
+                .long_help(
+                    "Display the resolution of the timestamps of the system clock instead of the \
+                     time.\n\nThe timestamps are multiples of it. OUTPUT_FMT defaults to '%s.%N'.",
+                )
+                .long("resolution")
+                .conflicts_with_all(&["date", "reference"]),
         )
         .arg(
             Arg::with_name("rfc2822")
@@ -64,7 +106,7 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                      more than one argument that changes OUTPUT_FMT is set, the last ones is used.",
                 )
                 .long("rfc2822")
-                .visible_alias("rfc-2822")
+                .visible_aliases(&["rfc-2822", "rfc-email"])
                 .short("R")
                 .overrides_with_all(&["iso8601", "rfc3339"]),
         )
@@ -78,7 +120,7 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                     "Display the date and time of the last modification of \
                      filename.\n\nOR\n\nDisplay the date and time represented by seconds, where \
                      seconds is the number of seconds since the Epoch (00:00:00 UTC, January 1, \
-                     1970; see time(3)).",
+                     1970; see time(3)), if there is no file with that name.",
                 )
                 .long("reference")
                 .visible_alias("read")
@@ -89,19 +131,29 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("iso8601")
                 .help("Use ISO 8601 date and time as OUTPUT_FMT.")
                 .long_help(
-                    "Use ISO 8601 date and time as OUTPUT_FMT.\n\nFMT may not be omitted, \
-                     different of other versions of this util.\n\nThe date and time is formatted \
-                     to the specified precision. When FMT is 'hours' (or the more precise \
-                     'minutes' or 'seconds'), the ISO 8601 format includes the timezone.\n\nIf \
-                     '+' operand is specified, this option will be ignored.\n\nIf more than one \
-                     argument that changes OUTPUT_FMT is set, the last ones is used.",
+                    "Use ISO 8601 date and time as OUTPUT_FMT.\n\nFMT defaults to 'date' when \
+                     omitted.\n\nThe date and time is formatted to the specified precision. When \
+                     FMT is 'hours' (or the more precise 'minutes', 'seconds' or 'nanoseconds'), \
+                     the ISO 8601 format includes the timezone.\n\nIf '+' operand is specified, \
+                     this option will be ignored.\n\nIf more than one argument that changes \
+                     OUTPUT_FMT is set, the last ones is used.",
                 )
                 .long("iso8601")
                 .visible_alias("iso-8601")
                 .short("I")
                 .value_name("FMT")
+                .min_values(0)
                 .possible_values(&[
-                    "date", "hour", "hours", "minute", "minutes", "second", "seconds",
+                    "date",
+                    "hour",
+                    "hours",
+                    "minute",
+                    "minutes",
+                    "second",
+                    "seconds",
+                    "nanosecond",
+                    "nanoseconds",
+                    "ns",
                 ])
                 .overrides_with_all(&["rfc2822", "rfc3339"]),
         )
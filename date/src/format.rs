@@ -0,0 +1,408 @@
+//! The `strftime` style formatting of the dates, with the conversions and flags of the
+//! GNU implementation in the C locale.
+use coreutils_core::datetime::{
+    calendar::{Fields, MONTH_NAMES, WEEKDAY_NAMES},
+    Timestamp, Zone,
+};
+
+/// A instant with the date, time and offset of it in a zone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Moment {
+    pub timestamp: Timestamp,
+    pub fields: Fields,
+    /// The offset from UTC in seconds
+    pub offset: i64,
+    pub zone_name: String,
+}
+
+impl Moment {
+    pub fn new(timestamp: Timestamp, zone: Zone) -> Self {
+        let offset = zone.offset_at(timestamp.seconds);
+        Moment {
+            timestamp,
+            fields: Fields::from_seconds(timestamp.seconds + offset),
+            offset,
+            zone_name: zone.name_at(timestamp.seconds),
+        }
+    }
+}
+
+/// The padding of a conversion, set by its flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pad {
+    /// The default padding of the conversion
+    Default,
+    /// The `-` flag, with no padding
+    None,
+    /// The `_` flag
+    Spaces,
+    /// The `0` flag
+    Zeros,
+    /// The `+` flag, padding with zeros and a plus sign on the large years
+    Plus,
+}
+
+/// The flags, width and colons of a conversion.
+#[derive(Debug, Clone, Copy)]
+struct Spec {
+    pad: Pad,
+    upper: bool,
+    swap_case: bool,
+    width: Option<usize>,
+    colons: usize,
+}
+
+/// Format `moment` with the conversions of `format`, like `%Y-%m-%d`.
+pub fn format(format: &str, moment: &Moment) -> String {
+    let mut output = String::new();
+    let mut chars = format.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+
+        let mut spec =
+            Spec { pad: Pad::Default, upper: false, swap_case: false, width: None, colons: 0 };
+        while let Some(&(_, flag)) = chars.peek() {
+            match flag {
+                '-' => spec.pad = Pad::None,
+                '_' => spec.pad = Pad::Spaces,
+                '0' => spec.pad = Pad::Zeros,
+                '+' => spec.pad = Pad::Plus,
+                '^' => spec.upper = true,
+                '#' => spec.swap_case = true,
+                _ => break,
+            }
+            chars.next();
+        }
+        while let Some(&(_, digit)) = chars.peek() {
+            match digit.to_digit(10) {
+                Some(digit) => {
+                    let width = spec.width.unwrap_or(0);
+                    spec.width = Some(width.saturating_mul(10).saturating_add(digit as usize));
+                    chars.next();
+                },
+                None => break,
+            }
+        }
+        while spec.colons < 3 && chars.peek().map(|&(_, c)| c) == Some(':') {
+            spec.colons += 1;
+            chars.next();
+        }
+        // The modifiers of the alternative representations, that are the same in the C
+        // locale
+        let mut has_modifier = false;
+        if spec.colons == 0 {
+            if let Some(&(_, 'E')) | Some(&(_, 'O')) = chars.peek() {
+                has_modifier = true;
+                chars.next();
+            }
+        }
+
+        let conversion = chars.next();
+        let end = chars.peek().map_or(format.len(), |&(index, _)| index);
+        let formatted = match conversion {
+            Some((_, '%')) if has_modifier => None,
+            Some((_, c)) => convert(c, spec, moment),
+            None => None,
+        };
+        match formatted {
+            Some(formatted) => output.push_str(&formatted),
+            // Like the other implementations, the invalid conversions are kept, padded to
+            // the width
+            None => {
+                let raw = &format[start..end];
+                if spec.pad != Pad::None {
+                    let padding = spec.width.unwrap_or(0).saturating_sub(raw.chars().count());
+                    output.push_str(&" ".repeat(padding));
+                }
+                output.push_str(raw);
+            },
+        }
+    }
+
+    output
+}
+
+/// The conversion `c` with `spec`, or `None` if it's invalid.
+fn convert(c: char, spec: Spec, moment: &Moment) -> Option<String> {
+    if spec.colons > 0 && c != 'z' {
+        return None;
+    }
+
+    let fields = moment.fields;
+    let hour_12 = (fields.hour + 11) % 12 + 1;
+    let text = |text: &str| Some(pad_text(&upper(text, spec), spec));
+    let number = |value: i64, digits: usize| Some(pad_number(value, digits, '0', spec, false));
+    let spaced = |value: i64, digits: usize| Some(pad_number(value, digits, ' ', spec, false));
+    let composite = |format_str: &str| {
+        let formatted = format(format_str, moment);
+        let formatted = if spec.upper { formatted.to_uppercase() } else { formatted };
+        Some(pad_text(&formatted, spec))
+    };
+
+    match c {
+        '%' => text("%"),
+        'a' => text(&WEEKDAY_NAMES[fields.weekday() as usize][..3]).map(|t| swap(t, spec)),
+        'A' => text(WEEKDAY_NAMES[fields.weekday() as usize]).map(|t| swap(t, spec)),
+        'b' | 'h' => text(&MONTH_NAMES[fields.month as usize - 1][..3]).map(|t| swap(t, spec)),
+        'B' => text(MONTH_NAMES[fields.month as usize - 1]).map(|t| swap(t, spec)),
+        'c' => composite("%a %b %e %H:%M:%S %Y"),
+        'C' => Some(pad_number(fields.year.div_euclid(100), 2, '0', spec, true)),
+        'd' => number(fields.day, 2),
+        'D' | 'x' => composite("%m/%d/%y"),
+        'e' => spaced(fields.day, 2),
+        'F' => {
+            // The width is of the whole date, so it's the width of the year plus the rest.
+            // Without flags, the large years have a plus sign.
+            let year_spec = match (spec.pad, spec.width) {
+                (Pad::Default, None) => Spec { pad: Pad::Plus, ..spec },
+                (_, width) => Spec { width: width.map(|width| width.saturating_sub(6)), ..spec },
+            };
+            let year = pad_number(fields.year, 4, '0', year_spec, true);
+            Some(format!("{}-{:02}-{:02}", year, fields.month, fields.day))
+        },
+        'g' => number(fields.iso_week().0.rem_euclid(100), 2),
+        'G' => Some(pad_number(fields.iso_week().0, 4, '0', spec, true)),
+        'H' => number(fields.hour, 2),
+        'I' => number(hour_12, 2),
+        'j' => number(fields.year_day() + 1, 3),
+        'k' => spaced(fields.hour, 2),
+        'l' => spaced(hour_12, 2),
+        'm' => number(fields.month, 2),
+        'M' => number(fields.minute, 2),
+        'n' => text("\n"),
+        'N' => Some(nanoseconds(moment.timestamp.nanos, spec)),
+        'p' => {
+            let meridian = if fields.hour < 12 { "AM" } else { "PM" };
+            let meridian =
+                if spec.swap_case { meridian.to_ascii_lowercase() } else { meridian.to_string() };
+            text(&meridian)
+        },
+        // Like the other implementations, it's never uppercase
+        'P' => Some(pad_text(if fields.hour < 12 { "am" } else { "pm" }, spec)),
+        'q' => number((fields.month - 1) / 3 + 1, 1),
+        'r' => composite("%I:%M:%S %p"),
+        'R' => composite("%H:%M"),
+        's' => number(moment.timestamp.seconds, 1),
+        'S' => number(fields.second, 2),
+        't' => text("\t"),
+        'T' | 'X' => composite("%H:%M:%S"),
+        'u' => number((fields.weekday() + 6) % 7 + 1, 1),
+        'U' => number((fields.year_day() + 7 - fields.weekday()) / 7, 2),
+        'V' => number(fields.iso_week().1, 2),
+        'w' => number(fields.weekday(), 1),
+        'W' => number((fields.year_day() + 7 - (fields.weekday() + 6) % 7) / 7, 2),
+        'y' => number(fields.year.rem_euclid(100), 2),
+        'Y' => Some(pad_number(fields.year, 4, '0', spec, true)),
+        'z' => Some(offset(moment.offset, spec)),
+        'Z' => {
+            let name = if spec.swap_case {
+                moment.zone_name.to_lowercase()
+            } else {
+                moment.zone_name.clone()
+            };
+            text(&name)
+        },
+        _ => None,
+    }
+}
+
+/// Apply the `^` flag to `text`.
+fn upper(text: &str, spec: Spec) -> String {
+    if spec.upper {
+        text.to_uppercase()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Apply the `#` flag to the names, that makes them uppercase.
+fn swap(text: String, spec: Spec) -> String {
+    if spec.swap_case {
+        text.to_uppercase()
+    } else {
+        text
+    }
+}
+
+/// Pad `text` to the width of `spec`, with spaces by default.
+fn pad_text(text: &str, spec: Spec) -> String {
+    let length = text.chars().count();
+    let padding = match (spec.pad, spec.width) {
+        (Pad::None, _) | (_, None) => 0,
+        (_, Some(width)) => width.saturating_sub(length),
+    };
+    let pad = match spec.pad {
+        Pad::Zeros | Pad::Plus => '0',
+        _ => ' ',
+    };
+
+    pad.to_string().repeat(padding) + text
+}
+
+/// Pad `value` to the width of `spec`, or to `digits` with `default_pad` by default.
+///
+/// On the years, the `+` flag adds a plus sign if the year has more digits than the
+/// default or the width is larger.
+fn pad_number(value: i64, digits: usize, default_pad: char, spec: Spec, is_year: bool) -> String {
+    let limit = if digits == 2 { 99 } else { 9999 };
+    let width = spec.width.unwrap_or(digits);
+    let sign = if value < 0 {
+        "-"
+    } else if is_year && spec.pad == Pad::Plus && (value > limit || width > digits) {
+        "+"
+    } else {
+        ""
+    };
+
+    let magnitude = if value < 0 { 0u64.wrapping_sub(value as u64) } else { value as u64 };
+    pad_signed(sign, magnitude, width, default_pad, spec)
+}
+
+/// Pad `sign` and `magnitude` to `width`, where zeros go after the sign and spaces before
+/// it.
+fn pad_signed(sign: &str, magnitude: u64, width: usize, default_pad: char, spec: Spec) -> String {
+    let pad = match spec.pad {
+        Pad::Default => default_pad,
+        Pad::None => return format!("{}{}", sign, magnitude),
+        Pad::Spaces => ' ',
+        Pad::Zeros | Pad::Plus => '0',
+    };
+
+    let width = width.saturating_sub(sign.len());
+    if pad == '0' {
+        format!("{}{:0width$}", sign, magnitude, width = width)
+    } else {
+        let number = format!("{}{}", sign, magnitude);
+        format!("{:>width$}", number, width = width + sign.len())
+    }
+}
+
+/// The nanoseconds, with the width as the number of digits, where the `_` flag replaces
+/// the trailing zeros with spaces.
+fn nanoseconds(nanos: u32, spec: Spec) -> String {
+    let digits = spec.width.unwrap_or(9);
+    let mut formatted = format!("{:09}", nanos);
+    if digits < 9 {
+        formatted.truncate(digits);
+    } else {
+        formatted.push_str(&"0".repeat(digits - 9));
+    }
+
+    if spec.pad == Pad::Spaces {
+        let trimmed = formatted.trim_end_matches('0').len();
+        formatted.replace_range(trimmed.., &" ".repeat(digits - trimmed));
+    }
+    formatted
+}
+
+/// The offset from UTC like `+hhmm`, `+hh:mm` with one colon, `+hh:mm:ss` with two and
+/// with three the minimal precision of the offset. The width is of the whole offset, so
+/// the hours are padded to the width minus the rest.
+fn offset(offset: i64, spec: Spec) -> String {
+    let sign = if offset < 0 { "-" } else { "+" };
+    let offset = offset.abs();
+    let (hours, minutes, seconds) = (offset / 3600, offset / 60 % 60, offset % 60);
+
+    let (number, rest) = match spec.colons {
+        0 => (hours * 100 + minutes, String::new()),
+        1 => (hours, format!(":{:02}", minutes)),
+        2 => (hours, format!(":{:02}:{:02}", minutes, seconds)),
+        _ => match (minutes, seconds) {
+            (0, 0) => (hours, String::new()),
+            (_, 0) => (hours, format!(":{:02}", minutes)),
+            _ => (hours, format!(":{:02}:{:02}", minutes, seconds)),
+        },
+    };
+    let digits = if spec.colons == 0 { 5 } else { 3 };
+    let width = spec.width.map_or(digits, |width| width.saturating_sub(rest.len()));
+
+    pad_signed(sign, number as u64, width, '0', spec) + &rest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Thursday 5 March 2020 04:03:02.123456789, in a zone of +05:30
+    fn moment() -> Moment {
+        let seconds =
+            Fields { year: 2020, month: 3, day: 5, hour: 4, minute: 3, second: 2 }.to_seconds();
+        Moment {
+            timestamp: Timestamp { seconds: seconds - 19800, nanos: 123_456_789 },
+            fields: Fields::from_seconds(seconds),
+            offset: 19800,
+            zone_name: "IST".to_string(),
+        }
+    }
+
+    fn check(cases: &[(&str, &str)]) {
+        let moment = moment();
+        for (format_str, expected) in cases {
+            assert_eq!(format(format_str, &moment), *expected, "format {}", format_str);
+        }
+    }
+
+    #[test]
+    fn conversions() {
+        check(&[
+            ("%a %A %b %B %h", "Thu Thursday Mar March Mar"),
+            ("%c", "Thu Mar  5 04:03:02 2020"),
+            ("%C %y %Y %G %g", "20 20 2020 2020 20"),
+            ("%d %e %j %m %u %w", "05  5 065 03 4 4"),
+            ("%D %F %x", "03/05/20 2020-03-05 03/05/20"),
+            ("%H %I %k %l %M %S %p %P", "04 04  4  4 03 02 AM am"),
+            ("%r %R %T %X", "04:03:02 AM 04:03 04:03:02 04:03:02"),
+            ("%N %q %s", "123456789 1 1583361182"),
+            ("%U %V %W", "09 10 09"),
+            ("%z %:z %::z %:::z %Z", "+0530 +05:30 +05:30:00 +05:30 IST"),
+            ("%n%t%%", "\n\t%"),
+        ]);
+    }
+
+    #[test]
+    fn flags() {
+        check(&[
+            (
+                "%10A|%010A|%-10A|%^a|%#a|%#p|%^p|%#Z",
+                "  Thursday|00Thursday|Thursday|THU|THU|am|AM|ist",
+            ),
+            ("%_5d|%-d|%_d|%-e|%05e|%0e", "    5|5| 5|5|00005|05"),
+            ("%3N|%12N|%_5N", "123|123456789000|12345"),
+            ("%_10z|%-z|%10:z|%4:z|%_::z", "      +530|+530|+000005:30|+5:30| +5:30:00"),
+            (
+                "%+Y|%+6Y|%6Y|%_6Y|%+_10Y|%12F|%_12F",
+                "2020|+02020|002020|  2020|      2020|002020-03-05|  2020-03-05",
+            ),
+            ("%10T|%010T|%^c", "  04:03:02|0004:03:02|THU MAR  5 04:03:02 2020"),
+            ("%Ey|%Oy|%Eq", "20|20|1"),
+        ]);
+    }
+
+    #[test]
+    fn invalid_conversions() {
+        check(&[
+            ("%Q", "%Q"),
+            ("%", "%"),
+            ("%5", "   %5"),
+            ("%-5", "%-5"),
+            ("%:x", "%:x"),
+            ("%E", "%E"),
+        ]);
+    }
+
+    #[test]
+    fn large_years() {
+        let fields = Fields { year: 10000, month: 1, day: 1, hour: 0, minute: 0, second: 0 };
+        let moment = Moment {
+            timestamp: Timestamp { seconds: fields.to_seconds(), nanos: 0 },
+            fields,
+            offset: 0,
+            zone_name: "UTC".to_string(),
+        };
+        assert_eq!(format("%Y %F %C %+C", &moment), "10000 +10000-01-01 100 +100");
+    }
+}
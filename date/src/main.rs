@@ -1,13 +1,17 @@
-use std::{path::Path, str::FromStr};
+use std::{os::unix::fs::MetadataExt, path::Path, str::FromStr};
 
 use clap::ArgMatches;
-use coreutils_core::time::{
-    Date, Duration, OffsetDateTime as DateTime, PrimitiveDateTime, Time, UtcOffset,
+use coreutils_core::{
+    datetime::{self, Timestamp, Zone},
+    time::{Date, Duration, OffsetDateTime as DateTime, PrimitiveDateTime, Time, UtcOffset},
 };
 
 mod cli;
+mod format;
 
-const DEFAULT_FMT_OUT: &str = "%a %b %d %H:%M:%S %z %Y";
+use format::Moment;
+
+const DEFAULT_FMT_OUT: &str = "%a %b %e %H:%M:%S %Z %Y";
 const RFC_2822_FMT: &str = "%a, %d %b %Y %T %z";
 
 fn main() {
@@ -20,10 +24,16 @@ fn main() {
 }
 
 fn date(matches: &ArgMatches) -> Result<(), String> {
-    let iso8601 = matches.value_of("iso8601");
-    let rfc2822 = matches.value_of("rfc2822");
+    // The precision of ISO 8601 may be omitted
+    let iso8601 = if matches.is_present("iso8601") {
+        Some(matches.value_of("iso8601").unwrap_or("date"))
+    } else {
+        None
+    };
+    let rfc2822 = matches.is_present("rfc2822");
     let rfc3339 = matches.value_of("rfc3339");
-    let is_set = matches.is_present("set") && !matches.is_present("no_set");
+    let no_set = matches.is_present("no_set");
+    let zone = if matches.is_present("utc") { Zone::Utc } else { Zone::Local };
 
     let utc_off = if matches.is_present("utc") {
         UtcOffset::UTC
@@ -34,12 +44,36 @@ fn date(matches: &ArgMatches) -> Result<(), String> {
         })
     };
 
+    if matches.is_present("resolution") {
+        let out_fmt = operand_format(matches, "%s.%N")?;
+        let timestamp = clock_resolution()?;
+
+        println!("{}", format::format(out_fmt, &Moment::new(timestamp, zone)));
+        return Ok(());
+    }
+
+    let set_str = matches.value_of("set");
+    if let Some(date_str) = matches.value_of("date").or(set_str) {
+        let out_fmt = operand_format(matches, default_format(rfc2822, iso8601, rfc3339))?;
+
+        let timestamp = parse_timestamp(date_str, zone, matches.is_present("debug"))?;
+        if matches.is_present("debug") {
+            eprintln!("date: output format: '{}'", out_fmt);
+        }
+
+        // The date is displayed even if it couldn't be set
+        let set_result = if set_str.is_some() && !no_set { set_os_time(timestamp) } else { Ok(()) };
+
+        println!("{}", format::format(out_fmt, &Moment::new(timestamp, zone)));
+        return set_result;
+    }
+
     let (out_fmt, date_str) = {
         match (matches.value_of("OPERAND"), matches.value_of("DATE")) {
             (Some(operand), None) => {
                 if let Some(s) = operand.strip_prefix('+') {
                     (s, "now")
-                } else if rfc2822.is_some() {
+                } else if rfc2822 {
                     (RFC_2822_FMT, operand)
                 } else if let Some(fmt_str) = iso8601 {
                     (iso8601_format_str(fmt_str), operand)
@@ -53,17 +87,7 @@ fn date(matches: &ArgMatches) -> Result<(), String> {
                 Some(op) => (op, date),
                 None => return Err("Operand format is invalid: Must have '+' at start".to_string()),
             },
-            (None, None) => {
-                if rfc2822.is_some() {
-                    (RFC_2822_FMT, "now")
-                } else if let Some(iso_str) = iso8601 {
-                    (iso8601_format_str(iso_str), "now")
-                } else if let Some(fmt_str) = rfc3339 {
-                    (rfc3339_format_str(fmt_str), "now")
-                } else {
-                    (DEFAULT_FMT_OUT, "now")
-                }
-            },
+            (None, None) => (default_format(rfc2822, iso8601, rfc3339), "now"),
             // SAFETY: Cannot happen, because it will always get the first argument as "OPERAND"
             // We fix that on the (true, false) case.
             (None, Some(_)) => unreachable!(),
@@ -71,13 +95,54 @@ fn date(matches: &ArgMatches) -> Result<(), String> {
     };
 
     let date = build_datetime(date_str, utc_off, matches.value_of("reference"))?;
+    let timestamp = Timestamp { seconds: date.timestamp(), nanos: date.nanosecond() };
+
+    // Like BSD, a date operand sets the date unless `-j` is used
+    let set_result = if date_str != "now" && !no_set { set_os_time(timestamp) } else { Ok(()) };
 
-    if is_set {
-        set_os_time(date)?;
+    println!("{}", format::format(out_fmt, &Moment::new(timestamp, zone)));
+    set_result
+}
+
+/// Returns the output format of the `+` operand, or `default` if there is none.
+///
+/// The options that give the date, like `--date`, take the place of the date operand, so
+/// the operand must be a format.
+fn operand_format<'a>(matches: &'a ArgMatches, default: &'a str) -> Result<&'a str, String> {
+    match (matches.value_of("OPERAND"), matches.value_of("DATE")) {
+        (Some(operand), None) => operand.strip_prefix('+').ok_or_else(|| {
+            format!(
+                "the argument '{}' lacks a leading '+';\nwhen using an option to specify date(s), \
+                 any non-option\nargument must be a format string beginning with '+'",
+                operand
+            )
+        }),
+        (Some(_), Some(extra)) => Err(format!("extra operand '{}'", extra)),
+        (None, _) => Ok(default),
     }
+}
 
-    println!("{}", date.format(out_fmt));
-    Ok(())
+/// Returns the output format of the options, when there is no `+` operand.
+fn default_format<'a>(
+    rfc2822: bool, iso8601: Option<&'a str>, rfc3339: Option<&'a str>,
+) -> &'a str {
+    if rfc2822 {
+        RFC_2822_FMT
+    } else if let Some(fmt_str) = iso8601 {
+        iso8601_format_str(fmt_str)
+    } else if let Some(fmt_str) = rfc3339 {
+        rfc3339_format_str(fmt_str)
+    } else {
+        DEFAULT_FMT_OUT
+    }
+}
+
+/// Parse the free-form `date_str` of `--date`, where the dates without a time zone are in
+/// `zone`.
+fn parse_timestamp(date_str: &str, zone: Zone, debug: bool) -> Result<Timestamp, String> {
+    let now = DateTime::now_utc();
+    let now = Timestamp { seconds: now.timestamp(), nanos: now.nanosecond() };
+    datetime::parse_date(date_str, now, zone, debug)
 }
 
 /// Build a [`DateTime`] from a `date_str`.
@@ -173,29 +238,24 @@ fn build_datetime(
     }
 }
 
-/// Reads datetime from `input`. Could be seconds or a filepath.
+/// Reads datetime from `input`. Could be a filepath or seconds.
 fn reference_datetime(input: &str, utc_off: UtcOffset) -> Result<DateTime, String> {
-    // First try to parse as a number, if it fails, treat as a file
-    match input.trim().parse::<i64>() {
-        Ok(sec) => Ok(DateTime::from_unix_timestamp(sec).to_offset(utc_off)),
-        Err(p_err) => match datetime_from_file(input, utc_off) {
-            Ok(d) => Ok(d),
-            Err(f_err) => Err(format!(
-                "Invalid read input: Neither a file or seconds: {} AND {}",
-                f_err, p_err
-            )),
+    // First try to read it as a file, if there is none, parse it as a number
+    match datetime_from_file(input, utc_off) {
+        Ok(d) => Ok(d),
+        Err(f_err) => match input.trim().parse::<i64>() {
+            Ok(sec) => Ok(DateTime::from_unix_timestamp(sec).to_offset(utc_off)),
+            Err(_) => Err(format!("{}: {}", input, f_err)),
         },
     }
 }
 
-/// Returns the last modified date of `filename`.
+/// Returns the last modified date of `filename`, with its nanoseconds.
 fn datetime_from_file(filename: impl AsRef<Path>, utc_off: UtcOffset) -> std::io::Result<DateTime> {
-    let path = filename.as_ref();
-
-    let metadata = path.metadata()?;
-    let modified = metadata.modified()?;
+    let metadata = filename.as_ref().metadata()?;
+    let nanos = i128::from(metadata.mtime()) * 1_000_000_000 + i128::from(metadata.mtime_nsec());
 
-    Ok(DateTime::from(modified).to_offset(utc_off))
+    Ok(DateTime::from_unix_timestamp_nanos(nanos).to_offset(utc_off))
 }
 
 /// Parses a slice of [`char`]s and return a value of a type that implements [`FromStr`].
@@ -229,11 +289,13 @@ fn iso8601_format_str(value: &str) -> &str {
     match value {
         "date" | "" => "%F",
         "hour" => "%FT%H",
-        "hours" => "%FT%H%z",
+        "hours" => "%FT%H%:z",
         "minute" => "%FT%H:%M",
-        "minutes" => "%FT%H:%M%z",
+        "minutes" => "%FT%H:%M%:z",
         "second" => "%FT%H:%M:%S",
-        "seconds" => "%FT%H:%M:%S%z",
+        "seconds" => "%FT%H:%M:%S%:z",
+        "nanosecond" => "%FT%H:%M:%S,%N",
+        "nanoseconds" | "ns" => "%FT%H:%M:%S,%N%:z",
         // SAFETY: Clap ensures that only the above values are used
         _ => unreachable!(),
     }
@@ -244,27 +306,49 @@ fn rfc3339_format_str(value: &str) -> &str {
     match value {
         "date" | "" => "%F",
         "hour" => "%F %H",
-        "hours" => "%F %H%z",
+        "hours" => "%F %H%:z",
         "minute" => "%F %H:%M",
-        "minutes" => "%F %H:%M%z",
+        "minutes" => "%F %H:%M%:z",
         "second" => "%F %H:%M:%S",
-        "seconds" => "%F %H:%M:%S%z",
+        "seconds" => "%F %H:%M:%S%:z",
         "nanosecond" => "%F %H:%M:%S.%N",
-        "nanoseconds" | "ns" => "%F %H:%M:%S.%N%z",
+        "nanoseconds" | "ns" => "%F %H:%M:%S.%N%:z",
         // SAFETY: Clap ensures that only the above values are used
         _ => unreachable!(),
     }
 }
 
-/// Sets the os datetime to `datetime`
-fn set_os_time(datetime: DateTime) -> Result<(), String> {
-    use coreutils_core::os::{time::set_time_of_day, Susec, Time, TimeVal};
+/// Sets the os datetime to `timestamp`
+fn set_os_time(timestamp: Timestamp) -> Result<(), String> {
+    use coreutils_core::os::{time::set_clock_time, Time, TimeSpec};
+
+    let time = TimeSpec { tv_sec: timestamp.seconds as Time, tv_nsec: timestamp.nanos as _ };
+
+    set_clock_time(time).map_err(|err| format!("cannot set date: {}", err))
+}
+
+/// Returns the resolution of the system clock, as a [`Timestamp`] since the Epoch.
+fn clock_resolution() -> Result<Timestamp, String> {
+    let res = coreutils_core::os::time::clock_resolution()
+        .map_err(|err| format!("cannot get the clock resolution: {}", err))?;
+
+    Ok(Timestamp { seconds: res.tv_sec as i64, nanos: res.tv_nsec as u32 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_dates_with_minus() {
+        let matches = cli::create_app().get_matches_from(vec!["date", "-d", "-1 day"]);
+        let date_str = matches.value_of("date").unwrap();
 
-    let time =
-        TimeVal { tv_sec: datetime.timestamp() as Time, tv_usec: datetime.microsecond() as Susec };
+        let now = Timestamp { seconds: 1_600_000_000, nanos: 0 };
+        let timestamp = datetime::parse_date(date_str, now, Zone::Utc, false);
+        assert_eq!(timestamp, Ok(Timestamp { seconds: 1_600_000_000 - 86_400, nanos: 0 }));
 
-    match set_time_of_day(time) {
-        Ok(_) => Ok(()),
-        Err(err) => Err(format!("Failed to set date: {}", err)),
+        let matches = cli::create_app().get_matches_from(vec!["date", "-s", "-1 hour"]);
+        assert_eq!(matches.value_of("set"), Some("-1 hour"));
     }
 }
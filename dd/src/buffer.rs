@@ -0,0 +1,61 @@
+//! The buffers of the blocks, that start at a memory page as the direct reads and writes
+//! need.
+use std::ops::{Deref, DerefMut};
+
+use coreutils_core::libc;
+
+/// The size of the memory pages when it can't be known.
+const PAGE_SIZE: usize = 4096;
+
+/// A buffer of bytes starting at a memory page.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Buffer {
+    /// The bytes with the ones before the start of the page.
+    bytes: Vec<u8>,
+    start: usize,
+    size: usize,
+}
+
+impl Buffer {
+    /// A buffer of `size` NULs.
+    pub(crate) fn new(size: usize) -> Self {
+        let page = match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+            page if page > 0 => page as usize,
+            _ => PAGE_SIZE,
+        };
+
+        let bytes = vec![0; size + page];
+        let start = bytes.as_ptr().align_offset(page);
+
+        Buffer { bytes, start, size }
+    }
+}
+
+impl Deref for Buffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[self.start..self.start + self.size]
+    }
+}
+
+impl DerefMut for Buffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes[self.start..self.start + self.size]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligned() {
+        let buffer = Buffer::new(1000);
+        let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+
+        assert_eq!(buffer.len(), 1000);
+        assert_eq!(buffer.as_ptr() as usize % page, 0);
+        assert!(buffer.iter().all(|&byte| byte == 0));
+    }
+}
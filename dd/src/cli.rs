@@ -0,0 +1,72 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("OPERAND")
+                .help("Operands of the copy, written as KEY=VALUE.")
+                .long_help(
+                    "Operands of the copy, written as KEY=VALUE.\n\nThe operands are:\n  \
+                     if=FILE      read from FILE instead of the standard input\n  \
+                     of=FILE      write to FILE instead of the standard output\n  \
+                     ibs=BYTES    read BYTES at a time (default: 512)\n  \
+                     obs=BYTES    write BYTES at a time (default: 512)\n  \
+                     bs=BYTES     read and write BYTES at a time, overriding ibs and obs\n  \
+                     count=N      copy only N input blocks\n  \
+                     skip=N       skip N input blocks at the start of the input\n  \
+                     seek=N       skip N output blocks at the start of the output\n  \
+                     conv=CONVS   convert the file as the comma separated list CONVS \
+                     says\n  \
+                     iflag=FLAGS  read as the comma separated list FLAGS says\n  \
+                     oflag=FLAGS  write as the comma separated list FLAGS says\n  \
+                     status=LEVEL report the statistics at LEVEL: none for none of them nor \
+                     the messages that are not errors, noxfer for the records but not the \
+                     transfer, or progress for the transfer each second too\n\nThe numbers \
+                     may be followed by a unit: c for 1, w for 2, b for 512, K, M, G, T, P, E, \
+                     Z or Y for the powers of 1024, that can also be written as KiB, MiB and so \
+                     on, or KB, MB and so on for the powers of 1000. They may also be \
+                     multiplied as NxM. The counts of blocks followed by B are counts of bytes \
+                     instead.\n\nThe conversions are:\n  \
+                     lcase       change the upper case letters to lower case\n  \
+                     ucase       change the lower case letters to upper case\n  \
+                     swab        swap each pair of input bytes\n  \
+                     sync        pad each input block with NULs to ibs bytes\n  \
+                     sparse      seek over the output blocks of NULs instead of writing \
+                     them\n  \
+                     excl        fail if the output file already exists\n  \
+                     nocreat     do not create the output file\n  \
+                     notrunc     do not truncate the output file\n  \
+                     noerror     continue after read errors\n  \
+                     fdatasync   synchronize the data of the output file before finishing\n  \
+                     fsync       synchronize the data and metadata of the output file \
+                     before finishing\n\nThe flags are:\n  \
+                     append      append to the output file, with conv=notrunc\n  \
+                     direct      read or write directly, without the cache, where the \
+                     system can\n  \
+                     directory   fail unless the file is a directory\n  \
+                     dsync       write the data synchronously\n  \
+                     sync        write the data and metadata synchronously\n  \
+                     fullblock   retry the short reads until the input block is full, \
+                     only for iflag\n  \
+                     nonblock    read or write without blocking\n  \
+                     noatime     do not update the access time of the file\n  \
+                     noctty      do not make the file the controlling terminal\n  \
+                     nofollow    do not follow the symbolic links\n  \
+                     count_bytes count the input copied in bytes, with count=\n  \
+                     skip_bytes  count the input skipped in bytes, with skip=\n  \
+                     seek_bytes  count the output skipped in bytes, with seek=\n\nThe \
+                     statistics are also reported when dd receives the SIGINFO signal, or \
+                     SIGUSR1 where there is no SIGINFO.",
+                )
+                .multiple(true),
+        )
+}
@@ -0,0 +1,721 @@
+use std::{
+    cmp,
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    mem,
+    os::unix::{
+        fs::OpenOptionsExt,
+        io::{AsRawFd, FromRawFd},
+    },
+    path::Path,
+    process,
+};
+
+use coreutils_core::libc;
+
+mod buffer;
+mod cli;
+mod operand;
+mod signal;
+mod stats;
+
+use buffer::Buffer;
+use operand::{Amount, Case, Flags, Operands, Status, O_DIRECT};
+use stats::Stats;
+
+/// A file copied from or to, with its name for the messages.
+struct Stream {
+    file: File,
+    name: String,
+}
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let operands = Operands::parse(matches.values_of_os("OPERAND").into_iter().flatten())
+        .unwrap_or_else(|err| {
+            eprintln!("dd: {}", err);
+            process::exit(1);
+        });
+
+    signal::catch();
+
+    let streams = open_input(operands.input.as_deref(), operands.input_flags)
+        .and_then(|input| Ok((input, open_output(&operands)?)));
+
+    let (mut input, mut output) = streams.unwrap_or_else(|err| {
+        eprintln!("dd: {}", err);
+        process::exit(1);
+    });
+
+    let mut stats = Stats::new(operands.status);
+    let success = copy(&operands, &mut input, &mut output, &mut stats);
+
+    stats.report();
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// Open the file at `path` to read with the `flags`, or the standard input if there is
+/// none.
+///
+/// # Errors
+/// If the file can't be opened, or the flags can't be set on the standard input, an error
+/// message will be returned.
+fn open_input(path: Option<&Path>, flags: Flags) -> Result<Stream, String> {
+    match path {
+        Some(path) => match OpenOptions::new().read(true).custom_flags(flags.open).open(path) {
+            Ok(file) => Ok(Stream { file, name: path.display().to_string() }),
+            Err(err) => Err(format!("failed to open '{}': {}", path.display(), err)),
+        },
+        None => {
+            // Only closed when dd finishes
+            let input = Stream {
+                file: unsafe { File::from_raw_fd(libc::STDIN_FILENO) },
+                name: "standard input".to_string(),
+            };
+
+            set_flags(&input, flags.open)?;
+            Ok(input)
+        },
+    }
+}
+
+/// Open the output file of the `operands` to write, or the standard output if there is
+/// none.
+///
+/// Unless the conversions say otherwise, it's created if it doesn't exist and truncated
+/// after the blocks it seeks.
+///
+/// # Errors
+/// If the file can't be opened or truncated, an error message will be returned.
+fn open_output(operands: &Operands) -> Result<Stream, String> {
+    let path = match &operands.output {
+        Some(path) => path,
+        None => {
+            // Only closed when dd finishes
+            let output = Stream {
+                file: unsafe { File::from_raw_fd(libc::STDOUT_FILENO) },
+                name: "standard output".to_string(),
+            };
+
+            set_flags(&output, operands.output_flags.open)?;
+            return Ok(output);
+        },
+    };
+
+    let conversions = operands.conversions;
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create(!conversions.nocreat)
+        .create_new(conversions.excl)
+        .truncate(!conversions.notrunc && operands.seek.is_empty())
+        .custom_flags(operands.output_flags.open)
+        .open(path)
+        .map_err(|err| format!("failed to open '{}': {}", path.display(), err))?;
+
+    if !conversions.notrunc && !operands.seek.is_empty() {
+        let is_file = matches!(file.metadata(), Ok(metadata) if metadata.is_file());
+
+        if let (true, Some(size)) = (is_file, operands.seek.total(operands.output_block)) {
+            file.set_len(size).map_err(|err| {
+                format!(
+                    "failed to truncate to {} bytes in output file '{}': {}",
+                    size,
+                    path.display(),
+                    err
+                )
+            })?;
+        }
+    }
+
+    Ok(Stream { file, name: path.display().to_string() })
+}
+
+/// Set the `flags` of open(2) on the `stream` that is already open, where the ones that
+/// are only for the opening are checked or ignored.
+///
+/// # Errors
+/// If the flags can't be set, or the stream is not a directory with `O_DIRECTORY`, an
+/// error message will be returned.
+fn set_flags(stream: &Stream, flags: libc::c_int) -> Result<(), String> {
+    let flags = flags & !(libc::O_NOCTTY | libc::O_NOFOLLOW);
+
+    if flags == 0 {
+        return Ok(());
+    }
+
+    let failed = |err| format!("setting flags for '{}': {}", stream.name, err);
+    let fd = stream.file.as_raw_fd();
+
+    let old = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+
+    if old < 0 {
+        return Err(failed(io::Error::last_os_error()));
+    }
+
+    let mut new = old | flags;
+
+    if new & libc::O_DIRECTORY != 0 && new != old {
+        if !stream.file.metadata().map_err(failed)?.is_dir() {
+            return Err(failed(io::Error::from_raw_os_error(libc::ENOTDIR)));
+        }
+        new &= !libc::O_DIRECTORY;
+    }
+
+    if new != old && unsafe { libc::fcntl(fd, libc::F_SETFL, new) } < 0 {
+        return Err(failed(io::Error::last_os_error()));
+    }
+
+    Ok(())
+}
+
+/// Copy the `input` to the `output` as the `operands` say, counting it in the `stats`.
+///
+/// The errors are reported as they happen, and whether there was none is returned.
+fn copy(operands: &Operands, input: &mut Stream, output: &mut Stream, stats: &mut Stats) -> bool {
+    let conversions = operands.conversions;
+    let block_size = operands.input_block;
+    let mut buffer = Buffer::new(block_size);
+
+    let fullblock = operands.input_flags.fullblock;
+    let direct = (operands.input_flags.open | operands.output_flags.open) & O_DIRECT != 0;
+    // Where the blocks are counted as reads, that may be short
+    let warn_partial = !operands.reblock
+        && !fullblock
+        && (operands.count.is_some() || !operands.skip.is_empty() || direct);
+
+    let mut reader = Reader::new(input, fullblock, warn_partial);
+
+    match skip(&mut reader, operands.skip, &mut buffer, stats) {
+        Ok(true) => {},
+        Ok(false) if stats.status() == Status::Silent => {},
+        Ok(false) => {
+            let name = quote_if_needed(&reader.input.name);
+            eprintln!("dd: {}: cannot skip to specified offset", name);
+        },
+        Err(err) => {
+            eprintln!("dd: error reading '{}': {}", reader.input.name, err);
+            return false;
+        },
+    }
+
+    if let Err(err) = seek(output, operands.seek, operands.output_block) {
+        eprintln!("dd: {}: cannot seek: {}", quote_if_needed(&output.name), err);
+        return false;
+    }
+
+    // `stream_position` is newer than the MSRV
+    #[allow(clippy::seek_from_current)]
+    let seekable = reader.input.file.seek(SeekFrom::Current(0)).is_ok();
+    let mut success = true;
+
+    let direct = operands.output_flags.open & O_DIRECT != 0;
+    let mut writer = Writer::new(output, operands.output_block, conversions.sparse, direct);
+    let mut swapped = Vec::new();
+    // The byte of a pair yet to be swapped
+    let mut saved = None;
+
+    let written = loop {
+        let size = match operands.count {
+            Some(count) if stats.records_in() >= count.blocks + (count.bytes > 0) as u64 => {
+                break Ok(());
+            },
+            Some(count) if stats.records_in() == count.blocks => count.bytes as usize,
+            _ => block_size,
+        };
+
+        stats.progress();
+        report_if_signaled(stats);
+
+        let read = match reader.read(&mut buffer[..size], stats) {
+            Ok(0) => break Ok(()),
+            Ok(read) => read,
+            Err(err) => {
+                let input = &mut reader.input;
+
+                if !conversions.noerror {
+                    eprintln!("dd: error reading '{}': {}", input.name, err);
+                    success = false;
+                    break Ok(());
+                }
+
+                if stats.status() != Status::Silent {
+                    eprintln!("dd: error reading '{}': {}", input.name, err);
+                }
+
+                stats.report();
+
+                // Past the bad block, when the input can be seeked
+                if seekable {
+                    if let Err(err) = input.file.seek(SeekFrom::Current(size as i64)) {
+                        eprintln!("dd: {}: cannot seek: {}", quote_if_needed(&input.name), err);
+                        success = false;
+                    }
+                }
+
+                if !conversions.sync {
+                    continue;
+                }
+
+                0
+            },
+        };
+
+        let mut length = read;
+
+        if read == block_size {
+            stats.full_in += 1;
+        } else {
+            stats.partial_in += 1;
+
+            if conversions.sync {
+                buffer[read..].iter_mut().for_each(|byte| *byte = 0);
+                length = block_size;
+            }
+        }
+
+        let block = &mut buffer[..length];
+
+        match conversions.case {
+            Some(Case::Lower) => block.make_ascii_lowercase(),
+            Some(Case::Upper) => block.make_ascii_uppercase(),
+            None => {},
+        }
+
+        let result = if conversions.swab {
+            swapped.clear();
+            swab(block, &mut saved, &mut swapped);
+            writer.push(&swapped, stats)
+        } else if operands.reblock {
+            writer.push(block, stats)
+        } else {
+            writer.write(block, stats)
+        };
+
+        if result.is_err() {
+            break result;
+        }
+    };
+
+    let written = written
+        .and_then(|_| match saved {
+            Some(byte) => writer.push(&[byte], stats),
+            None => Ok(()),
+        })
+        .and_then(|_| writer.finish(stats));
+
+    if let Err(err) = written {
+        eprintln!("dd: {}", err);
+        success = false;
+    }
+
+    let fsync = conversions.fsync || writer.synchronize;
+
+    if let Err(err) = synchronize(output, conversions.fdatasync, fsync) {
+        eprintln!("dd: {}", err);
+        success = false;
+    }
+
+    success
+}
+
+/// Skip the `amount` of blocks of the size of the `buffer` at the start of the input of
+/// the `reader`, seeking them or else reading them into the `buffer`.
+///
+/// Whether the whole amount was skipped is returned, that is not when the input ends
+/// before it or is read short of it.
+///
+/// # Errors
+/// If the blocks can't be read, an error variant will be returned.
+fn skip(
+    reader: &mut Reader, amount: Amount, buffer: &mut [u8], stats: &mut Stats,
+) -> io::Result<bool> {
+    if amount.is_empty() {
+        return Ok(true);
+    }
+
+    let total = amount.total(buffer.len());
+    let offset = total.filter(|&offset| offset <= i64::MAX as u64);
+    let file = &mut reader.input.file;
+
+    if let Some(offset) = offset {
+        if let Ok(position) = file.seek(SeekFrom::Current(offset as i64)) {
+            let metadata = file.metadata()?;
+            return Ok(!metadata.is_file() || position <= metadata.len());
+        }
+    }
+
+    // Not seekable, where each read counts as a block
+    let mut skipped = 0;
+
+    for _ in 0..amount.blocks {
+        match reader.read(buffer, stats)? {
+            0 => return Ok(false),
+            read => skipped += read as u64,
+        }
+    }
+
+    if amount.bytes > 0 {
+        skipped += reader.read(&mut buffer[..amount.bytes as usize], stats)? as u64;
+    }
+
+    Ok(total == Some(skipped))
+}
+
+/// Seek the `amount` of blocks of `block_size` at the start of the `output`.
+///
+/// # Errors
+/// If the output can't be seeked, an error variant will be returned.
+fn seek(output: &mut Stream, amount: Amount, block_size: usize) -> io::Result<()> {
+    if amount.is_empty() {
+        return Ok(());
+    }
+
+    let offset = amount
+        .total(block_size)
+        .filter(|&offset| offset <= i64::MAX as u64)
+        .ok_or_else(|| io::Error::from_raw_os_error(libc::EOVERFLOW))?;
+
+    output.file.seek(SeekFrom::Current(offset as i64)).map(|_| ())
+}
+
+/// Read from the `file` into the `buffer` once, unless the read is interrupted, where the
+/// `stats` are reported if the signal asking for them was received.
+///
+/// A read cut short by the signal goes on into the rest of the `buffer`, so it isn't
+/// counted as a partial block.
+fn read(file: &mut File, buffer: &mut [u8], stats: &mut Stats) -> io::Result<usize> {
+    let mut total = 0;
+
+    loop {
+        match file.read(&mut buffer[total..]) {
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => report_if_signaled(stats),
+            // The error is returned again by the next read
+            Err(_) if total > 0 => return Ok(total),
+            Err(err) => return Err(err),
+            Ok(bytes) if bytes > 0 && total + bytes < buffer.len() && signal::received() => {
+                total += bytes;
+                stats.report();
+                report_if_signaled(stats);
+            },
+            Ok(bytes) => return Ok(total + bytes),
+        }
+    }
+}
+
+/// The reader of the blocks of the input.
+struct Reader<'a> {
+    input: &'a mut Stream,
+    /// Whether the short reads are retried until the buffer is full.
+    fullblock: bool,
+    /// Whether a short read followed by another one is warned about, as the blocks are
+    /// counted as reads.
+    warn_partial: bool,
+    /// The bytes of the last read.
+    last: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(input: &'a mut Stream, fullblock: bool, warn_partial: bool) -> Self {
+        Reader { input, fullblock, warn_partial, last: 0 }
+    }
+
+    /// Read into the `buffer` once, or until it's full or the input ends with the
+    /// `fullblock`.
+    ///
+    /// # Errors
+    /// If the input can't be read, an error variant will be returned.
+    fn read(&mut self, buffer: &mut [u8], stats: &mut Stats) -> io::Result<usize> {
+        if self.fullblock {
+            let mut total = 0;
+
+            while total < buffer.len() {
+                match read(&mut self.input.file, &mut buffer[total..], stats)? {
+                    0 => break,
+                    bytes => total += bytes,
+                }
+            }
+
+            return Ok(total);
+        }
+
+        let bytes = read(&mut self.input.file, buffer, stats)?;
+
+        if self.warn_partial && bytes > 0 {
+            if self.last > 0 && self.last < buffer.len() {
+                if stats.status() != Status::Silent {
+                    let unit = if self.last == 1 { "byte" } else { "bytes" };
+                    eprintln!(
+                        "dd: warning: partial read ({} {}); suggest iflag=fullblock",
+                        self.last, unit
+                    );
+                }
+                self.warn_partial = false;
+            }
+            self.last = bytes;
+        }
+
+        Ok(bytes)
+    }
+}
+
+/// Report the `stats` if the signal asking for them was received, or report them and
+/// terminate dd if the copy was interrupted.
+fn report_if_signaled(stats: &mut Stats) {
+    if let Some(interrupted) = signal::interrupted() {
+        stats.report();
+        signal::terminate(interrupted);
+    }
+
+    if signal::received() {
+        stats.report();
+    }
+}
+
+/// Swap each pair of the `bytes` into `swapped`, where a pair may start with the byte
+/// `saved` from the bytes before, and its last byte is `saved` when there is an odd one.
+fn swab(bytes: &[u8], saved: &mut Option<u8>, swapped: &mut Vec<u8>) {
+    for &byte in bytes {
+        match saved.take() {
+            Some(first) => swapped.extend_from_slice(&[byte, first]),
+            None => *saved = Some(byte),
+        }
+    }
+}
+
+/// Synchronize the data of the `output` if `data`, or its data and metadata if `all`.
+///
+/// # Errors
+/// If it can't be synchronized, an error message will be returned.
+fn synchronize(output: &Stream, data: bool, all: bool) -> Result<(), String> {
+    let mut all = all;
+
+    if data {
+        if let Err(err) = output.file.sync_data() {
+            // Falls back to synchronize all of it
+            if !matches!(err.raw_os_error(), Some(libc::ENOSYS) | Some(libc::EINVAL)) {
+                eprintln!("dd: fdatasync failed for '{}': {}", output.name, err);
+            }
+            all = true;
+        }
+    }
+
+    if all {
+        output
+            .file
+            .sync_all()
+            .map_err(|err| format!("fsync failed for '{}': {}", output.name, err))?;
+    }
+
+    Ok(())
+}
+
+/// Quote the `name` when it has characters that are special for a shell.
+fn quote_if_needed(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "%+,-./:=@^_".contains(c);
+
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
+
+/// The writer of the blocks of the output.
+struct Writer<'a> {
+    output: &'a mut Stream,
+    block_size: usize,
+    /// The next output block, where the bytes are gathered.
+    pending: Buffer,
+    /// The bytes gathered in the next output block.
+    filled: usize,
+    /// Whether the blocks of NULs are seeked over instead of written.
+    sparse: bool,
+    /// Whether the output is written directly, until its last block that is shorter.
+    direct: bool,
+    /// Whether the last block was seeked over, so the output must be extended to it.
+    seeked: bool,
+    /// Whether the output must be synchronized, since its last block wasn't written
+    /// directly.
+    synchronize: bool,
+}
+
+impl<'a> Writer<'a> {
+    fn new(output: &'a mut Stream, block_size: usize, sparse: bool, direct: bool) -> Self {
+        Writer {
+            output,
+            block_size,
+            pending: Buffer::new(block_size),
+            filled: 0,
+            sparse,
+            direct,
+            seeked: false,
+            synchronize: false,
+        }
+    }
+
+    /// Gather the `bytes` into the output blocks, writing the blocks that are filled.
+    ///
+    /// # Errors
+    /// If a block can't be written, an error message will be returned.
+    fn push(&mut self, bytes: &[u8], stats: &mut Stats) -> Result<(), String> {
+        let mut bytes = bytes;
+
+        while !bytes.is_empty() {
+            let taken = cmp::min(bytes.len(), self.block_size - self.filled);
+
+            self.pending[self.filled..self.filled + taken].copy_from_slice(&bytes[..taken]);
+            self.filled += taken;
+            bytes = &bytes[taken..];
+
+            if self.filled == self.block_size {
+                self.flush(stats)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the bytes gathered in the next output block.
+    ///
+    /// # Errors
+    /// If they can't be written, an error message will be returned.
+    fn flush(&mut self, stats: &mut Stats) -> Result<(), String> {
+        let pending = mem::take(&mut self.pending);
+        let written = self.write(&pending[..self.filled], stats);
+
+        self.pending = pending;
+        self.filled = 0;
+
+        written
+    }
+
+    /// Write a `block`, counting it as a full output record if it has the size of a
+    /// block.
+    ///
+    /// # Errors
+    /// If it can't be written, an error message will be returned.
+    fn write(&mut self, block: &[u8], stats: &mut Stats) -> Result<(), String> {
+        if self.sparse && block.iter().all(|&byte| byte == 0) {
+            match self.output.file.seek(SeekFrom::Current(block.len() as i64)) {
+                Ok(_) => {
+                    self.seeked = true;
+                    stats.bytes += block.len() as u64;
+                    self.count(block, stats);
+                    return Ok(());
+                },
+                // Not seekable, so it's written from now on
+                Err(_) => self.sparse = false,
+            }
+        }
+
+        // The last block, that can't be written directly when it's shorter
+        if self.direct && block.len() < self.block_size {
+            self.direct = false;
+            self.synchronize = true;
+
+            let fd = self.output.file.as_raw_fd();
+            let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+
+            let unset =
+                flags >= 0 && unsafe { libc::fcntl(fd, libc::F_SETFL, flags & !O_DIRECT) } >= 0;
+
+            if !unset && stats.status() != Status::Silent {
+                let err = io::Error::last_os_error();
+                let name = quote_if_needed(&self.output.name);
+                eprintln!("dd: failed to turn off O_DIRECT: {}: {}", name, err);
+            }
+        }
+
+        self.seeked = false;
+        let mut written = 0;
+
+        while written < block.len() {
+            match self.output.file.write(&block[written..]) {
+                Ok(0) => {
+                    let err = io::Error::from(io::ErrorKind::WriteZero);
+                    return Err(format!("error writing '{}': {}", self.output.name, err));
+                },
+                Ok(bytes) => {
+                    written += bytes;
+                    stats.bytes += bytes as u64;
+                },
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => report_if_signaled(stats),
+                Err(err) => return Err(format!("error writing '{}': {}", self.output.name, err)),
+            }
+        }
+
+        self.count(block, stats);
+
+        Ok(())
+    }
+
+    fn count(&self, block: &[u8], stats: &mut Stats) {
+        if block.len() == self.block_size {
+            stats.full_out += 1;
+        } else {
+            stats.partial_out += 1;
+        }
+    }
+
+    /// Write the bytes gathered for the last block, and extend the output to the last
+    /// block seeked over.
+    ///
+    /// # Errors
+    /// If the block can't be written or the output can't be extended, an error message
+    /// will be returned.
+    fn finish(&mut self, stats: &mut Stats) -> Result<(), String> {
+        if self.filled > 0 {
+            self.flush(stats)?;
+        }
+
+        if self.seeked {
+            let file = &mut self.output.file;
+
+            // `stream_position` is newer than the MSRV
+            #[allow(clippy::seek_from_current)]
+            let position = file.seek(SeekFrom::Current(0));
+
+            if let (Ok(metadata), Ok(position)) = (file.metadata(), position) {
+                if metadata.is_file() && position > metadata.len() {
+                    file.set_len(position).map_err(|err| {
+                        format!(
+                            "failed to truncate to {} bytes in output file '{}': {}",
+                            position, self.output.name, err
+                        )
+                    })?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swapped_pairs() {
+        let mut saved = None;
+        let mut swapped = Vec::new();
+
+        swab(b"abc", &mut saved, &mut swapped);
+        assert_eq!((swapped.as_slice(), saved), (&b"ba"[..], Some(b'c')));
+
+        swab(b"defg", &mut saved, &mut swapped);
+        assert_eq!((swapped.as_slice(), saved), (&b"badcfe"[..], Some(b'g')));
+    }
+
+    #[test]
+    fn quotes() {
+        assert_eq!(quote_if_needed("/etc/passwd"), "/etc/passwd");
+        assert_eq!(quote_if_needed("standard input"), "'standard input'");
+        assert_eq!(quote_if_needed(""), "''");
+    }
+}
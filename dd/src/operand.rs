@@ -0,0 +1,476 @@
+//! The operands of dd, written as `KEY=VALUE`, with the numbers of bytes and blocks and
+//! the lists of conversions they take.
+use std::{ffi::OsStr, os::unix::ffi::OsStrExt, path::PathBuf};
+
+use coreutils_core::{
+    libc,
+    size::{parse_size, SizeError},
+};
+
+/// The suffixes of the units of the numbers.
+const SUFFIXES: &str = "bcEGkKMPTwYZ";
+
+/// The size of the blocks read and written when it isn't given.
+const BLOCK_SIZE: usize = 512;
+
+/// The flag of open(2) to read and write directly, where there is one.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd"
+))]
+pub(crate) const O_DIRECT: libc::c_int = libc::O_DIRECT;
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd"
+)))]
+pub(crate) const O_DIRECT: libc::c_int = 0;
+
+/// The flag of open(2) to synchronize the data of the writes, where there is one.
+#[cfg(not(target_os = "dragonfly"))]
+const O_DSYNC: libc::c_int = libc::O_DSYNC;
+
+#[cfg(target_os = "dragonfly")]
+const O_DSYNC: libc::c_int = 0;
+
+/// The flag of open(2) to not update the access time of the reads, where there is one.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const O_NOATIME: libc::c_int = libc::O_NOATIME;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const O_NOATIME: libc::c_int = 0;
+
+/// An amount of blocks, and of bytes after them, to copy or skip.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Amount {
+    pub(crate) blocks: u64,
+    pub(crate) bytes: u64,
+}
+
+impl Amount {
+    /// The amount of a `number` of blocks of `block_size`, or of bytes if `in_bytes`.
+    fn new(number: u64, in_bytes: bool, block_size: usize) -> Self {
+        if in_bytes {
+            let block_size = block_size as u64;
+            Amount { blocks: number / block_size, bytes: number % block_size }
+        } else {
+            Amount { blocks: number, bytes: 0 }
+        }
+    }
+
+    pub(crate) fn is_empty(self) -> bool {
+        self.blocks == 0 && self.bytes == 0
+    }
+
+    /// The bytes of the amount with blocks of `block_size`, if they fit in 64 bits.
+    pub(crate) fn total(self, block_size: usize) -> Option<u64> {
+        self.blocks.checked_mul(block_size as u64)?.checked_add(self.bytes)
+    }
+}
+
+/// The change of the case of the letters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Case {
+    Lower,
+    Upper,
+}
+
+/// The statistics reported, given with `status=`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Status {
+    /// None of them, nor the messages that are not errors (`none`).
+    Silent,
+    /// The records, but not the transfer (`noxfer`).
+    NoTransfer,
+    /// The records and the transfer.
+    Full,
+    /// The records and the transfer, and the transfer each second (`progress`).
+    Progress,
+}
+
+/// The conversions given with `conv=`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Conversions {
+    pub(crate) case: Option<Case>,
+    /// Swap each pair of input bytes.
+    pub(crate) swab: bool,
+    /// Pad each input block with NULs to its size.
+    pub(crate) sync: bool,
+    /// Seek over the output blocks of NULs instead of writing them.
+    pub(crate) sparse: bool,
+    /// Fail if the output file exists.
+    pub(crate) excl: bool,
+    /// Do not create the output file.
+    pub(crate) nocreat: bool,
+    /// Do not truncate the output file.
+    pub(crate) notrunc: bool,
+    /// Continue after the read errors.
+    pub(crate) noerror: bool,
+    pub(crate) fdatasync: bool,
+    pub(crate) fsync: bool,
+}
+
+/// The flags given with `iflag=` or `oflag=`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Flags {
+    /// Retry the short reads until the input block is full, only for the input.
+    pub(crate) fullblock: bool,
+    /// Count the input copied in bytes instead of blocks.
+    pub(crate) count_bytes: bool,
+    /// Count the input skipped in bytes instead of blocks.
+    pub(crate) skip_bytes: bool,
+    /// Count the output skipped in bytes instead of blocks.
+    pub(crate) seek_bytes: bool,
+    /// The flags of open(2) the file is opened with, or set on the standard input or
+    /// output.
+    pub(crate) open: libc::c_int,
+}
+
+/// The operands of a copy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Operands {
+    /// The file read, or the standard input if there is none.
+    pub(crate) input: Option<PathBuf>,
+    /// The file written, or the standard output if there is none.
+    pub(crate) output: Option<PathBuf>,
+    pub(crate) input_block: usize,
+    pub(crate) output_block: usize,
+    /// Whether the input blocks are gathered into output blocks, instead of written as
+    /// they are read.
+    pub(crate) reblock: bool,
+    /// The amount of input copied, or all of it if there is none.
+    pub(crate) count: Option<Amount>,
+    /// The amount of input skipped at its start.
+    pub(crate) skip: Amount,
+    /// The amount of output skipped at its start.
+    pub(crate) seek: Amount,
+    pub(crate) conversions: Conversions,
+    pub(crate) input_flags: Flags,
+    pub(crate) output_flags: Flags,
+    pub(crate) status: Status,
+}
+
+impl Operands {
+    /// Parse the `operands`, where the last of each key is the one taken.
+    ///
+    /// # Errors
+    /// If an operand is not one of dd, has a value that is not a valid number or
+    /// conversion, or if the conversions can't be combined, an error message will be
+    /// returned.
+    pub(crate) fn parse<'a>(operands: impl IntoIterator<Item = &'a OsStr>) -> Result<Self, String> {
+        let mut input = None;
+        let mut output = None;
+        let (mut input_block, mut output_block, mut block) = (None, None, None);
+        let (mut count, mut skip, mut seek) = (None, None, None);
+        let mut conversions = Conversions::default();
+        let (mut input_flags, mut output_flags) = (Flags::default(), Flags::default());
+        let mut status = Status::Full;
+
+        for operand in operands {
+            let operand = operand.as_bytes();
+            let unrecognized =
+                || format!("unrecognized operand '{}'", String::from_utf8_lossy(operand));
+
+            let equal = operand.iter().position(|&byte| byte == b'=').ok_or_else(unrecognized)?;
+            let (key, value) = (&operand[..equal], &operand[equal + 1..]);
+            let text = String::from_utf8_lossy(value);
+
+            match key {
+                b"if" => input = Some(PathBuf::from(OsStr::from_bytes(value))),
+                b"of" => output = Some(PathBuf::from(OsStr::from_bytes(value))),
+                b"ibs" => input_block = Some(parse_block_size(&text)?),
+                b"obs" => output_block = Some(parse_block_size(&text)?),
+                b"bs" => block = Some(parse_block_size(&text)?),
+                b"count" => count = Some(parse_number(&text)?),
+                b"skip" => skip = Some(parse_number(&text)?),
+                b"seek" => seek = Some(parse_number(&text)?),
+                b"conv" => parse_conversions(&text, &mut conversions)?,
+                b"iflag" => parse_flags(&text, true, &mut input_flags)?,
+                b"oflag" => parse_flags(&text, false, &mut output_flags)?,
+                b"status" => status = parse_status(&text)?,
+                _ => return Err(unrecognized()),
+            }
+        }
+
+        if conversions.excl && conversions.nocreat {
+            return Err("cannot combine excl and nocreat".to_string());
+        }
+
+        if output_flags.open & libc::O_APPEND != 0 && !conversions.notrunc {
+            eprintln!("dd: you probably want conv=notrunc with oflag=append");
+        }
+
+        let (input_block, output_block) = match block {
+            Some(block) => (block, block),
+            None => (input_block.unwrap_or(BLOCK_SIZE), output_block.unwrap_or(BLOCK_SIZE)),
+        };
+
+        let amount = |number: Option<(u64, bool)>, bytes: bool, block_size| {
+            number.map(|(number, in_bytes)| Amount::new(number, in_bytes || bytes, block_size))
+        };
+
+        Ok(Operands {
+            input,
+            output,
+            input_block,
+            output_block,
+            // The swapped pairs may span the input blocks
+            reblock: block.is_none() || conversions.swab,
+            count: amount(count, input_flags.count_bytes, input_block),
+            skip: amount(skip, input_flags.skip_bytes, input_block).unwrap_or_default(),
+            seek: amount(seek, output_flags.seek_bytes, output_block).unwrap_or_default(),
+            conversions,
+            input_flags,
+            output_flags,
+            status,
+        })
+    }
+}
+
+/// Parse a `number` of the operands, with the optional [`SUFFIXES`] of its units, that
+/// may be the product of two numbers as `NxM`, together with whether it ends with `B` to
+/// be a number of bytes instead of blocks.
+///
+/// # Errors
+/// If it is not a number or it doesn't fit in 64 bits, an error message will be returned.
+fn parse_number(number: &str) -> Result<(u64, bool), String> {
+    let invalid = |err| match err {
+        SizeError::Invalid => format!("invalid number: '{}'", number),
+        SizeError::TooLarge => format!("invalid number: '{}': {}", number, err),
+    };
+
+    let (first, second) = match number.find('x') {
+        Some(x) => (&number[..x], Some(&number[x + 1..])),
+        None => (number, None),
+    };
+
+    let (first, in_bytes) = match first.strip_suffix('B') {
+        Some(digits) if !digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit()) => {
+            (digits, true)
+        },
+        _ => (first, false),
+    };
+
+    if first == "0" && second.is_some() {
+        eprintln!("dd: warning: '0x' is a zero multiplier; use '00x' if that is intended");
+    }
+
+    let mut value = parse_size(first, SUFFIXES).map_err(invalid)?;
+
+    if let Some(second) = second {
+        let multiplier = parse_size(second, SUFFIXES).map_err(invalid)?;
+        value = value.checked_mul(multiplier).ok_or_else(|| invalid(SizeError::TooLarge))?;
+    }
+
+    Ok((value, in_bytes))
+}
+
+/// Parse the `size` of the blocks, that can't be 0.
+///
+/// # Errors
+/// If it is not a positive number of bytes that can be held in memory, an error message
+/// will be returned.
+fn parse_block_size(size: &str) -> Result<usize, String> {
+    match parse_number(size)?.0 {
+        0 => Err(format!("invalid number: '{}'", size)),
+        bytes if bytes > isize::MAX as u64 => {
+            Err(format!("invalid number: '{}': {}", size, SizeError::TooLarge))
+        },
+        bytes => Ok(bytes as usize),
+    }
+}
+
+/// Parse the comma separated list of `names` of the conversions into the `conversions`.
+///
+/// # Errors
+/// If a name is not of a conversion, or both of the changes of the case are given, an
+/// error message will be returned.
+fn parse_conversions(names: &str, conversions: &mut Conversions) -> Result<(), String> {
+    for name in names.split(',') {
+        let case = match name {
+            "lcase" => Some(Case::Lower),
+            "ucase" => Some(Case::Upper),
+            _ => None,
+        };
+
+        if let Some(case) = case {
+            if matches!(conversions.case, Some(other) if other != case) {
+                return Err("cannot combine lcase and ucase".to_string());
+            }
+            conversions.case = Some(case);
+            continue;
+        }
+
+        let flag = match name {
+            "swab" => &mut conversions.swab,
+            "sync" => &mut conversions.sync,
+            "sparse" => &mut conversions.sparse,
+            "excl" => &mut conversions.excl,
+            "nocreat" => &mut conversions.nocreat,
+            "notrunc" => &mut conversions.notrunc,
+            "noerror" => &mut conversions.noerror,
+            "fdatasync" => &mut conversions.fdatasync,
+            "fsync" => &mut conversions.fsync,
+            _ => return Err(format!("invalid conversion: '{}'", name)),
+        };
+
+        *flag = true;
+    }
+
+    Ok(())
+}
+
+/// Parse the comma separated list of `names` of the flags into the `flags`, of the input
+/// if `input` or else of the output.
+///
+/// # Errors
+/// If a name is not of a flag of the input or output, or of a flag of open(2) on this
+/// system, an error message will be returned.
+fn parse_flags(names: &str, input: bool, flags: &mut Flags) -> Result<(), String> {
+    for name in names.split(',') {
+        let open = match name {
+            "append" => libc::O_APPEND,
+            "direct" => O_DIRECT,
+            "directory" => libc::O_DIRECTORY,
+            "dsync" => O_DSYNC,
+            "sync" => libc::O_SYNC,
+            "nonblock" => libc::O_NONBLOCK,
+            "noatime" => O_NOATIME,
+            "noctty" => libc::O_NOCTTY,
+            "nofollow" => libc::O_NOFOLLOW,
+            _ => 0,
+        };
+
+        // Those that are 0 are not on this system
+        if open != 0 {
+            flags.open |= open;
+            continue;
+        }
+
+        let flag = match name {
+            "fullblock" if input => &mut flags.fullblock,
+            "count_bytes" => &mut flags.count_bytes,
+            "skip_bytes" => &mut flags.skip_bytes,
+            "seek_bytes" => &mut flags.seek_bytes,
+            _ => {
+                let side = if input { "input" } else { "output" };
+                return Err(format!("invalid {} flag: '{}'", side, name));
+            },
+        };
+
+        *flag = true;
+    }
+
+    Ok(())
+}
+
+/// Parse the `level` of the statistics reported.
+///
+/// # Errors
+/// If it is not a level, an error message will be returned.
+fn parse_status(level: &str) -> Result<Status, String> {
+    match level {
+        "none" => Ok(Status::Silent),
+        "noxfer" => Ok(Status::NoTransfer),
+        "progress" => Ok(Status::Progress),
+        _ => Err(format!("invalid status level: '{}'", level)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(operands: &[&str]) -> Result<Operands, String> {
+        Operands::parse(operands.iter().map(OsStr::new))
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse_number("10"), Ok((10, false)));
+        assert_eq!(parse_number("2b"), Ok((1024, false)));
+        assert_eq!(parse_number("1kB"), Ok((1000, false)));
+        assert_eq!(parse_number("2x3K"), Ok((6 * 1024, false)));
+        assert_eq!(parse_number("5B"), Ok((5, true)));
+        assert_eq!(parse_number("2Bx2"), Ok((4, true)));
+        assert_eq!(parse_number("1x2x3"), Err("invalid number: '1x2x3'".to_string()));
+        assert_eq!(parse_number("2x2B"), Err("invalid number: '2x2B'".to_string()));
+        assert_eq!(parse_number("-1"), Err("invalid number: '-1'".to_string()));
+        assert!(parse_number("9Ex2")
+            .unwrap_err()
+            .ends_with("Value too large for defined data type"));
+        assert_eq!(parse_block_size("0"), Err("invalid number: '0'".to_string()));
+    }
+
+    #[test]
+    fn operands() {
+        let operands =
+            parse(&["if=in", "ibs=3", "obs=1", "count=7B", "skip=2", "seek=3B"]).unwrap();
+
+        assert_eq!(operands.input, Some(PathBuf::from("in")));
+        assert_eq!(operands.output, None);
+        assert_eq!((operands.input_block, operands.output_block), (3, 1));
+        assert!(operands.reblock);
+        assert_eq!(operands.count, Some(Amount { blocks: 2, bytes: 1 }));
+        assert_eq!(operands.skip, Amount { blocks: 2, bytes: 0 });
+        assert_eq!(operands.seek, Amount { blocks: 3, bytes: 0 });
+
+        let operands = parse(&["bs=4", "ibs=2", "bs=8", "conv=ucase,sync", "conv=ucase"]).unwrap();
+
+        assert_eq!((operands.input_block, operands.output_block), (8, 8));
+        assert!(!operands.reblock);
+        assert_eq!(operands.conversions.case, Some(Case::Upper));
+        assert!(operands.conversions.sync);
+        assert_eq!(operands.status, Status::Full);
+        assert_eq!(parse(&["status=progress", "status=none"]).unwrap().status, Status::Silent);
+        assert!(parse(&["bs=4", "conv=swab"]).unwrap().reblock);
+    }
+
+    #[test]
+    fn flags() {
+        let operands = parse(&[
+            "bs=4",
+            "count=5",
+            "seek=6",
+            "iflag=fullblock,count_bytes",
+            "oflag=seek_bytes",
+        ])
+        .unwrap();
+
+        assert!(operands.input_flags.fullblock);
+        assert_eq!(operands.count, Some(Amount { blocks: 1, bytes: 1 }));
+        assert_eq!(operands.seek, Amount { blocks: 1, bytes: 2 });
+
+        let operands = parse(&["iflag=nonblock,sync", "oflag=append,nofollow,directory"]).unwrap();
+
+        assert_eq!(operands.input_flags.open, libc::O_NONBLOCK | libc::O_SYNC);
+        assert_eq!(
+            operands.output_flags.open,
+            libc::O_APPEND | libc::O_NOFOLLOW | libc::O_DIRECTORY
+        );
+        assert_eq!(
+            parse(&["oflag=fullblock"]),
+            Err("invalid output flag: 'fullblock'".to_string())
+        );
+        assert_eq!(parse(&["iflag=sync,"]), Err("invalid input flag: ''".to_string()));
+    }
+
+    #[test]
+    fn invalid_operands() {
+        assert_eq!(parse(&["foo"]), Err("unrecognized operand 'foo'".to_string()));
+        assert_eq!(parse(&["foo=1"]), Err("unrecognized operand 'foo=1'".to_string()));
+        assert_eq!(parse(&["conv=sync,foo"]), Err("invalid conversion: 'foo'".to_string()));
+        assert_eq!(parse(&["status=all"]), Err("invalid status level: 'all'".to_string()));
+        assert_eq!(parse(&["conv=lcase,ucase"]), Err("cannot combine lcase and ucase".to_string()));
+        assert_eq!(
+            parse(&["conv=nocreat", "conv=excl"]),
+            Err("cannot combine excl and nocreat".to_string())
+        );
+    }
+}
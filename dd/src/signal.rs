@@ -0,0 +1,97 @@
+//! The signal asking for the statistics of the copy in the middle of it, that is
+//! `SIGINFO` where there is one, or `SIGUSR1` otherwise, and the ones interrupting it,
+//! `SIGINT` and `SIGTERM`, after which the statistics are reported before terminating.
+use std::{
+    env, mem, process, ptr,
+    sync::atomic::{AtomicBool, AtomicI32, Ordering},
+};
+
+use coreutils_core::libc;
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+const INFO: libc::c_int = libc::SIGINFO;
+
+#[cfg(not(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+const INFO: libc::c_int = libc::SIGUSR1;
+
+/// Whether the signal was received since it was last taken.
+static RECEIVED: AtomicBool = AtomicBool::new(false);
+
+/// The signal interrupting the copy, or 0 if none was received.
+static INTERRUPTED: AtomicI32 = AtomicI32::new(0);
+
+extern "C" fn handle(_signal: libc::c_int) {
+    RECEIVED.store(true, Ordering::SeqCst);
+}
+
+extern "C" fn interrupt(signal: libc::c_int) {
+    INTERRUPTED.store(signal, Ordering::SeqCst);
+}
+
+/// Catch the signals, unless they are ignored. The one asking for the statistics isn't
+/// either if it is `SIGUSR1` with `POSIXLY_CORRECT`, since POSIX says it terminates dd.
+pub(crate) fn catch() {
+    if INFO != libc::SIGUSR1 || env::var_os("POSIXLY_CORRECT").is_none() {
+        install(INFO, handle);
+    }
+
+    install(libc::SIGINT, interrupt);
+    install(libc::SIGTERM, interrupt);
+}
+
+/// Set the `handler` of the `signal`, unless it is ignored.
+fn install(signal: libc::c_int, handler: extern "C" fn(libc::c_int)) {
+    unsafe {
+        let mut action: libc::sigaction = mem::zeroed();
+
+        if libc::sigaction(signal, ptr::null(), &mut action) != 0
+            || action.sa_sigaction == libc::SIG_IGN
+        {
+            return;
+        }
+
+        action.sa_sigaction = handler as libc::sighandler_t;
+        // Without restarting the reads and writes, so the ones waiting report at once
+        action.sa_flags = 0;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(signal, &action, ptr::null_mut());
+    }
+}
+
+/// Whether the signal was received since the last time, taking it.
+pub(crate) fn received() -> bool {
+    RECEIVED.swap(false, Ordering::SeqCst)
+}
+
+/// The signal interrupting the copy, if one was received.
+pub(crate) fn interrupted() -> Option<libc::c_int> {
+    match INTERRUPTED.load(Ordering::SeqCst) {
+        0 => None,
+        signal => Some(signal),
+    }
+}
+
+/// Terminate dd with the `signal`, as if it wasn't caught.
+pub(crate) fn terminate(signal: libc::c_int) -> ! {
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+
+    // The signal terminates dd before, unless it was blocked
+    process::exit(128 + signal)
+}
@@ -0,0 +1,270 @@
+//! The statistics of the copies: the records read and written, and the bytes written with
+//! the time taken and the rate of the transfer.
+use std::time::{Duration, Instant};
+
+use crate::operand::Status;
+
+/// The prefixes of the units of the powers of 1000.
+const SI_PREFIXES: [&str; 9] = ["", "k", "M", "G", "T", "P", "E", "Z", "Y"];
+
+/// The prefixes of the units of the powers of 1024.
+const IEC_PREFIXES: [&str; 9] = ["", "Ki", "Mi", "Gi", "Ti", "Pi", "Ei", "Zi", "Yi"];
+
+/// The statistics of a copy, counting the records as full when they have the size of a
+/// block, or as partial otherwise.
+#[derive(Debug, Clone)]
+pub(crate) struct Stats {
+    pub(crate) full_in: u64,
+    pub(crate) partial_in: u64,
+    pub(crate) full_out: u64,
+    pub(crate) partial_out: u64,
+    /// The bytes written.
+    pub(crate) bytes: u64,
+    status: Status,
+    start: Instant,
+    /// When the transfer is reported next with [`Status::Progress`].
+    next_progress: Instant,
+    /// The length of the last transfer reported with [`Status::Progress`], that is
+    /// overwritten by the next one.
+    progress_len: usize,
+}
+
+impl Stats {
+    /// The statistics of a copy starting now, reported at the `status` level.
+    pub(crate) fn new(status: Status) -> Self {
+        let start = Instant::now();
+
+        Stats {
+            full_in: 0,
+            partial_in: 0,
+            full_out: 0,
+            partial_out: 0,
+            bytes: 0,
+            status,
+            start,
+            next_progress: start + Duration::from_secs(1),
+            progress_len: 0,
+        }
+    }
+
+    pub(crate) fn status(&self) -> Status {
+        self.status
+    }
+
+    pub(crate) fn records_in(&self) -> u64 {
+        self.full_in + self.partial_in
+    }
+
+    /// Report the statistics to the standard error, as the status says.
+    pub(crate) fn report(&mut self) {
+        if self.status == Status::Silent {
+            return;
+        }
+
+        // After the last transfer reported with the progress
+        if self.progress_len > 0 {
+            eprintln!();
+            self.progress_len = 0;
+        }
+
+        eprint!("{}", self.summary(self.start.elapsed()));
+    }
+
+    /// Report the transfer to the standard error over the last one, if the status is
+    /// [`Status::Progress`] and a second passed since the last one.
+    pub(crate) fn progress(&mut self) {
+        let now = Instant::now();
+
+        if self.status != Status::Progress || now < self.next_progress {
+            return;
+        }
+
+        let line = transfer(self.bytes, now - self.start, true);
+        let padding = self.progress_len.saturating_sub(line.len());
+
+        eprint!("\r{}{:2$}", line, "", padding);
+
+        self.progress_len = line.len();
+        self.next_progress += Duration::from_secs(1);
+    }
+
+    /// The lines of the records and of the transfer, after `elapsed` time.
+    fn summary(&self, elapsed: Duration) -> String {
+        let records = format!(
+            "{}+{} records in\n{}+{} records out\n",
+            self.full_in, self.partial_in, self.full_out, self.partial_out
+        );
+
+        match self.status {
+            Status::NoTransfer => records,
+            _ => format!("{}{}\n", records, transfer(self.bytes, elapsed, false)),
+        }
+    }
+}
+
+/// The line of the `bytes` written in `elapsed` time, like `1024 bytes (1.0 kB, 1.0 KiB)
+/// copied, 0.5 s, 2.0 kB/s`, where the sizes are only given when they need a unit and
+/// the seconds are whole for the `progress`.
+fn transfer(bytes: u64, elapsed: Duration, progress: bool) -> String {
+    let copied = match bytes {
+        1 => "1 byte copied".to_string(),
+        0..=999 => format!("{} bytes copied", bytes),
+        1000..=1023 => format!("{} bytes ({}) copied", bytes, human_size(bytes, 1000)),
+        _ => format!(
+            "{} bytes ({}, {}) copied",
+            bytes,
+            human_size(bytes, 1000),
+            human_size(bytes, 1024)
+        ),
+    };
+
+    let seconds = elapsed.as_secs_f64();
+
+    let rate = if seconds > 0.0 {
+        format!("{}/s", human_rate(bytes as f64 / seconds))
+    } else {
+        "Infinity B/s".to_string()
+    };
+
+    let seconds = if progress { format!("{:.0}", seconds) } else { general(seconds) };
+
+    format!("{}, {} s, {}", copied, seconds, rate)
+}
+
+/// The `bytes` in the largest unit of the powers of `base` that is not more than them,
+/// with a decimal when they are less than 10 of the unit.
+fn human_size(bytes: u64, base: u64) -> String {
+    let prefixes = if base == 1024 { IEC_PREFIXES } else { SI_PREFIXES };
+    let base = base as f64;
+
+    let mut amount = bytes as f64;
+    let mut exponent = 0;
+
+    while amount >= base && exponent < prefixes.len() - 1 {
+        amount /= base;
+        exponent += 1;
+    }
+
+    let mut number = match exponent {
+        0 => format!("{}", bytes),
+        _ if amount < 10.0 => format!("{:.1}", amount),
+        _ => format!("{:.0}", amount),
+    };
+
+    // Rounded up to the next digit or unit
+    if number == "10.0" {
+        number = "10".to_string();
+    } else if number.parse() == Ok(base) && exponent < prefixes.len() - 1 {
+        number = "1.0".to_string();
+        exponent += 1;
+    }
+
+    format!("{} {}B", number, prefixes[exponent])
+}
+
+/// The `rate` of bytes per second in the units of the powers of 1000, from the kilobytes
+/// on, with a decimal when it is less than 100 of the unit.
+fn human_rate(rate: f64) -> String {
+    let mut amount = rate / 1000.0;
+    let mut exponent = 1;
+
+    while amount >= 1000.0 && exponent < SI_PREFIXES.len() - 1 {
+        amount /= 1000.0;
+        exponent += 1;
+    }
+
+    let mut number = format!("{:.1}", amount);
+
+    if number.len() > 4 {
+        number = format!("{:.0}", amount);
+    }
+
+    format!("{} {}B", number, SI_PREFIXES[exponent])
+}
+
+/// Format the `number` with 6 significant digits, in fixed or scientific notation as the
+/// `%g` of `printf(3)`.
+fn general(number: f64) -> String {
+    if number == 0.0 {
+        return "0".to_string();
+    }
+
+    let trim = |digits: &str| {
+        if digits.contains('.') {
+            digits.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            digits.to_string()
+        }
+    };
+
+    // The exponent after rounding to the significant digits
+    let scientific = format!("{:.5e}", number);
+    let (mantissa, exponent) = scientific.split_at(scientific.find('e').unwrap_or(0));
+    let exponent: i32 = exponent[1..].parse().unwrap_or(0);
+
+    if !(-4..6).contains(&exponent) {
+        let sign = if exponent < 0 { '-' } else { '+' };
+        format!("{}e{}{:02}", trim(mantissa), sign, exponent.abs())
+    } else {
+        trim(&format!("{:.*}", (5 - exponent) as usize, number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transfers() {
+        let transfer = |bytes, seconds| transfer(bytes, Duration::from_secs_f64(seconds), false);
+
+        assert_eq!(transfer(0, 0.0), "0 bytes copied, 0 s, Infinity B/s");
+        assert_eq!(transfer(1, 0.00130006), "1 byte copied, 0.00130006 s, 0.8 kB/s");
+        assert_eq!(transfer(999, 2.4939e-05), "999 bytes copied, 2.4939e-05 s, 40.1 MB/s");
+        assert_eq!(
+            transfer(1000, 0.000927756),
+            "1000 bytes (1.0 kB) copied, 0.000927756 s, 1.1 MB/s"
+        );
+        assert_eq!(
+            transfer(41000, 6.4906e-05),
+            "41000 bytes (41 kB, 40 KiB) copied, 6.4906e-05 s, 632 MB/s"
+        );
+        assert_eq!(
+            transfer(5_000_000_000, 2.20909),
+            "5000000000 bytes (5.0 GB, 4.7 GiB) copied, 2.20909 s, 2.3 GB/s"
+        );
+    }
+
+    #[test]
+    fn progress() {
+        assert_eq!(
+            transfer(2702420, Duration::from_millis(1003), true),
+            "2702420 bytes (2.7 MB, 2.6 MiB) copied, 1 s, 2.7 MB/s"
+        );
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(human_size(10239, 1024), "10 KiB");
+        assert_eq!(human_size(99999, 1000), "100 kB");
+        assert_eq!(human_size(1023999, 1024), "1000 KiB");
+        assert_eq!(human_size(999_999, 1000), "1.0 MB");
+    }
+
+    #[test]
+    fn summary() {
+        let mut stats = Stats::new(Status::Full);
+        stats.full_in = 2;
+        stats.partial_in = 1;
+        stats.full_out = 3;
+        stats.bytes = 7;
+
+        assert_eq!(
+            stats.summary(Duration::from_secs(7)),
+            "2+1 records in\n3+0 records out\n7 bytes copied, 7 s, 0.0 kB/s\n"
+        );
+
+        stats.status = Status::NoTransfer;
+        assert_eq!(stats.summary(Duration::from_secs(7)), "2+1 records in\n3+0 records out\n");
+    }
+}
@@ -0,0 +1,102 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("Files to display the space of their file systems.")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("all")
+                .help("Include the pseudo and duplicated file systems, and the empty ones.")
+                .long("all")
+                .short("a"),
+        )
+        .arg(
+            Arg::with_name("human_readable")
+                .help("Display the sizes in powers of 1024 (e.g., 1023M).")
+                .long("human-readable")
+                .short("h")
+                .overrides_with_all(&["si", "kibibytes"]),
+        )
+        .arg(
+            Arg::with_name("si")
+                .help("Display the sizes in powers of 1000 (e.g., 1.1G).")
+                .long("si")
+                .short("H")
+                .overrides_with_all(&["human_readable", "kibibytes"]),
+        )
+        .arg(
+            Arg::with_name("inodes")
+                .help("Display the usage of the file nodes instead of the blocks.")
+                .long("inodes")
+                .short("i"),
+        )
+        .arg(
+            Arg::with_name("kibibytes")
+                .help("Display the sizes in blocks of 1024 bytes, the default.")
+                .short("k")
+                .overrides_with_all(&["human_readable", "si"]),
+        )
+        .arg(
+            Arg::with_name("local")
+                .help("Only display the local file systems.")
+                .long("local")
+                .short("l"),
+        )
+        .arg(
+            Arg::with_name("type")
+                .help("Only display the file systems of the TYPE.")
+                .long("type")
+                .short("t")
+                .value_name("TYPE")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("print_type")
+                .help("Display the type of the file systems.")
+                .long("print-type")
+                .short("T"),
+        )
+        .arg(
+            Arg::with_name("exclude_type")
+                .help("Do not display the file systems of the TYPE.")
+                .long("exclude-type")
+                .short("x")
+                .value_name("TYPE")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("output")
+                .help("Display the columns of the FIELD_LIST, or all of them without it.")
+                .long_help(
+                    "Display the columns of the FIELD_LIST, separated by commas, or all of them \
+                     without it.\n\nThe fields are:\n  source  the device or name of the file \
+                     system\n  fstype  the type of the file system\n  itotal  total file nodes\n  \
+                     iused   used file nodes\n  iavail  available file nodes\n  ipcent  \
+                     percentage of used file nodes\n  size    total blocks\n  used    used \
+                     blocks\n  avail   available blocks\n  pcent   percentage of used blocks\n  \
+                     file    the FILE, if any\n  target  the mount point",
+                )
+                .long("output")
+                .value_name("FIELD_LIST")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .use_delimiter(true)
+                .multiple(true)
+                .conflicts_with_all(&["inodes", "print_type"]),
+        )
+}
@@ -0,0 +1,597 @@
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    os::unix::fs::{FileTypeExt, MetadataExt},
+    process,
+};
+
+use clap::ArgMatches;
+use coreutils_core::{
+    error::strerror,
+    os::{
+        fs_usage::FsUsage,
+        mount_table::{MountEntry, MountTable},
+    },
+    ByteSlice,
+};
+
+mod cli;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let flags = DfFlags::from_matches(&matches);
+    if let Some(fs_type) = flags.include.iter().find(|fs_type| flags.exclude.contains(fs_type)) {
+        eprintln!("df: file system type '{}' both selected and excluded", fs_type);
+        process::exit(1);
+    }
+
+    let fields = match output_fields(&matches) {
+        Ok(fields) => fields,
+        Err(err) => {
+            eprintln!("df: {}", err);
+            process::exit(1);
+        },
+    };
+
+    let table = MountTable::system();
+    let mut exit_code = 0;
+
+    let rows = match (matches.values_of("FILE"), &table) {
+        (Some(files), _) => files
+            .filter_map(|file| match file_row(file, table.as_ref().ok()) {
+                Ok(row) if flags.is_selected(&row.fs_type) => Some(row),
+                Ok(_) => None,
+                Err(err) => {
+                    eprintln!("df: {}: {}", file, strerror(&err));
+                    exit_code = 1;
+                    None
+                },
+            })
+            .collect(),
+        (None, Ok(table)) => mounted_rows(table, &flags, &mut exit_code),
+        (None, Err(err)) => {
+            eprintln!("df: {}", strerror(err));
+            process::exit(1);
+        },
+    };
+
+    if rows.is_empty() {
+        if exit_code == 0 {
+            eprintln!("df: no file systems processed");
+        }
+        process::exit(1);
+    }
+
+    if let Err(err) = print_table(&rows, &fields, &flags) {
+        eprintln!("df: {}", strerror(&err));
+        process::exit(1);
+    }
+
+    process::exit(exit_code);
+}
+
+#[derive(Debug, Clone)]
+struct DfFlags<'a> {
+    all: bool,
+    local: bool,
+    sizes: Sizes,
+    /// The columns are the default ones, not the of `--output`.
+    default_fields: bool,
+    /// The types of the file systems to display, or all if empty.
+    include: Vec<&'a str>,
+    /// The types of the file systems to not display.
+    exclude: Vec<&'a str>,
+}
+
+impl<'a> DfFlags<'a> {
+    fn from_matches(matches: &'a ArgMatches) -> Self {
+        let sizes = if matches.is_present("human_readable") {
+            Sizes::Human(1024)
+        } else if matches.is_present("si") {
+            Sizes::Human(1000)
+        } else {
+            Sizes::Blocks(1024)
+        };
+
+        DfFlags {
+            all: matches.is_present("all"),
+            local: matches.is_present("local"),
+            sizes,
+            default_fields: !matches.is_present("output"),
+            include: matches.values_of("type").map(Iterator::collect).unwrap_or_default(),
+            exclude: matches.values_of("exclude_type").map(Iterator::collect).unwrap_or_default(),
+        }
+    }
+
+    /// The file systems of `fs_type` are selected by `--type` and `--exclude-type`.
+    fn is_selected(&self, fs_type: &str) -> bool {
+        (self.include.is_empty() || self.include.contains(&fs_type))
+            && !self.exclude.contains(&fs_type)
+    }
+}
+
+/// How the sizes are displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Sizes {
+    /// In blocks of the size.
+    Blocks(u64),
+    /// With the largest suffix of the powers of the base, like `1.5G`.
+    Human(u64),
+}
+
+/// A column of the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Source,
+    FsType,
+    Inodes,
+    InodesUsed,
+    InodesAvail,
+    InodesPercent,
+    Size,
+    Used,
+    Avail,
+    Percent,
+    File,
+    Target,
+}
+
+impl Field {
+    const ALL: [Field; 12] = [
+        Field::Source,
+        Field::FsType,
+        Field::Inodes,
+        Field::InodesUsed,
+        Field::InodesAvail,
+        Field::InodesPercent,
+        Field::Size,
+        Field::Used,
+        Field::Avail,
+        Field::Percent,
+        Field::File,
+        Field::Target,
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        let field = match name {
+            "source" => Field::Source,
+            "fstype" => Field::FsType,
+            "itotal" => Field::Inodes,
+            "iused" => Field::InodesUsed,
+            "iavail" => Field::InodesAvail,
+            "ipcent" => Field::InodesPercent,
+            "size" => Field::Size,
+            "used" => Field::Used,
+            "avail" => Field::Avail,
+            "pcent" => Field::Percent,
+            "file" => Field::File,
+            "target" => Field::Target,
+            _ => return None,
+        };
+
+        Some(field)
+    }
+
+    fn header(self, flags: &DfFlags) -> String {
+        let header = match self {
+            Field::Source => "Filesystem",
+            Field::FsType => "Type",
+            Field::Inodes => "Inodes",
+            Field::InodesUsed => "IUsed",
+            Field::InodesAvail => "IFree",
+            Field::InodesPercent => "IUse%",
+            Field::Size => match flags.sizes {
+                Sizes::Blocks(size) => return format!("{}-blocks", block_size_name(size)),
+                Sizes::Human(_) => "Size",
+            },
+            Field::Used => "Used",
+            Field::Avail if flags.default_fields && !matches!(flags.sizes, Sizes::Human(_)) => {
+                "Available"
+            },
+            Field::Avail => "Avail",
+            Field::Percent => "Use%",
+            Field::File => "File",
+            Field::Target => "Mounted on",
+        };
+
+        header.to_string()
+    }
+
+    /// The minimum width of the column.
+    fn width(self) -> usize {
+        match self {
+            Field::Source => 14,
+            Field::FsType | Field::Percent | Field::InodesPercent => 4,
+            Field::File | Field::Target => 0,
+            _ => 5,
+        }
+    }
+
+    /// The column of text aligned to the left, instead of a number aligned to the right.
+    fn is_text(self) -> bool {
+        matches!(self, Field::Source | Field::FsType | Field::File | Field::Target)
+    }
+}
+
+/// The columns of the table selected with `--output`, or the default ones.
+fn output_fields(matches: &ArgMatches) -> Result<Vec<Field>, String> {
+    if !matches.is_present("output") {
+        let mut fields = if matches.is_present("inodes") {
+            vec![
+                Field::Source,
+                Field::Inodes,
+                Field::InodesUsed,
+                Field::InodesAvail,
+                Field::InodesPercent,
+                Field::Target,
+            ]
+        } else {
+            vec![
+                Field::Source,
+                Field::Size,
+                Field::Used,
+                Field::Avail,
+                Field::Percent,
+                Field::Target,
+            ]
+        };
+        if matches.is_present("print_type") {
+            fields.insert(1, Field::FsType);
+        }
+
+        return Ok(fields);
+    }
+
+    let names: Vec<_> = matches.values_of("output").into_iter().flatten().collect();
+    if names.is_empty() {
+        return Ok(Field::ALL.to_vec());
+    }
+
+    let mut fields = Vec::new();
+    for name in names {
+        let field = match Field::from_name(name) {
+            Some(field) => field,
+            None => return Err(format!("option --output: field '{}' unknown", name)),
+        };
+        if fields.contains(&field) {
+            return Err(format!("option --output: field '{}' used more than once", name));
+        }
+        fields.push(field);
+    }
+
+    Ok(fields)
+}
+
+/// A file system of the table, with its usage.
+#[derive(Debug)]
+struct Row {
+    source: String,
+    fs_type: String,
+    /// The file given by the user on the file system.
+    file: Option<String>,
+    target: String,
+    usage: FsUsage,
+}
+
+impl Row {
+    fn new(entry: Option<&MountEntry>, file: Option<&str>, usage: FsUsage) -> Self {
+        let name = |name: Option<String>| name.unwrap_or_else(|| "-".to_string());
+
+        Row {
+            source: name(entry.map(|entry| entry.source().to_str_lossy().into_owned())),
+            fs_type: name(entry.map(|entry| entry.fs_type().to_str_lossy().into_owned())),
+            file: file.map(String::from),
+            target: name(entry.map(|entry| entry.target().to_str_lossy().into_owned())),
+            usage,
+        }
+    }
+
+    fn cell(&self, field: Field, sizes: Sizes) -> String {
+        let usage = &self.usage;
+        let bytes = |blocks: u64| u128::from(blocks) * u128::from(usage.block_size());
+
+        match field {
+            Field::Source => self.source.clone(),
+            Field::FsType => self.fs_type.clone(),
+            Field::Inodes => count(u128::from(usage.files()), sizes),
+            Field::InodesUsed => count(u128::from(usage.used_files()), sizes),
+            Field::InodesAvail => count(u128::from(usage.free_files()), sizes),
+            Field::InodesPercent => percent(usage.used_files(), usage.free_files()),
+            Field::Size => size(bytes(usage.blocks()), sizes),
+            Field::Used => size(bytes(usage.used_blocks()), sizes),
+            Field::Avail => size(bytes(usage.available_blocks()), sizes),
+            Field::Percent => percent(usage.used_blocks(), usage.available_blocks()),
+            Field::File => self.file.clone().unwrap_or_else(|| "-".to_string()),
+            Field::Target => self.target.clone(),
+        }
+    }
+}
+
+/// The rows of the mounted file systems, without the pseudo, duplicated and empty ones
+/// unless `--all`.
+fn mounted_rows(table: &MountTable, flags: &DfFlags, exit_code: &mut i32) -> Vec<Row> {
+    // The entries with the device of their mount point, that may be of other file system
+    // mounted over them
+    let mut entries: Vec<(Option<u64>, &MountEntry)> = Vec::with_capacity(table.len());
+
+    for entry in table {
+        if (entry.is_dummy() && !flags.all)
+            || (entry.is_remote() && flags.local)
+            || !flags.is_selected(&entry.fs_type().to_str_lossy())
+        {
+            continue;
+        }
+
+        let dev = match fs::metadata(entry.target().to_path_lossy()) {
+            Ok(metadata) => Some(metadata.dev()),
+            Err(_) => entry.dev(),
+        };
+
+        if !flags.all {
+            let seen = entries.iter_mut().find(|(seen_dev, seen)| {
+                dev.is_some()
+                    && *seen_dev == dev
+                    && !(entry.is_remote() && seen.is_remote() && seen.source() != entry.source())
+            });
+
+            if let Some((_, seen)) = seen {
+                if shows_better(entry, seen) {
+                    *seen = entry;
+                }
+                continue;
+            }
+        }
+
+        entries.push((dev, entry));
+    }
+
+    entries
+        .into_iter()
+        .filter_map(|(_, entry)| match FsUsage::new(entry.target().to_path_lossy()) {
+            Ok(usage) if usage.blocks() == 0 && !flags.all => None,
+            Ok(usage) => Some(Row::new(Some(entry), None, usage)),
+            Err(err) => {
+                eprintln!("df: {}: {}", entry.target(), strerror(&err));
+                *exit_code = 1;
+                None
+            },
+        })
+        .collect()
+}
+
+/// The `entry` of a device shows it better than the `seen` one: it's a device file while
+/// the other is not, it's mounted nearer the root of the file systems, or it's mounted
+/// over the other.
+fn shows_better(entry: &MountEntry, seen: &MountEntry) -> bool {
+    let target_nearer_root = seen.target().len() > entry.target().len();
+    let source_below_root = match (seen.root(), entry.root()) {
+        (Some(seen), Some(root)) => seen.len() < root.len(),
+        _ => false,
+    };
+
+    (entry.source().contains(&b'/') && !seen.source().contains(&b'/'))
+        || (target_nearer_root && !source_below_root)
+        || (entry.source() != seen.source() && entry.target() == seen.target())
+}
+
+/// The row of the file system of `file`, or of the one mounted from it if it's a block
+/// device.
+fn file_row(file: &str, table: Option<&MountTable>) -> io::Result<Row> {
+    let metadata = fs::metadata(file)?;
+    let path = fs::canonicalize(file)?;
+
+    let entries = table.into_iter().flatten();
+    let mounted = if metadata.file_type().is_block_device() {
+        entries.rev().find(|entry| entry.source().to_path_lossy() == path)
+    } else {
+        // The last mounted over the others of the nearest mount point
+        entries
+            .filter(|entry| path.starts_with(entry.target().to_path_lossy()))
+            .max_by_key(|entry| entry.target().len())
+    };
+
+    let usage = match mounted {
+        Some(entry) if metadata.file_type().is_block_device() => {
+            FsUsage::new(entry.target().to_path_lossy())?
+        },
+        _ => FsUsage::new(&path)?,
+    };
+
+    Ok(Row::new(mounted, Some(file), usage))
+}
+
+/// Print the `fields` of the `rows` with their headers, in columns of the width of the
+/// largest cell.
+fn print_table(rows: &[Row], fields: &[Field], flags: &DfFlags) -> io::Result<()> {
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    lines.push(fields.iter().map(|field| field.header(flags)).collect::<Vec<_>>());
+    for row in rows {
+        lines.push(fields.iter().map(|&field| row.cell(field, flags.sizes)).collect());
+    }
+
+    let widths: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            lines.iter().map(|line| line[i].chars().count()).max().unwrap_or(0).max(field.width())
+        })
+        .collect();
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    for line in &lines {
+        for (i, (cell, field)) in line.iter().zip(fields).enumerate() {
+            if i > 0 {
+                write!(out, " ")?;
+            }
+
+            let width = widths[i];
+            if !field.is_text() {
+                write!(out, "{:>width$}", cell, width = width)?;
+            } else if i + 1 == fields.len() {
+                // The last column without trailing spaces
+                write!(out, "{}", cell)?;
+            } else {
+                write!(out, "{:<width$}", cell, width = width)?;
+            }
+        }
+        writeln!(out)?;
+    }
+
+    out.flush()
+}
+
+/// The name of the block `size`, like `1K` of 1024 bytes.
+// `is_multiple_of` is newer than the MSRV
+#[allow(clippy::manual_is_multiple_of)]
+fn block_size_name(size: u64) -> String {
+    let suffixes = ["", "K", "M", "G", "T", "P", "E"];
+
+    let mut size = size;
+    let mut exponent = 0;
+    while size % 1024 == 0 && size > 0 && exponent + 1 < suffixes.len() {
+        size /= 1024;
+        exponent += 1;
+    }
+
+    format!("{}{}", size, suffixes[exponent])
+}
+
+/// Display of the `bytes` in the `sizes`, rounding up.
+// `is_multiple_of` is newer than the MSRV
+#[allow(clippy::manual_is_multiple_of)]
+fn size(bytes: u128, sizes: Sizes) -> String {
+    match sizes {
+        Sizes::Blocks(size) => {
+            let size = u128::from(size);
+            (bytes / size + (bytes % size != 0) as u128).to_string()
+        },
+        Sizes::Human(base) => human(bytes, u128::from(base)),
+    }
+}
+
+/// Display of the count of file nodes, with a suffix if human readable.
+fn count(count: u128, sizes: Sizes) -> String {
+    match sizes {
+        Sizes::Blocks(_) => count.to_string(),
+        Sizes::Human(base) => human(count, u128::from(base)),
+    }
+}
+
+/// The percentage of the `used` from the total of `used` and `available`, rounding up, or
+/// `-` if there is none.
+fn percent(used: u64, available: u64) -> String {
+    let total = u128::from(used) + u128::from(available);
+    if total == 0 {
+        return "-".to_string();
+    }
+
+    let used = u128::from(used) * 100;
+    format!("{}%", used / total + (used % total != 0) as u128)
+}
+
+/// Display `amount` with the largest suffix of the powers of `base` that leaves it under
+/// the base, rounding up, and with a decimal digit if it's under 10, like GNU.
+// `is_multiple_of` is newer than the MSRV
+#[allow(clippy::manual_is_multiple_of)]
+fn human(amount: u128, base: u128) -> String {
+    let suffixes = ['K', 'M', 'G', 'T', 'P', 'E', 'Z', 'Y'];
+
+    if amount < base {
+        return amount.to_string();
+    }
+
+    let mut amount = amount;
+    let mut exponent = 0;
+    // The tenths of the amount and if there is more after them
+    let mut tenths = 0;
+    let mut remainder = false;
+
+    while amount >= base && exponent < suffixes.len() {
+        let scaled_tenths = amount % base * 10 + tenths;
+        remainder = remainder || scaled_tenths % base != 0;
+        tenths = scaled_tenths / base;
+        amount /= base;
+        exponent += 1;
+    }
+
+    let mut decimal = None;
+    if amount < 10 {
+        if remainder {
+            tenths += 1;
+            remainder = false;
+            if tenths == 10 {
+                amount += 1;
+                tenths = 0;
+            }
+        }
+        if amount < 10 {
+            decimal = Some(tenths);
+            tenths = 0;
+        }
+    }
+
+    if tenths > 0 || remainder {
+        amount += 1;
+        if amount == base && exponent < suffixes.len() {
+            amount = 1;
+            exponent += 1;
+            decimal = Some(0);
+        }
+    }
+
+    let suffix = match suffixes[exponent - 1] {
+        // The kilo of the SI is lowercase
+        'K' if base == 1000 => 'k',
+        suffix => suffix,
+    };
+
+    match decimal {
+        Some(tenths) => format!("{}.{}{}", amount, tenths, suffix),
+        None => format!("{}{}", amount, suffix),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn human_sizes() {
+        assert_eq!(human(0, 1024), "0");
+        assert_eq!(human(1023, 1024), "1023");
+        assert_eq!(human(1024, 1024), "1.0K");
+        assert_eq!(human(1025, 1024), "1.1K");
+        assert_eq!(human(10 * 1024 - 1, 1024), "10K");
+        assert_eq!(human(1024 * 1024 - 1, 1024), "1.0M");
+        assert_eq!(human(270_553_174_016, 1024), "252G");
+        assert_eq!(human(270_553_174_016, 1000), "271G");
+        assert_eq!(human(1500, 1000), "1.5k");
+    }
+
+    #[test]
+    fn sizes_and_percents() {
+        assert_eq!(size(4096 * 3, Sizes::Blocks(1024)), "12");
+        assert_eq!(size(1025, Sizes::Blocks(1024)), "2");
+        assert_eq!(block_size_name(1024), "1K");
+        assert_eq!(block_size_name(512), "512");
+        assert_eq!(percent(21_610_244, 74_447_164), "23%");
+        assert_eq!(percent(0, 10), "0%");
+        assert_eq!(percent(0, 0), "-");
+    }
+
+    #[test]
+    fn selected_types() {
+        let matches = cli::create_app().get_matches_from(vec!["df", "-t", "ext4", "-t", "xfs"]);
+        let flags = DfFlags::from_matches(&matches);
+        assert!(flags.is_selected("ext4"));
+        assert!(!flags.is_selected("tmpfs"));
+
+        let matches = cli::create_app().get_matches_from(vec!["df", "-x", "tmpfs"]);
+        let flags = DfFlags::from_matches(&matches);
+        assert!(flags.is_selected("ext4"));
+        assert!(!flags.is_selected("tmpfs"));
+    }
+}
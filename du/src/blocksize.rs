@@ -1,4 +1,4 @@
-use std::{fmt, slice::Iter};
+use std::fmt;
 
 use self::BlocksizeSuffix::*;
 
@@ -34,12 +34,6 @@ enum BlocksizeSuffix {
 }
 
 impl BlocksizeSuffix {
-    fn iter() -> Iter<'static, BlocksizeSuffix> {
-        static SUFFIXES: [BlocksizeSuffix; 18] =
-            [KB, K, KiB, MB, M, MiB, GB, G, GiB, TB, T, TiB, PB, P, PiB, EB, E, EiB];
-        SUFFIXES.iter()
-    }
-
     fn value(self) -> u64 {
         match self {
             KB => 10u64.pow(3),
@@ -79,23 +73,6 @@ impl BlocksizeSuffix {
             EiB => "EiB",
         }
     }
-
-    fn largest_from_value(value: u64, use_si: bool) -> Option<BlocksizeSuffix> {
-        let mut largest_suffix: Option<BlocksizeSuffix> = None;
-
-        for (count, suffix) in BlocksizeSuffix::iter().enumerate() {
-            // take base 10 suffixes
-            if use_si && count % 3 == 0 && value / suffix.value() > 1 {
-                largest_suffix = Some(*suffix);
-            }
-            // take single letter base 2 suffixes
-            if !use_si && count % 3 == 1 && value / suffix.value() > 1 {
-                largest_suffix = Some(*suffix);
-            }
-        }
-
-        largest_suffix
-    }
 }
 
 #[derive(Debug)]
@@ -178,19 +155,69 @@ impl Blocksize {
         self.use_si = true;
     }
 
-    pub fn use_largest_suffix(self) -> Self {
-        let total = self.value();
-        let suffix = BlocksizeSuffix::largest_from_value(total, self.use_si);
-
-        Blocksize { value: total / suffix.map_or(1, |s| s.value()), suffix, use_si: self.use_si }
-    }
-
     pub fn value(&self) -> u64 {
         self.value * self.suffix.map_or(1, |s| s.value())
     }
 
+    /// Format the value with the largest suffix of the powers of 1024, or 1000 if using
+    /// SI, that leaves it under them, rounding up and with a decimal digit under 10, like
+    /// `1.5K`.
+    // `is_multiple_of` is newer than the MSRV
+    #[allow(clippy::manual_is_multiple_of)]
     pub fn human_readable(&self) -> String {
-        format!("{}", self)
+        let (base, suffixes) = if self.use_si {
+            (1000, ['k', 'M', 'G', 'T', 'P', 'E'])
+        } else {
+            (1024, ['K', 'M', 'G', 'T', 'P', 'E'])
+        };
+
+        let mut amount = self.value();
+        if amount < base {
+            return amount.to_string();
+        }
+
+        let mut exponent = 0;
+        // The tenths of the amount and if there is more after them
+        let mut tenths = 0;
+        let mut remainder = false;
+
+        while amount >= base && exponent < suffixes.len() {
+            let scaled_tenths = amount % base * 10 + tenths;
+            remainder = remainder || scaled_tenths % base != 0;
+            tenths = scaled_tenths / base;
+            amount /= base;
+            exponent += 1;
+        }
+
+        let mut decimal = None;
+        if amount < 10 {
+            if remainder {
+                tenths += 1;
+                remainder = false;
+                if tenths == 10 {
+                    amount += 1;
+                    tenths = 0;
+                }
+            }
+            if amount < 10 {
+                decimal = Some(tenths);
+                tenths = 0;
+            }
+        }
+
+        if tenths > 0 || remainder {
+            amount += 1;
+            if amount == base && exponent < suffixes.len() {
+                amount = 1;
+                exponent += 1;
+                decimal = Some(0);
+            }
+        }
+
+        match decimal {
+            Some(tenths) => format!("{}.{}{}", amount, tenths, suffixes[exponent - 1]),
+            None => format!("{}{}", amount, suffixes[exponent - 1]),
+        }
     }
 
     pub fn suffix_str(&self) -> &'static str {
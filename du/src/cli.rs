@@ -193,7 +193,8 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 )
                 .long("threshold")
                 .short("t")
-                .value_name("SIZE"),
+                .value_name("SIZE")
+                .allow_hyphen_values(true),
         )
         .arg(
             Arg::with_name("time")
@@ -216,7 +217,18 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Exclude files that match PATTERN.")
                 .long("exclude")
                 .short("p")
-                .value_name("PATTERN"),
+                .value_name("PATTERN")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("exclude-from")
+                .help("Exclude files that match any pattern in FILE, one for each line.")
+                .long("exclude-from")
+                .short("X")
+                .value_name("FILE")
+                .multiple(true)
+                .number_of_values(1),
         )
         .arg(
             Arg::with_name("one-file-system")
@@ -1,6 +1,11 @@
 #[cfg(target_family = "unix")]
 use std::os::unix::fs::MetadataExt;
-use std::{fs::Metadata, path::Display, process};
+use std::{
+    collections::HashSet,
+    fs::{self, Metadata},
+    path::{Display, PathBuf},
+    process,
+};
 
 use clap::ArgMatches;
 use glob::Pattern;
@@ -23,9 +28,12 @@ fn main() {
     let paths = parse_files(&matches);
 
     let mut grand_total = 0;
+    // The files and directories already counted, that are not counted again from their
+    // other links, the followed symbolic links or the other FILE
+    let mut seen = HashSet::new();
 
     for path in paths {
-        process_path(path, &flags, &mut grand_total);
+        grand_total += process_path(path, &flags, &mut seen);
     }
 
     if flags.grand_total {
@@ -44,15 +52,6 @@ enum DisplayValue {
     DiskUsage(Blocksize),
 }
 
-impl DisplayValue {
-    fn size(&self) -> u64 {
-        match &self {
-            DisplayValue::INodes(inodes) => *inodes,
-            DisplayValue::DiskUsage(blocksize) => blocksize.value(),
-        }
-    }
-}
-
 #[derive(Debug)]
 struct DuFlagsAndOptions<'a> {
     pub show_all: bool,
@@ -68,7 +67,7 @@ struct DuFlagsAndOptions<'a> {
     pub separate_dirs: bool,
     pub grand_total: bool,
     pub blocksize: Blocksize,
-    pub exclude_pattern: Option<Pattern>,
+    pub exclude_patterns: Vec<Pattern>,
     pub max_depth: Option<usize>,
     pub threshold: Option<(bool, Blocksize)>,
     pub time: Option<TimeOption>,
@@ -101,7 +100,7 @@ impl<'a> DuFlagsAndOptions<'a> {
             separate_dirs: matches.is_present("separate-dirs"),
             grand_total: matches.is_present("total"),
             blocksize: parse_blocksize(matches),
-            exclude_pattern: parse_exclude_pattern(matches.value_of("exclude-pattern")),
+            exclude_patterns: parse_exclude_patterns(matches),
             max_depth: parse_depth(matches),
             threshold: parse_threshold(matches.value_of("threshold")),
             time: parse_time(matches),
@@ -171,25 +170,52 @@ fn parse_blocksize(matches: &ArgMatches) -> Blocksize {
     initial_size
 }
 
-fn parse_exclude_pattern(value: Option<&str>) -> Option<Pattern> {
-    if let Some(pattern) = value {
-        match Pattern::new(pattern) {
-            Ok(p) => return Some(p),
+fn parse_exclude_patterns(matches: &ArgMatches) -> Vec<Pattern> {
+    let mut patterns: Vec<String> =
+        matches.values_of("exclude-pattern").into_iter().flatten().map(String::from).collect();
+
+    for file in matches.values_of("exclude-from").into_iter().flatten() {
+        match fs::read_to_string(file) {
+            Ok(content) => {
+                patterns.extend(content.lines().filter(|l| !l.is_empty()).map(String::from))
+            },
             Err(err) => {
-                println!("du: error parsing value for --exclude-pattern: {}", err);
+                eprintln!("du: {}: {}", file, err);
                 process::exit(1);
             },
         }
     }
-    None
+
+    patterns
+        .iter()
+        .map(|pattern| match Pattern::new(pattern) {
+            Ok(p) => p,
+            Err(err) => {
+                eprintln!("du: error parsing value for --exclude: {}", err);
+                process::exit(1);
+            },
+        })
+        .collect()
+}
+
+/// The `path` matches one of the `patterns`, whole or from any of its components, so
+/// `b` excludes `./a/b`.
+fn is_excluded(path: &str, patterns: &[Pattern]) -> bool {
+    patterns.iter().any(|pattern| {
+        pattern.matches(path)
+            || path
+                .match_indices('/')
+                .any(|(i, _)| !path[i + 1..].starts_with('/') && pattern.matches(&path[i + 1..]))
+    })
 }
 
 fn parse_depth(matches: &ArgMatches) -> Option<usize> {
-    if matches.is_present("summarize") {
-        return Some(0);
-    }
     if let Some(depth) = matches.value_of("max-depth") {
         match depth.parse::<usize>() {
+            Ok(number) if number != 0 && matches.is_present("summarize") => {
+                eprintln!("du: warning: summarizing conflicts with --max-depth={}", number);
+                process::exit(1);
+            },
             Ok(number) => return Some(number),
             Err(err) => {
                 eprintln!("du: error parsing value for --max-depth: {}", err);
@@ -197,6 +223,9 @@ fn parse_depth(matches: &ArgMatches) -> Option<usize> {
             },
         }
     }
+    if matches.is_present("summarize") {
+        return Some(0);
+    }
     None
 }
 
@@ -206,11 +235,17 @@ fn parse_threshold(value: Option<&str>) -> Option<(bool, Blocksize)> {
 
         let threshold_slice = if is_negative { &threshold[1..] } else { threshold };
 
+        // A zero is valid as a threshold, not as a block size
+        if threshold == "0" {
+            return Some((false, Blocksize::new().with_value(0)));
+        }
+
         match Blocksize::from_str(threshold_slice) {
             Ok(blocksize) => return Some((is_negative, blocksize)),
             Err(err) => match err {
                 BlocksizeError::InvalidBlocksize => {
                     eprintln!("du: invalid --threshold argument: '{}'", threshold);
+                    process::exit(1);
                 },
                 BlocksizeError::InvalidSuffixError(s) => {
                     eprintln!("du: invalid suffix in --threshold argument: '{}'", &s);
@@ -268,146 +303,179 @@ fn parse_time_style(value: Option<&str>) -> TimeStyleOption {
     }
 }
 
-fn process_path(path: &str, flags_opts: &DuFlagsAndOptions, total_ref: &mut u64) {
-    let walker = WalkDir::new(path)
+/// A directory whose contents are being counted, that is printed after them.
+#[derive(Debug)]
+struct PendingDir {
+    path: PathBuf,
+    depth: usize,
+    size: u64,
+    time: Option<DuTime>,
+}
+
+/// Count and print the usage of the `path` and its contents, returning its total.
+///
+/// The files and directories already in `seen` are not counted.
+fn process_path(path: &str, flags_opts: &DuFlagsAndOptions, seen: &mut HashSet<(u64, u64)>) -> u64 {
+    let mut walker = WalkDir::new(path)
         .same_file_system(flags_opts.one_file_system)
         .follow_links(flags_opts.dereference)
-        .contents_first(true);
-
-    // tracks depth
-    let mut current_depth = 0;
+        .into_iter();
+
+    // The directories from the root to the current entry, finished when the walk leaves
+    // them
+    let mut pending: Vec<PendingDir> = Vec::new();
+    let mut total = 0;
+    let mut root_dev = None;
+
+    while let Some(entry) = walker.next() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+        let meta = match entry.metadata() {
+            Ok(meta) => meta,
+            Err(_) => continue,
+        };
+        if is_excluded(&entry.path().to_string_lossy(), &flags_opts.exclude_patterns) {
+            if meta.is_dir() {
+                walker.skip_current_dir();
+            }
+            continue;
+        }
 
-    // tracks subdir sizes, indexed by depth [0] => root
-    let mut subdir_sizes = vec![0_u64];
+        let depth = entry.depth();
+        finish_dirs(path, &mut pending, depth, flags_opts, &mut total);
 
-    // tracks max m/a/c-time of a subdir
-    let mut subir_max_times = vec![DuTime::new(0)];
+        // The walk doesn't descend into the other file systems, but still gives their
+        // mount points
+        if depth == 0 {
+            root_dev = get_dev(&meta);
+        } else if flags_opts.one_file_system && get_dev(&meta) != root_dev {
+            continue;
+        }
 
-    let mut arg_total = 0;
+        let follow_links = flags_opts.dereference || flags_opts.dereference_args;
 
-    walker
-        .into_iter()
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| {
-            flags_opts.exclude_pattern.as_ref().map_or(true, |p| !p.matches_path(&entry.path()))
-        })
-        .for_each(|entry| {
-            current_depth = entry.depth();
-            if let Ok(meta) = entry.metadata() {
-                if meta.is_dir() {
-                    let value =
-                        process_value(&meta, flags_opts, &mut subdir_sizes, current_depth, true);
-
-                    if flags_opts.grand_total {
-                        arg_total = value.size();
-                    }
-
-                    if let Some(t) = &flags_opts.time {
-                        let time = process_time(&meta, t, &mut subir_max_times, current_depth);
-                        filter_and_print(
-                            path,
-                            &entry.path().display(),
-                            value,
-                            Some(time),
-                            flags_opts,
-                            current_depth,
-                            true,
-                        );
-                    } else {
-                        filter_and_print(
-                            path,
-                            &entry.path().display(),
-                            value,
-                            None,
-                            flags_opts,
-                            current_depth,
-                            true,
-                        );
-                    }
-                } else {
-                    let value =
-                        process_value(&meta, flags_opts, &mut subdir_sizes, current_depth, false);
-
-                    if let Some(t) = &flags_opts.time {
-                        let time = process_time(&meta, t, &mut subir_max_times, current_depth);
-                        filter_and_print(
-                            path,
-                            &entry.path().display(),
-                            value,
-                            Some(time),
-                            flags_opts,
-                            current_depth,
-                            false,
-                        );
-                    } else {
-                        filter_and_print(
-                            path,
-                            &entry.path().display(),
-                            value,
-                            None,
-                            flags_opts,
-                            current_depth,
-                            false,
-                        );
-                    }
-                }
+        if !flags_opts.count_links && !first_seen(&meta, seen, follow_links) {
+            if meta.is_dir() {
+                walker.skip_current_dir();
             }
-        });
-
-    if flags_opts.grand_total {
-        *total_ref += arg_total;
-    }
-}
+            continue;
+        }
 
-// returns file size and manages the subdir sizes vector
-fn process_value(
-    meta: &Metadata, flags_opts: &DuFlagsAndOptions, subdir_sizes_r: &mut Vec<u64>, depth: usize,
-    is_dir: bool,
-) -> DisplayValue {
-    let subdir_count = subdir_sizes_r.len() - 1;
+        let size = get_size(&meta, flags_opts);
+        let time = flags_opts.time.as_ref().map(|t| get_display_time(&meta, t));
 
-    if depth > subdir_count {
-        // fill up vec, so index equals depth. e.g. file depth = 2 => [0, 0 ,0]
-        for _ in 0..(depth - subdir_count) {
-            subdir_sizes_r.push(0);
+        if meta.is_dir() {
+            pending.push(PendingDir { path: entry.path().to_path_buf(), depth, size, time });
+            continue;
         }
-    }
 
-    let display_value = get_display_value(meta, flags_opts);
+        match pending.last_mut() {
+            Some(parent) => {
+                parent.size += size;
+                parent.time = max_time(parent.time.take(), time.clone());
+            },
+            None => total += size,
+        }
 
-    if !is_dir || !flags_opts.separate_dirs {
-        // add size to subdir total size
-        subdir_sizes_r[depth] += display_value.size();
+        filter_and_print(
+            path,
+            &entry.path().display(),
+            display_value(size, flags_opts),
+            time,
+            flags_opts,
+            depth,
+            false,
+        );
     }
 
-    if is_dir && depth < subdir_count {
-        // when recursing back to the parent directory
-        let subdir_sum = subdir_sizes_r.pop().unwrap_or(0);
+    finish_dirs(path, &mut pending, 0, flags_opts, &mut total);
 
-        if !flags_opts.separate_dirs {
-            subdir_sizes_r[depth] += subdir_sum;
-        }
+    total
+}
 
-        match display_value {
-            DisplayValue::INodes(i) => return DisplayValue::INodes(i + subdir_sum),
-            DisplayValue::DiskUsage(b) => {
-                let blk_val = b.value();
-                return DisplayValue::DiskUsage(b.with_value(blk_val + subdir_sum));
+/// Print the `pending` directories at the `depth` and deeper, that have all their
+/// contents counted, adding their usage to their parents, or to the `total` for the root.
+fn finish_dirs(
+    root: &str, pending: &mut Vec<PendingDir>, depth: usize, flags_opts: &DuFlagsAndOptions,
+    total: &mut u64,
+) {
+    while matches!(pending.last(), Some(dir) if dir.depth >= depth) {
+        // Ok to unwrap, the last was checked
+        let dir = pending.pop().unwrap();
+
+        match pending.last_mut() {
+            Some(parent) => {
+                if !flags_opts.separate_dirs {
+                    parent.size += dir.size;
+                }
+                parent.time = max_time(parent.time.take(), dir.time.clone());
             },
+            None => *total += dir.size,
         }
+
+        filter_and_print(
+            root,
+            &dir.path.display(),
+            display_value(dir.size, flags_opts),
+            dir.time,
+            flags_opts,
+            dir.depth,
+            true,
+        );
+    }
+}
+
+/// Returns `true` if the file of `meta` was not in `seen`, and add it.
+#[cfg(target_family = "unix")]
+fn first_seen(meta: &Metadata, seen: &mut HashSet<(u64, u64)>, follow_links: bool) -> bool {
+    // The files with a single link can't be found again, unless they are directories or
+    // the symbolic links to them are followed
+    if !follow_links && !meta.is_dir() && meta.nlink() <= 1 {
+        return true;
     }
 
-    display_value
+    seen.insert((meta.dev(), meta.ino()))
+}
+
+#[cfg(target_family = "unix")]
+fn get_dev(meta: &Metadata) -> Option<u64> {
+    Some(meta.dev())
+}
+
+#[cfg(not(target_family = "unix"))]
+fn get_dev(_meta: &Metadata) -> Option<u64> {
+    None
+}
+
+#[cfg(not(target_family = "unix"))]
+fn first_seen(_meta: &Metadata, _seen: &mut HashSet<(u64, u64)>, _follow_links: bool) -> bool {
+    true
+}
+
+fn max_time(time: Option<DuTime>, other: Option<DuTime>) -> Option<DuTime> {
+    match (time, other) {
+        (Some(time), Some(other)) if other > time => Some(other),
+        (time, other) => time.or(other),
+    }
 }
 
-fn get_display_value(metadata: &Metadata, flags_opts: &DuFlagsAndOptions) -> DisplayValue {
+// returns the file size, or 1 if counting inodes
+fn get_size(metadata: &Metadata, flags_opts: &DuFlagsAndOptions) -> u64 {
     if flags_opts.use_inodes {
-        return DisplayValue::INodes(get_inode());
+        return get_inode();
     }
 
-    let bytes = get_bytes(metadata, flags_opts.use_apparent_size);
+    get_bytes(metadata, flags_opts.use_apparent_size)
+}
 
-    let mut disk_usage = Blocksize::new().with_value(bytes);
+fn display_value(size: u64, flags_opts: &DuFlagsAndOptions) -> DisplayValue {
+    if flags_opts.use_inodes {
+        return DisplayValue::INodes(size);
+    }
+
+    let mut disk_usage = Blocksize::new().with_value(size);
 
     if flags_opts.use_si {
         disk_usage.use_si();
@@ -443,28 +511,6 @@ fn get_inode() -> u64 {
     process::exit(1);
 }
 
-// returns file time and manages the max_time vector
-fn process_time(
-    meta: &Metadata, time: &TimeOption, subdir_times_r: &mut Vec<DuTime>, depth: usize,
-) -> DuTime {
-    let subdir_times_count = subdir_times_r.len() - 1;
-
-    if depth > subdir_times_count {
-        for _ in 0..(depth - subdir_times_count) {
-            subdir_times_r.push(DuTime::new(0));
-        }
-    }
-
-    let display_time = get_display_time(&meta, &time);
-
-    if display_time > subdir_times_r[depth] {
-        // replace subdir max value
-        subdir_times_r[depth] = display_time.clone();
-    }
-
-    display_time
-}
-
 #[cfg(target_family = "unix")]
 fn get_display_time(metadata: &Metadata, time: &TimeOption) -> DuTime {
     match time {
@@ -520,25 +566,26 @@ fn filter_and_print(
 }
 
 fn satisfies_threshold(value: &DisplayValue, threshold_opt: &Option<(bool, Blocksize)>) -> bool {
-    match value {
-        DisplayValue::INodes(_i) => true,
-        DisplayValue::DiskUsage(blocksize) => {
-            if let Some(threshold) = threshold_opt {
-                let (t_is_negative, t_value) = (threshold.0, threshold.1.value());
-
-                // exclude entries greater than THRESHOLD if negative
-                if t_is_negative && blocksize.value() > t_value {
-                    return false;
-                }
+    // The threshold is a count of inodes, or a size
+    let value = match value {
+        DisplayValue::INodes(inodes) => *inodes,
+        DisplayValue::DiskUsage(blocksize) => blocksize.value(),
+    };
+
+    if let Some(threshold) = threshold_opt {
+        let (t_is_negative, t_value) = (threshold.0, threshold.1.value());
+
+        // exclude entries greater than THRESHOLD if negative
+        if t_is_negative && value > t_value {
+            return false;
+        }
 
-                // exclude entries smaller then THRESHOLD if positive
-                if !t_is_negative && blocksize.value() < t_value {
-                    return false;
-                }
-            }
-            true
-        },
+        // exclude entries smaller then THRESHOLD if positive
+        if !t_is_negative && value < t_value {
+            return false;
+        }
     }
+    true
 }
 
 fn print_du(value: DisplayValue, path: String, flags_opts: &DuFlagsAndOptions) {
@@ -568,19 +615,21 @@ fn print_du_with_time(
 
 fn format_display_value(value: DisplayValue, flags_opts: &DuFlagsAndOptions) -> String {
     match value {
+        DisplayValue::INodes(inodes) if flags_opts.print_human_readable || flags_opts.use_si => {
+            let mut count = Blocksize::new().with_value(inodes);
+            if flags_opts.use_si {
+                count.use_si();
+            }
+            count.human_readable()
+        },
         DisplayValue::INodes(inodes) => inodes.to_string(),
         DisplayValue::DiskUsage(blocksize) => {
-            if flags_opts.print_human_readable {
-                let with_largest_suffix = blocksize.use_largest_suffix();
-                with_largest_suffix.human_readable()
+            if flags_opts.print_human_readable || flags_opts.use_si {
+                blocksize.human_readable()
             } else {
-                let mut blocksize_fraction = 0;
-
-                if blocksize.value() != 0 && blocksize.value() / flags_opts.blocksize.value() == 0 {
-                    blocksize_fraction = 1;
-                } else if blocksize.value() != 0 {
-                    blocksize_fraction = blocksize.value() / flags_opts.blocksize.value();
-                }
+                // Rounding up, so any usage is at least one block
+                let (value, size) = (blocksize.value(), flags_opts.blocksize.value());
+                let blocksize_fraction = value / size + (value % size != 0) as u64;
 
                 format!("{}{}", blocksize_fraction, flags_opts.blocksize.suffix_str())
             }
@@ -49,3 +49,74 @@ fn du_parse_time_style_pattern() {
     let year_only_style = "+%Y";
     assert_eq!(TimeStyleOption::Format("%Y"), parse_time_style(Some(&year_only_style)));
 }
+
+#[cfg(target_family = "unix")]
+#[test]
+fn du_first_seen_hard_links() {
+    let dir = std::env::temp_dir().join(format!("du_first_seen_{}", process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("file"), b"content").unwrap();
+    std::fs::hard_link(dir.join("file"), dir.join("link")).unwrap();
+    std::fs::write(dir.join("single"), b"content").unwrap();
+
+    let mut seen = HashSet::new();
+    let meta = |name: &str| std::fs::metadata(dir.join(name)).unwrap();
+    assert!(first_seen(&meta("file"), &mut seen, false));
+    assert!(!first_seen(&meta("link"), &mut seen, false));
+    assert!(first_seen(&meta("single"), &mut seen, false));
+    assert!(first_seen(&meta("single"), &mut seen, false));
+    assert!(first_seen(&meta("single"), &mut seen, true));
+    assert!(!first_seen(&meta("single"), &mut seen, true));
+    assert!(first_seen(&meta("."), &mut seen, false));
+    assert!(!first_seen(&meta("."), &mut seen, false));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn du_followed_links_counted_once() {
+    let dir = std::env::temp_dir().join(format!("du_followed_links_{}", process::id()));
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    std::fs::write(dir.join("sub/file"), b"content").unwrap();
+    std::os::unix::fs::symlink("sub", dir.join("dir_link")).unwrap();
+    std::os::unix::fs::symlink("sub/file", dir.join("file_link")).unwrap();
+
+    // The directory, sub and its file, without the ones reached again through the links
+    let path = dir.to_str().unwrap();
+    let matches = cli::create_app().get_matches_from(vec!["du", "-L", "--inodes", path]);
+    let flags = DuFlagsAndOptions::from_matches(&matches);
+    assert_eq!(process_path(path, &flags, &mut HashSet::new()), 3);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn du_is_excluded_components() {
+    let patterns = vec![Pattern::new("b").unwrap(), Pattern::new("*.txt").unwrap()];
+    assert!(is_excluded("./a/b", &patterns));
+    assert!(is_excluded("./a/notes.txt", &patterns));
+    assert!(!is_excluded("./a/bc", &patterns));
+    assert!(!is_excluded("./a/b/c", &patterns));
+}
+
+#[test]
+fn du_parse_threshold_signs() {
+    let (negative, size) = parse_threshold(Some("-10K")).unwrap();
+    assert!(negative);
+    assert_eq!(10 * 1024, size.value());
+    let (negative, size) = parse_threshold(Some("0")).unwrap();
+    assert!(!negative);
+    assert_eq!(0, size.value());
+}
+
+#[test]
+fn du_human_readable_rounds_up() {
+    assert_eq!(Blocksize::new().with_value(1023).human_readable(), "1023");
+    assert_eq!(Blocksize::new().with_value(3001).human_readable(), "3.0K");
+    let mut si = Blocksize::new().with_value(3001);
+    si.use_si();
+    assert_eq!(si.human_readable(), "3.1k");
+    assert_eq!(Blocksize::new().with_value(10 * 1024 + 1).human_readable(), "11K");
+    assert_eq!(Blocksize::new().with_value(1024 * 1024 - 1).human_readable(), "1.0M");
+}
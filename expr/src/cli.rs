@@ -0,0 +1,40 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App,
+    AppSettings::{AllowLeadingHyphen, ColoredHelp, TrailingVarArg},
+    Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp, AllowLeadingHyphen, TrailingVarArg])
+        .arg(
+            Arg::with_name("EXPRESSION")
+                .help("The expression to evaluate, with each token as a separate argument.")
+                .long_help(
+                    "The expression to evaluate, with each token as a separate argument.\n\nThe \
+                     operators, from the lowest to the highest precedence, are:\n\n  ARG1 | \
+                     ARG2    ARG1 if it is neither null nor 0, otherwise ARG2\n  ARG1 & ARG2    \
+                     ARG1 if neither argument is null or 0, otherwise 0\n  ARG1 < ARG2    \
+                     ARG1 is less than ARG2, also <=, =, ==, !=, >= and >\n  ARG1 + ARG2    \
+                     arithmetic sum of ARG1 and ARG2, also -\n  ARG1 * ARG2    arithmetic \
+                     product of ARG1 and ARG2, also / and %\n  STRING : REGEX anchored match of \
+                     the basic regular expression REGEX\n  match STRING REGEX    same as \
+                     STRING : REGEX\n  substr STRING POS LENGTH    substring of STRING, POS \
+                     counted from 1\n  index STRING CHARS    index in STRING where any CHARS is \
+                     found, or 0\n  length STRING  length of STRING\n  + TOKEN        \
+                     interpret TOKEN as a string, even if it is a keyword\n  ( EXPRESSION )    \
+                     value of EXPRESSION\n\nThe comparisons are numeric if both ARGs are \
+                     integers, otherwise lexicographical. The match returns the string matched \
+                     by the first \\( \\) group of REGEX, or the number of matched characters if \
+                     there is no group.\n\nThe exit status is 0 if EXPRESSION is neither null \
+                     nor 0, 1 if it is, 2 if it is invalid and 3 if an error occurred.",
+                )
+                .multiple(true),
+        )
+}
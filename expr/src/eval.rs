@@ -0,0 +1,462 @@
+//! The parser and evaluator of the expressions.
+use std::{cmp::Ordering, convert::TryFrom, fmt};
+
+use coreutils_core::bre::{self, BreError};
+
+/// The value of a expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(i128),
+    Str(String),
+}
+
+impl Value {
+    /// Whether the value is null, that is zero or the empty string.
+    pub fn is_null(&self) -> bool {
+        match self {
+            Value::Integer(n) => *n == 0,
+            Value::Str(s) => {
+                let digits = s.strip_prefix('-').unwrap_or(s);
+                s.is_empty() || !digits.is_empty() && digits.bytes().all(|b| b == b'0')
+            },
+        }
+    }
+
+    fn to_integer(&self) -> Result<i128, ExprError> {
+        match self {
+            Value::Integer(n) => Ok(*n),
+            Value::Str(s) if looks_like_integer(s) => s.parse().map_err(|_| ExprError::TooLarge),
+            Value::Str(_) => Err(ExprError::NonInteger),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Str(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Whether `s` is a integer, with a optional `-` and decimal digits.
+fn looks_like_integer(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprError {
+    MissingOperand,
+    UnexpectedArgument(String),
+    UnexpectedCloseParen,
+    MissingArgument(String),
+    ExpectingCloseParenAfter(String),
+    ExpectingCloseParenInstead(String),
+    NonInteger,
+    DivisionByZero,
+    TooLarge,
+    Overflow,
+    Regex(BreError),
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExprError::MissingOperand => write!(f, "missing operand"),
+            ExprError::UnexpectedArgument(arg) => {
+                write!(f, "syntax error: unexpected argument '{}'", arg)
+            },
+            ExprError::UnexpectedCloseParen => write!(f, "syntax error: unexpected ')'"),
+            ExprError::MissingArgument(arg) => {
+                write!(f, "syntax error: missing argument after '{}'", arg)
+            },
+            ExprError::ExpectingCloseParenAfter(arg) => {
+                write!(f, "syntax error: expecting ')' after '{}'", arg)
+            },
+            ExprError::ExpectingCloseParenInstead(arg) => {
+                write!(f, "syntax error: expecting ')' instead of '{}'", arg)
+            },
+            ExprError::NonInteger => write!(f, "non-integer argument"),
+            ExprError::DivisionByZero => write!(f, "division by zero"),
+            ExprError::TooLarge => write!(f, "integer is too large"),
+            ExprError::Overflow => write!(f, "arithmetic result out of range"),
+            ExprError::Regex(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// Evaluate the expression of the `args`.
+pub fn evaluate(args: &[String]) -> Result<Value, ExprError> {
+    if args.is_empty() {
+        return Err(ExprError::MissingOperand);
+    }
+
+    let mut parser = Parser { args, pos: 0 };
+    let value = parser.or(true)?;
+
+    match args.get(parser.pos) {
+        Some(arg) => Err(ExprError::UnexpectedArgument(arg.clone())),
+        None => Ok(value),
+    }
+}
+
+/// A recursive descent parser, with a method for each precedence level.
+///
+/// The `evaluate` parameter is false in the operands that are not evaluated, like the
+/// right side of `|` when the left one is not null, so they can't fail.
+struct Parser<'a> {
+    args: &'a [String],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    /// Advance if the next argument is `token`.
+    fn next_is(&mut self, token: &str) -> bool {
+        if self.args.get(self.pos).map(String::as_str) == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Advance if the next argument is one of the `tokens`, returning it.
+    fn next_of(&mut self, tokens: &[&'static str]) -> Option<&'static str> {
+        let arg = self.args.get(self.pos)?;
+        let token = tokens.iter().find(|&&token| token == arg)?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    /// `ARG1 | ARG2`
+    fn or(&mut self, evaluate: bool) -> Result<Value, ExprError> {
+        let mut left = self.and(evaluate)?;
+
+        while self.next_is("|") {
+            let right = self.and(evaluate && left.is_null())?;
+            if left.is_null() {
+                left = if right.is_null() { Value::Integer(0) } else { right };
+            }
+        }
+        Ok(left)
+    }
+
+    /// `ARG1 & ARG2`
+    fn and(&mut self, evaluate: bool) -> Result<Value, ExprError> {
+        let mut left = self.comparison(evaluate)?;
+
+        while self.next_is("&") {
+            let right = self.comparison(evaluate && !left.is_null())?;
+            if left.is_null() || right.is_null() {
+                left = Value::Integer(0);
+            }
+        }
+        Ok(left)
+    }
+
+    /// `ARG1 < ARG2` and the other comparisons
+    fn comparison(&mut self, evaluate: bool) -> Result<Value, ExprError> {
+        let mut left = self.sum(evaluate)?;
+
+        while let Some(op) = self.next_of(&["<", "<=", "=", "==", "!=", ">=", ">"]) {
+            let right = self.sum(evaluate)?;
+            if !evaluate {
+                continue;
+            }
+
+            let ordering = compare(&left, &right);
+            let result = match op {
+                "<" => ordering == Ordering::Less,
+                "<=" => ordering != Ordering::Greater,
+                "=" | "==" => ordering == Ordering::Equal,
+                "!=" => ordering != Ordering::Equal,
+                ">=" => ordering != Ordering::Less,
+                _ => ordering == Ordering::Greater,
+            };
+            left = Value::Integer(result as i128);
+        }
+        Ok(left)
+    }
+
+    /// `ARG1 + ARG2` and `ARG1 - ARG2`
+    fn sum(&mut self, evaluate: bool) -> Result<Value, ExprError> {
+        let mut left = self.product(evaluate)?;
+
+        while let Some(op) = self.next_of(&["+", "-"]) {
+            let right = self.product(evaluate)?;
+            if evaluate {
+                let (a, b) = (left.to_integer()?, right.to_integer()?);
+                let result = if op == "+" { a.checked_add(b) } else { a.checked_sub(b) };
+                left = Value::Integer(result.ok_or(ExprError::Overflow)?);
+            }
+        }
+        Ok(left)
+    }
+
+    /// `ARG1 * ARG2`, `ARG1 / ARG2` and `ARG1 % ARG2`
+    fn product(&mut self, evaluate: bool) -> Result<Value, ExprError> {
+        let mut left = self.matches(evaluate)?;
+
+        while let Some(op) = self.next_of(&["*", "/", "%"]) {
+            let right = self.matches(evaluate)?;
+            if evaluate {
+                let (a, b) = (left.to_integer()?, right.to_integer()?);
+                if op != "*" && b == 0 {
+                    return Err(ExprError::DivisionByZero);
+                }
+
+                let result = match op {
+                    "*" => a.checked_mul(b),
+                    "/" => a.checked_div(b),
+                    _ => a.checked_rem(b),
+                };
+                left = Value::Integer(result.ok_or(ExprError::Overflow)?);
+            }
+        }
+        Ok(left)
+    }
+
+    /// `STRING : REGEX`
+    fn matches(&mut self, evaluate: bool) -> Result<Value, ExprError> {
+        let mut left = self.keyword(evaluate)?;
+
+        while self.next_is(":") {
+            let right = self.keyword(evaluate)?;
+            if evaluate {
+                left = match_regex(&left.to_string(), &right.to_string())?;
+            }
+        }
+        Ok(left)
+    }
+
+    /// `match`, `substr`, `index`, `length` and `+ TOKEN`
+    fn keyword(&mut self, evaluate: bool) -> Result<Value, ExprError> {
+        if self.next_is("+") {
+            return match self.args.get(self.pos) {
+                Some(token) => {
+                    self.pos += 1;
+                    Ok(Value::Str(token.clone()))
+                },
+                None => Err(ExprError::MissingArgument("+".to_string())),
+            };
+        }
+
+        if self.next_is("length") {
+            let string = self.keyword(evaluate)?.to_string();
+            return Ok(Value::Integer(string.chars().count() as i128));
+        }
+
+        if self.next_is("match") {
+            let string = self.keyword(evaluate)?;
+            let regex = self.keyword(evaluate)?;
+            if !evaluate {
+                return Ok(Value::Integer(0));
+            }
+            return match_regex(&string.to_string(), &regex.to_string());
+        }
+
+        if self.next_is("index") {
+            let string = self.keyword(evaluate)?.to_string();
+            let chars = self.keyword(evaluate)?.to_string();
+
+            let index = string.chars().position(|c| chars.contains(c)).map_or(0, |i| i + 1);
+            return Ok(Value::Integer(index as i128));
+        }
+
+        if self.next_is("substr") {
+            let string = self.keyword(evaluate)?.to_string();
+            let pos = self.keyword(evaluate)?;
+            let len = self.keyword(evaluate)?;
+
+            // Invalid positions and lengths give the empty string
+            let substring = match (pos.to_integer(), len.to_integer()) {
+                (Ok(pos), Ok(len)) if pos > 0 && len > 0 => {
+                    let skip = usize::try_from(pos - 1).unwrap_or(usize::MAX);
+                    let take = usize::try_from(len).unwrap_or(usize::MAX);
+                    string.chars().skip(skip).take(take).collect()
+                },
+                _ => String::new(),
+            };
+            return Ok(Value::Str(substring));
+        }
+
+        self.primary(evaluate)
+    }
+
+    /// `( EXPRESSION )` and the string operands
+    fn primary(&mut self, evaluate: bool) -> Result<Value, ExprError> {
+        let arg = match self.args.get(self.pos) {
+            Some(arg) => arg,
+            None => {
+                let previous = self.args[self.pos - 1].clone();
+                return Err(ExprError::MissingArgument(previous));
+            },
+        };
+
+        if self.next_is("(") {
+            let value = self.or(evaluate)?;
+
+            return match self.args.get(self.pos) {
+                Some(arg) if arg == ")" => {
+                    self.pos += 1;
+                    Ok(value)
+                },
+                Some(arg) => Err(ExprError::ExpectingCloseParenInstead(arg.clone())),
+                None => {
+                    let previous = self.args[self.pos - 1].clone();
+                    Err(ExprError::ExpectingCloseParenAfter(previous))
+                },
+            };
+        }
+
+        if arg == ")" {
+            return Err(ExprError::UnexpectedCloseParen);
+        }
+
+        self.pos += 1;
+        Ok(Value::Str(arg.clone()))
+    }
+}
+
+/// Compare two values as integers if both are, otherwise as strings.
+fn compare(left: &Value, right: &Value) -> Ordering {
+    let (left, right) = (left.to_string(), right.to_string());
+    if !looks_like_integer(&left) || !looks_like_integer(&right) {
+        return left.cmp(&right);
+    }
+
+    // Compare the digits, so integers of any size can be compared
+    let split = |s: &str| -> (bool, String) {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        let digits = digits.trim_start_matches('0');
+        (negative && !digits.is_empty(), digits.to_string())
+    };
+    let (left_negative, left) = split(&left);
+    let (right_negative, right) = split(&right);
+
+    let magnitude = left.len().cmp(&right.len()).then_with(|| left.cmp(&right));
+    match (left_negative, right_negative) {
+        (false, false) => magnitude,
+        (true, true) => magnitude.reverse(),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+    }
+}
+
+/// Match `string` against the anchored `pattern`. Returns the first group if `pattern`
+/// has any, otherwise the number of matched characters.
+fn match_regex(string: &str, pattern: &str) -> Result<Value, ExprError> {
+    let regex = bre::compile(pattern).map_err(ExprError::Regex)?;
+
+    let captures = regex.captures(string);
+    if regex.captures_len() > 1 {
+        let group = captures.and_then(|captures| captures.get(1));
+        Ok(Value::Str(group.map_or_else(String::new, |group| group.as_str().to_string())))
+    } else {
+        let len = captures.map_or(0, |captures| captures[0].chars().count());
+        Ok(Value::Integer(len as i128))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(args: &[&str]) -> Result<Value, ExprError> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        evaluate(&args)
+    }
+
+    fn integer(n: i128) -> Result<Value, ExprError> {
+        Ok(Value::Integer(n))
+    }
+
+    fn string(s: &str) -> Result<Value, ExprError> {
+        Ok(Value::Str(s.to_string()))
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(eval(&["1", "+", "2", "*", "3"]), integer(7));
+        assert_eq!(eval(&["(", "1", "+", "2", ")", "*", "3"]), integer(9));
+        assert_eq!(eval(&["-5", "/", "3"]), integer(-1));
+        assert_eq!(eval(&["-5", "%", "3"]), integer(-2));
+        assert_eq!(eval(&["10", "-", "2", "-", "3"]), integer(5));
+        assert_eq!(eval(&["1", "/", "0"]), Err(ExprError::DivisionByZero));
+        assert_eq!(eval(&["a", "+", "1"]), Err(ExprError::NonInteger));
+        assert_eq!(eval(&["+1", "+", "1"]), Err(ExprError::NonInteger));
+        assert_eq!(
+            eval(&["170141183460469231731687303715884105727", "+", "1"]),
+            Err(ExprError::Overflow)
+        );
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(eval(&["2", ">", "10"]), integer(0));
+        assert_eq!(eval(&["2", ">", "10a"]), integer(1));
+        assert_eq!(eval(&["1", "=", "01"]), integer(1));
+        assert_eq!(eval(&["a", "!=", "b"]), integer(1));
+        assert_eq!(eval(&["-3", "<", "-02"]), integer(1));
+        assert_eq!(eval(&["99999999999999999999999999999999999999999", ">", "1"]), integer(1));
+    }
+
+    #[test]
+    fn logical() {
+        assert_eq!(eval(&["0", "|", "", "|", "b"]), string("b"));
+        assert_eq!(eval(&["0", "|", ""]), integer(0));
+        assert_eq!(eval(&["a", "|", "1", "/", "0"]), string("a"));
+        assert_eq!(eval(&["0", "&", "1", "/", "0"]), integer(0));
+        assert_eq!(eval(&["a", "&", "b"]), string("a"));
+        assert_eq!(eval(&["a", "&", "-0"]), integer(0));
+    }
+
+    #[test]
+    fn regex_match() {
+        assert_eq!(eval(&["abcd", ":", "a.c"]), integer(3));
+        assert_eq!(eval(&["abcd", ":", "b"]), integer(0));
+        assert_eq!(eval(&["abcd", ":", "a\\(.*\\)d"]), string("bc"));
+        assert_eq!(eval(&["abcd", ":", "x\\(.*\\)"]), string(""));
+        assert_eq!(eval(&["1", ":", "1", "+", "1"]), integer(2));
+        assert_eq!(eval(&["match", "abc", "a."]), integer(2));
+        assert_eq!(eval(&["a", ":", "\\("]), Err(ExprError::Regex(BreError::UnmatchedParen)));
+    }
+
+    #[test]
+    fn keywords() {
+        assert_eq!(eval(&["length", "héllo"]), integer(5));
+        assert_eq!(eval(&["index", "hello", "lo"]), integer(3));
+        assert_eq!(eval(&["substr", "hello", "2", "3"]), string("ell"));
+        assert_eq!(eval(&["substr", "hello", "0", "1"]), string(""));
+        assert_eq!(eval(&["+", "length"]), string("length"));
+        assert_eq!(eval(&["length", "+", "1"]), integer(1));
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_eq!(eval(&[]), Err(ExprError::MissingOperand));
+        assert_eq!(eval(&["1", "2"]), Err(ExprError::UnexpectedArgument("2".to_string())));
+        assert_eq!(eval(&["1", "+"]), Err(ExprError::MissingArgument("+".to_string())));
+        assert_eq!(eval(&[")"]), Err(ExprError::UnexpectedCloseParen));
+        assert_eq!(eval(&["(", "1"]), Err(ExprError::ExpectingCloseParenAfter("1".to_string())));
+        assert_eq!(
+            eval(&["(", "1", "2"]),
+            Err(ExprError::ExpectingCloseParenInstead("2".to_string()))
+        );
+        assert_eq!(eval(&["length"]), Err(ExprError::MissingArgument("length".to_string())));
+    }
+
+    #[test]
+    fn null_values() {
+        assert!(Value::Str("".to_string()).is_null());
+        assert!(Value::Str("-00".to_string()).is_null());
+        assert!(!Value::Str("-".to_string()).is_null());
+        assert!(!Value::Str("0a".to_string()).is_null());
+        assert!(Value::Integer(0).is_null());
+    }
+}
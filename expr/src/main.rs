@@ -0,0 +1,38 @@
+use std::{
+    io::{self, Write},
+    process,
+};
+
+mod cli;
+mod eval;
+
+use eval::ExprError;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let args: Vec<String> = matches
+        .values_of("EXPRESSION")
+        .map(|values| values.map(String::from).collect())
+        .unwrap_or_default();
+
+    let value = match eval::evaluate(&args) {
+        Ok(value) => value,
+        Err(err) => {
+            eprintln!("expr: {}", err);
+            if err == ExprError::MissingOperand {
+                eprintln!("Try 'expr --help' for more information.");
+            }
+            process::exit(2);
+        },
+    };
+
+    if let Err(err) = writeln!(io::stdout(), "{}", value) {
+        eprintln!("expr: write error: {}", err);
+        process::exit(3);
+    }
+
+    if value.is_null() {
+        process::exit(1);
+    }
+}
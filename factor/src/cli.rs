@@ -0,0 +1,23 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("NUMBER")
+                .help("Integers to factor.")
+                .long_help(
+                    "Integers to factor.\n\nWith no NUMBER, read them from standard input, \
+                     separated by whitespace.",
+                )
+                .multiple(true),
+        )
+}
@@ -0,0 +1,375 @@
+//! The primality test and the factorization of the numbers.
+use crate::montgomery::Montgomery;
+
+/// The primes used for the trial division, before the slower methods.
+const SMALL_PRIMES: [u16; 54] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251,
+];
+
+/// The bases of the Miller-Rabin test, that are enough to tell every number below
+/// [`MILLER_RABIN_LIMIT`] apart.
+const MILLER_RABIN_BASES: [u128; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+const MILLER_RABIN_LIMIT: u128 = 3_317_044_064_679_887_385_961_981;
+
+/// The prime factors of `n`, in ascending order.
+pub fn factor(mut n: u128) -> Vec<u128> {
+    let mut factors = Vec::new();
+
+    for &prime in SMALL_PRIMES.iter() {
+        let prime = u128::from(prime);
+        if prime * prime > n {
+            break;
+        }
+        while n / prime * prime == n {
+            factors.push(prime);
+            n /= prime;
+        }
+    }
+
+    // The remaining factors are greater than the last small prime
+    let last = u128::from(SMALL_PRIMES[SMALL_PRIMES.len() - 1]);
+    if n > 1 && n < last * last {
+        factors.push(n);
+    } else if n > 1 {
+        factor_large(n, &mut factors);
+    }
+
+    factors.sort_unstable();
+    factors
+}
+
+/// Add the prime factors of `n`, that has no small factors, to `factors`.
+fn factor_large(n: u128, factors: &mut Vec<u128>) {
+    if is_prime(n) {
+        factors.push(n);
+        return;
+    }
+
+    let divisor = match isqrt(n) {
+        root if root * root == n => root,
+        _ => pollard_rho(n),
+    };
+    factor_large(divisor, factors);
+    factor_large(n / divisor, factors);
+}
+
+/// Whether `n` is prime.
+///
+/// The Miller-Rabin test with fixed bases is exact for the numbers up to
+/// [`MILLER_RABIN_LIMIT`], the greater ones are also tested with the strong Lucas test
+/// (the Baillie-PSW test), that has no known counterexample.
+pub fn is_prime(n: u128) -> bool {
+    if n < 2 {
+        return false;
+    }
+    if let Some(&prime) =
+        SMALL_PRIMES.iter().find(|&&prime| n / u128::from(prime) * u128::from(prime) == n)
+    {
+        return n == u128::from(prime);
+    }
+
+    let mont = Montgomery::new(n);
+    if !MILLER_RABIN_BASES.iter().all(|&base| miller_rabin(&mont, base)) {
+        return false;
+    }
+    n < MILLER_RABIN_LIMIT || strong_lucas(&mont)
+}
+
+/// The strong probable prime test to `base`.
+fn miller_rabin(mont: &Montgomery, base: u128) -> bool {
+    let n = mont.modulus();
+    let shift = (n - 1).trailing_zeros();
+    let odd = (n - 1) >> shift;
+
+    let minus_one = mont.sub(0, mont.one());
+    let mut x = mont.pow(mont.convert(base % n), odd);
+    if x == mont.one() || x == minus_one {
+        return true;
+    }
+
+    for _ in 1..shift {
+        x = mont.mul(x, x);
+        if x == minus_one {
+            return true;
+        }
+    }
+    false
+}
+
+/// The strong Lucas probable prime test, with the parameters selected by the Selfridge's
+/// method. `n` must not be a perfect square.
+fn strong_lucas(mont: &Montgomery) -> bool {
+    let n = mont.modulus();
+    // A perfect square has no D to be found
+    if isqrt(n).pow(2) == n {
+        return false;
+    }
+
+    // The first D of 5, -7, 9, -11, ... with the Jacobi symbol (D/n) = -1
+    let mut d: i64 = 5;
+    loop {
+        match jacobi(d, n) {
+            -1 => break,
+            0 if magnitude(d) != n => return false,
+            _ => {},
+        }
+        d = if d > 0 { -(d + 2) } else { -d + 2 };
+    }
+
+    // P = 1 and Q = (1 - D) / 4
+    let to_mont = |value: i64| {
+        let abs = mont.convert(magnitude(value) % n);
+        if value < 0 {
+            mont.sub(0, abs)
+        } else {
+            abs
+        }
+    };
+    let d_mont = to_mont(d);
+    let q = to_mont((1 - d) / 4);
+
+    let shift = (n + 1).trailing_zeros();
+    let odd = (n + 1) >> shift;
+
+    // Compute U(odd), V(odd) and Q^odd from the highest bit
+    let mut u = mont.one();
+    let mut v = mont.one();
+    let mut qk = q;
+    for bit in (0..127 - odd.leading_zeros()).rev() {
+        u = mont.mul(u, v);
+        v = mont.sub(mont.mul(v, v), mont.add(qk, qk));
+        qk = mont.mul(qk, qk);
+
+        if odd >> bit & 1 == 1 {
+            let next_u = mont.half(mont.add(u, v));
+            v = mont.half(mont.add(mont.mul(d_mont, u), v));
+            u = next_u;
+            qk = mont.mul(qk, q);
+        }
+    }
+
+    if u == 0 || v == 0 {
+        return true;
+    }
+    for _ in 1..shift {
+        v = mont.sub(mont.mul(v, v), mont.add(qk, qk));
+        qk = mont.mul(qk, qk);
+        if v == 0 {
+            return true;
+        }
+    }
+    false
+}
+
+/// The Jacobi symbol (a/n), for the odd `n`.
+fn jacobi(a: i64, n: u128) -> i32 {
+    let mut a = if a < 0 {
+        let abs = magnitude(a) % n;
+        if abs == 0 {
+            0
+        } else {
+            n - abs
+        }
+    } else {
+        a as u128 % n
+    };
+    let mut n = n;
+    let mut result = 1;
+
+    while a != 0 {
+        while a % 2 == 0 {
+            a /= 2;
+            if n % 8 == 3 || n % 8 == 5 {
+                result = -result;
+            }
+        }
+        std::mem::swap(&mut a, &mut n);
+        if a % 4 == 3 && n % 4 == 3 {
+            result = -result;
+        }
+        a %= n;
+    }
+
+    if n == 1 {
+        result
+    } else {
+        0
+    }
+}
+
+/// The absolute value of `value`.
+fn magnitude(value: i64) -> u128 {
+    if value < 0 {
+        (-i128::from(value)) as u128
+    } else {
+        value as u128
+    }
+}
+
+/// A non trivial divisor of the odd composite `n`, with the Brent's variant of the
+/// Pollard's rho method.
+fn pollard_rho(n: u128) -> u128 {
+    // The products of this many differences are taken before each gcd
+    const BATCH: u64 = 128;
+
+    let mont = Montgomery::new(n);
+    let one = mont.one();
+
+    let mut c = one;
+    loop {
+        let f = |x: u128| mont.add(mont.mul(x, x), c);
+
+        let mut y = mont.add(one, one);
+        let mut x = y;
+        let mut saved = y;
+        let mut product = one;
+        let mut divisor = 1;
+        let mut length = 1;
+
+        while divisor == 1 {
+            x = y;
+            for _ in 0..length {
+                y = f(y);
+            }
+
+            let mut done = 0;
+            while done < length && divisor == 1 {
+                saved = y;
+                for _ in 0..BATCH.min(length - done) {
+                    y = f(y);
+                    product = mont.mul(product, mont.sub(x, y));
+                }
+                divisor = gcd(product, n);
+                done += BATCH;
+            }
+            length *= 2;
+        }
+
+        // The batch went past the divisor, so go back and take the gcd at each step
+        if divisor == n {
+            loop {
+                saved = f(saved);
+                divisor = gcd(mont.sub(x, saved), n);
+                if divisor != 1 {
+                    break;
+                }
+            }
+        }
+
+        if divisor != n {
+            return divisor;
+        }
+        // The cycle closed without a divisor, try with another polynomial
+        c = mont.add(c, one);
+    }
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    if a == 0 {
+        return b;
+    }
+    if b == 0 {
+        return a;
+    }
+
+    let shift = (a | b).trailing_zeros();
+    a >>= a.trailing_zeros();
+    loop {
+        b >>= b.trailing_zeros();
+        if a > b {
+            std::mem::swap(&mut a, &mut b);
+        }
+        b -= a;
+        if b == 0 {
+            return a << shift;
+        }
+    }
+}
+
+/// The integer square root of `n`.
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+
+    // Start from a estimation that is not less than the root
+    let mut x = 1u128 << ((129 - n.leading_zeros()) / 2);
+    loop {
+        let next = (x + n / x) / 2;
+        if next >= x {
+            return x;
+        }
+        x = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factor_small() {
+        assert_eq!(factor(0), Vec::<u128>::new());
+        assert_eq!(factor(1), Vec::<u128>::new());
+        assert_eq!(factor(2), vec![2]);
+        assert_eq!(factor(12), vec![2, 2, 3]);
+        assert_eq!(factor(65_521), vec![65_521]);
+        assert_eq!(factor(1_000_000), vec![2, 2, 2, 2, 2, 2, 5, 5, 5, 5, 5, 5]);
+    }
+
+    #[test]
+    fn factor_large() {
+        assert_eq!(factor(u64::MAX as u128), vec![3, 5, 17, 257, 641, 65_537, 6_700_417]);
+        assert_eq!(factor(18_446_744_030_759_878_681), vec![4_294_967_291, 4_294_967_291]);
+        assert_eq!(factor(18_446_744_073_709_551_617), vec![274_177, 67_280_421_310_721]);
+        assert_eq!(factor(u128::MAX), vec![
+            3,
+            5,
+            17,
+            257,
+            641,
+            65_537,
+            274_177,
+            6_700_417,
+            67_280_421_310_721
+        ]);
+        assert_eq!(factor(340_282_366_920_938_463_463_374_607_431_768_211_297), vec![
+            340_282_366_920_938_463_463_374_607_431_768_211_297
+        ]);
+    }
+
+    #[test]
+    fn primality() {
+        let primes: Vec<u128> = (0..1000).filter(|&n| is_prime(n)).collect();
+        assert_eq!(primes.len(), 168);
+        assert_eq!(primes[..5], [2, 3, 5, 7, 11]);
+
+        // Strong pseudoprimes to several bases
+        assert!(!is_prime(3_215_031_751));
+        assert!(!is_prime(3_825_123_056_546_413_051));
+        assert!(!is_prime(318_665_857_834_031_151_167_461));
+        assert!(is_prime(u64::MAX as u128 - 58));
+        assert!(is_prime((1 << 127) - 1));
+        assert!(!is_prime(((1 << 61) - 1) * ((1 << 61) - 1)));
+    }
+
+    #[test]
+    fn jacobi_symbols() {
+        assert_eq!(jacobi(5, 21), 1);
+        assert_eq!(jacobi(-7, 21), 0);
+        assert_eq!(jacobi(2, 7), 1);
+        assert_eq!(jacobi(-1, 7), -1);
+        assert_eq!(jacobi(1001, 9907), -1);
+    }
+
+    #[test]
+    fn square_roots() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    process,
+};
+
+mod cli;
+mod factor;
+mod montgomery;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let stdout = io::stdout();
+    let mut output = BufWriter::new(stdout.lock());
+
+    let result = match matches.values_of("NUMBER") {
+        Some(numbers) => factor_numbers(numbers, &mut output),
+        None => factor_input(&mut output),
+    };
+
+    match result.and_then(|valid| output.flush().map(|_| valid)) {
+        Ok(true) => {},
+        Ok(false) => process::exit(1),
+        // The output was closed, like `factor < numbers | head`
+        Err(ref err) if err.kind() == io::ErrorKind::BrokenPipe => {},
+        Err(err) => {
+            eprintln!("factor: {}", err);
+            process::exit(1);
+        },
+    }
+}
+
+/// Factor the `numbers` of the operands. Returns whether all of them were valid.
+fn factor_numbers<'a>(
+    numbers: impl Iterator<Item = &'a str>, output: &mut impl Write,
+) -> io::Result<bool> {
+    let mut valid = true;
+    for number in numbers {
+        valid &= factor_number(number, output)?;
+    }
+    Ok(valid)
+}
+
+/// Factor the numbers of the standard input, separated by whitespace. Returns whether all
+/// of them were valid.
+fn factor_input(output: &mut impl Write) -> io::Result<bool> {
+    let stdin = io::stdin();
+    let mut input = BufReader::new(stdin.lock());
+    let mut line = String::new();
+    let mut valid = true;
+
+    while input.read_line(&mut line)? > 0 {
+        for number in line.split_whitespace() {
+            valid &= factor_number(number, output)?;
+        }
+        line.clear();
+
+        // Show the factors right away when the numbers are typed
+        if input.buffer().is_empty() {
+            output.flush()?;
+        }
+    }
+
+    Ok(valid)
+}
+
+/// Write the prime factors of `number`, or the error if it's not valid. Returns whether
+/// it was valid.
+fn factor_number(number: &str, output: &mut impl Write) -> io::Result<bool> {
+    match parse_number(number) {
+        Ok(n) => {
+            write_factors(n, &factor::factor(n), output)?;
+            Ok(true)
+        },
+        Err(err) => {
+            // Keep the error after the previous factors
+            output.flush()?;
+            eprintln!("factor: {}", err);
+            Ok(false)
+        },
+    }
+}
+
+fn write_factors(n: u128, factors: &[u128], output: &mut impl Write) -> io::Result<()> {
+    write!(output, "{}:", n)?;
+    for factor in factors {
+        write!(output, " {}", factor)?;
+    }
+    writeln!(output)
+}
+
+/// Parse a non negative integer, that may start with whitespace and a `+`.
+fn parse_number(number: &str) -> Result<u128, ParseError> {
+    let digits = number.trim_start();
+    let digits = digits.strip_prefix('+').unwrap_or(digits);
+
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseError::Invalid(number.to_string()));
+    }
+    digits.parse().map_err(|_| ParseError::TooLarge(number.to_string()))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ParseError {
+    Invalid(String),
+    TooLarge(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Invalid(number) => {
+                write!(f, "'{}' is not a valid positive integer", number)
+            },
+            ParseError::TooLarge(number) => write!(f, "'{}' is too large", number),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_numbers() {
+        assert_eq!(parse_number("12"), Ok(12));
+        assert_eq!(parse_number("  +012"), Ok(12));
+        assert_eq!(parse_number("0"), Ok(0));
+        assert_eq!(parse_number("340282366920938463463374607431768211455"), Ok(u128::MAX));
+
+        assert_eq!(parse_number("12 "), Err(ParseError::Invalid("12 ".to_string())));
+        assert_eq!(parse_number("-3"), Err(ParseError::Invalid("-3".to_string())));
+        assert_eq!(parse_number("+"), Err(ParseError::Invalid("+".to_string())));
+        assert_eq!(
+            parse_number("340282366920938463463374607431768211456"),
+            Err(ParseError::TooLarge("340282366920938463463374607431768211456".to_string()))
+        );
+    }
+
+    #[test]
+    fn factor_operands() {
+        let mut output = Vec::new();
+        assert!(factor_number("1", &mut output).unwrap());
+        assert!(factor_number("+360", &mut output).unwrap());
+        assert!(!factor_number("x", &mut output).unwrap());
+        assert!(factor_number("18446744073709551617", &mut output).unwrap());
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "1:\n360: 2 2 2 3 3 5\n18446744073709551617: 274177 67280421310721\n"
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Modular arithmetic in the Montgomery form, to multiply modulo an odd number of up to
+//! 128 bits without dividing.
+
+/// The arithmetic modulo an odd `n`, where each number `a` is represented by `a * 2^128
+/// mod n`.
+#[derive(Debug, Clone, Copy)]
+pub struct Montgomery {
+    n: u128,
+    /// `-n^-1 mod 2^128`
+    n_neg_inv: u128,
+    /// `2^256 mod n`, to convert the numbers to the Montgomery form
+    r2: u128,
+    /// `1` in the Montgomery form
+    one: u128,
+}
+
+impl Montgomery {
+    /// Creates the arithmetic modulo `n`, that must be odd and greater than 1.
+    pub fn new(n: u128) -> Self {
+        debug_assert!(n > 1 && n % 2 == 1);
+
+        // Newton's iteration doubles the correct low bits, and n is its own inverse modulo 8
+        let mut inv = n;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u128.wrapping_sub(n.wrapping_mul(inv)));
+        }
+
+        let one = (u128::MAX % n + 1) % n;
+        let mut r2 = one;
+        for _ in 0..128 {
+            r2 = add_mod(r2, r2, n);
+        }
+
+        Montgomery { n, n_neg_inv: inv.wrapping_neg(), r2, one }
+    }
+
+    pub fn modulus(&self) -> u128 {
+        self.n
+    }
+
+    pub fn one(&self) -> u128 {
+        self.one
+    }
+
+    /// Convert `a`, less than the modulus, to the Montgomery form.
+    pub fn convert(&self, a: u128) -> u128 {
+        self.mul(a, self.r2)
+    }
+
+    /// Convert `a` back from the Montgomery form.
+    #[cfg(test)]
+    pub fn convert_back(&self, a: u128) -> u128 {
+        self.reduce(0, a)
+    }
+
+    pub fn add(&self, a: u128, b: u128) -> u128 {
+        add_mod(a, b, self.n)
+    }
+
+    pub fn sub(&self, a: u128, b: u128) -> u128 {
+        if a >= b {
+            a - b
+        } else {
+            a.wrapping_sub(b).wrapping_add(self.n)
+        }
+    }
+
+    /// Half of `a`, that is `a * 2^-1`.
+    pub fn half(&self, a: u128) -> u128 {
+        if a & 1 == 0 {
+            a / 2
+        } else {
+            a / 2 + self.n / 2 + 1
+        }
+    }
+
+    pub fn mul(&self, a: u128, b: u128) -> u128 {
+        let (high, low) = mul_wide(a, b);
+        self.reduce(high, low)
+    }
+
+    pub fn pow(&self, mut base: u128, mut exp: u128) -> u128 {
+        let mut result = self.one;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = self.mul(result, base);
+            }
+            base = self.mul(base, base);
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// Montgomery reduction of `high * 2^128 + low`, that must be less than `n * 2^128`.
+    fn reduce(&self, high: u128, low: u128) -> u128 {
+        let m = low.wrapping_mul(self.n_neg_inv);
+        let (mn_high, mn_low) = mul_wide(m, self.n);
+
+        // The low half of the sum is zero, it only carries when low isn't
+        let carry = (low != 0 || mn_low != 0) as u128;
+        let (sum, overflow1) = high.overflowing_add(mn_high);
+        let (sum, overflow2) = sum.overflowing_add(carry);
+
+        if overflow1 || overflow2 || sum >= self.n {
+            sum.wrapping_sub(self.n)
+        } else {
+            sum
+        }
+    }
+}
+
+fn add_mod(a: u128, b: u128, n: u128) -> u128 {
+    let (sum, overflow) = a.overflowing_add(b);
+    if overflow || sum >= n {
+        sum.wrapping_sub(n)
+    } else {
+        sum
+    }
+}
+
+/// The full product of `a` and `b`, as the high and low halves.
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+
+    let (a_high, a_low) = (a >> 64, a & MASK);
+    let (b_high, b_low) = (b >> 64, b & MASK);
+
+    let low = a_low * b_low;
+    let middle1 = a_high * b_low;
+    let middle2 = a_low * b_high;
+    let high = a_high * b_high;
+
+    let (middle, carry) = middle1.overflowing_add(middle2);
+    let high = high + ((carry as u128) << 64) + (middle >> 64);
+    let (low, carry) = low.overflowing_add(middle << 64);
+
+    (high + carry as u128, low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mul_wide_products() {
+        assert_eq!(mul_wide(3, 5), (0, 15));
+        assert_eq!(mul_wide(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+        assert_eq!(mul_wide(1 << 127, 4), (2, 0));
+    }
+
+    #[test]
+    fn mul_matches_remainder() {
+        for &n in &[3u128, 1_000_000_007, u64::MAX as u128 - 58, u128::MAX, (1 << 127) + 1] {
+            let mont = Montgomery::new(n);
+            for &(a, b) in &[(0u128, 5u128), (1, 1), (2, n - 1), (n - 1, n - 1), (n / 3, n / 7)] {
+                let (a, b) = (a % n, b % n);
+                let product = mont.convert_back(mont.mul(mont.convert(a), mont.convert(b)));
+
+                let expected = if n <= u64::MAX as u128 { a * b % n } else { slow_mul(a, b, n) };
+                assert_eq!(product, expected, "{} * {} mod {}", a, b, n);
+            }
+        }
+    }
+
+    #[test]
+    fn half_and_pow() {
+        let mont = Montgomery::new(101);
+        assert_eq!(mont.convert_back(mont.half(mont.convert(3))), 52);
+        assert_eq!(mont.convert_back(mont.pow(mont.convert(2), 100)), 1);
+        assert_eq!(mont.convert_back(mont.sub(mont.convert(3), mont.convert(5))), 99);
+    }
+
+    fn slow_mul(a: u128, mut b: u128, n: u128) -> u128 {
+        let mut result = 0;
+        let mut a = a;
+        while b > 0 {
+            if b & 1 == 1 {
+                result = add_mod(result, a, n);
+            }
+            a = add_mod(a, a, n);
+            b >>= 1;
+        }
+        result
+    }
+}
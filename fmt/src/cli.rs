@@ -0,0 +1,81 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("File(s) to use, or '-' to use from standard input.")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("crownMargin")
+                .help("Keep the indentation of the first two lines of the paragraphs.")
+                .long_help(
+                    "Keep the indentation of the first two lines of the paragraphs.\n\nThe other \
+                     lines are aligned with the second one.",
+                )
+                .long("crown-margin")
+                .short("c"),
+        )
+        .arg(
+            Arg::with_name("prefix")
+                .help("Reformat only the lines starting with STRING, without it.")
+                .long_help(
+                    "Reformat only the lines starting with STRING, without it.\n\nThe STRING is \
+                     added back to the start of the reformatted lines, and the other lines are \
+                     written unchanged. It is useful for the comments of a program.",
+                )
+                .long("prefix")
+                .short("p")
+                .value_name("STRING")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("splitOnly")
+                .help("Split the long lines, without joining the short ones.")
+                .long("split-only")
+                .short("s"),
+        )
+        .arg(
+            Arg::with_name("taggedParagraph")
+                .help("Indent the first line of the paragraphs differently from the second one.")
+                .long_help(
+                    "Indent the first line of the paragraphs differently from the second \
+                     one.\n\nIt is like --crown-margin, where a paragraph is ended by a line \
+                     indented like its first one.",
+                )
+                .long("tagged-paragraph")
+                .short("t"),
+        )
+        .arg(
+            Arg::with_name("uniformSpacing")
+                .help("Write one space between the words and two after the sentences.")
+                .long("uniform-spacing")
+                .short("u"),
+        )
+        .arg(
+            Arg::with_name("width")
+                .help("Write lines of at most WIDTH characters, instead of 75.")
+                .long("width")
+                .short("w")
+                .value_name("WIDTH")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("goal")
+                .help("Aim for lines of GOAL characters, instead of 93% of the width.")
+                .long("goal")
+                .short("g")
+                .value_name("GOAL")
+                .allow_hyphen_values(true),
+        )
+}
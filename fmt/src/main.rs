@@ -0,0 +1,911 @@
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    fs::File,
+    io::{self, stdin, stdout, BufRead, BufReader, BufWriter, ErrorKind, Write},
+    process,
+};
+
+use clap::ArgMatches;
+
+mod cli;
+
+/// The width of the lines when none is given.
+const WIDTH: usize = 75;
+
+/// The percentage of the width of the lines that their goal is shorter than it.
+const LEEWAY: usize = 7;
+
+/// The distance between the tab stops.
+const TAB_WIDTH: usize = 8;
+
+/// The indentation of the second line of a tagged paragraph that only has one line.
+const DEF_INDENT: usize = 3;
+
+/// The number of bytes of the words of a paragraph that are kept at once.
+const MAX_CHARS: usize = 5000;
+
+/// The number of words of a paragraph that are kept at once.
+const MAX_WORDS: usize = 1000;
+
+/// The cost of breaking the lines of a paragraph at some words, where the lowest is the
+/// one that looks the best.
+type Cost = i64;
+
+const MAX_COST: Cost = Cost::MAX;
+
+/// The costs of GNU, scaled like the square of a number of characters.
+const fn equiv(n: i64) -> Cost {
+    n * n
+}
+
+/// The cost of a line that is `n` characters shorter or longer than the goal.
+const fn short_cost(n: i64) -> Cost {
+    equiv(n * 10)
+}
+
+/// The cost of a line that has a length `n` characters different from the next one.
+const fn ragged_cost(n: i64) -> Cost {
+    short_cost(n) / 2
+}
+
+/// The cost of a line that starts with the last word of a sentence, of length `n`.
+const fn widow_cost(n: i64) -> Cost {
+    equiv(200) / (n + 2)
+}
+
+/// The cost of a line that ends with the first word of a sentence, of length `n`.
+const fn orphan_cost(n: i64) -> Cost {
+    equiv(150) / (n + 2)
+}
+
+const LINE_COST: Cost = equiv(70);
+const SENTENCE_BONUS: Cost = equiv(50);
+const NOBREAK_COST: Cost = equiv(600);
+const PAREN_BONUS: Cost = equiv(40);
+const PUNCT_BONUS: Cost = equiv(40);
+const LINE_CREDIT: Cost = equiv(3);
+
+fn main() {
+    let mut args: Vec<OsString> = env::args_os().collect();
+
+    // Like GNU, the first argument may be the obsolete form -WIDTH
+    let mut obsolete_width = None;
+    if let Some(first) = args.get(1).and_then(|arg| arg.to_str()) {
+        if first.starts_with('-') && first[1..].starts_with(|c: char| c.is_ascii_digit()) {
+            obsolete_width = Some(first[1..].to_string());
+            args.remove(1);
+        }
+    }
+
+    let matches = cli::create_app().get_matches_from(args);
+
+    let options =
+        Options::from_matches(&matches, obsolete_width.as_deref()).unwrap_or_else(|err| {
+            eprintln!("fmt: {}", err);
+            process::exit(1);
+        });
+
+    let files: Vec<&OsStr> = match matches.values_of_os("FILE") {
+        Some(files) => files.collect(),
+        None => vec![OsStr::new("-")],
+    };
+
+    let stdout = stdout();
+    let mut output = BufWriter::new(stdout.lock());
+    let mut success = true;
+
+    for file in files {
+        let name = file.to_string_lossy();
+
+        let result = if file == "-" {
+            fmt(&options, stdin().lock(), &mut output)
+        } else {
+            match File::open(file) {
+                Ok(opened) => fmt(&options, BufReader::new(opened), &mut output),
+                Err(err) => {
+                    eprintln!("fmt: cannot open '{}' for reading: {}", name, err);
+                    success = false;
+                    continue;
+                },
+            }
+        };
+
+        match result {
+            Ok(()) => {},
+            Err(Error::Read(err)) => {
+                eprintln!("fmt: {}: read error: {}", name, err);
+                success = false;
+            },
+            Err(Error::Write(err)) => {
+                eprintln!("fmt: write error: {}", err);
+                process::exit(1);
+            },
+        }
+    }
+
+    if let Err(err) = output.flush() {
+        eprintln!("fmt: write error: {}", err);
+        process::exit(1);
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+#[derive(Debug)]
+enum Error {
+    Read(io::Error),
+    Write(io::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Options {
+    /// Whether the first two lines of the paragraphs keep their indentation.
+    crown: bool,
+    /// Whether the first line of the paragraphs is indented differently from the others.
+    tagged: bool,
+    /// Whether the long lines are only split, without joining the short ones.
+    split: bool,
+    /// Whether the words are separated by one space, and the sentences by two.
+    uniform: bool,
+    /// The prefix of the lines that are reformatted, without its spaces.
+    prefix: Vec<u8>,
+    /// The number of spaces before the prefix.
+    prefix_lead_space: usize,
+    /// The length of the prefix, with its trailing spaces.
+    prefix_full_length: usize,
+    max_width: usize,
+    goal_width: usize,
+}
+
+impl Options {
+    fn from_matches(matches: &ArgMatches, obsolete_width: Option<&str>) -> Result<Self, String> {
+        let width = matches.value_of("width").or(obsolete_width);
+
+        let mut max_width = match width {
+            Some(width) => parse_width(width, MAX_CHARS / 2)?,
+            None => WIDTH,
+        };
+
+        let goal_width = match matches.value_of("goal") {
+            Some(goal) => {
+                let goal_width = parse_width(goal, max_width)?;
+                if width.is_none() {
+                    max_width = goal_width + 10;
+                }
+                goal_width
+            },
+            None => max_width * (2 * (100 - LEEWAY) + 1) / 200,
+        };
+
+        // The spaces before the prefix are only counted, and the ones after it are
+        // optional
+        let prefix = matches.value_of("prefix").unwrap_or("").as_bytes();
+        let prefix_lead_space = prefix.iter().take_while(|&&b| b == b' ').count();
+        let prefix = &prefix[prefix_lead_space..];
+        let trailing_space = prefix.iter().rev().take_while(|&&b| b == b' ').count();
+
+        Ok(Options {
+            crown: matches.is_present("crownMargin"),
+            tagged: matches.is_present("taggedParagraph"),
+            split: matches.is_present("splitOnly"),
+            uniform: matches.is_present("uniformSpacing"),
+            prefix: prefix[..prefix.len() - trailing_space].to_vec(),
+            prefix_lead_space,
+            prefix_full_length: prefix.len(),
+            max_width,
+            goal_width,
+        })
+    }
+}
+
+/// Parse the `width` of the lines, that is at most `max`.
+fn parse_width(width: &str, max: usize) -> Result<usize, String> {
+    let digits = width.trim_start();
+    let digits = digits.strip_prefix('+').unwrap_or(digits);
+
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("invalid width: '{}'", width));
+    }
+
+    match digits.parse::<usize>() {
+        Ok(parsed) if parsed <= max => Ok(parsed),
+        Ok(_) => Err(format!("invalid width: '{}': Numerical result out of range", width)),
+        Err(_) => Err(format!("invalid width: '{}': Value too large for defined data type", width)),
+    }
+}
+
+/// Reformat the paragraphs of the `input` and write them to the `output`.
+fn fmt<R: BufRead, W: Write>(options: &Options, input: R, output: &mut W) -> Result<(), Error> {
+    let mut fmt = Fmt::new(options, input, output);
+    fmt.run().map_err(Error::Write)?;
+
+    match fmt.error.take() {
+        Some(err) => Err(Error::Read(err)),
+        None => Ok(()),
+    }
+}
+
+/// Whether the `byte` ends a word.
+fn is_space(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n' | b'\x0b' | b'\x0c' | b'\r')
+}
+
+/// A word of a paragraph.
+#[derive(Debug, Clone, Copy, Default)]
+struct Word {
+    /// The start of the word in the text of the paragraph.
+    start: usize,
+    length: usize,
+    /// The number of spaces after the word.
+    space: usize,
+    /// Whether the word starts with a opening parenthesis or quote.
+    paren: bool,
+    /// Whether the word ends with a period, a question mark or a exclamation mark,
+    /// before the closing parentheses and quotes.
+    period: bool,
+    /// Whether the word ends with a punctuation character.
+    punct: bool,
+    /// Whether the word is the last one of a sentence.
+    last: bool,
+    /// The length of the line that starts with the word.
+    line_length: usize,
+    /// The cost of the paragraph from the word to its end.
+    best_cost: Cost,
+    /// The word that starts line after the one that starts with this word.
+    next_break: usize,
+}
+
+/// The reformatting of the paragraphs of a file, like GNU does.
+struct Fmt<'a, R, W> {
+    options: &'a Options,
+    input: R,
+    output: &'a mut W,
+    /// The error of the input, that ends it.
+    error: Option<io::Error>,
+    /// The text of the words of the paragraph.
+    text: Vec<u8>,
+    words: Vec<Word>,
+    /// Whether the input has tabs, where the indentation is written with tabs too.
+    tabs: bool,
+    /// The indentation of the prefix of the paragraph.
+    prefix_indent: usize,
+    /// The indentation of the first line of the paragraph.
+    first_indent: usize,
+    /// The indentation of the other lines of the paragraph.
+    other_indent: usize,
+    /// The first character after the prefix of the next line.
+    next_char: Option<u8>,
+    /// The indentation of the prefix of the next line.
+    next_prefix_indent: usize,
+    /// The length of the last line written of the paragraph.
+    last_line_length: usize,
+    /// The column of the input.
+    in_column: usize,
+    /// The column of the output.
+    out_column: usize,
+}
+
+impl<'a, R: BufRead, W: Write> Fmt<'a, R, W> {
+    fn new(options: &'a Options, input: R, output: &'a mut W) -> Self {
+        Fmt {
+            options,
+            input,
+            output,
+            error: None,
+            text: Vec::with_capacity(MAX_CHARS),
+            words: Vec::with_capacity(MAX_WORDS),
+            tabs: false,
+            prefix_indent: 0,
+            first_indent: 0,
+            other_indent: 0,
+            next_char: None,
+            next_prefix_indent: 0,
+            last_line_length: 0,
+            in_column: 0,
+            out_column: 0,
+        }
+    }
+
+    fn run(&mut self) -> io::Result<()> {
+        self.next_char = self.get_prefix();
+
+        while self.get_paragraph()? {
+            self.fmt_paragraph();
+            self.put_paragraph(self.words.len())?;
+        }
+
+        Ok(())
+    }
+
+    /// Read the next character of the input, or `None` at its end.
+    fn getc(&mut self) -> Option<u8> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        loop {
+            match self.input.fill_buf() {
+                Ok([]) => return None,
+                Ok(buffer) => {
+                    let c = buffer[0];
+                    self.input.consume(1);
+                    return Some(c);
+                },
+                Err(err) if err.kind() == ErrorKind::Interrupted => {},
+                Err(err) => {
+                    self.error = Some(err);
+                    return None;
+                },
+            }
+        }
+    }
+
+    /// Read the next paragraph, writing the lines before it unchanged, and return
+    /// whether there is one.
+    fn get_paragraph(&mut self) -> io::Result<bool> {
+        self.last_line_length = 0;
+        let mut c = self.next_char;
+
+        // The empty lines and the ones without the prefix are not reformatted
+        while c == Some(b'\n')
+            || c.is_none()
+            || self.next_prefix_indent < self.options.prefix_lead_space
+            || self.in_column < self.next_prefix_indent + self.options.prefix_full_length
+        {
+            c = self.copy_rest(c)?;
+            if c.is_none() {
+                self.next_char = None;
+                return Ok(false);
+            }
+
+            self.output.write_all(b"\n")?;
+            c = self.get_prefix();
+        }
+
+        self.prefix_indent = self.next_prefix_indent;
+        self.first_indent = self.in_column;
+        self.text.clear();
+        self.words.clear();
+
+        c = self.get_line(c)?;
+        self.set_other_indent(self.same_paragraph(c));
+
+        if self.options.split {
+            // Every line is a paragraph
+        } else if self.options.crown {
+            if self.same_paragraph(c) {
+                loop {
+                    c = self.get_line(c)?;
+                    if !self.same_paragraph(c) || self.in_column != self.other_indent {
+                        break;
+                    }
+                }
+            }
+        } else if self.options.tagged {
+            if self.same_paragraph(c) && self.in_column != self.first_indent {
+                loop {
+                    c = self.get_line(c)?;
+                    if !self.same_paragraph(c) || self.in_column != self.other_indent {
+                        break;
+                    }
+                }
+            }
+        } else {
+            while self.same_paragraph(c) && self.in_column == self.other_indent {
+                c = self.get_line(c)?;
+            }
+        }
+
+        if let Some(last) = self.words.last_mut() {
+            last.period = true;
+            last.last = true;
+        }
+
+        self.next_char = c;
+        Ok(true)
+    }
+
+    /// Write the rest of the line, with the prefix and the spaces read before the
+    /// character `c`, and return the newline after it, or `None` at the end of the input.
+    fn copy_rest(&mut self, mut c: Option<u8>) -> io::Result<Option<u8>> {
+        let options = self.options;
+        self.out_column = 0;
+
+        if self.in_column > self.next_prefix_indent || (c != Some(b'\n') && c.is_some()) {
+            self.put_space(self.next_prefix_indent)?;
+
+            for &byte in &options.prefix {
+                if self.out_column == self.in_column {
+                    break;
+                }
+                self.output.write_all(&[byte])?;
+                self.out_column += 1;
+            }
+
+            if c != Some(b'\n') && c.is_some() {
+                self.put_space(self.in_column.saturating_sub(self.out_column))?;
+            }
+
+            if c.is_none() && self.in_column >= self.next_prefix_indent + options.prefix.len() {
+                self.output.write_all(b"\n")?;
+            }
+        }
+
+        while let Some(byte) = c {
+            if byte == b'\n' {
+                break;
+            }
+            self.output.write_all(&[byte])?;
+            c = self.getc();
+        }
+
+        Ok(c)
+    }
+
+    /// Whether the line that starts with the character `c` is in the paragraph.
+    fn same_paragraph(&self, c: Option<u8>) -> bool {
+        self.next_prefix_indent == self.prefix_indent
+            && self.in_column >= self.next_prefix_indent + self.options.prefix_full_length
+            && c != Some(b'\n')
+            && c.is_some()
+    }
+
+    /// Read the words of the line that starts with the character `c`, and return the
+    /// first character after the prefix of the next line.
+    fn get_line(&mut self, mut c: Option<u8>) -> io::Result<Option<u8>> {
+        loop {
+            let mut word = Word { start: self.text.len(), ..Word::default() };
+
+            while let Some(byte) = c {
+                if self.text.len() == MAX_CHARS {
+                    self.set_other_indent(true);
+                    self.flush_paragraph(&mut word.start)?;
+                }
+
+                self.text.push(byte);
+                c = self.getc();
+
+                if c.map(is_space).unwrap_or(true) {
+                    break;
+                }
+            }
+
+            word.length = self.text.len() - word.start;
+            self.in_column += word.length;
+            self.check_punctuation(&mut word);
+
+            let start = self.in_column;
+            c = self.get_space(c);
+            word.space = self.in_column - start;
+            word.last = c.is_none() || (word.period && (c == Some(b'\n') || word.space > 1));
+
+            if c == Some(b'\n') || c.is_none() || self.options.uniform {
+                word.space = if word.last { 2 } else { 1 };
+            }
+
+            if self.words.len() == MAX_WORDS - 2 {
+                self.set_other_indent(true);
+                self.flush_paragraph(&mut word.start)?;
+            }
+
+            self.words.push(word);
+
+            if c == Some(b'\n') || c.is_none() {
+                return Ok(self.get_prefix());
+            }
+        }
+    }
+
+    /// Read the prefix of the next line, and return the first character after it.
+    fn get_prefix(&mut self) -> Option<u8> {
+        let options = self.options;
+        self.in_column = 0;
+
+        let c = self.getc();
+        let mut c = self.get_space(c);
+
+        if options.prefix.is_empty() {
+            self.next_prefix_indent = options.prefix_lead_space.min(self.in_column);
+        } else {
+            self.next_prefix_indent = self.in_column;
+
+            for &byte in &options.prefix {
+                if c != Some(byte) {
+                    return c;
+                }
+                self.in_column += 1;
+                c = self.getc();
+            }
+
+            c = self.get_space(c);
+        }
+
+        c
+    }
+
+    /// Read the spaces from the character `c`, and return the first one after them.
+    fn get_space(&mut self, mut c: Option<u8>) -> Option<u8> {
+        loop {
+            match c {
+                Some(b' ') => self.in_column += 1,
+                Some(b'\t') => {
+                    self.tabs = true;
+                    self.in_column = (self.in_column / TAB_WIDTH + 1) * TAB_WIDTH;
+                },
+                _ => return c,
+            }
+
+            c = self.getc();
+        }
+    }
+
+    /// Set the punctuation of the `word`.
+    fn check_punctuation(&self, word: &mut Word) {
+        let text = &self.text[word.start..word.start + word.length];
+
+        if let Some(&last) = text.last() {
+            word.paren = b"(['`\"".contains(&text[0]);
+            word.punct = last.is_ascii_punctuation();
+
+            let mut finish = text.len() - 1;
+            while finish > 0 && b")]'\"".contains(&text[finish]) {
+                finish -= 1;
+            }
+
+            word.period = b".?!".contains(&text[finish]);
+        }
+    }
+
+    /// Set the indentation of the other lines of the paragraph, where its first line is
+    /// followed by a line of the paragraph if `same_paragraph` is true.
+    fn set_other_indent(&mut self, same_paragraph: bool) {
+        if self.options.split {
+            self.other_indent = self.first_indent;
+        } else if self.options.crown {
+            self.other_indent = if same_paragraph { self.in_column } else { self.first_indent };
+        } else if self.options.tagged {
+            if same_paragraph && self.in_column != self.first_indent {
+                self.other_indent = self.in_column;
+            } else if self.other_indent == self.first_indent {
+                // With one line, the last indentation is used if it is different
+                self.other_indent = if self.first_indent == 0 { DEF_INDENT } else { 0 };
+            }
+        } else {
+            self.other_indent = self.first_indent;
+        }
+    }
+
+    /// Write the start of a paragraph that is too long to be kept, up to a good break,
+    /// where `pending_start` is the start of the word being read.
+    fn flush_paragraph(&mut self, pending_start: &mut usize) -> io::Result<()> {
+        // A single word is written as it is
+        if self.words.is_empty() {
+            self.output.write_all(&self.text)?;
+            self.text.clear();
+            *pending_start = 0;
+            return Ok(());
+        }
+
+        self.fmt_paragraph();
+
+        // Choose a break of low cost near the end
+        let limit = self.words.len();
+        let best_cost = |words: &[Word], i: usize| if i == limit { 0 } else { words[i].best_cost };
+        let mut split = limit;
+        let mut best_break = MAX_COST;
+        let mut w = self.words[0].next_break;
+
+        while w != limit {
+            let cost = self.words[w].best_cost - best_cost(&self.words, self.words[w].next_break);
+            if cost < best_break {
+                split = w;
+                best_break = cost;
+            }
+
+            if best_break <= MAX_COST - LINE_CREDIT {
+                best_break += LINE_CREDIT;
+            }
+
+            w = self.words[w].next_break;
+        }
+
+        self.put_paragraph(split)?;
+
+        // The words after the break start the paragraph
+        let shift = if split == limit { *pending_start } else { self.words[split].start };
+        self.text.drain(..shift);
+        self.words.drain(..split);
+        for word in &mut self.words {
+            word.start -= shift;
+        }
+        *pending_start -= shift;
+
+        Ok(())
+    }
+
+    /// Choose the breaks of the lines of the paragraph of lowest cost.
+    fn fmt_paragraph(&mut self) {
+        let limit = self.words.len();
+        let max_width = self.options.max_width;
+        let first_indent = self.first_indent;
+        let other_indent = self.other_indent;
+
+        // The paragraph ends with a sentinel
+        self.words.push(Word { length: max_width, ..Word::default() });
+
+        for start in (0..limit).rev() {
+            let mut best = MAX_COST;
+            let mut len = if start == 0 { first_indent } else { other_indent };
+
+            // A line has at least a word, however long
+            let mut w = start;
+            len += self.words[w].length;
+
+            loop {
+                w += 1;
+
+                // Consider breaking before the word `w`
+                let mut wcost = self.line_cost(w, len, limit) + self.words[w].best_cost;
+                if start == 0 && self.last_line_length > 0 {
+                    wcost += ragged_cost(len as i64 - self.last_line_length as i64);
+                }
+
+                if wcost < best {
+                    best = wcost;
+                    self.words[start].next_break = w;
+                    self.words[start].line_length = len;
+                }
+
+                if w == limit {
+                    break;
+                }
+
+                len += self.words[w - 1].space + self.words[w].length;
+                if len >= max_width {
+                    break;
+                }
+            }
+
+            self.words[start].best_cost = best + self.base_cost(start);
+        }
+
+        self.words.pop();
+    }
+
+    /// The cost of a line of length `len` ended before the word `next`, where `limit`
+    /// is the end of the paragraph.
+    fn line_cost(&self, next: usize, len: usize, limit: usize) -> Cost {
+        if next == limit {
+            return 0;
+        }
+
+        let mut cost = short_cost(self.options.goal_width as i64 - len as i64);
+
+        if self.words[next].next_break != limit {
+            cost += ragged_cost(len as i64 - self.words[next].line_length as i64);
+        }
+
+        cost
+    }
+
+    /// The cost of starting a line with the word `this`.
+    fn base_cost(&self, this: usize) -> Cost {
+        let mut cost = LINE_COST;
+
+        if this > 0 {
+            let previous = &self.words[this - 1];
+
+            if previous.period {
+                if previous.last {
+                    cost -= SENTENCE_BONUS;
+                } else {
+                    cost += NOBREAK_COST;
+                }
+            } else if previous.punct {
+                cost -= PUNCT_BONUS;
+            } else if this > 1 && self.words[this - 2].last {
+                cost += widow_cost(previous.length as i64);
+            }
+        }
+
+        let word = &self.words[this];
+        if word.paren {
+            cost -= PAREN_BONUS;
+        } else if word.last {
+            cost += orphan_cost(word.length as i64);
+        }
+
+        cost
+    }
+
+    /// Write the lines of the paragraph before the word `finish`.
+    fn put_paragraph(&mut self, finish: usize) -> io::Result<()> {
+        self.put_line(0, self.first_indent)?;
+
+        let mut w = self.words[0].next_break;
+        while w != finish {
+            self.put_line(w, self.other_indent)?;
+            w = self.words[w].next_break;
+        }
+
+        Ok(())
+    }
+
+    /// Write the line that starts with the word `w` with the `indent`.
+    fn put_line(&mut self, w: usize, indent: usize) -> io::Result<()> {
+        self.out_column = 0;
+        self.put_space(self.prefix_indent)?;
+        self.output.write_all(&self.options.prefix)?;
+        self.out_column += self.options.prefix.len();
+        self.put_space(indent.saturating_sub(self.out_column))?;
+
+        let end = self.words[w].next_break - 1;
+        for word in w..end {
+            self.put_word(word)?;
+            self.put_space(self.words[word].space)?;
+        }
+        self.put_word(end)?;
+
+        self.last_line_length = self.out_column;
+        self.output.write_all(b"\n")
+    }
+
+    fn put_word(&mut self, w: usize) -> io::Result<()> {
+        let word = self.words[w];
+        self.output.write_all(&self.text[word.start..word.start + word.length])?;
+        self.out_column += word.length;
+
+        Ok(())
+    }
+
+    /// Write `space` spaces, with tabs if the input has them.
+    fn put_space(&mut self, space: usize) -> io::Result<()> {
+        let space_target = self.out_column + space;
+
+        if self.tabs {
+            let tab_target = space_target / TAB_WIDTH * TAB_WIDTH;
+            if self.out_column + 1 < tab_target {
+                while self.out_column < tab_target {
+                    self.output.write_all(b"\t")?;
+                    self.out_column = (self.out_column / TAB_WIDTH + 1) * TAB_WIDTH;
+                }
+            }
+        }
+
+        while self.out_column < space_target {
+            self.output.write_all(b" ")?;
+            self.out_column += 1;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(width: usize) -> Options {
+        Options {
+            crown: false,
+            tagged: false,
+            split: false,
+            uniform: false,
+            prefix: Vec::new(),
+            prefix_lead_space: 0,
+            prefix_full_length: 0,
+            max_width: width,
+            goal_width: width * (2 * (100 - LEEWAY) + 1) / 200,
+        }
+    }
+
+    fn reformat(options: &Options, text: &str) -> String {
+        let mut output = Vec::new();
+        fmt(options, text.as_bytes(), &mut output).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn paragraphs() {
+        let options = options(20);
+
+        assert_eq!(
+            reformat(&options, "one two\nthree four five six seven\n\n  eight nine\n  ten\n"),
+            "one two three four\nfive six seven\n\n  eight nine ten\n"
+        );
+        assert_eq!(reformat(&options, "a b"), "a b\n");
+        assert_eq!(reformat(&options, "\n\n"), "\n\n");
+        assert_eq!(reformat(&options, "one. Two\nthree.  Four\n"), "one. Two three.\nFour\n");
+        assert_eq!(reformat(&options, "\ta b\n\tc\n"), "\ta b c\n");
+        assert_eq!(reformat(&options, "averyveryverylongword a\n"), "averyveryverylongword\na\n");
+    }
+
+    #[test]
+    fn breaks() {
+        let text =
+            "The quick brown fox jumps over the lazy dog. Pack my box with five dozen liquor jugs.";
+
+        assert_eq!(
+            reformat(&options(30), text),
+            "The quick brown fox jumps\nover the lazy dog. Pack my\nbox with five dozen \
+             liquor\njugs.\n"
+        );
+        assert_eq!(
+            reformat(&options(40), text),
+            "The quick brown fox jumps over the\nlazy dog. Pack my box with five dozen\nliquor \
+             jugs.\n"
+        );
+    }
+
+    #[test]
+    fn long_paragraphs() {
+        let text: String = (0..3000).map(|i| format!("word{} ", i)).collect();
+        let output = reformat(&options(75), &text);
+
+        assert!(output.lines().all(|line| line.len() <= 75));
+        assert!(output.split_whitespace().eq(text.split_whitespace()));
+    }
+
+    #[test]
+    fn spacing() {
+        let mut options = options(75);
+        assert_eq!(reformat(&options, "a  b.   c\n"), "a  b.   c\n");
+
+        options.uniform = true;
+        assert_eq!(reformat(&options, "a  b.   c\n"), "a b.  c\n");
+    }
+
+    #[test]
+    fn split() {
+        let mut options = options(10);
+        options.split = true;
+
+        assert_eq!(reformat(&options, "a b\nc d e f g h\n"), "a b\nc d e f\ng h\n");
+    }
+
+    #[test]
+    fn indents() {
+        let mut options = options(20);
+        options.crown = true;
+        assert_eq!(
+            reformat(&options, "  one two\nthree\n  four five six seven\n"),
+            "  one two three\n  four five six\n  seven\n"
+        );
+
+        options.crown = false;
+        options.tagged = true;
+        assert_eq!(reformat(&options, "one two\n  three\n"), "one two three\n");
+        assert_eq!(
+            reformat(&options, "one two three four five six\n"),
+            "one two three four\n   five six\n"
+        );
+    }
+
+    #[test]
+    fn prefixes() {
+        let mut options = options(20);
+        options.prefix = b"#".to_vec();
+        options.prefix_full_length = 2;
+
+        assert_eq!(
+            reformat(&options, "# one two\n# three four five six\ncode\n  # seven\n"),
+            "# one two three\n# four five six\ncode\n  # seven\n"
+        );
+    }
+
+    #[test]
+    fn widths() {
+        assert_eq!(parse_width("70", 2500), Ok(70));
+        assert_eq!(parse_width("+7", 2500), Ok(7));
+        assert_eq!(parse_width("x", 2500), Err("invalid width: 'x'".to_string()));
+        assert_eq!(parse_width("-3", 2500), Err("invalid width: '-3'".to_string()));
+        assert_eq!(
+            parse_width("3000", 2500),
+            Err("invalid width: '3000': Numerical result out of range".to_string())
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("File(s) to use, or '-' to use from standard input.")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("bytes")
+                .help("Count the bytes, instead of the columns.")
+                .long_help(
+                    "Count the bytes, instead of the columns.\n\nBy default, the characters are \
+                     counted by their width on a terminal, where a tab advances to the next \
+                     multiple of 8, a backspace goes back a column and a carriage return goes \
+                     back to the first one.",
+                )
+                .long("bytes")
+                .short("b"),
+        )
+        .arg(
+            Arg::with_name("spaces")
+                .help("Break the lines after their last blank that fits in the width.")
+                .long("spaces")
+                .short("s"),
+        )
+        .arg(
+            Arg::with_name("width")
+                .help("Use WIDTH columns, instead of 80.")
+                .long("width")
+                .short("w")
+                .value_name("WIDTH")
+                .allow_hyphen_values(true),
+        )
+}
@@ -0,0 +1,276 @@
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::{self, stdin, stdout, BufRead, BufReader, BufWriter, Write},
+    process, str,
+};
+
+use clap::ArgMatches;
+use unicode_width::UnicodeWidthChar;
+
+mod cli;
+
+/// The width used when none is given.
+const DEFAULT_WIDTH: usize = 80;
+
+/// The distance between the tab stops.
+const TAB_WIDTH: usize = 8;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let fold = Fold::from_matches(&matches).unwrap_or_else(|err| {
+        eprintln!("fold: {}", err);
+        process::exit(1);
+    });
+
+    let files: Vec<&OsStr> = match matches.values_of_os("FILE") {
+        Some(files) => files.collect(),
+        None => vec![OsStr::new("-")],
+    };
+
+    let stdout = stdout();
+    let mut output = BufWriter::new(stdout.lock());
+    let mut success = true;
+
+    for file in files {
+        let result = if file == "-" {
+            fold.fold(stdin().lock(), &mut output)
+        } else {
+            match File::open(file) {
+                Ok(opened) => fold.fold(BufReader::new(opened), &mut output),
+                Err(err) => Err(Error::Read(err)),
+            }
+        };
+
+        match result {
+            Ok(()) => {},
+            Err(Error::Read(err)) => {
+                eprintln!("fold: {}: {}", file.to_string_lossy(), err);
+                success = false;
+            },
+            Err(Error::Write(err)) => {
+                eprintln!("fold: write error: {}", err);
+                process::exit(1);
+            },
+        }
+    }
+
+    if let Err(err) = output.flush() {
+        eprintln!("fold: write error: {}", err);
+        process::exit(1);
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+#[derive(Debug)]
+enum Error {
+    Read(io::Error),
+    Write(io::Error),
+}
+
+/// The wrapper of the lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fold {
+    width: usize,
+    /// Whether the bytes are counted, instead of the columns of the characters.
+    bytes: bool,
+    /// Whether the lines are broken after their last blank that fits in the width.
+    spaces: bool,
+}
+
+impl Fold {
+    fn from_matches(matches: &ArgMatches) -> Result<Self, String> {
+        let width = match matches.value_of("width") {
+            Some(width) => parse_width(width)?,
+            None => DEFAULT_WIDTH,
+        };
+
+        Ok(Fold { width, bytes: matches.is_present("bytes"), spaces: matches.is_present("spaces") })
+    }
+
+    /// Write the lines of the `input` to the `output`, broken where they are wider than
+    /// the width.
+    fn fold<R: BufRead, W: Write>(&self, mut input: R, output: &mut W) -> Result<(), Error> {
+        let mut line = Vec::new();
+
+        loop {
+            line.clear();
+            if input.read_until(b'\n', &mut line).map_err(Error::Read)? == 0 {
+                return Ok(());
+            }
+
+            let content = line.strip_suffix(b"\n").unwrap_or(&line);
+            self.fold_line(content, output).map_err(Error::Write)?;
+            output.write_all(&line[content.len()..]).map_err(Error::Write)?;
+        }
+    }
+
+    /// Write the `line`, without its newline, to the `output`, broken where it is wider
+    /// than the width.
+    fn fold_line<W: Write>(&self, line: &[u8], output: &mut W) -> io::Result<()> {
+        // The start of the part of the line that is not written yet
+        let mut start = 0;
+        let mut position = 0;
+        let mut column = 0;
+
+        while position < line.len() {
+            let (len, next) = self.advance(column, &line[position..]);
+
+            if next <= self.width {
+                column = next;
+                position += len;
+                continue;
+            }
+
+            if self.spaces {
+                let blank = line[start..position].iter().rposition(|&b| b == b' ' || b == b'\t');
+
+                if let Some(blank) = blank {
+                    let end = start + blank + 1;
+                    output.write_all(&line[start..end])?;
+                    output.write_all(b"\n")?;
+
+                    start = end;
+                    column = self.columns(&line[start..position]);
+                    continue;
+                }
+            }
+
+            // Like GNU, a character wider than the width is written alone
+            if start == position {
+                column = next;
+                position += len;
+                continue;
+            }
+
+            output.write_all(&line[start..position])?;
+            output.write_all(b"\n")?;
+
+            start = position;
+            column = 0;
+        }
+
+        output.write_all(&line[start..])
+    }
+
+    /// The length of the character at the start of the `text`, and the column after it
+    /// when it is at the `column`.
+    ///
+    /// The bytes that are not valid UTF-8 are taken alone, like in a count of bytes.
+    fn advance(&self, column: usize, text: &[u8]) -> (usize, usize) {
+        if self.bytes {
+            return (1, column + 1);
+        }
+
+        match text[0] {
+            b'\x08' => return (1, column.saturating_sub(1)),
+            b'\r' => return (1, 0),
+            b'\t' => return (1, column + TAB_WIDTH - column % TAB_WIDTH),
+            _ => {},
+        }
+
+        let text = &text[..text.len().min(4)];
+        let valid = match str::from_utf8(text) {
+            Ok(valid) => valid,
+            Err(err) => str::from_utf8(&text[..err.valid_up_to()]).unwrap(),
+        };
+
+        match valid.chars().next() {
+            // Like GNU, the control characters are a column wide
+            Some(c) => (c.len_utf8(), column + c.width().unwrap_or(1)),
+            None => (1, column + 1),
+        }
+    }
+
+    /// The column at the end of the `text`.
+    fn columns(&self, text: &[u8]) -> usize {
+        let mut column = 0;
+        let mut position = 0;
+
+        while position < text.len() {
+            let (len, next) = self.advance(column, &text[position..]);
+            column = next;
+            position += len;
+        }
+
+        column
+    }
+}
+
+/// Parse the `width` of the lines.
+fn parse_width(width: &str) -> Result<usize, String> {
+    match width.parse::<usize>() {
+        Ok(parsed) if parsed > 0 && parsed < usize::MAX - TAB_WIDTH => Ok(parsed),
+        Ok(_) => {
+            Err(format!("invalid number of columns: '{}': Numerical result out of range", width))
+        },
+        Err(_) if !width.is_empty() && width.bytes().all(|b| b.is_ascii_digit()) => Err(format!(
+            "invalid number of columns: '{}': Value too large for defined data type",
+            width
+        )),
+        Err(_) => Err(format!("invalid number of columns: '{}'", width)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fold(text: &str, width: usize, bytes: bool, spaces: bool) -> String {
+        let mut output = Vec::new();
+        Fold { width, bytes, spaces }.fold(text.as_bytes(), &mut output).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn columns() {
+        assert_eq!(fold("abcdefgh\nab\n", 3, false, false), "abc\ndef\ngh\nab\n");
+        assert_eq!(fold("abcdef", 3, false, false), "abc\ndef");
+        assert_eq!(fold("ab\tc", 3, false, false), "ab\n\t\nc");
+        assert_eq!(fold("ab\tcdefghijk", 10, false, false), "ab\tcd\nefghijk");
+        assert_eq!(fold("abc\x08de", 3, false, false), "abc\x08d\ne");
+        assert_eq!(fold("abc\rdefg", 3, false, false), "abc\rdef\ng");
+        assert_eq!(fold("déjà vu", 3, false, false), "déj\nà v\nu");
+        assert_eq!(fold("日本語", 3, false, false), "日\n本\n語");
+        assert_eq!(fold("ae\u{301}io", 2, false, false), "ae\u{301}\nio");
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!(fold("ab\tc\x08d", 3, true, false), "ab\t\nc\x08d");
+
+        let mut output = Vec::new();
+        let fold = Fold { width: 2, bytes: true, spaces: false };
+        fold.fold("aé".as_bytes(), &mut output).unwrap();
+        assert_eq!(output, b"a\xc3\n\xa9");
+    }
+
+    #[test]
+    fn spaces() {
+        assert_eq!(fold("ab cd ef gh", 5, false, true), "ab \ncd \nef gh");
+        assert_eq!(fold("one two three", 6, false, true), "one \ntwo \nthree");
+        assert_eq!(fold("abcdefg hi", 4, false, true), "abcd\nefg \nhi");
+        assert_eq!(fold("a\tbcdefghij", 10, false, true), "a\t\nbcdefghij");
+    }
+
+    #[test]
+    fn widths() {
+        assert_eq!(parse_width("12"), Ok(12));
+        assert_eq!(
+            parse_width("0"),
+            Err("invalid number of columns: '0': Numerical result out of range".to_string())
+        );
+        assert_eq!(parse_width("-1"), Err("invalid number of columns: '-1'".to_string()));
+        assert_eq!(
+            parse_width("99999999999999999999"),
+            Err("invalid number of columns: '99999999999999999999': Value too large for defined \
+                 data type"
+                .to_string())
+        );
+    }
+}
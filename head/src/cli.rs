@@ -14,18 +14,45 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
         .arg(Arg::with_name("FILE").help("File(s) to use.").multiple(true))
         .arg(
             Arg::with_name("bytes")
-                .help("Display the byte counts.")
+                .help("Display the first N bytes, or all but the last N bytes with a leading '-'.")
+                .long_help(
+                    "Display the first N bytes, or all but the last N bytes with a leading \
+                     '-'.\n\nN may have a multiplier suffix: b 512, kB 1000, K 1024, MB \
+                     1000*1000, M 1024*1024, and so on for G, T, P, E. Binary prefixes can be \
+                     used too: KiB=K, MiB=M, and so on.",
+                )
                 .long("bytes")
                 .short("c")
                 .value_name("N")
+                .allow_hyphen_values(true)
                 .conflicts_with("lines"),
         )
         .arg(
             Arg::with_name("lines")
-                .help("Display the newline counts.")
+                .help("Display the first N lines, or all but the last N lines with a leading '-'.")
+                .long_help(
+                    "Display the first N lines, or all but the last N lines with a leading \
+                     '-'.\n\nN may have the same multiplier suffixes as --bytes.",
+                )
                 .long("lines")
                 .short("n")
                 .value_name("N")
+                .allow_hyphen_values(true)
                 .default_value("10"),
         )
+        .arg(
+            Arg::with_name("quiet")
+                .help("Never print headers giving file names.")
+                .long("quiet")
+                .short("q")
+                .visible_alias("silent")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .help("Always print headers giving file names.")
+                .long("verbose")
+                .short("v")
+                .multiple(true),
+        )
 }
@@ -1,30 +1,41 @@
 use std::{
+    collections::VecDeque,
+    convert::TryFrom,
     fs::File,
-    io::{self, BufRead, BufReader, Read, Write},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    process,
 };
 
-use clap::{value_t, ArgMatches, ErrorKind};
+use clap::ArgMatches;
+use coreutils_core::size::parse_size;
 
 mod cli;
 
 const DEFAULT_LINES_COUNT: usize = 10;
 const NEW_LINE: u8 = 0xA;
+/// The suffixes of the multipliers the counts can have.
+const COUNT_SUFFIXES: &str = "bkKmMgGtTPE";
 
 fn main() {
     let matches = cli::create_app().get_matches();
 
     let flags = Flags::from_matches(&matches);
-    let input_list = Input::from_matches(&matches);
+    let (inputs_count, input_list) = Input::from_matches(&matches);
+    let headers = Headers::from_matches(&matches).should_print(inputs_count);
 
-    head(&flags, input_list).unwrap_or_else(|_e| {
+    head(&flags, headers, input_list).unwrap_or_else(|_e| {
         std::process::exit(1);
     });
 }
 
-/// We truncate the input at either some number of lines or bytes
+/// We truncate the input at either some number of lines or bytes, or at all but the last
+/// number of lines or bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Flags {
     LinesCount(usize),
     BytesCount(usize),
+    AllButLastLines(usize),
+    AllButLastBytes(usize),
 }
 
 impl Flags {
@@ -32,22 +43,46 @@ impl Flags {
     ///
     /// This will exit the program early on invalid args
     fn from_matches(matches: &ArgMatches) -> Self {
-        match value_t!(matches, "bytes", usize) {
-            Ok(bytes_count) => Flags::BytesCount(bytes_count),
-            Err(e) => match e.kind {
-                ErrorKind::ValueValidation => e.exit(),
-                _ => match value_t!(matches, "lines", usize) {
-                    Ok(l) => Flags::LinesCount(l),
-                    Err(e) => match e.kind {
-                        ErrorKind::ValueValidation => e.exit(),
-                        _ => Flags::LinesCount(DEFAULT_LINES_COUNT),
-                    },
+        if let Some(bytes) = matches.value_of("bytes") {
+            match parse_count(bytes) {
+                Some((true, count)) => Flags::AllButLastBytes(count),
+                Some((false, count)) => Flags::BytesCount(count),
+                None => {
+                    eprintln!("head: invalid number of bytes: '{}'", bytes);
+                    process::exit(1);
                 },
-            },
+            }
+        } else if let Some(lines) = matches.value_of("lines") {
+            match parse_count(lines) {
+                Some((true, count)) => Flags::AllButLastLines(count),
+                Some((false, count)) => Flags::LinesCount(count),
+                None => {
+                    eprintln!("head: invalid number of lines: '{}'", lines);
+                    process::exit(1);
+                },
+            }
+        } else {
+            Flags::LinesCount(DEFAULT_LINES_COUNT)
         }
     }
 }
 
+/// Parse a count argument, returning if it has a leading '-' and the count.
+///
+/// The count may have a multiplier suffix: `b` 512, `kB` 1000, `K` 1024, `MB` 1000*1000,
+/// `M` 1024*1024, and so on for `G`, `T`, `P` and `E`. The binary suffixes can also be
+/// written as `KiB`, `MiB`, ...
+fn parse_count(value: &str) -> Option<(bool, usize)> {
+    let (all_but_last, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let count = parse_size(value, COUNT_SUFFIXES).ok()?;
+
+    Some((all_but_last, usize::try_from(count).ok()?))
+}
+
 /// Input is either a file, or STDIN
 enum Input {
     File(String),
@@ -55,59 +90,124 @@ enum Input {
 }
 
 impl Input {
-    /// Parse arguments into an Vec of Input enums.
-    fn from_matches(matches: &ArgMatches) -> Vec<Self> {
+    /// Parse arguments into an iterator of Input enums, with the number of inputs.
+    ///
+    /// The iterator is lazy, so every input is only checked when its turn comes.
+    fn from_matches<'a>(matches: &'a ArgMatches) -> (usize, Box<dyn Iterator<Item = Self> + 'a>) {
         if let Some(files) = matches.values_of("FILE") {
-            files
-                .map(|f| if f == "-" { Self::Stdin } else { Self::File(String::from(f)) })
-                .collect()
+            let count = files.len();
+            let inputs =
+                files.map(|f| if f == "-" { Self::Stdin } else { Self::File(String::from(f)) });
+            (count, Box::new(inputs))
         } else {
-            vec![Self::Stdin]
+            (1, Box::new(std::iter::once(Self::Stdin)))
+        }
+    }
+
+    /// The name used on the headers and error messages.
+    fn name(&self) -> &str {
+        match self {
+            Input::File(file) => file,
+            Input::Stdin => "standard input",
+        }
+    }
+}
+
+/// When to print the `==> name <==` headers before each input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Headers {
+    /// Only when there is more than one input
+    Multiple,
+    /// Never, with `-q`
+    Never,
+    /// Always, with `-v`
+    Always,
+}
+
+impl Headers {
+    /// Parse arguments into a Headers enum, the last one of `-q` and `-v` wins
+    fn from_matches(matches: &ArgMatches) -> Self {
+        let quiet = matches.indices_of("quiet").and_then(Iterator::max);
+        let verbose = matches.indices_of("verbose").and_then(Iterator::max);
+
+        match (quiet, verbose) {
+            (Some(q), Some(v)) if v > q => Headers::Always,
+            (Some(_), _) => Headers::Never,
+            (None, Some(_)) => Headers::Always,
+            (None, None) => Headers::Multiple,
+        }
+    }
+
+    fn should_print(self, inputs_count: usize) -> bool {
+        match self {
+            Headers::Multiple => inputs_count > 1,
+            Headers::Never => false,
+            Headers::Always => true,
         }
     }
 }
 
 /// Return the head of our input, truncated at a number of lines or bytes
-fn head(flags: &Flags, input_list: Vec<Input>) -> Result<(), io::Error> {
+fn head(
+    flags: &Flags, headers: bool, input_list: impl Iterator<Item = Input>,
+) -> Result<(), io::Error> {
     let mut err_return = Ok(());
-    let files_count = input_list.len();
+    let mut first_output = true;
 
-    for (i, input) in input_list.iter().enumerate() {
-        if i > 0 {
-            println!();
-        }
-        match input {
-            Input::File(file) => {
-                let f = match File::open(file) {
-                    Ok(f) => f,
-                    Err(err) => {
-                        eprintln!("head: Cannot open '{}' for reading: {}", file, err);
-                        err_return = Err(err);
-                        continue;
-                    },
-                };
-
-                if files_count > 1 {
-                    println!("==> {} <==", file);
-                }
-                let reader = BufReader::new(f);
-                read_stream(flags, reader, &mut io::stdout())?;
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    for input in input_list {
+        let result = match &input {
+            Input::File(file) => match File::open(file) {
+                Ok(f) => {
+                    print_header(&mut stdout, headers, &mut first_output, input.name())?;
+                    read_file(flags, f, &mut stdout)
+                },
+                Err(err) => {
+                    eprintln!("head: cannot open '{}' for reading: {}", file, err);
+                    err_return = Err(err);
+                    continue;
+                },
             },
 
             Input::Stdin => {
-                if files_count > 1 {
-                    println!("==> standard input <==");
-                }
+                print_header(&mut stdout, headers, &mut first_output, input.name())?;
                 let stdin = io::stdin();
                 let reader = BufReader::new(stdin.lock());
-                read_stream(flags, reader, &mut io::stdout())?;
+                read_stream(flags, reader, &mut stdout)
             },
+        };
+
+        if let Err(err) = result {
+            if err.kind() == io::ErrorKind::BrokenPipe {
+                return Err(err);
+            }
+            eprintln!("head: error reading '{}': {}", input.name(), err);
+            err_return = Err(err);
         }
     }
 
     err_return
 }
 
+/// Print the header for the input `name` if `headers` is set, separating it from the the
+/// previous output with a empty line
+fn print_header<W: Write>(
+    writer: &mut W, headers: bool, first_output: &mut bool, name: &str,
+) -> io::Result<()> {
+    if !headers {
+        return Ok(());
+    }
+
+    if !*first_output {
+        writeln!(writer)?;
+    }
+    *first_output = false;
+
+    writeln!(writer, "==> {} <==", name)
+}
+
 /// Read from a stream, truncated at a number of lines or bytes and write back to a stream
 fn read_stream<R: Read, W: Write>(
     flags: &Flags, mut reader: BufReader<R>, writer: &mut W,
@@ -128,10 +228,124 @@ fn read_stream<R: Read, W: Write>(
             reader.take(*bytes_count as u64).read_to_end(&mut buffer)?;
             writer.write_all(&buffer)?;
         },
+        Flags::AllButLastLines(lines_count) => {
+            // Ring buffer holding the last lines read, they are only written when a new
+            // line pushes them out
+            let mut lines = VecDeque::with_capacity(*lines_count + 1);
+            loop {
+                let mut buffer = Vec::new();
+                let bytes_read = reader.read_until(NEW_LINE, &mut buffer)?;
+                if bytes_read == 0 {
+                    break;
+                }
+                lines.push_back(buffer);
+                if lines.len() > *lines_count {
+                    if let Some(line) = lines.pop_front() {
+                        writer.write_all(&line)?;
+                    }
+                }
+            }
+        },
+        Flags::AllButLastBytes(bytes_count) => {
+            // Buffer holding at least the last bytes read, everything before them is
+            // written when it grows
+            let mut pending = Vec::new();
+            loop {
+                let buffer = reader.fill_buf()?;
+                if buffer.is_empty() {
+                    break;
+                }
+                pending.extend_from_slice(buffer);
+                let len = buffer.len();
+                reader.consume(len);
+
+                if pending.len() > *bytes_count {
+                    let ready = pending.len() - *bytes_count;
+                    writer.write_all(&pending[..ready])?;
+                    pending.drain(..ready);
+                }
+            }
+        },
     }
     Ok(())
 }
 
+/// Read from a file, truncated at a number of lines or bytes and write back to a stream
+///
+/// If the file is a regular file, the end of the file is found seeking instead of
+/// reading all the file to keep the last lines or bytes.
+fn read_file<W: Write>(flags: &Flags, mut file: File, writer: &mut W) -> Result<(), io::Error> {
+    let is_regular = file.metadata().map(|m| m.is_file()).unwrap_or(false);
+
+    let end = match flags {
+        Flags::AllButLastBytes(_) | Flags::AllButLastLines(_) if is_regular => {
+            file.seek(SeekFrom::End(0))?
+        },
+        _ => return read_stream(flags, BufReader::new(file), writer),
+    };
+
+    let start = match flags {
+        Flags::AllButLastBytes(bytes_count) => end.saturating_sub(*bytes_count as u64),
+        Flags::AllButLastLines(lines_count) => find_lines_from_end(&mut file, end, *lines_count)?,
+        _ => unreachable!(),
+    };
+
+    file.seek(SeekFrom::Start(0))?;
+    let copied = io::copy(&mut file.take(start), writer)?;
+
+    // The file was truncated while reading
+    if copied < start {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    Ok(())
+}
+
+/// Find the offset in `file` where the last `lines_count` lines start, reading it
+/// backwards from `end`.
+fn find_lines_from_end(file: &mut File, end: u64, lines_count: usize) -> io::Result<u64> {
+    const CHUNK_SIZE: u64 = 8192;
+
+    if lines_count == 0 {
+        return Ok(end);
+    }
+
+    let mut buffer = vec![0u8; CHUNK_SIZE as usize];
+    let mut pos = end;
+    let mut newlines_to_find = lines_count;
+    let mut is_last_byte = true;
+
+    while pos > 0 {
+        let read_size = CHUNK_SIZE.min(pos);
+        pos -= read_size;
+        file.seek(SeekFrom::Start(pos))?;
+
+        let chunk = &mut buffer[..read_size as usize];
+        file.read_exact(chunk)?;
+
+        for (i, &byte) in chunk.iter().enumerate().rev() {
+            if byte != NEW_LINE {
+                is_last_byte = false;
+                continue;
+            }
+
+            // The line terminator of the last line does not start a line
+            if is_last_byte {
+                is_last_byte = false;
+                continue;
+            }
+
+            newlines_to_find -= 1;
+            if newlines_to_find == 0 {
+                return Ok(pos + i as u64 + 1);
+            }
+        }
+    }
+
+    // The file does not have more lines than `lines_count`
+    Ok(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -157,4 +371,50 @@ mod tests {
 
         assert_eq!(String::from_utf8(out).unwrap(), "fo".to_string());
     }
+
+    #[test]
+    fn read_stream_all_but_last_lines() {
+        let buffer = b"foo\nbar\nbaz";
+        let flags = Flags::AllButLastLines(2);
+        let mut out = Vec::new();
+
+        read_stream(&flags, BufReader::new(&buffer[..]), &mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "foo\n".to_string());
+    }
+
+    #[test]
+    fn read_stream_all_but_last_bytes() {
+        let buffer = b"foo\nbar\nbaz";
+        let flags = Flags::AllButLastBytes(5);
+        let mut out = Vec::new();
+
+        read_stream(&flags, BufReader::new(&buffer[..]), &mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "foo\nba".to_string());
+    }
+
+    #[test]
+    fn parse_count_suffixes() {
+        assert_eq!(parse_count("10"), Some((false, 10)));
+        assert_eq!(parse_count("-10"), Some((true, 10)));
+        assert_eq!(parse_count("2b"), Some((false, 1024)));
+        assert_eq!(parse_count("-1K"), Some((true, 1024)));
+        assert_eq!(parse_count("1kB"), Some((false, 1000)));
+        assert_eq!(parse_count("1MiB"), Some((false, 1024 * 1024)));
+        assert_eq!(parse_count("1x"), None);
+        assert_eq!(parse_count("x"), None);
+    }
+
+    #[test]
+    fn print_header_separated() {
+        let mut out = Vec::new();
+        let mut first_output = true;
+
+        print_header(&mut out, true, &mut first_output, "foo").unwrap();
+        print_header(&mut out, true, &mut first_output, "bar").unwrap();
+        print_header(&mut out, false, &mut first_output, "baz").unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "==> foo <==\n\n==> bar <==\n".to_string());
+    }
 }
@@ -87,7 +87,7 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .conflicts_with_all(&["group", "groups", "user", "name", "pretty", "human"]),
         );
 
-    #[cfg(any(target_os = "freebsd", target_os = "macos"))]
+    #[cfg(any(target_os = "freebsd", target_os = "macos", target_os = "linux"))]
     let app = app.arg(
         Arg::with_name("audit")
             .help(
@@ -12,7 +12,9 @@ fn main() {
 
     let mut sep = '\n';
 
-    if flags.audit && (cfg!(target_os = "freebsd") || cfg!(target_os = "macos")) {
+    if flags.audit
+        && (cfg!(target_os = "freebsd") || cfg!(target_os = "macos") || cfg!(target_os = "linux"))
+    {
         audit_logic();
         return;
     }
@@ -230,10 +232,10 @@ fn pretty_logic(passwd: &Passwd, sep: char) {
     print!("{}", sep);
 }
 
-#[cfg(not(any(target_os = "freebsd", target_os = "macos")))]
+#[cfg(not(any(target_os = "freebsd", target_os = "macos", target_os = "linux")))]
 fn audit_logic() {}
 
-#[cfg(any(target_os = "freebsd", target_os = "macos"))]
+#[cfg(any(target_os = "freebsd", target_os = "macos", target_os = "linux"))]
 fn audit_logic() {
     match coreutils_core::os::audit::audit_info() {
         Ok(auditinfo) => println!("{}", auditinfo),
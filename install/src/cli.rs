@@ -0,0 +1,164 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("SOURCE")
+                .help("Files to install, and destination file or directory.")
+                .long_help(
+                    "Files to install, and destination file or directory.\n\nWith more than two \
+                     operands the last one is the directory the SOURCEs are installed in. With -d \
+                     all of them are directories to create.",
+                )
+                .value_names(&["SOURCE", "DEST"])
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("backup")
+                .help("Make a backup of each existing destination file.")
+                .long_help(
+                    "Make a backup of each existing destination file.\n\nCONTROL is 'none' or \
+                     'off' to never make backups, 'numbered' or 't' to make numbered backups, \
+                     'existing' or 'nil' to make numbered backups of the files that have them and \
+                     simple backups of the others, or 'simple' or 'never' to make simple backups. \
+                     Without it the VERSION_CONTROL environment variable is used, and 'existing' \
+                     by default.",
+                )
+                .long("backup")
+                .value_name("CONTROL")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&[
+                    "none", "off", "numbered", "t", "existing", "nil", "simple", "never",
+                ]),
+        )
+        .arg(
+            Arg::with_name("backupDefault")
+                .help("Same as --backup but does not accept a argument.")
+                .short("b"),
+        )
+        .arg(Arg::with_name("copy").help("Ignored, for compatibility.").short("c"))
+        .arg(
+            Arg::with_name("compare")
+                .help("Do not copy the files that are already like the installed ones.")
+                .long_help(
+                    "Do not copy the files that are already like the installed ones.\n\nThe \
+                     destination files are kept, with their modification times, when they have \
+                     the contents, mode, owner and group they would have once installed.",
+                )
+                .long("compare")
+                .short("C"),
+        )
+        .arg(
+            Arg::with_name("directory")
+                .help("Treat all the arguments as directories, and create them.")
+                .long_help(
+                    "Treat all the arguments as directories, and create them with their missing \
+                     parents.\n\nThe mode, owner and group are set on the directories given, also \
+                     when they exist.",
+                )
+                .long("directory")
+                .short("d"),
+        )
+        .arg(
+            Arg::with_name("createLeading")
+                .help(
+                    "Create all the missing leading components of DEST, or all the components of \
+                     --target-directory.",
+                )
+                .short("D"),
+        )
+        .arg(
+            Arg::with_name("group")
+                .help("Set the group of the installed files, instead of the current one.")
+                .long("group")
+                .short("g")
+                .value_name("GROUP")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("mode")
+                .help("Set the permission mode of the installed files, instead of rwxr-xr-x.")
+                .long_help(
+                    "Set the permission mode of the installed files, instead of \
+                     rwxr-xr-x.\n\nMODE is a octal number or symbolic changes like in chmod, \
+                     applied to no permissions.",
+                )
+                .long("mode")
+                .short("m")
+                .value_name("MODE")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("owner")
+                .help("Set the owner of the installed files, instead of the current user.")
+                .long("owner")
+                .short("o")
+                .value_name("OWNER")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("preserveTimestamps")
+                .help(
+                    "Set the access and modification times of the installed files to the ones of \
+                     the SOURCEs.",
+                )
+                .long("preserve-timestamps")
+                .short("p"),
+        )
+        .arg(
+            Arg::with_name("strip")
+                .help("Strip the symbol tables of the installed files.")
+                .long("strip")
+                .short("s"),
+        )
+        .arg(
+            Arg::with_name("stripProgram")
+                .help("Program used to strip the installed files with -s, 'strip' by default.")
+                .long("strip-program")
+                .value_name("PROGRAM")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("suffix")
+                .help("Override the usual backup suffix, '~' by default.")
+                .long_help(
+                    "Override the usual backup suffix. Without it the SIMPLE_BACKUP_SUFFIX \
+                     environment variable is used, and '~' by default.\n\nIt also makes the \
+                     backups like --backup.",
+                )
+                .long("suffix")
+                .short("S")
+                .value_name("SUFFIX")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("targetDirectory")
+                .help("Install all the SOURCE arguments in DIRECTORY.")
+                .long("target-directory")
+                .short("t")
+                .value_name("DIRECTORY"),
+        )
+        .arg(
+            Arg::with_name("noTargetDirectory")
+                .help("Treat DEST as a normal file.")
+                .long("no-target-directory")
+                .short("T"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .help("Print the name of each file installed, and each directory created.")
+                .long("verbose")
+                .short("v"),
+        )
+}
@@ -0,0 +1,605 @@
+use std::{
+    env,
+    ffi::{CString, OsStr, OsString},
+    fs::{self, DirBuilder, File, Metadata, Permissions},
+    io::{self, BufRead, BufReader},
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{DirBuilderExt, MetadataExt, PermissionsExt},
+        io::AsRawFd,
+    },
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+use clap::ArgMatches;
+use coreutils_core::{
+    backup::*,
+    copy, libc,
+    mktemp::Template,
+    mode::Mode,
+    os::{
+        get_real_gid, get_real_uid, group::Group, passwd::Passwd, time::set_file_times, Gid,
+        TimeSpec, Uid,
+    },
+    target::{find_target, Target},
+};
+
+mod cli;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let flags = InstallFlags::from_matches(&matches);
+
+    let operands: Vec<&Path> = matches
+        .values_of_os("SOURCE")
+        .map(|values| values.map(Path::new).collect())
+        .unwrap_or_default();
+
+    let target_directory = matches.value_of_os("targetDirectory").map(Path::new);
+
+    if flags.directory {
+        if target_directory.is_some() {
+            eprintln!("install: target directory not allowed when installing a directory");
+            process::exit(1);
+        }
+
+        if operands.is_empty() {
+            eprintln!("install: missing file operand");
+            process::exit(1);
+        }
+
+        let mut success = true;
+
+        for dir in operands {
+            success &= install_directory(dir, &flags);
+        }
+
+        if !success {
+            process::exit(1);
+        }
+        return;
+    }
+
+    // With -D the target directory is created before it is checked
+    if let (true, Some(dir)) = (flags.create_leading, target_directory) {
+        if !create_parents(dir, &flags) {
+            process::exit(1);
+        }
+    }
+
+    let target = find_target(&operands, target_directory, matches.is_present("noTargetDirectory"));
+    let target = match target {
+        Ok(target) => target,
+        Err(err) => {
+            eprintln!("install: {}", err);
+            process::exit(1);
+        },
+    };
+
+    let mut success = true;
+
+    match target {
+        Target::Directory { sources, directory } => {
+            for source in sources {
+                let name = source.file_name().unwrap_or(source.as_os_str());
+
+                success &= install(source, &directory.join(name), &flags);
+            }
+        },
+        Target::File { source, dest } => {
+            if flags.create_leading {
+                if let Some(parent) = dest.parent() {
+                    success = create_parents(parent, &flags);
+                }
+            }
+
+            success = success && install(source, dest, &flags);
+        },
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+#[derive(Debug, Clone)]
+struct InstallFlags {
+    backup: BackupMode,
+    suffix: String,
+    /// The permissions of the installed files.
+    mode: u32,
+    /// The permissions of the directories created with `-d`.
+    dir_mode: u32,
+    owner: Option<Uid>,
+    group: Option<Gid>,
+    /// The program that strips the installed files, with `-s`.
+    strip: Option<OsString>,
+    compare: bool,
+    create_leading: bool,
+    directory: bool,
+    preserve_timestamps: bool,
+    verbose: bool,
+}
+
+impl InstallFlags {
+    fn from_matches(matches: &ArgMatches) -> Self {
+        let backup = if matches.is_present("backup")
+            || matches.is_present("backupDefault")
+            || matches.is_present("suffix")
+        {
+            match matches.value_of("backup") {
+                Some(control) => BackupMode::from(control),
+                None => match env::var("VERSION_CONTROL") {
+                    Ok(control) if control.is_empty() => BackupMode::Existing,
+                    Ok(control) if BACKUP_CONTROLS.contains(&control.as_str()) => {
+                        BackupMode::from(control.as_str())
+                    },
+                    Ok(control) => {
+                        eprintln!("install: invalid argument '{}' for '$VERSION_CONTROL'", control);
+                        process::exit(1);
+                    },
+                    Err(_) => BackupMode::Existing,
+                },
+            }
+        } else {
+            BackupMode::None
+        };
+
+        // A suffix with a slash would put the backups in other directory
+        let suffix = match matches.value_of("suffix") {
+            Some(suffix) => suffix.to_string(),
+            None => env::var("SIMPLE_BACKUP_SUFFIX").unwrap_or_default(),
+        };
+        let suffix =
+            if suffix.is_empty() || suffix.contains('/') { "~".to_string() } else { suffix };
+
+        // The mode is applied to no permissions, without the umask
+        let (mode, dir_mode) = match matches.value_of("mode").map(str::parse::<Mode>) {
+            Some(Ok(mode)) => (mode.apply(0, false, 0), mode.apply(0, true, 0)),
+            Some(Err(err)) => {
+                eprintln!("install: {}", err);
+                process::exit(1);
+            },
+            None => (0o755, 0o755),
+        };
+
+        let strip = matches.is_present("strip");
+        let directory = matches.is_present("directory");
+        let preserve_timestamps = matches.is_present("preserveTimestamps");
+        let mut compare = matches.is_present("compare");
+
+        if strip && directory {
+            eprintln!("install: the strip option may not be used when installing a directory");
+            process::exit(1);
+        }
+
+        if !strip && matches.is_present("stripProgram") {
+            eprintln!(
+                "install: WARNING: ignoring --strip-program option as -s option was not specified"
+            );
+        }
+
+        if compare && preserve_timestamps {
+            eprintln!(
+                "install: options --compare (-C) and --preserve-timestamps are mutually exclusive"
+            );
+            process::exit(1);
+        }
+
+        if compare && strip {
+            eprintln!("install: options --compare (-C) and --strip are mutually exclusive");
+            process::exit(1);
+        }
+
+        // The special bits are not compared, so the files are always copied
+        if compare && mode & !0o777 != 0 {
+            eprintln!(
+                "install: the --compare (-C) option is ignored when you specify a mode with \
+                 non-permission bits"
+            );
+            compare = false;
+        }
+
+        let strip = if strip {
+            Some(
+                matches
+                    .value_of_os("stripProgram")
+                    .unwrap_or_else(|| OsStr::new("strip"))
+                    .to_os_string(),
+            )
+        } else {
+            None
+        };
+
+        InstallFlags {
+            backup,
+            suffix,
+            mode,
+            dir_mode,
+            owner: matches.value_of("owner").map(parse_owner),
+            group: matches.value_of("group").map(parse_group),
+            strip,
+            compare,
+            create_leading: matches.is_present("createLeading"),
+            directory,
+            preserve_timestamps,
+            verbose: matches.is_present("verbose"),
+        }
+    }
+}
+
+/// The values of `--backup` and the `VERSION_CONTROL` environment variable.
+const BACKUP_CONTROLS: [&str; 8] =
+    ["none", "off", "numbered", "t", "existing", "nil", "simple", "never"];
+
+/// Parse the `owner` of `-o`, a user name or ID.
+fn parse_owner(owner: &str) -> Uid {
+    match Passwd::from_name(owner) {
+        Ok(passwd) => passwd.uid(),
+        Err(_) => owner.parse().unwrap_or_else(|_| {
+            eprintln!("install: invalid user '{}'", owner);
+            process::exit(1);
+        }),
+    }
+}
+
+/// Parse the `group` of `-g`, a group name or ID.
+fn parse_group(group: &str) -> Gid {
+    match Group::from_name(group) {
+        Ok(group) => group.id(),
+        Err(_) => group.parse().unwrap_or_else(|_| {
+            eprintln!("install: invalid group '{}'", group);
+            process::exit(1);
+        }),
+    }
+}
+
+/// Install the `source` file as `dest`, returning `false` if it failed.
+///
+/// The copy is made in a temporary file next to `dest` and renamed to it once it has all
+/// its attributes, so `dest` is never seen incomplete.
+fn install(source: &Path, dest: &Path, flags: &InstallFlags) -> bool {
+    let metadata = match fs::metadata(source) {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            eprintln!("install: cannot stat '{}': {}", source.display(), err);
+            return false;
+        },
+    };
+
+    if metadata.is_dir() {
+        eprintln!("install: omitting directory '{}'", source.display());
+        return false;
+    }
+
+    if let Ok(dest_metadata) = fs::metadata(dest) {
+        if dest_metadata.dev() == metadata.dev() && dest_metadata.ino() == metadata.ino() {
+            eprintln!("install: '{}' and '{}' are the same file", source.display(), dest.display());
+            return false;
+        }
+    }
+
+    if flags.compare && !needs_copy(source, dest, flags) {
+        return true;
+    }
+
+    let exists = match fs::symlink_metadata(dest) {
+        Ok(dest_metadata) if dest_metadata.is_dir() => {
+            eprintln!(
+                "install: cannot overwrite directory '{}' with non-directory",
+                dest.display()
+            );
+            return false;
+        },
+        Ok(_) => true,
+        Err(_) => false,
+    };
+
+    let temp = match make_temp(source, dest, &metadata, flags) {
+        Ok(temp) => temp,
+        Err(err) => {
+            eprintln!("install: {}", err);
+            return false;
+        },
+    };
+
+    let mut backup = None;
+
+    if exists {
+        let made = match flags.backup {
+            BackupMode::None => Ok(None),
+            BackupMode::Numbered => create_numbered_backup(dest).map(Some),
+            BackupMode::Existing => create_existing_backup(dest, &flags.suffix).map(Some),
+            BackupMode::Simple => create_simple_backup(dest, &flags.suffix).map(Some),
+        };
+
+        match made {
+            Ok(made) => backup = made,
+            Err(err) => {
+                eprintln!("install: cannot backup '{}': {}", dest.display(), err);
+                let _ = fs::remove_file(&temp);
+                return false;
+            },
+        }
+    }
+
+    if let Err(err) = fs::rename(&temp, dest) {
+        eprintln!("install: cannot create regular file '{}': {}", dest.display(), err);
+        let _ = fs::remove_file(&temp);
+
+        // The file in the destination is put back
+        if let Some(backup) = backup {
+            let _ = fs::rename(backup, dest);
+        }
+
+        return false;
+    }
+
+    if flags.verbose {
+        match backup {
+            Some(backup) => println!(
+                "'{}' -> '{}' (backup: '{}')",
+                source.display(),
+                dest.display(),
+                backup.display()
+            ),
+            None => println!("'{}' -> '{}'", source.display(), dest.display()),
+        }
+    }
+
+    true
+}
+
+/// Copy the `source` of the `metadata` to a temporary file in the directory of `dest`,
+/// and set its attributes.
+///
+/// # Errors
+/// If it can't be made, the temporary file is removed and the message of the error is
+/// returned.
+fn make_temp(
+    source: &Path, dest: &Path, metadata: &Metadata, flags: &InstallFlags,
+) -> Result<PathBuf, String> {
+    let dir = match dest.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    // Ok to unwrap, the template has enough X's
+    let template = Template::new(dir.join(".install.XXXXXX"), 0).unwrap();
+
+    let (file, temp) = template
+        .create_file()
+        .map_err(|err| format!("cannot create regular file '{}': {}", dest.display(), err))?;
+
+    let fill = || -> Result<(), String> {
+        let input = File::open(source)
+            .map_err(|err| format!("cannot open '{}' for reading: {}", source.display(), err))?;
+
+        copy::copy(input.as_raw_fd(), file.as_raw_fd()).map_err(|err| {
+            format!("cannot copy '{}' to '{}': {}", source.display(), dest.display(), err)
+        })?;
+
+        if let Some(program) = &flags.strip {
+            strip(&temp, program)?;
+        }
+
+        set_attributes(&temp, flags.mode, dest, flags)?;
+
+        if flags.preserve_timestamps {
+            let atime =
+                TimeSpec { tv_sec: metadata.atime() as _, tv_nsec: metadata.atime_nsec() as _ };
+            let mtime =
+                TimeSpec { tv_sec: metadata.mtime() as _, tv_nsec: metadata.mtime_nsec() as _ };
+
+            set_file_times(&temp, Some(atime), Some(mtime), false).map_err(|err| {
+                format!("cannot set timestamps for '{}': {}", dest.display(), err)
+            })?;
+        }
+
+        Ok(())
+    };
+
+    match fill() {
+        Ok(()) => Ok(temp),
+        Err(err) => {
+            let _ = fs::remove_file(&temp);
+            Err(err)
+        },
+    }
+}
+
+/// Returns `true` if `dest` is not already like the `source` once installed, with `-C`.
+fn needs_copy(source: &Path, dest: &Path, flags: &InstallFlags) -> bool {
+    let (metadata, dest_metadata) = match (fs::symlink_metadata(source), fs::symlink_metadata(dest))
+    {
+        (Ok(metadata), Ok(dest_metadata)) => (metadata, dest_metadata),
+        _ => return true,
+    };
+
+    // The files with the special bits are always copied
+    if !metadata.is_file()
+        || !dest_metadata.is_file()
+        || metadata.mode() & 0o7000 != 0
+        || dest_metadata.mode() & 0o7000 != 0
+    {
+        return true;
+    }
+
+    if metadata.len() != dest_metadata.len() || dest_metadata.mode() & 0o777 != flags.mode {
+        return true;
+    }
+
+    // Without -o and -g the files are owned by the user
+    let owner = flags.owner.unwrap_or_else(get_real_uid);
+    let group = flags.group.unwrap_or_else(get_real_gid);
+
+    if dest_metadata.uid() != owner || dest_metadata.gid() != group {
+        return true;
+    }
+
+    !same_contents(source, dest).unwrap_or(false)
+}
+
+/// Returns `true` if the files in `path` and `other` have the same contents.
+///
+/// # Errors
+/// If the files can't be read, an error variant will be returned.
+fn same_contents(path: &Path, other: &Path) -> io::Result<bool> {
+    let mut file = BufReader::new(File::open(path)?);
+    let mut other = BufReader::new(File::open(other)?);
+
+    loop {
+        let len = {
+            let (buf, other_buf) = (file.fill_buf()?, other.fill_buf()?);
+
+            if buf.is_empty() || other_buf.is_empty() {
+                return Ok(buf.is_empty() && other_buf.is_empty());
+            }
+
+            let len = buf.len().min(other_buf.len());
+            if buf[..len] != other_buf[..len] {
+                return Ok(false);
+            }
+
+            len
+        };
+
+        file.consume(len);
+        other.consume(len);
+    }
+}
+
+/// Strip the installed file in `path` with the `program` of `--strip-program`.
+///
+/// # Errors
+/// If the program can't be run or fails, the message of the error is returned.
+fn strip(path: &Path, program: &OsStr) -> Result<(), String> {
+    match Command::new(program).arg(path).status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(_) => Err("strip process terminated abnormally".to_string()),
+        Err(err) => Err(format!("cannot run '{}': {}", program.to_string_lossy(), err)),
+    }
+}
+
+/// Create the `dir` of `-d` with its missing parents, and set its attributes, returning
+/// `false` if it failed.
+fn install_directory(dir: &Path, flags: &InstallFlags) -> bool {
+    if !create_parents(dir, flags) {
+        return false;
+    }
+
+    match set_attributes(dir, flags.dir_mode, dir, flags) {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!("install: {}", err);
+            false
+        },
+    }
+}
+
+/// Create the `dir` and its missing parents with the default permissions, returning
+/// `false` if it failed.
+fn create_parents(dir: &Path, flags: &InstallFlags) -> bool {
+    let mut ancestors: Vec<&Path> =
+        dir.ancestors().filter(|ancestor| !ancestor.as_os_str().is_empty()).collect();
+    ancestors.reverse();
+
+    for ancestor in ancestors {
+        if ancestor.is_dir() {
+            continue;
+        }
+
+        match DirBuilder::new().mode(0o755).create(ancestor) {
+            Ok(()) => {
+                if flags.verbose {
+                    println!("install: creating directory '{}'", ancestor.display());
+                }
+            },
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists && ancestor.is_dir() => {},
+            Err(err) => {
+                // It exists but is not a directory
+                let err = match err.kind() {
+                    io::ErrorKind::AlreadyExists => io::Error::from_raw_os_error(libc::ENOTDIR),
+                    _ => err,
+                };
+
+                eprintln!("install: cannot create directory '{}': {}", ancestor.display(), err);
+                return false;
+            },
+        }
+    }
+
+    true
+}
+
+/// Set the owner and group of `-o` and `-g` and the `mode` of the file in `path`,
+/// installed as `name`.
+///
+/// # Errors
+/// If they can't be set, the message of the error is returned.
+fn set_attributes(path: &Path, mode: u32, name: &Path, flags: &InstallFlags) -> Result<(), String> {
+    // The owner is changed first, since it can clear the set-user-ID and set-group-ID bits
+    if flags.owner.is_some() || flags.group.is_some() {
+        change_owner(path, flags.owner, flags.group)
+            .map_err(|err| format!("cannot change ownership of '{}': {}", name.display(), err))?;
+    }
+
+    fs::set_permissions(path, Permissions::from_mode(mode))
+        .map_err(|err| format!("cannot change permissions of '{}': {}", name.display(), err))
+}
+
+/// Change the owner and group of the file in `path`, leaving the ones that are `None`.
+fn change_owner(path: &Path, owner: Option<Uid>, group: Option<Gid>) -> io::Result<()> {
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    let (owner, group) = (owner.unwrap_or(Uid::MAX), group.unwrap_or(Gid::MAX));
+
+    match unsafe { libc::chown(path.as_ptr(), owner, group) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(args: &[&str]) -> InstallFlags {
+        let args = ["install"].iter().chain(args).chain(&["a", "b"]);
+        InstallFlags::from_matches(&cli::create_app().get_matches_from(args))
+    }
+
+    #[test]
+    fn modes() {
+        assert_eq!(flags(&[]).mode, 0o755);
+        assert_eq!(flags(&["-m", "644"]).mode, 0o644);
+        assert_eq!(flags(&["-m", "u+x"]).mode, 0o100);
+        assert_eq!(flags(&["-m", "a+X"]).mode, 0);
+        assert_eq!(flags(&["-m", "a+X"]).dir_mode, 0o111);
+    }
+
+    #[test]
+    fn backup() {
+        assert_eq!(flags(&[]).backup, BackupMode::None);
+        assert_eq!(flags(&["--backup=numbered"]).backup, BackupMode::Numbered);
+        assert_eq!(flags(&["-S", ".bak"]).suffix, ".bak");
+        assert_eq!(flags(&["--suffix=a/b", "-b"]).suffix, "~");
+    }
+
+    #[test]
+    fn compare() {
+        assert!(flags(&["-C"]).compare);
+        assert!(!flags(&["-C", "-m", "4755"]).compare);
+        assert_eq!(flags(&["-s"]).strip, Some(OsString::from("strip")));
+        assert_eq!(flags(&["-s", "--strip-program=true"]).strip, Some(OsString::from("true")));
+        assert_eq!(flags(&["--strip-program=true"]).strip, None);
+    }
+
+    #[test]
+    fn owners() {
+        assert_eq!(parse_owner("root"), 0);
+        assert_eq!(parse_owner("1234"), 1234);
+        assert_eq!(parse_group("5678"), 5678);
+    }
+}
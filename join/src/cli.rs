@@ -0,0 +1,140 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("The two files to join, where one of them may be '-' for standard input.")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("unpaired")
+                .help("Also write the lines of the file FILENUM that are not paired.")
+                .long_help(
+                    "Also write the lines of the file FILENUM that are not paired.\n\nThe FILENUM \
+                     is 1 or 2, and both may be given.",
+                )
+                .short("a")
+                .value_name("FILENUM")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("empty")
+                .help("Write EMPTY for the fields that are missing or empty.")
+                .long_help(
+                    "Write EMPTY for the fields that are missing or empty.\n\nThe missing fields \
+                     are only written with -o.",
+                )
+                .short("e")
+                .value_name("EMPTY")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("ignoreCase")
+                .help("Compare the join fields ignoring the case of the letters.")
+                .long("ignore-case")
+                .short("i"),
+        )
+        .arg(
+            Arg::with_name("join")
+                .help("Join on the field FIELD of both files, like -1 FIELD -2 FIELD.")
+                .short("j")
+                .value_name("FIELD")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Write the fields of FORMAT, instead of the default ones.")
+                .long_help(
+                    "Write the fields of FORMAT, instead of the default ones.\n\nThe FORMAT is a \
+                     list of FILENUM.FIELD separated by commas or blanks, where 0 is the join \
+                     field. When it is 'auto', the fields are the join field followed by as many \
+                     fields of each file as in its first line.\n\nBy default, the join field is \
+                     followed by the other fields of the first file and of the second one.",
+                )
+                .short("o")
+                .value_name("FORMAT")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("separator")
+                .help("Separate the fields by CHAR in the input and the output.")
+                .long_help(
+                    "Separate the fields by CHAR in the input and the output.\n\nBy default, the \
+                     fields are separated by blanks in the input, which are ignored at the start \
+                     of the lines, and by a space in the output. When CHAR is empty, the whole \
+                     lines are the fields, and '\\0' stands for the NUL character.",
+                )
+                .short("t")
+                .value_name("CHAR")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("onlyUnpaired")
+                .help("Write only the lines of the file FILENUM that are not paired.")
+                .short("v")
+                .value_name("FILENUM")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("field1")
+                .help("Join on the field FIELD of the first file, instead of 1.")
+                .short("1")
+                .value_name("FIELD")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("field2")
+                .help("Join on the field FIELD of the second file, instead of 1.")
+                .short("2")
+                .value_name("FIELD")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("checkOrder")
+                .help("Fail when the files are not sorted.")
+                .long_help(
+                    "Fail when the files are not sorted.\n\nBy default, it is only reported once \
+                     some line is not paired, and the exit status is then 1.",
+                )
+                .long("check-order")
+                .overrides_with("nocheckOrder"),
+        )
+        .arg(
+            Arg::with_name("nocheckOrder")
+                .help("Do not check whether the files are sorted.")
+                .long("nocheck-order")
+                .overrides_with("checkOrder"),
+        )
+        .arg(
+            Arg::with_name("header")
+                .help("Join the first lines of the files as headers, without comparing them.")
+                .long("header"),
+        )
+        .arg(
+            Arg::with_name("zeroTerminated")
+                .help("End the lines with NUL characters, instead of newlines.")
+                .long("zero-terminated")
+                .short("z"),
+        )
+}
@@ -0,0 +1,724 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    fs::File,
+    io::{self, stdin, stdout, BufRead, BufReader, BufWriter, Write},
+    process,
+};
+
+use clap::{ArgMatches, Values};
+use coreutils_core::collate::Collator;
+
+mod cli;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let options = Options::from_matches(&matches).unwrap_or_else(|err| {
+        eprintln!("join: {}", err);
+        process::exit(1);
+    });
+
+    let files: Vec<_> = matches.values_of("FILE").map_or_else(Vec::new, Values::collect);
+    match files.len() {
+        0 => fail("missing operand"),
+        1 => fail(&format!("missing operand after '{}'", files[0])),
+        2 => {},
+        _ => fail(&format!("extra operand '{}'", files[2])),
+    }
+    if files[0] == "-" && files[1] == "-" {
+        fail("both files cannot be standard input");
+    }
+
+    let stdin = stdin();
+    let open = |file: &str| -> Box<dyn BufRead> {
+        if file == "-" {
+            return Box::new(stdin.lock());
+        }
+        match File::open(file) {
+            Ok(opened) => Box::new(BufReader::new(opened)),
+            Err(err) => fail(&format!("{}: {}", file, err)),
+        }
+    };
+    let inputs = [Input::new(open(files[0]), files[0]), Input::new(open(files[1]), files[1])];
+
+    let stdout = stdout();
+    let mut join = Join::new(&options, inputs, BufWriter::new(stdout.lock()));
+    let result = join.join();
+
+    // Like GNU, the errors follow the lines written before them
+    let flushed = join.output.flush();
+    match result {
+        Ok(()) => {},
+        Err(Error::Write(err)) => fail(&format!("write error: {}", err)),
+        Err(err) => fail(&err.to_string()),
+    }
+    if let Err(err) = flushed {
+        fail(&format!("write error: {}", err));
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("join: {}", message);
+    process::exit(1);
+}
+
+#[derive(Debug)]
+enum Error {
+    Read(String, io::Error),
+    Write(io::Error),
+    /// The files are not sorted, with the message describing it.
+    Unsorted(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Read(name, err) => write!(f, "{}: {}", name, err),
+            Error::Write(err) => write!(f, "write error: {}", err),
+            Error::Unsorted(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Write(err)
+    }
+}
+
+/// The separator of the fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Separator {
+    /// Runs of blanks in the input, ignored at the start of the lines, and a space in the
+    /// output.
+    Blanks,
+    /// The encoding of a single character.
+    Char(Vec<u8>),
+    /// Nothing, so the whole lines are the fields, and a newline in the output.
+    Line,
+}
+
+/// How the input order is checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Check {
+    /// Checked once some line is not paired, with a warning that fails at the end.
+    Default,
+    /// Checked from the start, failing at the first disorder.
+    Enabled,
+    Disabled,
+}
+
+/// A field of the output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Spec {
+    /// The join field, of whichever file has it.
+    Join,
+    /// The file and the field, both numbered from 0.
+    Field(usize, usize),
+}
+
+/// The fields written for the lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Format {
+    /// The join field, followed by the other fields of both lines.
+    Default,
+    /// Like the default, with the count of the fields of each file from its first line.
+    Auto,
+    List(Vec<Spec>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Options {
+    /// The join fields of the files, numbered from 0.
+    fields: [usize; 2],
+    separator: Separator,
+    ignore_case: bool,
+    /// Whether the lines of each file that are not paired are written.
+    unpaired: [bool; 2],
+    /// Whether the paired lines are written.
+    paired: bool,
+    /// The string written for the missing and empty fields.
+    empty: Option<Vec<u8>>,
+    format: Format,
+    header: bool,
+    check: Check,
+    line_terminator: u8,
+    collator: Collator,
+}
+
+impl Options {
+    fn from_matches(matches: &ArgMatches) -> Result<Self, String> {
+        let mut separator = None;
+        for value in matches.values_of("separator").into_iter().flatten() {
+            let parsed = match value {
+                "" => Separator::Line,
+                "\\0" => Separator::Char(vec![b'\0']),
+                _ if value.chars().count() == 1 => Separator::Char(value.as_bytes().to_vec()),
+                _ => return Err(format!("multi-character tab '{}'", value)),
+            };
+            if matches!(separator, Some(ref separator) if *separator != parsed) {
+                return Err("incompatible tabs".to_string());
+            }
+            separator = Some(parsed);
+        }
+
+        // Like GNU, the join fields are checked in the order of the command line
+        let mut settings = Vec::new();
+        for (name, files) in &[("field1", &[0][..]), ("field2", &[1]), ("join", &[0, 1])] {
+            if let (Some(values), Some(indices)) =
+                (matches.values_of(name), matches.indices_of(name))
+            {
+                settings.extend(indices.zip(values).map(|(index, value)| (index, *files, value)));
+            }
+        }
+        settings.sort_by_key(|setting| setting.0);
+
+        let mut fields = [None, None];
+        for (_, files, value) in settings {
+            let parsed = parse_field(value)?;
+            for &file in files {
+                match fields[file] {
+                    Some(field) if field != parsed => {
+                        return Err(format!(
+                            "incompatible join fields {}, {}",
+                            field + 1,
+                            parsed + 1
+                        ));
+                    },
+                    _ => fields[file] = Some(parsed),
+                }
+            }
+        }
+
+        let mut unpaired = [false, false];
+        for name in &["unpaired", "onlyUnpaired"] {
+            for value in matches.values_of(name).into_iter().flatten() {
+                match value.parse::<i64>() {
+                    Ok(file @ 1..=2) => unpaired[file as usize - 1] = true,
+                    _ => return Err(format!("invalid field number: '{}'", value)),
+                }
+            }
+        }
+
+        let mut format = Format::Default;
+        let mut list = Vec::new();
+        for value in matches.values_of("format").into_iter().flatten() {
+            if value == "auto" {
+                format = Format::Auto;
+            } else {
+                list.extend(parse_format(value)?);
+            }
+        }
+        if !list.is_empty() {
+            format = Format::List(list);
+        }
+
+        let check = if matches.is_present("checkOrder") {
+            Check::Enabled
+        } else if matches.is_present("nocheckOrder") {
+            Check::Disabled
+        } else {
+            Check::Default
+        };
+
+        Ok(Options {
+            fields: [fields[0].unwrap_or(0), fields[1].unwrap_or(0)],
+            separator: separator.unwrap_or(Separator::Blanks),
+            ignore_case: matches.is_present("ignoreCase"),
+            unpaired,
+            paired: !matches.is_present("onlyUnpaired"),
+            empty: matches.value_of("empty").map(|empty| empty.as_bytes().to_vec()),
+            format,
+            header: matches.is_present("header"),
+            check,
+            line_terminator: if matches.is_present("zeroTerminated") { b'\0' } else { b'\n' },
+            collator: Collator::from_env(),
+        })
+    }
+
+    /// Compare the join fields `a` and `b`, where the empty ones are before all the
+    /// others.
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        match (a.is_empty(), b.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ if self.ignore_case => {
+                self.collator.compare(&a.to_ascii_uppercase(), &b.to_ascii_uppercase())
+            },
+            _ => self.collator.compare(a, b),
+        }
+    }
+}
+
+/// Parse the number of a `field`, taken from 1 on the command line, and counted from 0.
+///
+/// Like GNU, the numbers that are too large are the largest field.
+fn parse_field(field: &str) -> Result<usize, String> {
+    match field.parse::<usize>() {
+        Ok(0) => Err(format!("invalid field number: '{}'", field)),
+        Ok(parsed) => Ok(parsed - 1),
+        Err(_) if !field.is_empty() && field.bytes().all(|b| b.is_ascii_digit()) => {
+            Ok(usize::MAX - 1)
+        },
+        Err(_) => Err(format!("invalid field number: '{}'", field)),
+    }
+}
+
+/// Parse the fields of the `format`, separated by commas or blanks.
+fn parse_format(format: &str) -> Result<Vec<Spec>, String> {
+    format
+        .split(&[',', ' ', '\t'][..])
+        .filter(|spec| !spec.is_empty())
+        .map(|spec| match spec.as_bytes() {
+            [b'0'] => Ok(Spec::Join),
+            [b'1', b'.', ..] | [b'2', b'.', ..] => {
+                let file = usize::from(spec.as_bytes()[0] - b'1');
+                Ok(Spec::Field(file, parse_field(&spec[2..])?))
+            },
+            [b'0', ..] | [b'1', ..] | [b'2', ..] => {
+                Err(format!("invalid field specifier: '{}'", spec))
+            },
+            _ => Err(format!("invalid file number in field spec: '{}'", spec)),
+        })
+        .collect()
+}
+
+/// A line split in fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Line {
+    /// The text of the line, without its terminator.
+    text: Vec<u8>,
+    /// The starts and ends of the fields in the text.
+    fields: Vec<(usize, usize)>,
+}
+
+impl Line {
+    fn new(text: Vec<u8>, separator: &Separator) -> Self {
+        let mut fields = Vec::new();
+        let len = text.len();
+
+        if len > 0 {
+            match separator {
+                Separator::Line => fields.push((0, len)),
+                Separator::Char(separator) => {
+                    let mut start = 0;
+                    while let Some(found) = find(&text[start..], separator) {
+                        fields.push((start, start + found));
+                        start += found + separator.len();
+                    }
+                    fields.push((start, len));
+                },
+                Separator::Blanks => {
+                    // Like GNU, the blanks at the end are followed by an empty field
+                    let is_blank = |b: &u8| matches!(b, b' ' | b'\t' | b'\n');
+                    let mut start = text.iter().position(|b| !is_blank(b)).unwrap_or(len);
+                    while start < len {
+                        let end =
+                            text[start..].iter().position(is_blank).map_or(len, |p| start + p);
+                        fields.push((start, end));
+                        if end == len {
+                            break;
+                        }
+                        start =
+                            text[end..].iter().position(|b| !is_blank(b)).map_or(len, |p| end + p);
+                        if start == len {
+                            fields.push((len, len));
+                        }
+                    }
+                },
+            }
+        }
+
+        Line { text, fields }
+    }
+
+    /// The field `n`, empty when it is missing.
+    fn field(&self, n: usize) -> &[u8] {
+        self.fields.get(n).map_or(&[], |&(start, end)| &self.text[start..end])
+    }
+}
+
+/// The position of the first occurrence of the `separator` in the `text`.
+fn find(text: &[u8], separator: &[u8]) -> Option<usize> {
+    match separator {
+        [byte] => text.iter().position(|b| b == byte),
+        _ => text.windows(separator.len()).position(|window| window == separator),
+    }
+}
+
+/// An input file.
+struct Input<R> {
+    reader: R,
+    name: String,
+    /// The number of the last line read.
+    line_number: u64,
+    /// The join field of the last line read, when the order is checked.
+    previous: Option<Vec<u8>>,
+    /// Whether its disorder was reported.
+    warned: bool,
+}
+
+impl<R: BufRead> Input<R> {
+    fn new(reader: R, name: &str) -> Self {
+        Input { reader, name: name.to_string(), line_number: 0, previous: None, warned: false }
+    }
+}
+
+/// The joiner of the lines of two inputs.
+struct Join<'a, R, W> {
+    options: &'a Options,
+    inputs: [Input<R>; 2],
+    output: W,
+    /// The count of the fields of each file with the automatic format.
+    counts: [usize; 2],
+    /// Whether some line was not paired, which starts the default order check.
+    seen_unpaired: bool,
+}
+
+impl<'a, R: BufRead, W: Write> Join<'a, R, W> {
+    fn new(options: &'a Options, inputs: [Input<R>; 2], output: W) -> Self {
+        Join { options, inputs, output, counts: [0, 0], seen_unpaired: false }
+    }
+
+    /// Write the joined lines of the inputs to the output, like GNU.
+    fn join(&mut self) -> Result<(), Error> {
+        let fields = self.options.fields;
+        let mut seq1: Vec<_> = self.read_line(0)?.into_iter().collect();
+        let mut seq2: Vec<_> = self.read_line(1)?.into_iter().collect();
+
+        if self.options.format == Format::Auto {
+            self.counts = [
+                seq1.first().map_or(0, |line| line.fields.len()),
+                seq2.first().map_or(0, |line| line.fields.len()),
+            ];
+        }
+
+        if self.options.header {
+            if !seq1.is_empty() || !seq2.is_empty() {
+                self.write_line(seq1.first(), seq2.first())?;
+            }
+            // The headers are not compared with the next lines
+            for input in &mut self.inputs {
+                input.previous = None;
+            }
+            if !seq1.is_empty() {
+                seq1 = self.read_line(0)?.into_iter().collect();
+            }
+            if !seq2.is_empty() {
+                seq2 = self.read_line(1)?.into_iter().collect();
+            }
+        }
+
+        while !seq1.is_empty() && !seq2.is_empty() {
+            match self.options.compare(seq1[0].field(fields[0]), seq2[0].field(fields[1])) {
+                Ordering::Less => {
+                    if self.options.unpaired[0] {
+                        self.write_line(Some(&seq1[0]), None)?;
+                    }
+                    seq1 = self.read_line(0)?.into_iter().collect();
+                    self.seen_unpaired = true;
+                    continue;
+                },
+                Ordering::Greater => {
+                    if self.options.unpaired[1] {
+                        self.write_line(None, Some(&seq2[0]))?;
+                    }
+                    seq2 = self.read_line(1)?.into_iter().collect();
+                    self.seen_unpaired = true;
+                    continue;
+                },
+                Ordering::Equal => {},
+            }
+
+            // Read the lines of each file as long as they pair with the first line of the
+            // other one, where the last line read is kept for the next round unless it
+            // is the end of the file.
+            let end1 = self.read_paired(0, &mut seq1, seq2[0].field(fields[1]))?;
+            let end2 = self.read_paired(1, &mut seq2, seq1[0].field(fields[0]))?;
+            let paired1 = if end1 { seq1.len() } else { seq1.len() - 1 };
+            let paired2 = if end2 { seq2.len() } else { seq2.len() - 1 };
+
+            if self.options.paired {
+                for line1 in &seq1[..paired1] {
+                    for line2 in &seq2[..paired2] {
+                        self.write_line(Some(line1), Some(line2))?;
+                    }
+                }
+            }
+
+            seq1 = if end1 { Vec::new() } else { seq1.split_off(paired1) };
+            seq2 = if end2 { Vec::new() } else { seq2.split_off(paired2) };
+        }
+
+        // Like GNU, the rest of the files is read for the order check, until a disorder
+        // is reported, unless their lines are written
+        let check = self.options.check != Check::Disabled;
+        for (which, seq) in [seq1, seq2].iter().enumerate() {
+            let unpaired = self.options.unpaired[which];
+            if seq.is_empty() || !(unpaired || check) {
+                continue;
+            }
+            if unpaired {
+                self.write_unpaired(which, &seq[0])?;
+            }
+            while let Some(line) = self.read_line(which)? {
+                if unpaired {
+                    self.write_unpaired(which, &line)?;
+                }
+                if self.inputs[which].warned && !unpaired {
+                    break;
+                }
+            }
+        }
+
+        if self.inputs.iter().any(|input| input.warned) {
+            return Err(Error::Unsorted("input is not in sorted order".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Read the lines of the input `which` to the `seq` as long as their join field is
+    /// `field`, the one of the first line of the other input.
+    ///
+    /// Returns whether the end of the input was reached.
+    fn read_paired(
+        &mut self, which: usize, seq: &mut Vec<Line>, field: &[u8],
+    ) -> Result<bool, Error> {
+        loop {
+            match self.read_line(which)? {
+                Some(line) => {
+                    let paired =
+                        self.options.compare(line.field(self.options.fields[which]), field)
+                            == Ordering::Equal;
+                    seq.push(line);
+                    if !paired {
+                        return Ok(false);
+                    }
+                },
+                None => return Ok(true),
+            }
+        }
+    }
+
+    /// Read the next line of the input `which`, checking its order.
+    fn read_line(&mut self, which: usize) -> Result<Option<Line>, Error> {
+        let options = self.options;
+        let input = &mut self.inputs[which];
+
+        let mut text = Vec::new();
+        match input.reader.read_until(options.line_terminator, &mut text) {
+            Ok(0) => return Ok(None),
+            Ok(_) => {},
+            Err(err) => return Err(Error::Read(input.name.clone(), err)),
+        }
+        if text.last() == Some(&options.line_terminator) {
+            text.pop();
+        }
+        input.line_number += 1;
+
+        let line = Line::new(text, &options.separator);
+        let field = line.field(options.fields[which]);
+
+        let check = match options.check {
+            Check::Default => self.seen_unpaired,
+            Check::Enabled => true,
+            Check::Disabled => false,
+        };
+        if check && !input.warned {
+            if let Some(previous) = &input.previous {
+                if options.compare(previous, field) == Ordering::Greater {
+                    let message = format!(
+                        "{}:{}: is not sorted: {}",
+                        input.name,
+                        input.line_number,
+                        String::from_utf8_lossy(&line.text)
+                    );
+                    if options.check == Check::Enabled {
+                        return Err(Error::Unsorted(message));
+                    }
+
+                    self.output.flush()?;
+                    eprintln!("join: {}", message);
+                    input.warned = true;
+                }
+            }
+        }
+
+        input.previous = Some(field.to_vec());
+        Ok(Some(line))
+    }
+
+    /// Write the `line` of the input `which`, that is not paired.
+    fn write_unpaired(&mut self, which: usize, line: &Line) -> io::Result<()> {
+        if which == 0 {
+            self.write_line(Some(line), None)
+        } else {
+            self.write_line(None, Some(line))
+        }
+    }
+
+    /// Write the fields of `line1` and `line2`, where a missing one is not paired.
+    fn write_line(&mut self, line1: Option<&Line>, line2: Option<&Line>) -> io::Result<()> {
+        let options = self.options;
+        let separator: &[u8] = match &options.separator {
+            Separator::Blanks => b" ",
+            Separator::Char(separator) => separator,
+            Separator::Line => b"\n",
+        };
+        let lines = [line1, line2];
+        // The join field is taken from whichever line is there
+        let join =
+            if line1.is_some() { (line1, options.fields[0]) } else { (line2, options.fields[1]) };
+
+        match &options.format {
+            Format::List(list) => {
+                for (i, spec) in list.iter().enumerate() {
+                    if i > 0 {
+                        self.output.write_all(separator)?;
+                    }
+                    let (line, field) = match *spec {
+                        Spec::Join => join,
+                        Spec::Field(file, field) => (lines[file], field),
+                    };
+                    self.write_field(line, field)?;
+                }
+            },
+            _ => {
+                self.write_field(join.0, join.1)?;
+                for (which, line) in lines.iter().enumerate() {
+                    let count = if options.format == Format::Auto {
+                        self.counts[which]
+                    } else {
+                        line.map_or(0, |line| line.fields.len())
+                    };
+                    for field in (0..count).filter(|&field| field != options.fields[which]) {
+                        self.output.write_all(separator)?;
+                        self.write_field(*line, field)?;
+                    }
+                }
+            },
+        }
+
+        self.output.write_all(&[options.line_terminator])
+    }
+
+    /// Write the field `n` of the `line`, or the empty string when it is missing or
+    /// empty.
+    fn write_field(&mut self, line: Option<&Line>, n: usize) -> io::Result<()> {
+        let field = line.map_or(&[][..], |line| line.field(n));
+        match &self.options.empty {
+            Some(empty) if field.is_empty() => self.output.write_all(empty),
+            _ => self.output.write_all(field),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(args: &[&str]) -> Options {
+        let matches = cli::create_app().get_matches_from(["join"].iter().chain(args));
+        let mut options = Options::from_matches(&matches).unwrap();
+        options.collator = Collator::bytes();
+        options
+    }
+
+    fn join(args: &[&str], file1: &str, file2: &str) -> Result<String, String> {
+        let options = options(args);
+        let inputs = [Input::new(file1.as_bytes(), "a"), Input::new(file2.as_bytes(), "b")];
+        let mut join = Join::new(&options, inputs, Vec::new());
+        join.join().map_err(|err| err.to_string())?;
+
+        Ok(String::from_utf8(join.output).unwrap())
+    }
+
+    #[test]
+    fn fields() {
+        let blanks = |text: &str| Line::new(text.as_bytes().to_vec(), &Separator::Blanks).fields;
+        assert_eq!(blanks("a  b"), vec![(0, 1), (3, 4)]);
+        assert_eq!(blanks(" \ta b "), vec![(2, 3), (4, 5), (6, 6)]);
+        assert_eq!(blanks("  "), vec![]);
+        assert_eq!(blanks(""), vec![]);
+
+        let commas = |text: &str| {
+            Line::new(text.as_bytes().to_vec(), &Separator::Char(b",".to_vec())).fields
+        };
+        assert_eq!(commas(",a,"), vec![(0, 0), (1, 2), (3, 3)]);
+        assert_eq!(commas("a b"), vec![(0, 3)]);
+    }
+
+    #[test]
+    fn pairs() {
+        let file1 = "a 1\nb 2\nb 3\nd 4\n";
+        let file2 = "a x\nb y\nb z\nc w\n";
+        assert_eq!(join(&[], file1, file2).unwrap(), "a 1 x\nb 2 y\nb 2 z\nb 3 y\nb 3 z\n");
+        assert_eq!(
+            join(&["-a1", "-a", "2"], file1, file2).unwrap(),
+            "a 1 x\nb 2 y\nb 2 z\nb 3 y\nb 3 z\nc w\nd 4\n"
+        );
+        assert_eq!(join(&["-v1"], file1, file2).unwrap(), "d 4\n");
+        assert_eq!(join(&["-v", "2"], file1, file2).unwrap(), "c w\n");
+        assert_eq!(join(&["-2", "2", "-t", " "], "x a\n", "a x\n").unwrap(), "x a a\n");
+        assert_eq!(join(&["-i"], "A 1\nb 2\n", "a x\nB y\n").unwrap(), "A 1 x\nb 2 y\n");
+        assert_eq!(join(&["-z", "-t", ","], "a,1\0b,2", "b,x\0").unwrap(), "b,2,x\0");
+        assert_eq!(join(&["-t", ""], "a b\nc\n", "a b\nd\n").unwrap(), "a b\n");
+    }
+
+    #[test]
+    fn formats() {
+        let file1 = "a 1 \nb 2\n";
+        let file2 = "a x\nb\nc y\n";
+        assert_eq!(join(&[], file1, file2).unwrap(), "a 1  x\nb 2\n");
+        assert_eq!(join(&["-o", "auto", "-e", "E"], file1, file2).unwrap(), "a 1 E x\nb 2 E E\n");
+        assert_eq!(
+            join(&["-o", "0,2.2", "-o", "1.2", "-a2", "-e", "-"], file1, file2).unwrap(),
+            "a x 1\nb - 2\nc y -\n"
+        );
+        assert_eq!(
+            join(&["-t", ",", "-e", "E"], ",1\nb,,2\n", ",x\nb,y\n").unwrap(),
+            "E,1,x\nb,E,2,y\n"
+        );
+    }
+
+    #[test]
+    fn headers() {
+        assert_eq!(join(&["--header"], "k v\na 1\n", "k w\na x\n").unwrap(), "k v w\na 1 x\n");
+        assert_eq!(join(&["--header", "-o", "auto"], "", "k w z\n").unwrap(), "k w z\n");
+        assert_eq!(join(&["--header", "--check-order"], "z\na\n", "y\na\n").unwrap(), "z\na\n");
+    }
+
+    #[test]
+    fn order() {
+        assert_eq!(join(&[], "b\na\n", "b\na\n").unwrap(), "b\na\n");
+        assert_eq!(join(&[], "b\nc\na\n", "c\n").unwrap_err(), "input is not in sorted order");
+        assert_eq!(join(&[], "b\na\n", "b\n").unwrap(), "b\n");
+        assert_eq!(
+            join(&["--check-order"], "b\na 1\n", "b\n").unwrap_err(),
+            "a:2: is not sorted: a 1"
+        );
+        assert_eq!(join(&["--nocheck-order"], "a\nc\nb\n", "b\n").unwrap(), "");
+        assert_eq!(
+            join(&["--nocheck-order", "--check-order"], "c\nb\n", "c\n").unwrap_err(),
+            "a:2: is not sorted: b"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = |args: &[&str]| {
+            let matches = cli::create_app().get_matches_from(["join"].iter().chain(args));
+            Options::from_matches(&matches).unwrap_err()
+        };
+        assert_eq!(error(&["-t", "ab"]), "multi-character tab 'ab'");
+        assert_eq!(error(&["-t", ",", "-t", ":"]), "incompatible tabs");
+        assert_eq!(error(&["-j", "2", "-1", "1"]), "incompatible join fields 2, 1");
+        assert_eq!(error(&["-a", "3"]), "invalid field number: '3'");
+        assert_eq!(error(&["-1", "0"]), "invalid field number: '0'");
+        assert_eq!(error(&["-o", "3.1"]), "invalid file number in field spec: '3.1'");
+        assert_eq!(error(&["-o", "1x"]), "invalid field specifier: '1x'");
+        assert_eq!(error(&["-o", "1.x"]), "invalid field number: 'x'");
+    }
+}
@@ -0,0 +1,147 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("TARGET")
+                .help("Targets of the links, and name of the link or directory to put them in.")
+                .long_help(
+                    "Targets of the links, and name of the link or directory to put them \
+                     in.\n\nWith a single TARGET the link is put in the current directory, with \
+                     the name of the TARGET. With more than two operands the last one is the \
+                     directory the links are put in.",
+                )
+                .value_names(&["TARGET", "LINK_NAME"])
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("backup")
+                .help("Make a backup of each existing destination file.")
+                .long_help(
+                    "Make a backup of each existing destination file.\n\nCONTROL is 'none' or \
+                     'off' to never make backups, 'numbered' or 't' to make numbered backups, \
+                     'existing' or 'nil' to make numbered backups of the files that have them and \
+                     simple backups of the others, or 'simple' or 'never' to make simple backups. \
+                     Without it the VERSION_CONTROL environment variable is used, and 'existing' \
+                     by default.",
+                )
+                .long("backup")
+                .value_name("CONTROL")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&[
+                    "none", "off", "numbered", "t", "existing", "nil", "simple", "never",
+                ]),
+        )
+        .arg(
+            Arg::with_name("backupDefault")
+                .help("Same as --backup but does not accept a argument.")
+                .short("b"),
+        )
+        .arg(
+            Arg::with_name("force")
+                .help("Replace the existing destination files.")
+                .long_help(
+                    "Replace the existing destination files.\n\nThe new link is made with other \
+                     name and then renamed to the destination, so it is never missing.\n\nThis \
+                     option overrides any previous -i option.",
+                )
+                .long("force")
+                .short("f"),
+        )
+        .arg(
+            Arg::with_name("interactive")
+                .help("Prompt whether to replace the existing destination files.")
+                .long_help(
+                    "Prompt whether to replace the existing destination files.\n\nThis option \
+                     overrides any previous -f option.",
+                )
+                .long("interactive")
+                .short("i"),
+        )
+        .arg(
+            Arg::with_name("logical")
+                .help("Make hard links to the files the symbolic link TARGETs point to.")
+                .long_help(
+                    "Make hard links to the files the symbolic link TARGETs point to.\n\nThis \
+                     option overrides any previous -P option.",
+                )
+                .long("logical")
+                .short("L"),
+        )
+        .arg(
+            Arg::with_name("noDereference")
+                .help("Treat LINK_NAME as a normal file if it is a symbolic link to a directory.")
+                .long("no-dereference")
+                .short("n"),
+        )
+        .arg(
+            Arg::with_name("physical")
+                .help("Make hard links to the symbolic link TARGETs themselves, the default.")
+                .long_help(
+                    "Make hard links to the symbolic link TARGETs themselves, the \
+                     default.\n\nThis option overrides any previous -L option.",
+                )
+                .long("physical")
+                .short("P"),
+        )
+        .arg(
+            Arg::with_name("relative")
+                .help("Make the symbolic links relative to their location.")
+                .long_help(
+                    "Make the symbolic links relative to their location.\n\nThe TARGET and the \
+                     directory of the link are resolved without symbolic links, and the link \
+                     points to the path from one to the other. It requires -s.",
+                )
+                .long("relative")
+                .short("r"),
+        )
+        .arg(
+            Arg::with_name("symbolic")
+                .help("Make symbolic links instead of hard links.")
+                .long("symbolic")
+                .short("s"),
+        )
+        .arg(
+            Arg::with_name("suffix")
+                .help("Override the usual backup suffix, '~' by default.")
+                .long_help(
+                    "Override the usual backup suffix. Without it the SIMPLE_BACKUP_SUFFIX \
+                     environment variable is used, and '~' by default.\n\nIt also makes the \
+                     backups like --backup.",
+                )
+                .long("suffix")
+                .short("S")
+                .value_name("SUFFIX")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("targetDirectory")
+                .help("Put the links to all the TARGET arguments in DIRECTORY.")
+                .long("target-directory")
+                .short("t")
+                .value_name("DIRECTORY"),
+        )
+        .arg(
+            Arg::with_name("noTargetDirectory")
+                .help("Treat LINK_NAME as a normal file.")
+                .long("no-target-directory")
+                .short("T"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .help("Print the name of each linked file.")
+                .long("verbose")
+                .short("v"),
+        )
+}
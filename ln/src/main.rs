@@ -0,0 +1,476 @@
+use std::{
+    env,
+    ffi::{CString, OsStr},
+    fs, io,
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{symlink, MetadataExt},
+    },
+    path::{Path, PathBuf},
+    process,
+};
+
+use clap::ArgMatches;
+use coreutils_core::{
+    backup::*,
+    canonicalize::{canonicalize, relative_path, Missing},
+    error::strerror,
+    input::*,
+    libc,
+    target::{find_target, Error, Target},
+};
+
+mod cli;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let flags = LnFlags::from_matches(&matches);
+
+    let operands: Vec<&Path> = matches
+        .values_of("TARGET")
+        .map(|values| values.map(Path::new).collect())
+        .unwrap_or_default();
+
+    let target_directory = matches.value_of("targetDirectory").map(Path::new);
+    let is_default = target_directory.is_none() && !flags.no_target_directory;
+
+    let target = match *operands.as_slice() {
+        // Without LINK_NAME the link is put in the current directory
+        [target] if is_default => {
+            Ok(Target::Directory { sources: vec![target], directory: Path::new(".") })
+        },
+        // With -n a symbolic link to a directory is a normal LINK_NAME
+        [ref targets @ .., link_name]
+            if is_default && flags.no_dereference && is_symlink(link_name) =>
+        {
+            match *targets {
+                [target] => Ok(Target::File { source: target, dest: link_name }),
+                _ => Err(Error::NotADirectory(link_name.display().to_string(), false)),
+            }
+        },
+        _ => find_target(&operands, target_directory, flags.no_target_directory),
+    };
+    let success = match target {
+        Ok(Target::Directory { sources, directory }) => sources
+            .iter()
+            .map(|target| link(target, &link_in(directory, target), &flags))
+            .fold(true, |success, linked| success & linked),
+        Ok(Target::File { source, dest }) => link(source, dest, &flags),
+        Err(err) => {
+            eprintln!("ln: {}", err);
+            false
+        },
+    };
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// What is done with the destination files that exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Existing {
+    /// The link is not made.
+    Fail,
+    /// The user is asked whether to replace each of them, with `-i`.
+    Ask,
+    /// They are replaced, with `-f`.
+    Replace,
+}
+
+#[derive(Debug, Clone)]
+struct LnFlags {
+    existing: Existing,
+    backup: BackupMode,
+    suffix: String,
+    /// The hard links are made to the files the symbolic links point to, with `-L`.
+    logical: bool,
+    no_dereference: bool,
+    no_target_directory: bool,
+    relative: bool,
+    symbolic: bool,
+    verbose: bool,
+}
+
+impl LnFlags {
+    fn from_matches(matches: &ArgMatches) -> Self {
+        // The last of -f and -i is the one used
+        let existing = match (matches.index_of("force"), matches.index_of("interactive")) {
+            (Some(force), Some(interactive)) if interactive > force => Existing::Ask,
+            (Some(_), _) => Existing::Replace,
+            (None, Some(_)) => Existing::Ask,
+            (None, None) => Existing::Fail,
+        };
+
+        // The last of -L and -P is the one used
+        let logical = match (matches.index_of("logical"), matches.index_of("physical")) {
+            (Some(logical), Some(physical)) => logical > physical,
+            (logical, _) => logical.is_some(),
+        };
+
+        let backup = if matches.is_present("backup")
+            || matches.is_present("backupDefault")
+            || matches.is_present("suffix")
+        {
+            match matches.value_of("backup") {
+                Some(control) => BackupMode::from(control),
+                None => match env::var("VERSION_CONTROL") {
+                    Ok(control) if control.is_empty() => BackupMode::Existing,
+                    Ok(control) if BACKUP_CONTROLS.contains(&control.as_str()) => {
+                        BackupMode::from(control.as_str())
+                    },
+                    Ok(control) => {
+                        eprintln!("ln: invalid argument '{}' for '$VERSION_CONTROL'", control);
+                        process::exit(1);
+                    },
+                    Err(_) => BackupMode::Existing,
+                },
+            }
+        } else {
+            BackupMode::None
+        };
+
+        // A suffix with a slash would put the backups in other directory
+        let suffix = match matches.value_of("suffix") {
+            Some(suffix) => suffix.to_string(),
+            None => env::var("SIMPLE_BACKUP_SUFFIX").unwrap_or_default(),
+        };
+        let suffix =
+            if suffix.is_empty() || suffix.contains('/') { "~".to_string() } else { suffix };
+
+        let flags = LnFlags {
+            existing,
+            backup,
+            suffix,
+            logical,
+            no_dereference: matches.is_present("noDereference"),
+            no_target_directory: matches.is_present("noTargetDirectory"),
+            relative: matches.is_present("relative"),
+            symbolic: matches.is_present("symbolic"),
+            verbose: matches.is_present("verbose"),
+        };
+
+        if flags.relative && !flags.symbolic {
+            eprintln!("ln: cannot do --relative without --symbolic");
+            process::exit(1);
+        }
+
+        flags
+    }
+}
+
+/// The values of `--backup` and the `VERSION_CONTROL` environment variable.
+const BACKUP_CONTROLS: [&str; 8] =
+    ["none", "off", "numbered", "t", "existing", "nil", "simple", "never"];
+
+/// Returns `true` if the `path` is a symbolic link.
+fn is_symlink(path: &Path) -> bool {
+    matches!(fs::symlink_metadata(path), Ok(metadata) if metadata.file_type().is_symlink())
+}
+
+/// The link to `target` put in the `directory`, with the name of the target.
+fn link_in(directory: &Path, target: &Path) -> PathBuf {
+    directory.join(target.file_name().unwrap_or(target.as_os_str()))
+}
+
+/// Make the link to `target` in `dest`, returning `false` if it failed.
+fn link(target: &Path, dest: &Path, flags: &LnFlags) -> bool {
+    if !flags.symbolic {
+        match target_metadata(target, flags) {
+            Ok(metadata) if metadata.is_dir() => {
+                eprintln!("ln: {}: hard link not allowed for directory", target.display());
+                return false;
+            },
+            Ok(_) => {},
+            Err(err) => {
+                eprintln!("ln: failed to access '{}': {}", target.display(), strerror(&err));
+                return false;
+            },
+        }
+    }
+
+    // What the symbolic link points to
+    let contents = if flags.relative {
+        match relative_target(target, dest) {
+            Ok(contents) => contents,
+            Err(err) => {
+                eprintln!("ln: cannot resolve '{}': {}", target.display(), strerror(&err));
+                return false;
+            },
+        }
+    } else {
+        target.to_path_buf()
+    };
+
+    let mut backup = None;
+    let mut replace = false;
+
+    if let Ok(dest_metadata) = fs::symlink_metadata(dest) {
+        if flags.existing != Existing::Fail || flags.backup != BackupMode::None {
+            if dest_metadata.is_dir() {
+                eprintln!("ln: {}: cannot overwrite directory", dest.display());
+                return false;
+            }
+
+            if same_file(target, &contents, dest, &dest_metadata, flags) {
+                eprintln!("ln: '{}' and '{}' are the same file", target.display(), dest.display());
+                return false;
+            }
+        }
+
+        if flags.existing == Existing::Ask {
+            let is_affirmative = Input::new()
+                .with_msg(&format!("ln: replace '{}'? ", dest.display()))
+                .with_err_msg("ln: could not read user input")
+                .is_affirmative();
+
+            if !is_affirmative {
+                return true;
+            }
+        }
+
+        let made = match flags.backup {
+            BackupMode::None => Ok(None),
+            BackupMode::Numbered => create_numbered_backup(dest).map(Some),
+            BackupMode::Existing => create_existing_backup(dest, &flags.suffix).map(Some),
+            BackupMode::Simple => create_simple_backup(dest, &flags.suffix).map(Some),
+        };
+
+        match made {
+            Ok(made) => backup = made,
+            Err(err) => {
+                eprintln!("ln: cannot backup '{}': {}", dest.display(), strerror(&err));
+                return false;
+            },
+        }
+
+        replace = backup.is_none() && flags.existing != Existing::Fail;
+    }
+
+    let linked = if replace {
+        replace_link(&contents, dest, flags)
+    } else {
+        make_link(&contents, dest, flags)
+    };
+
+    if let Err(err) = linked {
+        link_error(&contents, dest, &err, flags);
+
+        // The file in the destination is put back
+        if let Some(backup) = backup {
+            let _ = fs::rename(backup, dest);
+        }
+
+        return false;
+    }
+
+    if flags.verbose {
+        let arrow = if flags.symbolic { "->" } else { "=>" };
+
+        match backup {
+            Some(backup) => println!(
+                "'{}' ~ '{}' {} '{}'",
+                backup.display(),
+                dest.display(),
+                arrow,
+                contents.display()
+            ),
+            None => println!("'{}' {} '{}'", dest.display(), arrow, contents.display()),
+        }
+    }
+
+    true
+}
+
+/// Get what the symbolic link in `dest` points to with `-r`, the path to `target`
+/// relative to the directory of `dest`, both resolved without symbolic links.
+///
+/// # Errors
+/// If they can't be resolved, an error variant will be returned.
+fn relative_target(target: &Path, dest: &Path) -> io::Result<PathBuf> {
+    let target = canonicalize(target, Missing::Any)?;
+
+    let directory = match dest.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let directory = canonicalize(directory, Missing::Any)?;
+
+    Ok(relative_path(target, directory))
+}
+
+/// Returns `true` if the existing `dest`, with the `dest_metadata`, is the file that
+/// would be linked, the `target` of a hard link or the file the `contents` of a symbolic
+/// link point to, so replacing it would remove the file.
+///
+/// Like GNU, it isn't if `dest` is other hard link of the file, that can be replaced.
+fn same_file(
+    target: &Path, contents: &Path, dest: &Path, dest_metadata: &fs::Metadata, flags: &LnFlags,
+) -> bool {
+    let (source, metadata) = if flags.symbolic {
+        // With backups the file is renamed, so the link still points to it
+        if flags.backup != BackupMode::None {
+            return false;
+        }
+
+        let directory = dest.parent().unwrap_or_else(|| Path::new(""));
+        let source = directory.join(contents);
+        let metadata = fs::metadata(&source);
+        (source, metadata)
+    } else {
+        (target.to_path_buf(), target_metadata(target, flags))
+    };
+
+    matches!(metadata, Ok(metadata)
+        if metadata.dev() == dest_metadata.dev()
+            && metadata.ino() == dest_metadata.ino()
+            && (metadata.nlink() == 1 || is_same_entry(&source, dest)))
+}
+
+/// Get the metadata of the `target` of a hard link, or of the file it points to if it is
+/// a symbolic link and `-L`.
+fn target_metadata(target: &Path, flags: &LnFlags) -> io::Result<fs::Metadata> {
+    if flags.logical {
+        fs::metadata(target)
+    } else {
+        fs::symlink_metadata(target)
+    }
+}
+
+/// Make the link in `dest`, a symbolic link with the `contents` with `-s` or a hard link
+/// to the file in them.
+fn make_link(contents: &Path, dest: &Path, flags: &LnFlags) -> io::Result<()> {
+    if flags.symbolic {
+        return symlink(contents, dest);
+    }
+
+    let contents = CString::new(contents.as_os_str().as_bytes())?;
+    let dest = CString::new(dest.as_os_str().as_bytes())?;
+    let follow = if flags.logical { libc::AT_SYMLINK_FOLLOW } else { 0 };
+
+    let res = unsafe {
+        libc::linkat(libc::AT_FDCWD, contents.as_ptr(), libc::AT_FDCWD, dest.as_ptr(), follow)
+    };
+
+    match res {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Make the link in `dest` like [`make_link`], replacing the file in it.
+///
+/// The link is made with a temporary name in the same directory and then renamed to
+/// `dest`, so the destination is never missing.
+fn replace_link(contents: &Path, dest: &Path, flags: &LnFlags) -> io::Result<()> {
+    let directory = dest.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut attempt = 0;
+    let temp = loop {
+        let temp = directory.join(format!(".ln{}.{}", process::id(), attempt));
+
+        match make_link(contents, &temp, flags) {
+            Ok(()) => break temp,
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+            Err(err) => return Err(err),
+        }
+    };
+
+    // `inspect_err` is newer than the MSRV
+    #[allow(clippy::manual_inspect)]
+    let renamed = fs::rename(&temp, dest).map_err(|err| {
+        let _ = fs::remove_file(&temp);
+        err
+    });
+
+    // Renaming over other hard link of the same file does nothing
+    if renamed.is_ok() && fs::symlink_metadata(&temp).is_ok() {
+        fs::remove_file(&temp)?;
+    }
+
+    renamed
+}
+
+/// Print the error of the link in `dest`, with the `contents` of a symbolic link or the
+/// target of a hard link when they are the problem.
+fn link_error(contents: &Path, dest: &Path, err: &io::Error, flags: &LnFlags) {
+    let errno = err.raw_os_error();
+    let kind = if flags.symbolic { "symbolic" } else { "hard" };
+
+    let to_target = if flags.symbolic {
+        errno == Some(libc::ENAMETOOLONG) || contents.as_os_str() == OsStr::new("")
+    } else {
+        ![libc::EDQUOT, libc::EEXIST, libc::ENOSPC, libc::EROFS].iter().any(|&e| errno == Some(e))
+    };
+
+    if !flags.symbolic && errno == Some(libc::EMLINK) {
+        eprintln!("ln: failed to create hard link to '{}': {}", contents.display(), strerror(err));
+    } else if to_target {
+        let arrow = if flags.symbolic { "->" } else { "=>" };
+        eprintln!(
+            "ln: failed to create {} link '{}' {} '{}': {}",
+            kind,
+            dest.display(),
+            arrow,
+            contents.display(),
+            strerror(err)
+        );
+    } else {
+        eprintln!("ln: failed to create {} link '{}': {}", kind, dest.display(), strerror(err));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(args: &[&str]) -> LnFlags {
+        let args = ["ln"].iter().chain(args).chain(&["a", "b"]);
+        LnFlags::from_matches(&cli::create_app().get_matches_from(args))
+    }
+
+    #[test]
+    fn existing() {
+        assert_eq!(flags(&[]).existing, Existing::Fail);
+        assert_eq!(flags(&["-f"]).existing, Existing::Replace);
+        assert_eq!(flags(&["-f", "-i"]).existing, Existing::Ask);
+        assert_eq!(flags(&["-i", "-f"]).existing, Existing::Replace);
+    }
+
+    #[test]
+    fn logical() {
+        assert!(!flags(&[]).logical);
+        assert!(flags(&["-L"]).logical);
+        assert!(!flags(&["-L", "-P"]).logical);
+        assert!(flags(&["-P", "-L"]).logical);
+    }
+
+    #[test]
+    fn backup() {
+        assert_eq!(flags(&[]).backup, BackupMode::None);
+        assert_eq!(flags(&["--backup=t"]).backup, BackupMode::Numbered);
+        assert_eq!(flags(&["-S", ".old"]).suffix, ".old");
+        assert_eq!(flags(&["-b", "--suffix=x/y"]).suffix, "~");
+    }
+
+    #[test]
+    fn replace_hard_link() {
+        let dir = env::temp_dir().join(format!("ln.same.{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (target, dest) = (dir.join("target"), dir.join("dest"));
+        fs::write(&target, b"same").unwrap();
+        fs::hard_link(&target, &dest).unwrap();
+
+        assert!(link(&target, &dest, &flags(&["-f"])));
+        assert!(link(&target, &dest, &flags(&["-b"])));
+        assert_eq!(fs::read(dir.join("dest~")).unwrap(), b"same");
+        assert_eq!(fs::metadata(&target).unwrap().nlink(), 3);
+
+        // Replacing the same entry would remove the file
+        assert!(!link(&target, &dir.join(".").join("target"), &flags(&["-f"])));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -40,16 +40,19 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("file_status_modification")
                 .help(
                     "Use time of last modification of the file status information instead of last \
-                     modification of the file itself for sorting -t or writing -l.",
+                     modification of the file itself for sorting -t or writing -l. Without -l, \
+                     also sort by it.",
                 )
                 .short("c")
-                .long("file-status-modification"),
+                .long("file-status-modification")
+                .overrides_with("last_accessed"),
         )
         .arg(
             Arg::with_name("order_top_to_bottom")
                 .help("Write multi-text-column output with entries sorted down the columns.")
                 .short("C")
-                .long("order-top-to-bottom"),
+                .long("order-top-to-bottom")
+                .overrides_with_all(&["order_left_to_right", "one_per_line"]),
         )
         .arg(
             Arg::with_name("directory")
@@ -72,6 +75,39 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .short("F")
                 .long("classify"),
         )
+        .arg(
+            Arg::with_name("color")
+                .help(
+                    "Color the file names by their type, with the colors of LS_COLORS. WHEN can \
+                     be always, auto (if the output is a terminal, the default) or never.",
+                )
+                .long("color")
+                .value_name("WHEN")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&[
+                    "always", "yes", "force", "never", "no", "none", "auto", "tty", "if-tty",
+                ])
+                .hide_possible_values(true),
+        )
+        .arg(
+            Arg::with_name("hyperlink")
+                .help(
+                    "Link the file names to their files, for the terminals that support the OSC 8 \
+                     escapes. WHEN can be always (the default without it), auto (if the output is \
+                     a terminal) or never (the default).",
+                )
+                .long("hyperlink")
+                .value_name("WHEN")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&[
+                    "always", "yes", "force", "never", "no", "none", "auto", "tty", "if-tty",
+                ])
+                .hide_possible_values(true),
+        )
         .arg(
             Arg::with_name("no_dereference")
                 .help("Follow symbolic links listed on the command line.")
@@ -79,13 +115,26 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .long("no-dereference"),
         )
         .arg(
-            Arg::with_name("block_size")
+            Arg::with_name("kibibytes")
                 .help(
                     "Set the block size for the -s option and the per-directory block count \
                      written for the -l, -n, -s, -g, and -o options to 1024 bytes.",
                 )
                 .short("k")
-                .long("block-size"),
+                .long("kibibytes")
+                .overrides_with("block_size"),
+        )
+        .arg(
+            Arg::with_name("block_size")
+                .help(
+                    "Set the block size for the -s option and the per-directory block count to \
+                     SIZE, a number of bytes with an optional multiple like 1K or 1MB. With only \
+                     the multiple, like K, it is appended to the counts.",
+                )
+                .long("block-size")
+                .value_name("SIZE")
+                .takes_value(true)
+                .overrides_with("kibibytes"),
         )
         .arg(
             Arg::with_name("comma_separate")
@@ -118,12 +167,19 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("last_accessed")
                 .help(
                     "Use time of last access instead of last modification of the file for sorting \
-                     -t or writing -l.",
+                     -t or writing -l. Without -l, also sort by it.",
                 )
                 .short("u")
-                .long("last-accessed"),
+                .long("last-accessed")
+                .overrides_with("file_status_modification"),
         )
         .arg(Arg::with_name("list").help("Use a long listing format").short("l").long("list"))
+        .arg(
+            Arg::with_name("context")
+                .help("For each file, write the file's SELinux security context, or ? without one.")
+                .short("Z")
+                .long("context"),
+        )
         .arg(
             Arg::with_name("no_owner")
                 .help("Like -l, but do not list owner.")
@@ -174,24 +230,121 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("sort_size")
                 .help("Sort by first file size, largest first.")
                 .short("S")
-                .long("sort-size"),
+                .long("sort-size")
+                .overrides_with_all(&[
+                    "time",
+                    "sort_version",
+                    "sort_extension",
+                    "unsorted",
+                    "sort",
+                ]),
         )
         .arg(
             Arg::with_name("time")
                 .help("Sort by modification time, newest first.")
                 .short("t")
-                .long("time"),
+                .long("time")
+                .overrides_with_all(&[
+                    "sort_size",
+                    "sort_version",
+                    "sort_extension",
+                    "unsorted",
+                    "sort",
+                ]),
+        )
+        .arg(
+            Arg::with_name("time_style")
+                .help(
+                    "Display the times of the long listing with the STYLE: full-iso, long-iso, \
+                     iso, locale (the default) or +FORMAT of strftime. With \
+                     FORMAT1<newline>FORMAT2, the FORMAT1 is for the old files and the FORMAT2 \
+                     for the recent ones. By default it is the TIME_STYLE environment variable.",
+                )
+                .long("time-style")
+                .value_name("STYLE")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("sort_version")
+                .help("Sort by the version numbers in the file names, like 1.9 before 1.10.")
+                .short("v")
+                .long("sort-version")
+                .overrides_with_all(&["sort_size", "time", "sort_extension", "unsorted", "sort"]),
+        )
+        .arg(
+            Arg::with_name("sort_extension")
+                .help("Sort alphabetically by the extension of the file names.")
+                .short("X")
+                .long("sort-extension")
+                .overrides_with_all(&["sort_size", "time", "sort_version", "unsorted", "sort"]),
+        )
+        .arg(
+            Arg::with_name("unsorted")
+                .help("Do not sort, listing the files in the order of the directory.")
+                .short("U")
+                .long("unsorted")
+                .overrides_with_all(&[
+                    "sort_size",
+                    "time",
+                    "sort_version",
+                    "sort_extension",
+                    "sort",
+                ]),
+        )
+        .arg(
+            Arg::with_name("sort")
+                .help(
+                    "Sort by WORD instead of the name: none (-U), size (-S), time (-t), version \
+                     (-v) or extension (-X).",
+                )
+                .long("sort")
+                .value_name("WORD")
+                .takes_value(true)
+                .possible_values(&["none", "size", "time", "version", "extension"])
+                .overrides_with_all(&[
+                    "sort_size",
+                    "time",
+                    "sort_version",
+                    "sort_extension",
+                    "unsorted",
+                ]),
+        )
+        .arg(
+            Arg::with_name("group_directories_first")
+                .help(
+                    "List the directories before the other files, each sorted on its own. It is \
+                     disabled by --sort=none (-U).",
+                )
+                .long("group-directories-first"),
         )
         .arg(
             Arg::with_name("order_left_to_right")
                 .help("Sort columns left to right.")
                 .short("x")
-                .long("order-left-to-right"),
+                .long("order-left-to-right")
+                .overrides_with_all(&["order_top_to_bottom", "one_per_line"]),
         )
         .arg(
             Arg::with_name("one_per_line")
                 .help("Force output to be one entry per line.")
                 .short("1")
-                .long("one-per-line"),
+                .long("one-per-line")
+                .overrides_with_all(&["order_top_to_bottom", "order_left_to_right"]),
+        )
+        .arg(
+            Arg::with_name("width")
+                .help(
+                    "Set the width of the lines of the multi-text-column output to COLS, or no \
+                     limit with 0. By default it is the COLUMNS environment variable, or the \
+                     width of the terminal, or 80.",
+                )
+                .short("w")
+                .long("width")
+                .value_name("COLS")
+                .takes_value(true)
+                .validator(|cols| match cols.parse::<usize>() {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(format!("invalid line width: '{}'", cols)),
+                }),
         )
 }
@@ -1,4 +1,6 @@
 use std::{
+    env,
+    fmt::Write,
     fs, io,
     os::unix::{
         ffi::OsStrExt,
@@ -8,21 +10,18 @@ use std::{
     string::String,
 };
 
-use ansi_term::Color;
-use chrono::{DateTime, Local, TimeZone};
+use chrono::{DateTime, Duration, Local, TimeZone};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use coreutils_core::os::selinux;
 use coreutils_core::{
-    os::{group::Group, passwd::Passwd, tty::is_tty},
+    libc,
+    ls_colors::{Indicator, LsColors},
+    os::{group::Group, hostname::hostname, passwd::Passwd},
     BStr, BString,
 };
 
 use crate::flags::Flags;
 
-#[derive(PartialEq, Eq)]
-pub(crate) enum FileColor {
-    Show,
-    Hide,
-}
-
 pub(crate) type Files = Vec<File>;
 
 /// Represents a file and it's properties
@@ -38,16 +37,6 @@ impl File {
     pub fn from(path: PathBuf, flags: Flags) -> io::Result<Self> {
         let metadata = File::metadata(&path, &flags)?;
 
-        if flags.dereference && metadata.file_type().is_symlink() {
-            let symlink = fs::read_link(path)?;
-
-            let name = File::path_buf_to_file_name(&symlink)?;
-
-            let metadata = File::metadata(&symlink, &flags)?;
-
-            return Ok(File { name, path: symlink, metadata, flags });
-        }
-
         let name = File::path_buf_to_file_name(&path)?;
 
         Ok(File { name, path, metadata, flags })
@@ -61,16 +50,26 @@ impl File {
     }
 
     /// Retrieves the number of blocks allocated to a file as a string
-    pub fn blocks(&self) -> u64 {
-        let blocks = self.metadata.blocks();
+    pub fn blocks(&self) -> String {
+        File::scale_blocks(self.metadata.blocks(), &self.flags)
+    }
 
-        if self.flags.block_size {
-            let st_size = blocks * 512;
+    /// Converts a number of 512 bytes blocks to the block size of the flags, rounding
+    /// up, as a string
+    pub fn scale_blocks(blocks: u64, flags: &Flags) -> String {
+        File::scale_bytes(u128::from(blocks) * 512, flags)
+    }
 
-            st_size / 1024
-        } else {
-            blocks
-        }
+    /// Converts a number of bytes to the block size of the flags, rounding up, as a
+    /// string
+    fn scale_bytes(bytes: u128, flags: &Flags) -> String {
+        let block_size = u128::from(flags.block_size);
+
+        // `div_ceil` is newer than the MSRV
+        #[allow(clippy::manual_div_ceil)]
+        let scaled = (bytes + block_size - 1) / block_size;
+
+        format!("{}{}", scaled, flags.block_suffix.unwrap_or(""))
     }
 
     /// Retrieves a files permissions as a string
@@ -80,11 +79,57 @@ impl File {
         unix_mode::to_string(mode)
     }
 
+    /// Checks if the file has an access control list besides its permissions
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn has_acl(&self) -> bool {
+        use std::{ffi::CString, ptr};
+
+        // Symbolic links have no access control lists of their own
+        if self.metadata.file_type().is_symlink() {
+            return false;
+        }
+
+        let path = match CString::new(self.path.as_os_str().as_bytes()) {
+            Ok(path) => path,
+            Err(_) => return false,
+        };
+
+        let size = |name: &[u8]| unsafe {
+            libc::getxattr(path.as_ptr(), name.as_ptr() as *const libc::c_char, ptr::null_mut(), 0)
+        };
+
+        // The list of the permissions alone has a header and three entries
+        size(b"system.posix_acl_access\0") > 28 || size(b"system.posix_acl_default\0") > 0
+    }
+
+    /// Checks if the file has an access control list besides its permissions
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn has_acl(&self) -> bool {
+        false
+    }
+
     /// Retrieves the number of hard links pointing to a file as a string
     pub fn hard_links(&self) -> String {
         self.metadata.nlink().to_string()
     }
 
+    /// Retrieves the SELinux security context as a string, or `?` if it has none
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn context(&self) -> String {
+        let follow = self.flags.dereference || self.flags.no_dereference;
+
+        match selinux::file_context(&self.path, follow) {
+            Ok(context) => context.to_string(),
+            Err(_) => String::from("?"),
+        }
+    }
+
+    /// Retrieves the SELinux security context as a string, or `?` if it has none
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn context(&self) -> String {
+        String::from("?")
+    }
+
     /// Retrieves the inode number as a string
     pub fn inode(&self) -> String {
         self.metadata.ino().to_string()
@@ -116,9 +161,28 @@ impl File {
         }
     }
 
-    /// Retrieve the file's size, in bytes, as a string
+    /// Retrieve the file's size, in bytes or in the blocks of `--block-size`, as a string
     pub fn size(&self) -> String {
-        self.metadata.len().to_string()
+        if self.flags.scale_sizes {
+            File::scale_bytes(u128::from(self.metadata.len()), &self.flags)
+        } else {
+            self.metadata.len().to_string()
+        }
+    }
+
+    /// Retrieves the major and minor numbers of a character or block device as
+    /// strings
+    pub fn device(&self) -> Option<(String, String)> {
+        let file_type = self.metadata.file_type();
+
+        if !file_type.is_char_device() && !file_type.is_block_device() {
+            return None;
+        }
+
+        let dev = self.metadata.rdev() as libc::dev_t;
+        let (major, minor) = (libc::major(dev) as u64, libc::minor(dev) as u64);
+
+        Some((major.to_string(), minor.to_string()))
     }
 
     /// Retrieves the file's timestamp as a string
@@ -126,8 +190,8 @@ impl File {
     /// By default the file's modified time is displayed. The `-u` flag will
     /// display the last accessed time. The `-c` flag will display the last
     /// modified time of the file's status information. The date format used is
-    /// `%b %e %H:%M` unless the duration is greater than six months, which case
-    /// the date format will be `%b %e  %Y`.
+    /// `%b %e %H:%M` unless the time is more than six months ago or in the future,
+    /// which case the date format will be `%b %e  %Y`.
     pub fn time(&self) -> String {
         let (secs, nsecs) = if self.flags.last_accessed {
             // Retrieve the files last accessed time
@@ -143,19 +207,45 @@ impl File {
 
         let datetime: DateTime<Local> = Local.timestamp(secs, nsecs as u32);
 
-        let mut fmt = "%b %e %H:%M";
-
         let now: DateTime<Local> = Local::now();
 
-        let duration = datetime.signed_duration_since(now);
+        // Half of the average gregorian year
+        let six_months_ago = now - Duration::seconds(31_556_952 / 2);
+
+        let (old, recent) = self.flags.time_style.formats();
+
+        let fmt = if datetime > six_months_ago && datetime <= now { recent } else { old };
 
-        let six_months = -182;
+        File::format_time(&datetime, fmt)
+    }
+
+    /// Formats the `datetime` with the `strftime` conversions of `fmt`, where `%N` is the
+    /// nanoseconds, or displays `fmt` as is if it has a invalid conversion
+    fn format_time(datetime: &DateTime<Local>, fmt: &str) -> String {
+        let mut chrono_fmt = String::with_capacity(fmt.len());
+        let mut chars = fmt.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                chrono_fmt.push(c);
+                continue;
+            }
 
-        if duration.num_days() < six_months {
-            fmt = "%b %e  %Y";
+            match chars.next() {
+                Some('N') => chrono_fmt.push_str("%9f"),
+                Some(next) => {
+                    chrono_fmt.push('%');
+                    chrono_fmt.push(next);
+                },
+                None => chrono_fmt.push('%'),
+            }
         }
 
-        datetime.format(fmt).to_string()
+        let mut result = String::new();
+        match write!(result, "{}", datetime.format(&chrono_fmt)) {
+            Ok(_) => result,
+            Err(_) => fmt.to_string(),
+        }
     }
 
     /// Check if a path is an executable file
@@ -192,39 +282,46 @@ impl File {
 
     /// Retrieves the metadata from a `PathBuf`.
     ///
-    /// Symbolic links will be followed if the `-H` flag is present.
+    /// Symbolic links will be followed if the `-H` or `-L` flag is present, unless
+    /// their target is missing.
     pub fn metadata(path: &Path, flags: &Flags) -> io::Result<fs::Metadata> {
-        if flags.no_dereference { fs::metadata(path) } else { fs::symlink_metadata(path) }
+        if flags.no_dereference || flags.dereference {
+            fs::metadata(path).or_else(|_| fs::symlink_metadata(path))
+        } else {
+            fs::symlink_metadata(path)
+        }
     }
 
     /// Gets a file name from a directory entry and adds appropriate formatting
-    pub fn file_name(&self, color: FileColor) -> String {
-        // Determine if the file name should have a color applied.
-        let show_color = color == FileColor::Show && is_tty(&io::stdout());
+    pub fn file_name(&self, colors: Option<&LsColors>) -> String {
+        self.format_name(colors, self.flags)
+    }
 
-        let file_name = self.name.clone();
+    /// Gets the number of chars the file name takes on the terminal
+    pub fn name_width(&self) -> usize {
+        self.format_name(None, Flags { hyperlink: false, ..self.flags }).chars().count()
+    }
 
-        let mut result = file_name.to_string();
+    /// Formats the file name with the colors and indicators of the `flags`
+    fn format_name(&self, colors: Option<&LsColors>, flags: Flags) -> String {
+        let file_name = self.name.to_string();
 
         let file_type = self.metadata.file_type();
 
-        let flags = self.flags;
+        let link = File::hyperlink(&self.path, &file_name, &flags);
 
-        if File::is_executable(&self.path) {
-            if show_color {
-                result = self.add_executable_color(&file_name);
-            }
+        let mut result = match colors {
+            Some(colors) => {
+                File::paint(colors, &file_name, &link, &self.path, Some(&self.metadata))
+            },
+            None => link,
+        };
 
-            if flags.classify {
-                result = format!("{}*", result);
-            }
+        if File::is_executable(&self.path) && flags.classify {
+            result = format!("{}*", result);
         }
 
         if file_type.is_symlink() && !flags.dereference {
-            if show_color {
-                result = self.add_symlink_color(&file_name);
-            }
-
             if flags.classify && !flags.show_list() {
                 result = format!("{}@", result);
             }
@@ -233,15 +330,33 @@ impl File {
                 let symlink = fs::read_link(self.path.clone());
 
                 if let Ok(symlink) = symlink {
-                    let symlink_name = BString::from(symlink.as_os_str().as_bytes());
-                    let mut symlink_result = symlink_name.to_string();
-
-                    if File::is_executable(&symlink) {
-                        symlink_result = self.add_executable_color(&symlink_name);
-
-                        if flags.classify {
-                            symlink_result = format!("{}*", symlink_result);
-                        }
+                    let symlink_name = BString::from(symlink.as_os_str().as_bytes()).to_string();
+
+                    // Following the link, as the target is relative to its directory
+                    let target = fs::metadata(&self.path);
+
+                    let target_path = match self.path.parent() {
+                        Some(parent) => parent.join(&symlink),
+                        None => symlink.clone(),
+                    };
+                    let link = File::hyperlink(&target_path, &symlink_name, &flags);
+
+                    let mut symlink_result = match colors {
+                        Some(colors) if colors.checks_link_targets() => File::paint(
+                            colors,
+                            &symlink_name,
+                            &link,
+                            &symlink,
+                            target.as_ref().ok(),
+                        ),
+                        _ => link,
+                    };
+
+                    let is_executable = matches!(target, Ok(target)
+                        if target.is_file() && target.permissions().mode() & 0o111 != 0);
+
+                    if is_executable && flags.classify {
+                        symlink_result = format!("{}*", symlink_result);
                     }
 
                     result = format!("{} -> {}", result, symlink_result);
@@ -249,55 +364,128 @@ impl File {
             }
         }
 
-        if file_type.is_fifo() {
-            if show_color {
-                result = self.add_fifo_color(&file_name);
-            }
+        if file_type.is_fifo() && flags.classify {
+            result = format!("{}|", result);
+        }
 
-            if flags.classify {
-                result = format!("{}|", result);
-            }
+        if self.metadata.is_dir() && (flags.classify || flags.indicator) {
+            result = format!("{}/", result);
         }
 
-        if file_type.is_char_device() && show_color {
-            result = self.add_char_device_color(&file_name);
+        result
+    }
+
+    /// Paints the `text` of the file name with the color of its type, or of a missing
+    /// file without `metadata`
+    fn paint(
+        colors: &LsColors, name: &str, text: &str, path: &Path, metadata: Option<&fs::Metadata>,
+    ) -> String {
+        let color = match metadata {
+            Some(metadata) => File::color(colors, name, path, metadata),
+            None => colors.indicator(Indicator::MissingFile),
+        };
+
+        match color {
+            Some(color) => colors.paint(color, text),
+            None => text.to_string(),
+        }
+    }
+
+    /// Links the `text` to the file of the `path` with a OSC 8 escape, if the flags have
+    /// `--hyperlink`
+    pub fn hyperlink(path: &Path, text: &str, flags: &Flags) -> String {
+        if !flags.hyperlink {
+            return text.to_string();
         }
 
-        if self.metadata.is_dir() {
-            if show_color {
-                result = self.add_directory_color(&file_name);
+        // The URL needs a absolute path, so a missing file, or a link to one, is joined to
+        // its directory
+        let path = fs::canonicalize(path).unwrap_or_else(|_| {
+            let parent = path.parent().unwrap_or_else(|| Path::new(""));
+            let target = fs::read_link(path).map_or(path.to_path_buf(), |link| parent.join(link));
+
+            let directory = match target.parent() {
+                Some(directory) if !directory.as_os_str().is_empty() => {
+                    fs::canonicalize(directory).unwrap_or_else(|_| directory.to_path_buf())
+                },
+                _ => env::current_dir().unwrap_or_default(),
+            };
+
+            match target.file_name() {
+                Some(name) => directory.join(name),
+                None => directory,
             }
+        });
 
-            if flags.classify || flags.indicator {
-                result = format!("{}/", result);
+        let host = hostname().map(|host| host.to_string()).unwrap_or_default();
+        let mut url = format!("file://{}", host);
+
+        for &byte in path.as_os_str().as_bytes() {
+            if byte.is_ascii_alphanumeric() || b"-_.~/".contains(&byte) {
+                url.push(byte as char);
+            } else {
+                url.push_str(&format!("%{:02X}", byte));
             }
         }
 
-        result
+        format!("\x1b]8;;{}\x07{}\x1b]8;;\x07", url, text)
     }
 
-    /// Adds a bold green color to a file name to represent an executable
-    pub fn add_executable_color(&self, file_name: &BString) -> String {
-        Color::Green.bold().paint(file_name.to_string()).to_string()
-    }
+    /// Retrieves the color of a file from its type, special permissions and suffix
+    fn color<'a>(
+        colors: &'a LsColors, name: &str, path: &Path, metadata: &fs::Metadata,
+    ) -> Option<&'a str> {
+        let file_type = metadata.file_type();
+        let mode = metadata.permissions().mode();
+
+        // The first of the colored indicators that apply to the file
+        let first_colored = |indicators: &[(bool, Indicator)]| {
+            indicators
+                .iter()
+                .filter(|(applies, _)| *applies)
+                .find_map(|&(_, indicator)| colors.indicator(indicator))
+        };
 
-    /// Adds a bold blue color to a directory name
-    pub fn add_directory_color(&self, directory_name: &BString) -> String {
-        Color::Blue.bold().paint(directory_name.to_string()).to_string()
-    }
+        if file_type.is_symlink() {
+            return match fs::metadata(path) {
+                Ok(target) if colors.link_as_target() => File::color(colors, name, path, &target),
+                Err(_)
+                    if colors.link_as_target() || colors.indicator(Indicator::Orphan).is_some() =>
+                {
+                    colors.indicator(Indicator::Orphan)
+                },
+                _ => colors.indicator(Indicator::Link),
+            };
+        }
 
-    /// Adds a yellow color on a black background to an FIFO name.
-    pub fn add_fifo_color(&self, named_pipe_name: &BString) -> String {
-        Color::Yellow.on(Color::Black).paint(named_pipe_name.to_string()).to_string()
-    }
+        if file_type.is_dir() {
+            let (sticky, other_writable) = (mode & 0o1000 != 0, mode & 0o002 != 0);
 
-    /// Adds a bold yellow color on a black background to a char device name.
-    pub fn add_char_device_color(&self, char_device_name: &BString) -> String {
-        Color::Yellow.on(Color::Black).bold().paint(char_device_name.to_string()).to_string()
-    }
+            return first_colored(&[
+                (sticky && other_writable, Indicator::StickyOtherWritable),
+                (other_writable, Indicator::OtherWritable),
+                (sticky, Indicator::Sticky),
+                (true, Indicator::Directory),
+            ]);
+        }
+
+        if file_type.is_fifo() {
+            return colors.indicator(Indicator::Fifo);
+        } else if file_type.is_socket() {
+            return colors.indicator(Indicator::Socket);
+        } else if file_type.is_block_device() {
+            return colors.indicator(Indicator::BlockDevice);
+        } else if file_type.is_char_device() {
+            return colors.indicator(Indicator::CharDevice);
+        }
 
-    /// Adds a bold cyan color to a file name to represent a symlink
-    pub fn add_symlink_color(&self, symlink_name: &BString) -> String {
-        Color::Cyan.bold().paint(symlink_name.to_string()).to_string()
+        first_colored(&[
+            (mode & 0o4000 != 0, Indicator::Setuid),
+            (mode & 0o2000 != 0, Indicator::Setgid),
+            (mode & 0o111 != 0, Indicator::Executable),
+            (metadata.nlink() > 1, Indicator::MultiHardLink),
+        ])
+        .or_else(|| colors.extension(name))
+        .or_else(|| colors.indicator(Indicator::File))
     }
 }
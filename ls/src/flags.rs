@@ -1,20 +1,28 @@
-use std::io;
+use std::{env, io, process};
 
 use clap::ArgMatches;
-use coreutils_core::os::tty::is_tty;
+use coreutils_core::{
+    os::tty::{is_tty, tty_dimensions},
+    size::parse_size,
+};
 
 /// Represents the command line arguments available to `ls`
 #[derive(Default, Copy, Clone)]
 pub(crate) struct Flags {
     pub all: bool,
     pub almost_all: bool,
-    pub block_size: bool,
+    pub block_size: u64,
+    pub block_suffix: Option<&'static str>,
     pub classify: bool,
+    pub color: bool,
     pub comma_separate: bool,
+    pub context: bool,
     pub directory: bool,
     pub dereference: bool,
     pub file_status_modification: bool,
+    pub group_directories_first: bool,
     pub hide_control_chars: bool,
+    pub hyperlink: bool,
     pub indicator: bool,
     pub inode: bool,
     pub last_accessed: bool,
@@ -29,9 +37,15 @@ pub(crate) struct Flags {
     pub order_top_to_bottom: bool,
     pub recursive: bool,
     pub reverse: bool,
+    pub scale_sizes: bool,
     pub size: bool,
+    pub sort_extension: bool,
     pub sort_size: bool,
+    pub sort_version: bool,
     pub time: bool,
+    pub time_style: TimeStyle,
+    pub unsorted: bool,
+    pub width: usize,
 }
 
 impl Flags {
@@ -39,13 +53,39 @@ impl Flags {
     pub fn from_matches(matches: &ArgMatches<'_>) -> Self {
         let all = matches.is_present("all");
         let almost_all = matches.is_present("almost_all");
-        let block_size = matches.is_present("block_size");
+        let (block_size, block_suffix) = match matches.value_of("block_size") {
+            Some(size) => match parse_block_size(size) {
+                Some(block_size) => block_size,
+                None => {
+                    eprintln!("ls: invalid --block-size argument '{}'", size);
+                    process::exit(1);
+                },
+            },
+            None if matches.is_present("kibibytes") => (1024, None),
+            None => (512, None),
+        };
         let classify = matches.is_present("classify");
+        let color = match matches.value_of("color") {
+            Some("never") | Some("no") | Some("none") => false,
+            Some("auto") | Some("tty") | Some("if-tty") => is_tty(&io::stdout()),
+            // Without a value it is always
+            _ if matches.is_present("color") => true,
+            _ => is_tty(&io::stdout()),
+        };
         let comma_separate = matches.is_present("comma_separate");
+        let context = matches.is_present("context");
         let dereference = matches.is_present("dereference");
         let directory = matches.is_present("directory");
         let file_status_modification = matches.is_present("file_status_modification");
+        let group_directories_first = matches.is_present("group_directories_first");
         let hide_control_chars = matches.is_present("hide_control_chars");
+        let hyperlink = match matches.value_of("hyperlink") {
+            Some("never") | Some("no") | Some("none") => false,
+            Some("auto") | Some("tty") | Some("if-tty") => is_tty(&io::stdout()),
+            // Without a value it is always
+            _ if matches.is_present("hyperlink") => true,
+            _ => false,
+        };
         let indicator = matches.is_present("indicator");
         let inode = matches.is_present("inode");
         let last_accessed = matches.is_present("last_accessed");
@@ -60,20 +100,54 @@ impl Flags {
         let order_top_to_bottom = matches.is_present("order_top_to_bottom");
         let recursive = matches.is_present("recursive");
         let reverse = matches.is_present("reverse");
+        // Only a explicit block size applies to the sizes of the files
+        let scale_sizes = matches.is_present("block_size");
         let size = matches.is_present("size");
-        let sort_size = matches.is_present("sort_size");
-        let time = matches.is_present("time");
+        // The `--sort` is the same as its short options
+        let sort = matches.value_of("sort");
+        let sort_extension = matches.is_present("sort_extension") || sort == Some("extension");
+        let sort_size = matches.is_present("sort_size") || sort == Some("size");
+        let sort_version = matches.is_present("sort_version") || sort == Some("version");
+        let time = matches.is_present("time") || sort == Some("time");
+        let time_style = match matches.value_of("time_style") {
+            Some(style) => Some(style.to_string()),
+            None => env::var("TIME_STYLE").ok(),
+        };
+        let time_style = match time_style {
+            Some(style) => TimeStyle::parse(&style).unwrap_or_else(|| {
+                eprintln!("ls: invalid argument '{}' for '--time-style'", style);
+                process::exit(1);
+            }),
+            None => TimeStyle::Locale,
+        };
+        let unsorted = no_sort || matches.is_present("unsorted") || sort == Some("none");
+
+        // The width is validated by the argument parser
+        let width = match matches.value_of("width") {
+            Some(width) => width.parse().unwrap_or(0),
+            None => env::var("COLUMNS")
+                .ok()
+                .and_then(|columns| columns.parse().ok())
+                .filter(|&columns| columns > 0)
+                .or_else(|| tty_dimensions(&io::stdout()).map(|(columns, _)| columns as usize))
+                .unwrap_or(80),
+        };
 
         Flags {
             all,
             almost_all,
             block_size,
+            block_suffix,
             classify,
+            color,
             comma_separate,
+            context,
             directory,
             dereference,
             file_status_modification,
+            group_directories_first,
             hide_control_chars,
+            hyperlink,
             indicator,
             inode,
             last_accessed,
@@ -88,9 +162,15 @@ impl Flags {
             order_top_to_bottom,
             recursive,
             reverse,
+            scale_sizes,
             size,
+            sort_extension,
             sort_size,
+            sort_version,
             time,
+            time_style,
+            unsorted,
+            width,
         }
     }
 
@@ -106,8 +186,100 @@ impl Flags {
             && (self.order_left_to_right || self.order_top_to_bottom || is_tty(&io::stdout()))
     }
 
+    /// Whether to sort by the time, with `-t`, or with `-u` or `-c` without a long
+    /// listing or other sorting
+    pub fn sort_time(&self) -> bool {
+        self.time
+            || ((self.last_accessed || self.file_status_modification)
+                && !self.show_list()
+                && !self.unsorted
+                && !self.sort_size
+                && !self.sort_version
+                && !self.sort_extension)
+    }
+
     /// Whether or not to show hidden files and directories
     pub fn show_hidden(&self) -> bool {
         self.all || self.almost_all || self.no_sort
     }
 }
+
+/// The styles of the times of the long listing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TimeStyle {
+    /// The date and time with the nanoseconds and the offset of the zone
+    FullIso,
+    /// The date and time to the minute
+    LongIso,
+    /// The month, day and time of the recent files, and the date of the old ones
+    Iso,
+    /// The month, day and time of the recent files, and the month, day and year of the
+    /// old ones
+    Locale,
+    /// The `strftime` formats of the old and the recent files
+    Format(&'static str, &'static str),
+}
+
+impl TimeStyle {
+    /// Parse a style of `--time-style` or the `TIME_STYLE` environment variable, ignoring
+    /// the `posix-` prefix as the times are always in the C locale.
+    fn parse(style: &str) -> Option<Self> {
+        if let Some(format) = style.strip_prefix('+') {
+            // The flags are copied to every file, so the format lives until the exit
+            let format: &'static str = Box::leak(format.to_string().into_boxed_str());
+
+            return match format.find('\n') {
+                Some(i) => Some(TimeStyle::Format(&format[..i], &format[i + 1..])),
+                None => Some(TimeStyle::Format(format, format)),
+            };
+        }
+
+        match style {
+            "full-iso" => Some(TimeStyle::FullIso),
+            "long-iso" => Some(TimeStyle::LongIso),
+            "iso" => Some(TimeStyle::Iso),
+            "locale" => Some(TimeStyle::Locale),
+            _ if style.starts_with("posix-") => Some(TimeStyle::Locale),
+            _ => None,
+        }
+    }
+
+    /// Get the formats of the old and the recent times
+    pub fn formats(self) -> (&'static str, &'static str) {
+        match self {
+            TimeStyle::FullIso => ("%Y-%m-%d %H:%M:%S.%N %z", "%Y-%m-%d %H:%M:%S.%N %z"),
+            TimeStyle::LongIso => ("%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M"),
+            TimeStyle::Iso => ("%Y-%m-%d ", "%m-%d %H:%M"),
+            TimeStyle::Locale => ("%b %e  %Y", "%b %e %H:%M"),
+            TimeStyle::Format(old, recent) => (old, recent),
+        }
+    }
+}
+
+// `#[default]` on the enum variants is newer than the MSRV
+#[allow(clippy::derivable_impls)]
+impl Default for TimeStyle {
+    fn default() -> Self {
+        TimeStyle::Locale
+    }
+}
+
+/// Parse the `--block-size` argument, a number of bytes with an optional multiple like
+/// `1K` (1024), `1KB` (1000) or `1KiB` (1024), into the bytes and the multiple to append
+/// to the counts if it had no number.
+fn parse_block_size(size: &str) -> Option<(u64, Option<&'static str>)> {
+    const SUFFIXES: [&str; 18] = [
+        "K", "KB", "KiB", "M", "MB", "MiB", "G", "GB", "GiB", "T", "TB", "TiB", "P", "PB", "PiB",
+        "E", "EB", "EiB",
+    ];
+
+    let block_size = parse_size(size, "KkMGTPE").ok().filter(|&block_size| block_size > 0)?;
+
+    // The kilo may be in lowercase
+    let suffix = SUFFIXES.iter().copied().find(|&known| {
+        known == size
+            || (known.starts_with('K') && size.starts_with('k') && known[1..] == size[1..])
+    });
+
+    Some((block_size, suffix))
+}
@@ -1,13 +1,18 @@
 use std::{
+    cmp::Ordering,
     fs,
     io::{self, BufWriter, Write},
     os::unix::{ffi::OsStrExt, fs::MetadataExt},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process,
     string::String,
 };
 
-use coreutils_core::bstr::{BString, ByteSlice};
+use coreutils_core::{
+    bstr::{BString, ByteSlice},
+    ls_colors::LsColors,
+    version_sort,
+};
 
 extern crate chrono;
 
@@ -27,249 +32,219 @@ fn main() {
     let files = matches.values_of("FILE").unwrap();
     let flags = Flags::from_matches(&matches);
 
+    // The colors are disabled if they can't be parsed
+    let colors = if flags.color {
+        match LsColors::from_env() {
+            Ok(colors) => Some(colors),
+            Err(err) => {
+                eprintln!("ls: {}", err);
+                None
+            },
+        }
+    } else {
+        None
+    };
+
     let mut exit_code = 0;
 
     let mut writer = BufWriter::new(io::stdout());
 
-    if flags.directory {
-        let mut result = Files::new();
+    // The files are listed first, then the contents of the directories
+    let mut result = Files::new();
+    let mut directories = Files::new();
 
-        for file in files {
-            let path = PathBuf::from(file);
+    for file in files.clone() {
+        let path = PathBuf::from(file);
 
-            let item = File::from_name(BString::from(path.as_os_str().as_bytes()), path, flags);
+        let item = File::from_name(BString::from(path.as_os_str().as_bytes()), path, flags);
 
-            match item {
-                Ok(item) => {
+        match item {
+            Ok(item) => {
+                if !flags.directory && item.path.is_dir() {
+                    directories.push(item);
+                } else {
                     result.push(item);
-                },
-                Err(err) => {
-                    eprintln!("ls: cannot access '{}': {}", file, err);
-                    process::exit(1);
-                },
-            }
+                }
+            },
+            Err(err) => {
+                eprintln!("ls: cannot access '{}': {}", file, err);
+                exit_code = 1;
+            },
         }
+    }
 
+    if !flags.unsorted {
         sort(&mut result, &flags);
+        sort(&mut directories, &flags);
+    }
 
-        exit_code = output(result, &mut writer, flags);
-    } else if flags.recursive {
-        for file in files {
-            exit_code = recursive_output(file, &mut writer, &flags);
-        }
-    } else {
-        let multiple = files.len() > 1;
-
-        for (i, file) in files.enumerate() {
-            if multiple {
-                if i != 0 {
-                    match writeln!(writer) {
-                        Ok(_) => {},
-                        Err(err) => {
-                            eprintln!("ls: {}", err);
-                            process::exit(1);
-                        },
-                    }
-                }
+    let mut first = result.is_empty();
 
-                match writeln!(writer, "{}:", file) {
-                    Ok(_) => {},
-                    Err(err) => {
-                        eprintln!("ls: {}", err);
-                        process::exit(1);
-                    },
-                }
-            }
+    if !result.is_empty() {
+        // The files are listed like with `-d`, without the total of the blocks
+        let file_flags = Flags { directory: true, ..flags };
 
-            let mut result = Files::new();
+        exit_code = exit_code.max(output(result, &mut writer, file_flags, colors.as_ref()));
+    }
 
-            let path = PathBuf::from(file);
+    let show_header = flags.recursive || files.len() > 1;
 
-            if path.is_file() {
-                let item = File::from(PathBuf::from(file), flags);
+    for directory in directories {
+        let name = directory.name.to_string();
 
-                match item {
-                    Ok(item) => {
-                        result.push(item);
-                    },
-                    Err(err) => {
-                        eprintln!("ls: cannot access {}: {}", err, file);
-                        process::exit(1);
-                    },
-                }
-            } else {
-                result = collect(file, &flags);
-            }
+        let code = list_directory(
+            &name,
+            show_header,
+            &mut first,
+            &mut writer,
+            &flags,
+            colors.as_ref(),
+            &mut Vec::new(),
+        );
 
-            exit_code = output(result, &mut writer, flags);
-        }
+        exit_code = exit_code.max(code);
     }
 
     if exit_code != 0 {
+        // The exit skips the flush of the writer on its drop
+        if let Err(err) = writer.flush() {
+            eprintln!("ls: {}", err);
+        }
+
         process::exit(exit_code);
     }
 }
 
-/// Read the `&str` as a directory and collect the results into a `File` vector.
-fn collect(file: &str, flags: &Flags) -> Files {
-    let mut result = Files::new();
-
-    match fs::read_dir(file) {
-        Ok(dir) => {
-            for entry in dir {
-                let entry = match entry {
-                    Ok(entry) => entry,
-                    Err(err) => {
-                        eprintln!("ls: cannot access '{}': {}", file, err);
-                        process::exit(1);
-                    },
-                };
-
-                let file = match File::from(entry.path(), *flags) {
-                    Ok(file) => file,
-                    Err(err) => {
-                        eprintln!("ls: cannot access '{}': {}", file, err);
-                        process::exit(1);
-                    },
-                };
-
-                if !File::is_hidden(&file.name.as_bstr()) || flags.show_hidden() {
-                    result.push(file);
-                }
-            }
-
-            if !flags.no_sort {
-                sort(&mut result, &flags);
-            }
-        },
+/// List the contents of the directory `name`, and of its subdirectories if the `-R` flag
+/// is set.
+///
+/// The `active` directories are the ones being listed, by their device and inode numbers,
+/// to not list them again when a symbolic link loops back to them.
+fn list_directory(
+    name: &str, show_header: bool, first: &mut bool, writer: &mut BufWriter<io::Stdout>,
+    flags: &Flags, colors: Option<&LsColors>, active: &mut Vec<(u64, u64)>,
+) -> i32 {
+    let id = match fs::metadata(name) {
+        Ok(metadata) => (metadata.dev(), metadata.ino()),
         Err(err) => {
-            eprintln!("ls: cannot access '{}': {}", file, err);
-            process::exit(1);
+            eprintln!("ls: cannot access '{}': {}", name, err);
+            return 1;
         },
+    };
+
+    if active.contains(&id) {
+        eprintln!("ls: {}: not listing already-listed directory", name);
+        return 1;
     }
 
-    if !flags.directory && (flags.all || flags.no_sort) {
-        // Retrieve the current directories information. This must
-        // be canonicalized in case the path is relative.
-        let current = PathBuf::from(file).canonicalize();
+    if show_header {
+        let link = File::hyperlink(Path::new(name), name, flags);
+        let header = if *first { format!("{}:", link) } else { format!("\n{}:", link) };
 
-        let current = match current {
-            Ok(current) => current,
+        match writeln!(writer, "{}", header) {
+            Ok(_) => {},
             Err(err) => {
                 eprintln!("ls: {}", err);
                 process::exit(1);
             },
-        };
+        }
+    }
 
-        let dot = File::from_name(BString::from("."), current.clone(), *flags);
+    *first = false;
 
-        let dot = match dot {
-            Ok(dot) => dot,
-            Err(err) => {
-                eprintln!("ls: {}", err);
-                process::exit(1);
-            },
-        };
+    let files = match collect(name, flags) {
+        Ok(files) => files,
+        Err(err) => {
+            eprintln!("ls: cannot open directory '{}': {}", name, err);
+            return 1;
+        },
+    };
 
-        // Retrieve the parent path. Default to the current path if the
-        // parent doesn't exist
-        let parent_path = match dot.path.parent() {
-            Some(parent) => parent,
-            None => current.as_path(),
-        };
+    // The subdirectories are listed after the directory, skipping the dot and dot-dot
+    let subdirectories: Vec<String> = if flags.recursive {
+        files
+            .iter()
+            .filter(|file| file.metadata.is_dir() && file.name != "." && file.name != "..")
+            .map(|file| {
+                let separator = if name.ends_with('/') { "" } else { "/" };
+                format!("{}{}{}", name, separator, file.name)
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
 
-        let dot_dot = File::from_name(BString::from(".."), PathBuf::from(parent_path), *flags);
+    let mut exit_code = output(files, writer, *flags, colors);
 
-        let dot_dot = match dot_dot {
-            Ok(dot_dot) => dot_dot,
-            Err(err) => {
-                eprintln!("ls: {}", err);
-                process::exit(1);
-            },
-        };
+    active.push(id);
 
-        result.insert(0, dot);
-        result.insert(1, dot_dot);
+    for subdirectory in subdirectories {
+        let code = list_directory(&subdirectory, true, first, writer, flags, colors, active);
+
+        exit_code = exit_code.max(code);
     }
 
-    result
+    active.pop();
+
+    exit_code
 }
 
-/// Recursively display sub directories from a given path.
-fn recursive_output(file: &str, writer: &mut BufWriter<io::Stdout>, flags: &Flags) -> i32 {
-    match writeln!(writer, "\n{}:", file) {
-        Ok(_) => {},
-        Err(err) => {
-            eprintln!("ls:  '{}'", err);
-        },
+/// Read the `&str` as a directory and collect the results into a `File` vector.
+fn collect(file: &str, flags: &Flags) -> io::Result<Files> {
+    let mut result = Files::new();
+
+    for entry in fs::read_dir(file)? {
+        let file = File::from(entry?.path(), *flags)?;
+
+        if !File::is_hidden(file.name.as_bstr()) || flags.show_hidden() {
+            result.push(file);
+        }
     }
 
-    let path = PathBuf::from(file);
+    if !flags.unsorted {
+        sort(&mut result, flags);
+    }
 
-    let files = if path.is_file() {
-        let mut result = Files::new();
-        let item = File::from(PathBuf::from(file), *flags);
+    if !flags.directory && (flags.all || flags.no_sort) {
+        // Retrieve the current directories information. This must
+        // be canonicalized in case the path is relative.
+        let current = PathBuf::from(file).canonicalize()?;
 
-        match item {
-            Ok(item) => {
-                result.push(item);
-            },
-            Err(err) => {
-                eprintln!("ls: cannot access {}: {}", err, file);
-            },
-        };
+        let dot = File::from_name(BString::from("."), current.clone(), *flags)?;
 
-        result
-    } else {
-        collect(file, flags)
-    };
-    let mut exit_code = output(files, writer, *flags);
+        // Retrieve the parent path. Default to the current path if the
+        // parent doesn't exist
+        let parent_path = match dot.path.parent() {
+            Some(parent) => parent,
+            None => current.as_path(),
+        };
 
-    if path.is_file() {
-        return exit_code;
-    }
+        let dot_dot = File::from_name(BString::from(".."), PathBuf::from(parent_path), *flags)?;
 
-    match fs::read_dir(file) {
-        Ok(dir) => {
-            for entry in dir {
-                match entry {
-                    Ok(entry) => {
-                        let path = entry.path();
-
-                        if path.is_dir() {
-                            let file_string = path.to_string_lossy().to_string();
-
-                            exit_code = recursive_output(&file_string, writer, &flags);
-                        }
-                    },
-                    Err(err) => {
-                        eprintln!("ls: cannot access '{}': {}", file, err);
-                    },
-                };
-            }
-        },
-        Err(err) => {
-            eprintln!("ls: cannot access '{}': {}", file, err);
-        },
+        result.insert(0, dot);
+        result.insert(1, dot_dot);
     }
 
-    exit_code
+    Ok(result)
 }
 
 /// Sort a list of files based on the provided flags.
 fn sort(files: &mut Files, flags: &Flags) {
-    if flags.time {
-        if flags.last_accessed {
-            files.sort_by_key(sort_by_access_time);
-        } else if flags.file_status_modification {
-            files.sort_by_key(sort_by_last_changed_time)
-        } else {
-            files.sort_by_key(sort_by_time);
-        }
-        files.reverse();
+    if flags.sort_time() {
+        // Newest first, by name for the same time
+        files.sort_by(|a, b| {
+            sort_by_time(b, flags).cmp(&sort_by_time(a, flags)).then_with(|| compare_names(a, b))
+        });
     } else if flags.sort_size {
-        files.sort_by_key(sort_by_size);
-        files.reverse();
+        files.sort_by(|a, b| {
+            sort_by_size(b).cmp(&sort_by_size(a)).then_with(|| compare_names(a, b))
+        });
+    } else if flags.sort_extension {
+        files.sort_by(|a, b| {
+            sort_by_extension(a).cmp(sort_by_extension(b)).then_with(|| compare_names(a, b))
+        });
+    } else if flags.sort_version {
+        files.sort_by(|a, b| version_sort::compare(a.name.as_bytes(), b.name.as_bytes()));
     } else {
         // Sort the directory entries by file name by default
         files.sort_by_key(sort_by_name);
@@ -278,16 +253,31 @@ fn sort(files: &mut Files, flags: &Flags) {
     if flags.reverse {
         files.reverse();
     }
+
+    // Keeping the order of the sort in the directories and in the other files
+    if flags.group_directories_first {
+        files.sort_by_key(|file| !is_directory(file));
+    }
 }
 
-/// Sort a list of files by last accessed time
-fn sort_by_access_time(file: &File) -> i64 {
-    file.metadata.atime()
+/// Check if the file is a directory or a symbolic link to one
+fn is_directory(file: &File) -> bool {
+    file.metadata.is_dir() || matches!(fs::metadata(&file.path), Ok(metadata) if metadata.is_dir())
 }
 
-/// Sort a list of files by last change of file status information
-fn sort_by_last_changed_time(file: &File) -> i64 {
-    file.metadata.ctime()
+/// Compare two files by their file names
+fn compare_names(a: &File, b: &File) -> Ordering {
+    sort_by_name(a).cmp(&sort_by_name(b))
+}
+
+/// Sort a list of files by the extension of their file names, the ones without it first
+fn sort_by_extension(file: &File) -> &[u8] {
+    let name = file.name.as_bytes();
+
+    match name.rfind_byte(b'.') {
+        Some(dot) => &name[dot + 1..],
+        None => b"",
+    }
 }
 
 /// Sort a list of files by file name alphabetically
@@ -300,7 +290,16 @@ fn sort_by_size(file: &File) -> u64 {
     file.metadata.len()
 }
 
-/// Sort a list of directories by modification time
-fn sort_by_time(file: &File) -> i64 {
-    file.metadata.mtime()
+/// Sort a list of files by the time of the last modification, access with `-u` or change
+/// of the file status information with `-c`
+fn sort_by_time(file: &File, flags: &Flags) -> (i64, i64) {
+    let metadata = &file.metadata;
+
+    if flags.last_accessed {
+        (metadata.atime(), metadata.atime_nsec())
+    } else if flags.file_status_modification {
+        (metadata.ctime(), metadata.ctime_nsec())
+    } else {
+        (metadata.mtime(), metadata.mtime_nsec())
+    }
 }
@@ -3,26 +3,34 @@ use std::{
     os::unix::fs::MetadataExt,
 };
 
-use coreutils_core::{
-    os::tty::{tty_dimensions, IsTty},
-    BString, ByteSlice,
-};
+use coreutils_core::{ls_colors::LsColors, os::tty::IsTty, BString, ByteSlice};
 use io::Stdout;
-use term_grid::{Alignment, Cell, Direction, Filling, Grid, GridOptions};
 
 extern crate chrono;
 
 use crate::{
-    file::{FileColor, Files},
+    file::{File, Files},
     flags::Flags,
     table::{Row, Table},
 };
 
-pub(crate) fn output(result: Files, writer: &mut BufWriter<io::Stdout>, flags: Flags) -> i32 {
+pub(crate) fn output(
+    result: Files, writer: &mut BufWriter<io::Stdout>, flags: Flags, colors: Option<&LsColors>,
+) -> i32 {
     let mut exit_code = 0;
 
+    // The short formats only have the total of the blocks with `-s`
+    if flags.size && !flags.show_list() && !flags.directory {
+        let total = result.iter().map(|file| file.metadata.blocks()).sum();
+
+        if let Err(err) = writeln!(writer, "total {}", File::scale_blocks(total, &flags)) {
+            eprintln!("ls: {}", err);
+            return 1;
+        }
+    }
+
     if flags.show_list() {
-        match list(result, writer, flags) {
+        match list(result, writer, flags, colors) {
             Ok(_) => {},
             Err(err) => {
                 eprintln!("ls: {}", err);
@@ -30,13 +38,7 @@ pub(crate) fn output(result: Files, writer: &mut BufWriter<io::Stdout>, flags: F
             },
         }
     } else if flags.show_grid() {
-        let direction = if flags.order_left_to_right && !flags.order_top_to_bottom {
-            Direction::LeftToRight
-        } else {
-            Direction::TopToBottom
-        };
-
-        match grid(result, writer, direction) {
+        match grid(result, writer, flags, colors) {
             Ok(_) => {},
             Err(err) => {
                 eprintln!("ls: {}", err);
@@ -44,7 +46,7 @@ pub(crate) fn output(result: Files, writer: &mut BufWriter<io::Stdout>, flags: F
             },
         }
     } else {
-        match default(result, writer, flags) {
+        match default(result, writer, flags, colors) {
             Ok(_) => {},
             Err(err) => {
                 eprintln!("ls: {}", err);
@@ -58,11 +60,17 @@ pub(crate) fn output(result: Files, writer: &mut BufWriter<io::Stdout>, flags: F
 
 /// Writes the provided files in the default format.
 pub(crate) fn default(
-    files: Files, writer: &mut BufWriter<Stdout>, flags: Flags,
+    files: Files, writer: &mut BufWriter<Stdout>, flags: Flags, colors: Option<&LsColors>,
 ) -> io::Result<()> {
+    let prefixes = prefixes(&files, &flags);
+
     if !writer.get_ref().is_tty() {
-        for file in &files {
-            if flags.hide_control_chars {
+        for (file, prefix) in files.iter().zip(&prefixes) {
+            write!(writer, "{}", prefix)?;
+
+            if colors.is_some() || flags.hyperlink {
+                writeln!(writer, "{}", file.file_name(colors))?;
+            } else if flags.hide_control_chars {
                 writeln!(writer, "{}", file.name)?;
             } else {
                 writer.write_all(file.name.as_bytes())?;
@@ -72,21 +80,21 @@ pub(crate) fn default(
 
         return Ok(());
     } else if flags.one_per_line {
-        for file in &files {
-            let file_name = file.file_name(FileColor::Show);
+        for (file, prefix) in files.iter().zip(&prefixes) {
+            let file_name = file.file_name(colors);
 
-            writeln!(writer, "{}", file_name)?;
+            writeln!(writer, "{}{}", prefix, file_name)?;
         }
 
         return Ok(());
     } else if flags.comma_separate {
-        for (i, file) in files.iter().enumerate() {
-            let file_name = file.file_name(FileColor::Show);
+        for (i, (file, prefix)) in files.iter().zip(&prefixes).enumerate() {
+            let file_name = file.file_name(colors);
 
             if (i + 1) == files.len() {
-                writeln!(writer, "{}", file_name)?;
+                writeln!(writer, "{}{}", prefix, file_name)?;
             } else {
-                write!(writer, "{}, ", file_name)?;
+                write!(writer, "{}{}, ", prefix, file_name)?;
             }
         }
 
@@ -97,52 +105,145 @@ pub(crate) fn default(
 }
 
 /// Writes the provided files in a grid format.
+///
+/// The files are placed in the most columns that fit in the width of the lines, each
+/// column as wide as its longest file name and two spaces, like GNU.
 pub(crate) fn grid(
-    files: Files, writer: &mut BufWriter<Stdout>, direction: Direction,
+    files: Files, writer: &mut BufWriter<Stdout>, flags: Flags, colors: Option<&LsColors>,
 ) -> io::Result<()> {
-    let mut grid = Grid::new(GridOptions { filling: Filling::Spaces(2), direction });
-
-    let width = match tty_dimensions(&io::stdout()) {
-        Some(result) => result.0,
-        None => {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                "Unable to retrieve terminal dimensions.",
-            ));
-        },
-    };
-
-    for file in &files {
-        grid.add(Cell {
-            alignment: Alignment::Left,
-            contents: file.file_name(FileColor::Show),
-            width: file.file_name(FileColor::Hide).len(),
-        });
+    let by_rows = flags.order_left_to_right;
+
+    let prefixes = prefixes(&files, &flags);
+
+    let names: Vec<String> = files
+        .iter()
+        .zip(&prefixes)
+        .map(|(file, prefix)| format!("{}{}", prefix, file.file_name(colors)))
+        .collect();
+    let widths: Vec<usize> = files
+        .iter()
+        .zip(&prefixes)
+        .map(|(file, prefix)| prefix.chars().count() + file.name_width())
+        .collect();
+
+    let columns = grid_columns(&widths, flags.width, by_rows);
+    // `div_ceil` is newer than the MSRV
+    #[allow(clippy::manual_div_ceil)]
+    let rows = (names.len() + columns - 1) / columns;
+
+    // The width of each column, with the separation to the next one
+    let mut column_widths = vec![0; columns];
+    for (i, width) in widths.iter().enumerate() {
+        let column = if by_rows { i % columns } else { i / rows };
+        column_widths[column] = column_widths[column].max(width + 2);
     }
 
-    match grid.fit_into_width(width.into()) {
-        Some(display) => {
-            write!(writer, "{}", display)?;
-            Ok(())
-        },
-        None => {
-            for file in &files {
-                writeln!(writer, "{}", file.file_name(FileColor::Show))?;
+    for row in 0..rows {
+        let cells: Vec<usize> = if by_rows {
+            (row * columns..names.len().min((row + 1) * columns)).collect()
+        } else {
+            (row..names.len()).step_by(rows).collect()
+        };
+
+        for (column, &i) in cells.iter().enumerate() {
+            write!(writer, "{}", names[i])?;
+
+            // The last file of the row is not padded
+            if column + 1 < cells.len() {
+                write!(writer, "{:1$}", "", column_widths[column] - widths[i])?;
             }
+        }
+
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// The inode, blocks and security context columns before the file names of the short
+/// formats, aligned to the right unless they are separated by commas.
+fn prefixes(files: &Files, flags: &Flags) -> Vec<String> {
+    let mut columns: Vec<Vec<String>> = Vec::new();
 
-            Ok(())
-        },
+    if flags.inode {
+        columns.push(files.iter().map(|file| file.inode()).collect());
     }
+
+    if flags.size {
+        columns.push(files.iter().map(|file| file.blocks()).collect());
+    }
+
+    if flags.context {
+        columns.push(files.iter().map(|file| file.context()).collect());
+    }
+
+    let widths: Vec<usize> = columns
+        .iter()
+        .map(|column| {
+            let width = column.iter().map(String::len).max().unwrap_or(0);
+            if flags.comma_separate {
+                0
+            } else {
+                width
+            }
+        })
+        .collect();
+
+    (0..files.len())
+        .map(|i| {
+            columns
+                .iter()
+                .zip(&widths)
+                .map(|(column, &width)| format!("{:>1$} ", column[i], width))
+                .collect()
+        })
+        .collect()
+}
+
+/// The most columns that fit the file names of the `widths` in lines of `line_width`,
+/// without a limit if it is zero.
+fn grid_columns(widths: &[usize], line_width: usize, by_rows: bool) -> usize {
+    // The columns are at least one character and the separation wide
+    const MIN_COLUMN_WIDTH: usize = 3;
+
+    let max_columns = match line_width {
+        0 => widths.len(),
+        width => (width / MIN_COLUMN_WIDTH).max(1).min(widths.len()),
+    };
+
+    (1..=max_columns)
+        .rev()
+        .find(|&columns| {
+            // `div_ceil` is newer than the MSRV
+            #[allow(clippy::manual_div_ceil)]
+            let rows = (widths.len() + columns - 1) / columns;
+            let mut column_widths = vec![MIN_COLUMN_WIDTH; columns];
+
+            for (i, width) in widths.iter().enumerate() {
+                let column = if by_rows { i % columns } else { i / rows };
+                let separation = if column + 1 == columns { 0 } else { 2 };
+                column_widths[column] = column_widths[column].max(width + separation);
+            }
+
+            line_width == 0 || column_widths.iter().sum::<usize>() < line_width
+        })
+        .unwrap_or(1)
 }
 
 /// Writes the provided files in a list format.
-pub(crate) fn list(files: Files, writer: &mut BufWriter<Stdout>, flags: Flags) -> io::Result<()> {
+pub(crate) fn list(
+    files: Files, writer: &mut BufWriter<Stdout>, flags: Flags, colors: Option<&LsColors>,
+) -> io::Result<()> {
     let mut inode_width = 1;
     let mut block_width = 1;
     let mut hard_links_width = 1;
     let mut user_width = 1;
     let mut group_width = 1;
+    let mut context_width = 1;
     let mut size_width = 1;
+    let mut major_width = 0;
+    let mut minor_width = 0;
+    let mut any_acl = false;
 
     let mut rows = Table::new();
 
@@ -163,22 +264,27 @@ pub(crate) fn list(files: Files, writer: &mut BufWriter<Stdout>, flags: Flags) -
             row.inode = inode;
         }
 
-        total += file.blocks();
+        total += file.metadata.blocks();
 
         // Process the file's block size
         if flags.size {
-            let block = file.blocks() as usize;
-            let block_len = block.to_string().len();
+            let block = file.blocks();
+            let block_len = block.len();
 
             if block_len > block_width {
                 block_width = block_len;
             }
 
-            row.block = block.to_string();
+            row.block = block;
         }
 
-        // Process the file's permissions
-        let permissions = file.permissions();
+        // Process the file's permissions, marking the access control lists
+        let mut permissions = file.permissions();
+
+        if file.has_acl() {
+            permissions.push('+');
+            any_acl = true;
+        }
 
         row.permissions = permissions;
 
@@ -194,10 +300,14 @@ pub(crate) fn list(files: Files, writer: &mut BufWriter<Stdout>, flags: Flags) -
 
         // Process the file's user name
         if !flags.no_owner {
+            // Users without a name are displayed by their ID
             let user = match file.user() {
-                Ok(file_user) => file_user,
-                Err(err) => {
-                    eprintln!("ls: {}", err);
+                Ok(file_user) => {
+                    row.numeric_user = flags.numeric_uid_gid;
+                    file_user
+                },
+                Err(_) => {
+                    row.numeric_user = true;
                     BString::from(file.metadata.uid().to_string())
                 },
             };
@@ -213,10 +323,14 @@ pub(crate) fn list(files: Files, writer: &mut BufWriter<Stdout>, flags: Flags) -
 
         // Process the file's group name
         if !flags.no_group {
+            // Groups without a name are displayed by their ID
             let group = match file.group() {
-                Ok(file_group) => file_group,
-                Err(err) => {
-                    eprintln!("ls: {}", err);
+                Ok(file_group) => {
+                    row.numeric_group = flags.numeric_uid_gid;
+                    file_group
+                },
+                Err(_) => {
+                    row.numeric_group = true;
                     BString::from(file.metadata.gid().to_string())
                 },
             };
@@ -230,29 +344,53 @@ pub(crate) fn list(files: Files, writer: &mut BufWriter<Stdout>, flags: Flags) -
             row.group = group;
         }
 
-        // Process the file's size
-        let size = file.size();
-        let size_len = file.size().len();
+        // Process the file's security context
+        if flags.context {
+            let context = file.context();
+
+            context_width = context_width.max(context.len());
 
-        if size_len > size_width {
-            size_width = size_len;
+            row.context = context;
         }
 
-        row.size = size;
+        // Process the file's size, or the numbers of the device
+        if let Some((major, minor)) = file.device() {
+            major_width = major_width.max(major.len());
+            minor_width = minor_width.max(minor.len());
+
+            row.device = Some((major, minor));
+        } else {
+            let size = file.size();
+            let size_len = size.len();
+
+            if size_len > size_width {
+                size_width = size_len;
+            }
+
+            row.size = size;
+        }
 
         // Process the file's timestamp
         row.time = file.time();
 
         // Process the file's name
-        row.file_name = file.file_name(FileColor::Show);
+        row.file_name = file.file_name(colors);
 
         rows.push(row);
     }
 
     if !flags.directory {
-        writeln!(writer, "total {}", total)?;
+        writeln!(writer, "total {}", File::scale_blocks(total, &flags))?;
+    }
+
+    // The devices are displayed like `MAJOR, MINOR` in the size column
+    if major_width > 0 {
+        size_width = size_width.max(major_width + 2 + minor_width);
     }
 
+    // The permissions are padded to leave room for the `+` of the access control lists
+    let permissions_width = if any_acl { 11 } else { 10 };
+
     for row in rows {
         if flags.inode {
             write!(writer, "{:>1$} ", row.inode, inode_width)?;
@@ -262,22 +400,40 @@ pub(crate) fn list(files: Files, writer: &mut BufWriter<Stdout>, flags: Flags) -
             write!(writer, "{:>1$} ", row.block, block_width)?;
         }
 
-        write!(writer, "{:<1} ", row.permissions)?;
+        write!(writer, "{:<1$} ", row.permissions, permissions_width)?;
         write!(writer, "{:>1$} ", row.hard_links, hard_links_width)?;
 
+        // The names are aligned to the left and the IDs to the right
         if !flags.no_owner {
-            write!(writer, "{:<1$} ", row.user.to_string(), user_width)?;
+            if row.numeric_user {
+                write!(writer, "{:>1$} ", row.user.to_string(), user_width)?;
+            } else {
+                write!(writer, "{:<1$} ", row.user.to_string(), user_width)?;
+            }
         }
 
         if !flags.no_group {
-            write!(writer, "{:<1$} ", row.group.to_string(), group_width)?;
+            if row.numeric_group {
+                write!(writer, "{:>1$} ", row.group.to_string(), group_width)?;
+            } else {
+                write!(writer, "{:<1$} ", row.group.to_string(), group_width)?;
+            }
         }
 
-        write!(writer, "{:>1$} ", row.size, size_width)?;
-        write!(writer, "{:<1} ", row.time)?;
-        write!(writer, "{:<1} ", row.file_name)?;
+        if flags.context {
+            write!(writer, "{:<1$} ", row.context, context_width)?;
+        }
 
-        writeln!(writer)?;
+        match row.device {
+            Some((major, minor)) => {
+                let device = format!("{:>2$}, {:>3$}", major, minor, major_width, minor_width);
+                write!(writer, "{:>1$} ", device, size_width)?;
+            },
+            None => write!(writer, "{:>1$} ", row.size, size_width)?,
+        }
+
+        write!(writer, "{:<1} ", row.time)?;
+        writeln!(writer, "{}", row.file_name)?;
     }
 
     Ok(())
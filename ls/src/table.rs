@@ -11,7 +11,11 @@ pub(crate) struct Row {
     pub hard_links: String,
     pub user: BString,
     pub group: BString,
+    pub context: String,
+    pub numeric_user: bool,
+    pub numeric_group: bool,
     pub size: String,
+    pub device: Option<(String, String)>,
     pub time: String,
     pub file_name: String,
 }
@@ -25,10 +29,28 @@ impl Row {
         let hard_links = String::new();
         let user = BString::from("");
         let group = BString::from("");
+        let context = String::new();
+        let numeric_user = false;
+        let numeric_group = false;
         let size = String::new();
+        let device = None;
         let time = String::new();
         let file_name = String::new();
 
-        Row { inode, block, permissions, hard_links, user, group, size, time, file_name }
+        Row {
+            inode,
+            block,
+            permissions,
+            hard_links,
+            user,
+            group,
+            context,
+            numeric_user,
+            numeric_group,
+            size,
+            device,
+            time,
+            file_name,
+        }
     }
 }
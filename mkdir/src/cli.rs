@@ -20,13 +20,24 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
         .arg(
             Arg::with_name("mode")
                 .help("Set file mode (as in chmod), not a=rwx - umask.")
+                .long_help(
+                    "Set file mode (as in chmod), not a=rwx - umask.\n\nMODE is a octal number or \
+                     a symbolic mode, applied to a=rwx. It only sets the mode of the DIRECTORY \
+                     arguments, not the parent directories made with -p.",
+                )
                 .long("mode")
                 .short("m")
-                .value_name("MODE"),
+                .value_name("MODE")
+                .allow_hyphen_values(true),
         )
         .arg(
             Arg::with_name("parents")
                 .help("No error if existing, make parent directories as needed.")
+                .long_help(
+                    "No error if existing, make parent directories as needed.\n\nThe parent \
+                     directories are made with the default mode, with the owner always able to \
+                     write and search them.",
+                )
                 .long("parents")
                 .short("p"),
         )
@@ -36,4 +47,26 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .long("verbose")
                 .short("v"),
         )
+        .arg(
+            Arg::with_name("defaultContext")
+                .help(
+                    "Set the SELinux security context of each created directory to the default \
+                     type.",
+                )
+                .long_help(
+                    "Set the SELinux security context of each created directory to the default \
+                     type.\n\nIt is the one the type transitions of the policy give them, and it \
+                     is ignored without SELinux.",
+                )
+                .short("Z"),
+        )
+        .arg(
+            Arg::with_name("context")
+                .help("Like -Z, or with CTX set the SELinux security context to CTX.")
+                .long("context")
+                .value_name("CTX")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true),
+        )
 }
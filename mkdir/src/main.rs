@@ -1,51 +1,304 @@
-use std::{fs, os::unix::fs::PermissionsExt, process};
+use std::{
+    ffi::OsStr,
+    fs::{self, DirBuilder, Permissions},
+    io,
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{DirBuilderExt, PermissionsExt},
+    },
+    path::Path,
+    process,
+};
+
+use clap::ArgMatches;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use coreutils_core::os::selinux;
+use coreutils_core::{
+    error::strerror,
+    libc,
+    mode::{umask, Mode},
+};
+
+const SET_UID: u32 = 0o4000;
+const SET_GID: u32 = 0o2000;
+const STICKY: u32 = 0o1000;
 
 mod cli;
 
 fn main() {
     let matches = cli::create_app().get_matches();
 
-    // Required, so ok to unwrap
-    let directories = matches.values_of("DIRECTORY").unwrap();
-    let verbose = matches.is_present("verbose");
-    let parents = matches.is_present("parents");
-    let has_mode = matches.is_present("mode");
+    let flags = MkdirFlags::from_matches(&matches);
 
-    let mut exit_code = 0;
+    set_context(&matches);
 
-    let mkdir = {
-        if parents { fs::create_dir_all } else { fs::create_dir }
-    };
+    // Required, so ok to unwrap
+    let directories = matches.values_of_os("DIRECTORY").unwrap();
+
+    let mut success = true;
 
     for dir in directories {
-        match mkdir(dir) {
-            Ok(_) => {
-                if verbose {
-                    println!("mkdir: created directory '{}'", dir)
-                };
-                if has_mode {
-                    let mode = matches.value_of("mode").unwrap();
-                    match fs::metadata(dir) {
-                        Ok(v) => {
-                            let mut perms = v.permissions();
-                            let umode: u32 = mode.parse().unwrap();
-                            perms.set_mode(umode);
-                        },
-                        Err(err) => {
-                            eprintln!("mkdir: {}", err);
-                            exit_code = 1;
-                        },
-                    }
-                }
+        if !make_directory(Path::new(dir), &flags) {
+            success = false;
+        }
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct MkdirFlags {
+    /// The mode of the DIRECTORY arguments, or `None` for the default one.
+    mode: Option<u32>,
+    /// The bits that the mode sets or clears.
+    mode_bits: u32,
+    /// The mode of the parent directories made with `-p`.
+    parent_mode: u32,
+    umask: u32,
+    parents: bool,
+    verbose: bool,
+}
+
+impl MkdirFlags {
+    fn from_matches(matches: &ArgMatches) -> Self {
+        let umask = umask();
+
+        let (mode, mode_bits) = match matches.value_of("mode").map(str::parse::<Mode>) {
+            Some(Ok(mode)) => {
+                let (mode, mode_bits) = mode.apply_with_bits(0o777, true, umask);
+                (Some(mode), mode_bits)
             },
-            Err(err) => {
-                eprintln!("mkdir: cannot create directory '{}': {}", dir, err);
-                exit_code = 1;
+            Some(Err(err)) => {
+                eprintln!("mkdir: {}", err);
+                process::exit(1);
+            },
+            None => (None, 0),
+        };
+
+        MkdirFlags {
+            mode,
+            mode_bits,
+            // The owner must be able to make the next directories in them
+            parent_mode: (0o777 & !umask) | 0o300,
+            umask,
+            parents: matches.is_present("parents"),
+            verbose: matches.is_present("verbose"),
+        }
+    }
+}
+
+/// Set the security context of the directories made, to the default one with `-Z` or to
+/// the one given with `--context`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_context(matches: &ArgMatches) {
+    let context = matches.value_of("context");
+
+    if !matches.is_present("defaultContext") && !matches.is_present("context") {
+        return;
+    }
+
+    if !selinux::is_enabled() {
+        if context.is_some() {
+            eprintln!("mkdir: warning: ignoring --context; it requires an SELinux-enabled kernel");
+        }
+        return;
+    }
+
+    if let Err(err) = selinux::set_create_context(context.map(str::as_bytes)) {
+        match context {
+            Some(context) => eprintln!(
+                "mkdir: failed to set default file creation context to '{}': {}",
+                context,
+                strerror(&err)
+            ),
+            None => {
+                eprintln!("mkdir: failed to set default file creation context: {}", strerror(&err))
             },
         }
+        process::exit(1);
+    }
+}
+
+/// SELinux is only on Linux.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn set_context(matches: &ArgMatches) {
+    if matches.value_of("context").is_some() {
+        eprintln!("mkdir: warning: ignoring --context; it requires an SELinux-enabled kernel");
     }
+}
+
+/// Make the directory in `path`, and its parents with `-p`, returning `false` if it
+/// failed.
+fn make_directory(path: &Path, flags: &MkdirFlags) -> bool {
+    if flags.parents && !parents(path).into_iter().all(|parent| make_parent(parent, flags)) {
+        return false;
+    }
+
+    let mode = flags.mode.unwrap_or(0o777);
+
+    // Like GNU, the directories whose special bits are set are made without the write
+    // permission of the group and the others until they are
+    let special = flags.mode_bits & (SET_UID | SET_GID) != 0 || mode & STICKY != 0;
+    let made_mode = if special { mode & !0o022 } else { mode };
+
+    // The umask is only applied to the bits that the mode doesn't have
+    let made = match flags.mode {
+        Some(mode) => {
+            with_umask(flags.umask & !mode, || DirBuilder::new().mode(made_mode).create(path))
+        },
+        None => DirBuilder::new().mode(made_mode).create(path),
+    };
+
+    match made {
+        Ok(()) => {},
+        Err(err)
+            if flags.parents && err.kind() == io::ErrorKind::AlreadyExists && path.is_dir() =>
+        {
+            return true;
+        },
+        Err(err) => {
+            eprintln!("mkdir: cannot create directory '{}': {}", path.display(), strerror(&err));
+            return false;
+        },
+    }
+
+    if flags.verbose {
+        println!("mkdir: created directory '{}'", path.display());
+    }
+
+    if !special {
+        return true;
+    }
+
+    // The bits the mode doesn't change are kept as the directory was made
+    let made_mode = match fs::metadata(path) {
+        Ok(metadata) => metadata.permissions().mode() & 0o7777,
+        Err(err) => {
+            eprintln!("mkdir: cannot set permissions of '{}': {}", path.display(), strerror(&err));
+            return false;
+        },
+    };
+
+    if (made_mode ^ mode) & flags.mode_bits == 0 {
+        return true;
+    }
+
+    set_mode(path, mode | (made_mode & !flags.mode_bits))
+}
+
+/// Run `f` with the `umask` of the process, restoring it after.
+fn with_umask<T>(umask: u32, f: impl FnOnce() -> T) -> T {
+    let old = unsafe { libc::umask(umask as libc::mode_t) };
+    let result = f();
+    unsafe { libc::umask(old) };
+
+    result
+}
+
+/// Make the parent directory in `path` with `-p`, if it doesn't exist, returning `false`
+/// if it failed.
+fn make_parent(path: &Path, flags: &MkdirFlags) -> bool {
+    match DirBuilder::new().mode(flags.parent_mode).create(path) {
+        Ok(()) => {},
+        // It may also have been made by other process in the meantime
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+            if path.is_dir() {
+                return true;
+            }
+
+            let err = io::Error::from_raw_os_error(libc::ENOTDIR);
+            eprintln!("mkdir: cannot create directory '{}': {}", path.display(), strerror(&err));
+            return false;
+        },
+        Err(err) => {
+            eprintln!("mkdir: cannot create directory '{}': {}", path.display(), strerror(&err));
+            return false;
+        },
+    }
+
+    if flags.verbose {
+        println!("mkdir: created directory '{}'", path.display());
+    }
+
+    if flags.parent_mode & flags.umask != 0 {
+        set_mode(path, flags.parent_mode)
+    } else {
+        true
+    }
+}
+
+/// Set the `mode` of the directory in `path`, returning `false` if it failed.
+fn set_mode(path: &Path, mode: u32) -> bool {
+    match fs::set_permissions(path, Permissions::from_mode(mode)) {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!("mkdir: cannot set permissions of '{}': {}", path.display(), strerror(&err));
+            false
+        },
+    }
+}
+
+/// The parent directories of the `path`, in the order they are made and as they are
+/// written in it, without the `.` and `..` components.
+fn parents(path: &Path) -> Vec<&Path> {
+    let bytes = path.as_os_str().as_bytes();
+
+    let mut parents = Vec::new();
+    let mut start = 0;
+
+    for (i, _) in bytes.iter().enumerate().filter(|(_, &byte)| byte == b'/') {
+        let component = &bytes[start..i];
+        start = i + 1;
+
+        // The last component may have slashes after it
+        let is_last = bytes[i..].iter().all(|&byte| byte == b'/');
+
+        if !is_last && !matches!(component, b"" | b"." | b"..") {
+            parents.push(Path::new(OsStr::from_bytes(&bytes[..i])));
+        }
+    }
+
+    parents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parent_directories() {
+        assert_eq!(parents(Path::new("a//b/./c/")), [Path::new("a"), Path::new("a//b")]);
+        assert_eq!(parents(Path::new("/a/b")), [Path::new("/a")]);
+        assert_eq!(parents(Path::new("../a/../b")), [Path::new("../a")]);
+        assert!(parents(Path::new("a")).is_empty());
+    }
+
+    #[test]
+    fn modes_under_umask() {
+        let dir = std::env::temp_dir().join(format!("mkdir.modes.{}", process::id()));
+        fs::create_dir(&dir).unwrap();
+        let old = unsafe { libc::umask(0o022) };
+
+        let make = |name: &str, mode: &str| {
+            let path = dir.join(name);
+            let args = vec!["mkdir".as_ref(), "-m".as_ref(), mode.as_ref(), path.as_os_str()];
+            let flags = MkdirFlags::from_matches(&cli::create_app().get_matches_from(args));
+
+            assert!(make_directory(&path, &flags));
+            fs::metadata(&path).unwrap().permissions().mode() & 0o7777
+        };
+
+        // Only the special bits keep the write permission of the group and the others off
+        assert_eq!(make("sticky", "+t"), 0o1755);
+        assert_eq!(make("others_sticky", "o=t"), 0o1750);
+        assert_eq!(make("octal_sticky", "1777"), 0o1777);
+        assert_eq!(make("user_write", "u+w"), 0o777);
+        assert_eq!(make("group_write", "g-w"), 0o757);
+        assert_eq!(make("no_write", "-w"), 0o577);
 
-    if exit_code != 0 {
-        process::exit(exit_code);
+        unsafe { libc::umask(old) };
+        fs::remove_dir_all(&dir).unwrap();
     }
 }
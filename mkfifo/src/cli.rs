@@ -11,12 +11,36 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
         .version_message("Display version information.")
         .help_short("?")
         .settings(&[ColoredHelp])
-        .arg(Arg::with_name("NAME").help("Name of the FIFO.").required(true))
+        .arg(Arg::with_name("NAME").help("Name of the FIFOs.").required(true).multiple(true))
         .arg(
             Arg::with_name("mode")
-                .help("Set file permission bits to MODE, not a=rw - umask")
+                .help("Set file permission bits to MODE, not a=rw - umask.")
+                .long_help(
+                    "Set file permission bits to MODE, not a=rw - umask.\n\nMODE is a octal \
+                     number or a symbolic mode, as in chmod, applied to a=rw.",
+                )
                 .long("mode")
                 .short("m")
-                .default_value("644"),
+                .value_name("MODE")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("defaultContext")
+                .help("Set the SELinux security context of each created FIFO to the default type.")
+                .long_help(
+                    "Set the SELinux security context of each created FIFO to the default \
+                     type.\n\nIt is the one the type transitions of the policy give them, and it \
+                     is ignored without SELinux.",
+                )
+                .short("Z"),
+        )
+        .arg(
+            Arg::with_name("context")
+                .help("Like -Z, or with CTX set the SELinux security context to CTX.")
+                .long("context")
+                .value_name("CTX")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true),
         )
 }
@@ -1,6 +1,17 @@
-use std::process;
+use std::{
+    fs::{self, Permissions},
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    process,
+};
 
-use coreutils_core::mkfifo::mkfifo;
+use clap::ArgMatches;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use coreutils_core::os::selinux;
+use coreutils_core::{
+    mkfifo::mkfifo,
+    mode::{umask, Mode},
+};
 
 mod cli;
 
@@ -8,20 +19,108 @@ fn main() {
     let matches = cli::create_app().get_matches();
 
     // Ok to unwrap because it is required.
-    let filepath = matches.value_of("NAME").unwrap();
+    let names = matches.values_of_os("NAME").unwrap();
 
-    // Ok to unwrap because always have a default.
-    let mode = matches.value_of("mode").unwrap();
-    let mode: u32 = u32::from_str_radix(mode, 8).unwrap_or_else(|_| {
-        eprintln!("mkfifo: Invalid mode: '{}' is not an octal number", mode);
+    let mode = matches.value_of("mode").map(parse_mode);
+
+    set_context(&matches);
+
+    let mut success = true;
+
+    for name in names {
+        let path = Path::new(name);
+
+        if let Err(err) = mkfifo(path, mode.unwrap_or(0o666)) {
+            eprintln!("mkfifo: cannot create fifo '{}': {}", path.display(), err);
+            success = false;
+            continue;
+        }
+
+        // The umask is only applied to the default mode
+        if let Some(mode) = mode {
+            if let Err(err) = fs::set_permissions(path, Permissions::from_mode(mode)) {
+                eprintln!("mkfifo: cannot set permissions of '{}': {}", path.display(), err);
+                success = false;
+            }
+        }
+    }
+
+    if !success {
         process::exit(1);
-    });
+    }
+}
 
-    match mkfifo(filepath, mode) {
-        Ok(_) => (),
-        Err(e) => {
-            eprintln!("mkfifo: Failed creating the fifo:\n{}", e);
+/// Parse the `mode` of `-m`, applied to a=rw.
+fn parse_mode(mode: &str) -> u32 {
+    let mode = match mode.parse::<Mode>() {
+        Ok(mode) => mode.apply(0o666, false, umask()),
+        Err(err) => {
+            eprintln!("mkfifo: {}", err);
             process::exit(1);
         },
+    };
+
+    if mode & !0o777 != 0 {
+        eprintln!("mkfifo: mode must specify only file permission bits");
+        process::exit(1);
+    }
+
+    mode
+}
+
+/// Set the security context of the FIFOs made, to the default one with `-Z` or to the
+/// one given with `--context`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_context(matches: &ArgMatches) {
+    let context = matches.value_of("context");
+
+    if !matches.is_present("defaultContext") && !matches.is_present("context") {
+        return;
+    }
+
+    if !selinux::is_enabled() {
+        if context.is_some() {
+            eprintln!("mkfifo: warning: ignoring --context; it requires an SELinux-enabled kernel");
+        }
+        return;
+    }
+
+    if let Err(err) = selinux::set_create_context(context.map(str::as_bytes)) {
+        match context {
+            Some(context) => eprintln!(
+                "mkfifo: failed to set default file creation context to '{}': {}",
+                context, err
+            ),
+            None => eprintln!("mkfifo: failed to set default file creation context: {}", err),
+        }
+        process::exit(1);
+    }
+}
+
+/// SELinux is only on Linux.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn set_context(matches: &ArgMatches) {
+    if matches.value_of("context").is_some() {
+        eprintln!("mkfifo: warning: ignoring --context; it requires an SELinux-enabled kernel");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes() {
+        assert_eq!(parse_mode("644"), 0o644);
+        assert_eq!(parse_mode("0600"), 0o600);
+        assert_eq!(parse_mode("u=rw,go=r"), 0o644);
+        assert_eq!(parse_mode("a-w"), 0o444);
+        assert_eq!(parse_mode("g+x"), 0o676);
+
+        // Without the users, the umask is applied
+        assert_eq!(parse_mode("=rw"), 0o666 & !umask());
+
+        let matches = cli::create_app().get_matches_from(vec!["mkfifo", "-m", "-w", "fifo"]);
+        assert_eq!(matches.value_of("mode"), Some("-w"));
     }
 }
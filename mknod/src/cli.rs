@@ -0,0 +1,58 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("NAME")
+                .help("Name, type and device numbers of the special file.")
+                .long_help(
+                    "Name, type and device numbers of the special file.\n\nTYPE is 'b' for a \
+                     block special file, 'c' or 'u' for a character special file, or 'p' for a \
+                     FIFO. MAJOR and MINOR are required for the block and character special files \
+                     and not allowed for the FIFOs, and they are hexadecimal if they begin with \
+                     0x or 0X, octal if they begin with 0 and decimal otherwise.",
+                )
+                .value_names(&["NAME", "TYPE", "MAJOR", "MINOR"])
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("mode")
+                .help("Set file permission bits to MODE, not a=rw - umask.")
+                .long_help(
+                    "Set file permission bits to MODE, not a=rw - umask.\n\nMODE is a octal \
+                     number or a symbolic mode, as in chmod, applied to a=rw.",
+                )
+                .long("mode")
+                .short("m")
+                .value_name("MODE")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("defaultContext")
+                .help("Set the SELinux security context of the special file to the default type.")
+                .long_help(
+                    "Set the SELinux security context of the special file to the default \
+                     type.\n\nIt is the one the type transitions of the policy give it, and it is \
+                     ignored without SELinux.",
+                )
+                .short("Z"),
+        )
+        .arg(
+            Arg::with_name("context")
+                .help("Like -Z, or with CTX set the SELinux security context to CTX.")
+                .long("context")
+                .value_name("CTX")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true),
+        )
+}
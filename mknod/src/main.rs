@@ -0,0 +1,227 @@
+use std::{
+    fs::{self, Permissions},
+    os::unix::fs::PermissionsExt,
+    process,
+};
+
+use clap::ArgMatches;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use coreutils_core::os::selinux;
+use coreutils_core::{
+    libc,
+    mkfifo::{mkfifo, mknod},
+    mode::{umask, Mode},
+};
+
+mod cli;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let operands: Vec<&str> = matches.values_of("NAME").map(Iterator::collect).unwrap_or_default();
+
+    let (name, node) = parse_operands(&operands).unwrap_or_else(|err| {
+        eprintln!("mknod: {}", err);
+        process::exit(1);
+    });
+
+    let mode = matches.value_of("mode").map(parse_mode);
+
+    set_context(&matches);
+
+    let permissions = mode.unwrap_or(0o666);
+    // The file types are of 32 bits or less, depending on the system
+    #[allow(clippy::useless_conversion)]
+    let made = match node {
+        Node::Block(device) => mknod(name, u32::from(libc::S_IFBLK) | permissions, device),
+        Node::Character(device) => mknod(name, u32::from(libc::S_IFCHR) | permissions, device),
+        Node::Fifo => mkfifo(name, permissions),
+    };
+
+    if let Err(err) = made {
+        eprintln!("mknod: {}: {}", name, err);
+        process::exit(1);
+    }
+
+    // The umask is only applied to the default mode
+    if let Some(mode) = mode {
+        if let Err(err) = fs::set_permissions(name, Permissions::from_mode(mode)) {
+            eprintln!("mknod: cannot set permissions of '{}': {}", name, err);
+            process::exit(1);
+        }
+    }
+}
+
+/// The special files made, with their device numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Node {
+    Block(u64),
+    Character(u64),
+    Fifo,
+}
+
+/// Parse the `operands`, the name, type and device numbers of the special file.
+///
+/// # Errors
+/// If there are too many or too few operands for the type, or the type or the device
+/// numbers are invalid, the message of the error is returned.
+fn parse_operands<'a>(operands: &[&'a str]) -> Result<(&'a str, Node), String> {
+    // Only the FIFOs have no device numbers, and the missing operands are told like them
+    let expected = match operands.get(1) {
+        Some(kind) if !kind.starts_with('p') => 4,
+        None if !operands.is_empty() => 4,
+        _ => 2,
+    };
+
+    if operands.len() < expected {
+        let mut err = match operands.last() {
+            Some(last) => format!("missing operand after '{}'", last),
+            None => "missing operand".to_string(),
+        };
+
+        if expected == 4 && operands.len() == 2 {
+            err.push_str("\nSpecial files require major and minor device numbers.");
+        }
+
+        return Err(err);
+    }
+
+    if operands.len() > expected {
+        let mut err = format!("extra operand '{}'", operands[expected]);
+
+        if expected == 2 && operands.len() == 4 {
+            err.push_str("\nFifos do not have major and minor device numbers.");
+        }
+
+        return Err(err);
+    }
+
+    let device = || -> Result<u64, String> {
+        let major = parse_number(operands[2])
+            .ok_or_else(|| format!("invalid major device number '{}'", operands[2]))?;
+        let minor = parse_number(operands[3])
+            .ok_or_else(|| format!("invalid minor device number '{}'", operands[3]))?;
+
+        Ok(libc::makedev(major as _, minor as _) as u64)
+    };
+
+    let node = match operands[1] {
+        "b" => Node::Block(device()?),
+        "c" | "u" => Node::Character(device()?),
+        "p" => Node::Fifo,
+        kind => return Err(format!("invalid device type '{}'", kind)),
+    };
+
+    Ok((operands[0], node))
+}
+
+/// Parse a device `number`, that is hexadecimal if it begins with `0x` or `0X`, octal if
+/// it begins with `0` and decimal otherwise.
+fn parse_number(number: &str) -> Option<u32> {
+    let (digits, radix) =
+        if let Some(digits) = number.strip_prefix("0x").or_else(|| number.strip_prefix("0X")) {
+            (digits, 16)
+        } else if number.len() > 1 && number.starts_with('0') {
+            (&number[1..], 8)
+        } else {
+            (number, 10)
+        };
+
+    if !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+
+    u32::from_str_radix(digits, radix).ok()
+}
+
+/// Parse the `mode` of `-m`, applied to a=rw.
+fn parse_mode(mode: &str) -> u32 {
+    let mode = match mode.parse::<Mode>() {
+        Ok(mode) => mode.apply(0o666, false, umask()),
+        Err(err) => {
+            eprintln!("mknod: {}", err);
+            process::exit(1);
+        },
+    };
+
+    if mode & !0o777 != 0 {
+        eprintln!("mknod: mode must specify only file permission bits");
+        process::exit(1);
+    }
+
+    mode
+}
+
+/// Set the security context of the special file made, to the default one with `-Z` or to
+/// the one given with `--context`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_context(matches: &ArgMatches) {
+    let context = matches.value_of("context");
+
+    if !matches.is_present("defaultContext") && !matches.is_present("context") {
+        return;
+    }
+
+    if !selinux::is_enabled() {
+        if context.is_some() {
+            eprintln!("mknod: warning: ignoring --context; it requires an SELinux-enabled kernel");
+        }
+        return;
+    }
+
+    if let Err(err) = selinux::set_create_context(context.map(str::as_bytes)) {
+        match context {
+            Some(context) => eprintln!(
+                "mknod: failed to set default file creation context to '{}': {}",
+                context, err
+            ),
+            None => eprintln!("mknod: failed to set default file creation context: {}", err),
+        }
+        process::exit(1);
+    }
+}
+
+/// SELinux is only on Linux.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn set_context(matches: &ArgMatches) {
+    if matches.value_of("context").is_some() {
+        eprintln!("mknod: warning: ignoring --context; it requires an SELinux-enabled kernel");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse_number("10"), Some(10));
+        assert_eq!(parse_number("010"), Some(8));
+        assert_eq!(parse_number("0x1f"), Some(31));
+        assert_eq!(parse_number("0X1F"), Some(31));
+        assert_eq!(parse_number("0"), Some(0));
+        assert_eq!(parse_number("0x"), None);
+        assert_eq!(parse_number("09"), None);
+        assert_eq!(parse_number("+1"), None);
+        assert_eq!(parse_number("99999999999"), None);
+    }
+
+    #[test]
+    fn operands() {
+        assert_eq!(parse_operands(&["a", "p"]), Ok(("a", Node::Fifo)));
+        assert_eq!(
+            parse_operands(&["a", "u", "1", "3"]),
+            Ok(("a", Node::Character(libc::makedev(1, 3) as u64)))
+        );
+        assert!(parse_operands(&["a", "b"])
+            .unwrap_err()
+            .starts_with("missing operand after 'b'\n"));
+        assert!(parse_operands(&["a", "p", "1", "2"])
+            .unwrap_err()
+            .starts_with("extra operand '1'\n"));
+        assert_eq!(
+            parse_operands(&["a", "z", "1", "2"]),
+            Err("invalid device type 'z'".to_string())
+        );
+    }
+}
@@ -16,7 +16,9 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Template to use when creating the temporary file/directory.")
                 .long_help(
                     "Template to use when creating the temporary file/directory.\n\nThe template \
-                     may be any file name with some number of X's appended to it.",
+                     may be any file name with at least 3 consecutive X's in its last component, \
+                     and the last run of X's is replaced by random characters.\n\nIf TEMPLATE is \
+                     not given, tmp.XXXXXXXXXX is used, and --tmpdir is implied.",
                 ),
         )
         .arg(
@@ -25,41 +27,81 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .long("directory")
                 .short("d"),
         )
+        .arg(
+            Arg::with_name("unsafe")
+                .help(
+                    "Do not create anything, only print a name. Use of this option is discouraged.",
+                )
+                .long_help(
+                    "Do not create anything, only print a name of a file that does not \
+                     exist.\n\nThis introduces a race condition, since another file may be \
+                     created with that name before it is used.\n\nUse of this option is not \
+                     encouraged.",
+                )
+                .long("dry-run")
+                .visible_alias("unsafe")
+                .short("u"),
+        )
         .arg(
             Arg::with_name("quiet")
-                .help("Fail silently if an error occurs.")
+                .help("Suppress diagnostics about file/directory-creation failure.")
                 .long_help(
-                    "Fail silently if an error occurs. This is useful if a script does not want \
-                     error output to go to standard error.",
+                    "Suppress diagnostics about file/directory-creation failure. This is useful \
+                     if a script does not want error output to go to standard error.",
                 )
                 .long("quiet")
                 .short("q"),
         )
         .arg(
-            Arg::with_name("t")
+            Arg::with_name("suffix")
+                .help("Append SUFF to TEMPLATE.")
+                .long_help(
+                    "Append SUFF to TEMPLATE.\n\nSUFF must not contain a slash. This option is \
+                     implied if TEMPLATE does not end in X, with the characters after its last X \
+                     as SUFF.",
+                )
+                .long("suffix")
+                .value_name("SUFF")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("tmpdir")
                 .help(
-                    "Create the file/directory in the directory specified by the TMPDIR \
-                     environment variable if set, otherwise /tmp/.",
+                    "Interpret TEMPLATE relative to DIR. If DIR is not specified, use $TMPDIR if \
+                     set, else /tmp.",
                 )
                 .long_help(
-                    "Create the file/directory in the directory specified by the TMPDIR \
-                     environment variable if set, otherwise /tmp/.\n\nIf TEMPLATE doesnot end \
-                     with X, generate a template using TEMPLATE as a prefix.\n\nIf TEMPLATE does \
-                     end with X, use it directly as a template.",
+                    "Interpret TEMPLATE relative to DIR. If DIR is not specified, use $TMPDIR if \
+                     set, else /tmp.\n\nWith this option, TEMPLATE must not be an absolute name. \
+                     Unlike with -t, TEMPLATE may contain slashes, but mktemp creates only the \
+                     final component.",
                 )
-                .long("single-file-name")
-                .short("t"),
+                .long("tmpdir")
+                .value_name("DIR")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .empty_values(true),
         )
         .arg(
-            Arg::with_name("unsafe")
-                .help("Unsafe mode. Use of this option is discouraged.")
+            Arg::with_name("p")
+                .help("Same as --tmpdir but requires a argument.")
+                .short("p")
+                .value_name("DIR")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("t")
+                .help(
+                    "Interpret TEMPLATE as a single file name component, relative to a directory: \
+                     $TMPDIR if set, else the directory of -p, else /tmp.",
+                )
                 .long_help(
-                    "Operate in \"unsafe\" mode.\n\nThe temp file will be unlinked before mktemp \
-                     exits.\nThis is slightly better than mktemp(3) but still introduces a race \
-                     condition.\n\nUse of this option is not encouraged.",
+                    "Interpret TEMPLATE as a single file name component, relative to a directory: \
+                     $TMPDIR if set, else the directory of -p, else /tmp.\n\nThis option is \
+                     deprecated, use --tmpdir instead.",
                 )
-                .long("unsafe")
-                .visible_alias("dry-run")
-                .short("u"),
+                .long("single-file-name")
+                .short("t"),
         )
 }
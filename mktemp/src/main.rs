@@ -1,117 +1,187 @@
-use std::{env, fs, path::PathBuf, process};
-
-use coreutils_core::{
-    libc::EINVAL,
-    mktemp::{mkdtemp, mkstemp},
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    os::unix::ffi::OsStrExt,
+    process,
 };
 
+use clap::ArgMatches;
+use coreutils_core::mktemp::Template;
+
 mod cli;
 
+/// The template used when none is given.
+const DEFAULT_TEMPLATE: &str = "tmp.XXXXXXXXXX";
+
 fn main() {
     let matches = cli::create_app().get_matches();
 
     let directory = matches.is_present("directory");
-    let quiet = matches.is_present("quiet");
-    let flag_t = matches.is_present("t");
-    let unsafe_flag = matches.is_present("unsafe");
-
-    // Construct a template appropriate for use with mkstemp/mkdtemp
-    let template = if let Some(template_arg) = matches.value_of("TEMPLATE") {
-        if flag_t {
-            // Use value of environment value TMPDIR if set, otherwise /tmp
-            let tmpdir = if let Ok(res) = env::var("TMPDIR") {
-                PathBuf::from(res)
-            } else {
-                PathBuf::from("/tmp")
-            };
-
-            // If the template argument ends with X, treat it as a template appropriate for use
-            // with mkstemp/mkdtemp.
-            // If it does not, construct an appropriate template using the string we were passed as
-            // a prefix.
-            // This means the user will get a reasonable result, regardless of whether we're called
-            // as if we were a BSD or GNU mktemp.
-            if template_arg.ends_with('X') {
-                tmpdir.join(template_arg)
-            } else {
-                tmpdir.join(format!("{}.XXXXXXXX", template_arg))
-            }
-        } else {
-            // -t was not passed, use the passed template as-is
-            PathBuf::from(template_arg)
-        }
+
+    let template = template(&matches).unwrap_or_else(|err| {
+        eprintln!("mktemp: {}", err);
+        process::exit(1);
+    });
+
+    let created = if matches.is_present("unsafe") {
+        template.unused_name()
+    } else if directory {
+        template.create_dir()
     } else {
-        // No template was passed, use a sensible default
-        PathBuf::from("/tmp/tmp.XXXXXXXX")
+        template.create_file().map(|(_, path)| path)
     };
 
-    if directory {
-        match mkdtemp(template.to_str().unwrap()) {
-            Ok(dir) => {
-                if unsafe_flag {
-                    if let Err(rmerr) = fs::remove_dir(dir.clone()) {
-                        if !quiet {
-                            eprintln!(
-                                "mktemp: Failed to remove temporary directory in unsafe mode: {}",
-                                rmerr
-                            );
-                        }
-                    }
-                }
-                println!("{}", dir);
-            },
-            Err(err) => {
-                if !quiet {
-                    eprintln!(
-                        "mktemp: failed to create directory using template '{}': {}",
-                        // Ok to unwrap cause the template is created over already checked UTF-8
-                        // strings
-                        template.to_str().unwrap(),
-                        // Ok to unwrap cause the function can only return an OS error kind
-                        if err.raw_os_error().unwrap() == EINVAL {
-                            "Too few X's in template".to_string()
-                        } else {
-                            format!("{}", err)
-                        }
-                    );
-                }
-                process::exit(1);
-            },
-        }
-    } else {
-        // Create a file
-
-        match mkstemp(template.to_str().unwrap()) {
-            Ok(res) => {
-                if unsafe_flag {
-                    if let Err(rmerr) = fs::remove_file(res.path.clone()) {
-                        if !quiet {
-                            eprintln!(
-                                "mktemp: Failed to remove temporary file in unsafe mode: {}",
-                                rmerr
-                            );
-                        }
-                    }
-                }
-                println!("{}", res);
-            },
-            Err(err) => {
-                if !quiet {
-                    eprintln!(
-                        "mktemp: failed to create file using template '{}': {}",
-                        // Ok to unwrap cause the template is created over already checked UTF-8
-                        // strings
-                        template.to_str().unwrap(),
-                        // Ok to unwrap cause the function can only return an OS error kind
-                        if err.raw_os_error().unwrap() == EINVAL {
-                            "Too few X's in template".to_string()
-                        } else {
-                            format!("{}", err)
-                        }
-                    );
-                }
-                process::exit(1);
-            },
-        }
+    match created {
+        Ok(path) => println!("{}", path.display()),
+        Err(err) => {
+            if !matches.is_present("quiet") {
+                eprintln!(
+                    "mktemp: failed to create {} via template '{}': {}",
+                    if directory { "directory" } else { "file" },
+                    template.path().display(),
+                    err
+                );
+            }
+            process::exit(1);
+        },
+    }
+}
+
+/// Get the template of the temporary file, with the suffix and the directory it is
+/// relative to.
+///
+/// # Errors
+/// If the template or the suffix are invalid, the message of the error is returned.
+fn template(matches: &ArgMatches) -> Result<Template, String> {
+    let given = matches.value_of_os("TEMPLATE");
+    let mut name = given.unwrap_or_else(|| OsStr::new(DEFAULT_TEMPLATE)).as_bytes().to_vec();
+
+    let suffix_len = match matches.value_of_os("suffix") {
+        Some(suffix) => {
+            if !name.ends_with(b"X") {
+                return Err(format!(
+                    "with --suffix, template '{}' must end in X",
+                    String::from_utf8_lossy(&name)
+                ));
+            }
+
+            name.extend_from_slice(suffix.as_bytes());
+            suffix.len()
+        },
+        // Without --suffix, the suffix is what is after the last X
+        None => name.iter().rev().position(|&b| b == b'X').unwrap_or(0),
+    };
+
+    let suffix = &name[name.len() - suffix_len..];
+    if suffix.contains(&b'/') {
+        return Err(format!(
+            "invalid suffix '{}', contains directory separator",
+            String::from_utf8_lossy(suffix)
+        ));
+    }
+
+    let template = Template::new(OsStr::from_bytes(&name), suffix_len)
+        .ok_or_else(|| format!("too few X's in template '{}'", String::from_utf8_lossy(&name)))?;
+
+    let single_name = matches.is_present("t");
+    if given.is_some() && !single_name && !matches.is_present("p") && !matches.is_present("tmpdir")
+    {
+        return Ok(template);
+    }
+
+    let dir_arg = matches
+        .value_of_os("p")
+        .or_else(|| matches.value_of_os("tmpdir"))
+        .filter(|dir| !dir.is_empty())
+        .map(OsStr::to_os_string);
+    let env_dir = env::var_os("TMPDIR").filter(|dir| !dir.is_empty());
+
+    // TMPDIR only has precedence over the directory given with -t
+    let dir = if single_name { env_dir.or(dir_arg) } else { dir_arg.or(env_dir) }
+        .unwrap_or_else(|| OsString::from("/tmp"));
+
+    if single_name && name.contains(&b'/') {
+        return Err(format!(
+            "invalid template, '{}', contains directory separator",
+            String::from_utf8_lossy(&name)
+        ));
+    }
+
+    if !single_name && name.starts_with(b"/") {
+        return Err(format!(
+            "invalid template, '{}'; with --tmpdir, it may not be absolute",
+            String::from_utf8_lossy(&name)
+        ));
+    }
+
+    Ok(template.in_dir(dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: Vec<&str>) -> Result<Template, String> {
+        template(&cli::create_app().get_matches_from(args))
+    }
+
+    #[test]
+    fn templates() {
+        assert_eq!(parse(vec!["mktemp", "a.XXX"]), Ok(Template::new("a.XXX", 0).unwrap()));
+        assert_eq!(parse(vec!["mktemp", "a.XXXb"]), Ok(Template::new("a.XXXb", 1).unwrap()));
+        assert_eq!(
+            parse(vec!["mktemp", "--suffix=.txt", "a.XXX"]),
+            Ok(Template::new("a.XXX.txt", 4).unwrap())
+        );
+        assert_eq!(
+            parse(vec!["mktemp", "-p", "/dir", "a.XXX"]),
+            Ok(Template::new("a.XXX", 0).unwrap().in_dir("/dir"))
+        );
+
+        assert_eq!(
+            parse(vec!["mktemp", "--suffix=.txt", "a.XXXb"]),
+            Err("with --suffix, template 'a.XXXb' must end in X".to_string())
+        );
+        assert_eq!(
+            parse(vec!["mktemp", "--suffix=b/c", "a.XXX"]),
+            Err("invalid suffix 'b/c', contains directory separator".to_string())
+        );
+        assert_eq!(
+            parse(vec!["mktemp", "a.XXbXX"]),
+            Err("too few X's in template 'a.XXbXX'".to_string())
+        );
+        assert_eq!(
+            parse(vec!["mktemp", "-t", "a/XXX"]),
+            Err("invalid template, 'a/XXX', contains directory separator".to_string())
+        );
+        assert_eq!(
+            parse(vec!["mktemp", "-p", "/dir", "/a.XXX"]),
+            Err("invalid template, '/a.XXX'; with --tmpdir, it may not be absolute".to_string())
+        );
+    }
+
+    #[test]
+    fn directories() {
+        let default = || Template::new(DEFAULT_TEMPLATE, 0).unwrap();
+        let template = || Template::new("a.XXX", 0).unwrap();
+
+        env::set_var("TMPDIR", "/env");
+        assert_eq!(parse(vec!["mktemp"]), Ok(default().in_dir("/env")));
+        assert_eq!(parse(vec!["mktemp", "-p", "/dir"]), Ok(default().in_dir("/dir")));
+        assert_eq!(parse(vec!["mktemp", "-p", ""]), Ok(default().in_dir("/env")));
+
+        // TMPDIR is only before the directory given with -t
+        assert_eq!(parse(vec!["mktemp", "-t", "a.XXX"]), Ok(template().in_dir("/env")));
+        assert_eq!(
+            parse(vec!["mktemp", "-t", "-p", "/dir", "a.XXX"]),
+            Ok(template().in_dir("/env"))
+        );
+
+        env::remove_var("TMPDIR");
+        assert_eq!(parse(vec!["mktemp"]), Ok(default().in_dir("/tmp")));
+        assert_eq!(
+            parse(vec!["mktemp", "-t", "-p", "/dir", "a.XXX"]),
+            Ok(template().in_dir("/dir"))
+        );
     }
 }
@@ -15,21 +15,33 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("SOURCE")
                 .help("Source files and destination files.")
                 .value_names(&["SOURCE", "DEST"])
-                .multiple(true)
-                .required(true),
+                .multiple(true),
         )
         .arg(
             Arg::with_name("backup")
                 .help("Make a backup of each existing destination file.")
+                .long_help(
+                    "Make a backup of each existing destination file.\n\nCONTROL is 'none' or \
+                     'off' to never make backups, 'numbered' or 't' to make numbered backups, \
+                     'existing' or 'nil' to make numbered backups of the files that have them and \
+                     simple backups of the others, or 'simple' or 'never' to make simple backups. \
+                     Without it the VERSION_CONTROL environment variable is used, and 'existing' \
+                     by default.",
+                )
                 .long("backup")
-                .short("b")
-                .value_name("TYPE")
-                .env("VERSION_CONTROL")
-                .default_value("existing")
+                .value_name("CONTROL")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
                 .possible_values(&[
                     "none", "off", "numbered", "t", "existing", "nil", "simple", "never",
                 ]),
         )
+        .arg(
+            Arg::with_name("backupDefault")
+                .help("Same as --backup but does not accept a argument.")
+                .short("b"),
+        )
         .arg(
             Arg::with_name("force")
                 .help("Do not prompt before overwriting.")
@@ -38,8 +50,7 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                      or -i options.",
                 )
                 .long("force")
-                .short("f")
-                .overrides_with_all(&["noClobber", "interactive"]),
+                .short("f"),
         )
         .arg(
             Arg::with_name("interactive")
@@ -51,20 +62,17 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                      option overrides any previous -f or -n options.",
                 )
                 .long("interactive")
-                .short("i")
-                .overrides_with_all(&["noClobber", "force"]),
+                .short("i"),
         )
         .arg(
             Arg::with_name("noClobber")
                 .help("Do not overwrite an existing file.")
                 .long_help(
                     "Do not overwrite an existing file.\n\nThis option overrides any previous -f \
-                     or -i options.",
+                     or -i options, and any later --update option.",
                 )
                 .long("no-clobber")
-                .short("n")
-                .overrides_with_all(&["force", "interactive"])
-                .conflicts_with("backup"),
+                .short("n"),
         )
         .arg(
             Arg::with_name("stripTrailingSlashes")
@@ -74,20 +82,23 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
         )
         .arg(
             Arg::with_name("suffix")
-                .help("Override the usual backup suffix.")
+                .help("Override the usual backup suffix, '~' by default.")
+                .long_help(
+                    "Override the usual backup suffix. Without it the SIMPLE_BACKUP_SUFFIX \
+                     environment variable is used, and '~' by default.\n\nIt also makes the \
+                     backups like --backup.",
+                )
                 .long("suffix")
                 .short("S")
-                .value_name("STRING")
-                .env("SIMPLE_BACKUP_SUFFIX")
-                .default_value("~"),
+                .value_name("SUFFIX")
+                .takes_value(true),
         )
         .arg(
             Arg::with_name("targetDirectory")
                 .help("Move all SOURCE arguments into DIRECTORY.")
                 .long("target-directory")
                 .short("t")
-                .value_name("DIRECTORY")
-                .conflicts_with("noTargetDirectory"),
+                .value_name("DIRECTORY"),
         )
         .arg(
             Arg::with_name("noTargetDirectory")
@@ -97,11 +108,26 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
         )
         .arg(
             Arg::with_name("update")
+                .help("Control which existing destination files are replaced.")
+                .long_help(
+                    "Control which existing destination files are replaced.\n\nMODE is 'all' to \
+                     replace them all, as without this option, 'none' to replace none of them, or \
+                     'older' to only replace the ones older than the source file. It is 'older' \
+                     without MODE.\n\nThis option overrides any previous -i option.",
+                )
+                .long("update")
+                .value_name("MODE")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&["all", "none", "older"]),
+        )
+        .arg(
+            Arg::with_name("updateOlder")
                 .help(
                     "Move only when the SOURCE file is newer than the destination file or when \
                      the destination file is missing.",
                 )
-                .long("update")
                 .short("u"),
         )
         .arg(
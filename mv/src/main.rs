@@ -1,10 +1,24 @@
+#[cfg(unix)]
+use std::io;
 use std::{
-    fs,
-    path::{Path, PathBuf},
+    env,
+    fs::{self, Metadata},
+    path::Path,
+    process,
 };
 
 use clap::ArgMatches;
-use coreutils_core::{backup::*, input::*};
+use coreutils_core::{
+    backup::*,
+    error::strerror,
+    input::*,
+    target::{find_target, Target},
+};
+#[cfg(unix)]
+use coreutils_core::{
+    copier::{Copier, CopyOptions},
+    libc,
+};
 
 mod cli;
 
@@ -13,108 +27,161 @@ fn main() {
 
     let flags = MvFlags::from_matches(&matches);
 
-    let sources: Vec<PathBuf> = {
+    let operands: Vec<&Path> = {
         let strip = flags.strip_trailing_slashes;
 
-        // "SOURCE" is required, so it is ok to unwrap
         matches
             .values_of("SOURCE")
-            .unwrap()
-            .map(Path::new)
-            .map(|val| if strip { val.components().as_path() } else { val })
-            .map(|val| val.to_owned())
-            .collect()
+            .map(|values| {
+                values
+                    .map(Path::new)
+                    .map(|val| if strip { val.components().as_path() } else { val })
+                    .collect()
+            })
+            .unwrap_or_default()
     };
 
-    let success = if !flags.target_directory.is_empty() {
-        move_files(sources, &PathBuf::from(&flags.target_directory), &flags)
-    } else if !flags.no_target_directory && sources.last().unwrap().is_dir() {
-        let target = sources.last().unwrap();
-        move_files(sources[..sources.len() - 1].to_vec(), &target.to_path_buf(), &flags)
-    } else if sources.len() == 2 {
-        rename_file(&sources[0], &sources[1], &flags)
-    } else if sources.len() == 1 {
-        eprintln!("mv: No target supplied");
-        false
-    } else {
-        let target = sources.last().unwrap();
-        move_files(sources[..sources.len() - 1].to_vec(), &target.to_path_buf(), &flags)
+    let target = find_target(
+        &operands,
+        matches.value_of("targetDirectory").map(Path::new),
+        flags.no_target_directory,
+    );
+    let success = match target {
+        Ok(Target::Directory { sources, directory }) => move_files(&sources, directory, &flags),
+        Ok(Target::File { source, dest }) => rename_file(source, dest, &flags),
+        Err(err) => {
+            eprintln!("mv: {}", err);
+            false
+        },
     };
 
     if !success {
-        std::process::exit(1);
+        process::exit(1);
     }
 }
 
-#[derive(Debug, Clone)]
-enum OverwriteMode {
-    Force,
-    Interactive,
-    NoClobber,
+/// What is done with the destination files that exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Overwrite {
+    /// They are replaced.
+    Always,
+    /// The user is asked whether to replace each of them, with `-i`.
+    Ask,
+    /// They are kept, with `-n` and `--update=none`.
+    Never,
 }
 
 #[derive(Debug, Clone)]
 struct MvFlags {
-    backup: BackupMode,
-    overwrite: OverwriteMode,
+    overwrite: Overwrite,
+    /// Only the destination files older than their sources are replaced, with `-u`.
     update: bool,
+    backup: BackupMode,
+    suffix: String,
     strip_trailing_slashes: bool,
     verbose: bool,
-    suffix: String,
-    target_directory: String,
     no_target_directory: bool,
 }
 
-impl OverwriteMode {
-    pub fn from_matches(matches: &ArgMatches) -> OverwriteMode {
-        let mut res = (OverwriteMode::Force, 0);
+impl MvFlags {
+    pub fn from_matches(matches: &ArgMatches) -> MvFlags {
+        // The options of the existing destinations apply in order, but `-n` is not
+        // overridden by the later `--update`
+        let (mut overwrite, mut update, mut no_clobber) = (Overwrite::Always, false, false);
 
-        if matches.is_present("force") && matches.index_of("force").unwrap() > res.1 {
-            res = (OverwriteMode::Force, matches.index_of("force").unwrap());
-        }
+        let mut overwrite_options: Vec<(usize, &str)> =
+            ["force", "interactive", "noClobber", "update", "updateOlder"]
+                .iter()
+                .filter_map(|&name| matches.index_of(name).map(|index| (index, name)))
+                .collect();
+        overwrite_options.sort_unstable();
 
-        if matches.is_present("interactive") && matches.index_of("interactive").unwrap() > res.1 {
-            res = (OverwriteMode::Interactive, matches.index_of("interactive").unwrap());
+        for (_, name) in overwrite_options {
+            match (name, matches.value_of("update")) {
+                ("force", _) => {
+                    overwrite = Overwrite::Always;
+                    no_clobber = false;
+                },
+                ("interactive", _) => {
+                    overwrite = Overwrite::Ask;
+                    no_clobber = false;
+                },
+                ("noClobber", _) => {
+                    overwrite = Overwrite::Never;
+                    no_clobber = true;
+                },
+                ("updateOlder", _) => update = true,
+                ("update", Some(mode)) if !no_clobber => {
+                    update = mode == "older";
+                    overwrite = if mode == "none" { Overwrite::Never } else { Overwrite::Always };
+                },
+                _ => {},
+            }
         }
 
-        if matches.is_present("noClobber") && matches.index_of("noClobber").unwrap() > res.1 {
-            res = (OverwriteMode::NoClobber, matches.index_of("noClobber").unwrap());
+        // Without a value `--update` has no index, but only sets what `-u` does
+        if matches.is_present("update") && matches.value_of("update").is_none() {
+            update = true;
         }
 
-        res.0
-    }
-}
+        let backup = if matches.is_present("backup")
+            || matches.is_present("backupDefault")
+            || matches.is_present("suffix")
+        {
+            match matches.value_of("backup") {
+                Some(control) => BackupMode::from(control),
+                None => match env::var("VERSION_CONTROL") {
+                    Ok(control) if control.is_empty() => BackupMode::Existing,
+                    Ok(control) if BACKUP_CONTROLS.contains(&control.as_str()) => {
+                        BackupMode::from(control.as_str())
+                    },
+                    Ok(control) => {
+                        eprintln!("mv: invalid argument '{}' for '$VERSION_CONTROL'", control);
+                        process::exit(1);
+                    },
+                    Err(_) => BackupMode::Existing,
+                },
+            }
+        } else {
+            BackupMode::None
+        };
 
-impl MvFlags {
-    pub fn from_matches(matches: &ArgMatches) -> MvFlags {
-        let target_dir = matches.value_of("targetDirectory").unwrap_or("").to_string();
+        if backup != BackupMode::None && overwrite == Overwrite::Never {
+            eprintln!("mv: options --backup and --no-clobber are mutually exclusive");
+            process::exit(1);
+        }
+
+        // A suffix with a slash would put the backups in other directory
+        let suffix = match matches.value_of("suffix") {
+            Some(suffix) => suffix.to_string(),
+            None => env::var("SIMPLE_BACKUP_SUFFIX").unwrap_or_default(),
+        };
+        let suffix =
+            if suffix.is_empty() || suffix.contains('/') { "~".to_string() } else { suffix };
 
         MvFlags {
-            backup: BackupMode::from_string(matches.value_of("backup").unwrap()),
-            overwrite: OverwriteMode::from_matches(matches),
-            update: matches.is_present("update"),
+            overwrite,
+            update,
+            backup,
+            suffix,
             strip_trailing_slashes: matches.is_present("stripTrailingSlashes"),
             verbose: matches.is_present("verbose"),
-            suffix: matches.value_of("suffix").unwrap().to_string(),
-            target_directory: target_dir,
             no_target_directory: matches.is_present("noTargetDirectory"),
         }
     }
 }
 
+/// The values of `--backup` and the `VERSION_CONTROL` environment variable.
+const BACKUP_CONTROLS: [&str; 8] =
+    ["none", "off", "numbered", "t", "existing", "nil", "simple", "never"];
 
-fn move_files(sources: Vec<PathBuf>, target: &Path, flags: &MvFlags) -> bool {
-    if !target.is_dir() {
-        eprintln!("mv: '{}' is not a directory", target.display());
-        return false;
-    }
-
+fn move_files(sources: &[&Path], target: &Path, flags: &MvFlags) -> bool {
     let mut success = true;
     for source in sources {
         if let Some(filename) = source.file_name() {
             let new = target.join(filename);
 
-            if !rename_file(&source, &new, flags) {
+            if !rename_file(source, &new, flags) {
                 success = false;
             }
         } else {
@@ -127,73 +194,225 @@ fn move_files(sources: Vec<PathBuf>, target: &Path, flags: &MvFlags) -> bool {
 }
 
 fn rename_file(curr: &Path, new: &Path, flags: &MvFlags) -> bool {
-    if new.exists() {
-        match &flags.overwrite {
-            OverwriteMode::Force => {},
-            OverwriteMode::Interactive => {
-                let is_affirmative = Input::new()
-                    .with_msg(&format!("mv: overwrite '{}'?", new.display()))
-                    .with_err_msg("mv: could not read user input")
-                    .is_affirmative();
-                if !is_affirmative {
-                    return true;
-                }
-            },
-            OverwriteMode::NoClobber => return true,
-        };
+    let metadata = match fs::symlink_metadata(curr) {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            eprintln!("mv: cannot stat '{}': {}", curr.display(), strerror(&err));
+            return false;
+        },
+    };
+
+    let mut backup = None;
+
+    if let Ok(new_metadata) = fs::symlink_metadata(new) {
+        let same = same_file(curr, new, &metadata, &new_metadata);
 
-        if flags.update && file_older(curr, new) {
+        // Like GNU, nothing is done with -n, and the backup moves the destination away
+        if same && flags.overwrite == Overwrite::Never {
             return true;
         }
 
-        let res = match &flags.backup {
-            BackupMode::Numbered => Some(create_numbered_backup(new)),
-            BackupMode::Existing => Some(create_existing_backup(new, &flags.suffix)),
-            BackupMode::Simple => Some(create_simple_backup(new, &flags.suffix)),
-            BackupMode::None => None,
+        if same && (flags.backup == BackupMode::None || is_same_entry(curr, new)) {
+            eprintln!("mv: '{}' and '{}' are the same file", curr.display(), new.display());
+            return false;
+        }
+
+        if !replaces(new, &metadata, &new_metadata, flags) {
+            return true;
+        }
+
+        let made = match flags.backup {
+            BackupMode::None => Ok(None),
+            BackupMode::Numbered => create_numbered_backup(new).map(Some),
+            BackupMode::Existing => create_existing_backup(new, &flags.suffix).map(Some),
+            BackupMode::Simple => create_simple_backup(new, &flags.suffix).map(Some),
         };
 
-        if let Some(res) = res {
-            match res {
-                Ok(file) => println!("mv: Created backup file {}", file.display()),
-                Err(err) => {
-                    eprintln!("mv: Backup failed: {}", err);
-                    return false;
-                },
-            };
+        match made {
+            Ok(made) => backup = made,
+            Err(err) => {
+                eprintln!("mv: cannot backup '{}': {}", new.display(), strerror(&err));
+                return false;
+            },
         }
     }
 
-    match fs::rename(curr, new) {
-        Ok(()) => {
+    let renamed = match fs::rename(curr, new) {
+        #[cfg(unix)]
+        Err(err) if err.raw_os_error() == Some(libc::EXDEV) => move_across(curr, new),
+        renamed => renamed.map(|_| true),
+    };
+
+    match renamed {
+        Ok(false) => false,
+        Ok(true) => {
             if flags.verbose {
-                println!("mv: Renamed {} to {}", curr.display(), new.display());
+                match backup {
+                    Some(backup) => println!(
+                        "renamed '{}' -> '{}' (backup: '{}')",
+                        curr.display(),
+                        new.display(),
+                        backup.display()
+                    ),
+                    None => println!("renamed '{}' -> '{}'", curr.display(), new.display()),
+                }
             }
 
             true
         },
-        Err(msg) => {
-            eprintln!("mv: Cannot rename {}: {}", curr.display(), msg);
+        Err(err) => {
+            eprintln!(
+                "mv: cannot move '{}' to '{}': {}",
+                curr.display(),
+                new.display(),
+                strerror(&err)
+            );
             false
         },
     }
 }
 
-fn file_older(f: &Path, ff: &Path) -> bool {
-    let f_attrs = match fs::metadata(f) {
-        Ok(attrs) => attrs,
-        Err(msg) => {
-            eprintln!("mv: stat failed: {}", msg);
-            return true;
-        },
-    };
-    let ff_attrs = match fs::metadata(ff) {
-        Ok(attrs) => attrs,
-        Err(msg) => {
-            eprintln!("mv: stat failed: {}", msg);
-            return true;
-        },
+/// Returns `true` if `curr` and `new`, of the `metadata` and `new_metadata`, are the same
+/// file.
+#[cfg(unix)]
+fn same_file(_curr: &Path, _new: &Path, metadata: &Metadata, new_metadata: &Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    new_metadata.dev() == metadata.dev() && new_metadata.ino() == metadata.ino()
+}
+
+/// Returns `true` if `curr` and `new`, of the `metadata` and `new_metadata`, are the same
+/// file.
+#[cfg(not(unix))]
+fn same_file(curr: &Path, new: &Path, _metadata: &Metadata, _new_metadata: &Metadata) -> bool {
+    match (fs::canonicalize(curr), fs::canonicalize(new)) {
+        (Ok(curr), Ok(new)) => curr == new,
+        _ => false,
+    }
+}
+
+/// Returns `true` if the existing `new` is replaced by the source of the `metadata`, by
+/// `--update` and asking the user with `-i`.
+fn replaces(new: &Path, metadata: &Metadata, new_metadata: &Metadata, flags: &MvFlags) -> bool {
+    if flags.update {
+        if let (Ok(modified), Ok(new_modified)) = (metadata.modified(), new_metadata.modified()) {
+            if new_modified >= modified {
+                return false;
+            }
+        }
+    }
+
+    match flags.overwrite {
+        Overwrite::Always => true,
+        Overwrite::Never => false,
+        Overwrite::Ask => Input::new()
+            .with_msg(&format!("mv: overwrite '{}'? ", new.display()))
+            .with_err_msg("mv: could not read user input")
+            .is_affirmative(),
+    }
+}
+
+/// Move `curr` to `new` on other file system, copying it with all its attributes and then
+/// removing it, returning `false` if the error was already reported.
+///
+/// Like a rename, the file or empty directory in `new` is replaced. When the copy fails
+/// what was copied is removed, leaving `curr` as it was.
+#[cfg(unix)]
+fn move_across(curr: &Path, new: &Path) -> io::Result<bool> {
+    let removed = match fs::symlink_metadata(new) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir(new),
+        Ok(_) => fs::remove_file(new),
+        Err(_) => Ok(()),
     };
 
-    f_attrs.modified().unwrap() < ff_attrs.modified().unwrap()
+    if let Err(err) = removed {
+        eprintln!(
+            "mv: inter-device move failed: '{}' to '{}'; unable to remove target: {}",
+            curr.display(),
+            new.display(),
+            strerror(&err)
+        );
+        return Ok(false);
+    }
+
+    let options = CopyOptions::archive();
+
+    if !Copier::new("mv", &options).copy(curr, new) {
+        let _ = remove_all(new);
+        return Ok(false);
+    }
+
+    if let Err(err) = remove_all(curr) {
+        eprintln!("mv: cannot remove '{}': {}", curr.display(), strerror(&err));
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+/// Remove the file in `path`, with its contents if it is a directory.
+#[cfg(unix)]
+fn remove_all(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(args: &[&str]) -> MvFlags {
+        let args = ["mv"].iter().chain(args).chain(&["a", "b"]);
+        MvFlags::from_matches(&cli::create_app().get_matches_from(args))
+    }
+
+    #[test]
+    fn overwrite() {
+        let overwrite = |args| {
+            let flags = flags(args);
+            (flags.overwrite, flags.update)
+        };
+
+        assert_eq!(overwrite(&[]), (Overwrite::Always, false));
+        assert_eq!(overwrite(&["-n", "-i"]), (Overwrite::Ask, false));
+        assert_eq!(overwrite(&["-i", "-f"]), (Overwrite::Always, false));
+        assert_eq!(overwrite(&["-f", "-n"]), (Overwrite::Never, false));
+        assert_eq!(overwrite(&["-i", "-u"]), (Overwrite::Ask, true));
+        assert_eq!(overwrite(&["-i", "--update=older"]), (Overwrite::Always, true));
+        assert_eq!(overwrite(&["-n", "--update=all"]), (Overwrite::Never, false));
+    }
+
+    #[test]
+    fn backup() {
+        assert_eq!(flags(&[]).backup, BackupMode::None);
+        assert_eq!(flags(&["--backup=numbered"]).backup, BackupMode::Numbered);
+        assert_eq!(flags(&["--backup=off"]).backup, BackupMode::None);
+        assert_eq!(flags(&["-S", ".bak"]).suffix, ".bak");
+        assert_eq!(flags(&["--suffix=a/b", "-b"]).suffix, "~");
+    }
+
+    #[test]
+    fn same_file_backup() {
+        let dir = env::temp_dir().join(format!("mv.same.{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (source, link) = (dir.join("source"), dir.join("link"));
+        fs::write(&source, b"same").unwrap();
+        fs::hard_link(&source, &link).unwrap();
+
+        assert!(!rename_file(&source, &link, &flags(&[])));
+        assert!(rename_file(&source, &link, &flags(&["-n"])));
+        assert!(source.exists());
+
+        // The backup moves the link away
+        assert!(rename_file(&source, &link, &flags(&["-b"])));
+        assert!(!source.exists());
+        assert_eq!(fs::read(&link).unwrap(), b"same");
+        assert_eq!(fs::read(dir.join("link~")).unwrap(), b"same");
+        assert!(!rename_file(&link, &dir.join(".").join("link"), &flags(&["-b"])));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -73,6 +73,7 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .long("line-increment")
                 .short("i")
                 .value_name("NUMBER")
+                .allow_hyphen_values(true)
                 .default_value("1"),
         )
         .arg(
@@ -81,13 +82,14 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .long_help(
                     "Group of NUMBER empty lines counted as one.\n\nIf numbering of all lines is \
                      specified for the current logical section using the corresponding '-b a', \
-                     '-f a' or '-H' a option, specify the number of adjacent blank lines to be \
+                     '-f a' or '-h a' option, specify the number of adjacent blank lines to be \
                      considered as one. For example, '-l 2' results in only the second adjacent \
                      blank line being numbered.",
                 )
                 .long("join-blank-lines")
                 .short("l")
                 .value_name("NUMBER")
+                .allow_hyphen_values(true)
                 .default_value("1"),
         )
         .arg(
@@ -101,8 +103,7 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .long("number-format")
                 .short("n")
                 .value_name("FORMAT")
-                .default_value("rn")
-                .possible_values(&["ln", "rn", "rz"]),
+                .default_value("rn"),
         )
         .arg(
             Arg::with_name("no_renumber")
@@ -117,7 +118,8 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 )
                 .long("number-separator")
                 .short("s")
-                .value_name("STRING"),
+                .value_name("STRING")
+                .allow_hyphen_values(true),
         )
         .arg(
             Arg::with_name("starting_line_number")
@@ -125,6 +127,7 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .help("First line number for each section.")
                 .short("v")
                 .value_name("NUMBER")
+                .allow_hyphen_values(true)
                 .default_value("1"),
         )
         .arg(
@@ -133,6 +136,7 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .long("number-width")
                 .short("w")
                 .value_name("NUMBER")
+                .allow_hyphen_values(true)
                 .default_value("6"),
         )
 }
@@ -1,46 +1,35 @@
 use std::{
-    env::current_dir,
+    ffi::OsStr,
     fs::File,
-    io::{prelude::BufRead, stdin, stdout, BufReader, Write},
+    io::{stdin, stdout, BufRead, BufReader, BufWriter, Write},
+    path::Path,
     process,
 };
 
 use clap::ArgMatches;
-use regex::Regex;
+use coreutils_core::bre;
+use regex::bytes::Regex;
 
 mod cli;
 
 fn main() {
     let matches = cli::create_app().get_matches();
 
-    let nl_args = NlArgs::from_matches(&matches);
+    let nl_args = NlArgs::from_matches(&matches).unwrap_or_else(|err| {
+        eprintln!("nl: {}", err);
+        process::exit(1);
+    });
 
     let mut nl = Nl::new(nl_args);
 
-    let cwd = match current_dir() {
-        Ok(path) => path,
-        Err(err) => {
-            eprintln!("nl: error reading current working directory: {}", err);
-            process::exit(1);
-        },
+    let files: Vec<&OsStr> = match matches.values_of_os("FILE") {
+        Some(files) => files.collect(),
+        None => vec![OsStr::new("-")],
     };
 
-    let files: Vec<String> = match matches.values_of("FILE") {
-        Some(files) => files
-            .map(|file| {
-                if file == "-" {
-                    return String::from("-");
-                }
-
-                file.split_whitespace()
-                    .map(|s| cwd.join(s.to_string()).to_str().unwrap().to_string())
-                    .collect()
-            })
-            .collect(),
-        None => vec!["-".to_string()],
-    };
-
-    nl.convert(files);
+    if !nl.convert(&files) {
+        process::exit(1);
+    }
 }
 
 
@@ -58,37 +47,41 @@ enum Style {
 impl PartialEq for Style {
     fn eq(&self, other: &Style) -> bool {
         match (self, other) {
-            (&Style::All, &Style::All)
-            | (&Style::Nonempty, &Style::Nonempty)
-            | (&Style::None, &Style::None) => true,
-            (&Style::Regex(ref reg1), &Style::Regex(ref reg2)) => {
-                reg1.to_string() == reg2.to_string()
-            },
+            (Style::All, Style::All)
+            | (Style::Nonempty, Style::Nonempty)
+            | (Style::None, Style::None) => true,
+            (Style::Regex(reg1), Style::Regex(reg2)) => reg1.as_str() == reg2.as_str(),
             (..) => false,
         }
     }
 }
 
 impl Style {
-    fn from_value(value: Option<&str>, default_value: Style) -> Self {
+    /// The style of the `value` of the numbering of the `section`, or the
+    /// `default_value` if there is none, where `pBRE` has a basic regular expression.
+    ///
+    /// # Errors
+    /// If the style is not valid, or its regular expression isn't, an error message will
+    /// be returned.
+    fn from_value(
+        value: Option<&str>, default_value: Style, section: &str,
+    ) -> Result<Self, String> {
         match value {
-            Some("a") => Style::All,
-            Some("t") => Style::Nonempty,
-            Some("n") => Style::None,
+            Some("a") => Ok(Style::All),
+            Some("t") => Ok(Style::Nonempty),
+            Some("n") => Ok(Style::None),
             Some(reg) => {
                 if let Some(r) = reg.strip_prefix('p') {
-                    let regex = Regex::new(r).unwrap_or_else(|err| {
-                        eprintln!("{}", err.to_string());
-                        std::process::exit(1);
-                    });
+                    let translated = bre::translate(r).map_err(|err| err.to_string())?;
+                    let regex =
+                        Regex::new(&translated).map_err(|_| bre::BreError::Invalid.to_string())?;
 
-                    Style::Regex(regex)
+                    Ok(Style::Regex(regex))
                 } else {
-                    eprintln!("nl: invalid body numbering style: ‘{}’", reg[1..].to_string());
-                    std::process::exit(1);
+                    Err(format!("invalid {} numbering style: '{}'", section, reg))
                 }
             },
-            None => default_value,
+            None => Ok(default_value),
         }
     }
 }
@@ -101,15 +94,21 @@ enum Format {
 }
 
 impl Format {
-    fn from_value(value: Option<&str>) -> Self {
+    fn from_value(value: Option<&str>) -> Result<Self, String> {
         match value {
-            Some("ln") => Format::Ln,
-            Some("rn") | None => Format::Rn,
-            Some("rz") => Format::Rz,
-            Some(s) => {
-                eprintln!("nl: invalid line numbering format: ‘{}’", s);
-                std::process::exit(1);
-            },
+            Some("ln") => Ok(Format::Ln),
+            Some("rn") | None => Ok(Format::Rn),
+            Some("rz") => Ok(Format::Rz),
+            Some(s) => Err(format!("invalid line numbering format: '{}'", s)),
+        }
+    }
+
+    /// The `number` in the format, in the `width`.
+    fn format(&self, number: i64, width: usize) -> String {
+        match self {
+            Format::Ln => format!("{:<1$}", number, width),
+            Format::Rn => format!("{:>1$}", number, width),
+            Format::Rz => format!("{:01$}", number, width),
         }
     }
 }
@@ -120,7 +119,7 @@ struct NlArgs {
     section_delimiter: String,
     footer_numbering: Style,
     header_numbering: Style,
-    line_increment: usize,
+    line_increment: i64,
     join_blank_lines: usize,
     number_format: Format,
     no_renumber: bool,
@@ -130,44 +129,86 @@ struct NlArgs {
 }
 
 impl NlArgs {
-    fn from_matches(matches: &ArgMatches) -> Self {
-        let line_increment_str = matches.value_of("line_increment").unwrap_or("1");
-        let line_increment = line_increment_str.parse::<usize>().unwrap_or_else(|_err| {
-            eprintln!("nl: invalid line number increment: ‘{}’", line_increment_str);
-            std::process::exit(1);
-        });
-
-        let join_blank_lines_str = matches.value_of("join_blank_lines").unwrap_or("1");
-        let join_blank_lines = join_blank_lines_str.parse::<usize>().unwrap_or_else(|_err| {
-            eprintln!("nl: invalid line number of blank lines: ‘{}’", join_blank_lines_str);
-            std::process::exit(1);
-        });
-
-        let starting_line_number_str = matches.value_of("starting_line_number_str").unwrap_or("1");
-        let starting_line_number = starting_line_number_str.parse::<i64>().unwrap_or_else(|_err| {
-            eprintln!("nl: invalid starting line number: ‘{}’", starting_line_number_str);
-            std::process::exit(1);
-        });
-
-        let number_width_str = matches.value_of("number_width").unwrap_or("6");
-        let number_width = number_width_str.parse::<usize>().unwrap_or_else(|_err| {
-            eprintln!("nl: invalid line number field width: ‘{}’", number_width_str);
-            std::process::exit(1);
-        });
-
-        NlArgs {
-            body_numbering: Style::from_value(matches.value_of("body_numbering"), Style::Nonempty),
+    /// The arguments of the `matches`.
+    ///
+    /// # Errors
+    /// If an option has a value that is not valid, an error message will be returned.
+    fn from_matches(matches: &ArgMatches) -> Result<Self, String> {
+        let line_increment = parse_number(
+            matches.value_of("line_increment").unwrap_or("1"),
+            i64::MIN,
+            i64::MAX,
+            "invalid line number increment",
+        )?;
+
+        let join_blank_lines = parse_number(
+            matches.value_of("join_blank_lines").unwrap_or("1"),
+            1,
+            i64::MAX,
+            "invalid line number of blank lines",
+        )? as usize;
+
+        let starting_line_number = parse_number(
+            matches.value_of("starting_line_number").unwrap_or("1"),
+            i64::MIN,
+            i64::MAX,
+            "invalid starting line number",
+        )?;
+
+        let number_width = parse_number(
+            matches.value_of("number_width").unwrap_or("6"),
+            1,
+            i64::from(i32::MAX),
+            "invalid line number field width",
+        )? as usize;
+
+        Ok(NlArgs {
+            body_numbering: Style::from_value(
+                matches.value_of("body_numbering"),
+                Style::Nonempty,
+                "body",
+            )?,
             section_delimiter: matches.value_of("section_delimiter").unwrap_or("\\:").to_string(),
-            footer_numbering: Style::from_value(matches.value_of("footer_numbering"), Style::None),
-            header_numbering: Style::from_value(matches.value_of("header_numbering"), Style::None),
+            footer_numbering: Style::from_value(
+                matches.value_of("footer_numbering"),
+                Style::None,
+                "footer",
+            )?,
+            header_numbering: Style::from_value(
+                matches.value_of("header_numbering"),
+                Style::None,
+                "header",
+            )?,
             line_increment,
             join_blank_lines,
-            number_format: Format::from_value(matches.value_of("number_format")),
+            number_format: Format::from_value(matches.value_of("number_format"))?,
             no_renumber: matches.is_present("no_renumber"),
             number_separator: matches.value_of("number_separator").unwrap_or("\t").to_string(),
             starting_line_number,
             number_width,
-        }
+        })
+    }
+}
+
+/// Parse the decimal `number`, between `min` and `max`, where `what` it is starts the
+/// error message.
+///
+/// # Errors
+/// If it is not a number, or it is not in the range, an error message will be returned.
+fn parse_number(number: &str, min: i64, max: i64, what: &str) -> Result<i64, String> {
+    let digits = number.strip_prefix(|c| c == '+' || c == '-').unwrap_or(number);
+
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(format!("{}: '{}'", what, number));
+    }
+
+    match number.parse::<i64>() {
+        Ok(value) if min <= value && value <= max => Ok(value),
+        // Like GNU, the values that are far from the range of an `int` are too large
+        Ok(value) if i64::from(i32::MIN / 2) <= value && value <= i64::from(i32::MAX / 2) => {
+            Err(format!("{}: '{}': Numerical result out of range", what, number))
+        },
+        _ => Err(format!("{}: '{}': Value too large for defined data type", what, number)),
     }
 }
 
@@ -179,88 +220,152 @@ enum Section {
 }
 
 struct SectionDelimiters {
-    body: String,
-    header: String,
-    footer: String,
+    body: Vec<u8>,
+    header: Vec<u8>,
+    footer: Vec<u8>,
 }
 
 impl SectionDelimiters {
-    fn new(delimiter: String) -> SectionDelimiters {
-        SectionDelimiters {
-            header: delimiter.repeat(3),
-            body: delimiter.repeat(2),
-            footer: delimiter,
+    /// The delimiters of the sections of the `delimiter`, where a single character is
+    /// followed by a `:`, and an empty one delimits no section.
+    fn new(delimiter: &str) -> SectionDelimiters {
+        let mut footer = delimiter.as_bytes().to_vec();
+
+        if footer.len() == 1 {
+            footer.push(b':');
+        }
+
+        SectionDelimiters { header: footer.repeat(3), body: footer.repeat(2), footer }
+    }
+
+    /// The section that the `line` starts, if it is a delimiter.
+    fn section(&self, line: &[u8]) -> Option<Section> {
+        if self.footer.is_empty() {
+            None
+        } else if line == &self.header[..] {
+            Some(Section::Header)
+        } else if line == &self.body[..] {
+            Some(Section::Body)
+        } else if line == &self.footer[..] {
+            Some(Section::Footer)
+        } else {
+            None
         }
     }
 }
 
 struct Nl {
     ind: i64,
+    /// Whether the next line number overflowed, which is an error only if it is used.
+    overflow: bool,
     section: Section,
     num_of_prev_blank_lines: usize,
     section_delimiters: SectionDelimiters,
+    /// The spaces of the lines that are not numbered.
+    no_number: Vec<u8>,
     args: NlArgs,
 }
 
 impl Nl {
     fn new(args: NlArgs) -> Self {
-        let section_delimiters = SectionDelimiters::new(args.section_delimiter.clone());
+        let section_delimiters = SectionDelimiters::new(&args.section_delimiter);
+        let no_number = vec![b' '; args.number_width + args.number_separator.len()];
 
         Nl {
             ind: args.starting_line_number,
+            overflow: false,
             section: Section::Body,
             num_of_prev_blank_lines: 0,
             section_delimiters,
+            no_number,
             args,
         }
     }
 
-    fn convert(&mut self, files: Vec<String>) {
-        let mut stdout = stdout();
+    /// Write the `files` to the standard output with their lines numbered, as if they
+    /// were a single input, where `-` is the standard input, printing the errors of the
+    /// files that can't be read.
+    ///
+    /// Returns whether every file was numbered.
+    fn convert(&mut self, files: &[&OsStr]) -> bool {
+        let stdout = stdout();
+        let mut output = BufWriter::new(stdout.lock());
+        let mut success = true;
+
+        for &file in files {
+            let result = if file == "-" {
+                let stdin = stdin();
+                let reader = stdin.lock();
+                self.convert_file(reader, &mut output)
+            } else {
+                match File::open(file) {
+                    Ok(fd) => self.convert_file(BufReader::new(fd), &mut output),
+                    Err(err) => Err(Error::Read(err)),
+                }
+            };
+
+            match result {
+                Ok(()) => {},
+                Err(Error::Read(err)) => {
+                    eprintln!("nl: {}: {}", Path::new(file).display(), err);
+                    success = false;
+                },
+                Err(Error::Write(err)) => {
+                    eprintln!("nl: write error: {}", err);
+                    process::exit(1);
+                },
+                Err(Error::Overflow) => {
+                    output.flush().ok();
+                    eprintln!("nl: line number overflow");
+                    process::exit(1);
+                },
+            }
+        }
 
-        let write_error = |err| {
+        if let Err(err) = output.flush() {
             eprintln!("nl: write error: {}", err);
             process::exit(1);
-        };
+        }
 
-        let read_error = |err| {
-            eprintln!("nl: read error: {}", err);
-            String::new()
-        };
+        success
+    }
 
-        for file_path in files {
-            if file_path == "-" {
-                let stdin = stdin();
-                for line in stdin.lock().lines() {
-                    stdout
-                        .write_fmt(format_args!(
-                            "{}\n",
-                            self.convert_line(line.unwrap_or_else(read_error))
-                        ))
-                        .unwrap_or_else(write_error);
-                    stdout.flush().unwrap_or_else(write_error);
-                }
-            } else {
-                let fd = File::open(file_path).unwrap();
-                let reader = BufReader::new(fd);
-                for line in reader.lines() {
-                    stdout
-                        .write_fmt(format_args!(
-                            "{}\n",
-                            self.convert_line(line.unwrap_or_else(read_error))
-                        ))
-                        .unwrap_or_else(write_error);
-                    stdout.flush().unwrap_or_else(write_error);
-                }
+    /// Write the lines of the `reader` to the `output` with their numbers, where the
+    /// last one ends with a newline even if it is missing.
+    fn convert_file(
+        &mut self, mut reader: impl BufRead, output: &mut impl Write,
+    ) -> Result<(), Error> {
+        let mut line = Vec::new();
+        let mut converted = Vec::new();
+
+        loop {
+            line.clear();
+
+            if reader.read_until(b'\n', &mut line).map_err(Error::Read)? == 0 {
+                return Ok(());
             }
+
+            if line.last() == Some(&b'\n') {
+                line.pop();
+            }
+
+            converted.clear();
+            self.convert_line(&line, &mut converted)?;
+            converted.push(b'\n');
+            output.write_all(&converted).map_err(Error::Write)?;
         }
     }
 
-    fn convert_line(&mut self, line: String) -> String {
-        let is_section_changed = self.check_and_change_section(&line);
-
-        if is_section_changed {
-            return String::new();
+    /// Write the `line` to the `output` with its number, if it is numbered in its
+    /// section, or with the spaces of one if it isn't, where a delimiter of a section is
+    /// replaced by an empty line.
+    ///
+    /// # Errors
+    /// If the line is numbered after the overflow of the line numbers, an error variant
+    /// will be returned.
+    fn convert_line(&mut self, line: &[u8], output: &mut Vec<u8>) -> Result<(), Error> {
+        if self.check_and_change_section(line) {
+            return Ok(());
         }
 
         let numbering = match self.section {
@@ -269,70 +374,75 @@ impl Nl {
             Section::Footer => &self.args.footer_numbering,
         };
 
-        let should_number: bool = match numbering {
-            Style::All => {
-                !line.is_empty() || self.num_of_prev_blank_lines + 1 == self.args.join_blank_lines
+        let should_number = match numbering {
+            // Like GNU, only the blank lines of this style are joined
+            Style::All if self.args.join_blank_lines > 1 => {
+                if line.is_empty() {
+                    self.num_of_prev_blank_lines += 1;
+                }
+
+                let numbered =
+                    !line.is_empty() || self.num_of_prev_blank_lines == self.args.join_blank_lines;
+
+                if numbered {
+                    self.num_of_prev_blank_lines = 0;
+                }
+
+                numbered
             },
-            Style::None => false,
+            Style::All => true,
             Style::Nonempty => !line.is_empty(),
-            Style::Regex(re) => re.is_match(line.as_str()),
-        };
-
-        let mut new_line = String::new();
-        let num_str = self.ind.to_string();
-        let whitespaces = if num_str.len() < self.args.number_width {
-            self.args.number_width - num_str.len()
-        } else {
-            0
+            Style::None => false,
+            Style::Regex(re) => re.is_match(line),
         };
 
         if should_number {
-            if self.args.number_format == Format::Rn {
-                new_line.push_str(&String::from(" ").repeat(whitespaces));
-            } else if self.args.number_format == Format::Rz {
-                new_line.push_str(&String::from("0").repeat(whitespaces));
+            if self.overflow {
+                return Err(Error::Overflow);
             }
 
-            new_line.push_str(&num_str);
+            let number = self.args.number_format.format(self.ind, self.args.number_width);
+            output.extend_from_slice(number.as_bytes());
+            output.extend_from_slice(self.args.number_separator.as_bytes());
 
-            if self.args.number_format == Format::Ln {
-                new_line.push_str(&String::from(" ").repeat(whitespaces));
+            match self.ind.checked_add(self.args.line_increment) {
+                Some(ind) => self.ind = ind,
+                None => self.overflow = true,
             }
-
-            new_line.push_str(&self.args.number_separator);
-
-            self.ind += self.args.line_increment as i64;
-            self.num_of_prev_blank_lines = 0;
-        } else if !line.is_empty() {
-            new_line.push_str(&String::from(" ").repeat(self.args.number_width + 1));
-        } else if line.is_empty() {
-            self.num_of_prev_blank_lines += 1;
+        } else {
+            output.extend_from_slice(&self.no_number);
         }
 
+        output.extend_from_slice(line);
 
-        new_line.push_str(&line);
-
-        new_line
+        Ok(())
     }
 
-    fn check_and_change_section(&mut self, line: &str) -> bool {
-        let mut is_changed = false;
-
-        if line == self.section_delimiters.header {
-            self.section = Section::Header;
-            is_changed = true;
-        } else if line == self.section_delimiters.body {
-            self.section = Section::Body;
-            is_changed = true;
-        } else if line == self.section_delimiters.footer {
-            self.section = Section::Footer;
-            is_changed = true;
-        }
+    /// Change the section if the `line` is a delimiter, restarting the line numbers
+    /// unless they aren't renumbered.
+    ///
+    /// Returns whether the line is a delimiter.
+    fn check_and_change_section(&mut self, line: &[u8]) -> bool {
+        let section = match self.section_delimiters.section(line) {
+            Some(section) => section,
+            None => return false,
+        };
 
-        if is_changed && !self.args.no_renumber {
+        self.section = section;
+
+        if !self.args.no_renumber {
             self.ind = self.args.starting_line_number;
+            self.overflow = false;
         }
 
-        is_changed
+        true
     }
 }
+
+/// The errors of the numbering of a file.
+#[derive(Debug)]
+enum Error {
+    Read(std::io::Error),
+    Write(std::io::Error),
+    Overflow,
+}
@@ -1,7 +1,13 @@
 use super::*;
 
 fn get_default_args() -> NlArgs {
-    NlArgs::from_matches(&ArgMatches::default())
+    NlArgs::from_matches(&ArgMatches::default()).unwrap()
+}
+
+fn convert(nl: &mut Nl, line: &str) -> String {
+    let mut output = Vec::new();
+    nl.convert_line(line.as_bytes(), &mut output).unwrap();
+    String::from_utf8(output).unwrap()
 }
 
 #[test]
@@ -29,10 +35,10 @@ mod body_numbering {
         let args = get_default_args();
         let mut nl = Nl::new(args);
 
-        assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-        assert_eq!(nl.convert_line(String::from("line 2")), "     2\tline 2");
-        assert_eq!(nl.convert_line(String::from("")), "");
-        assert_eq!(nl.convert_line(String::from("line 3")), "     3\tline 3");
+        assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+        assert_eq!(convert(&mut nl, "line 2"), "     2\tline 2");
+        assert_eq!(convert(&mut nl, ""), "       ");
+        assert_eq!(convert(&mut nl, "line 3"), "     3\tline 3");
     }
 
     #[test]
@@ -42,10 +48,10 @@ mod body_numbering {
 
         let mut nl = Nl::new(args);
 
-        assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-        assert_eq!(nl.convert_line(String::from("line 2")), "     2\tline 2");
-        assert_eq!(nl.convert_line(String::from("")), "     3\t");
-        assert_eq!(nl.convert_line(String::from("line 3")), "     4\tline 3");
+        assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+        assert_eq!(convert(&mut nl, "line 2"), "     2\tline 2");
+        assert_eq!(convert(&mut nl, ""), "     3\t");
+        assert_eq!(convert(&mut nl, "line 3"), "     4\tline 3");
     }
 
     #[test]
@@ -55,9 +61,9 @@ mod body_numbering {
 
         let mut nl = Nl::new(args);
 
-        assert_eq!(nl.convert_line(String::from("line 1")), "       line 1");
-        assert_eq!(nl.convert_line(String::from("line 2")), "       line 2");
-        assert_eq!(nl.convert_line(String::from("")), "");
+        assert_eq!(convert(&mut nl, "line 1"), "       line 1");
+        assert_eq!(convert(&mut nl, "line 2"), "       line 2");
+        assert_eq!(convert(&mut nl, ""), "       ");
     }
 
     #[test]
@@ -68,11 +74,11 @@ mod body_numbering {
 
         let mut nl = Nl::new(args);
 
-        assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-        assert_eq!(nl.convert_line(String::from("line 2")), "     2\tline 2");
-        assert_eq!(nl.convert_line(String::from("line 22")), "       line 22");
-        assert_eq!(nl.convert_line(String::from("")), "");
-        assert_eq!(nl.convert_line(String::from("line 5")), "     3\tline 5");
+        assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+        assert_eq!(convert(&mut nl, "line 2"), "     2\tline 2");
+        assert_eq!(convert(&mut nl, "line 22"), "       line 22");
+        assert_eq!(convert(&mut nl, ""), "       ");
+        assert_eq!(convert(&mut nl, "line 5"), "     3\tline 5");
     }
 }
 
@@ -82,15 +88,15 @@ fn starting_line_number() {
     args.starting_line_number = 5;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "     5\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "     6\tline 2");
+    assert_eq!(convert(&mut nl, "line 1"), "     5\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), "     6\tline 2");
 
     let mut args = get_default_args();
     args.starting_line_number = -5;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "    -5\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "    -4\tline 2");
+    assert_eq!(convert(&mut nl, "line 1"), "    -5\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), "    -4\tline 2");
 }
 
 #[test]
@@ -99,8 +105,8 @@ fn line_increment() {
     args.line_increment = 5;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "     6\tline 2");
+    assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), "     6\tline 2");
 }
 
 #[test]
@@ -109,8 +115,8 @@ fn number_separator() {
     args.number_separator = String::from("   ");
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "     1   line 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "     2   line 2");
+    assert_eq!(convert(&mut nl, "line 1"), "     1   line 1");
+    assert_eq!(convert(&mut nl, "line 2"), "     2   line 2");
 }
 
 #[test]
@@ -120,20 +126,20 @@ fn number_width() {
     args.line_increment = 3;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), " 1\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), " 4\tline 2");
-    assert_eq!(nl.convert_line(String::from("line 2")), " 7\tline 2");
-    assert_eq!(nl.convert_line(String::from("line 2")), "10\tline 2");
+    assert_eq!(convert(&mut nl, "line 1"), " 1\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), " 4\tline 2");
+    assert_eq!(convert(&mut nl, "line 2"), " 7\tline 2");
+    assert_eq!(convert(&mut nl, "line 2"), "10\tline 2");
 
     let mut args = get_default_args();
     args.number_width = 1;
     args.line_increment = 3;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "1\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "4\tline 2");
-    assert_eq!(nl.convert_line(String::from("line 2")), "7\tline 2");
-    assert_eq!(nl.convert_line(String::from("line 2")), "10\tline 2");
+    assert_eq!(convert(&mut nl, "line 1"), "1\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), "4\tline 2");
+    assert_eq!(convert(&mut nl, "line 2"), "7\tline 2");
+    assert_eq!(convert(&mut nl, "line 2"), "10\tline 2");
 }
 
 #[test]
@@ -142,48 +148,48 @@ fn join_blank_lines() {
     args.join_blank_lines = 2;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-    nl.convert_line(String::from(""));
-    nl.convert_line(String::from(""));
-    nl.convert_line(String::from(""));
-    nl.convert_line(String::from(""));
-    assert_eq!(nl.convert_line(String::from("line 6")), "     2\tline 6");
+    assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+    convert(&mut nl, "");
+    convert(&mut nl, "");
+    convert(&mut nl, "");
+    convert(&mut nl, "");
+    assert_eq!(convert(&mut nl, "line 6"), "     2\tline 6");
 
     let mut args = get_default_args();
     args.join_blank_lines = 2;
     args.body_numbering = Style::All;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-    nl.convert_line(String::from(""));
-    nl.convert_line(String::from(""));
-    nl.convert_line(String::from(""));
-    nl.convert_line(String::from(""));
-    assert_eq!(nl.convert_line(String::from("line 6")), "     4\tline 6");
+    assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+    convert(&mut nl, "");
+    convert(&mut nl, "");
+    convert(&mut nl, "");
+    convert(&mut nl, "");
+    assert_eq!(convert(&mut nl, "line 6"), "     4\tline 6");
 
     let mut args = get_default_args();
     args.join_blank_lines = 3;
     args.body_numbering = Style::All;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-    nl.convert_line(String::from(""));
-    nl.convert_line(String::from(""));
-    nl.convert_line(String::from(""));
-    nl.convert_line(String::from(""));
-    assert_eq!(nl.convert_line(String::from("line 6")), "     3\tline 6");
+    assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+    convert(&mut nl, "");
+    convert(&mut nl, "");
+    convert(&mut nl, "");
+    convert(&mut nl, "");
+    assert_eq!(convert(&mut nl, "line 6"), "     3\tline 6");
 
     let mut args = get_default_args();
     args.join_blank_lines = 4;
     args.body_numbering = Style::All;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-    nl.convert_line(String::from(""));
-    nl.convert_line(String::from(""));
-    nl.convert_line(String::from(""));
-    assert_eq!(nl.convert_line(String::from("")), "     2\t");
-    assert_eq!(nl.convert_line(String::from("line 6")), "     3\tline 6");
+    assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+    convert(&mut nl, "");
+    convert(&mut nl, "");
+    convert(&mut nl, "");
+    assert_eq!(convert(&mut nl, ""), "     2\t");
+    assert_eq!(convert(&mut nl, "line 6"), "     3\tline 6");
 }
 
 #[test]
@@ -191,12 +197,12 @@ fn section_delimiter() {
     let args = get_default_args();
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "     2\tline 2");
-    assert_eq!(nl.convert_line(String::from("\\:\\:\\:")), "");
-    assert_eq!(nl.convert_line(String::from("line 3")), "       line 3");
-    assert_eq!(nl.convert_line(String::from("\\:\\:")), "");
-    assert_eq!(nl.convert_line(String::from("line 4")), "     1\tline 4");
+    assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), "     2\tline 2");
+    assert_eq!(convert(&mut nl, "\\:\\:\\:"), "");
+    assert_eq!(convert(&mut nl, "line 3"), "       line 3");
+    assert_eq!(convert(&mut nl, "\\:\\:"), "");
+    assert_eq!(convert(&mut nl, "line 4"), "     1\tline 4");
 }
 
 #[test]
@@ -205,31 +211,31 @@ fn footer_numbering() {
     let mut nl = Nl::new(args);
 
     assert_eq!(nl.section, Section::Body);
-    assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "     2\tline 2");
-    assert_eq!(nl.convert_line(String::from("\\:")), "");
-    assert_eq!(nl.convert_line(String::from("line 3")), "       line 3");
+    assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), "     2\tline 2");
+    assert_eq!(convert(&mut nl, "\\:"), "");
+    assert_eq!(convert(&mut nl, "line 3"), "       line 3");
     assert_eq!(nl.section, Section::Footer);
 
     let mut args = get_default_args();
     args.footer_numbering = Style::Nonempty;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "     2\tline 2");
-    assert_eq!(nl.convert_line(String::from("\\:")), "");
-    assert_eq!(nl.convert_line(String::from("line 3")), "     1\tline 3");
-    assert_eq!(nl.convert_line(String::from("")), "");
+    assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), "     2\tline 2");
+    assert_eq!(convert(&mut nl, "\\:"), "");
+    assert_eq!(convert(&mut nl, "line 3"), "     1\tline 3");
+    assert_eq!(convert(&mut nl, ""), "       ");
 
     let mut args = get_default_args();
     args.footer_numbering = Style::All;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "     2\tline 2");
-    assert_eq!(nl.convert_line(String::from("\\:")), "");
-    assert_eq!(nl.convert_line(String::from("line 3")), "     1\tline 3");
-    assert_eq!(nl.convert_line(String::from("line 4")), "     2\tline 4");
+    assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), "     2\tline 2");
+    assert_eq!(convert(&mut nl, "\\:"), "");
+    assert_eq!(convert(&mut nl, "line 3"), "     1\tline 3");
+    assert_eq!(convert(&mut nl, "line 4"), "     2\tline 4");
 }
 
 #[test]
@@ -238,30 +244,30 @@ fn header_numbering() {
     let mut nl = Nl::new(args);
 
     assert_eq!(nl.section, Section::Body);
-    assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "     2\tline 2");
-    assert_eq!(nl.convert_line(String::from("\\:\\:\\:")), "");
-    assert_eq!(nl.convert_line(String::from("line 3")), "       line 3");
+    assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), "     2\tline 2");
+    assert_eq!(convert(&mut nl, "\\:\\:\\:"), "");
+    assert_eq!(convert(&mut nl, "line 3"), "       line 3");
     assert_eq!(nl.section, Section::Header);
 
     let mut args = get_default_args();
     args.header_numbering = Style::Nonempty;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "     2\tline 2");
-    assert_eq!(nl.convert_line(String::from("\\:\\:\\:")), "");
-    assert_eq!(nl.convert_line(String::from("line 3")), "     1\tline 3");
-    assert_eq!(nl.convert_line(String::from("")), "");
+    assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), "     2\tline 2");
+    assert_eq!(convert(&mut nl, "\\:\\:\\:"), "");
+    assert_eq!(convert(&mut nl, "line 3"), "     1\tline 3");
+    assert_eq!(convert(&mut nl, ""), "       ");
 
     let mut args = get_default_args();
     args.header_numbering = Style::All;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "     2\tline 2");
-    assert_eq!(nl.convert_line(String::from("\\:\\:\\:")), "");
-    assert_eq!(nl.convert_line(String::from("line 3")), "     1\tline 3");
+    assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), "     2\tline 2");
+    assert_eq!(convert(&mut nl, "\\:\\:\\:"), "");
+    assert_eq!(convert(&mut nl, "line 3"), "     1\tline 3");
 }
 
 #[test]
@@ -271,10 +277,10 @@ fn number_format() {
     args.line_increment = 3;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), " 1\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), " 4\tline 2");
-    assert_eq!(nl.convert_line(String::from("line 2")), " 7\tline 2");
-    assert_eq!(nl.convert_line(String::from("line 2")), "10\tline 2");
+    assert_eq!(convert(&mut nl, "line 1"), " 1\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), " 4\tline 2");
+    assert_eq!(convert(&mut nl, "line 2"), " 7\tline 2");
+    assert_eq!(convert(&mut nl, "line 2"), "10\tline 2");
 
     let mut args = get_default_args();
     args.number_width = 2;
@@ -282,10 +288,10 @@ fn number_format() {
     args.number_format = Format::Ln;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "1 \tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "4 \tline 2");
-    assert_eq!(nl.convert_line(String::from("line 2")), "7 \tline 2");
-    assert_eq!(nl.convert_line(String::from("line 2")), "10\tline 2");
+    assert_eq!(convert(&mut nl, "line 1"), "1 \tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), "4 \tline 2");
+    assert_eq!(convert(&mut nl, "line 2"), "7 \tline 2");
+    assert_eq!(convert(&mut nl, "line 2"), "10\tline 2");
 
     let mut args = get_default_args();
     args.number_width = 2;
@@ -293,10 +299,10 @@ fn number_format() {
     args.number_format = Format::Rz;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "01\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "04\tline 2");
-    assert_eq!(nl.convert_line(String::from("line 2")), "07\tline 2");
-    assert_eq!(nl.convert_line(String::from("line 2")), "10\tline 2");
+    assert_eq!(convert(&mut nl, "line 1"), "01\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), "04\tline 2");
+    assert_eq!(convert(&mut nl, "line 2"), "07\tline 2");
+    assert_eq!(convert(&mut nl, "line 2"), "10\tline 2");
 }
 
 #[test]
@@ -306,8 +312,73 @@ fn no_renumber() {
     args.no_renumber = true;
     let mut nl = Nl::new(args);
 
-    assert_eq!(nl.convert_line(String::from("line 1")), "     1\tline 1");
-    assert_eq!(nl.convert_line(String::from("line 2")), "     2\tline 2");
-    assert_eq!(nl.convert_line(String::from("\\:\\:\\:")), "");
-    assert_eq!(nl.convert_line(String::from("line 3")), "     3\tline 3");
+    assert_eq!(convert(&mut nl, "line 1"), "     1\tline 1");
+    assert_eq!(convert(&mut nl, "line 2"), "     2\tline 2");
+    assert_eq!(convert(&mut nl, "\\:\\:\\:"), "");
+    assert_eq!(convert(&mut nl, "line 3"), "     3\tline 3");
+}
+
+#[test]
+fn section_delimiter_characters() {
+    let mut args = get_default_args();
+    args.section_delimiter = String::from("x");
+    let mut nl = Nl::new(args);
+
+    assert_eq!(convert(&mut nl, "x"), "     1\tx");
+    assert_eq!(convert(&mut nl, "x:x:x:"), "");
+    assert_eq!(nl.section, Section::Header);
+
+    let mut args = get_default_args();
+    args.section_delimiter = String::from("abc");
+    let mut nl = Nl::new(args);
+
+    assert_eq!(convert(&mut nl, "abcabc"), "");
+    assert_eq!(convert(&mut nl, "abc"), "");
+    assert_eq!(nl.section, Section::Footer);
+
+    let mut args = get_default_args();
+    args.section_delimiter = String::new();
+    let mut nl = Nl::new(args);
+
+    assert_eq!(convert(&mut nl, ""), "       ");
+    assert_eq!(nl.section, Section::Body);
+}
+
+#[test]
+fn line_number_overflow() {
+    let mut args = get_default_args();
+    args.starting_line_number = i64::MAX;
+    let mut nl = Nl::new(args);
+
+    assert_eq!(convert(&mut nl, "line 1"), format!("{}\tline 1", i64::MAX));
+    assert_eq!(convert(&mut nl, ""), "       ");
+    assert!(matches!(nl.convert_line(b"line 2", &mut Vec::new()), Err(Error::Overflow)));
+    assert_eq!(convert(&mut nl, "\\:\\:"), "");
+    assert_eq!(convert(&mut nl, "line 3"), format!("{}\tline 3", i64::MAX));
+}
+
+#[test]
+fn invalid_values() {
+    let message = |style: &str| Style::from_value(Some(style), Style::None, "body").unwrap_err();
+
+    assert_eq!(message("x"), "invalid body numbering style: 'x'");
+    assert_eq!(message("p\\("), "Unmatched ( or \\(");
+    assert_eq!(Format::from_value(Some("q")).unwrap_err(), "invalid line numbering format: 'q'");
+
+    assert_eq!(parse_number("+5", 1, 10, "n"), Ok(5));
+    assert_eq!(parse_number("-5", -10, 10, "n"), Ok(-5));
+    assert_eq!(parse_number("5x", 1, 10, "n"), Err(String::from("n: '5x'")));
+    assert_eq!(parse_number("-", 1, 10, "n"), Err(String::from("n: '-'")));
+    assert_eq!(
+        parse_number("0", 1, 10, "n"),
+        Err(String::from("n: '0': Numerical result out of range"))
+    );
+    assert_eq!(
+        parse_number("2147483648", 1, 10, "n"),
+        Err(String::from("n: '2147483648': Value too large for defined data type"))
+    );
+    assert_eq!(
+        parse_number("99999999999999999999", 1, 10, "n"),
+        Err(String::from("n: '99999999999999999999': Value too large for defined data type"))
+    );
 }
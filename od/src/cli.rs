@@ -0,0 +1,114 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+/// The options that are the same as a type of `--format`, with the type and their help.
+pub(crate) const ALIASES: &[(&str, &str, &str)] = &[
+    ("a", "a", "Same as -t a, write named characters."),
+    ("b", "o1", "Same as -t o1, write octal bytes."),
+    ("c", "c", "Same as -t c, write printable characters or backslash escapes."),
+    ("d", "u2", "Same as -t u2, write unsigned decimal 2-byte units."),
+    ("f", "fF", "Same as -t fF, write floats."),
+    ("i", "dI", "Same as -t dI, write decimal ints."),
+    ("l", "dL", "Same as -t dL, write decimal longs."),
+    ("o", "o2", "Same as -t o2, write octal 2-byte units."),
+    ("s", "d2", "Same as -t d2, write decimal 2-byte units."),
+    ("x", "x2", "Same as -t x2, write hexadecimal 2-byte units."),
+];
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    let app = App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("File(s) to use, or '-' to use from standard input.")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("addressRadix")
+                .help("Write the addresses in RADIX, that is one of [doxn].")
+                .long_help(
+                    "Write the addresses in RADIX, that is one of [doxn].\n\nRADIX is d for \
+                     decimal, o for octal (the default), x for hexadecimal or n for no address.",
+                )
+                .long("address-radix")
+                .short("A")
+                .value_name("RADIX")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("skipBytes")
+                .help("Skip BYTES input bytes first.")
+                .long_help(
+                    "Skip BYTES input bytes first.\n\nBYTES is decimal, octal with a 0 prefix or \
+                     hexadecimal with a 0x or 0X prefix, with an optional unit: b for 512, K, M, \
+                     G, T, P, E, Z or Y for the powers of 1024, that can also be written as KiB, \
+                     MiB and so on, or KB, MB and so on for the powers of 1000.",
+                )
+                .long("skip-bytes")
+                .short("j")
+                .value_name("BYTES")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("readBytes")
+                .help("Write at most BYTES input bytes.")
+                .long_help(
+                    "Write at most BYTES input bytes.\n\nBYTES is like the one of --skip-bytes.",
+                )
+                .long("read-bytes")
+                .short("N")
+                .value_name("BYTES")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Write the bytes in the TYPE, that is octal 2-byte units by default.")
+                .long_help(
+                    "Write the bytes in the TYPE, that is octal 2-byte units by default.\n\nTYPE \
+                     is made up of one or more of these specifications:\n  a        named \
+                     character, ignoring high-order bit\n  c        printable character or \
+                     backslash escape\n  d[SIZE]  signed decimal, SIZE bytes per integer\n  \
+                     f[SIZE]  floating point, SIZE bytes per float\n  o[SIZE]  octal, SIZE bytes \
+                     per integer\n  u[SIZE]  unsigned decimal, SIZE bytes per integer\n  x[SIZE]  \
+                     hexadecimal, SIZE bytes per integer\n\nSIZE is a number. For the integers, \
+                     it may also be C for a char, S for a short, I for an int or L for a long. \
+                     For the floats, it may also be F for a float or D for a double.\n\nAdding a \
+                     z suffix to any type writes the printable characters at the end of each \
+                     line.\n\nWith more than one type, each line of bytes is written once in each \
+                     of them, with their values aligned.",
+                )
+                .long("format")
+                .short("t")
+                .value_name("TYPE")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("endian")
+                .help("Read the values of the types larger than a byte in the ORDER.")
+                .long_help(
+                    "Read the values of the types larger than a byte in the ORDER.\n\nORDER is \
+                     big or little, instead of the one of the system.",
+                )
+                .long("endian")
+                .value_name("ORDER")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("outputDuplicates")
+                .help("Write the duplicate lines, instead of a '*' for them.")
+                .long("output-duplicates")
+                .short("v"),
+        );
+
+    ALIASES.iter().fold(app, |app, &(alias, _, help)| {
+        app.arg(Arg::with_name(alias).help(help).short(alias).multiple(true))
+    })
+}
@@ -0,0 +1,406 @@
+//! The types that the bytes of the input are written in.
+use std::{
+    fmt::{LowerExp, Write},
+    str::FromStr,
+};
+
+/// The names of the ASCII control characters and the space, for the named characters.
+const NAMES: [&str; 33] = [
+    "nul", "soh", "stx", "etx", "eot", "enq", "ack", "bel", "bs", "ht", "nl", "vt", "ff", "cr",
+    "so", "si", "dle", "dc1", "dc2", "dc3", "dc4", "nak", "syn", "etb", "can", "em", "sub", "esc",
+    "fs", "gs", "rs", "us", "sp",
+];
+
+/// The decimal digits of the precision of a float, and of a double.
+const FLOAT_DIGITS: usize = 6;
+const DOUBLE_DIGITS: usize = 15;
+
+/// The byte order of the values of the types that are larger than a byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    /// The byte order of the system.
+    pub(crate) fn native() -> Self {
+        if cfg!(target_endian = "little") {
+            Endian::Little
+        } else {
+            Endian::Big
+        }
+    }
+}
+
+/// How the values of a type are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    /// The named character of the lower 7 bits of a byte.
+    Named,
+    /// The printable character, backslash escape or octal number of a byte.
+    Character,
+    Signed,
+    Unsigned,
+    Octal,
+    Hexadecimal,
+    Float,
+}
+
+/// A type of the output, with the size of its values in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Type {
+    pub(crate) kind: Kind,
+    pub(crate) size: usize,
+    /// Whether the printable characters of the bytes are written at the end of the lines.
+    pub(crate) trailer: bool,
+}
+
+impl Type {
+    /// The width of the widest value of the type.
+    pub(crate) fn width(&self) -> usize {
+        match (self.kind, self.size) {
+            (Kind::Named, _) | (Kind::Character, _) => 3,
+            (Kind::Signed, 1) => 4,
+            (Kind::Signed, 2) => 6,
+            (Kind::Signed, 4) => 11,
+            (Kind::Signed, _) => 20,
+            (Kind::Unsigned, size) => unsigned_width(size, 10),
+            (Kind::Octal, size) => unsigned_width(size, 8),
+            (Kind::Hexadecimal, size) => size * 2,
+            (Kind::Float, 4) => 15,
+            (Kind::Float, _) => 24,
+        }
+    }
+
+    /// Write the value of the `bytes` in the byte order of the `endian`, that are the
+    /// size of the type, right aligned to the `width`.
+    pub(crate) fn write(&self, bytes: &[u8], endian: Endian, width: usize, output: &mut String) {
+        let value = unsigned(bytes, endian);
+
+        // Writing to a `String` can't fail
+        let _ = match self.kind {
+            Kind::Named => match bytes[0] & 0x7f {
+                byte if usize::from(byte) < NAMES.len() => {
+                    write!(output, "{:>1$}", NAMES[usize::from(byte)], width)
+                },
+                0x7f => write!(output, "{:>1$}", "del", width),
+                byte => write!(output, "{:>1$}", char::from(byte), width),
+            },
+            Kind::Character => match bytes[0] {
+                b'\0' => write!(output, "{:>1$}", "\\0", width),
+                0x07 => write!(output, "{:>1$}", "\\a", width),
+                0x08 => write!(output, "{:>1$}", "\\b", width),
+                0x0c => write!(output, "{:>1$}", "\\f", width),
+                b'\n' => write!(output, "{:>1$}", "\\n", width),
+                b'\r' => write!(output, "{:>1$}", "\\r", width),
+                b'\t' => write!(output, "{:>1$}", "\\t", width),
+                0x0b => write!(output, "{:>1$}", "\\v", width),
+                byte @ b' '..=b'~' => write!(output, "{:>1$}", char::from(byte), width),
+                byte => write!(output, "{:>1$}", format!("{:03o}", byte), width),
+            },
+            Kind::Signed => {
+                // Extend the sign of the smaller sizes
+                let shift = 64 - 8 * self.size as u32;
+                write!(output, "{:>1$}", ((value << shift) as i64) >> shift, width)
+            },
+            Kind::Unsigned => write!(output, "{:>1$}", value, width),
+            Kind::Octal => {
+                let padding = width.saturating_sub(self.width());
+                write!(output, "{:2$}{:03$o}", "", value, padding, self.width())
+            },
+            Kind::Hexadecimal => {
+                let padding = width.saturating_sub(self.width());
+                write!(output, "{:2$}{:03$x}", "", value, padding, self.width())
+            },
+            Kind::Float if self.size == 4 => {
+                let float = f32::from_bits(value as u32);
+                let text = float_text(
+                    float,
+                    float.is_nan(),
+                    float.is_sign_negative(),
+                    float.is_normal(),
+                    FLOAT_DIGITS,
+                );
+                write!(output, "{:>1$}", text, width)
+            },
+            Kind::Float => {
+                let float = f64::from_bits(value);
+                let text = float_text(
+                    float,
+                    float.is_nan(),
+                    float.is_sign_negative(),
+                    float.is_normal(),
+                    DOUBLE_DIGITS,
+                );
+                write!(output, "{:>1$}", text, width)
+            },
+        };
+    }
+}
+
+/// Parse the `types` of a `--format`, that are one or more types optionally followed by
+/// a size and a `z`.
+///
+/// # Errors
+/// If a type or its size is not valid, an error message will be returned.
+pub(crate) fn parse_types(types: &str) -> Result<Vec<Type>, String> {
+    let bytes = types.as_bytes();
+    let mut parsed = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let kind = match bytes[i] {
+            b'a' => Kind::Named,
+            b'c' => Kind::Character,
+            b'd' => Kind::Signed,
+            b'u' => Kind::Unsigned,
+            b'o' => Kind::Octal,
+            b'x' => Kind::Hexadecimal,
+            b'f' => Kind::Float,
+            _ => return Err(invalid_character(types, i)),
+        };
+        i += 1;
+
+        let size = match kind {
+            Kind::Named | Kind::Character => 1,
+            Kind::Float => parse_size(types, &mut i, 8, &[(b'F', 4), (b'D', 8), (b'L', 16)])?,
+            _ => parse_size(types, &mut i, 4, &[(b'C', 1), (b'S', 2), (b'I', 4), (b'L', 8)])?,
+        };
+
+        let valid = match kind {
+            Kind::Float => size == 4 || size == 8,
+            _ => size.is_power_of_two() && size <= 8,
+        };
+
+        if !valid {
+            let what = if kind == Kind::Float { "floating point" } else { "integral" };
+            return Err(format!(
+                "invalid type string '{}';\nthis system doesn't provide a {}-byte {} type",
+                types, size, what
+            ));
+        }
+
+        let trailer = bytes.get(i) == Some(&b'z');
+        if trailer {
+            i += 1;
+        }
+
+        parsed.push(Type { kind, size, trailer });
+    }
+
+    Ok(parsed)
+}
+
+/// Parse the size of a type of the `types` at the position `i`, moving it after the
+/// size, that is the `default` if there is none, or a number or one of the `letters`.
+fn parse_size(
+    types: &str, i: &mut usize, default: usize, letters: &[(u8, usize)],
+) -> Result<usize, String> {
+    let bytes = types.as_bytes();
+
+    if let Some(&(_, size)) = letters.iter().find(|(letter, _)| bytes.get(*i) == Some(letter)) {
+        *i += 1;
+        return Ok(size);
+    }
+
+    let start = *i;
+
+    while matches!(bytes.get(*i), Some(byte) if byte.is_ascii_digit()) {
+        *i += 1;
+    }
+
+    match &types[start..*i] {
+        "" => Ok(default),
+        digits => digits.parse().map_err(|_| format!("invalid type string '{}'", types)),
+    }
+}
+
+/// The error message of the invalid character at `i` of the `types`.
+fn invalid_character(types: &str, i: usize) -> String {
+    let character = types[i..].chars().next().unwrap_or_default();
+    format!("invalid character '{}' in type string '{}'", character, types)
+}
+
+/// The text of the `float` like the one of `%g` of C, with the fewest digits that are
+/// read back as it, that are at least the `precision` if it is `normal`, like GNU.
+fn float_text<F>(float: F, nan: bool, negative: bool, normal: bool, precision: usize) -> String
+where
+    F: LowerExp + FromStr + PartialEq,
+{
+    if nan {
+        return if negative { "-nan" } else { "nan" }.to_string();
+    }
+
+    // The shortest digits that are read back as the float, that may not be the ones of
+    // its rounding
+    let shortest = format!("{:e}", float);
+    let digits = match shortest.find('e') {
+        Some(e) => shortest[..e].bytes().filter(u8::is_ascii_digit).count(),
+        // The infinities
+        None => return shortest,
+    };
+
+    let mut precision = if normal { digits.max(precision) } else { digits };
+
+    loop {
+        let exponential = format!("{:.*e}", precision - 1, float);
+
+        if matches!(exponential.parse::<F>(), Ok(parsed) if parsed == float) {
+            return general_text(&exponential, precision);
+        }
+
+        precision += 1;
+    }
+}
+
+/// The text of the `exponential` text of a float, with the `precision` of its digits,
+/// like the one of `%g` of C, that is in the exponential notation only if its exponent
+/// is smaller than -4, or not smaller than the precision.
+fn general_text(exponential: &str, precision: usize) -> String {
+    let e = exponential.find('e').unwrap_or(exponential.len());
+    let exponent = exponential[e + 1..].parse::<i32>().unwrap_or(0);
+    let sign = if exponential.starts_with('-') { "-" } else { "" };
+
+    let digits: String = exponential[..e].chars().filter(char::is_ascii_digit).collect();
+    let digits = match digits.trim_end_matches('0') {
+        "" => "0",
+        digits => digits,
+    };
+
+    if exponent < -4 || exponent >= precision as i32 {
+        let fraction = if digits.len() > 1 { format!(".{}", &digits[1..]) } else { String::new() };
+        let exponent_sign = if exponent < 0 { '-' } else { '+' };
+
+        format!("{}{}{}e{}{:02}", sign, &digits[..1], fraction, exponent_sign, exponent.abs())
+    } else if exponent < 0 {
+        format!("{}0.{}{}", sign, "0".repeat((-exponent - 1) as usize), digits)
+    } else {
+        let point = exponent as usize + 1;
+
+        if digits.len() <= point {
+            format!("{}{}{}", sign, digits, "0".repeat(point - digits.len()))
+        } else {
+            format!("{}{}.{}", sign, &digits[..point], &digits[point..])
+        }
+    }
+}
+
+/// The number of digits of the largest unsigned integer of the `size` in the `radix`.
+fn unsigned_width(size: usize, radix: u64) -> usize {
+    let mut max = u64::MAX >> (64 - 8 * size);
+    let mut digits = 1;
+
+    while max >= radix {
+        max /= radix;
+        digits += 1;
+    }
+
+    digits
+}
+
+/// The unsigned integer of the `bytes` in the byte order of the `endian`.
+fn unsigned(bytes: &[u8], endian: Endian) -> u64 {
+    let mut buffer = [0; 8];
+
+    match endian {
+        Endian::Little => {
+            buffer[..bytes.len()].copy_from_slice(bytes);
+            u64::from_le_bytes(buffer)
+        },
+        Endian::Big => {
+            buffer[8 - bytes.len()..].copy_from_slice(bytes);
+            u64::from_be_bytes(buffer)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(types: &str, bytes: &[u8]) -> String {
+        let format = parse_types(types).unwrap()[0];
+        let mut output = String::new();
+        format.write(bytes, Endian::native(), format.width() + 1, &mut output);
+        output
+    }
+
+    #[test]
+    fn types() {
+        let parsed = parse_types("ax1zdSfu8cz").unwrap();
+        let kinds: Vec<_> =
+            parsed.iter().map(|kind| (kind.kind, kind.size, kind.trailer)).collect();
+
+        assert_eq!(kinds, [
+            (Kind::Named, 1, false),
+            (Kind::Hexadecimal, 1, true),
+            (Kind::Signed, 2, false),
+            (Kind::Float, 8, false),
+            (Kind::Unsigned, 8, false),
+            (Kind::Character, 1, true),
+        ]);
+        assert_eq!(parse_types("d").unwrap()[0].size, 4);
+        assert_eq!(parse_types("oL").unwrap()[0].size, 8);
+        assert_eq!(parse_types("fF").unwrap()[0].size, 4);
+        assert_eq!(parse_types("").unwrap(), []);
+    }
+
+    #[test]
+    fn types_errors() {
+        let message = |types: &str| parse_types(types).unwrap_err();
+
+        assert_eq!(message("q"), "invalid character 'q' in type string 'q'");
+        assert_eq!(message("aC"), "invalid character 'C' in type string 'aC'");
+        assert_eq!(
+            message("x3"),
+            "invalid type string 'x3';\nthis system doesn't provide a 3-byte integral type"
+        );
+        assert_eq!(
+            message("fL"),
+            "invalid type string 'fL';\nthis system doesn't provide a 16-byte floating point type"
+        );
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(text("a", b"\x8a"), "  nl");
+        assert_eq!(text("a", b" "), "  sp");
+        assert_eq!(text("a", b"\xff"), " del");
+        assert_eq!(text("c", b"\t"), "  \\t");
+        assert_eq!(text("c", b"\xff"), " 377");
+        assert_eq!(text("d1", b"\x80"), " -128");
+        assert_eq!(text("dL", &[0xff; 8]), "                   -1");
+        assert_eq!(text("u2", b"\xff\xff"), " 65535");
+        assert_eq!(text("o2", b"\x01\x00"), " 000001");
+        assert_eq!(text("x4", b"\xff\x00\x00\x00"), " 000000ff");
+
+        let format = parse_types("x2").unwrap()[0];
+        let mut output = String::new();
+        format.write(b"\x12\x34", Endian::Big, 5, &mut output);
+        format.write(b"\x12\x34", Endian::Little, 5, &mut output);
+        assert_eq!(output, " 1234 3412");
+    }
+
+    // The exact float, rounded to the even digit
+    #[allow(clippy::excessive_precision)]
+    #[test]
+    fn floats() {
+        let float = |float: f32| text("fF", &float.to_ne_bytes()).trim_start().to_string();
+        let double = |double: f64| text("fD", &double.to_ne_bytes()).trim_start().to_string();
+
+        assert_eq!(float(1.0), "1");
+        assert_eq!(float(100_000.0), "100000");
+        assert_eq!(float(1e6), "1e+06");
+        assert_eq!(float(-1_279_853.25), "-1279853.2");
+        assert_eq!(float(1e-45), "1e-45");
+        assert_eq!(float(-0.0), "-0");
+        assert_eq!(float(f32::NEG_INFINITY), "-inf");
+        assert_eq!(double(0.0001), "0.0001");
+        assert_eq!(double(1e-5), "1e-05");
+        assert_eq!(double(1e15), "1e+15");
+        assert_eq!(double(1.0 / 3.0), "0.3333333333333333");
+        assert_eq!(double(f64::MAX), "1.7976931348623157e+308");
+        assert_eq!(double(-f64::NAN), "-nan");
+    }
+}
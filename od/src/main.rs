@@ -0,0 +1,539 @@
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::{self, stdin, stdout, BufReader, BufWriter, Read, Seek, SeekFrom, Stdin, Write},
+    path::Path,
+    process,
+};
+
+use clap::ArgMatches;
+use coreutils_core::size::{parse_size, SizeError};
+
+mod cli;
+mod format;
+
+use format::{Endian, Kind, Type};
+
+/// The suffixes of the units of the numbers of bytes.
+const SUFFIXES: &str = "bEGKkMmPTYZ";
+
+/// The number of bytes written in each line.
+const BYTES_PER_LINE: usize = 16;
+
+/// The type written when none is given.
+const DEFAULT_TYPE: Type = Type { kind: Kind::Octal, size: 2, trailer: false };
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let options = Options::from_matches(&matches).unwrap_or_else(|err| {
+        eprintln!("od: {}", err);
+        process::exit(1);
+    });
+
+    let files: Vec<&OsStr> = match matches.values_of_os("FILE") {
+        Some(files) => files.collect(),
+        None => vec![OsStr::new("-")],
+    };
+
+    let mut input = Input::new(files);
+
+    // Like GNU, nothing is written if no file can be opened
+    if !input.open_next() {
+        process::exit(1);
+    }
+
+    if let Err(err) = od(&mut input, &options) {
+        eprintln!("od: {}", err);
+        process::exit(1);
+    }
+
+    if !input.success {
+        process::exit(1);
+    }
+}
+
+/// The radix of the addresses of the lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Radix {
+    Decimal,
+    Octal,
+    Hexadecimal,
+    None,
+}
+
+impl Radix {
+    /// The text of the `address` in the radix.
+    fn format(self, address: u64) -> String {
+        match self {
+            Radix::Decimal => format!("{:07}", address),
+            Radix::Octal => format!("{:07o}", address),
+            Radix::Hexadecimal => format!("{:06x}", address),
+            Radix::None => String::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Options {
+    radix: Radix,
+    skip: u64,
+    limit: Option<u64>,
+    /// The types of the bytes, where each line is written in each of them.
+    formats: Vec<Type>,
+    endian: Endian,
+    /// Whether the lines that are the same as the one before them are written, instead of
+    /// a `*` for all of them.
+    duplicates: bool,
+}
+
+impl Options {
+    /// The options of the `matches`.
+    ///
+    /// # Errors
+    /// If an option has a value that is not valid, an error message will be returned.
+    fn from_matches(matches: &ArgMatches) -> Result<Self, String> {
+        // Like GNU, only the first character of the radix is used
+        let radix = match matches.value_of("addressRadix").map(|radix| radix.chars().next()) {
+            None | Some(Some('o')) => Radix::Octal,
+            Some(Some('d')) => Radix::Decimal,
+            Some(Some('x')) => Radix::Hexadecimal,
+            Some(Some('n')) => Radix::None,
+            Some(_) => {
+                let radix = matches.value_of("addressRadix").unwrap_or_default();
+                return Err(format!(
+                    "invalid output address radix '{}'; it must be one character from [doxn]",
+                    radix
+                ));
+            },
+        };
+
+        let skip = match matches.value_of("skipBytes") {
+            Some(skip) => parse_bytes(skip, "-j")?,
+            None => 0,
+        };
+
+        let limit = match matches.value_of("readBytes") {
+            Some(limit) => Some(parse_bytes(limit, "-N")?),
+            None => None,
+        };
+
+        // The types and the options of the types, in the order they were given
+        let mut types: Vec<(usize, &str)> = Vec::new();
+
+        if let (Some(indices), Some(values)) =
+            (matches.indices_of("format"), matches.values_of("format"))
+        {
+            types.extend(indices.zip(values));
+        }
+
+        for &(alias, format, _) in cli::ALIASES {
+            if let Some(indices) = matches.indices_of(alias) {
+                types.extend(indices.map(|index| (index, format)));
+            }
+        }
+
+        types.sort_by_key(|&(index, _)| index);
+
+        let mut formats = Vec::new();
+
+        for (_, spec) in types {
+            formats.extend(format::parse_types(spec)?);
+        }
+
+        if formats.is_empty() {
+            formats.push(DEFAULT_TYPE);
+        }
+
+        // Like GNU, the orders may be abbreviated
+        let endian = match matches.value_of("endian") {
+            None => Endian::native(),
+            Some(endian) if !endian.is_empty() && "little".starts_with(endian) => Endian::Little,
+            Some(endian) if !endian.is_empty() && "big".starts_with(endian) => Endian::Big,
+            Some(endian) => {
+                return Err(format!(
+                    "{} argument '{}' for '--endian'\nValid arguments are:\n  - 'little'\n  - \
+                     'big'",
+                    if endian.is_empty() { "ambiguous" } else { "invalid" },
+                    endian
+                ));
+            },
+        };
+
+        Ok(Options {
+            radix,
+            skip,
+            limit,
+            formats,
+            endian,
+            duplicates: matches.is_present("outputDuplicates"),
+        })
+    }
+}
+
+/// Parse the number of `bytes` of the `option`, that is decimal, octal with a `0` prefix
+/// or hexadecimal with a `0x` prefix, with an optional suffix of its unit.
+///
+/// # Errors
+/// If it is not a number, or it doesn't fit in 64 bits, an error message will be
+/// returned.
+fn parse_bytes(bytes: &str, option: &str) -> Result<u64, String> {
+    let (radix, digits) =
+        if let Some(digits) = bytes.strip_prefix("0x").or_else(|| bytes.strip_prefix("0X")) {
+            (16, digits)
+        } else if bytes.len() > 1 && bytes.starts_with('0') {
+            (8, &bytes[1..])
+        } else {
+            (10, bytes)
+        };
+
+    let result = if radix == 10 {
+        parse_size(bytes, SUFFIXES)
+    } else {
+        let end = digits.find(|c: char| !c.is_digit(radix)).unwrap_or(digits.len());
+        let (number, suffix) = digits.split_at(end);
+
+        let number = match u64::from_str_radix(number, radix) {
+            Ok(number) => Ok(number),
+            // The leading zero of an octal number is a digit
+            Err(_) if number.is_empty() && radix == 8 => Ok(0),
+            Err(_) if number.is_empty() => Err(SizeError::Invalid),
+            Err(_) => Err(SizeError::TooLarge),
+        };
+
+        match number {
+            Ok(number) if suffix.is_empty() => Ok(number),
+            Ok(_) if suffix.starts_with(|c: char| c.is_ascii_digit()) => Err(SizeError::Invalid),
+            Ok(number) => parse_size(suffix, SUFFIXES)
+                .and_then(|unit| number.checked_mul(unit).ok_or(SizeError::TooLarge)),
+            Err(err) => Err(err),
+        }
+    };
+
+    result.map_err(|err| match err {
+        // A number or a unit followed by something else
+        SizeError::Invalid
+            if bytes.starts_with(|c: char| c.is_ascii_digit() || SUFFIXES.contains(c)) =>
+        {
+            format!("invalid suffix in {} argument '{}'", option, bytes)
+        },
+        SizeError::Invalid => format!("invalid {} argument '{}'", option, bytes),
+        SizeError::TooLarge => format!("{} argument '{}' too large", option, bytes),
+    })
+}
+
+/// Write the bytes of the `input` in the lines of the `options`.
+///
+/// # Errors
+/// If the bytes to skip are more than the ones of the input, or the output can't be
+/// written, an error message will be returned.
+fn od(input: &mut Input, options: &Options) -> Result<(), String> {
+    input.skip(options.skip)?;
+
+    let stdout = stdout();
+    let mut output = BufWriter::new(stdout.lock());
+    let write_error = |err: io::Error| format!("write error: {}", err);
+
+    let mut address = options.skip;
+    let mut remaining = options.limit.unwrap_or(u64::MAX);
+    let mut block = [0; BYTES_PER_LINE];
+    let mut previous = [0; BYTES_PER_LINE];
+    let mut first = true;
+    let mut suppressing = false;
+    let mut line = String::new();
+
+    // The width of a line of each type, where the values of the types with fewer
+    // characters are spread to align them with the others
+    let width = options
+        .formats
+        .iter()
+        .map(|format| (format.width() + 1) * (BYTES_PER_LINE / format.size))
+        .max()
+        .unwrap_or(0);
+
+    while remaining > 0 {
+        let wanted = remaining.min(BYTES_PER_LINE as u64) as usize;
+        let read = input.read(&mut block[..wanted]);
+
+        if read == 0 {
+            break;
+        }
+
+        for byte in &mut block[read..] {
+            *byte = 0;
+        }
+
+        if !options.duplicates && !first && read == BYTES_PER_LINE && block == previous {
+            if !suppressing {
+                output.write_all(b"*\n").map_err(write_error)?;
+                suppressing = true;
+            }
+        } else {
+            suppressing = false;
+            line.clear();
+            write_lines(options, address, &block, read, width, &mut line);
+            output.write_all(line.as_bytes()).map_err(write_error)?;
+        }
+
+        first = false;
+        previous = block;
+        address += read as u64;
+        remaining -= read as u64;
+    }
+
+    if options.radix != Radix::None {
+        writeln!(output, "{}", options.radix.format(address)).map_err(write_error)?;
+    }
+
+    output.flush().map_err(write_error)
+}
+
+/// Write the lines of the first `read` bytes of the `block` at the `address` to the
+/// `line`, one for each type of the `options`, that have the `width`.
+fn write_lines(
+    options: &Options, address: u64, block: &[u8], read: usize, width: usize, line: &mut String,
+) {
+    let address = options.radix.format(address);
+
+    for (i, format) in options.formats.iter().enumerate() {
+        if i == 0 {
+            line.push_str(&address);
+        } else {
+            line.push_str(&" ".repeat(address.len()));
+        }
+
+        write_values(format, options.endian, block, read, width, line);
+        line.push('\n');
+    }
+}
+
+/// Write the values of the first `read` bytes of the `block` in the `format` and the
+/// byte order of the `endian` to the `line`, spread in the `width`, followed by their
+/// printable characters if the format has them, where the missing bytes of the last
+/// value are zeros.
+fn write_values(
+    format: &Type, endian: Endian, block: &[u8], read: usize, width: usize, line: &mut String,
+) {
+    let fields = BYTES_PER_LINE / format.size;
+    let blank = (BYTES_PER_LINE - read) / format.size;
+    let padding = width - format.width() * fields;
+
+    // Like GNU, the padding is spread from the last value to the first
+    let mut remaining = padding;
+
+    for (i, value) in (blank + 1..=fields).rev().zip(block.chunks(format.size)) {
+        let next = padding * (i - 1) / fields;
+        format.write(value, endian, remaining - next + format.width(), line);
+        remaining = next;
+    }
+
+    if format.trailer {
+        line.push_str(&" ".repeat(blank * format.width() + padding * blank / fields));
+        line.push_str("  >");
+        line.extend(block[..read].iter().map(|&byte| match byte {
+            b' '..=b'~' => char::from(byte),
+            _ => '.',
+        }));
+        line.push('<');
+    }
+}
+
+/// The files of the input, that are read as if they were a single one.
+struct Input<'a> {
+    files: std::vec::IntoIter<&'a OsStr>,
+    current: Option<(Source, &'a OsStr)>,
+    /// Whether every file was read.
+    success: bool,
+}
+
+/// A file of the input.
+enum Source {
+    Stdin(BufReader<Stdin>),
+    File(BufReader<File>),
+}
+
+impl<'a> Input<'a> {
+    fn new(files: Vec<&'a OsStr>) -> Self {
+        Input { files: files.into_iter(), current: None, success: true }
+    }
+
+    /// Open the next file that can be opened, printing the errors of the ones that
+    /// can't.
+    ///
+    /// Returns whether there was a file to open.
+    fn open_next(&mut self) -> bool {
+        self.current = None;
+
+        while let Some(file) = self.files.next() {
+            if file == "-" {
+                self.current = Some((Source::Stdin(BufReader::new(stdin())), file));
+                return true;
+            }
+
+            match File::open(file) {
+                Ok(opened) => {
+                    self.current = Some((Source::File(BufReader::new(opened)), file));
+                    return true;
+                },
+                Err(err) => self.error(file, &err),
+            }
+        }
+
+        false
+    }
+
+    /// Print the `err` of the `file`.
+    fn error(&mut self, file: &OsStr, err: &io::Error) {
+        eprintln!("od: {}: {}", Path::new(file).display(), err);
+        self.success = false;
+    }
+
+    /// Read bytes to fill the `buffer`, from the next files when a file ends.
+    ///
+    /// Returns the number of bytes read, that is less than the length of the buffer only
+    /// when the input ends.
+    fn read(&mut self, buffer: &mut [u8]) -> usize {
+        let mut filled = 0;
+
+        while filled < buffer.len() {
+            let result = match &mut self.current {
+                Some((Source::Stdin(reader), _)) => reader.read(&mut buffer[filled..]),
+                Some((Source::File(reader), _)) => reader.read(&mut buffer[filled..]),
+                None => return filled,
+            };
+
+            match result {
+                Ok(0) => {
+                    self.open_next();
+                },
+                Ok(read) => filled += read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {},
+                Err(err) => {
+                    if let Some((_, file)) = self.current {
+                        self.error(file, &err);
+                    }
+                    self.open_next();
+                },
+            }
+        }
+
+        filled
+    }
+
+    /// Skip the first `bytes` of the input, seeking the regular files instead of reading
+    /// them.
+    ///
+    /// # Errors
+    /// If the input has fewer bytes, an error message will be returned.
+    fn skip(&mut self, mut bytes: u64) -> Result<(), String> {
+        while bytes > 0 {
+            let skipped = match &mut self.current {
+                Some((Source::File(reader), _)) => match seek_file(reader, bytes) {
+                    Some(skipped) => Ok(skipped),
+                    None => io::copy(&mut reader.by_ref().take(bytes), &mut io::sink()),
+                },
+                Some((Source::Stdin(reader), _)) => {
+                    io::copy(&mut reader.by_ref().take(bytes), &mut io::sink())
+                },
+                None => return Err("cannot skip past end of combined input".to_string()),
+            };
+
+            match skipped {
+                Ok(skipped) => bytes -= skipped,
+                Err(err) => {
+                    if let Some((_, file)) = self.current {
+                        self.error(file, &err);
+                    }
+                },
+            }
+
+            if bytes > 0 {
+                self.open_next();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Seek up to `bytes` forward in the `file` if it is a regular file, but not after its
+/// end.
+///
+/// Returns the number of bytes skipped, or `None` if it is not a regular file.
+fn seek_file(file: &mut BufReader<File>, bytes: u64) -> Option<u64> {
+    let metadata = file.get_ref().metadata().ok()?;
+
+    if !metadata.is_file() {
+        return None;
+    }
+
+    // `stream_position` is newer than the MSRV
+    #[allow(clippy::seek_from_current)]
+    let position = file.seek(SeekFrom::Current(0)).ok()?;
+    let skipped = bytes.min(metadata.len().saturating_sub(position));
+    file.seek(SeekFrom::Current(skipped as i64)).ok()?;
+
+    Some(skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes() {
+        assert_eq!(parse_bytes("10", "-j"), Ok(10));
+        assert_eq!(parse_bytes("010", "-j"), Ok(8));
+        assert_eq!(parse_bytes("0", "-j"), Ok(0));
+        assert_eq!(parse_bytes("0x1b", "-j"), Ok(27));
+        assert_eq!(parse_bytes("0X10k", "-j"), Ok(16 * 1024));
+        assert_eq!(parse_bytes("2b", "-j"), Ok(1024));
+        assert_eq!(parse_bytes("01KB", "-j"), Ok(1000));
+        assert_eq!(parse_bytes("0k", "-j"), Ok(0));
+        assert_eq!(parse_bytes("1MiB", "-N"), Ok(1024 * 1024));
+    }
+
+    #[test]
+    fn bytes_errors() {
+        assert_eq!(parse_bytes("x", "-j"), Err("invalid -j argument 'x'".to_string()));
+        assert_eq!(parse_bytes("-1", "-j"), Err("invalid -j argument '-1'".to_string()));
+        assert_eq!(parse_bytes("Kq", "-j"), Err("invalid suffix in -j argument 'Kq'".to_string()));
+        assert_eq!(parse_bytes("0x", "-N"), Err("invalid suffix in -N argument '0x'".to_string()));
+        assert_eq!(parse_bytes("09", "-N"), Err("invalid suffix in -N argument '09'".to_string()));
+        assert_eq!(
+            parse_bytes("0x1q", "-N"),
+            Err("invalid suffix in -N argument '0x1q'".to_string())
+        );
+        assert_eq!(parse_bytes("1Y", "-j"), Err("-j argument '1Y' too large".to_string()));
+        assert_eq!(
+            parse_bytes("99999999999999999999", "-N"),
+            Err("-N argument '99999999999999999999' too large".to_string())
+        );
+    }
+
+    #[test]
+    fn lines() {
+        let options = Options {
+            radix: Radix::Hexadecimal,
+            skip: 0,
+            limit: None,
+            formats: format::parse_types("x1z")
+                .unwrap()
+                .into_iter()
+                .chain(format::parse_types("d4").unwrap())
+                .collect(),
+            endian: Endian::Little,
+            duplicates: false,
+        };
+        let mut block = [0; BYTES_PER_LINE];
+        block[..5].copy_from_slice(b"ab\0\0c");
+        let mut line = String::new();
+        write_lines(&options, 16, &block, 5, 48, &mut line);
+
+        assert_eq!(
+            line,
+            "000010 61 62 00 00 63                                   >ab..c<\n             \
+             25185          99\n"
+        );
+    }
+}
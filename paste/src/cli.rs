@@ -0,0 +1,45 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("File(s) to use, or '-' to use from standard input.")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("delimiters")
+                .help("Separate the lines with the characters of LIST in turn, instead of tabs.")
+                .long_help(
+                    "Separate the lines with the characters of LIST in turn, instead of \
+                     tabs.\n\nThe characters start over on each line of the output, or each file \
+                     with --serial. The escapes \\n, \\t, \\\\ and \\0 stand for a newline, a \
+                     tab, a backslash and no character at all.",
+                )
+                .long("delimiters")
+                .short("d")
+                .value_name("LIST")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("serial")
+                .help("Write the lines of each file on a single line, one file at a time.")
+                .long("serial")
+                .short("s"),
+        )
+        .arg(
+            Arg::with_name("zeroTerminated")
+                .help("End the lines with NUL characters, instead of newlines.")
+                .long("zero-terminated")
+                .short("z"),
+        )
+}
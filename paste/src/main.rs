@@ -0,0 +1,353 @@
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::{self, stdin, stdout, BufRead, BufReader, BufWriter, Write},
+    process,
+};
+
+use clap::ArgMatches;
+
+mod cli;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let paste = Paste::from_matches(&matches).unwrap_or_else(|err| {
+        eprintln!("paste: {}", err);
+        process::exit(1);
+    });
+
+    let files: Vec<&OsStr> = match matches.values_of_os("FILE") {
+        Some(files) => files.collect(),
+        None => vec![OsStr::new("-")],
+    };
+    let names: Vec<_> = files.iter().map(|file| file.to_string_lossy()).collect();
+
+    let stdin = stdin();
+    let stdout = stdout();
+    let mut output = BufWriter::new(stdout.lock());
+
+    let result = if paste.serial {
+        let mut success = true;
+        for (file, name) in files.iter().zip(&names) {
+            let input: io::Result<Box<dyn BufRead>> = if *file == "-" {
+                Ok(Box::new(stdin.lock()))
+            } else {
+                File::open(file).map(|opened| Box::new(BufReader::new(opened)) as Box<dyn BufRead>)
+            };
+
+            match input {
+                Ok(mut input) => match paste.serial(&mut input, name, &mut output) {
+                    Ok(read) => success &= read,
+                    Err(err) => write_error(&err),
+                },
+                Err(err) => {
+                    // Like GNU, the errors follow the lines written before them
+                    output.flush().unwrap_or_else(|err| write_error(&err));
+                    eprintln!("paste: {}: {}", name, err);
+                    success = false;
+                },
+            }
+        }
+        Ok(success)
+    } else {
+        // Like GNU, all the files are opened first, and all the '-' share standard input
+        let mut inputs: Vec<Box<dyn BufRead>> = Vec::new();
+        let mut stdin_input = None;
+        let mut sources = Vec::new();
+        for (file, name) in files.iter().zip(&names) {
+            if *file == "-" {
+                let index = *stdin_input.get_or_insert_with(|| {
+                    inputs.push(Box::new(stdin.lock()));
+                    inputs.len() - 1
+                });
+                sources.push(index);
+                continue;
+            }
+
+            match File::open(file) {
+                Ok(opened) => {
+                    inputs.push(Box::new(BufReader::new(opened)));
+                    sources.push(inputs.len() - 1);
+                },
+                Err(err) => {
+                    eprintln!("paste: {}: {}", name, err);
+                    process::exit(1);
+                },
+            }
+        }
+
+        paste.parallel(&mut inputs, &sources, &names, &mut output)
+    };
+
+    let success = result.unwrap_or_else(|err| write_error(&err));
+    if let Err(err) = output.flush() {
+        write_error(&err);
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+fn write_error(err: &io::Error) -> ! {
+    eprintln!("paste: write error: {}", err);
+    process::exit(1);
+}
+
+/// The joiner of the lines of the files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Paste {
+    /// The delimiters used in turn, where an empty one writes nothing.
+    delimiters: Vec<Vec<u8>>,
+    /// Whether the lines of each file are joined, instead of the lines of all the files.
+    serial: bool,
+    line_terminator: u8,
+}
+
+impl Paste {
+    fn from_matches(matches: &ArgMatches) -> Result<Self, String> {
+        let delimiters = match matches.value_of("delimiters") {
+            Some(list) => parse_delimiters(list)?,
+            None => vec![b"\t".to_vec()],
+        };
+
+        Ok(Paste {
+            delimiters,
+            serial: matches.is_present("serial"),
+            line_terminator: if matches.is_present("zeroTerminated") { b'\0' } else { b'\n' },
+        })
+    }
+
+    /// Write the lines of the `inputs` side by side to the `output`, until they are all
+    /// read.
+    ///
+    /// Each of the `sources` is the index of its input, where the ones sharing an input
+    /// take its lines in turn. Like GNU, the delimiters of the sources that are already
+    /// read are held until some line follows them, and the reading errors are reported
+    /// with the `names` of the sources, which are then closed.
+    ///
+    /// Returns whether all the sources were read without errors.
+    fn parallel<R: BufRead, W: Write>(
+        &self, inputs: &mut [R], sources: &[usize], names: &[impl AsRef<str>], output: &mut W,
+    ) -> io::Result<bool> {
+        let mut open: Vec<_> = sources.iter().copied().map(Some).collect();
+        let mut remaining = open.len();
+        let mut success = true;
+        let mut line = Vec::new();
+        let mut held = Vec::new();
+
+        while remaining > 0 {
+            let mut written = false;
+            let mut delimiters = self.delimiters.iter().cycle();
+            held.clear();
+
+            for i in 0..open.len() {
+                if remaining == 0 {
+                    break;
+                }
+                let last = i + 1 == open.len();
+
+                line.clear();
+                if let Some(index) = open[i] {
+                    match inputs[index].read_until(self.line_terminator, &mut line) {
+                        Ok(_) => {},
+                        Err(err) => {
+                            output.flush()?;
+                            eprintln!("paste: {}: {}", names[i].as_ref(), err);
+                            success = false;
+                            line.clear();
+                        },
+                    }
+
+                    if line.is_empty() {
+                        open[i] = None;
+                        remaining -= 1;
+                    }
+                }
+
+                if line.is_empty() {
+                    if !last {
+                        held.extend_from_slice(delimiters.next().unwrap());
+                    } else if written {
+                        output.write_all(&held)?;
+                        output.write_all(&[self.line_terminator])?;
+                    }
+                    continue;
+                }
+
+                written = true;
+                output.write_all(&held)?;
+                held.clear();
+
+                let content = line.strip_suffix(&[self.line_terminator]).unwrap_or(&line);
+                output.write_all(content)?;
+                if last {
+                    output.write_all(&[self.line_terminator])?;
+                } else {
+                    output.write_all(delimiters.next().unwrap())?;
+                }
+            }
+        }
+
+        Ok(success)
+    }
+
+    /// Write the lines of the `input` on a single line to the `output`, with its reading
+    /// errors reported with its `name`.
+    ///
+    /// Returns whether the input was read without errors.
+    fn serial<R: BufRead, W: Write>(
+        &self, input: &mut R, name: &str, output: &mut W,
+    ) -> io::Result<bool> {
+        let mut delimiters = self.delimiters.iter().cycle();
+        let mut line = Vec::new();
+        // Whether a line was ended, and is followed by a delimiter unless it is the last
+        let mut ended = false;
+
+        let result = loop {
+            line.clear();
+            match input.read_until(self.line_terminator, &mut line) {
+                Ok(0) => break Ok(()),
+                Ok(_) => {},
+                Err(err) => break Err(err),
+            }
+
+            if ended {
+                output.write_all(delimiters.next().unwrap())?;
+            }
+
+            let content = line.strip_suffix(&[self.line_terminator]).unwrap_or(&line);
+            output.write_all(content)?;
+            ended = content.len() < line.len();
+        };
+
+        output.write_all(&[self.line_terminator])?;
+
+        // Like GNU, the error follows the line of the input
+        if let Err(err) = result {
+            output.flush()?;
+            eprintln!("paste: {}: {}", name, err);
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+/// Parse the `list` of the delimiters, with its escapes.
+///
+/// Like GNU, a backslash followed by any other character stands for that character, and
+/// an empty list is a single empty delimiter.
+fn parse_delimiters(list: &str) -> Result<Vec<Vec<u8>>, String> {
+    let mut delimiters = Vec::new();
+    let mut chars = list.chars();
+
+    while let Some(c) = chars.next() {
+        let delimiter = if c == '\\' {
+            match chars.next() {
+                Some('0') => "",
+                Some('b') => "\x08",
+                Some('f') => "\x0c",
+                Some('n') => "\n",
+                Some('r') => "\r",
+                Some('t') => "\t",
+                Some('v') => "\x0b",
+                Some(escaped) => {
+                    delimiters.push(escaped.to_string().into_bytes());
+                    continue;
+                },
+                None => {
+                    return Err(format!(
+                        "delimiter list ends with an unescaped backslash: {}",
+                        list
+                    ))
+                },
+            }
+        } else {
+            delimiters.push(c.to_string().into_bytes());
+            continue;
+        };
+
+        delimiters.push(delimiter.as_bytes().to_vec());
+    }
+
+    if delimiters.is_empty() {
+        delimiters.push(Vec::new());
+    }
+
+    Ok(delimiters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paste(delimiters: &str, serial: bool) -> Paste {
+        Paste { delimiters: parse_delimiters(delimiters).unwrap(), serial, line_terminator: b'\n' }
+    }
+
+    fn parallel(paste: &Paste, inputs: &[&str], sources: &[usize]) -> String {
+        let mut inputs: Vec<_> = inputs.iter().map(|input| input.as_bytes()).collect();
+        let names = vec!["-"; sources.len()];
+        let mut output = Vec::new();
+        assert!(paste.parallel(&mut inputs, sources, &names, &mut output).unwrap());
+
+        String::from_utf8(output).unwrap()
+    }
+
+    fn serial(paste: &Paste, input: &str) -> String {
+        let mut output = Vec::new();
+        assert!(paste.serial(&mut input.as_bytes(), "-", &mut output).unwrap());
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn lines_side_by_side() {
+        let tab = paste("\\t", false);
+        assert_eq!(parallel(&tab, &["1\n2\n3\n", "a\nb"], &[0, 1]), "1\ta\n2\tb\n3\t\n");
+        assert_eq!(parallel(&tab, &["a\n", "1\n2\n"], &[0, 1]), "a\t1\n\t2\n");
+        assert_eq!(parallel(&tab, &["", "1\n"], &[0, 1, 0]), "\t1\t\n");
+        assert_eq!(parallel(&tab, &["", ""], &[0, 1]), "");
+
+        let list = paste("-é\\0", false);
+        assert_eq!(
+            parallel(&list, &["1\n", "2\n", "3\n", "4\n", "5\n"], &[0, 1, 2, 3, 4]),
+            "1-2é34-5\n"
+        );
+        assert_eq!(parallel(&list, &["", "", "", "x\n"], &[0, 1, 2, 3]), "-éx\n");
+    }
+
+    #[test]
+    fn shared_input() {
+        let tab = paste("\\t", false);
+        assert_eq!(parallel(&tab, &["1\n2\n3\n4\n5\n"], &[0, 0]), "1\t2\n3\t4\n5\t\n");
+        assert_eq!(parallel(&tab, &["1\n2\n3\n", "a\nb\n"], &[0, 1, 0]), "1\ta\t2\n3\tb\t\n");
+    }
+
+    #[test]
+    fn serial_lines() {
+        assert_eq!(serial(&paste("\\t", true), "1\n2\n3\n"), "1\t2\t3\n");
+        assert_eq!(serial(&paste(",;", true), "1\n2\n3\n4"), "1,2;3,4\n");
+        assert_eq!(serial(&paste("", true), "1\n\n2\n"), "12\n");
+        assert_eq!(serial(&paste("\\t", true), ""), "\n");
+        assert_eq!(serial(&paste("\\t", true), "\n\n"), "\t\n");
+
+        let zero = Paste { line_terminator: b'\0', ..paste("\\n", true) };
+        assert_eq!(serial(&zero, "a\0b\nc\0"), "a\nb\nc\0");
+    }
+
+    #[test]
+    fn delimiters() {
+        assert_eq!(
+            parse_delimiters("\\n\\t\\\\\\0"),
+            Ok(vec![b"\n".to_vec(), b"\t".to_vec(), b"\\".to_vec(), vec![]])
+        );
+        assert_eq!(parse_delimiters("a\\qé"), Ok(vec![b"a".to_vec(), b"q".to_vec(), "é".into()]));
+        assert_eq!(parse_delimiters(""), Ok(vec![vec![]]));
+        assert_eq!(
+            parse_delimiters("x\\"),
+            Err("delimiter list ends with an unescaped backslash: x\\".to_string())
+        );
+    }
+}
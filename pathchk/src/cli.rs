@@ -0,0 +1,46 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("NAME")
+                .help("Names of files to check.")
+                .long_help(
+                    "Names of files to check.\n\nBy default, the names and their components must \
+                     not be longer than the limits of the file systems where they are, and the \
+                     existing directories in them must be searchable.",
+                )
+                .required(true)
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("posix")
+                .help("Check for the portability to all the POSIX systems.")
+                .long_help(
+                    "Check for the portability to all the POSIX systems.\n\nThe names must not be \
+                     empty, longer than 255 bytes or have components longer than 14 bytes, and \
+                     must only have the characters 'A' to 'Z', 'a' to 'z', '0' to '9', '.', '_', \
+                     '-' and '/'.",
+                )
+                .short("p"),
+        )
+        .arg(
+            Arg::with_name("extra")
+                .help("Check for empty names and components that start with '-'.")
+                .short("P"),
+        )
+        .arg(
+            Arg::with_name("portability")
+                .help("Check for the portability to all the POSIX systems, like -p and -P.")
+                .long("portability"),
+        )
+}
@@ -0,0 +1,186 @@
+use std::{ffi::OsStr, fs, io, os::unix::ffi::OsStrExt, path::Path, process};
+
+use coreutils_core::os::limits;
+
+mod cli;
+
+/// The most bytes of the names of files in all the POSIX systems, with the terminating
+/// zero byte.
+const POSIX_PATH_MAX: usize = 256;
+
+/// The most bytes of the components of the names of files in all the POSIX systems.
+const POSIX_NAME_MAX: usize = 14;
+
+/// The characters of the names of files in all the POSIX systems.
+const PORTABLE_CHARS: &[u8] = b"/ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789._-";
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let posix = matches.is_present("posix") || matches.is_present("portability");
+    let extra = matches.is_present("extra") || matches.is_present("portability");
+
+    let mut success = true;
+
+    // NAME is required, so it's ok to unwrap
+    for name in matches.values_of_os("NAME").unwrap() {
+        if let Err(err) = check(name.as_bytes(), posix, extra) {
+            eprintln!("pathchk: {}", err);
+            success = false;
+        }
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// Check that the `name` of a file is valid in the file systems where it is, or in all
+/// the POSIX systems with `posix`, and that it's not empty and has no components that
+/// start with `-` with `extra`.
+///
+/// # Errors
+/// The error is the message of the first check failed.
+fn check(name: &[u8], posix: bool, extra: bool) -> Result<(), String> {
+    let shown = String::from_utf8_lossy(name);
+
+    if extra && components(name).any(|(_, component)| component.starts_with(b"-")) {
+        return Err(format!("leading '-' in a component of file name '{}'", shown));
+    }
+
+    if (posix || extra) && name.is_empty() {
+        return Err("empty file name".to_string());
+    }
+
+    let mut exists = false;
+
+    if posix {
+        if let Some(index) = name.iter().position(|byte| !PORTABLE_CHARS.contains(byte)) {
+            let nonportable = String::from_utf8_lossy(&name[index..]).chars().next();
+            return Err(format!(
+                "nonportable character '{}' in file name '{}'",
+                nonportable.unwrap_or_default(),
+                shown
+            ));
+        }
+    } else {
+        // The directories that can't be searched are found looking for the file
+        match fs::symlink_metadata(Path::new(OsStr::from_bytes(name))) {
+            Ok(_) => exists = true,
+            Err(err) if err.kind() == io::ErrorKind::NotFound && !name.is_empty() => {},
+            Err(err) => return Err(format!("{}: {}", shown, err)),
+        }
+    }
+
+    // The existing files have valid names, and the ones too short for the limits of any
+    // system too
+    if posix || (!exists && name.len() >= POSIX_PATH_MAX) {
+        let path_max = if posix {
+            Some(POSIX_PATH_MAX)
+        } else {
+            let dir = if name.starts_with(b"/") { "/" } else { "." };
+            limits::path_max(dir).map_err(|err| {
+                format!("{}: unable to determine maximum file name length: {}", dir, err)
+            })?
+        };
+
+        if let Some(path_max) = path_max.filter(|&path_max| name.len() >= path_max) {
+            return Err(format!(
+                "limit {} exceeded by length {} of file name '{}'",
+                path_max - 1,
+                name.len(),
+                shown
+            ));
+        }
+    }
+
+    if !posix
+        && (exists || components(name).all(|(_, component)| component.len() <= POSIX_NAME_MAX))
+    {
+        return Ok(());
+    }
+
+    // The components of missing directories have the limit of the last one that exists,
+    // and the components of no directory the one of any system otherwise
+    let mut name_max = POSIX_NAME_MAX;
+    let mut known_name_max = if posix { Some(POSIX_NAME_MAX) } else { None };
+
+    for (start, component) in components(name) {
+        match known_name_max {
+            Some(known_name_max) => name_max = known_name_max,
+            None => {
+                let dir =
+                    if start == 0 { OsStr::new(".") } else { OsStr::from_bytes(&name[..start]) };
+
+                match limits::name_max(dir) {
+                    Ok(limit) => name_max = limit.unwrap_or(usize::MAX),
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                        known_name_max = Some(name_max);
+                    },
+                    Err(err) => return Err(format!("{}: {}", Path::new(dir).display(), err)),
+                }
+            },
+        }
+
+        if component.len() > name_max {
+            return Err(format!(
+                "limit {} exceeded by length {} of file name component '{}'",
+                name_max,
+                component.len(),
+                String::from_utf8_lossy(component)
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// The components of the `name` of a file, with where they start in it.
+fn components(name: &[u8]) -> impl Iterator<Item = (usize, &[u8])> {
+    let mut start = 0;
+
+    name.split(|&byte| byte == b'/')
+        .map(move |component| {
+            let component_start = start;
+            start += component.len() + 1;
+            (component_start, component)
+        })
+        .filter(|(_, component)| !component.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn posix() {
+        assert!(check(b"a/b.c_d-e/", true, false).is_ok());
+        assert!(check(b"", true, false).is_err());
+        assert!(check(b"a b", true, false).is_err());
+        assert!(check(b"abcdefghijklmnop", true, false).is_err());
+        assert!(check("a/".repeat(128).as_bytes(), true, false).is_err());
+    }
+
+    #[test]
+    fn extra() {
+        assert!(check(b"a/-b", false, false).is_ok());
+        assert!(check(b"a/-b", false, true).is_err());
+        assert!(check(b"", false, true).is_err());
+    }
+
+    #[test]
+    fn file_systems() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+
+        assert!(check(format!("{}/src/main.rs", dir).as_bytes(), false, false).is_ok());
+        assert!(check(format!("{}/nowhere/abcdefghijklmnop", dir).as_bytes(), false, false).is_ok());
+        assert!(check(format!("{}/src/main.rs/file", dir).as_bytes(), false, false).is_err());
+        assert!(check(format!("{}/{}", dir, "a".repeat(300)).as_bytes(), false, false).is_err());
+    }
+
+    #[test]
+    fn component_starts() {
+        let starts: Vec<_> = components(b"/a//bc/d/").map(|(start, _)| start).collect();
+        assert_eq!(starts, [1, 4, 7]);
+    }
+}
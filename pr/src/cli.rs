@@ -0,0 +1,194 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("File(s) to use, or '-' to use from standard input.")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("columns")
+                .help("Write COLUMN columns, filled down the pages.")
+                .long_help(
+                    "Write COLUMN columns, filled down the pages.\n\nThe columns are balanced on \
+                     the last page, and their lines are truncated to fit in the width of the page.",
+                )
+                .long("columns")
+                .value_name("COLUMN")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("across")
+                .help("Fill the columns across the pages, instead of down.")
+                .long("across")
+                .short("a")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("doubleSpace")
+                .help("Write an empty line after each line.")
+                .long("double-space")
+                .short("d")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("formFeed")
+                .help("Separate the pages with form feeds, instead of empty lines.")
+                .long("form-feed")
+                .short("F")
+                .multiple(true),
+        )
+        .arg(Arg::with_name("f").help("Like -F.").short("f").multiple(true).hidden(true))
+        .arg(
+            Arg::with_name("header")
+                .help("Use HEADER in the headers, instead of the name of the file.")
+                .long("header")
+                .short("h")
+                .value_name("HEADER")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("joinLines")
+                .help("Write the whole lines of the columns, without aligning them.")
+                .long("join-lines")
+                .short("J")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("length")
+                .help("Write pages of PAGE_LENGTH lines, instead of 66.")
+                .long_help(
+                    "Write pages of PAGE_LENGTH lines, instead of 66.\n\nThe 5 lines of the \
+                     header and the 5 of the trailer are part of the page, and are left out when \
+                     they don't leave any line for the text.",
+                )
+                .long("length")
+                .short("l")
+                .value_name("PAGE_LENGTH")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("merge")
+                .help("Write the files side by side, one in each column.")
+                .long("merge")
+                .short("m")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("numberLines")
+                .help("Number the lines, with DIGITS digits followed by SEP.")
+                .long_help(
+                    "Number the lines, with DIGITS digits followed by SEP.\n\nThe numbers have 5 \
+                     digits followed by a tab by default. Only the first column is numbered when \
+                     merging files.",
+                )
+                .long("number-lines")
+                .short("n")
+                .value_name("SEP[DIGITS]")
+                .min_values(0)
+                .multiple(true)
+                .require_equals(true)
+                .empty_values(true),
+        )
+        .arg(
+            Arg::with_name("firstLineNumber")
+                .help("Start the numbers of the lines at NUMBER, instead of 1.")
+                .long("first-line-number")
+                .short("N")
+                .value_name("NUMBER")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("indent")
+                .help("Indent the lines with MARGIN spaces.")
+                .long("indent")
+                .short("o")
+                .value_name("MARGIN")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("separator")
+                .help("Separate the columns with CHAR, instead of a tab, without aligning them.")
+                .long_help(
+                    "Separate the columns with CHAR, instead of a tab, without aligning \
+                     them.\n\nThe lines are truncated instead, like with --sep-string, when a \
+                     width is given with -w.",
+                )
+                .long("separator")
+                .short("s")
+                .value_name("CHAR")
+                .min_values(0)
+                .multiple(true)
+                .require_equals(true)
+                .empty_values(true),
+        )
+        .arg(
+            Arg::with_name("sepString")
+                .help("Separate the columns with STRING, instead of aligning them with spaces.")
+                .long("sep-string")
+                .short("S")
+                .value_name("STRING")
+                .min_values(0)
+                .multiple(true)
+                .require_equals(true)
+                .empty_values(true),
+        )
+        .arg(
+            Arg::with_name("omitHeader")
+                .help("Omit the headers and the trailers of the pages.")
+                .long_help(
+                    "Omit the headers and the trailers of the pages.\n\nThe form feeds of the \
+                     files are kept.",
+                )
+                .long("omit-header")
+                .short("t")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("omitPagination")
+                .help("Omit the headers, the trailers and the form feeds of the pages.")
+                .long("omit-pagination")
+                .short("T")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("width")
+                .help("Write pages of PAGE_WIDTH columns, instead of 72, when there are columns.")
+                .long("width")
+                .short("w")
+                .value_name("PAGE_WIDTH")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("pageWidth")
+                .help("Write pages of PAGE_WIDTH columns, instead of 72, truncating the lines.")
+                .long("page-width")
+                .short("W")
+                .value_name("PAGE_WIDTH")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+}
@@ -0,0 +1,1693 @@
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    fs::File,
+    io::{self, stdin, stdout, BufRead, BufReader, BufWriter, ErrorKind, Write},
+    mem, process, str,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::ArgMatches;
+use coreutils_core::datetime::{calendar::Fields, Zone};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+mod cli;
+
+/// The number of lines of the pages when none is given.
+const LINES_PER_PAGE: i64 = 66;
+
+/// The number of lines of the headers, and of the trailers, of the pages.
+const LINES_PER_HEADER: isize = 5;
+
+/// The width of the pages when none is given.
+const CHARS_PER_LINE: i64 = 72;
+
+/// The number of digits of the line numbers when none is given.
+const CHARS_PER_NUMBER: usize = 5;
+
+/// The distance between the tab stops, of the input and of the output.
+const TAB_WIDTH: isize = 8;
+
+/// The long options with a value, that may be the next argument.
+const LONG_OPTIONS_WITH_VALUES: [&str; 7] = [
+    "--columns",
+    "--header",
+    "--length",
+    "--first-line-number",
+    "--indent",
+    "--width",
+    "--page-width",
+];
+
+/// The form feed, that ends a page.
+const FORM_FEED: u8 = b'\x0c';
+
+fn main() {
+    let matches = cli::create_app().get_matches_from(normalize_args(env::args_os()));
+
+    let files: Vec<&OsStr> = match matches.values_of_os("FILE") {
+        Some(files) => files.collect(),
+        None => Vec::new(),
+    };
+
+    let options = Options::from_matches(&matches, files.len()).unwrap_or_else(|err| {
+        eprintln!("pr: {}", err);
+        process::exit(1);
+    });
+
+    let stdout = stdout();
+    let mut pr = Pr::new(&options, BufWriter::new(stdout.lock()));
+    let mut success = true;
+
+    let mut open = |file: &OsStr| match Input::open(file) {
+        Ok(input) => Some(input),
+        Err(err) => {
+            eprintln!("pr: {}: {}", file.to_string_lossy(), err);
+            success = false;
+            None
+        },
+    };
+
+    let result = if files.is_empty() {
+        pr.print(vec![Input::stdin()])
+    } else if options.parallel {
+        let inputs = files.iter().filter_map(|file| open(file)).collect();
+        pr.print(inputs)
+    } else {
+        files.iter().filter_map(|file| open(file)).try_for_each(|input| pr.print(vec![input]))
+    };
+
+    match result.and_then(|()| pr.output.flush().map_err(Error::Write)) {
+        Ok(()) => {},
+        Err(Error::Read(name, err)) => {
+            let _ = pr.output.flush();
+            eprintln!("pr: {}: {}", name, err);
+            process::exit(1);
+        },
+        Err(Error::Write(err)) => {
+            eprintln!("pr: write error: {}", err);
+            process::exit(1);
+        },
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+#[derive(Debug)]
+enum Error {
+    /// An error reading the file of the name.
+    Read(String, io::Error),
+    Write(io::Error),
+}
+
+/// The arguments with the obsolete options `-COLUMN` and the optional values of the
+/// short options in forms that clap parses.
+///
+/// Like GNU, the digits of the options are the number of columns, like `-3` for
+/// `--columns=3`, and the optional values of `-n`, `-s` and `-S` are the rest of their
+/// argument, like `-n:3`.
+fn normalize_args(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let mut args = args.into_iter();
+    let mut normalized: Vec<OsString> = args.next().into_iter().collect();
+    let mut digits = String::new();
+    // Whether the last option is a digit, so a digit that follows it adds to the number
+    let mut in_digits = false;
+
+    while let Some(arg) = args.next() {
+        let text = match arg.to_str() {
+            Some(text) if text.starts_with('-') && text.len() > 1 && !text.starts_with("--") => {
+                text.to_string()
+            },
+            _ => {
+                in_digits = false;
+
+                if arg == "--" {
+                    normalized.push(arg);
+                    break;
+                }
+
+                if arg == "--columns"
+                    || matches!(arg.to_str(), Some(s) if s.starts_with("--columns="))
+                {
+                    digits.clear();
+                }
+
+                if arg == "--separator" || arg == "--sep-string" {
+                    normalized.push(format!("{}=", arg.to_string_lossy()).into());
+                    continue;
+                }
+
+                let takes_value = LONG_OPTIONS_WITH_VALUES.iter().any(|&option| arg == option);
+                normalized.push(arg);
+                if takes_value {
+                    normalized.extend(args.next());
+                }
+                continue;
+            },
+        };
+
+        for (i, option) in text.char_indices().skip(1) {
+            let rest = &text[i + option.len_utf8()..];
+
+            if option.is_ascii_digit() {
+                if !in_digits {
+                    digits.clear();
+                }
+                digits.push(option);
+                in_digits = true;
+                continue;
+            }
+
+            in_digits = false;
+
+            match option {
+                'h' | 'l' | 'N' | 'o' | 'w' | 'W' => {
+                    normalized.push(format!("-{}", option).into());
+                    if rest.is_empty() {
+                        normalized.extend(args.next());
+                    } else {
+                        normalized.push(rest.into());
+                    }
+                    break;
+                },
+                'n' | 's' | 'S' => {
+                    let long = match option {
+                        'n' => "--number-lines",
+                        's' => "--separator",
+                        _ => "--sep-string",
+                    };
+
+                    // The separators always have a value, so clap knows their positions
+                    if rest.is_empty() && option == 'n' {
+                        normalized.push(long.into());
+                    } else {
+                        normalized.push(format!("{}={}", long, rest).into());
+                    }
+                    break;
+                },
+                _ => normalized.push(format!("-{}", option).into()),
+            }
+        }
+    }
+
+    if !digits.is_empty() {
+        normalized.push(format!("--columns={}", digits).into());
+    }
+
+    // The operands after `--`
+    normalized.extend(args);
+
+    normalized
+}
+
+/// The options of the pages, with the ones implied by the others.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Options {
+    /// The number of columns of the file, or of the files when they are merged.
+    columns: usize,
+    /// Whether the files are merged, each one in its own column.
+    parallel: bool,
+    /// Whether the columns are filled down the pages, by storing the lines of a page.
+    storing: bool,
+    double_space: bool,
+    /// Whether the pages end with a form feed, instead of empty lines.
+    form_feed: bool,
+    /// The text of the headers that replaces the name of the file.
+    header: Option<String>,
+    /// The number of lines of text in a page.
+    lines_per_body: isize,
+    /// Whether the pages have a header and a trailer.
+    extremities: bool,
+    /// Whether the form feeds of the files are written, when there are no headers.
+    keep_form_feeds: bool,
+    numbered: bool,
+    number_separator: u8,
+    chars_per_number: usize,
+    /// The columns taken by a line number and its separator.
+    number_width: isize,
+    first_line_number: i64,
+    margin: isize,
+    /// The separator of the columns, where its spaces align the columns.
+    separator: Vec<u8>,
+    use_separator: bool,
+    /// Whether the lines are truncated to the width of the columns.
+    truncate: bool,
+    /// Whether the tabs of the files are expanded to spaces.
+    untabify: bool,
+    /// Whether the spaces written are replaced by tabs where they can.
+    tabify: bool,
+    chars_per_line: isize,
+    chars_per_column: isize,
+}
+
+impl Options {
+    /// The options of the `matches`, for the number of `files` given.
+    fn from_matches(matches: &ArgMatches, files: usize) -> Result<Self, String> {
+        // The last of the options that cancel each other wins
+        let last = |name: &str| matches.indices_of(name).and_then(Iterator::last).unwrap_or(0);
+
+        let explicit_columns = matches.is_present("columns");
+        let mut columns = last_number(matches, "columns", 1, "invalid number of columns")?
+            .map_or(1, |columns| columns as usize);
+
+        let lines_per_page =
+            last_number(matches, "length", 1, "'-l PAGE_LENGTH' invalid number of lines")?
+                .unwrap_or(LINES_PER_PAGE) as isize;
+        let first_line_number = last_number(
+            matches,
+            "firstLineNumber",
+            i32::MIN.into(),
+            "'-N NUMBER' invalid starting line number",
+        )?
+        .unwrap_or(1);
+        let margin = last_number(matches, "indent", 0, "'-o MARGIN' invalid line offset")?
+            .unwrap_or(0) as isize;
+
+        let width =
+            last_number(matches, "width", 1, "'-w PAGE_WIDTH' invalid number of characters")?;
+        let page_width =
+            last_number(matches, "pageWidth", 1, "'-W PAGE_WIDTH' invalid number of characters")?;
+        let chars_per_line = page_width.or(width).unwrap_or(CHARS_PER_LINE) as isize;
+        let mut truncate = page_width.is_some();
+
+        let (number_separator, chars_per_number) = match last_value(matches, "numberLines") {
+            Some(numbering) => parse_numbering(&numbering)?,
+            None => (b'\t', CHARS_PER_NUMBER),
+        };
+
+        let parallel = matches.is_present("merge");
+        let across = matches.is_present("across");
+
+        if parallel && explicit_columns {
+            return Err("cannot specify number of columns when printing in parallel".to_string());
+        }
+
+        if parallel && across {
+            return Err("cannot specify both printing across and printing in parallel".to_string());
+        }
+
+        let (mut separator, mut use_separator) = match last_value(matches, "sepString") {
+            Some(separator) => (separator.as_bytes().to_vec(), true),
+            None if matches.is_present("sepString") => (Vec::new(), true),
+            None => match last_value(matches, "separator") {
+                Some(separator) => (separator.as_bytes().to_vec(), false),
+                None => (Vec::new(), false),
+            },
+        };
+
+        // Like GNU, the old options -s and -w don't align the columns without -W or -S,
+        // when there are columns
+        let old_width = width.is_some() && last("width") > last("pageWidth");
+        let old_separator =
+            matches.is_present("separator") && last("separator") > last("sepString");
+        let mut join = matches.is_present("joinLines");
+
+        if old_width {
+            if parallel || explicit_columns {
+                truncate = true;
+                use_separator |= old_separator;
+            } else {
+                join = true;
+            }
+        } else if !use_separator && old_separator && (parallel || explicit_columns) {
+            if truncate {
+                use_separator = true;
+            } else {
+                join = true;
+                use_separator = !separator.is_empty();
+            }
+        }
+
+        let (mut extremities, mut keep_form_feeds) =
+            if matches.is_present("omitHeader") && last("omitHeader") > last("omitPagination") {
+                (false, true)
+            } else {
+                (!matches.is_present("omitPagination"), false)
+            };
+
+        let mut lines_per_body = lines_per_page - 2 * LINES_PER_HEADER;
+        if lines_per_body <= 0 {
+            extremities = false;
+            keep_form_feeds = true;
+        }
+
+        if !extremities {
+            lines_per_body = lines_per_page;
+        }
+
+        let double_space = matches.is_present("doubleSpace");
+        if double_space {
+            lines_per_body /= 2;
+        }
+
+        // Standard input can't be merged with itself
+        let parallel = parallel && files > 0;
+        if parallel {
+            columns = files;
+        }
+
+        let mut storing = !across && !parallel;
+        let mut untabify = false;
+        let mut tabify = false;
+
+        if columns > 1 {
+            if !use_separator {
+                separator = if join { b"\t".to_vec() } else { b" ".to_vec() };
+                use_separator = true;
+            } else if !join && separator == b"\t" {
+                // A tab can't align the columns
+                separator = b" ".to_vec();
+            }
+
+            truncate = true;
+            untabify = separator != b"\t";
+            tabify = true;
+        } else {
+            storing = false;
+        }
+
+        if join {
+            truncate = false;
+        }
+
+        let numbered = matches.is_present("numberLines");
+        let number_width = match number_separator {
+            _ if !numbered => 0,
+            b'\t' => chars_per_number as isize + TAB_WIDTH - chars_per_number as isize % TAB_WIDTH,
+            _ => chars_per_number as isize + 1,
+        };
+
+        // The numbers are only part of the width of the columns of a single file
+        let chars_used_by_number = if parallel { number_width } else { 0 };
+        let separators = (columns as isize - 1).saturating_mul(separator.len() as isize);
+        let useful_chars = (chars_per_line - chars_used_by_number).saturating_sub(separators);
+        let chars_per_column = useful_chars.max(0) / columns as isize;
+
+        if chars_per_column < 1 {
+            return Err("page width too narrow".to_string());
+        }
+
+        Ok(Options {
+            columns,
+            parallel,
+            storing,
+            double_space,
+            form_feed: matches.is_present("formFeed") || matches.is_present("f"),
+            header: matches.value_of_lossy("header").map(|header| header.into_owned()),
+            lines_per_body,
+            extremities,
+            keep_form_feeds,
+            numbered,
+            number_separator,
+            chars_per_number,
+            number_width,
+            first_line_number,
+            margin,
+            separator,
+            use_separator,
+            truncate,
+            untabify,
+            tabify,
+            chars_per_line,
+            chars_per_column,
+        })
+    }
+}
+
+/// The last of the values of the option `name`.
+fn last_value(matches: &ArgMatches, name: &str) -> Option<String> {
+    matches.values_of_lossy(name).and_then(|values| values.into_iter().last())
+}
+
+/// The last of the numbers of the option `name`, that are at least `min`, described by
+/// `message` when they are invalid.
+fn last_number(
+    matches: &ArgMatches, name: &str, min: i64, message: &str,
+) -> Result<Option<i64>, String> {
+    let mut number = None;
+
+    for value in matches.values_of_lossy(name).into_iter().flatten() {
+        number = Some(parse_number(&value, min, message)?);
+    }
+
+    Ok(number)
+}
+
+/// Parse the `number`, between `min` and the maximum of a C `int`, described by
+/// `message` when it is invalid.
+fn parse_number(number: &str, min: i64, message: &str) -> Result<i64, String> {
+    let trimmed = number.trim_start();
+    let digits = trimmed.strip_prefix(|c| c == '+' || c == '-').unwrap_or(trimmed);
+
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("{}: '{}'", message, number));
+    }
+
+    let reason = match trimmed.parse::<i64>() {
+        Ok(parsed) if parsed >= min && parsed <= i32::MAX.into() => return Ok(parsed),
+        // Like GNU, the numbers far from the minimum don't fit in a `int`
+        Ok(parsed) if parsed > (i32::MAX / 2).into() || parsed < (i32::MIN / 2).into() => {
+            "Value too large for defined data type"
+        },
+        Ok(_) => "Numerical result out of range",
+        Err(_) => "Value too large for defined data type",
+    };
+
+    Err(format!("{}: '{}': {}", message, number, reason))
+}
+
+/// Parse the `numbering` of the lines, a separator that is not a digit and a number of
+/// digits, each one optional.
+fn parse_numbering(numbering: &str) -> Result<(u8, usize), String> {
+    let (separator, digits) = match numbering.as_bytes().first() {
+        Some(first) if !first.is_ascii_digit() => (*first, &numbering.as_bytes()[1..]),
+        _ => (b'\t', numbering.as_bytes()),
+    };
+
+    if digits.is_empty() {
+        return Ok((separator, CHARS_PER_NUMBER));
+    }
+
+    let digits = String::from_utf8_lossy(digits);
+    let message = format!("'-n' extra characters or invalid number in the argument: '{}'", digits);
+
+    match parse_number(&digits, 1, "") {
+        Ok(parsed) => Ok((separator, parsed as usize)),
+        Err(err) if err.ends_with("Value too large for defined data type") => {
+            Err(format!("{}: Value too large for defined data type", message))
+        },
+        Err(_) => Err(message),
+    }
+}
+
+/// A file to write in pages.
+struct Input<'a> {
+    /// The name of the file in the error messages.
+    name: String,
+    /// The name of the file in the headers, empty for standard input.
+    title: String,
+    /// The time of the last modification of the file, in seconds since the Epoch.
+    modified: Option<i64>,
+    reader: Box<dyn BufRead + 'a>,
+}
+
+impl Input<'static> {
+    /// Open the `file`, or standard input for `-`.
+    fn open(file: &OsStr) -> io::Result<Self> {
+        if file == "-" {
+            return Ok(Input::stdin());
+        }
+
+        let opened = File::open(file)?;
+        let modified = opened.metadata().and_then(|metadata| metadata.modified()).ok();
+
+        Ok(Input {
+            name: file.to_string_lossy().into_owned(),
+            title: file.to_string_lossy().into_owned(),
+            modified: modified.map(seconds_since_epoch),
+            reader: Box::new(BufReader::new(opened)),
+        })
+    }
+
+    fn stdin() -> Self {
+        Input {
+            name: "standard input".to_string(),
+            title: String::new(),
+            modified: None,
+            reader: Box::new(BufReader::new(stdin())),
+        }
+    }
+}
+
+impl Input<'_> {
+    /// The next byte of the file, without reading it.
+    fn peek(&mut self) -> Result<Option<u8>, Error> {
+        loop {
+            match self.reader.fill_buf() {
+                Ok(buffer) => return Ok(buffer.first().copied()),
+                Err(err) if err.kind() == ErrorKind::Interrupted => {},
+                Err(err) => return Err(Error::Read(self.name.clone(), err)),
+            }
+        }
+    }
+
+    /// Read the next byte of the file.
+    fn getc(&mut self) -> Result<Option<u8>, Error> {
+        let byte = self.peek()?;
+        if byte.is_some() {
+            self.reader.consume(1);
+        }
+
+        Ok(byte)
+    }
+}
+
+/// The status of the file of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Open,
+    /// A form feed ends the lines stored for the page.
+    FormFeedFound,
+    /// A form feed ended the page, so the file waits for the next one.
+    OnHold,
+    Closed,
+}
+
+/// A column of the pages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Column {
+    /// The index of the file of the column.
+    input: usize,
+    status: Status,
+    /// Whether the lines of the column are stored until the page is written.
+    stored: bool,
+    /// The index of the next stored line of the column.
+    current_line: usize,
+    lines_stored: usize,
+    /// The number of lines stored, or of the lines left in the page.
+    lines_to_print: isize,
+    /// The position of the first character of the column.
+    start_position: isize,
+    numbered: bool,
+    /// Whether a whole page was written without a form feed, so a form feed right
+    /// after it doesn't make an empty page.
+    full_page_printed: bool,
+}
+
+/// The writer of the pages, that follows GNU.
+///
+/// The positions are in columns of the output, where the spaces are only written
+/// before a character, so they can be replaced by tabs.
+struct Pr<'a, W: Write> {
+    options: &'a Options,
+    output: W,
+    inputs: Vec<Input<'a>>,
+    columns: Vec<Column>,
+    /// The date in the headers.
+    date_text: String,
+    /// The name of the file in the headers.
+    file_text: String,
+    /// The width left in the headers by the date and the name of the file.
+    header_width: isize,
+    /// The current time, in the headers of standard input and of the merged files.
+    now: Option<i64>,
+    page_number: u64,
+    line_number: i64,
+    /// The number of files that are neither on hold nor closed.
+    files_ready: isize,
+    output_position: isize,
+    /// The position in the line of the column being read.
+    input_position: isize,
+    spaces_not_printed: isize,
+    separators_not_printed: isize,
+    /// The position where the column being written starts.
+    padding_not_printed: isize,
+    /// Whether a line was written, so it has to end with a newline.
+    pad_vertically: bool,
+    /// Whether the empty columns of the merged files have to be aligned before the
+    /// next one that is not empty.
+    align_empty_columns: bool,
+    empty_line: bool,
+    /// Whether the line read is only a form feed.
+    form_feed_only: bool,
+    print_a_header: bool,
+    print_a_form_feed: bool,
+    /// The bytes of the last character read, with a tab expanded to spaces.
+    clump: Vec<u8>,
+    /// The lines of the page stored, for the columns filled down the pages.
+    buffer: Vec<u8>,
+    /// The starts of the lines in the buffer, followed by its end.
+    line_starts: Vec<usize>,
+    /// The input positions at the ends of the lines in the buffer.
+    line_ends: Vec<isize>,
+}
+
+impl<'a, W: Write> Pr<'a, W> {
+    fn new(options: &'a Options, output: W) -> Self {
+        Pr {
+            options,
+            output,
+            inputs: Vec::new(),
+            columns: Vec::new(),
+            date_text: String::new(),
+            file_text: String::new(),
+            header_width: 0,
+            now: None,
+            page_number: 1,
+            line_number: 1,
+            files_ready: 0,
+            output_position: 0,
+            input_position: 0,
+            spaces_not_printed: 0,
+            separators_not_printed: 0,
+            padding_not_printed: 0,
+            pad_vertically: false,
+            align_empty_columns: false,
+            empty_line: true,
+            form_feed_only: false,
+            print_a_header: false,
+            print_a_form_feed: false,
+            clump: Vec::new(),
+            buffer: Vec::new(),
+            line_starts: Vec::new(),
+            line_ends: Vec::new(),
+        }
+    }
+
+    /// Write the pages of the `inputs`, that are merged files or a single file.
+    fn print(&mut self, inputs: Vec<Input<'a>>) -> Result<(), Error> {
+        if inputs.is_empty() {
+            return Ok(());
+        }
+
+        self.init_columns(inputs);
+        self.page_number = 1;
+        self.line_number = self.options.first_line_number;
+
+        while self.print_page()? {}
+
+        Ok(())
+    }
+
+    /// Set up the header and the columns of the `inputs`.
+    fn init_columns(&mut self, inputs: Vec<Input<'a>>) {
+        let options = self.options;
+
+        // Like GNU, the merged files and standard input are dated now
+        let (modified, title) = match &inputs[..] {
+            [input] if !options.parallel => (input.modified, input.title.as_str()),
+            _ => (None, ""),
+        };
+
+        let seconds = match modified {
+            Some(seconds) => seconds,
+            None => *self.now.get_or_insert_with(|| seconds_since_epoch(SystemTime::now())),
+        };
+        self.date_text = format_date(seconds);
+        self.file_text = options.header.clone().unwrap_or_else(|| title.to_string());
+        self.header_width = options.chars_per_line
+            - self.date_text.width() as isize
+            - self.file_text.width() as isize;
+
+        let count = if options.parallel { inputs.len() } else { options.columns };
+        self.files_ready = inputs.len() as isize;
+        self.inputs = inputs;
+
+        let separator_length = options.separator.len() as isize;
+        let mut h = options.margin;
+        let mut h_next = if !options.truncate {
+            0
+        } else if options.parallel && options.numbered {
+            // The first column of the merged files is widened for the numbers
+            h + options.chars_per_column + options.number_width
+        } else {
+            h + options.chars_per_column
+        };
+
+        // The separator is in the start position, so all the columns are padded alike
+        h += separator_length;
+
+        self.columns = (0..count)
+            .map(|i| {
+                let column = Column {
+                    input: if options.parallel { i } else { 0 },
+                    status: Status::Open,
+                    stored: options.storing,
+                    current_line: 0,
+                    lines_stored: 0,
+                    lines_to_print: 0,
+                    start_position: h,
+                    numbered: options.numbered && (!options.parallel || i == 0),
+                    full_page_printed: false,
+                };
+
+                if options.truncate {
+                    h = h_next + separator_length;
+                    h_next = h + options.chars_per_column;
+                } else {
+                    h = 0;
+                    h_next = 0;
+                }
+
+                column
+            })
+            .collect();
+    }
+
+    /// Write a page, returning whether there are pages left.
+    fn print_page(&mut self) -> Result<bool, Error> {
+        let options = self.options;
+
+        self.init_page()?;
+
+        if self.cols_ready_to_print() == 0 {
+            return Ok(false);
+        }
+
+        if options.extremities {
+            self.print_a_header = true;
+        }
+
+        // Whether a line was written in the page
+        let mut pv = false;
+        self.pad_vertically = false;
+
+        let mut lines_left_on_page = options.lines_per_body;
+        if options.double_space {
+            lines_left_on_page *= 2;
+        }
+
+        while lines_left_on_page > 0 && self.cols_ready_to_print() > 0 {
+            self.output_position = 0;
+            self.spaces_not_printed = 0;
+            self.separators_not_printed = 0;
+            self.pad_vertically = false;
+            self.align_empty_columns = false;
+            self.empty_line = true;
+
+            for j in 0..self.columns.len() {
+                self.input_position = 0;
+
+                if self.columns[j].lines_to_print > 0
+                    || self.columns[j].status == Status::FormFeedFound
+                {
+                    self.form_feed_only = false;
+                    self.padding_not_printed = self.columns[j].start_position;
+
+                    let printed = if self.columns[j].stored {
+                        self.print_stored(j)?
+                    } else {
+                        self.read_line(j)?
+                    };
+                    if !printed {
+                        self.read_rest_of_line(j)?;
+                    }
+                    pv |= self.pad_vertically;
+
+                    self.columns[j].lines_to_print -= 1;
+                    if self.columns[j].lines_to_print <= 0 && self.cols_ready_to_print() == 0 {
+                        break;
+                    }
+
+                    let status = self.columns[j].status;
+                    if options.parallel && status != Status::Open {
+                        if self.empty_line {
+                            self.align_empty_columns = true;
+                        } else if status == Status::Closed
+                            || (status == Status::OnHold && self.form_feed_only)
+                        {
+                            self.align_column(j)?;
+                        }
+                    }
+                } else if options.parallel {
+                    if self.empty_line {
+                        self.align_empty_columns = true;
+                    } else {
+                        self.align_column(j)?;
+                    }
+                }
+
+                if options.use_separator {
+                    self.separators_not_printed += 1;
+                }
+            }
+
+            if self.pad_vertically {
+                self.write(b"\n")?;
+                lines_left_on_page -= 1;
+            }
+
+            if self.cols_ready_to_print() == 0 && !options.extremities {
+                break;
+            }
+
+            if options.double_space && pv {
+                self.write(b"\n")?;
+                lines_left_on_page -= 1;
+            }
+        }
+
+        if lines_left_on_page == 0 {
+            for column in &mut self.columns {
+                if column.status == Status::Open {
+                    column.full_page_printed = true;
+                }
+            }
+        }
+
+        self.pad_vertically = pv;
+
+        if pv && options.extremities {
+            self.pad_down(lines_left_on_page + LINES_PER_HEADER)?;
+        } else if options.keep_form_feeds && self.print_a_form_feed {
+            self.write(&[FORM_FEED])?;
+            self.print_a_form_feed = false;
+        }
+
+        self.page_number += 1;
+        self.reset_status();
+
+        Ok(true)
+    }
+
+    /// Set the number of lines to write in each column of the page.
+    fn init_page(&mut self) -> Result<(), Error> {
+        if self.options.storing {
+            self.store_columns()?;
+
+            for column in &mut self.columns {
+                column.lines_to_print = column.lines_stored as isize;
+            }
+        } else {
+            let lines_per_body = self.options.lines_per_body;
+
+            for column in &mut self.columns {
+                column.lines_to_print =
+                    if column.status == Status::Closed { 0 } else { lines_per_body };
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The number of columns with lines left to write.
+    fn cols_ready_to_print(&self) -> usize {
+        let storing = self.options.storing;
+
+        self.columns
+            .iter()
+            .filter(|column| {
+                column.status == Status::Open
+                    || column.status == Status::FormFeedFound
+                    || (storing && column.lines_stored > 0 && column.lines_to_print > 0)
+            })
+            .count()
+    }
+
+    /// Store the lines of the page of the columns filled down, balanced between the
+    /// columns.
+    fn store_columns(&mut self) -> Result<(), Error> {
+        self.buffer.clear();
+        self.line_starts.clear();
+        self.line_ends.clear();
+
+        let mut buff_start = 0;
+
+        for column in &mut self.columns {
+            column.lines_stored = 0;
+        }
+
+        for i in 0..self.columns.len() {
+            if self.files_ready == 0 {
+                break;
+            }
+
+            self.columns[i].current_line = self.line_starts.len();
+
+            let mut j = self.options.lines_per_body;
+            while j > 0 && self.files_ready != 0 {
+                j -= 1;
+
+                if self.columns[i].status != Status::Open {
+                    continue;
+                }
+
+                self.input_position = 0;
+                if !self.read_line(i)? {
+                    self.read_rest_of_line(i)?;
+                }
+
+                if self.columns[i].status == Status::Open || buff_start != self.buffer.len() {
+                    self.columns[i].lines_stored += 1;
+                    self.line_starts.push(buff_start);
+                    self.line_ends.push(self.input_position);
+                    buff_start = self.buffer.len();
+                }
+            }
+        }
+
+        self.line_starts.push(buff_start);
+
+        let total = self.line_ends.len();
+        let mut first_line = 0;
+        let count = self.columns.len();
+
+        for (i, column) in self.columns.iter_mut().enumerate() {
+            let lines = total / count + if i < total % count { 1 } else { 0 };
+
+            column.lines_stored = lines;
+            column.current_line = first_line;
+            first_line += lines;
+        }
+
+        Ok(())
+    }
+
+    /// Read a line of the file of the `column`, writing it or storing it, and returning
+    /// false if the rest of the line is truncated.
+    fn read_line(&mut self, column: usize) -> Result<bool, Error> {
+        let options = self.options;
+
+        let mut c = self.getc(column)?;
+        let last_input_position = self.input_position;
+
+        if c == Some(FORM_FEED) && self.columns[column].full_page_printed {
+            c = self.getc(column)?;
+            if c == Some(b'\n') {
+                c = self.getc(column)?;
+            }
+        }
+        self.columns[column].full_page_printed = false;
+
+        match c {
+            Some(FORM_FEED) => {
+                self.skip_newline(column)?;
+                self.form_feed_only = true;
+
+                if self.print_a_header && !options.storing {
+                    self.pad_vertically = true;
+                    self.print_header()?;
+                } else if options.keep_form_feeds {
+                    self.print_a_form_feed = true;
+                }
+
+                self.hold_file(column);
+                return Ok(true);
+            },
+            None => {
+                self.close_file(column);
+                return Ok(true);
+            },
+            Some(b'\n') => {},
+            Some(c) => self.char_to_clump(column, c)?,
+        }
+
+        if options.truncate && self.input_position > options.chars_per_column {
+            self.input_position = last_input_position;
+            return Ok(false);
+        }
+
+        if !self.columns[column].stored {
+            self.pad_vertically = true;
+
+            if self.print_a_header && !options.storing {
+                self.print_header()?;
+            }
+
+            if options.parallel && self.align_empty_columns {
+                // The empty columns before this one are aligned at the start of the line
+                let empty = self.separators_not_printed;
+                self.separators_not_printed = 0;
+
+                for q in 0..empty as usize {
+                    self.align_column(q)?;
+                    self.separators_not_printed += 1;
+                }
+
+                self.padding_not_printed = self.columns[column].start_position;
+                self.spaces_not_printed =
+                    if options.truncate { options.chars_per_column } else { 0 };
+                self.align_empty_columns = false;
+            }
+
+            self.pad_to_column()?;
+        }
+
+        if self.columns[column].numbered {
+            self.add_line_number(column)?;
+        }
+
+        self.empty_line = false;
+        if c == Some(b'\n') {
+            return Ok(true);
+        }
+
+        self.print_clump(column)?;
+
+        loop {
+            match self.getc(column)? {
+                Some(b'\n') => return Ok(true),
+                Some(FORM_FEED) => {
+                    self.skip_newline(column)?;
+                    if options.keep_form_feeds {
+                        self.print_a_form_feed = true;
+                    }
+
+                    self.hold_file(column);
+                    return Ok(true);
+                },
+                None => {
+                    self.close_file(column);
+                    return Ok(true);
+                },
+                Some(c) => {
+                    let last_input_position = self.input_position;
+                    self.char_to_clump(column, c)?;
+
+                    if options.truncate && self.input_position > options.chars_per_column {
+                        self.input_position = last_input_position;
+                        return Ok(false);
+                    }
+
+                    self.print_clump(column)?;
+                },
+            }
+        }
+    }
+
+    /// Skip the rest of the truncated line of the `column`.
+    fn read_rest_of_line(&mut self, column: usize) -> Result<(), Error> {
+        loop {
+            match self.getc(column)? {
+                Some(b'\n') => return Ok(()),
+                Some(FORM_FEED) => {
+                    self.skip_newline(column)?;
+                    if self.options.keep_form_feeds {
+                        self.print_a_form_feed = true;
+                    }
+
+                    self.hold_file(column);
+                    return Ok(());
+                },
+                None => {
+                    self.close_file(column);
+                    return Ok(());
+                },
+                Some(_) => {},
+            }
+        }
+    }
+
+    /// Write the next stored line of the `column`.
+    fn print_stored(&mut self, column: usize) -> Result<bool, Error> {
+        let options = self.options;
+
+        let line = self.columns[column].current_line;
+        self.columns[column].current_line += 1;
+        self.pad_vertically = true;
+
+        if self.print_a_header {
+            self.print_header()?;
+        }
+
+        if self.columns[column].status == Status::FormFeedFound {
+            for column in &mut self.columns {
+                column.status = Status::OnHold;
+            }
+
+            if self.columns[0].lines_to_print <= 0 {
+                if !options.extremities {
+                    self.pad_vertically = false;
+                }
+
+                // Only the header is written
+                return Ok(true);
+            }
+        }
+
+        self.pad_to_column()?;
+
+        let buffer = mem::take(&mut self.buffer);
+        let result = self.print_text(&buffer[self.line_starts[line]..self.line_starts[line + 1]]);
+        self.buffer = buffer;
+        result?;
+
+        if self.spaces_not_printed == 0 {
+            let start_position = self.columns[column].start_position;
+            let separator_length = options.separator.len() as isize;
+
+            self.output_position = start_position + self.line_ends[line];
+            if start_position - separator_length == options.margin {
+                self.output_position -= separator_length;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Write the padding and the separator before the column being written.
+    fn pad_to_column(&mut self) -> Result<(), Error> {
+        let separator_length = self.options.separator.len() as isize;
+
+        if self.padding_not_printed - separator_length > 0 {
+            self.pad_across_to(self.padding_not_printed - separator_length)?;
+            self.padding_not_printed = 0;
+        }
+
+        if self.options.use_separator {
+            self.print_sep_string()?;
+        }
+
+        Ok(())
+    }
+
+    /// Align the empty `column` of a merged file, with its separator and line number.
+    fn align_column(&mut self, column: usize) -> Result<(), Error> {
+        let separator_length = self.options.separator.len() as isize;
+
+        self.padding_not_printed = self.columns[column].start_position;
+        if separator_length < self.padding_not_printed {
+            self.pad_across_to(self.padding_not_printed - separator_length)?;
+            self.padding_not_printed = 0;
+        }
+
+        if self.options.use_separator {
+            self.print_sep_string()?;
+        }
+
+        if self.columns[column].numbered {
+            self.add_line_number(column)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write or store the line number of the `column`.
+    fn add_line_number(&mut self, column: usize) -> Result<(), Error> {
+        let options = self.options;
+        let digits = options.chars_per_number;
+
+        // The numbers are cut to their last digits
+        let number = format!("{:>1$}", self.line_number, digits);
+        self.line_number += 1;
+        self.put(column, &number.as_bytes()[number.len() - digits..])?;
+
+        if self.columns.len() > 1 {
+            if options.number_separator == b'\t' {
+                let spaces = options.number_width as usize - digits;
+                self.put(column, &vec![b' '; spaces])?;
+            } else {
+                self.put(column, &[options.number_separator])?;
+            }
+        } else {
+            // Like POSIX, a tab is written as it is with a single column
+            self.put(column, &[options.number_separator])?;
+            if options.number_separator == b'\t' {
+                self.output_position += TAB_WIDTH - self.output_position % TAB_WIDTH;
+            }
+        }
+
+        if options.truncate && !options.parallel {
+            self.input_position += options.number_width;
+        }
+
+        Ok(())
+    }
+
+    /// Write the header of the page.
+    fn print_header(&mut self) -> Result<(), Error> {
+        let margin = self.options.margin;
+
+        self.output_position = 0;
+        self.pad_across_to(margin)?;
+        self.print_white_space()?;
+
+        let page_text = format!("Page {}", self.page_number);
+        let available = (self.header_width - page_text.width() as isize).max(0) as usize;
+        let lhs_spaces = available / 2;
+
+        // Like GNU, the parts of the header are separated by at least a space
+        write!(
+            self.output,
+            "\n\n{:margin$}{}{:lhs$}{}{:rhs$}{}\n\n\n",
+            "",
+            self.date_text,
+            " ",
+            self.file_text,
+            " ",
+            page_text,
+            margin = margin as usize,
+            lhs = lhs_spaces,
+            rhs = available - lhs_spaces
+        )
+        .map_err(Error::Write)?;
+
+        self.print_a_header = false;
+        self.output_position = 0;
+
+        Ok(())
+    }
+
+    /// Write the newlines, or the form feed, that end the page after its `lines` left.
+    fn pad_down(&mut self, lines: isize) -> Result<(), Error> {
+        if self.options.form_feed {
+            self.write(&[FORM_FEED])
+        } else {
+            for _ in 0..lines {
+                self.write(b"\n")?;
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Move the output to the `position`, with spaces that are written before the next
+    /// character if the spaces are replaced by tabs.
+    fn pad_across_to(&mut self, position: isize) -> Result<(), Error> {
+        if self.options.tabify {
+            self.spaces_not_printed = position - self.output_position;
+        } else {
+            for _ in self.output_position..position {
+                self.write(b" ")?;
+            }
+
+            self.output_position = position;
+        }
+
+        Ok(())
+    }
+
+    /// Write the spaces not written yet, replaced by tabs where they can.
+    fn print_white_space(&mut self) -> Result<(), Error> {
+        let mut h_old = self.output_position;
+        let goal = h_old + self.spaces_not_printed;
+
+        while goal - h_old > 1 {
+            let h_new = h_old + TAB_WIDTH - h_old % TAB_WIDTH;
+            if h_new > goal {
+                break;
+            }
+
+            self.write(b"\t")?;
+            h_old = h_new;
+        }
+
+        for _ in h_old..goal {
+            self.write(b" ")?;
+        }
+
+        self.output_position = goal;
+        self.spaces_not_printed = 0;
+
+        Ok(())
+    }
+
+    /// Write the separator of the columns, where its spaces may be tabs.
+    fn print_sep_string(&mut self) -> Result<(), Error> {
+        let separator = &self.options.separator;
+
+        if self.separators_not_printed <= 0 {
+            if self.spaces_not_printed > 0 {
+                self.print_white_space()?;
+            }
+
+            return Ok(());
+        }
+
+        // Like GNU, the separator is only written once for all the separators not
+        // written yet
+        let mut bytes = separator.iter();
+
+        while self.separators_not_printed > 0 {
+            for &byte in bytes.by_ref() {
+                if byte == b' ' {
+                    self.spaces_not_printed += 1;
+                } else {
+                    if self.spaces_not_printed > 0 {
+                        self.print_white_space()?;
+                    }
+
+                    self.write(&[byte])?;
+                    self.output_position += 1;
+                }
+            }
+
+            if self.spaces_not_printed > 0 {
+                self.print_white_space()?;
+            }
+
+            self.separators_not_printed -= 1;
+        }
+
+        Ok(())
+    }
+
+    /// Read the character that starts with the byte `c` in the file of the `column`,
+    /// with a tab expanded to spaces if the tabs are expanded, and advance the input
+    /// position by its width.
+    fn char_to_clump(&mut self, column: usize, c: u8) -> Result<(), Error> {
+        self.clump.clear();
+        self.clump.push(c);
+
+        let width = match c {
+            b'\t' => {
+                let width = TAB_WIDTH - self.input_position % TAB_WIDTH;
+                if self.options.untabify {
+                    self.clump = vec![b' '; width as usize];
+                }
+
+                width
+            },
+            0xc2..=0xf4 => {
+                let len = match c {
+                    0xc2..=0xdf => 2,
+                    0xe0..=0xef => 3,
+                    _ => 4,
+                };
+
+                while self.clump.len() < len {
+                    match self.peek(column)? {
+                        Some(next @ 0x80..=0xbf) => {
+                            self.clump.push(next);
+                            self.getc(column)?;
+                        },
+                        _ => break,
+                    }
+                }
+
+                char_width(&self.clump).1
+            },
+            _ => char_width(&self.clump).1,
+        };
+
+        // Too many backspaces put the input at its start, never before it
+        if width < 0 && self.input_position == 0 {
+            self.clump.clear();
+        } else if width < 0 && self.input_position <= -width {
+            self.input_position = 0;
+        } else {
+            self.input_position += width;
+        }
+
+        Ok(())
+    }
+
+    /// Write or store the last character read in the `column`.
+    fn print_clump(&mut self, column: usize) -> Result<(), Error> {
+        let clump = mem::take(&mut self.clump);
+        let result = self.put(column, &clump);
+        self.clump = clump;
+
+        result
+    }
+
+    /// Write the `text` of the `column`, or store it if its lines are stored.
+    fn put(&mut self, column: usize, text: &[u8]) -> Result<(), Error> {
+        if self.columns[column].stored {
+            self.buffer.extend_from_slice(text);
+            Ok(())
+        } else {
+            self.print_text(text)
+        }
+    }
+
+    /// Write the `text`, keeping its spaces until the next character if they are
+    /// replaced by tabs.
+    fn print_text(&mut self, text: &[u8]) -> Result<(), Error> {
+        if !self.options.tabify {
+            return self.write(text);
+        }
+
+        let mut start = 0;
+
+        while start < text.len() {
+            let (len, width) = char_width(&text[start..]);
+            let character = &text[start..start + len];
+            start += len;
+
+            if character == b" " {
+                self.spaces_not_printed += 1;
+                continue;
+            }
+
+            if self.spaces_not_printed > 0 {
+                self.print_white_space()?;
+            }
+
+            self.output_position += width;
+            self.write(character)?;
+        }
+
+        Ok(())
+    }
+
+    /// Hold the file of the `column` until the next page, after a form feed.
+    fn hold_file(&mut self, column: usize) {
+        if self.options.parallel {
+            self.columns[column].status = Status::OnHold;
+        } else {
+            let status = if self.options.storing { Status::FormFeedFound } else { Status::OnHold };
+
+            for column in &mut self.columns {
+                column.status = status;
+            }
+        }
+
+        self.columns[column].lines_to_print = 0;
+        self.files_ready -= 1;
+    }
+
+    /// Close the file of the `column`, at its end.
+    fn close_file(&mut self, column: usize) {
+        if self.columns[column].status == Status::Closed {
+            return;
+        }
+
+        if self.options.parallel {
+            self.columns[column].status = Status::Closed;
+            self.columns[column].lines_to_print = 0;
+        } else {
+            for column in &mut self.columns {
+                column.status = Status::Closed;
+                if column.lines_stored == 0 {
+                    column.lines_to_print = 0;
+                }
+            }
+        }
+
+        self.files_ready -= 1;
+    }
+
+    /// Open again the files held until the next page.
+    fn reset_status(&mut self) {
+        for column in &mut self.columns {
+            if column.status == Status::OnHold {
+                column.status = Status::Open;
+                self.files_ready += 1;
+            }
+        }
+
+        if self.options.storing {
+            self.files_ready = if self.columns[0].status == Status::Closed { 0 } else { 1 };
+        }
+    }
+
+    fn peek(&mut self, column: usize) -> Result<Option<u8>, Error> {
+        self.inputs[self.columns[column].input].peek()
+    }
+
+    fn getc(&mut self, column: usize) -> Result<Option<u8>, Error> {
+        self.inputs[self.columns[column].input].getc()
+    }
+
+    /// Skip the newline right after a form feed in the file of the `column`.
+    fn skip_newline(&mut self, column: usize) -> Result<(), Error> {
+        if self.peek(column)? == Some(b'\n') {
+            self.getc(column)?;
+        }
+
+        Ok(())
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.output.write_all(bytes).map_err(Error::Write)
+    }
+}
+
+/// The length of the character at the start of the `text`, and its width.
+///
+/// Like GNU, a backspace moves back a column, and the other bytes that are not
+/// printable characters, like the invalid UTF-8, take no column.
+fn char_width(text: &[u8]) -> (usize, isize) {
+    match text[0] {
+        b' '..=b'~' => return (1, 1),
+        b'\x08' => return (1, -1),
+        0..=0x7f => return (1, 0),
+        _ => {},
+    }
+
+    let text = &text[..text.len().min(4)];
+    let valid = match str::from_utf8(text) {
+        Ok(valid) => valid,
+        Err(err) => str::from_utf8(&text[..err.valid_up_to()]).unwrap(),
+    };
+
+    match valid.chars().next() {
+        Some(c) => (c.len_utf8(), c.width().unwrap_or(0) as isize),
+        None => (1, 0),
+    }
+}
+
+/// The seconds since the Epoch of the `time`.
+fn seconds_since_epoch(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(err) => {
+            let before = err.duration();
+            -(before.as_secs() as i64) - i64::from(before.subsec_nanos() > 0)
+        },
+    }
+}
+
+/// The date of `seconds` since the Epoch in the local time, like `2020-01-02 03:04`.
+fn format_date(seconds: i64) -> String {
+    let fields = Fields::from_seconds(seconds + Zone::Local.offset_at(seconds));
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        fields.year, fields.month, fields.day, fields.hour, fields.minute
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pr(args: &[&str], files: &[&str]) -> String {
+        let args = ["pr"].iter().chain(args).map(OsString::from);
+        let matches = cli::create_app().get_matches_from(normalize_args(args));
+        let options = Options::from_matches(&matches, files.len()).unwrap();
+
+        let mut output = Vec::new();
+        let mut pr = Pr::new(&options, &mut output);
+        let mut inputs = files.iter().map(|text| Input {
+            name: "file".to_string(),
+            title: "file".to_string(),
+            modified: Some(0),
+            reader: Box::new(text.as_bytes()),
+        });
+
+        if options.parallel {
+            pr.print(inputs.collect()).unwrap();
+        } else {
+            inputs.try_for_each(|input| pr.print(vec![input])).unwrap();
+        }
+
+        String::from_utf8(output).unwrap()
+    }
+
+    fn header(page: usize) -> String {
+        format!("\n\n{}{:23}file{:23}Page {}\n\n\n", format_date(0), "", "", page)
+    }
+
+    #[test]
+    fn pages() {
+        assert_eq!(
+            pr(&["-l", "15"], &["1\n2\n3\n4\n5\n6\n7\n"]),
+            format!(
+                "{}1\n2\n3\n4\n5\n{}{}6\n7\n{}",
+                header(1),
+                "\n".repeat(5),
+                header(2),
+                "\n".repeat(8)
+            )
+        );
+        assert_eq!(pr(&["-l", "15", "-F"], &["1\n"]), format!("{}1\n\x0c", header(1)));
+        assert_eq!(pr(&["-l", "15"], &[""]), "");
+        assert_eq!(pr(&["-l", "5"], &["1\n2\n3\n4\n5\n6\n"]), "1\n2\n3\n4\n5\n6\n");
+    }
+
+    #[test]
+    fn omit_header() {
+        assert_eq!(pr(&["-t", "-l", "2"], &["1\n2\n3\n"]), "1\n2\n3\n");
+        assert_eq!(pr(&["-t", "-d"], &["1\n2"]), "1\n\n2\n");
+        assert_eq!(pr(&["-t"], &["1\n", "2\n"]), "1\n2\n");
+    }
+
+    #[test]
+    fn columns() {
+        let lines = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+
+        assert_eq!(
+            pr(&["-t", "-3"], &[lines]),
+            "1\t\t\t5\t\t\t8\n2\t\t\t6\t\t\t9\n3\t\t\t7\t\t\t10\n4\n"
+        );
+        assert_eq!(
+            pr(&["-t", "-3", "-a"], &[lines]),
+            "1\t\t\t2\t\t\t3\n4\t\t\t5\t\t\t6\n7\t\t\t8\t\t\t9\n10\n"
+        );
+        assert_eq!(
+            pr(&["-t", "-l", "2", "-2"], &["1\n2\n3\n4\n5\n"]),
+            "1\t\t\t\t    3\n2\t\t\t\t    4\n5\n"
+        );
+        assert_eq!(pr(&["-t", "-w", "9", "-3"], &["abcd\nefgh\n"]), "ab ef\n");
+        assert_eq!(pr(&["-t", "-2", "-S|"], &["a\nb\n"]), "a\t\t\t\t   |b\n");
+        assert_eq!(pr(&["-t", "-2", "-J", "-S|"], &["a\nb\n"]), "a|b\n");
+        assert_eq!(
+            pr(&["-t", "-2", "-s:"], &["abcdefghijklmnopqrstuvwxyz0123456789ab\nb\n"]),
+            "abcdefghijklmnopqrstuvwxyz0123456789ab:b\n"
+        );
+    }
+
+    #[test]
+    fn merge() {
+        assert_eq!(
+            pr(&["-t", "-m"], &["1\n2\n", "a\nb\nc\n"]),
+            "1\t\t\t\t    a\n2\t\t\t\t    b\n\t\t\t\t    c\n"
+        );
+        assert_eq!(pr(&["-t", "-m", "-s"], &["1\n2\n", "a\nb\nc\n"]), "1\ta\n2\tb\n\tc\n");
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(pr(&["-t", "-n"], &["a\nb\n"]), "    1\ta\n    2\tb\n");
+        assert_eq!(pr(&["-t", "-n:3", "-o", "2"], &["a\nb\n"]), "    1:a\n    2:b\n");
+        assert_eq!(pr(&["-t", "-n", "-N", "99", "-n,2"], &["a\nb\n"]), "99,a\n00,b\n");
+        assert_eq!(pr(&["-t", "-n"], &["a\n", "b\n"]), "    1\ta\n    1\tb\n");
+    }
+
+    #[test]
+    fn form_feeds() {
+        assert_eq!(pr(&["-t"], &["a\x0cb\nc\n"]), "a\n\x0cb\nc\n");
+        assert_eq!(pr(&["-T"], &["a\x0cb\nc\n"]), "a\nb\nc\n");
+        assert_eq!(
+            pr(&["-l", "12"], &["a\x0cb\nc\n"]),
+            format!("{}a\n{}{}b\nc\n{}", header(1), "\n".repeat(6), header(2), "\n".repeat(5))
+        );
+    }
+
+    #[test]
+    fn args() {
+        let normalize = |args: &[&str]| {
+            let args = ["pr"].iter().chain(args).map(OsString::from);
+            normalize_args(args)
+                .into_iter()
+                .skip(1)
+                .map(|arg| arg.into_string().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(normalize(&["-3", "file"]), ["file", "--columns=3"]);
+        assert_eq!(normalize(&["-t3a", "-h", "-4"]), ["-t", "-a", "-h", "-4", "--columns=3"]);
+        assert_eq!(normalize(&["-1", "-2", "-t", "-3"]), ["-t", "--columns=3"]);
+        assert_eq!(normalize(&["-n3", "-s", "-S:"]), [
+            "--number-lines=3",
+            "--separator=",
+            "--sep-string=:"
+        ]);
+        assert_eq!(normalize(&["-n", "--", "-3"]), ["--number-lines", "--", "-3"]);
+    }
+
+    #[test]
+    fn numbers_of_options() {
+        assert_eq!(parse_number(" +12", 1, "invalid"), Ok(12));
+        assert_eq!(parse_number("-5", i32::MIN.into(), "invalid"), Ok(-5));
+        assert_eq!(parse_number("1x", 1, "invalid"), Err("invalid: '1x'".to_string()));
+        assert_eq!(
+            parse_number("0", 1, "invalid"),
+            Err("invalid: '0': Numerical result out of range".to_string())
+        );
+        assert_eq!(
+            parse_number("3000000000", 1, "invalid"),
+            Err("invalid: '3000000000': Value too large for defined data type".to_string())
+        );
+        assert_eq!(parse_numbering(":3"), Ok((b':', 3)));
+        assert_eq!(parse_numbering("4"), Ok((b'\t', 4)));
+        assert_eq!(
+            parse_numbering("x0"),
+            Err("'-n' extra characters or invalid number in the argument: '0'".to_string())
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("The files to index, where '-' is standard input.")
+                .long_help(
+                    "The files to index, where '-' is standard input.\n\nWith -G, only one file \
+                     is read, and the index is written to the second FILE, if any.",
+                )
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("autoReference")
+                .help("Write the file name and the line number of each keyword as its reference.")
+                .long_help(
+                    "Write the file name and the line number of each keyword as its \
+                     reference.\n\nThe references are FILE:LINE, where FILE is empty for standard \
+                     input.",
+                )
+                .long("auto-reference")
+                .short("A"),
+        )
+        .arg(
+            Arg::with_name("traditional")
+                .help("Behave more like System V ptx.")
+                .long_help(
+                    "Behave more like System V ptx.\n\nThe output is written with roff \
+                     directives, the contexts are the lines, and the keywords are separated only \
+                     by spaces, tabs and newlines.",
+                )
+                .long("traditional")
+                .short("G"),
+        )
+        .arg(
+            Arg::with_name("flagTruncation")
+                .help("Flag the truncated contexts with STRING, instead of '/'.")
+                .long_help(
+                    "Flag the truncated contexts with STRING, instead of '/'.\n\nSTRING may have \
+                     the escapes \\\\, \\a, \\b, \\f, \\n, \\r, \\t, \\v, \\0NNN (octal) and \
+                     \\xHHH (hexadecimal), and an empty STRING flags nothing.",
+                )
+                .long("flag-truncation")
+                .short("F")
+                .value_name("STRING")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("macroName")
+                .help("Name the roff or TeX macro of the lines STRING, instead of 'xx'.")
+                .long("macro-name")
+                .short("M")
+                .value_name("STRING")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("roff")
+                .help("Write the lines as roff directives, like --format=roff.")
+                .short("O")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("tex")
+                .help("Write the lines as TeX directives, like --format=tex.")
+                .short("T")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Write the lines as directives of FORMAT.")
+                .long("format")
+                .value_name("FORMAT")
+                .possible_values(&["roff", "tex"])
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("rightSideRefs")
+                .help("Write the references at the right of the lines, outside of their width.")
+                .long("right-side-refs")
+                .short("R"),
+        )
+        .arg(
+            Arg::with_name("sentenceRegexp")
+                .help("Match the ends of the contexts with REGEXP.")
+                .long_help(
+                    "Match the ends of the contexts with REGEXP.\n\nBy default, the contexts are \
+                     the sentences, or the lines with -G or -r, and an empty REGEXP makes each \
+                     file a single context. Like GNU, the regular expressions group with \\( \\) \
+                     and \\|, and repeat with *, + and ?.",
+                )
+                .long("sentence-regexp")
+                .short("S")
+                .value_name("REGEXP")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("wordRegexp")
+                .help("Match each keyword with REGEXP.")
+                .long_help(
+                    "Match each keyword with REGEXP.\n\nBy default, the keywords are the \
+                     sequences of letters, or of the characters other than spaces, tabs and \
+                     newlines with -G.",
+                )
+                .long("word-regexp")
+                .short("W")
+                .value_name("REGEXP")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("breakFile")
+                .help("Separate the keywords with the characters of FILE.")
+                .long("break-file")
+                .short("b")
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::with_name("ignoreCase")
+                .help("Fold the lower case letters to upper case to sort and match the keywords.")
+                .long("ignore-case")
+                .short("f"),
+        )
+        .arg(
+            Arg::with_name("gapSize")
+                .help("Separate the fields of the lines with NUMBER columns, instead of 3.")
+                .long("gap-size")
+                .short("g")
+                .value_name("NUMBER")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("ignoreFile")
+                .help("Ignore the keywords of FILE, one per line.")
+                .long("ignore-file")
+                .short("i")
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::with_name("onlyFile")
+                .help("Index only the keywords of FILE, one per line.")
+                .long("only-file")
+                .short("o")
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::with_name("references")
+                .help("Read the first field of each line as its reference.")
+                .long("references")
+                .short("r"),
+        )
+        .arg(
+            Arg::with_name("typesetMode")
+                .help("Do nothing, like GNU.")
+                .long("typeset-mode")
+                .short("t"),
+        )
+        .arg(
+            Arg::with_name("width")
+                .help("Write lines of NUMBER columns, instead of 72.")
+                .long_help(
+                    "Write lines of NUMBER columns, instead of 72.\n\nThe references written at \
+                     the left of the lines are in the width.",
+                )
+                .long("width")
+                .short("w")
+                .value_name("NUMBER")
+                .allow_hyphen_values(true),
+        )
+}
@@ -0,0 +1,1045 @@
+use std::{
+    cmp::Ordering,
+    fs::{self, File},
+    io::{self, stdin, stdout, BufWriter, Read, Write},
+    process,
+};
+
+use clap::{ArgMatches, Values};
+use coreutils_core::emacs;
+use regex::bytes::Regex;
+
+mod cli;
+
+/// The end of the contexts by default, a sentence or a line, like GNU.
+const SENTENCE: &[u8] = b"[.?!][]\"')}]*\\($\\|\t\\|  \\)[ \t\n]*";
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+    let options = Options::from_matches(&matches).unwrap_or_else(|err| fail(&err));
+
+    let mut files: Vec<_> = matches.values_of("FILE").map_or_else(Vec::new, Values::collect);
+    let mut output = None;
+    if options.traditional {
+        // Like System V, the second operand is the output file
+        if files.len() > 2 {
+            fail(&format!("extra operand '{}'", files[2]));
+        }
+        output = files.get(1).copied();
+        files.truncate(1);
+    }
+    if files.is_empty() {
+        files.push("-");
+    }
+
+    let inputs = files
+        .iter()
+        .map(|&file| {
+            let text = read(file).unwrap_or_else(|err| fail(&err));
+            // Like GNU, the name of standard input is empty in the references
+            let name = if file == "-" { String::new() } else { file.to_string() };
+            Input { name, text }
+        })
+        .collect();
+    let ptx = Ptx::new(&options, inputs).unwrap_or_else(|err| fail(&err));
+
+    let result = match output {
+        Some(output) => match File::create(output) {
+            Ok(file) => ptx.write(&mut BufWriter::new(file)),
+            Err(err) => fail(&format!("{}: {}", output, err)),
+        },
+        None => {
+            let stdout = stdout();
+
+            // The lock has to be dropped before `stdout`
+            #[allow(clippy::let_and_return)]
+            let result = ptx.write(&mut BufWriter::new(stdout.lock()));
+            result
+        },
+    };
+    if let Err(err) = result {
+        fail(&format!("write error: {}", err));
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("ptx: {}", message);
+    process::exit(1);
+}
+
+/// Read the whole `file`, where '-' and an empty name are standard input.
+fn read(file: &str) -> Result<Vec<u8>, String> {
+    let result = if file == "-" || file.is_empty() {
+        let mut text = Vec::new();
+        stdin().lock().read_to_end(&mut text).map(|_| text)
+    } else {
+        fs::read(file)
+    };
+    result.map_err(|err| format!("{}: {}", file, err))
+}
+
+/// How the lines are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// The fields are aligned in columns.
+    Dumb,
+    Roff,
+    Tex,
+}
+
+/// A regular expression, with its text for the error messages.
+#[derive(Debug, Clone)]
+struct Pattern {
+    regex: Regex,
+    text: Vec<u8>,
+}
+
+impl Pattern {
+    fn new(text: Vec<u8>, ignore_case: bool) -> Result<Self, String> {
+        match emacs::compile(&text, ignore_case) {
+            Ok(regex) => Ok(Pattern { regex, text }),
+            Err(err) => Err(format!("{} (for regexp {})", err, quote(&text))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Options {
+    /// Whether it behaves like System V ptx, without the GNU extensions.
+    traditional: bool,
+    auto_reference: bool,
+    input_reference: bool,
+    right_reference: bool,
+    ignore_case: bool,
+    width: i64,
+    gap: i64,
+    /// The flag of the truncated contexts, where an empty one flags nothing.
+    truncation: Vec<u8>,
+    macro_name: String,
+    format: Format,
+    /// The end of the contexts, where `None` makes each file a single context.
+    sentence: Option<Pattern>,
+    /// The keywords, or `None` for the sequences of the word characters.
+    word: Option<Pattern>,
+    /// Whether each byte is a word character.
+    word_chars: [bool; 256],
+    /// The keywords ignored, sorted, if any.
+    ignore: Option<Vec<Vec<u8>>>,
+    /// The only keywords indexed, sorted, if any.
+    only: Option<Vec<Vec<u8>>>,
+}
+
+impl Options {
+    fn from_matches(matches: &ArgMatches) -> Result<Self, String> {
+        let traditional = matches.is_present("traditional");
+        let ignore_case = matches.is_present("ignoreCase");
+        let input_reference = matches.is_present("references");
+
+        let width = match matches.value_of("width") {
+            Some(width) => parse_number(width)
+                .filter(|&width| width > 0)
+                .ok_or_else(|| format!("invalid line width: '{}'", width))?,
+            None => 72,
+        };
+        let gap = match matches.value_of("gapSize") {
+            Some(gap) => parse_number(gap)
+                .filter(|&gap| gap > 0)
+                .ok_or_else(|| format!("invalid gap width: '{}'", gap))?,
+            None => 3,
+        };
+
+        // Like GNU, the last of -O, -T and --format is used
+        let last = |name: &str| matches.indices_of(name).and_then(Iterator::max);
+        let formats = [
+            (last("roff"), Format::Roff),
+            (last("tex"), Format::Tex),
+            (last("format"), match matches.values_of("format").and_then(Iterator::last) {
+                Some("tex") => Format::Tex,
+                _ => Format::Roff,
+            }),
+        ];
+        let format = match formats
+            .iter()
+            .filter(|(index, _)| index.is_some())
+            .max_by_key(|(index, _)| index)
+        {
+            Some(&(_, format)) => format,
+            None if traditional => Format::Roff,
+            None => Format::Dumb,
+        };
+
+        let sentence = match matches.value_of("sentenceRegexp") {
+            Some(sentence) => unescape(sentence),
+            None if traditional || input_reference => b"\n".to_vec(),
+            None => SENTENCE.to_vec(),
+        };
+        let sentence =
+            if sentence.is_empty() { None } else { Some(Pattern::new(sentence, ignore_case)?) };
+        let word = matches.value_of("wordRegexp").map(unescape).unwrap_or_default();
+        let word = if word.is_empty() { None } else { Some(Pattern::new(word, ignore_case)?) };
+
+        let mut word_chars = [true; 256];
+        match matches.value_of("breakFile") {
+            Some(file) => {
+                for &c in &read(file)? {
+                    word_chars[usize::from(c)] = false;
+                }
+            },
+            None if !traditional => {
+                for (c, is_word) in word_chars.iter_mut().enumerate() {
+                    *is_word = (c as u8).is_ascii_alphabetic();
+                }
+            },
+            None => {},
+        }
+        // Without the GNU extensions, the spaces, tabs and newlines are always breaks
+        if traditional {
+            for &c in b" \t\n" {
+                word_chars[usize::from(c)] = false;
+            }
+        }
+
+        let words = |name: &str| -> Result<Option<Vec<Vec<u8>>>, String> {
+            let file = match matches.value_of(name) {
+                Some(file) => file,
+                None => return Ok(None),
+            };
+            let text = read(file)?;
+            let mut words: Vec<_> = text
+                .split(|&c| c == b'\n')
+                .filter(|word| !word.is_empty())
+                .map(Vec::from)
+                .collect();
+            words.sort_by(|word1, word2| compare_words(word1, word2, ignore_case));
+            Ok(Some(words).filter(|words| !words.is_empty()))
+        };
+
+        Ok(Options {
+            traditional,
+            auto_reference: matches.is_present("autoReference"),
+            input_reference,
+            right_reference: matches.is_present("rightSideRefs"),
+            ignore_case,
+            width,
+            gap,
+            truncation: matches.value_of("flagTruncation").map_or(b"/".to_vec(), unescape),
+            macro_name: matches.value_of("macroName").unwrap_or("xx").to_string(),
+            format,
+            sentence,
+            word,
+            word_chars,
+            ignore: words("ignoreFile")?,
+            only: words("onlyFile")?,
+        })
+    }
+
+    /// Whether the `word` is in the `words`, which are sorted.
+    fn has_word(&self, words: &[Vec<u8>], word: &[u8]) -> bool {
+        words.binary_search_by(|other| compare_words(other, word, self.ignore_case)).is_ok()
+    }
+
+    /// The position after the word, or else the character, at the `cursor` of the `text`,
+    /// up to the `limit`.
+    fn skip_something(&self, text: &[u8], cursor: usize, limit: usize) -> usize {
+        match &self.word {
+            Some(word) => match word.regex.find(&text[cursor..limit]) {
+                // Unlike GNU, the empty words are skipped like a character, to not loop
+                Some(found) if found.start() == 0 && found.end() > 0 => cursor + found.end(),
+                _ => cursor + 1,
+            },
+            None if self.word_chars[usize::from(text[cursor])] => {
+                (cursor..limit).find(|&i| !self.word_chars[usize::from(text[i])]).unwrap_or(limit)
+            },
+            None => cursor + 1,
+        }
+    }
+}
+
+/// Parse the `number` like `strtoimax` with the base 0, with an optional sign, and the
+/// prefixes `0x` for hexadecimal and `0` for octal.
+fn parse_number(number: &str) -> Option<i64> {
+    let number = number.trim_start_matches(|c: char| c.is_ascii_whitespace());
+    let (negative, digits) = match number.as_bytes().first() {
+        Some(b'-') => (true, &number[1..]),
+        Some(b'+') => (false, &number[1..]),
+        _ => (false, number),
+    };
+    let (radix, digits) = if digits.starts_with("0x") || digits.starts_with("0X") {
+        (16, &digits[2..])
+    } else if digits.starts_with('0') && digits.len() > 1 {
+        (8, &digits[1..])
+    } else {
+        (10, digits)
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+
+    let number = i64::from_str_radix(digits, radix).ok()?;
+    Some(if negative { -number } else { number })
+}
+
+/// Replace the escapes of the `string` by the bytes they stand for, like GNU.
+///
+/// The unknown escapes are kept, for the regular expressions, and `\c` ends the string,
+/// like a backslash at its end.
+fn unescape(string: &str) -> Vec<u8> {
+    let s = string.as_bytes();
+    let mut unescaped = Vec::with_capacity(s.len());
+
+    let mut i = 0;
+    while i < s.len() {
+        if s[i] != b'\\' {
+            unescaped.push(s[i]);
+            i += 1;
+            continue;
+        }
+
+        i += 1;
+        // The digits of the hexadecimal and octal escapes, at most 3
+        let digits = |radix: u32| {
+            let digits = s[i + 1..].iter().take(3).map(|&c| char::from(c).to_digit(radix));
+            let digits = digits.take_while(Option::is_some).flatten();
+            digits.fold((0, 0), |(len, value), digit| (len + 1, radix * value + digit))
+        };
+        let c = match s.get(i) {
+            Some(b'x') => match digits(16) {
+                (0, _) => {
+                    unescaped.extend_from_slice(b"\\x");
+                    i += 1;
+                    continue;
+                },
+                (len, value) => {
+                    i += len;
+                    value as u8
+                },
+            },
+            Some(b'0') => {
+                let (len, value) = digits(8);
+                i += len;
+                value as u8
+            },
+            Some(b'a') => b'\x07',
+            Some(b'b') => b'\x08',
+            Some(b'f') => b'\x0c',
+            Some(b'n') => b'\n',
+            Some(b'r') => b'\r',
+            Some(b't') => b'\t',
+            Some(b'v') => b'\x0b',
+            Some(b'c') | None => break,
+            Some(&c) => {
+                unescaped.extend_from_slice(&[b'\\', c]);
+                i += 1;
+                continue;
+            },
+        };
+        unescaped.push(c);
+        i += 1;
+    }
+
+    unescaped
+}
+
+/// The `text` quoted for the error messages, like GNU.
+fn quote(text: &[u8]) -> String {
+    let mut quoted = String::from("'");
+    for &c in text {
+        match c {
+            b'\x07' => quoted.push_str("\\a"),
+            b'\x08' => quoted.push_str("\\b"),
+            b'\x0c' => quoted.push_str("\\f"),
+            b'\n' => quoted.push_str("\\n"),
+            b'\r' => quoted.push_str("\\r"),
+            b'\t' => quoted.push_str("\\t"),
+            b'\x0b' => quoted.push_str("\\v"),
+            b'\\' | b'\'' => {
+                quoted.push('\\');
+                quoted.push(char::from(c));
+            },
+            b' '..=b'~' => quoted.push(char::from(c)),
+            _ => quoted.push_str(&format!("\\{:03o}", c)),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Compare the words by their bytes, folded to upper case if `ignore_case`.
+fn compare_words(word1: &[u8], word2: &[u8], ignore_case: bool) -> Ordering {
+    if ignore_case {
+        let fold = u8::to_ascii_uppercase;
+        word1.iter().map(fold).cmp(word2.iter().map(fold))
+    } else {
+        word1.cmp(word2)
+    }
+}
+
+/// Whether the byte is a space of the "C" locale, with the vertical tab.
+fn is_space(c: u8) -> bool {
+    matches!(c, b' ' | b'\t'..=b'\r')
+}
+
+/// The position after the spaces at the `cursor` of the `text`, up to the `limit`.
+fn skip_white(text: &[u8], cursor: usize, limit: usize) -> usize {
+    (cursor..limit).find(|&i| !is_space(text[i])).unwrap_or_else(|| limit.max(cursor))
+}
+
+/// The position before the spaces that end at the `cursor` of the `text`, down to the
+/// `start`.
+fn skip_white_backwards(text: &[u8], cursor: usize, start: usize) -> usize {
+    (start..cursor).rev().find(|&i| !is_space(text[i])).map_or(start.min(cursor), |i| i + 1)
+}
+
+/// The position after the characters other than spaces at the `cursor` of the `text`, up
+/// to the `limit`.
+fn skip_non_white(text: &[u8], cursor: usize, limit: usize) -> usize {
+    (cursor..limit).find(|&i| is_space(text[i])).unwrap_or_else(|| limit.max(cursor))
+}
+
+/// An input file.
+struct Input {
+    /// The name of the file, empty for standard input.
+    name: String,
+    text: Vec<u8>,
+}
+
+/// An occurrence of a keyword in its context, with positions in the text of its file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Occurrence {
+    file: usize,
+    key_start: usize,
+    key_end: usize,
+    /// The start and the end of the context.
+    left: usize,
+    right: usize,
+    /// The count of the lines before it in all the files, for the automatic references.
+    line: u64,
+    /// The start of its line, for the references of the input.
+    line_start: usize,
+}
+
+/// A part of the text, which is empty when its start is after its end.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Field {
+    start: usize,
+    end: usize,
+}
+
+impl Field {
+    fn new(start: usize, end: usize) -> Self {
+        Field { start, end }
+    }
+
+    /// The width, which is negative when the start is after the end, like GNU.
+    fn width(self) -> i64 {
+        self.end as i64 - self.start as i64
+    }
+
+    fn text(self, text: &[u8]) -> &[u8] {
+        if self.start < self.end {
+            &text[self.start..self.end]
+        } else {
+            &[]
+        }
+    }
+}
+
+/// The fields of a line of the index, where the `tail` is the end of the right context
+/// written at the left, and the `head` is the start of the left context written at the
+/// right.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Fields {
+    tail: Field,
+    before: Field,
+    keyafter: Field,
+    head: Field,
+    reference: Vec<u8>,
+    tail_truncated: bool,
+    before_truncated: bool,
+    keyafter_truncated: bool,
+    head_truncated: bool,
+}
+
+/// The permuted index of the inputs.
+struct Ptx<'a> {
+    options: &'a Options,
+    inputs: Vec<Input>,
+    /// The occurrences of the keywords, in sorted order.
+    occurrences: Vec<Occurrence>,
+    /// The count of the lines read, and at the end of each file, like GNU.
+    lines: u64,
+    file_lines: Vec<u64>,
+    /// The length of the longest word, kept or not.
+    max_word: i64,
+    reference_width: i64,
+    half_width: i64,
+    before_width: i64,
+    keyafter_width: i64,
+}
+
+impl<'a> Ptx<'a> {
+    /// Find and sort the keywords of the `inputs`.
+    ///
+    /// # Errors
+    /// If the end of the contexts matches at their start, an error message is returned.
+    fn new(options: &'a Options, inputs: Vec<Input>) -> Result<Self, String> {
+        let mut ptx = Ptx {
+            options,
+            inputs,
+            occurrences: Vec::new(),
+            lines: 0,
+            file_lines: Vec::new(),
+            max_word: 0,
+            reference_width: 0,
+            half_width: 0,
+            before_width: 0,
+            keyafter_width: 0,
+        };
+
+        for file in 0..ptx.inputs.len() {
+            ptx.find_occurrences(file)?;
+            // Like GNU, a possible incomplete last line is counted
+            ptx.lines += 1;
+            ptx.file_lines.push(ptx.lines);
+        }
+
+        let inputs = &ptx.inputs;
+        let key = |occurrence: &Occurrence| {
+            &inputs[occurrence.file].text[occurrence.key_start..occurrence.key_end]
+        };
+        ptx.occurrences
+            .sort_by(|first, second| compare_words(key(first), key(second), options.ignore_case));
+
+        ptx.compute_widths();
+        Ok(ptx)
+    }
+
+    /// Find the keywords of the input `file`, in its contexts.
+    fn find_occurrences(&mut self, file: usize) -> Result<(), String> {
+        let options = self.options;
+        let text = &self.inputs[file].text[..];
+        let end = text.len();
+
+        let mut line_start = 0;
+        let mut line_scan = 0;
+        let mut reference_len = 0;
+        if options.input_reference {
+            line_scan = skip_non_white(text, line_scan, end);
+            reference_len = line_scan;
+            line_scan = skip_white(text, line_scan, end);
+        }
+
+        let mut cursor = 0;
+        while cursor < end {
+            let mut context_start = cursor;
+            let mut next_context = end;
+            if let Some(sentence) = &options.sentence {
+                match sentence.regex.find(&text[cursor..]) {
+                    // Like GNU, a match at the start is taken as an empty one
+                    Some(found) if found.start() == 0 => {
+                        return Err(format!(
+                            "error: regular expression has a match of length zero: {}",
+                            quote(&sentence.text)
+                        ));
+                    },
+                    Some(found) => next_context = cursor + found.end(),
+                    None => {},
+                }
+            }
+            // The end of the context is in its right context, without its spaces
+            let context_end = skip_white_backwards(text, next_context, context_start);
+
+            while cursor <= context_end {
+                let (word_start, word_end) = match &options.word {
+                    Some(word) => match word.regex.find(&text[cursor..context_end]) {
+                        Some(found) => (cursor + found.start(), cursor + found.end()),
+                        None => break,
+                    },
+                    None => {
+                        let is_word = |i: usize| options.word_chars[usize::from(text[i])];
+                        let start = match (cursor..context_end).find(|&i| is_word(i)) {
+                            Some(start) => start,
+                            None => break,
+                        };
+                        (start, (start..context_end).find(|&i| !is_word(i)).unwrap_or(context_end))
+                    },
+                };
+                if word_start == word_end {
+                    cursor = word_start + 1;
+                    continue;
+                }
+                cursor = word_end;
+                // Any word may start the fields, so all of them are measured
+                self.max_word = self.max_word.max((word_end - word_start) as i64);
+
+                if options.input_reference {
+                    while line_scan < word_start {
+                        if text[line_scan] == b'\n' {
+                            self.lines += 1;
+                            line_scan += 1;
+                            line_start = line_scan;
+                            line_scan = skip_non_white(text, line_scan, end);
+                            reference_len = line_scan - line_start;
+                        } else {
+                            line_scan += 1;
+                        }
+                    }
+                    // The words of the references are not keywords
+                    if line_scan > word_start {
+                        continue;
+                    }
+                }
+
+                let key = &text[word_start..word_end];
+                if matches!(options.ignore, Some(ref words) if options.has_word(words, key))
+                    || matches!(options.only, Some(ref words) if !options.has_word(words, key))
+                {
+                    continue;
+                }
+
+                if options.auto_reference {
+                    while line_scan < word_start {
+                        if text[line_scan] == b'\n' {
+                            self.lines += 1;
+                        }
+                        line_scan += 1;
+                    }
+                } else if options.input_reference {
+                    self.reference_width = self.reference_width.max(reference_len as i64);
+                }
+
+                // The reference that starts the context is not in it
+                if options.input_reference && line_start == context_start {
+                    context_start = skip_non_white(text, context_start, context_end);
+                    context_start = skip_white(text, context_start, context_end);
+                }
+
+                self.occurrences.push(Occurrence {
+                    file,
+                    key_start: word_start,
+                    key_end: word_end,
+                    left: context_start,
+                    right: context_end,
+                    line: self.lines,
+                    line_start,
+                });
+            }
+
+            cursor = next_context;
+        }
+
+        Ok(())
+    }
+
+    /// Compute the widths of the fields, like GNU.
+    fn compute_widths(&mut self) {
+        let options = self.options;
+
+        if options.auto_reference {
+            let mut previous = 0;
+            for (input, &lines) in self.inputs.iter().zip(&self.file_lines) {
+                let width = (lines + 1 - previous).to_string().len() + input.name.len();
+                self.reference_width = self.reference_width.max(width as i64);
+                previous = lines;
+            }
+            // With the colon after the file name
+            self.reference_width += 1;
+        }
+
+        let mut width = options.width;
+        if (options.auto_reference || options.input_reference) && !options.right_reference {
+            width -= self.reference_width + options.gap;
+        }
+        width = width.max(0);
+
+        let truncation = options.truncation.len() as i64;
+        self.half_width = width / 2;
+        self.before_width = self.half_width - options.gap;
+        self.keyafter_width = self.half_width;
+        if options.traditional {
+            self.keyafter_width -= 2 * truncation + 1;
+        } else {
+            // There are at most two truncation flags on either side of the gap
+            self.before_width = (self.before_width - 2 * truncation).max(0);
+            self.keyafter_width -= 2 * truncation;
+        }
+    }
+
+    /// The fields of the line of the `occurrence`, like GNU.
+    fn fields(&self, occurrence: &Occurrence) -> Fields {
+        let options = self.options;
+        let text = &self.inputs[occurrence.file].text[..];
+        let truncating = !options.truncation.is_empty();
+        let key_start = occurrence.key_start;
+        let (left_context, right_context) = (occurrence.left, occurrence.right);
+        let mut fields = Fields::default();
+
+        // The keyword and what follows it, with whole words
+        let keyafter_limit = key_start as i64 + self.keyafter_width;
+        let mut keyafter_end = occurrence.key_end;
+        let mut cursor = keyafter_end;
+        while cursor < right_context && cursor as i64 <= keyafter_limit {
+            keyafter_end = cursor;
+            cursor = options.skip_something(text, cursor, right_context);
+        }
+        if cursor as i64 <= keyafter_limit {
+            keyafter_end = cursor;
+        }
+        fields.keyafter_truncated = truncating && keyafter_end < right_context;
+        fields.keyafter =
+            Field::new(key_start, skip_white_backwards(text, keyafter_end, key_start));
+
+        // A long left context is only read from a safe distance before the keyword
+        let distance = self.half_width + self.max_word;
+        let left_start = if (key_start - left_context) as i64 > distance {
+            options.skip_something(text, key_start - distance as usize, key_start)
+        } else {
+            left_context
+        };
+
+        // What precedes the keyword, with whole words
+        let before_end = skip_white_backwards(text, key_start, left_start);
+        let mut before_start = left_start;
+        while before_start as i64 + self.before_width < before_end as i64 {
+            before_start = options.skip_something(text, before_start, before_end);
+        }
+        fields.before_truncated =
+            truncating && skip_white_backwards(text, before_start, 0) > left_context;
+        fields.before = Field::new(skip_white(text, before_start, text.len()), before_end);
+
+        // The rest of the right context, in the room left at the left
+        let tail_width = self.before_width - fields.before.width() - options.gap;
+        if tail_width > 0 {
+            let tail_start = skip_white(text, fields.keyafter.end, text.len());
+            let tail_limit = tail_start as i64 + tail_width;
+            let mut tail_end = tail_start;
+            let mut cursor = tail_end;
+            while cursor < right_context && (cursor as i64) < tail_limit {
+                tail_end = cursor;
+                cursor = options.skip_something(text, cursor, right_context);
+            }
+            if (cursor as i64) < tail_limit {
+                tail_end = cursor;
+            }
+
+            if tail_end > tail_start {
+                fields.keyafter_truncated = false;
+                fields.tail_truncated = truncating && tail_end < right_context;
+            }
+            fields.tail = Field::new(tail_start, skip_white_backwards(text, tail_end, tail_start));
+        }
+
+        // The rest of the left context, in the room left at the right
+        let head_width = self.keyafter_width - fields.keyafter.width() - options.gap;
+        if head_width > 0 {
+            let head_end = skip_white_backwards(text, fields.before.start, 0);
+            let mut head_start = left_start;
+            while head_start as i64 + head_width < head_end as i64 {
+                head_start = options.skip_something(text, head_start, head_end);
+            }
+
+            if head_end > head_start {
+                fields.before_truncated = false;
+                fields.head_truncated = truncating && head_start > left_context;
+            }
+            fields.head = Field::new(skip_white(text, head_start, head_end), head_end);
+        }
+
+        if options.auto_reference {
+            let previous =
+                if occurrence.file > 0 { self.file_lines[occurrence.file - 1] } else { 0 };
+            let name = &self.inputs[occurrence.file].name;
+            fields.reference = format!("{}:{}", name, occurrence.line + 1 - previous).into_bytes();
+        } else if options.input_reference {
+            let start = occurrence.line_start;
+            fields.reference = text[start..skip_non_white(text, start, right_context)].to_vec();
+        }
+
+        fields
+    }
+
+    /// Write the lines of the index to the `output`.
+    fn write<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        for occurrence in &self.occurrences {
+            let fields = self.fields(occurrence);
+            let text = &self.inputs[occurrence.file].text[..];
+            match self.options.format {
+                Format::Dumb => self.write_dumb(output, &fields, text)?,
+                Format::Roff => self.write_roff(output, &fields, text)?,
+                Format::Tex => self.write_tex(output, &fields, text)?,
+            }
+        }
+
+        output.flush()
+    }
+
+    /// Write the line of the `fields` in aligned columns.
+    fn write_dumb<W: Write>(&self, output: &mut W, fields: &Fields, text: &[u8]) -> io::Result<()> {
+        let options = self.options;
+        let gap = options.gap;
+        let truncation = &options.truncation[..];
+        let flag_width = |truncated: bool| if truncated { truncation.len() as i64 } else { 0 };
+        let flag = |output: &mut W, truncated: bool| {
+            output.write_all(if truncated { truncation } else { b"" })
+        };
+        let spaces = |output: &mut W, count: i64| {
+            output.write_all(" ".repeat(count.max(0) as usize).as_bytes())
+        };
+        let referenced = options.auto_reference || options.input_reference;
+        let reference_width = fields.reference.len() as i64;
+
+        if !options.right_reference {
+            self.write_field(output, &fields.reference)?;
+            if options.auto_reference {
+                // The colon after the line number is taken from the gap
+                output.write_all(b":")?;
+                spaces(output, self.reference_width + gap - reference_width - 1)?;
+            } else {
+                spaces(output, self.reference_width + gap - reference_width)?;
+            }
+        }
+
+        let before_width = fields.before.width() + flag_width(fields.before_truncated);
+        if fields.tail.start < fields.tail.end {
+            self.write_field(output, fields.tail.text(text))?;
+            flag(output, fields.tail_truncated)?;
+            let tail_width = fields.tail.width() + flag_width(fields.tail_truncated);
+            spaces(output, self.half_width - gap - before_width - tail_width)?;
+        } else {
+            spaces(output, self.half_width - gap - before_width)?;
+        }
+
+        flag(output, fields.before_truncated)?;
+        self.write_field(output, fields.before.text(text))?;
+        spaces(output, gap)?;
+
+        self.write_field(output, fields.keyafter.text(text))?;
+        flag(output, fields.keyafter_truncated)?;
+
+        let keyafter_width = fields.keyafter.width() + flag_width(fields.keyafter_truncated);
+        if fields.head.start < fields.head.end {
+            let head_width = fields.head.width() + flag_width(fields.head_truncated);
+            spaces(output, self.half_width - keyafter_width - head_width)?;
+            flag(output, fields.head_truncated)?;
+            self.write_field(output, fields.head.text(text))?;
+        } else if referenced && options.right_reference {
+            spaces(output, self.half_width - keyafter_width)?;
+        }
+
+        if referenced && options.right_reference {
+            spaces(output, gap)?;
+            self.write_field(output, &fields.reference)?;
+        }
+        output.write_all(b"\n")
+    }
+
+    /// Write the line of the `fields` as a roff directive.
+    fn write_roff<W: Write>(&self, output: &mut W, fields: &Fields, text: &[u8]) -> io::Result<()> {
+        let options = self.options;
+        let truncation = &options.truncation[..];
+        let flag = |truncated: bool| if truncated { truncation } else { b"" };
+
+        write!(output, ".{} \"", options.macro_name)?;
+        self.write_field(output, fields.tail.text(text))?;
+        output.write_all(flag(fields.tail_truncated))?;
+
+        output.write_all(b"\" \"")?;
+        output.write_all(flag(fields.before_truncated))?;
+        self.write_field(output, fields.before.text(text))?;
+
+        output.write_all(b"\" \"")?;
+        self.write_field(output, fields.keyafter.text(text))?;
+        output.write_all(flag(fields.keyafter_truncated))?;
+
+        output.write_all(b"\" \"")?;
+        output.write_all(flag(fields.head_truncated))?;
+        self.write_field(output, fields.head.text(text))?;
+        output.write_all(b"\"")?;
+
+        if options.auto_reference || options.input_reference {
+            output.write_all(b" \"")?;
+            self.write_field(output, &fields.reference)?;
+            output.write_all(b"\"")?;
+        }
+        output.write_all(b"\n")
+    }
+
+    /// Write the line of the `fields` as a TeX directive, where the keyword is apart from
+    /// what follows it, and the truncations are not flagged.
+    fn write_tex<W: Write>(&self, output: &mut W, fields: &Fields, text: &[u8]) -> io::Result<()> {
+        let options = self.options;
+        let keyafter = fields.keyafter;
+        let key_end = options.skip_something(text, keyafter.start, keyafter.end);
+
+        write!(output, "\\{} {{", options.macro_name)?;
+        self.write_field(output, fields.tail.text(text))?;
+        output.write_all(b"}{")?;
+        self.write_field(output, fields.before.text(text))?;
+        output.write_all(b"}{")?;
+        self.write_field(output, Field::new(keyafter.start, key_end).text(text))?;
+        output.write_all(b"}{")?;
+        self.write_field(output, Field::new(key_end, keyafter.end).text(text))?;
+        output.write_all(b"}{")?;
+        self.write_field(output, fields.head.text(text))?;
+        output.write_all(b"}")?;
+
+        if options.auto_reference || options.input_reference {
+            output.write_all(b"{")?;
+            self.write_field(output, &fields.reference)?;
+            output.write_all(b"}")?;
+        }
+        output.write_all(b"\n")
+    }
+
+    /// Write the `text` of a field, with its spaces as single spaces, and the characters
+    /// special to the format escaped.
+    fn write_field<W: Write>(&self, output: &mut W, text: &[u8]) -> io::Result<()> {
+        let format = self.options.format;
+        for &c in text {
+            match c {
+                b'"' if format == Format::Roff => output.write_all(b"\"\"")?,
+                b'$' | b'%' | b'&' | b'#' | b'_' if format == Format::Tex => {
+                    output.write_all(&[b'\\', c])?
+                },
+                b'{' | b'}' if format == Format::Tex => write!(output, "$\\{}$", char::from(c))?,
+                b'\\' if format == Format::Tex => output.write_all(b"\\backslash{}")?,
+                c if is_space(c) => output.write_all(b" ")?,
+                _ => output.write_all(&[c])?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ptx_with(options: &Options, input: &str) -> Result<String, String> {
+        let inputs = vec![Input { name: "t1".to_string(), text: input.as_bytes().to_vec() }];
+        let ptx = Ptx::new(options, inputs)?;
+
+        let mut output = Vec::new();
+        ptx.write(&mut output).unwrap();
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    fn options(args: &[&str]) -> Result<Options, String> {
+        let matches = cli::create_app().get_matches_from(["ptx"].iter().chain(args));
+        Options::from_matches(&matches)
+    }
+
+    fn ptx(args: &[&str], input: &str) -> Result<String, String> {
+        ptx_with(&options(args)?, input)
+    }
+
+    const TEXT: &str = "The cat sat. A dog ran!\n";
+
+    #[test]
+    fn permute() {
+        assert_eq!(
+            ptx(&["-w", "30"], TEXT).unwrap(),
+            "      /cat sat.   A dog ran!\n   A dog/         The cat sat.\n   dog/     The   cat \
+             sat. A\n    /cat sat. A   dog ran!\n     sat. A dog   ran!       /cat\n        The \
+             cat   sat. A dog/\n"
+        );
+        assert_eq!(
+            ptx(&["-w", "20"], "one two three four five six seven\n").unwrap(),
+            "     /four   five six/\n    /three   four/\n             one two/\n      /six   \
+             seven\n     /five   six/\n      /two   three/\n       one   two/\n"
+        );
+        assert_eq!(
+            ptx(&["-w", "30", "-f"], TEXT).unwrap().lines().last(),
+            Some("   A dog/         The cat sat.")
+        );
+        assert_eq!(
+            ptx(&["-G", "-w", "30", "-F", ""], "the cat\nthe dog\n").unwrap(),
+            ".xx \"\" \"the\" \"cat\" \"\"\n.xx \"\" \"the\" \"dog\" \"\"\n.xx \"\" \"\" \"the \
+             cat\" \"\"\n.xx \"\" \"\" \"the dog\" \"\"\n"
+        );
+        assert_eq!(ptx(&[], "").unwrap(), "");
+    }
+
+    #[test]
+    fn words() {
+        let mut options = options(&["-w", "30", "-f"]).unwrap();
+        options.ignore = Some(vec![b"a".to_vec(), b"the".to_vec()]);
+        assert_eq!(
+            ptx_with(&options, TEXT).unwrap(),
+            "   dog/     The   cat sat. A\n    /cat sat. A   dog ran!\n     sat. A dog   \
+             ran!       /cat\n        The cat   sat. A dog/\n"
+        );
+
+        options.ignore = None;
+        options.only = Some(vec![b"DOG".to_vec()]);
+        assert_eq!(ptx_with(&options, TEXT).unwrap(), "    /cat sat. A   dog ran!\n");
+
+        assert_eq!(
+            ptx(&["-W", "a\\w", "-w", "30"], "the bat\n").unwrap(),
+            "          the b   at\n"
+        );
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(
+            ptx(&["-O", "-w", "30"], TEXT).unwrap().lines().next(),
+            Some(".xx \"\" \"/cat sat.\" \"A dog ran!\" \"\"")
+        );
+        assert_eq!(
+            ptx(&["-T", "-F", "", "-w", "30", "-M", "idx"], TEXT).unwrap().lines().nth(4),
+            Some("\\idx {}{sat. A dog}{ran}{!}{cat}")
+        );
+        assert_eq!(
+            ptx(&["-T"], "a $_{x}\\ \"q\"\n").unwrap().lines().next(),
+            Some("\\xx {}{}{a}{ \\$\\_$\\{$x$\\}$\\backslash{} \"q\"}{}")
+        );
+        assert_eq!(
+            ptx(&["-O", "--format=tex", "-O"], "\"q\"\n").unwrap(),
+            ".xx \"\" \"\"\"\" \"q\"\"\" \"\"\n"
+        );
+    }
+
+    #[test]
+    fn references() {
+        assert_eq!(
+            ptx(&["-A", "-w", "40"], TEXT).unwrap().lines().nth(1),
+            Some("t1:1:  dog ran/        The cat sat. A")
+        );
+        assert_eq!(
+            ptx(&["-r", "-R", "-w", "30"], "r1 the cat\nr2 a dog\n").unwrap(),
+            "               a dog             r2\n         the   cat               r1\n           \
+             a   dog               r2\n               the cat           r1\n"
+        );
+        assert_eq!(
+            ptx(&["-A", "-O"], "x\n\ny").unwrap(),
+            ".xx \"\" \"\" \"x  y\" \"\" \"t1:1\"\n.xx \"\" \"x\" \"y\" \"\" \"t1:3\"\n"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = |args: &[&str]| ptx(args, "-a").unwrap_err();
+        assert_eq!(error(&["-w", "0"]), "invalid line width: '0'");
+        assert_eq!(error(&["-w", "2x"]), "invalid line width: '2x'");
+        assert_eq!(error(&["-g", "-1"]), "invalid gap width: '-1'");
+        assert_eq!(error(&["-W", "\\("]), "Unmatched ( or \\( (for regexp '\\\\(')");
+        assert_eq!(
+            error(&["-S", "-\\|"]),
+            "error: regular expression has a match of length zero: '-\\\\|'"
+        );
+    }
+
+    #[test]
+    fn arguments() {
+        assert_eq!(parse_number("0x10"), Some(16));
+        assert_eq!(parse_number(" +010"), Some(8));
+        assert_eq!(parse_number("-3"), Some(-3));
+        assert_eq!(parse_number("08"), None);
+        assert_eq!(parse_number(""), None);
+        assert_eq!(unescape("a\\tb\\x41\\0101\\q"), b"a\tbAA\\q");
+        assert_eq!(unescape("\\xg\\cz"), b"\\xg");
+        assert_eq!(unescape("a\\"), b"a");
+        assert_eq!(quote(b"a\n'\\\x01"), "'a\\n\\'\\\\\\001'");
+    }
+}
@@ -0,0 +1,105 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("Files to display.")
+                .long_help(
+                    "Files to display.\n\nWithout the canonicalize options, the files that are \
+                     not symbolic links fail.",
+                )
+                .required(true)
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("canonicalize")
+                .help(
+                    "Display the canonical name, following all the symbolic links. All but the \
+                     last component must exist.",
+                )
+                .long_help(
+                    "Display the canonical name, following all the symbolic links. All but the \
+                     last component must exist.\n\nThe canonical name is the absolute name \
+                     without symbolic links, '.' or '..' components. A name that ends with '/' \
+                     has to be a directory when it exists.",
+                )
+                .long("canonicalize")
+                .short("f")
+                .overrides_with_all(&["canonicalizeExisting", "canonicalizeMissing"]),
+        )
+        .arg(
+            Arg::with_name("canonicalizeExisting")
+                .help(
+                    "Display the canonical name, following all the symbolic links. All the \
+                     components must exist.",
+                )
+                .long("canonicalize-existing")
+                .short("e")
+                .overrides_with_all(&["canonicalize", "canonicalizeMissing"]),
+        )
+        .arg(
+            Arg::with_name("canonicalizeMissing")
+                .help(
+                    "Display the canonical name, following all the symbolic links. Any component \
+                     can be missing.",
+                )
+                .long_help(
+                    "Display the canonical name, following all the symbolic links. Any component \
+                     can be missing.\n\nThe components after the first missing one are added as \
+                     they are, with '..' removing the previous one.",
+                )
+                .long("canonicalize-missing")
+                .short("m")
+                .overrides_with_all(&["canonicalize", "canonicalizeExisting"]),
+        )
+        .arg(
+            Arg::with_name("noNewline")
+                .help("Do not output the delimiter after the name.")
+                .long_help(
+                    "Do not output the delimiter after the name.\n\nIt is ignored with more than \
+                     one FILE.",
+                )
+                .long("no-newline")
+                .short("n"),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .help("Do not print most of the error messages. (default)")
+                .long("quiet")
+                .short("q")
+                .overrides_with_all(&["silent", "verbose"]),
+        )
+        .arg(
+            Arg::with_name("silent")
+                .help("Same as --quiet.")
+                .long("silent")
+                .short("s")
+                .overrides_with_all(&["quiet", "verbose"]),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .help("Print the error messages.")
+                .long("verbose")
+                .short("v")
+                .overrides_with_all(&["quiet", "silent"]),
+        )
+        .arg(
+            Arg::with_name("zero")
+                .help(
+                    "Output a zero byte (ASCII NUL) at the end of each name, rather than a \
+                     newline.",
+                )
+                .long("zero")
+                .short("z"),
+        )
+}
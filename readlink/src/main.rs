@@ -0,0 +1,111 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+use coreutils_core::{
+    canonicalize::{canonicalize, Missing},
+    ByteVec,
+};
+
+mod cli;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    // FILE is required, so it's ok to unwrap
+    let files: Vec<_> = matches.values_of_os("FILE").unwrap().collect();
+
+    // The last of the canonicalize options applies, even in a cluster with other flags
+    let missing = [
+        ("canonicalize", Missing::Last),
+        ("canonicalizeExisting", Missing::None),
+        ("canonicalizeMissing", Missing::Any),
+    ]
+    .iter()
+    .filter_map(|&(name, missing)| matches.index_of(name).map(|index| (index, missing)))
+    .max_by_key(|&(index, _)| index)
+    .map(|(_, missing)| missing);
+
+    let mut no_newline = matches.is_present("noNewline");
+    if no_newline && files.len() > 1 {
+        eprintln!("readlink: ignoring --no-newline with multiple arguments");
+        no_newline = false;
+    }
+
+    let delimiter: &[u8] = if no_newline {
+        b""
+    } else if matches.is_present("zero") {
+        b"\0"
+    } else {
+        b"\n"
+    };
+
+    let verbose = match matches.index_of("verbose") {
+        Some(verbose) => ["quiet", "silent"]
+            .iter()
+            .all(|&name| matches.index_of(name).map(|i| i < verbose).unwrap_or(true)),
+        None => false,
+    };
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut success = true;
+
+    for file in files {
+        let path = Path::new(file);
+
+        let name = match read(path, missing) {
+            Ok(name) => name,
+            Err(err) => {
+                if verbose {
+                    eprintln!("readlink: {}: {}", path.display(), err);
+                }
+                success = false;
+                continue;
+            },
+        };
+
+        let written = stdout
+            .write_all(&Vec::from_path_lossy(&name))
+            .and_then(|_| stdout.write_all(delimiter));
+
+        if let Err(err) = written {
+            eprintln!("readlink: write error: {}", err);
+            process::exit(1);
+        }
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// Read the target of the symbolic link at `path`, or its canonical name with the
+/// components that can be `missing` when they are given.
+fn read(path: &Path, missing: Option<Missing>) -> io::Result<PathBuf> {
+    match missing {
+        Some(missing) => canonicalize(path, missing),
+        None => fs::read_link(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes() {
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+
+        assert!(read(&src.join("main.rs"), None).is_err());
+        assert_eq!(read(&src.join("../src"), Some(Missing::None)).unwrap(), src);
+        assert!(read(&src.join("nowhere/file"), Some(Missing::Last)).is_err());
+        assert_eq!(
+            read(&src.join("nowhere/../file"), Some(Missing::Any)).unwrap(),
+            src.join("file")
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("Files to resolve.")
+                .long_help(
+                    "Files to resolve.\n\nThe resolved names are absolute, without symbolic \
+                     links, '.' or '..' components. All but the last component must exist by \
+                     default, and a name that ends with '/' has to be a directory when it exists.",
+                )
+                .required(true)
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("canonicalizeExisting")
+                .help("All the components must exist.")
+                .long("canonicalize-existing")
+                .short("e")
+                .overrides_with("canonicalizeMissing"),
+        )
+        .arg(
+            Arg::with_name("canonicalizeMissing")
+                .help("Any component can be missing.")
+                .long_help(
+                    "Any component can be missing.\n\nThe components after the first missing one \
+                     are added as they are, with '..' removing the previous one.",
+                )
+                .long("canonicalize-missing")
+                .short("m")
+                .overrides_with("canonicalizeExisting"),
+        )
+        .arg(
+            Arg::with_name("logical")
+                .help("Resolve the '..' components before the symbolic links.")
+                .long("logical")
+                .short("L")
+                .overrides_with_all(&["physical", "strip"]),
+        )
+        .arg(
+            Arg::with_name("physical")
+                .help("Resolve the symbolic links as they are found. (default)")
+                .long("physical")
+                .short("P")
+                .overrides_with_all(&["logical", "strip"]),
+        )
+        .arg(
+            Arg::with_name("strip")
+                .help("Do not resolve the symbolic links, only the '.' and '..' components.")
+                .long_help(
+                    "Do not resolve the symbolic links, only the '.' and '..' components.\n\nOnly \
+                     the whole resolved name is checked to exist, but what is before a '..' \
+                     component has to be a directory unless any component can be missing.",
+                )
+                .long("strip")
+                .visible_alias("no-symlinks")
+                .short("s")
+                .overrides_with_all(&["logical", "physical"]),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .help("Do not print most of the error messages.")
+                .long("quiet")
+                .short("q"),
+        )
+        .arg(
+            Arg::with_name("relativeTo")
+                .help("Display the resolved names relative to DIR.")
+                .long_help(
+                    "Display the resolved names relative to DIR.\n\nDIR is resolved like the \
+                     files. With --relative-base that is not a parent of DIR, the names are \
+                     absolute.",
+                )
+                .long("relative-to")
+                .value_name("DIR")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("relativeBase")
+                .help("Display the resolved names under DIR as relative, and the others absolute.")
+                .long_help(
+                    "Display the resolved names under DIR as relative, and the others \
+                     absolute.\n\nThe names are relative to DIR, or to the one of --relative-to.",
+                )
+                .long("relative-base")
+                .value_name("DIR")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("zero")
+                .help(
+                    "Output a zero byte (ASCII NUL) at the end of each name, rather than a \
+                     newline.",
+                )
+                .long("zero")
+                .short("z"),
+        )
+}
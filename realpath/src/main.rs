@@ -0,0 +1,168 @@
+use std::{
+    ffi::OsStr,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+use clap::ArgMatches;
+use coreutils_core::{
+    canonicalize::{canonicalize, normalize, relative_path, Missing},
+    ByteVec,
+};
+
+mod cli;
+
+/// How the symbolic links are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Links {
+    /// Resolve them as they are found.
+    Physical,
+    /// Resolve them after the `..` components.
+    Logical,
+    /// Keep them.
+    Strip,
+}
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let missing = last(&matches, &[
+        ("canonicalizeExisting", Missing::None),
+        ("canonicalizeMissing", Missing::Any),
+    ])
+    .unwrap_or(Missing::Last);
+
+    let links = last(&matches, &[
+        ("logical", Links::Logical),
+        ("physical", Links::Physical),
+        ("strip", Links::Strip),
+    ])
+    .unwrap_or(Links::Physical);
+
+    let resolve_dir = |dir: &OsStr| {
+        let dir = Path::new(dir);
+
+        // The directories have to be directories only when everything has to exist
+        // `io::Error::other` is newer than the MSRV
+        #[allow(clippy::io_other_error)]
+        let resolved = resolve(dir, missing, links).and_then(|resolved| {
+            if missing == Missing::None && !resolved.is_dir() {
+                Err(io::Error::new(io::ErrorKind::Other, "Not a directory"))
+            } else {
+                Ok(resolved)
+            }
+        });
+
+        resolved.unwrap_or_else(|err| {
+            eprintln!("realpath: {}: {}", dir.display(), err);
+            process::exit(1);
+        })
+    };
+
+    let base = matches.value_of_os("relativeBase").map(resolve_dir);
+    let mut to = matches.value_of_os("relativeTo").map(resolve_dir).or_else(|| base.clone());
+
+    // The names are absolute when the base is not a parent of the directory
+    if let (Some(dir), Some(base)) = (&to, &base) {
+        if !dir.starts_with(base) {
+            to = None;
+        }
+    }
+
+    let quiet = matches.is_present("quiet");
+    let delimiter: &[u8] = if matches.is_present("zero") { b"\0" } else { b"\n" };
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut success = true;
+
+    // FILE is required, so it's ok to unwrap
+    for file in matches.values_of_os("FILE").unwrap() {
+        let path = Path::new(file);
+
+        let name = match resolve(path, missing, links) {
+            Ok(name) => relative(name, to.as_deref(), base.as_deref()),
+            Err(err) => {
+                if !quiet {
+                    eprintln!("realpath: {}: {}", path.display(), err);
+                }
+                success = false;
+                continue;
+            },
+        };
+
+        let written = stdout
+            .write_all(&Vec::from_path_lossy(&name))
+            .and_then(|_| stdout.write_all(delimiter));
+
+        if let Err(err) = written {
+            eprintln!("realpath: write error: {}", err);
+            process::exit(1);
+        }
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// Get the value of the last of the `options` given in the `matches`, even in a cluster
+/// with other flags.
+fn last<T: Copy>(matches: &ArgMatches, options: &[(&str, T)]) -> Option<T> {
+    options
+        .iter()
+        .filter_map(|&(name, value)| matches.index_of(name).map(|index| (index, value)))
+        .max_by_key(|&(index, _)| index)
+        .map(|(_, value)| value)
+}
+
+/// Resolve the `path` with the components that can be `missing`, resolving the symbolic
+/// `links` as asked.
+fn resolve(path: &Path, missing: Missing, links: Links) -> io::Result<PathBuf> {
+    match links {
+        Links::Physical => canonicalize(path, missing),
+        Links::Logical => canonicalize(normalize(path, missing)?, missing),
+        Links::Strip => normalize(path, missing),
+    }
+}
+
+/// Get the resolved `name` relative to the directory `to`, if there is one and the `name`
+/// is under the `base` when there is one.
+fn relative(name: PathBuf, to: Option<&Path>, base: Option<&Path>) -> PathBuf {
+    match to {
+        Some(to) if base.map(|base| name.starts_with(base)).unwrap_or(true) => {
+            relative_path(name, to)
+        },
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_names() {
+        let name = PathBuf::from("/a/b/c");
+
+        assert_eq!(relative(name.clone(), None, None), name);
+        assert_eq!(relative(name.clone(), Some(Path::new("/a/x")), None), Path::new("../b/c"));
+        assert_eq!(relative(name.clone(), Some(Path::new("/x/y")), Some(Path::new("/x"))), name);
+        assert_eq!(
+            relative(name.clone(), Some(Path::new("/a/b")), Some(Path::new("/a"))),
+            Path::new("c")
+        );
+    }
+
+    #[test]
+    fn links() {
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let missing = src.join("nowhere/file");
+
+        assert!(resolve(&missing, Missing::Last, Links::Physical).is_err());
+        assert!(resolve(&missing, Missing::Last, Links::Logical).is_err());
+        assert_eq!(resolve(&missing, Missing::Last, Links::Strip).unwrap(), missing);
+        assert_eq!(resolve(&src.join("../src/."), Missing::None, Links::Logical).unwrap(), src);
+    }
+}
@@ -0,0 +1,31 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("File(s) to use, or '-' to use from standard input.")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("bytes")
+                .help("Reverse the bytes of the lines, instead of their characters.")
+                .long_help(
+                    "Reverse the bytes of the lines, instead of their characters.\n\nBy default, \
+                     the lines are read as UTF-8, where a character is kept together with the \
+                     combining marks and joiners that follow it, and the bytes that are not valid \
+                     are reversed alone.",
+                )
+                .long("bytes")
+                .short("b"),
+        )
+}
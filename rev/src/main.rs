@@ -0,0 +1,207 @@
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::{self, stdin, stdout, BufRead, BufReader, BufWriter, Write},
+    ops::Range,
+    process, str,
+};
+
+use unicode_width::UnicodeWidthChar;
+
+mod cli;
+
+/// The zero width joiner, that joins the characters around it.
+const ZERO_WIDTH_JOINER: char = '\u{200d}';
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let bytes = matches.is_present("bytes");
+
+    let files: Vec<&OsStr> = match matches.values_of_os("FILE") {
+        Some(files) => files.collect(),
+        None => vec![OsStr::new("-")],
+    };
+
+    let stdout = stdout();
+    let mut output = BufWriter::new(stdout.lock());
+    let mut success = true;
+
+    for file in files {
+        let name = file.to_string_lossy();
+
+        let result = if file == "-" {
+            rev(stdin().lock(), &mut output, bytes)
+        } else {
+            match File::open(file) {
+                Ok(opened) => rev(BufReader::new(opened), &mut output, bytes),
+                Err(err) => {
+                    eprintln!("rev: cannot open {}: {}", name, err);
+                    success = false;
+                    continue;
+                },
+            }
+        };
+
+        match result {
+            Ok(()) => {},
+            Err(Error::Read(err)) => {
+                eprintln!("rev: {}: {}", name, err);
+                success = false;
+            },
+            Err(Error::Write(err)) => {
+                eprintln!("rev: write error: {}", err);
+                process::exit(1);
+            },
+        }
+    }
+
+    if let Err(err) = output.flush() {
+        eprintln!("rev: write error: {}", err);
+        process::exit(1);
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+#[derive(Debug)]
+enum Error {
+    Read(io::Error),
+    Write(io::Error),
+}
+
+/// Write the lines of the `input` to the `output`, each one reversed, by bytes if
+/// `bytes` is true.
+fn rev<R: BufRead, W: Write>(mut input: R, output: &mut W, bytes: bool) -> Result<(), Error> {
+    let mut line = Vec::new();
+    let mut reversed = Vec::new();
+
+    loop {
+        line.clear();
+        if input.read_until(b'\n', &mut line).map_err(Error::Read)? == 0 {
+            return Ok(());
+        }
+
+        let content = line.strip_suffix(b"\n").unwrap_or(&line);
+
+        reversed.clear();
+        if bytes {
+            reversed.extend(content.iter().rev());
+        } else {
+            for cluster in clusters(content).into_iter().rev() {
+                reversed.extend_from_slice(&content[cluster]);
+            }
+        }
+        reversed.extend_from_slice(&line[content.len()..]);
+
+        output.write_all(&reversed).map_err(Error::Write)?;
+    }
+}
+
+/// The ranges of the characters of the UTF-8 `text`, with the combining marks, the
+/// modifiers and the joined characters that follow them, and of its invalid bytes.
+fn clusters(text: &[u8]) -> Vec<Range<usize>> {
+    let mut clusters: Vec<Range<usize>> = Vec::new();
+    let mut start = 0;
+    // Whether the last character joins the next one to it
+    let mut joining = false;
+    // Whether the last character is a regional indicator not paired in a flag yet
+    let mut regional = false;
+
+    while start < text.len() {
+        let (valid, invalid) = match str::from_utf8(&text[start..]) {
+            Ok(valid) => (valid, 0),
+            Err(err) => {
+                let valid = str::from_utf8(&text[start..start + err.valid_up_to()]).unwrap();
+                (valid, err.error_len().unwrap_or(text.len() - start - err.valid_up_to()))
+            },
+        };
+
+        for (i, c) in valid.char_indices() {
+            let range = start + i..start + i + c.len_utf8();
+            let is_regional = ('\u{1f1e6}'..='\u{1f1ff}').contains(&c);
+
+            let attached = joining
+                || (regional && is_regional)
+                || (c.width() == Some(0) && !c.is_control())
+                || ('\u{1f3fb}'..='\u{1f3ff}').contains(&c);
+
+            match clusters.last_mut() {
+                Some(last) if attached => last.end = range.end,
+                _ => clusters.push(range),
+            }
+
+            regional = is_regional && !(regional && attached);
+            joining = c == ZERO_WIDTH_JOINER;
+        }
+
+        start += valid.len();
+
+        // The invalid bytes are reversed alone
+        for byte in start..start + invalid {
+            clusters.push(byte..byte + 1);
+        }
+
+        start += invalid;
+        joining = false;
+        regional = false;
+    }
+
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reverse(text: &[u8], bytes: bool) -> Vec<u8> {
+        let mut output = Vec::new();
+        rev(text, &mut output, bytes).unwrap();
+
+        output
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(reverse(b"abc\ndef\n", false), b"cba\nfed\n");
+        assert_eq!(reverse(b"abc\n\nde", false), b"cba\n\ned");
+        assert_eq!(reverse(b"a\0b\r\n", false), b"\rb\0a\n");
+        assert_eq!(reverse(b"", false), b"");
+    }
+
+    #[test]
+    fn characters() {
+        assert_eq!(reverse("déjà vu\n".as_bytes(), false), "uv àjéd\n".as_bytes());
+        assert_eq!(reverse("日本語".as_bytes(), false), "語本日".as_bytes());
+        assert_eq!(reverse("ae\u{301}o".as_bytes(), false), "oe\u{301}a".as_bytes());
+        assert_eq!(
+            reverse("a\u{1f469}\u{200d}\u{1f4bb}b".as_bytes(), false),
+            "b\u{1f469}\u{200d}\u{1f4bb}a".as_bytes()
+        );
+        assert_eq!(
+            reverse("a\u{1f44d}\u{1f3fd}".as_bytes(), false),
+            "\u{1f44d}\u{1f3fd}a".as_bytes()
+        );
+        assert_eq!(
+            reverse("\u{1f1eb}\u{1f1f7}\u{1f1ea}\u{1f1f8}\u{1f1e9}".as_bytes(), false),
+            "\u{1f1e9}\u{1f1ea}\u{1f1f8}\u{1f1eb}\u{1f1f7}".as_bytes()
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(reverse(b"a\xffb\xc3\n", false), b"\xc3b\xffa\n");
+        assert_eq!(
+            reverse(b"\xe6\x97a\xc3\xa9", false),
+            "éa".bytes().chain(vec![0x97, 0xe6]).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!(reverse("ab\u{e9}\n".as_bytes(), true), b"\xa9\xc3ba\n");
+        assert_eq!(reverse(b"a\xffb", true), b"b\xffa");
+    }
+}
@@ -18,7 +18,6 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                     "File(s) to be deleted.\n\nTo remove a file whose name starts with a '-', for \
                      example '-foo', use 'rm -- -foo'",
                 )
-                .required(true)
                 .multiple(true),
         )
         .arg(
@@ -69,10 +68,27 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
         .arg(
             Arg::with_name("preserveRoot")
                 .help("Do not remove '/'. (default)")
+                .long_help(
+                    "Do not remove '/'. (default)\n\nWith 'all', also skip the FILE arguments \
+                     that are on a different device than their parent directory.",
+                )
                 .long("preserve-root")
                 .short("p")
+                .value_name("all")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&["all"])
                 .conflicts_with("noPreserveRoot"),
         )
+        .arg(
+            Arg::with_name("oneFileSystem")
+                .help(
+                    "When removing recursively, skip the directories on a different file system \
+                     than their FILE argument.",
+                )
+                .long("one-file-system"),
+        )
         .arg(
             Arg::with_name("recursive")
                 .help("Remove directories and their contents recursively.")
@@ -101,6 +117,23 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .long("recursive-compat")
                 .short("R"),
         )
+        .arg(
+            Arg::with_name("threads")
+                .help("Remove the subdirectories with N threads at the same time.")
+                .long_help(
+                    "Remove the subdirectories with N threads at the same time, when removing \
+                     recursively. The messages are printed in the same order as without \
+                     it.\n\nThe directories are still removed one at a time with -i, or when the \
+                     write-protected files may be asked about.",
+                )
+                .long("threads")
+                .value_name("N")
+                .takes_value(true)
+                .validator(|threads| match threads.parse::<usize>() {
+                    Ok(threads) if threads > 0 => Ok(()),
+                    _ => Err(format!("invalid number of threads: '{}'", threads)),
+                }),
+        )
         .arg(Arg::with_name("directories").help("Remove empty directories.").long("dir").short("d"))
         .arg(
             Arg::with_name("verbose")
@@ -1,68 +1,70 @@
-use std::{
-    env::current_dir,
-    fs::{self, FileType, Permissions},
-    io,
-    path::{Path, PathBuf},
-    process,
-};
+use std::{path::Path, process};
 
 use clap::ArgMatches;
 use coreutils_core::input::*;
 
 mod cli;
+#[cfg(unix)]
+mod remove;
+#[cfg(windows)]
+#[path = "remove_windows.rs"]
+mod remove;
+
+use remove::Remover;
 
 fn main() {
     let matches = cli::create_app().get_matches();
 
     let flags = RmFlags::from_matches(&matches);
 
-    let cwd = match current_dir() {
-        Ok(path) => path,
-        Err(err) => {
-            eprintln!("rm: error reading current working directory: {}", err);
-            process::exit(1);
-        },
+    let files: Vec<&Path> = match matches.values_of("FILE") {
+        Some(files) => files.map(Path::new).collect(),
+        None => Vec::new(),
     };
 
-    // Safe to unwrap since we said it is required on clap configuration
-    let files_relative: Vec<&str> = matches.values_of("FILE").unwrap().collect();
-
-    // Safe to unwrap since we said it is required on clap configuration
-    let files: Vec<PathBuf> = matches.values_of("FILE").unwrap().map(|s| cwd.join(s)).collect();
+    if files.is_empty() {
+        // Without files there is nothing to do with -f
+        if flags.force {
+            return;
+        }
 
-    if flags.preserve_root && files.contains(&PathBuf::from("/")) {
-        eprintln!(
-            "rm: it is dangerous to operate on '/', use --no-preserve-root to override this \
-             failsafe."
-        );
+        eprintln!("rm: missing operand");
         process::exit(1);
     }
 
-    if flags.interactive_batch && (files.len() > 3 || flags.recursive) {
-        let is_affirmative = Input::new()
-            .with_msg("rm: are you sure you want to do this deletion? [Y/n]: ")
-            .is_affirmative();
+    if flags.asks_once(files.len()) {
+        let plural = if files.len() == 1 { "" } else { "s" };
+        let recursively = if flags.recursive { " recursively" } else { "" };
+        let msg = format!("rm: remove {} argument{}{}? ", files.len(), plural, recursively);
 
-        if !is_affirmative {
-            process::exit(1);
+        if !Input::new().with_msg(&msg).is_affirmative() {
+            return;
         }
     }
 
-    match rm(&files, &files_relative, flags) {
-        Ok(()) => {},
-        Err(msg) => {
-            eprintln!("rm: {}", msg);
-            process::exit(1);
-        },
-    };
+    let remover = Remover::new(flags);
+    let mut success = true;
+
+    for file in files {
+        success &= remover.remove(file);
+    }
+
+    if !success {
+        process::exit(1);
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
+// The devices and the threads are only of the removal relative to the open directories
+#[cfg_attr(windows, allow(dead_code))]
 struct RmFlags {
     pub force: bool,
     pub interactive: bool,
     pub interactive_batch: bool,
     pub preserve_root: bool,
+    pub preserve_all: bool,
+    pub one_file_system: bool,
+    pub threads: usize,
     pub recursive: bool,
     pub dirs: bool,
     pub verbose: bool,
@@ -79,6 +81,10 @@ impl RmFlags {
             interactive: matches.is_present("interactive"),
             interactive_batch: matches.is_present("interactiveBatch"),
             preserve_root: !matches.is_present("noPreserveRoot"),
+            preserve_all: matches.value_of("preserveRoot") == Some("all"),
+            one_file_system: matches.is_present("oneFileSystem"),
+            // Safe to unwrap since it is validated on clap configuration
+            threads: matches.value_of("threads").map_or(1, |threads| threads.parse().unwrap()),
             recursive: matches.is_present("recursive") | matches.is_present("recursive_compat"),
             dirs: matches.is_present("directories"),
             verbose: matches.is_present("verbose"),
@@ -101,264 +107,42 @@ impl RmFlags {
 
         flags
     }
-}
-
-fn ask(
-    filetype: FileType, permissions: &Permissions, filename: &str, flags: RmFlags,
-) -> Result<bool, ()> {
-    if !flags.interactive && permissions.readonly() {
-        if filetype.is_file() {
-            let msg = format!("rm: delete write_protected regular file '{}'? [Y/n]: ", filename);
-            return Ok(Input::new().with_msg(&msg).is_affirmative());
-        } else if filetype.is_dir() {
-            let msg = format!("rm: delete write_protected dir file '{}'? [Y/n]: ", filename);
-            return Ok(Input::new().with_msg(&msg).is_affirmative());
-        }
-    }
 
-    if flags.interactive {
-        if filetype.is_file() && permissions.readonly() {
-            let msg = format!("rm: delete write_protected regular file '{}'? [Y/n]: ", filename);
-            return Ok(Input::new().with_msg(&msg).is_affirmative());
-        } else if filetype.is_file() && !permissions.readonly() {
-            let msg = format!("rm: delete regular file '{}'? [Y/n]: ", filename);
-            return Ok(Input::new().with_msg(&msg).is_affirmative());
-        } else if filetype.is_dir() && permissions.readonly() {
-            let msg = format!("rm: delete write_protected directory file '{}'? [Y/n]: ", filename);
-            return Ok(Input::new().with_msg(&msg).is_affirmative());
-        } else if filetype.is_dir() && !permissions.readonly() {
-            let msg = format!("rm: delete directory file '{}'? [Y/n]: ", filename);
-            return Ok(Input::new().with_msg(&msg).is_affirmative());
-        }
+    /// Returns `true` if the user is asked once before removing the `count` files with
+    /// `-I`, that is only when there are more than three of them or with `-r`.
+    fn asks_once(&self, count: usize) -> bool {
+        self.interactive_batch && (count > 3 || self.recursive)
     }
-
-    Err(())
 }
 
-fn rm(files: &[PathBuf], relative: &[&str], flags: RmFlags) -> io::Result<()> {
-    for (index, file) in files.iter().enumerate() {
-        let metadata = file.metadata()?;
-        let permissions = metadata.permissions();
-        let filetype = metadata.file_type();
-
-        if filetype.is_file() {
-            if !flags.force && (flags.interactive ^ permissions.readonly()) {
-                let is_affirmative = match ask(filetype, &permissions, &relative[index], flags) {
-                    Ok(i) => i,
-                    Err(_) => {
-                        eprintln!("rm: failed to get input when interactive of write protected");
-                        process::exit(1);
-                    },
-                };
-
-                if is_affirmative {
-                    match fs::remove_file(&file) {
-                        Ok(()) => {
-                            if flags.verbose {
-                                println!("removed {}", file.display());
-                            }
-                        },
-                        Err(err) => eprintln!(
-                            "rm: cannot remove regular file '{}', {}",
-                            relative[index], err
-                        ),
-                    };
-                }
-            } else {
-                match fs::remove_file(&file) {
-                    Ok(()) => {
-                        if flags.verbose {
-                            println!("removed {}", file.display());
-                        }
-                    },
-                    Err(err) => {
-                        eprintln!("rm: cannot remove regular file '{}', {}", relative[index], err)
-                    },
-                }
-            }
-        } else if filetype.is_dir() {
-            if flags.recursive {
-                rm_dir_all(file, &relative[index], filetype, &permissions, flags)?;
-            } else if flags.dirs {
-                if !flags.force && (flags.interactive ^ permissions.readonly()) {
-                    let is_affirmative = match ask(filetype, &permissions, &relative[index], flags)
-                    {
-                        Ok(i) => i,
-                        Err(_) => {
-                            eprintln!(
-                                "rm: failed to get input when interactive of write protected"
-                            );
-                            process::exit(1);
-                        },
-                    };
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                    if is_affirmative {
-                        match fs::remove_dir(&file) {
-                            Ok(()) => {
-                                if flags.verbose {
-                                    println!("removed {}", file.display());
-                                }
-                            },
-                            Err(err) => eprintln!(
-                                "rm: cannot remove directory file '{}': {}",
-                                relative[index], err
-                            ),
-                        };
-                    }
-                } else {
-                    match fs::remove_dir(&file) {
-                        Ok(()) => {
-                            if flags.verbose {
-                                println!("removed {}", file.display());
-                            }
-                        },
-                        Err(err) => eprintln!(
-                            "rm: cannot remove directory file '{}': {}",
-                            relative[index], err
-                        ),
-                    };
-                }
-            } else {
-                eprintln!("rm: cannot remove '{}': it is a directory", relative[index]);
-            }
-        } else {
-            eprintln!("rm: cannot remove '{}': no such file or directory", relative[index]);
-        }
-    }
-    Ok(())
-}
-
-fn rm_dir_all(
-    file: &Path, relative: &str, filetype: FileType, permissions: &Permissions, flags: RmFlags,
-) -> io::Result<()> {
-    let file_type = fs::symlink_metadata(file)?.file_type();
-    if file_type.is_symlink() {
-        if !flags.force && (flags.interactive ^ permissions.readonly()) {
-            let is_affirmative = match ask(filetype, &permissions, &relative, flags) {
-                Ok(i) => i,
-                Err(_) => {
-                    eprintln!("rm: failed to get input when interactive of write protected");
-                    process::exit(1);
-                },
-            };
-
-            if is_affirmative {
-                match fs::remove_file(file) {
-                    Ok(()) => {
-                        if flags.verbose {
-                            println!("removed {}", file.display());
-                        }
-                    },
-                    Err(err) => eprintln!("rm: cannot remove regular file '{}': {}", relative, err),
-                }
-            }
-            Ok(())
-        } else {
-            match fs::remove_file(file) {
-                Ok(()) => {
-                    if flags.verbose {
-                        println!("removed {}", file.display());
-                    }
-                },
-                Err(err) => eprintln!("rm: cannot remove regular file '{}': {}", relative, err),
-            };
-            Ok(())
-        }
-    } else {
-        rm_dir_all_recursive(file, relative, filetype, permissions, flags)
-    }
-}
-
-fn rm_dir_all_recursive(
-    file: &Path, relative: &str, filetype: FileType, permissions: &Permissions, flags: RmFlags,
-) -> io::Result<()> {
-    if flags.interactive {
-        let msg = format!("rm: Descend into directory '{}'? [Y/n]: ", relative);
-        let is_affirmative = Input::new().with_msg(&msg).is_affirmative();
-
-        if !is_affirmative {
-            return Ok(());
-        }
+    fn flags(args: Vec<&str>) -> RmFlags {
+        RmFlags::from_matches(&cli::create_app().get_matches_from(args))
     }
 
-    for child in fs::read_dir(&file)? {
-        let child = child?;
-        let child_permissions = child.metadata()?.permissions();
-        let child_type = child.file_type()?;
-        let child_relative = format!("{}/{}", relative, child.file_name().to_string_lossy());
+    #[test]
+    fn last_prompt_flag() {
+        let rm = flags(vec!["rm", "-i", "-f", "a"]);
+        assert!(rm.force && !rm.interactive && !rm.interactive_batch);
 
-        if child_type.is_dir() {
-            rm_dir_all_recursive(
-                &child.path(),
-                &child_relative,
-                child_type,
-                &child_permissions,
-                flags,
-            )?
-        } else if !flags.force && (flags.interactive || child_permissions.readonly()) {
-            let is_affirmative = match ask(child_type, &child_permissions, &child_relative, flags) {
-                Ok(i) => i,
-                Err(_) => {
-                    eprintln!("rm: failed to get input when interactive of write protected");
-                    process::exit(1);
-                },
-            };
+        let rm = flags(vec!["rm", "-f", "-I", "a"]);
+        assert!(!rm.force && !rm.interactive && rm.interactive_batch);
 
-            if is_affirmative {
-                match fs::remove_file(&child.path()) {
-                    Ok(()) => {
-                        if flags.verbose {
-                            println!("removed {}", child.path().display());
-                        }
-                    },
-                    Err(err) => {
-                        eprintln!("rm: cannot remove regular file '{}': {}", child_relative, err)
-                    },
-                }
-            }
-        } else {
-            match fs::remove_file(&child.path()) {
-                Ok(()) => {
-                    if flags.verbose {
-                        println!("removed {}", child.path().display());
-                    }
-                },
-                Err(err) => {
-                    eprintln!("rm: cannot remove regular file '{}': {}", child_relative, err)
-                },
-            }
-        }
+        let rm = flags(vec!["rm", "-I", "-f", "-i", "a"]);
+        assert!(!rm.force && rm.interactive && !rm.interactive_batch);
     }
 
-    if !flags.force && (flags.interactive ^ permissions.readonly()) {
-        let is_affirmative = match ask(filetype, &permissions, &relative, flags) {
-            Ok(i) => i,
-            Err(_) => {
-                eprintln!("rm: failed to get input when interactive of write protected");
-                process::exit(1);
-            },
-        };
+    #[test]
+    fn batch_prompt_threshold() {
+        let rm = flags(vec!["rm", "-I", "a"]);
+        assert!(!rm.asks_once(1));
+        assert!(!rm.asks_once(3));
+        assert!(rm.asks_once(4));
 
-        if is_affirmative {
-            match fs::remove_dir(&file) {
-                Ok(()) => {
-                    if flags.verbose {
-                        println!("removed {}", file.display());
-                    }
-                },
-                Err(err) => eprintln!("rm: cannot remove directory file '{}': {}", relative, err),
-            }
-        }
-    } else {
-        match fs::remove_dir(&file) {
-            Ok(()) => {
-                if flags.verbose {
-                    println!("removed {}", file.display());
-                }
-            },
-            Err(err) => eprintln!("rm: cannot remove directory file '{}': {}", relative, err),
-        }
+        assert!(flags(vec!["rm", "-I", "-r", "a"]).asks_once(1));
+        assert!(!flags(vec!["rm", "-f", "a"]).asks_once(4));
     }
-
-    Ok(())
 }
@@ -0,0 +1,837 @@
+//! The removal of the files, and of the directories with their contents, relative to the
+//! open directories that hold them.
+//!
+//! The directories are walked with the file descriptors of [`Dir`], so one that is
+//! renamed or replaced by a symbolic link while it is removed can't make the removal
+//! leave the tree, and their entries are removed as they are read.
+//!
+//! The trees are walked without recursion, and only the deepest directories are kept
+//! open, so any depth can be removed. The others are opened again from their child
+//! through `..`, checking that they are still the same directory.
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    ffi::{OsStr, OsString},
+    fmt::Display,
+    fs, io,
+    os::unix::{ffi::OsStrExt, fs::MetadataExt},
+    path::{Path, PathBuf},
+};
+
+use coreutils_core::{
+    input::Input,
+    libc,
+    os::{dir::Dir, tty::is_tty},
+};
+
+use crate::RmFlags;
+
+mod parallel;
+
+/// The most directories of the trees kept open at once.
+const MAX_OPEN_DIRS: usize = 64;
+
+/// Removes the files with the flags.
+pub(crate) struct Remover {
+    flags: RmFlags,
+    /// The device and inode numbers of `/`, with `--preserve-root`.
+    root: Option<(u64, u64)>,
+    /// If the standard input is a terminal, to ask before removing the write-protected
+    /// files.
+    stdin_tty: bool,
+    /// The messages kept to be printed later, if they are buffered.
+    log: Option<RefCell<Vec<Message>>>,
+}
+
+/// The device and the mount of a file system, if the system gives it, to not leave it
+/// with `--one-file-system`.
+type Device = (u64, Option<u64>);
+
+/// A directory of a tree whose contents are being removed.
+struct Level {
+    /// The open directory, or `None` if it was closed to open a deeper one.
+    dir: Option<Dir>,
+    /// The name of the directory in its parent.
+    name: OsString,
+    path: PathBuf,
+    /// The status of the directory, to check that it is the same when it is opened again.
+    stat: libc::stat,
+    /// The entries that were kept, that are skipped when the directory is read again.
+    kept: HashSet<OsString>,
+}
+
+impl Remover {
+    pub(crate) fn new(flags: RmFlags) -> Self {
+        let root = if flags.preserve_root {
+            fs::metadata("/").ok().map(|root| (root.dev(), root.ino()))
+        } else {
+            None
+        };
+
+        Remover { flags, root, stdin_tty: is_tty(&io::stdin()), log: None }
+    }
+
+    /// Remove the file of the command line in `path`, returning `false` if it, or the
+    /// contents of a directory, could not be removed.
+    pub(crate) fn remove(&self, path: &Path) -> bool {
+        if is_dot(path) {
+            self.error(format!(
+                "refusing to remove '.' or '..' directory: skipping '{}'",
+                path.display()
+            ));
+            return false;
+        }
+
+        let (parent, name) = match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => (parent, name),
+            // The root has no parent to remove it from
+            _ => return self.remove_root(path),
+        };
+        let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+
+        let parent = match Dir::open(parent) {
+            Ok(parent) => parent,
+            Err(err) if self.missing(&err) => return true,
+            Err(err) => {
+                self.error(format!("cannot remove '{}': {}", path.display(), err));
+                return false;
+            },
+        };
+
+        let stat = match parent.stat_at(name, false) {
+            Ok(stat) => stat,
+            Err(err) if self.missing(&err) => return true,
+            Err(err) => {
+                self.error(format!("cannot remove '{}': {}", path.display(), err));
+                return false;
+            },
+        };
+
+        if !is_dir(&stat) {
+            return self.remove_file(&parent, name, path, &stat).is_some();
+        }
+
+        if self.flags.recursive {
+            if self.flags.preserve_all && matches!(parent.stat(), Ok(p) if p.st_dev != stat.st_dev)
+            {
+                self.error(format!(
+                    "skipping '{}', since it's on a different device",
+                    path.display()
+                ));
+                self.error("and --preserve-root=all is in effect");
+                return false;
+            }
+
+            return self.remove_tree(&parent, name, path, &stat, None);
+        }
+
+        if !self.flags.dirs {
+            self.error(format!("cannot remove '{}': Is a directory", path.display()));
+            return false;
+        }
+
+        self.remove_dir(&parent, name, path, &stat).is_some()
+    }
+
+    /// Remove the directory `name` of `parent`, in `path` and with the `stat`, and all
+    /// its contents, returning `false` if any of them could not be removed.
+    ///
+    /// With `--one-file-system`, the directories not on the `device`, or on the one of
+    /// the directory if it is `None`, are skipped.
+    fn remove_tree(
+        &self, parent: &Dir, name: &OsStr, path: &Path, stat: &libc::stat, device: Option<Device>,
+    ) -> bool {
+        let level = match self.enter(parent, name, path, stat, device) {
+            Some(Ok(level)) => level,
+            Some(Err(_)) => return false,
+            None => return true,
+        };
+
+        // The `dev_t` isn't of 64 bits on all the systems
+        #[allow(clippy::unnecessary_cast)]
+        let device = match device {
+            Some(device) => device,
+            None if self.flags.one_file_system => {
+                (stat.st_dev as u64, level.dir.as_ref().and_then(Dir::mount_id))
+            },
+            None => (stat.st_dev as u64, None),
+        };
+
+        let removed = if self.parallel() {
+            self.remove_parallel(level, device)
+        } else {
+            self.remove_contents(level, device)
+        };
+
+        match removed {
+            Some(true) => self.remove_dir(parent, name, path, stat).is_some(),
+            Some(false) => true,
+            None => false,
+        }
+    }
+
+    /// Remove the contents of the directory of the `level`, on the `device`. Returns
+    /// `Some(true)` if all of them were removed, `Some(false)` if the user didn't want
+    /// some of them removed, and `None` if any of them failed.
+    fn remove_contents(&self, level: Level, device: Device) -> Option<bool> {
+        let mut success = true;
+        let mut levels = vec![level];
+
+        // If all the contents of each level were removed, so it can be removed too
+        let mut emptied = vec![true];
+
+        loop {
+            // The first level is only popped to return
+            let level = levels.last_mut().unwrap();
+
+            // The deepest level is always open
+            let dir = level.dir.as_mut().unwrap();
+
+            let entry = match dir.read() {
+                Some(Ok(entry)) if level.kept.contains(&entry) => continue,
+                Some(Ok(entry)) => Some(entry),
+                Some(Err(err)) => {
+                    self.error(format!(
+                        "cannot read directory '{}': {}",
+                        level.path.display(),
+                        err
+                    ));
+                    success = false;
+                    *emptied.last_mut().unwrap() = false;
+                    None
+                },
+                None => None,
+            }
+            .map(|entry| (level.path.join(&entry), entry));
+
+            let (entry_path, entry) = match entry {
+                Some(entry) => entry,
+                None => {
+                    let level = levels.pop().unwrap();
+                    let removable = emptied.pop().unwrap();
+
+                    let parent = match levels.last_mut() {
+                        Some(parent) => {
+                            if let Err(err) = reopen(parent, &level) {
+                                self.error(format!(
+                                    "cannot remove '{}': {}",
+                                    parent.path.display(),
+                                    err
+                                ));
+                                return None;
+                            }
+                            parent.dir.as_ref().unwrap()
+                        },
+                        None if success => return Some(removable),
+                        None => return None,
+                    };
+
+                    let removed = removable
+                        && match self.remove_dir(parent, &level.name, &level.path, &level.stat) {
+                            Some(removed) => removed,
+                            None => {
+                                success = false;
+                                false
+                            },
+                        };
+
+                    if !removed {
+                        if let (Some(parent), Some(emptied)) =
+                            (levels.last_mut(), emptied.last_mut())
+                        {
+                            parent.kept.insert(level.name);
+                            *emptied = false;
+                        }
+                    }
+
+                    continue;
+                },
+            };
+
+            let dir = level.dir.as_ref().unwrap();
+
+            let removed = match dir.stat_at(&entry, false) {
+                Ok(stat) if is_dir(&stat) => {
+                    match self.enter(dir, &entry, &entry_path, &stat, Some(device)) {
+                        Some(Ok(child)) => {
+                            close_shallow(&mut levels, self.max_open_dirs());
+                            levels.push(child);
+                            emptied.push(true);
+                            continue;
+                        },
+                        Some(Err(_)) => None,
+                        None => Some(false),
+                    }
+                },
+                Ok(stat) => self.remove_file(dir, &entry, &entry_path, &stat),
+                Err(err) if self.missing(&err) => Some(true),
+                Err(err) => {
+                    self.error(format!("cannot remove '{}': {}", entry_path.display(), err));
+                    None
+                },
+            };
+
+            if removed != Some(true) {
+                success &= removed.is_some();
+                level.kept.insert(entry);
+                *emptied.last_mut().unwrap() = false;
+            }
+        }
+    }
+
+    /// Open the directory `name` of `parent`, in `path` and with the `stat`, to remove
+    /// its contents, asking the user before with `-i` if it is not empty. Returns `None`
+    /// if the user didn't want it.
+    ///
+    /// The root directory with `--preserve-root`, and the directories not on the `device`
+    /// with `--one-file-system`, are not opened.
+    // The `dev_t` and `ino_t` aren't of 64 bits on all the systems
+    #[allow(clippy::unnecessary_cast)]
+    fn enter(
+        &self, parent: &Dir, name: &OsStr, path: &Path, stat: &libc::stat, device: Option<Device>,
+    ) -> Option<Result<Level, ()>> {
+        if self.root == Some((stat.st_dev as u64, stat.st_ino as u64)) {
+            self.dangerous(path);
+            return Some(Err(()));
+        }
+
+        let skip = || {
+            self.error(format!("skipping '{}', since it's on a different device", path.display()));
+            Some(Err(()))
+        };
+
+        let device = device.filter(|_| self.flags.one_file_system);
+
+        if matches!(device, Some((dev, _)) if dev != stat.st_dev as u64) {
+            return skip();
+        }
+
+        let dir = match parent.open_dir(name) {
+            Ok(dir) => dir,
+            Err(err) => {
+                self.error(format!("cannot remove '{}': {}", path.display(), err));
+                return Some(Err(()));
+            },
+        };
+
+        if let Some((_, Some(mount))) = device {
+            if matches!(dir.mount_id(), Some(id) if id != mount) {
+                return skip();
+            }
+        }
+
+        if self.flags.interactive && !dir.is_empty().unwrap_or(false) {
+            let protected =
+                if self.protected(parent, name, stat) { "write-protected " } else { "" };
+
+            if !ask(&format!("descend into {}directory '{}'", protected, path.display())) {
+                return None;
+            }
+        }
+
+        Some(Ok(Level {
+            dir: Some(dir),
+            name: name.to_os_string(),
+            path: path.to_path_buf(),
+            stat: *stat,
+            kept: HashSet::new(),
+        }))
+    }
+
+    /// Remove the entry `name` of `parent` in `path`, that is not a directory. Returns
+    /// `Some(false)` if the user didn't want it removed, and `None` if it failed.
+    fn remove_file(
+        &self, parent: &Dir, name: &OsStr, path: &Path, stat: &libc::stat,
+    ) -> Option<bool> {
+        if !self.confirm(parent, name, path, stat) {
+            return Some(false);
+        }
+
+        match parent.remove_file(name) {
+            Ok(_) => {
+                if self.flags.verbose {
+                    self.out(format!("removed '{}'", path.display()));
+                }
+                Some(true)
+            },
+            Err(err) if self.missing(&err) => Some(true),
+            Err(err) => {
+                self.error(format!("cannot remove '{}': {}", path.display(), err));
+                None
+            },
+        }
+    }
+
+    /// Remove the empty directory `name` of `parent` in `path`, like
+    /// [`remove_file`](Remover::remove_file).
+    fn remove_dir(
+        &self, parent: &Dir, name: &OsStr, path: &Path, stat: &libc::stat,
+    ) -> Option<bool> {
+        if !self.confirm(parent, name, path, stat) {
+            return Some(false);
+        }
+
+        match parent.remove_dir(name) {
+            Ok(_) => {
+                if self.flags.verbose {
+                    self.out(format!("removed directory '{}'", path.display()));
+                }
+                Some(true)
+            },
+            Err(err) if self.missing(&err) => Some(true),
+            Err(err) => {
+                self.error(format!("cannot remove '{}': {}", path.display(), err));
+                None
+            },
+        }
+    }
+
+    /// Remove the contents of the root directory in `path`, that can only be tried with
+    /// `--no-preserve-root`.
+    fn remove_root(&self, path: &Path) -> bool {
+        if !self.flags.recursive {
+            self.error(format!("cannot remove '{}': Is a directory", path.display()));
+            return false;
+        }
+
+        if self.flags.preserve_root {
+            self.dangerous(path);
+            return false;
+        }
+
+        let root = match Dir::open(path) {
+            Ok(root) => root,
+            Err(err) => {
+                self.error(format!("cannot remove '{}': {}", path.display(), err));
+                return false;
+            },
+        };
+
+        let mut success = true;
+        let mut root = root;
+
+        // The `dev_t` isn't of 64 bits on all the systems
+        #[allow(clippy::unnecessary_cast)]
+        let device = root.stat().map(|stat| (stat.st_dev as u64, root.mount_id()));
+        let device = device.ok();
+
+        while let Some(entry) = root.read() {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    self.error(format!("cannot read directory '{}': {}", path.display(), err));
+                    return false;
+                },
+            };
+            let entry_path = path.join(&entry);
+
+            success &= match root.stat_at(&entry, false) {
+                Ok(stat) if is_dir(&stat) => {
+                    self.remove_tree(&root, &entry, &entry_path, &stat, device)
+                },
+                Ok(stat) => self.remove_file(&root, &entry, &entry_path, &stat).is_some(),
+                Err(err) => {
+                    self.error(format!("cannot remove '{}': {}", entry_path.display(), err));
+                    false
+                },
+            };
+        }
+
+        match fs::remove_dir(path) {
+            Ok(_) => success,
+            Err(err) => {
+                self.error(format!("cannot remove '{}': {}", path.display(), err));
+                false
+            },
+        }
+    }
+
+    /// Returns `true` if the entry `name` of `parent`, in `path` and with the `stat`, is
+    /// removed, asking the user before removing it with `-i`, or if it is
+    /// write-protected, unless `-f`.
+    fn confirm(&self, parent: &Dir, name: &OsStr, path: &Path, stat: &libc::stat) -> bool {
+        if self.flags.force {
+            return true;
+        }
+
+        let protected = self.protected(parent, name, stat);
+
+        if !(self.flags.interactive || protected) {
+            return true;
+        }
+
+        let protected = if protected { "write-protected " } else { "" };
+
+        ask(&format!("remove {}{} '{}'", protected, file_type(stat), path.display()))
+    }
+
+    /// The most directories of each tree kept open at once, that are shared by the
+    /// workers of `--threads`.
+    fn max_open_dirs(&self) -> usize {
+        (MAX_OPEN_DIRS / self.flags.threads).max(2)
+    }
+
+    /// Returns `true` if the `err` is of a file that doesn't exist, that is ignored with
+    /// `-f`.
+    fn missing(&self, err: &io::Error) -> bool {
+        self.flags.force && matches!(err.raw_os_error(), Some(libc::ENOENT) | Some(libc::ENOTDIR))
+    }
+
+    /// Returns `true` if the subdirectories are removed by the workers of `--threads`,
+    /// that never ask the user.
+    fn parallel(&self) -> bool {
+        self.flags.threads > 1 && !self.flags.interactive && (self.flags.force || !self.stdin_tty)
+    }
+
+    /// Returns `true` if the entry `name` of `parent`, with the `stat`, can't be written
+    /// and isn't a symbolic link, that is only checked with `-i` or if the user can
+    /// answer from a terminal.
+    fn protected(&self, parent: &Dir, name: &OsStr, stat: &libc::stat) -> bool {
+        (self.flags.interactive || self.stdin_tty)
+            && stat.st_mode & libc::S_IFMT != libc::S_IFLNK
+            && !parent.writable(name)
+    }
+
+    /// Tell that the root directory in `path` is not removed with `--preserve-root`.
+    fn dangerous(&self, path: &Path) {
+        if path.as_os_str() == "/" {
+            self.error("it is dangerous to operate recursively on '/'");
+        } else {
+            self.error(format!(
+                "it is dangerous to operate recursively on '{}' (same as '/')",
+                path.display()
+            ));
+        }
+        self.error("use --no-preserve-root to override this failsafe");
+    }
+
+    /// Print the `msg` of a error, or keep it with the others if it is buffered.
+    fn error(&self, msg: impl Display) {
+        match &self.log {
+            Some(log) => log.borrow_mut().push(Message::Err(format!("rm: {}", msg))),
+            None => eprintln!("rm: {}", msg),
+        }
+    }
+
+    /// Print the `msg` of `-v`, or keep it with the others if it is buffered.
+    fn out(&self, msg: impl Display) {
+        match &self.log {
+            Some(log) => log.borrow_mut().push(Message::Out(msg.to_string())),
+            None => println!("{}", msg),
+        }
+    }
+
+    /// Take the messages kept if it is buffered.
+    fn take_log(&self) -> Vec<Message> {
+        self.log.as_ref().map(|log| log.replace(Vec::new())).unwrap_or_default()
+    }
+
+    /// Get a remover with the same options whose messages are buffered.
+    fn buffered(&self) -> Self {
+        Remover { log: Some(RefCell::default()), ..*self }
+    }
+}
+
+/// A message kept by a buffered remover, to print it later.
+enum Message {
+    Out(String),
+    Err(String),
+}
+
+impl Message {
+    fn print(&self) {
+        match self {
+            Message::Out(msg) => println!("{}", msg),
+            Message::Err(msg) => eprintln!("{}", msg),
+        }
+    }
+}
+
+/// Open again the directory of the `parent` level of the `child` one through `..`, if
+/// it was closed.
+///
+/// # Errors
+/// If the directory can't be opened, or `..` is no longer the same directory, because
+/// the child was moved, an error variant will be returned.
+fn reopen(parent: &mut Level, child: &Level) -> io::Result<()> {
+    if parent.dir.is_some() {
+        return Ok(());
+    }
+
+    // The deepest level is always open
+    let dir = child.dir.as_ref().unwrap().open_dir("..")?;
+    let stat = dir.stat()?;
+
+    if (stat.st_dev, stat.st_ino) != (parent.stat.st_dev, parent.stat.st_ino) {
+        let msg = format!("'{}' was moved while it was removed", child.path.display());
+
+        // `io::Error::other` is newer than the MSRV
+        #[allow(clippy::io_other_error)]
+        let err = io::Error::new(io::ErrorKind::Other, msg);
+
+        return Err(err);
+    }
+
+    parent.dir = Some(dir);
+    Ok(())
+}
+
+/// Close the shallowest open directory of the `levels` if there are `max` of them open,
+/// to open a deeper one.
+fn close_shallow(levels: &mut [Level], max: usize) {
+    // The open levels are always the deepest ones
+    let open = levels.iter().rev().take_while(|level| level.dir.is_some()).count();
+
+    if open >= max {
+        levels[levels.len() - open].dir = None;
+    }
+}
+
+/// Ask the user the `question`, returning `true` if the answer is affirmative.
+fn ask(question: &str) -> bool {
+    Input::new()
+        .with_msg(&format!("rm: {}? ", question))
+        .with_err_msg("rm: could not read user input")
+        .is_affirmative()
+}
+
+fn is_dir(stat: &libc::stat) -> bool {
+    stat.st_mode & libc::S_IFMT == libc::S_IFDIR
+}
+
+/// The type of the file of the `stat`, as told in the questions.
+fn file_type(stat: &libc::stat) -> &'static str {
+    match stat.st_mode & libc::S_IFMT {
+        libc::S_IFREG if stat.st_size == 0 => "regular empty file",
+        libc::S_IFREG => "regular file",
+        libc::S_IFDIR => "directory",
+        libc::S_IFLNK => "symbolic link",
+        libc::S_IFIFO => "fifo",
+        libc::S_IFSOCK => "socket",
+        libc::S_IFCHR => "character special file",
+        libc::S_IFBLK => "block special file",
+        _ => "weird file",
+    }
+}
+
+/// Returns `true` if the last component of the `path` is `.` or `..`, that are not
+/// removed.
+fn is_dot(path: &Path) -> bool {
+    let bytes = path.as_os_str().as_bytes();
+    let trimmed = match bytes.iter().rposition(|&byte| byte != b'/') {
+        Some(last) => &bytes[..=last],
+        None => return false,
+    };
+    let name = trimmed.rsplit(|&byte| byte == b'/').next().unwrap_or(trimmed);
+
+    name == b"." || name == b".."
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+    use crate::cli;
+
+    /// A remover with the flags of the `args`, whose messages are buffered.
+    fn remover(args: Vec<&str>) -> Remover {
+        let matches = cli::create_app().get_matches_from(args);
+        let mut remover = Remover::new(RmFlags::from_matches(&matches)).buffered();
+        remover.stdin_tty = false;
+        remover
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("rm.{}.{}", name, process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// The messages of `-v` and the errors kept by the `remover`.
+    fn messages(remover: &Remover) -> (Vec<String>, Vec<String>) {
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+
+        for message in remover.take_log() {
+            match message {
+                Message::Out(msg) => out.push(msg),
+                Message::Err(msg) => err.push(msg),
+            }
+        }
+
+        (out, err)
+    }
+
+    /// Open the directory `name` of `dir` to remove its contents.
+    fn level(remover: &Remover, dir: &Path, name: &str) -> Level {
+        let parent = Dir::open(dir).unwrap();
+        let stat = parent.stat_at(name, false).unwrap();
+
+        match remover.enter(&parent, OsStr::new(name), &dir.join(name), &stat, None) {
+            Some(Ok(level)) => level,
+            _ => panic!("'{}' was not opened", name),
+        }
+    }
+
+    #[test]
+    fn dirs() {
+        let dir = temp_dir("dirs");
+        let empty = dir.join("empty");
+        let full = dir.join("full");
+        fs::create_dir_all(&empty).unwrap();
+        fs::create_dir_all(&full).unwrap();
+        fs::write(full.join("file"), b"").unwrap();
+
+        let rm = remover(vec!["rm", "x"]);
+        assert!(!rm.remove(&empty));
+        assert!(empty.is_dir());
+        let msg = format!("rm: cannot remove '{}': Is a directory", empty.display());
+        assert_eq!(messages(&rm).1, [msg]);
+
+        let rm = remover(vec!["rm", "-d", "-v", "x"]);
+        assert!(rm.remove(&empty));
+        assert!(!empty.exists());
+        assert_eq!(messages(&rm).0, [format!("removed directory '{}'", empty.display())]);
+
+        // Only the empty directories are removed with -d
+        assert!(!rm.remove(&full));
+        assert!(full.join("file").exists());
+        assert_eq!(messages(&rm).1.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn force() {
+        let dir = temp_dir("force");
+        let file = dir.join("file");
+        fs::write(&file, b"").unwrap();
+
+        let rm = remover(vec!["rm", "x"]);
+        assert!(!rm.remove(&dir.join("missing")));
+        assert_eq!(messages(&rm).1.len(), 1);
+
+        let rm = remover(vec!["rm", "-f", "x"]);
+        assert!(rm.remove(&dir.join("missing")));
+        assert!(rm.remove(&dir.join("missing/below")));
+        assert!(rm.remove(&file.join("below")));
+        assert_eq!(messages(&rm), (Vec::new(), Vec::new()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn verbose() {
+        let dir = temp_dir("verbose");
+        let tree = dir.join("tree");
+        fs::create_dir_all(tree.join("sub")).unwrap();
+        fs::write(tree.join("sub/file"), b"").unwrap();
+
+        let rm = remover(vec!["rm", "-r", "-v", "x"]);
+        assert!(rm.remove(&tree));
+        assert!(!tree.exists());
+        assert_eq!(messages(&rm).0, [
+            format!("removed '{}'", tree.join("sub/file").display()),
+            format!("removed directory '{}'", tree.join("sub").display()),
+            format!("removed directory '{}'", tree.display()),
+        ]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn one_file_system() {
+        let dir = temp_dir("one_file_system");
+        let tree = dir.join("tree");
+        fs::create_dir_all(tree.join("sub")).unwrap();
+        fs::write(tree.join("file"), b"").unwrap();
+        fs::write(tree.join("sub/file"), b"").unwrap();
+
+        // The subdirectory is taken to be on other device than the tree
+        let rm = remover(vec!["rm", "-r", "--one-file-system", "x"]);
+        let other = (fs::metadata(&tree).unwrap().dev().wrapping_add(1), None);
+        assert_eq!(rm.remove_contents(level(&rm, &dir, "tree"), other), None);
+        assert!(!tree.join("file").exists());
+        assert!(tree.join("sub/file").exists());
+        let msg = format!(
+            "rm: skipping '{}', since it's on a different device",
+            tree.join("sub").display()
+        );
+        assert_eq!(messages(&rm).1, [msg]);
+
+        // Without it the device doesn't matter
+        let rm = remover(vec!["rm", "-r", "x"]);
+        assert_eq!(rm.remove_contents(level(&rm, &dir, "tree"), other), Some(true));
+        assert!(!tree.join("sub").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn threads() {
+        let dir = temp_dir("threads");
+        let tree = dir.join("tree");
+        for sub in &["a", "b", "c", "d"] {
+            fs::create_dir_all(tree.join(sub).join("deeper")).unwrap();
+            fs::write(tree.join(sub).join("file"), b"").unwrap();
+        }
+        fs::write(tree.join("file"), b"").unwrap();
+
+        // The subdirectories that the workers fail to remove make the whole removal fail
+        let rm = remover(vec!["rm", "-r", "--one-file-system", "--threads=2", "x"]);
+        let device = (fs::metadata(&tree).unwrap().dev(), None);
+        let other = (device.0.wrapping_add(1), None);
+        assert_eq!(rm.remove_parallel(level(&rm, &dir, "tree"), other), None);
+        assert!(!tree.join("file").exists());
+        for sub in &["a", "b", "c", "d"] {
+            assert!(tree.join(sub).join("file").exists());
+        }
+
+        let rm = remover(vec!["rm", "-r", "--threads=2", "x"]);
+        assert_eq!(rm.remove_parallel(level(&rm, &dir, "tree"), device), Some(true));
+        assert_eq!(fs::read_dir(&tree).unwrap().count(), 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reopen_same_dir() {
+        let dir = temp_dir("reopen");
+        fs::create_dir_all(dir.join("parent/child")).unwrap();
+        fs::create_dir_all(dir.join("other")).unwrap();
+
+        let level = |path: &Path, open: bool| Level {
+            dir: if open { Some(Dir::open(path).unwrap()) } else { None },
+            name: path.file_name().unwrap().to_os_string(),
+            path: path.to_path_buf(),
+            stat: Dir::open(path).unwrap().stat().unwrap(),
+            kept: HashSet::new(),
+        };
+        let child = level(&dir.join("parent/child"), true);
+
+        let mut parent = level(&dir.join("parent"), false);
+        reopen(&mut parent, &child).unwrap();
+        assert!(parent.dir.is_some());
+
+        // The parent is no longer the directory of `..` of the child
+        let mut moved = level(&dir.join("other"), false);
+        moved.path = dir.join("parent");
+        let err = reopen(&mut moved, &child).unwrap_err();
+        assert!(err.to_string().contains("was moved while it was removed"));
+        assert!(moved.dir.is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dots() {
+        assert!(is_dot(Path::new(".")));
+        assert!(is_dot(Path::new("..")));
+        assert!(is_dot(Path::new("a/.")));
+        assert!(is_dot(Path::new("a/../")));
+        assert!(!is_dot(Path::new("a")));
+        assert!(!is_dot(Path::new(".a/")));
+        assert!(!is_dot(Path::new("/")));
+    }
+}
@@ -0,0 +1,238 @@
+//! The removal of the subdirectories of a directory at the same time, with `--threads`.
+//!
+//! The directory is read by the main thread, that removes its files and opens its
+//! subdirectories, whose contents are removed by the workers, each with the file
+//! descriptors of its own directories. The messages of each entry are kept until the
+//! ones of the entries before it are printed, so they are printed in the same order as
+//! without threads.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsString,
+    panic,
+    path::PathBuf,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+use coreutils_core::{libc, os::dir::Dir};
+
+use super::{is_dir, Device, Level, Message, Remover};
+
+/// A subdirectory whose contents were removed by a worker.
+struct Done {
+    /// The position of the subdirectory in its parent.
+    index: usize,
+    name: OsString,
+    path: PathBuf,
+    stat: libc::stat,
+    /// If its contents were removed, like [`remove_contents`](Remover::remove_contents).
+    removed: Option<bool>,
+    messages: Vec<Message>,
+}
+
+/// The progress of the removal of the entries of a directory.
+struct Progress {
+    /// The messages of the entries that can't be printed yet, by their position.
+    pending: BTreeMap<usize, Vec<Message>>,
+    /// The positions of the subdirectories that the workers are removing.
+    running: BTreeSet<usize>,
+    success: bool,
+    emptied: bool,
+}
+
+impl Remover {
+    /// Remove the contents of the directory of the `level`, on the `device`, like
+    /// [`remove_contents`](Remover::remove_contents), but with the workers of
+    /// `--threads` removing the contents of its subdirectories.
+    pub(super) fn remove_parallel(&self, level: Level, device: Device) -> Option<bool> {
+        // The subdirectories waiting for a worker are open, so there are only a few
+        let (jobs, queue) = mpsc::sync_channel::<(usize, Level)>(self.flags.threads);
+        let queue = Arc::new(Mutex::new(queue));
+        let (done, finished) = mpsc::channel();
+
+        let workers: Vec<_> = (0..self.flags.threads)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                let done = done.clone();
+                let worker = self.buffered();
+
+                thread::spawn(move || loop {
+                    // The queue is only locked to take a job
+                    let job = queue.lock().unwrap().recv();
+                    let (index, level) = match job {
+                        Ok(job) => job,
+                        Err(_) => break,
+                    };
+
+                    let (name, path, stat) = (level.name.clone(), level.path.clone(), level.stat);
+                    let removed = worker.remove_contents(level, device);
+                    let messages = worker.take_log();
+
+                    if done.send(Done { index, name, path, stat, removed, messages }).is_err() {
+                        break;
+                    }
+                })
+            })
+            .collect();
+        drop(done);
+
+        let main = self.buffered();
+        let mut progress = Progress {
+            pending: BTreeMap::new(),
+            running: BTreeSet::new(),
+            success: true,
+            emptied: true,
+        };
+
+        // A new level is always open
+        let mut dir = level.dir.unwrap();
+
+        for index in 0.. {
+            let entry = match dir.read() {
+                Some(Ok(entry)) => entry,
+                Some(Err(err)) => {
+                    main.error(format!(
+                        "cannot read directory '{}': {}",
+                        level.path.display(),
+                        err
+                    ));
+                    progress.record(None);
+                    progress.push(index, main.take_log());
+                    break;
+                },
+                None => break,
+            };
+            let path = level.path.join(&entry);
+
+            match dir.stat_at(&entry, false) {
+                Ok(stat) if is_dir(&stat) => {
+                    match main.enter(&dir, &entry, &path, &stat, Some(device)) {
+                        Some(Ok(child)) => {
+                            // The workers only stop early if one of them panicked
+                            if jobs.send((index, child)).is_err() {
+                                break;
+                            }
+                            progress.running.insert(index);
+                        },
+                        Some(Err(_)) => progress.record(None),
+                        None => progress.record(Some(false)),
+                    }
+                },
+                Ok(stat) => progress.record(main.remove_file(&dir, &entry, &path, &stat)),
+                Err(err) => {
+                    main.error(format!("cannot remove '{}': {}", path.display(), err));
+                    progress.record(None);
+                },
+            }
+
+            progress.push(index, main.take_log());
+
+            for done in finished.try_iter() {
+                progress.finish(&main, &dir, done);
+            }
+        }
+        drop(jobs);
+
+        for done in finished.iter() {
+            progress.finish(&main, &dir, done);
+        }
+
+        for worker in workers {
+            worker.join().unwrap_or_else(|err| panic::resume_unwind(err));
+        }
+
+        if progress.success {
+            Some(progress.emptied)
+        } else {
+            None
+        }
+    }
+}
+
+impl Progress {
+    /// Record the result of the removal of a entry, like the one of
+    /// [`remove_file`](Remover::remove_file).
+    fn record(&mut self, removed: Option<bool>) {
+        match removed {
+            Some(true) => {},
+            Some(false) => self.emptied = false,
+            None => {
+                self.success = false;
+                self.emptied = false;
+            },
+        }
+    }
+
+    /// Remove the subdirectory of the `done` worker from `dir`, if its contents were
+    /// removed, with the `main` remover.
+    fn finish(&mut self, main: &Remover, dir: &Dir, done: Done) {
+        self.running.remove(&done.index);
+
+        let removed = match done.removed {
+            Some(true) => main.remove_dir(dir, &done.name, &done.path, &done.stat),
+            removed => removed,
+        };
+        self.record(removed);
+
+        let mut messages = done.messages;
+        messages.extend(main.take_log());
+        self.push(done.index, messages);
+    }
+
+    /// Add the `messages` of the entry in the `index`, and print all the ones that have
+    /// no subdirectory being removed before them.
+    fn push(&mut self, index: usize, messages: Vec<Message>) {
+        if !messages.is_empty() {
+            self.pending.entry(index).or_default().extend(messages);
+        }
+
+        let first_running = self.running.iter().next().copied();
+
+        while let Some(&index) = self.pending.keys().next() {
+            if matches!(first_running, Some(running) if running < index) {
+                break;
+            }
+
+            for message in self.pending.remove(&index).unwrap() {
+                message.print();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_order() {
+        let mut progress = Progress {
+            pending: BTreeMap::new(),
+            running: BTreeSet::new(),
+            success: true,
+            emptied: true,
+        };
+        progress.running.insert(1);
+        progress.running.insert(3);
+
+        // The messages after a running subdirectory wait for it
+        progress.push(0, vec![Message::Out("0".to_string())]);
+        progress.push(2, vec![Message::Err("2".to_string())]);
+        progress.push(4, vec![Message::Err("4".to_string())]);
+        assert_eq!(progress.pending.keys().collect::<Vec<_>>(), [&2, &4]);
+
+        progress.running.remove(&1);
+        progress.record(None);
+        progress.push(1, vec![Message::Err("1".to_string())]);
+        assert_eq!(progress.pending.keys().collect::<Vec<_>>(), [&4]);
+
+        progress.running.remove(&3);
+        progress.record(Some(true));
+        progress.push(3, Vec::new());
+        assert!(progress.pending.is_empty());
+
+        // A single failure is the failure of the whole directory
+        assert!(!progress.success);
+        assert!(!progress.emptied);
+    }
+}
@@ -0,0 +1,256 @@
+//! The removal of the files, and of the directories with their contents, through their
+//! paths, on the systems without the directories relative to file descriptors.
+//!
+//! The links to directories, as the junctions, are removed without entering them, so the
+//! trees never lead to other file systems and `--one-file-system` has no effect. The
+//! trees are removed by a single thread, whatever `--threads` is.
+use std::{
+    fmt::Display,
+    fs::{self, Metadata},
+    io,
+    path::Path,
+};
+
+use coreutils_core::input::Input;
+
+use crate::RmFlags;
+
+/// Removes the files with the flags.
+pub(crate) struct Remover {
+    flags: RmFlags,
+}
+
+impl Remover {
+    pub(crate) fn new(flags: RmFlags) -> Self {
+        Remover { flags }
+    }
+
+    /// Remove the file of the command line in `path`, returning `false` if it, or the
+    /// contents of a directory, could not be removed.
+    pub(crate) fn remove(&self, path: &Path) -> bool {
+        if is_dot(path) {
+            self.error(format!(
+                "refusing to remove '.' or '..' directory: skipping '{}'",
+                path.display()
+            ));
+            return false;
+        }
+
+        let metadata = match fs::symlink_metadata(path) {
+            Ok(metadata) => metadata,
+            Err(err) if self.missing(&err) => return true,
+            Err(err) => {
+                self.error(format!("cannot remove '{}': {}", path.display(), err));
+                return false;
+            },
+        };
+
+        if !metadata.is_dir() {
+            return self.remove_file(path, &metadata).is_some();
+        }
+
+        if self.flags.recursive {
+            if self.flags.preserve_root && is_root(path) {
+                self.error(format!(
+                    "it is dangerous to operate recursively on '{}'",
+                    path.display()
+                ));
+                self.error("use --no-preserve-root to override this failsafe");
+                return false;
+            }
+
+            return self.remove_tree(path, &metadata).is_some();
+        }
+
+        if !self.flags.dirs {
+            self.error(format!("cannot remove '{}': Is a directory", path.display()));
+            return false;
+        }
+
+        self.remove_dir(path, &metadata).is_some()
+    }
+
+    /// Remove the directory in `path`, with the `metadata`, and all its contents. Returns
+    /// `Some(true)` if all of them were removed, `Some(false)` if the user didn't want
+    /// some of them removed, and `None` if any of them failed.
+    fn remove_tree(&self, path: &Path, metadata: &Metadata) -> Option<bool> {
+        let entries: Vec<_> = match fs::read_dir(path) {
+            Ok(entries) => entries.collect(),
+            Err(err) => {
+                self.error(format!("cannot remove '{}': {}", path.display(), err));
+                return None;
+            },
+        };
+
+        if self.flags.interactive && !entries.is_empty() {
+            let protected =
+                if metadata.permissions().readonly() { "write-protected " } else { "" };
+
+            if !ask(&format!("descend into {}directory '{}'", protected, path.display())) {
+                return Some(false);
+            }
+        }
+
+        let mut removed = Some(true);
+
+        for entry in entries {
+            let entry_path = match entry {
+                Ok(entry) => entry.path(),
+                Err(err) => {
+                    self.error(format!("cannot read directory '{}': {}", path.display(), err));
+                    removed = None;
+                    continue;
+                },
+            };
+
+            let entry_removed = match fs::symlink_metadata(&entry_path) {
+                Ok(metadata) if metadata.is_dir() => self.remove_tree(&entry_path, &metadata),
+                Ok(metadata) => self.remove_file(&entry_path, &metadata),
+                Err(err) if self.missing(&err) => Some(true),
+                Err(err) => {
+                    self.error(format!("cannot remove '{}': {}", entry_path.display(), err));
+                    None
+                },
+            };
+
+            removed = removed.and_then(|removed| entry_removed.map(|entry| removed && entry));
+        }
+
+        match removed {
+            Some(true) => self.remove_dir(path, metadata),
+            removed => removed,
+        }
+    }
+
+    /// Remove the file in `path`, that is not a directory. Returns `Some(false)` if the
+    /// user didn't want it removed, and `None` if it failed.
+    fn remove_file(&self, path: &Path, metadata: &Metadata) -> Option<bool> {
+        if !self.confirm(path, metadata) {
+            return Some(false);
+        }
+
+        let removed = match fs::remove_file(path) {
+            // The links to directories are removed like the directories
+            Err(_) if metadata.file_type().is_symlink() => fs::remove_dir(path),
+            removed => removed,
+        };
+
+        match removed {
+            Ok(_) => {
+                if self.flags.verbose {
+                    println!("removed '{}'", path.display());
+                }
+                Some(true)
+            },
+            Err(err) if self.missing(&err) => Some(true),
+            Err(err) => {
+                self.error(format!("cannot remove '{}': {}", path.display(), err));
+                None
+            },
+        }
+    }
+
+    /// Remove the empty directory in `path`, like [`remove_file`](Remover::remove_file).
+    fn remove_dir(&self, path: &Path, metadata: &Metadata) -> Option<bool> {
+        if !self.confirm(path, metadata) {
+            return Some(false);
+        }
+
+        match fs::remove_dir(path) {
+            Ok(_) => {
+                if self.flags.verbose {
+                    println!("removed directory '{}'", path.display());
+                }
+                Some(true)
+            },
+            Err(err) if self.missing(&err) => Some(true),
+            Err(err) => {
+                self.error(format!("cannot remove '{}': {}", path.display(), err));
+                None
+            },
+        }
+    }
+
+    /// Returns `true` if the file in `path`, with the `metadata`, is removed, asking the
+    /// user before removing it with `-i`, or if it is read-only, unless `-f`.
+    fn confirm(&self, path: &Path, metadata: &Metadata) -> bool {
+        if self.flags.force {
+            return true;
+        }
+
+        let protected = metadata.permissions().readonly();
+
+        if !(self.flags.interactive || protected) {
+            return true;
+        }
+
+        let protected = if protected { "write-protected " } else { "" };
+
+        ask(&format!("remove {}{} '{}'", protected, file_type(metadata), path.display()))
+    }
+
+    /// Returns `true` if the `err` is of a file that doesn't exist, that is ignored with
+    /// `-f`.
+    fn missing(&self, err: &io::Error) -> bool {
+        self.flags.force && err.kind() == io::ErrorKind::NotFound
+    }
+
+    fn error(&self, msg: impl Display) {
+        eprintln!("rm: {}", msg);
+    }
+}
+
+/// Ask the user the `question`, returning `true` if the answer is affirmative.
+fn ask(question: &str) -> bool {
+    Input::new()
+        .with_msg(&format!("rm: {}? ", question))
+        .with_err_msg("rm: could not read user input")
+        .is_affirmative()
+}
+
+/// The type of the file of the `metadata`, as told in the questions.
+fn file_type(metadata: &Metadata) -> &'static str {
+    let file_type = metadata.file_type();
+
+    if file_type.is_file() && metadata.len() == 0 {
+        "regular empty file"
+    } else if file_type.is_file() {
+        "regular file"
+    } else if file_type.is_dir() {
+        "directory"
+    } else if file_type.is_symlink() {
+        "symbolic link"
+    } else {
+        "weird file"
+    }
+}
+
+/// Returns `true` if the directory in `path` is the root of its drive.
+fn is_root(path: &Path) -> bool {
+    matches!(fs::canonicalize(path), Ok(path) if path.parent().is_none())
+}
+
+/// Returns `true` if the last component of the `path` is `.` or `..`, that are not
+/// removed.
+fn is_dot(path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    let trimmed = path.trim_end_matches(&['/', '\\'][..]);
+    let name = trimmed.rsplit(&['/', '\\'][..]).next().unwrap_or(trimmed);
+
+    name == "." || name == ".."
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dots() {
+        assert!(is_dot(Path::new(".")));
+        assert!(is_dot(Path::new("a\\..")));
+        assert!(is_dot(Path::new("a/.\\")));
+        assert!(!is_dot(Path::new("a")));
+        assert!(!is_dot(Path::new(".a\\")));
+        assert!(!is_dot(Path::new("C:\\")));
+    }
+}
@@ -20,12 +20,23 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
         .arg(
             Arg::with_name("ignore-fail-nonempty")
                 .help("Ignore each failure that is solely because a directory is non-empty.")
-                .long("ignore-fail-on-nonempty")
+                .long_help(
+                    "Ignore each failure that is solely because a directory is non-empty.\n\nWith \
+                     -p the removal of the parents stops at the first non-empty one without an \
+                     error.",
+                )
+                .long("ignore-fail-on-non-empty")
+                .alias("ignore-fail-on-nonempty")
                 .short("I"),
         )
         .arg(
             Arg::with_name("parents")
                 .help("Remove DIRECTORY and its ancestors.")
+                .long_help(
+                    "Remove DIRECTORY and its ancestors.\n\nEach component of the path is removed \
+                     after the one after it, so 'rmdir -p a/b/c' is like 'rmdir a/b/c a/b a', \
+                     stopping at the first that fails.",
+                )
                 .long("parents")
                 .short("p"),
         )
@@ -1,30 +1,29 @@
-use std::{
-    fs, io,
-    path::{Path, PathBuf},
-    process,
-};
+use std::{fs, io, path::Path, process};
 
 use clap::ArgMatches;
+use coreutils_core::libc;
 
 mod cli;
 
 fn main() {
     let matches = cli::create_app().get_matches();
 
+    let flags = RmDirFlags::from_matches(&matches);
+
     // "DIRECTORY" is required, so it is ok to unwrap
-    let dirs: Vec<PathBuf> = matches.values_of("DIRECTORY").unwrap().map(PathBuf::from).collect();
+    let dirs = matches.values_of_os("DIRECTORY").unwrap();
 
-    let flags = RmDirFlags::from_matches(&matches);
-    let mut ret_val = 0;
+    let mut success = true;
 
     for dir in dirs {
-        if let Err(err) = rmdir(&dir, flags) {
-            eprintln!("rmdir: failed to remove {}: {}", err.0.display(), err.1);
-            ret_val = 1;
-        };
+        if !rmdir(Path::new(dir), flags) {
+            success = false;
+        }
     }
 
-    process::exit(ret_val);
+    if !success {
+        process::exit(1);
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -44,87 +43,92 @@ impl RmDirFlags {
     }
 }
 
-#[derive(Debug)]
-struct RmdirError(PathBuf, io::Error);
-
-fn rmdir(dir: &Path, flags: RmDirFlags) -> Result<(), RmdirError> {
-    let full_dir = match dir.canonicalize() {
-        Ok(f) => f,
-        Err(err) => return Err(RmdirError(dir.to_path_buf(), err)),
-    };
-
-    if flags.parents {
-        let empty_path = Path::new("");
-        let mut path = dir.to_path_buf();
-        loop {
-            let full_path = match dir.canonicalize() {
-                Ok(f) => f,
-                Err(err) => return Err(RmdirError(path, err)),
-            };
-
-            if path == empty_path {
-                return Ok(()); // there are no more parents
-            }
-
-            // For verbose we display the full path
-            if flags.verbose {
-                println!("rmdir: removing directory '{}'", full_path.display());
-            }
-
-            if flags.ignore {
-                match fs::remove_dir_all(&path) {
-                    Ok(_) => {
-                        if flags.verbose {
-                            println!(
-                                "rmdir: removed all {} directory content",
-                                full_path.display()
-                            );
-                        }
-                    },
-                    Err(err) => return Err(RmdirError(full_path, err)),
-                }
-            } else {
-                match fs::remove_dir(&path) {
-                    Ok(_) => {
-                        if flags.verbose {
-                            println!("rmdir: removed directory {}", full_path.display());
-                        }
-                    },
-                    Err(err) => return Err(RmdirError(full_path, err)),
-                }
-            }
+/// Remove the directory in `dir`, and its parents with `-p`, returning `false` if it
+/// failed.
+fn rmdir(dir: &Path, flags: RmDirFlags) -> bool {
+    if flags.verbose {
+        println!("rmdir: removing directory, '{}'", dir.display());
+    }
 
-            if !path.pop() {
-                return Ok(()); // there are no more parents
-            }
-        }
-    } else if !flags.parents && flags.ignore {
-        if flags.verbose {
-            println!("rmdir: removing directory '{}'", full_dir.display());
+    if let Err(err) = fs::remove_dir(dir) {
+        if is_ignorable(&err, dir, flags) {
+            return true;
         }
 
-        match fs::remove_dir_all(&dir) {
-            Ok(_) => {
-                if flags.verbose {
-                    println!("rmdir: removed all {} directory content", full_dir.display());
-                }
-            },
-            Err(err) => return Err(RmdirError(full_dir, err)),
+        if is_symlink_with_slash(&err, dir) {
+            eprintln!("rmdir: failed to remove '{}': Symbolic link not followed", dir.display());
+        } else {
+            eprintln!("rmdir: failed to remove '{}': {}", dir.display(), err);
         }
-    } else {
+
+        return false;
+    }
+
+    if !flags.parents {
+        return true;
+    }
+
+    for parent in parents(dir) {
         if flags.verbose {
-            println!("rmdir: removing directory '{}'", full_dir.display());
+            println!("rmdir: removing directory, '{}'", parent.display());
         }
 
-        match fs::remove_dir(&dir) {
-            Ok(_) => {
-                if flags.verbose {
-                    println!("rmdir: removed directory {}", full_dir.display());
-                }
-            },
-            Err(err) => return Err(RmdirError(full_dir, err)),
+        if let Err(err) = fs::remove_dir(parent) {
+            // The removal of the parents stops quietly at the one that is not empty
+            if is_ignorable(&err, parent, flags) {
+                return true;
+            }
+
+            eprintln!("rmdir: failed to remove directory '{}': {}", parent.display(), err);
+            return false;
         }
     }
 
-    Ok(())
+    true
+}
+
+/// Returns `true` if the `err` of the removal of `dir` is ignored with
+/// `--ignore-fail-on-non-empty`, because the directory is not empty.
+fn is_ignorable(err: &io::Error, dir: &Path, flags: RmDirFlags) -> bool {
+    if !flags.ignore {
+        return false;
+    }
+
+    match err.raw_os_error() {
+        Some(libc::ENOTEMPTY) | Some(libc::EEXIST) => true,
+        // These are only given for the directories that are not empty by some systems
+        Some(libc::EACCES) | Some(libc::EPERM) | Some(libc::EROFS) | Some(libc::EBUSY) => {
+            fs::read_dir(dir).ok().and_then(|mut entries| entries.next()).is_some()
+        },
+        _ => false,
+    }
+}
+
+/// Returns `true` if the `err` of the removal of `dir` is because it is a symbolic link
+/// to a directory with a trailing slash, that is not followed.
+fn is_symlink_with_slash(err: &io::Error, dir: &Path) -> bool {
+    err.raw_os_error() == Some(libc::ENOTDIR)
+        && dir.to_string_lossy().ends_with('/')
+        && matches!(fs::symlink_metadata(dir.components().as_path()),
+            Ok(metadata) if metadata.file_type().is_symlink())
+        && dir.is_dir()
+}
+
+/// The parents of the `dir` removed with `-p`, from the last component to the first one,
+/// as they are written in it.
+fn parents(dir: &Path) -> Vec<&Path> {
+    dir.ancestors().skip(1).take_while(|parent| !parent.as_os_str().is_empty()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parent_directories() {
+        assert_eq!(parents(Path::new("a/b/c/")), [Path::new("a/b"), Path::new("a")]);
+        assert_eq!(parents(Path::new("x//y///z")), [Path::new("x//y"), Path::new("x")]);
+        assert_eq!(parents(Path::new("/a/b")), [Path::new("/a"), Path::new("/")]);
+        assert!(parents(Path::new("a")).is_empty());
+    }
 }
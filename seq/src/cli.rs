@@ -1,5 +1,7 @@
 use clap::{
-    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+    crate_authors, crate_description, crate_name, crate_version, App,
+    AppSettings::{AllowNegativeNumbers, ColoredHelp},
+    Arg,
 };
 
 pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
@@ -10,9 +12,21 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
         .help_message("Display help information.")
         .version_message("Display version information.")
         .help_short("?")
-        .settings(&[ColoredHelp])
+        .settings(&[ColoredHelp, AllowNegativeNumbers])
         .usage("seq [FLAGS] [OPTIONS] [FIRST [INCREMENT]] <LAST>")
-        .arg(Arg::with_name("FIRST INCREMENT LAST").required(true).hidden(true))
+        .arg(Arg::with_name("FIRST INCREMENT LAST").multiple(true).required(true).hidden(true))
+        .arg(
+            Arg::with_name("FORMAT")
+                .short("f")
+                .long("format")
+                .help("Use printf style floating-point FORMAT.")
+                .long_help(
+                    "Use printf style floating-point FORMAT.\n\nThe FORMAT must have exactly one \
+                     of the %e, %f or %g directives (or their upper case), with the optional \
+                     flags, width and precision. Use %% for a literal %.",
+                )
+                .value_name("FORMAT"),
+        )
         .arg(
             Arg::with_name("SEPARATOR")
                 .short("s")
@@ -29,7 +43,7 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Equalize the widths of all numbers by padding with zeros as necessary.")
                 .long_help(
                     "Equalize the widths of all numbers by padding with zeros as \
-                     necessary.\n\nThis option has no effect with the -f option.",
+                     necessary.\n\nThis option can't be used with the -f option.",
                 )
                 .takes_value(false),
         )
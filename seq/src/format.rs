@@ -0,0 +1,304 @@
+//! The printf(3) like `-f` format of the numbers.
+use std::fmt;
+
+/// A format with a single floating point directive, like `%.2f`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Format {
+    prefix: String,
+    spec: Spec,
+    suffix: String,
+}
+
+/// The floating point directive of a [`Format`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Spec {
+    left_align: bool,
+    plus_sign: bool,
+    space_sign: bool,
+    alternate: bool,
+    zero_pad: bool,
+    width: usize,
+    precision: Option<usize>,
+    conversion: char,
+}
+
+/// The error of a invalid [`Format`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatError {
+    NoDirective(String),
+    TooManyDirectives(String),
+    UnknownDirective(String, String),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::NoDirective(format) => write!(f, "format '{}' has no % directive", format),
+            FormatError::TooManyDirectives(format) => {
+                write!(f, "format '{}' has too many % directives", format)
+            },
+            FormatError::UnknownDirective(format, directive) => {
+                write!(f, "format '{}' has unknown {} directive", format, directive)
+            },
+        }
+    }
+}
+
+impl Format {
+    /// Parse a format with exactly one `%` directive of the `e`, `f` or `g` conversions
+    /// (or their upper case), besides the `%%`.
+    pub fn parse(format: &str) -> Result<Self, FormatError> {
+        let (prefix, rest) = split_literal(format);
+        let rest = match rest {
+            Some(rest) => rest,
+            None => return Err(FormatError::NoDirective(format.to_string())),
+        };
+
+        let mut chars = rest.char_indices().peekable();
+        let mut spec = Spec::default();
+
+        while let Some(&(_, c)) = chars.peek() {
+            match c {
+                '-' => spec.left_align = true,
+                '+' => spec.plus_sign = true,
+                ' ' => spec.space_sign = true,
+                '#' => spec.alternate = true,
+                '0' => spec.zero_pad = true,
+                // The thousands grouping, that has no effect on the C locale
+                '\'' => {},
+                _ => break,
+            }
+            chars.next();
+        }
+
+        let number = |chars: &mut std::iter::Peekable<std::str::CharIndices>| {
+            let mut n: usize = 0;
+            while let Some(&(_, c)) = chars.peek() {
+                match c.to_digit(10) {
+                    Some(digit) => n = n.saturating_mul(10).saturating_add(digit as usize),
+                    None => break,
+                }
+                chars.next();
+            }
+            n
+        };
+
+        spec.width = number(&mut chars);
+        if let Some(&(_, '.')) = chars.peek() {
+            chars.next();
+            spec.precision = Some(number(&mut chars));
+        }
+
+        let (end, conversion) = match chars.next() {
+            Some((i, c)) => (i + c.len_utf8(), c),
+            None => {
+                let directive = format!("%{}", rest);
+                return Err(FormatError::UnknownDirective(format.to_string(), directive));
+            },
+        };
+
+        if !matches!(conversion, 'e' | 'E' | 'f' | 'F' | 'g' | 'G') {
+            let directive = format!("%{}", &rest[..end]);
+            return Err(FormatError::UnknownDirective(format.to_string(), directive));
+        }
+        spec.conversion = conversion;
+
+        let (suffix, extra) = split_literal(&rest[end..]);
+        if extra.is_some() {
+            return Err(FormatError::TooManyDirectives(format.to_string()));
+        }
+
+        Ok(Format { prefix, spec, suffix })
+    }
+
+    /// Format `value`.
+    pub fn format(&self, value: f64) -> String {
+        let mut output = self.prefix.clone();
+        output.push_str(&self.spec.format(value));
+        output.push_str(&self.suffix);
+        output
+    }
+}
+
+/// Split `format` at the first `%` directive, replacing the `%%` before it by `%`.
+/// Returns the literal text and the directive, without the `%`, if there is one.
+fn split_literal(format: &str) -> (String, Option<&str>) {
+    let mut literal = String::new();
+    let mut rest = format;
+
+    while let Some(pos) = rest.find('%') {
+        literal.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+
+        match rest.strip_prefix('%') {
+            Some(after) => {
+                literal.push('%');
+                rest = after;
+            },
+            None => return (literal, Some(rest)),
+        }
+    }
+
+    literal.push_str(rest);
+    (literal, None)
+}
+
+impl Spec {
+    fn format(&self, value: f64) -> String {
+        let upper = self.conversion.is_ascii_uppercase();
+
+        let mut digits = if value.is_nan() {
+            "nan".to_string()
+        } else if value.is_infinite() {
+            "inf".to_string()
+        } else {
+            let precision = self.precision.unwrap_or(6);
+            match self.conversion.to_ascii_lowercase() {
+                'e' => format_exponent(value.abs(), precision, self.alternate),
+                'f' => format_fixed(value.abs(), precision, self.alternate),
+                _ => format_general(value.abs(), precision, self.alternate),
+            }
+        };
+        if upper {
+            digits = digits.to_ascii_uppercase();
+        }
+
+        let sign = if value.is_sign_negative() && !value.is_nan() {
+            "-"
+        } else if self.plus_sign {
+            "+"
+        } else if self.space_sign {
+            " "
+        } else {
+            ""
+        };
+
+        let len = sign.len() + digits.len();
+        if len >= self.width {
+            return format!("{}{}", sign, digits);
+        }
+
+        let padding = self.width - len;
+        if self.left_align {
+            format!("{}{}{}", sign, digits, " ".repeat(padding))
+        } else if self.zero_pad && value.is_finite() {
+            format!("{}{}{}", sign, "0".repeat(padding), digits)
+        } else {
+            format!("{}{}{}", " ".repeat(padding), sign, digits)
+        }
+    }
+}
+
+/// Format the positive `value` as `%f`.
+fn format_fixed(value: f64, precision: usize, alternate: bool) -> String {
+    let mut output = format!("{:.*}", precision, value);
+    if alternate && precision == 0 {
+        output.push('.');
+    }
+    output
+}
+
+/// Format the positive `value` as `%e`.
+fn format_exponent(value: f64, precision: usize, alternate: bool) -> String {
+    let output = format!("{:.*e}", precision, value);
+    let (mantissa, exponent) = output.split_at(output.find('e').unwrap());
+    let exponent: i32 = exponent[1..].parse().unwrap();
+
+    let point = if alternate && precision == 0 { "." } else { "" };
+    let sign = if exponent < 0 { '-' } else { '+' };
+    format!("{}{}e{}{:02}", mantissa, point, sign, exponent.abs())
+}
+
+/// Format the positive `value` as `%g`, `%e` for the big or small exponents and `%f`
+/// otherwise, without the trailing zeros.
+fn format_general(value: f64, precision: usize, alternate: bool) -> String {
+    let precision = precision.max(1);
+
+    // The exponent after the rounding to the precision
+    let exponent = if value == 0.0 {
+        0
+    } else {
+        let output = format!("{:.*e}", precision - 1, value);
+        output[output.find('e').unwrap() + 1..].parse().unwrap()
+    };
+
+    let output = if exponent < -4 || exponent >= precision as i32 {
+        format_exponent(value, precision - 1, alternate)
+    } else {
+        format_fixed(value, (precision as i32 - 1 - exponent) as usize, alternate)
+    };
+
+    if alternate {
+        return output;
+    }
+
+    let (mantissa, exponent) = match output.find('e') {
+        Some(pos) => output.split_at(pos),
+        None => (output.as_str(), ""),
+    };
+    let mantissa = if mantissa.contains('.') {
+        mantissa.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        mantissa
+    };
+    format!("{}{}", mantissa, exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(format: &str, value: f64) -> String {
+        Format::parse(format).unwrap().format(value)
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Format::parse("abc"), Err(FormatError::NoDirective("abc".to_string())));
+        assert_eq!(Format::parse("%%"), Err(FormatError::NoDirective("%%".to_string())));
+        assert_eq!(Format::parse("%f%f"), Err(FormatError::TooManyDirectives("%f%f".to_string())));
+        assert_eq!(
+            Format::parse("%5d"),
+            Err(FormatError::UnknownDirective("%5d".to_string(), "%5d".to_string()))
+        );
+        assert_eq!(
+            Format::parse("a%"),
+            Err(FormatError::UnknownDirective("a%".to_string(), "%".to_string()))
+        );
+    }
+
+    #[test]
+    fn format_fixed() {
+        assert_eq!(format("%f", 1.5), "1.500000");
+        assert_eq!(format("%.2f%%", 3.0), "3.00%");
+        assert_eq!(format("%08.2f", -1.0), "-0001.00");
+        assert_eq!(format("%-6.1f|", 2.0), "2.0   |");
+        assert_eq!(format("%+.0f", 2.0), "+2");
+        assert_eq!(format("%#.0f", 2.0), "2.");
+        assert_eq!(format("x%5.1fy", 0.25), "x  0.2y");
+        assert_eq!(format("%F", f64::INFINITY), "INF");
+        assert_eq!(format("%05f", f64::NEG_INFINITY), " -inf");
+    }
+
+    #[test]
+    fn format_exponent() {
+        assert_eq!(format("%e", 1.0), "1.000000e+00");
+        assert_eq!(format("%.3E", 12345.0), "1.234E+04");
+        assert_eq!(format("%.1e", 0.00012), "1.2e-04");
+        assert_eq!(format("%.0e", 1e300), "1e+300");
+    }
+
+    #[test]
+    fn format_general() {
+        assert_eq!(format("%g", 1.0), "1");
+        assert_eq!(format("%g", 0.0001), "0.0001");
+        assert_eq!(format("%g", 0.00001), "1e-05");
+        assert_eq!(format("%g", 123456.0), "123456");
+        assert_eq!(format("%g", 1234567.0), "1.23457e+06");
+        assert_eq!(format("%.3g", 999.9), "1e+03");
+        assert_eq!(format("%G", 1e-10), "1E-10");
+        assert_eq!(format("%#g", 1.0), "1.00000");
+        assert_eq!(format("%g", 0.0), "0");
+        assert_eq!(format("%g", -0.0), "-0");
+    }
+}
@@ -1,6 +1,13 @@
-use std::process;
+use std::{
+    cmp::Ordering,
+    io::{self, BufWriter, Write},
+    process,
+};
 
 mod cli;
+mod format;
+
+use format::Format;
 
 fn main() {
     let matches = cli::create_app().get_matches();
@@ -8,27 +15,70 @@ fn main() {
     if let Some(values) = matches.values_of("FIRST INCREMENT LAST") {
         let args: Vec<&str> = values.collect();
         if args.len() > 3 {
-            eprintln!("seq: extra operand '{}'\n Try 'seq --help' for more information.", args[3]);
-            process::exit(1);
+            usage_error(&format!("extra operand '{}'", args[3]));
         }
         let separator = matches.value_of("SEPARATOR").map(String::from).unwrap();
-        let decimals = max_decimal_digits(&args);
-        let padding = if matches.is_present("WIDTH") { Some(max_digits(&args)) } else { None };
+        let equal_width = matches.is_present("WIDTH");
+
+        let format = matches.value_of("FORMAT").map(|format| {
+            Format::parse(format).unwrap_or_else(|err| {
+                eprintln!("seq: {}", err);
+                process::exit(1);
+            })
+        });
+        if format.is_some() && equal_width {
+            usage_error("format string may not be specified when printing equal width strings");
+        }
+
         let (first, inc, last) = find_operands(&args);
-        let valid_range = (first <= last && inc > 0.0) || (first >= last && inc < 0.0);
-        if valid_range {
-            let seq = Seq::new(first, inc, last, decimals, separator, padding);
-            for val in seq.into_iter() {
-                print!("{}", val);
+        let (first_arg, inc_arg, last_arg) = match args.len() {
+            1 => ("1", "1", args[0]),
+            2 => (args[0], "1", args[1]),
+            _ => (args[0], args[1], args[2]),
+        };
+
+        let stdout = io::stdout();
+        let mut output = BufWriter::new(stdout.lock());
+
+        let result = if format.is_none() && [first_arg, inc_arg, last_arg].iter().all(is_integer) {
+            let padding = if equal_width { Some(max_digits(&[first_arg, last_arg])) } else { None };
+            print_integers(first_arg, inc_arg, last_arg, &separator, padding, &mut output)
+        } else {
+            let format = match format {
+                Some(format) => Some(format),
+                // Numbers like `inf` don't have a precision to follow
+                None if [first_arg, inc_arg, last_arg]
+                    .iter()
+                    .any(|arg| precision(arg).is_none()) =>
+                {
+                    Some(Format::parse("%g").unwrap())
+                },
+                None => None,
+            };
+            let decimals = max_decimal_digits(&[first_arg, inc_arg]);
+            let padding = if equal_width { Some(max_digits(&[first_arg, last_arg])) } else { None };
+
+            let seq = Seq::new(first, inc, last, decimals, separator, padding).with_format(format);
+            print_sequence(seq, &mut output)
+        };
+
+        if let Err(err) = result.and_then(|_| output.flush()) {
+            // The output was closed, like `seq inf | head`
+            if err.kind() != io::ErrorKind::BrokenPipe {
+                eprintln!("seq: write error: {}", err);
+                process::exit(1);
             }
-            println!();
         }
     } else {
-        eprintln!("seq: missing operand\n Try 'seq --help' for more information.");
-        process::exit(1);
+        usage_error("missing operand");
     }
 }
 
+fn usage_error(msg: &str) -> ! {
+    eprintln!("seq: {}\nTry 'seq --help' for more information.", msg);
+    process::exit(1);
+}
+
 fn find_operands(args: &[&str]) -> (f64, f64, f64) {
     match args.len() {
         1 => (1.0, 1.0, parse_float(args[0])),
@@ -37,8 +87,7 @@ fn find_operands(args: &[&str]) -> (f64, f64, f64) {
             let inc = parse_float(args[1]);
 
             if inc == 0.0 {
-                eprintln!("seq: invalid zero increment value");
-                process::exit(1);
+                usage_error(&format!("invalid Zero increment value: '{}'", args[1]));
             }
 
             (parse_float(args[0]), inc, parse_float(args[2]))
@@ -47,10 +96,106 @@ fn find_operands(args: &[&str]) -> (f64, f64, f64) {
 }
 
 fn parse_float(s: &str) -> f64 {
-    s.parse::<f64>().unwrap_or_else(|_| {
-        eprintln!("seq: invalid floating point argument: {}", s);
-        process::exit(1);
-    })
+    match s.parse::<f64>() {
+        Ok(value) if value.is_nan() => {
+            usage_error(&format!("invalid 'not-a-number' argument: '{}'", s))
+        },
+        Ok(value) => value,
+        Err(_) => usage_error(&format!("invalid floating point argument: '{}'", s)),
+    }
+}
+
+/// Print the numbers of `seq`, ending the line if any was printed.
+fn print_sequence(seq: Seq, output: &mut impl Write) -> io::Result<()> {
+    let mut printed = false;
+    for val in seq.into_iter() {
+        output.write_all(val.as_bytes())?;
+        printed = true;
+    }
+
+    if printed {
+        output.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Print the sequence of the non negative integers `first` to `last`, without converting
+/// them to floating point, so big numbers are printed exactly.
+fn print_integers(
+    first: &str, inc: &str, last: &str, separator: &str, padding: Option<usize>,
+    output: &mut impl Write,
+) -> io::Result<()> {
+    let inc = trim_zeros(inc);
+    let last = trim_zeros(last);
+    let mut current = trim_zeros(first).to_vec();
+
+    if compare_integers(&current, last) == Ordering::Greater {
+        return Ok(());
+    }
+
+    loop {
+        if let Some(width) = padding {
+            for _ in current.len()..width {
+                output.write_all(b"0")?;
+            }
+        }
+        output.write_all(&current)?;
+
+        add_integers(&mut current, inc);
+        if compare_integers(&current, last) == Ordering::Greater {
+            break;
+        }
+        output.write_all(separator.as_bytes())?;
+    }
+
+    output.write_all(b"\n")
+}
+
+/// Whether `arg` is a integer that [`print_integers`] can handle.
+fn is_integer(arg: &&str) -> bool {
+    !arg.is_empty() && arg.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn trim_zeros(digits: &str) -> &[u8] {
+    let trimmed = digits.trim_start_matches('0');
+    if trimmed.is_empty() {
+        b"0"
+    } else {
+        trimmed.as_bytes()
+    }
+}
+
+/// Compare the digits of two integers without leading zeros.
+fn compare_integers(a: &[u8], b: &[u8]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// Add the digits of `inc` to the digits of `value`.
+fn add_integers(value: &mut Vec<u8>, inc: &[u8]) {
+    let mut carry = 0;
+    let mut inc = inc.iter().rev();
+
+    for digit in value.iter_mut().rev() {
+        let sum = (*digit - b'0') + inc.next().map_or(0, |d| d - b'0') + carry;
+        *digit = b'0' + sum % 10;
+        carry = sum / 10;
+    }
+
+    // The increment may have more digits than the value
+    let mut high = Vec::new();
+    for d in inc {
+        let sum = (d - b'0') + carry;
+        high.push(b'0' + sum % 10);
+        carry = sum / 10;
+    }
+    if carry > 0 {
+        high.push(b'0' + carry);
+    }
+
+    if !high.is_empty() {
+        high.reverse();
+        value.splice(..0, high);
+    }
 }
 
 struct Seq {
@@ -60,31 +205,129 @@ struct Seq {
     decimals: usize,
     seperator: String,
     padding: Option<usize>,
+    format: Option<Format>,
 }
 
 impl Seq {
     fn new(
         first: f64, inc: f64, last: f64, decimals: usize, seperator: String, padding: Option<usize>,
     ) -> Seq {
-        Seq { first, inc, last, decimals, seperator, padding }
+        Seq { first, inc, last, decimals, seperator, padding, format: None }
+    }
+
+    /// Use the printf like `format` instead of the decimals and padding.
+    fn with_format(mut self, format: Option<Format>) -> Seq {
+        self.format = format;
+        self
     }
 
     fn is_complete(&self, value: f64) -> bool {
         self.inc > 0.0 && value > self.last || self.inc < 0.0 && value < self.last
     }
+
+    fn format(&self, value: f64) -> String {
+        if let Some(ref format) = self.format {
+            return format.format(value);
+        }
+
+        let value = format!("{:.*}", self.decimals, value);
+        let digits = value.find('.').unwrap_or(value.len());
+
+        match self.padding {
+            Some(width) if width > digits => {
+                let mut padded = String::with_capacity(value.len() + (width - digits));
+
+                if let Some(val) = value.strip_prefix('-') {
+                    padded.push('-');
+                    padded.push_str(&"0".repeat(width - digits));
+                    padded.push_str(val);
+                } else {
+                    padded.push_str(&"0".repeat(width - digits));
+                    padded.push_str(&value);
+                }
+
+                padded
+            },
+            _ => value,
+        }
+    }
+}
+
+/// Scan a number operand, returning the width it's printed with and the number of decimal
+/// digits, or `None` if it has no precision, like `inf`.
+///
+/// The decimal digits are moved by the exponent, so `1e-2` has 2 decimal digits and
+/// `1.5e1` has none.
+fn scan(arg: &str) -> (usize, Option<usize>) {
+    let width = arg.len() as i64;
+    match arg.parse::<f64>() {
+        Ok(value) if value.is_finite() => {},
+        _ => return (arg.len(), None),
+    }
+
+    let exponent_pos = arg.find(&['e', 'E'][..]);
+    let mantissa = &arg[..exponent_pos.unwrap_or(arg.len())];
+    let decimal_point = mantissa.find('.');
+
+    let mut width = width;
+    let mut precision: i64 = 0;
+    if let Some(pos) = decimal_point {
+        let fraction = (mantissa.len() - pos - 1) as i64;
+        precision = fraction;
+        if fraction == 0 {
+            // `#.` is printed as `#`
+            width -= 1;
+        } else if pos == 0 || !mantissa.as_bytes()[pos - 1].is_ascii_digit() {
+            // `.#` and `-.#` are printed as `0.#` and `-0.#`
+            width += 1;
+        }
+    }
+
+    if let Some(pos) = exponent_pos {
+        let mut exponent: i64 = arg[pos + 1..].parse().unwrap_or(0);
+        let fraction = precision;
+        precision += if exponent < 0 { -exponent } else { -fraction.min(exponent) };
+
+        // The exponent is not printed
+        width -= (arg.len() - pos) as i64;
+        if exponent < 0 {
+            match decimal_point {
+                Some(point) if point + 1 == pos => width += 1,
+                Some(_) => {},
+                None => width += 1,
+            }
+            exponent = -exponent;
+        } else {
+            if decimal_point.is_some() && precision == 0 && width > 0 {
+                width -= 1;
+            }
+            exponent -= fraction.min(exponent);
+        }
+        width += exponent;
+    }
+
+    (width.max(0) as usize, Some(precision.max(0) as usize))
+}
+
+/// The number of decimal digits of `arg`, or `None` if it has no precision.
+fn precision(arg: &str) -> Option<usize> {
+    scan(arg).1
 }
 
 fn max_decimal_digits(args: &[&str]) -> usize {
     // args will never be empty and all elements are already validated as f64
-    args.iter()
-        .map(|v| v.len() - v.find('.').map(|pos| pos + 1).unwrap_or_else(|| v.len()))
-        .max()
-        .unwrap()
+    args.iter().map(|v| precision(v).unwrap_or(0)).max().unwrap()
 }
 
 fn max_digits(args: &[&str]) -> usize {
     // args will never be empty and each element is already validated as f64
-    args.iter().map(|v| v.find('.').unwrap_or_else(|| v.len())).max().unwrap()
+    args.iter()
+        .map(|v| match scan(v) {
+            (width, Some(precision)) if precision > 0 => width.saturating_sub(precision + 1),
+            (width, _) => width,
+        })
+        .max()
+        .unwrap()
 }
 
 impl IntoIterator for Seq {
@@ -92,50 +335,65 @@ impl IntoIterator for Seq {
     type Item = String;
 
     fn into_iter(self) -> Self::IntoIter {
-        let current = self.first;
-        SeqIterator { seq: self, current }
+        SeqIterator { seq: self, index: 0, next: None, done: false }
     }
 }
 
 struct SeqIterator {
     seq: Seq,
-    current: f64,
+    index: u64,
+    /// The number after the one being returned, to know if the separator is needed.
+    next: Option<String>,
+    done: bool,
+}
+
+impl SeqIterator {
+    /// The next number of the sequence, without the separator.
+    fn next_value(&mut self) -> Option<String> {
+        if self.done {
+            return None;
+        }
+
+        // Multiply instead of adding up the increments, so the rounding errors don't
+        // accumulate
+        let value = self.seq.first + self.index as f64 * self.seq.inc;
+        self.index += 1;
+
+        if !self.seq.is_complete(value) {
+            return Some(self.seq.format(value));
+        }
+        self.done = true;
+
+        // The last number may go past LAST due to the rounding, like `seq 0 0.1 0.3`, but
+        // still be printed as LAST
+        if self.index > 1 {
+            let formatted = self.seq.format(value);
+            let previous = self.seq.first + (self.index - 2) as f64 * self.seq.inc;
+
+            if let Ok(printed) = formatted.trim().parse::<f64>() {
+                if printed == self.seq.last && previous != printed {
+                    return Some(formatted);
+                }
+            }
+        }
+        None
+    }
 }
 
 impl Iterator for SeqIterator {
     type Item = String;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.seq.is_complete(self.current) {
-            None
-        } else {
-            let value = format!("{:.*}", self.seq.decimals, self.current);
-            let digits = value.find('.').unwrap_or_else(|| value.len());
-
-            let mut value = match self.seq.padding {
-                Some(width) if width > digits => {
-                    let mut padded = String::with_capacity(value.len() + (width - digits));
-
-                    if let Some(val) = value.strip_prefix('-') {
-                        padded.push('-');
-                        padded.push_str(&"0".repeat(width - digits));
-                        padded.push_str(val);
-                    } else {
-                        padded.push_str(&"0".repeat(width - digits));
-                        padded.push_str(&value);
-                    }
-
-                    padded
-                },
-                _ => value,
-            };
-
-            if !self.seq.is_complete(self.current + self.seq.inc) {
-                value.push_str(&self.seq.seperator);
-            }
-            self.current += self.seq.inc;
-            Some(value)
+        let mut value = match self.next.take() {
+            Some(value) => value,
+            None => self.next_value()?,
+        };
+
+        self.next = self.next_value();
+        if self.next.is_some() {
+            value.push_str(&self.seq.seperator);
         }
+        Some(value)
     }
 }
 
@@ -150,6 +408,8 @@ mod tests {
         assert_eq!(max_decimal_digits(&["1.22", "1.3", "1"]), 2);
         assert_eq!(max_decimal_digits(&["1", "1.3", "1.1"]), 1);
         assert_eq!(max_decimal_digits(&["1"]), 0);
+        assert_eq!(max_decimal_digits(&["1e-2", "0.5e-2"]), 3);
+        assert_eq!(max_decimal_digits(&["1.5e1", "1"]), 0);
     }
 
     #[test]
@@ -157,6 +417,8 @@ mod tests {
         assert_eq!(max_digits(&["1.22", "12.3", "1"]), 2);
         assert_eq!(max_digits(&["1.22", "12.3", "123"]), 3);
         assert_eq!(max_digits(&["1.22", "-152.3", "123"]), 4);
+        assert_eq!(max_digits(&[".5", "1"]), 1);
+        assert_eq!(max_digits(&["1e1", "5"]), 2);
     }
 
     #[test]
@@ -235,12 +497,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_generate_last_despite_rounding() {
+        assert_eq!(
+            Seq::new(0.0, 0.1, 0.3, 1, " ".to_owned(), None).into_iter().collect::<Vec<String>>(),
+            to_string(vec!["0.0 ", "0.1 ", "0.2 ", "0.3"])
+        );
+
+        assert_eq!(
+            Seq::new(0.0, 0.000001, 0.000003, 6, " ".to_owned(), None)
+                .into_iter()
+                .collect::<Vec<String>>(),
+            to_string(vec!["0.000000 ", "0.000001 ", "0.000002 ", "0.000003"])
+        );
+    }
+
+    #[test]
+    fn should_generate_formatted_sequence() {
+        let format = Format::parse("%05.1f").ok();
+        assert_eq!(
+            Seq::new(-1.0, 0.5, 0.0, 0, ",".to_owned(), None)
+                .with_format(format)
+                .into_iter()
+                .collect::<Vec<String>>(),
+            to_string(vec!["-01.0,", "-00.5,", "000.0"])
+        );
+    }
+
+    #[test]
+    fn should_print_integers() {
+        let mut output = Vec::new();
+        print_integers("18446744073709551614", "1", "18446744073709551617", ",", None, &mut output)
+            .unwrap();
+        assert_eq!(output, b"18446744073709551614,18446744073709551615,18446744073709551616,18446744073709551617\n");
+
+        let mut output = Vec::new();
+        print_integers("98", "2", "103", "\n", Some(3), &mut output).unwrap();
+        assert_eq!(output, b"098\n100\n102\n");
+
+        let mut output = Vec::new();
+        print_integers("5", "1", "3", "\n", None, &mut output).unwrap();
+        assert_eq!(output, b"");
+    }
+
+    #[test]
+    fn should_add_integers() {
+        let mut value = b"999".to_vec();
+        add_integers(&mut value, b"1");
+        assert_eq!(value, b"1000");
+
+        let mut value = b"5".to_vec();
+        add_integers(&mut value, b"12345");
+        assert_eq!(value, b"12350");
+    }
+
     #[test]
     fn should_output_with_same_first_and_last() {
         let mut cmd = Command::new("seq");
-        cmd.args(&["1"]).assert().stdout("1\n");
+        cmd.arg("1").assert().stdout("1\n");
 
         let mut cmd = Command::new("seq");
-        cmd.args(&["2", "2"]).assert().stdout("2\n");
+        cmd.arg("2").arg("2").assert().stdout("2\n");
     }
 }
@@ -0,0 +1,69 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("File to shuffle, or the lines to shuffle with -e.")
+                .long_help(
+                    "File to shuffle, or the lines to shuffle with -e.\n\nWith no FILE, or when \
+                     FILE is -, read standard input.",
+                )
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("echo").help("Treat each ARG as an input line.").short("e").long("echo"),
+        )
+        .arg(
+            Arg::with_name("input_range")
+                .value_name("LO-HI")
+                .help("Treat each number LO through HI as an input line.")
+                .short("i")
+                .long("input-range"),
+        )
+        .arg(
+            Arg::with_name("head_count")
+                .value_name("COUNT")
+                .help("Output at most COUNT lines.")
+                .short("n")
+                .long("head-count"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .value_name("FILE")
+                .help("Write result to FILE instead of standard output.")
+                .short("o")
+                .long("output"),
+        )
+        .arg(
+            Arg::with_name("random_source")
+                .value_name("FILE")
+                .help("Get random bytes from FILE.")
+                .long("random-source"),
+        )
+        .arg(
+            Arg::with_name("repeat")
+                .help("Output lines can be repeated.")
+                .long_help(
+                    "Output lines can be repeated.\n\nWithout -n, the lines are written until the \
+                     output is closed.",
+                )
+                .short("r")
+                .long("repeat"),
+        )
+        .arg(
+            Arg::with_name("zero_terminated")
+                .help("Line delimiter is NUL, not newline.")
+                .short("z")
+                .long("zero-terminated"),
+        )
+}
@@ -0,0 +1,493 @@
+use std::{
+    collections::HashMap,
+    error, fmt,
+    fs::File,
+    io::{self, prelude::*, BufReader, BufWriter},
+    process,
+};
+
+use clap::ArgMatches;
+use coreutils_core::random::RandomSource;
+
+mod cli;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    if let Err(err) = shuf(&matches) {
+        match err {
+            // The output was closed, like `shuf -r ... | head`
+            ShufError::Write(_, ref err) if err.kind() == io::ErrorKind::BrokenPipe => {},
+            _ => {
+                eprintln!("shuf: {}.", err);
+                process::exit(1);
+            },
+        }
+    }
+}
+
+/// The lines to shuffle.
+#[derive(Debug)]
+enum Input<'a> {
+    /// The operands of `-e`.
+    Echo(Vec<&'a str>),
+    /// The numbers from `LO` to `HI` of `-i`.
+    Range(u64, u64),
+    /// The lines of a file, or of the standard input if it is `-`.
+    File(&'a str),
+}
+
+#[derive(Debug)]
+struct ShufFlags<'a> {
+    input: Input<'a>,
+    head_count: Option<u64>,
+    repeat: bool,
+    delimiter: u8,
+    output: Option<&'a str>,
+    random_source: Option<&'a str>,
+}
+
+impl<'a> ShufFlags<'a> {
+    fn from_matches(matches: &'a ArgMatches) -> Result<Self, ShufError> {
+        let mut operands = matches.values_of("FILE").into_iter().flatten();
+
+        let input = match (matches.is_present("echo"), matches.value_of("input_range")) {
+            (true, Some(_)) => return Err(ShufError::EchoAndRange),
+            (true, None) => Input::Echo(operands.collect()),
+            (false, Some(range)) => {
+                if let Some(extra) = operands.next() {
+                    return Err(ShufError::ExtraOperand(extra.to_string()));
+                }
+                let (low, high) =
+                    parse_range(range).ok_or_else(|| ShufError::InvalidRange(range.to_string()))?;
+                Input::Range(low, high)
+            },
+            (false, None) => {
+                let file = operands.next().unwrap_or("-");
+                if let Some(extra) = operands.next() {
+                    return Err(ShufError::ExtraOperand(extra.to_string()));
+                }
+                Input::File(file)
+            },
+        };
+
+        let head_count = match matches.value_of("head_count") {
+            Some(count) => {
+                Some(count.parse().map_err(|_| ShufError::InvalidCount(count.to_string()))?)
+            },
+            None => None,
+        };
+
+        Ok(ShufFlags {
+            input,
+            head_count,
+            repeat: matches.is_present("repeat"),
+            delimiter: if matches.is_present("zero_terminated") { b'\0' } else { b'\n' },
+            output: matches.value_of("output"),
+            random_source: matches.value_of("random_source"),
+        })
+    }
+}
+
+/// Parse the `-i` range, `LO-HI`. `HI` can be `LO - 1` for a empty range.
+fn parse_range(range: &str) -> Option<(u64, u64)> {
+    let dash = range.find('-')?;
+    let (low, high) = (&range[..dash], &range[dash + 1..]);
+
+    let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !is_number(low) || !is_number(high) {
+        return None;
+    }
+
+    let (low, high): (u64, u64) = (low.parse().ok()?, high.parse().ok()?);
+    // The number of lines must fit in a u64
+    if high.checked_add(1)? < low || (low == 0 && high == u64::MAX) {
+        return None;
+    }
+
+    Some((low, high))
+}
+
+fn shuf(matches: &ArgMatches) -> Result<(), ShufError> {
+    let flags = ShufFlags::from_matches(matches)?;
+
+    let mut random = match flags.random_source {
+        Some(path) => RandomSource::from_file(
+            File::open(path).map_err(|err| ShufError::Read(path.to_string(), err))?,
+        ),
+        None => RandomSource::new(),
+    };
+    let random_err = |err| ShufError::Read(flags.random_source.unwrap_or("").to_string(), err);
+
+    // All the input is read before the output is created, so it can be the same file
+    let lines: Vec<Vec<u8>> = match flags.input {
+        Input::Range(low, high) if flags.repeat => {
+            let mut output = open_output(flags.output)?;
+            return repeat_range(
+                low,
+                high,
+                flags.head_count,
+                flags.delimiter,
+                &mut random,
+                &mut output,
+            )
+            .map_err(|err| err.into_shuf_error(&flags, random_err));
+        },
+        Input::Range(low, high) => {
+            let count = flags.head_count.unwrap_or(u64::MAX);
+            let numbers = shuffle_range(low, high, count, &mut random).map_err(random_err)?;
+            numbers.iter().map(|n| n.to_string().into_bytes()).collect()
+        },
+        Input::Echo(ref args) => {
+            let mut lines: Vec<_> = args.iter().map(|arg| arg.as_bytes().to_vec()).collect();
+            if !flags.repeat {
+                let count = flags.head_count.unwrap_or(u64::MAX);
+                shuffle(&mut lines, count, &mut random).map_err(random_err)?;
+            }
+            lines
+        },
+        Input::File(path) => {
+            let read_err = |err| ShufError::Read(path.to_string(), err);
+            let reader: Box<dyn BufRead> = if path == "-" {
+                Box::new(BufReader::new(io::stdin()))
+            } else {
+                Box::new(BufReader::new(File::open(path).map_err(read_err)?))
+            };
+
+            match flags.head_count {
+                // Keep only the sampled lines in memory
+                Some(count) if !flags.repeat => {
+                    sample_lines(reader, flags.delimiter, count, &mut random)
+                        .map_err(|err| err.into_shuf_error(&flags, random_err))?
+                },
+                _ => {
+                    let mut lines = read_lines(reader, flags.delimiter).map_err(read_err)?;
+                    if !flags.repeat {
+                        shuffle(&mut lines, u64::MAX, &mut random).map_err(random_err)?;
+                    }
+                    lines
+                },
+            }
+        },
+    };
+
+    let mut output = open_output(flags.output)?;
+    let write_err =
+        |err| ShufError::Write(flags.output.unwrap_or("standard output").to_string(), err);
+
+    if flags.repeat {
+        repeat_lines(&lines, flags.head_count, flags.delimiter, &mut random, &mut output)
+            .map_err(|err| err.into_shuf_error(&flags, random_err))?;
+    } else {
+        for line in &lines {
+            write_line(&mut output, line, flags.delimiter).map_err(write_err)?;
+        }
+    }
+
+    output.flush().map_err(write_err)
+}
+
+fn open_output(path: Option<&str>) -> Result<Box<dyn Write>, ShufError> {
+    match path {
+        Some(path) => {
+            let file = File::create(path).map_err(|err| ShufError::Write(path.to_string(), err))?;
+            Ok(Box::new(BufWriter::new(file)))
+        },
+        None => Ok(Box::new(BufWriter::new(io::stdout()))),
+    }
+}
+
+fn write_line<W: Write + ?Sized>(output: &mut W, line: &[u8], delimiter: u8) -> io::Result<()> {
+    output.write_all(line)?;
+    output.write_all(&[delimiter])
+}
+
+/// Read all the lines of `reader`, without the delimiters.
+fn read_lines<R: BufRead>(mut reader: R, delimiter: u8) -> io::Result<Vec<Vec<u8>>> {
+    let mut lines = Vec::new();
+
+    loop {
+        let mut line = Vec::new();
+        if reader.read_until(delimiter, &mut line)? == 0 {
+            return Ok(lines);
+        }
+        if line.last() == Some(&delimiter) {
+            line.pop();
+        }
+        lines.push(line);
+    }
+}
+
+/// Move `count` random elements of `items`, in a random order, to the start, dropping the
+/// others.
+fn shuffle<T>(items: &mut Vec<T>, count: u64, random: &mut RandomSource) -> io::Result<()> {
+    let len = items.len() as u64;
+    let count = count.min(len);
+
+    // Fisher–Yates, stopped after `count` elements
+    for i in 0..count {
+        let j = i + random.up_to(len - 1 - i)?;
+        items.swap(i as usize, j as usize);
+    }
+    items.truncate(count as usize);
+
+    Ok(())
+}
+
+/// Get `count` random numbers from `low` to `high` without repetitions. Only the numbers
+/// moved by the shuffle are kept in memory, so a small sample of a huge range is cheap.
+fn shuffle_range(
+    low: u64, high: u64, count: u64, random: &mut RandomSource,
+) -> io::Result<Vec<u64>> {
+    if high < low {
+        return Ok(Vec::new());
+    }
+    let last = high - low;
+    let count = count.min(last + 1);
+
+    // The position i has the value i unless it was swapped
+    let mut swapped: HashMap<u64, u64> = HashMap::new();
+    let mut numbers = Vec::with_capacity(count.min(1 << 20) as usize);
+
+    for i in 0..count {
+        let j = i + random.up_to(last - i)?;
+        let value_j = swapped.get(&j).copied().unwrap_or(j);
+        let value_i = swapped.remove(&i).unwrap_or(i);
+        if j != i {
+            swapped.insert(j, value_i);
+        }
+        numbers.push(low + value_j);
+    }
+
+    Ok(numbers)
+}
+
+/// Error of the functions that both read or write the lines and use the random source.
+#[derive(Debug)]
+enum StreamError {
+    Random(io::Error),
+    Input(io::Error),
+    Output(io::Error),
+    NoLines,
+}
+
+impl StreamError {
+    fn into_shuf_error<F>(self, flags: &ShufFlags, random_err: F) -> ShufError
+    where
+        F: Fn(io::Error) -> ShufError,
+    {
+        match self {
+            StreamError::Random(err) => random_err(err),
+            StreamError::Input(err) => {
+                let path = match flags.input {
+                    Input::File(path) => path,
+                    _ => "-",
+                };
+                ShufError::Read(path.to_string(), err)
+            },
+            StreamError::Output(err) => {
+                ShufError::Write(flags.output.unwrap_or("standard output").to_string(), err)
+            },
+            StreamError::NoLines => ShufError::NoLines,
+        }
+    }
+}
+
+/// Choose `count` random lines of `reader` with reservoir sampling, in a random order.
+fn sample_lines<R: BufRead>(
+    mut reader: R, delimiter: u8, count: u64, random: &mut RandomSource,
+) -> Result<Vec<Vec<u8>>, StreamError> {
+    let mut sample = Vec::new();
+    let mut seen: u64 = 0;
+
+    loop {
+        let mut line = Vec::new();
+        if reader.read_until(delimiter, &mut line).map_err(StreamError::Input)? == 0 {
+            break;
+        }
+        if line.last() == Some(&delimiter) {
+            line.pop();
+        }
+
+        // Each line read so far stays in the sample with the same probability
+        if seen < count {
+            sample.push(line);
+        } else {
+            let j = random.up_to(seen).map_err(StreamError::Random)?;
+            if j < count {
+                sample[j as usize] = line;
+            }
+        }
+        seen += 1;
+    }
+
+    shuffle(&mut sample, count, random).map_err(StreamError::Random)?;
+    Ok(sample)
+}
+
+/// Write `count` random lines, or until the output is closed without a `count`.
+fn repeat_lines<W: Write + ?Sized>(
+    lines: &[Vec<u8>], count: Option<u64>, delimiter: u8, random: &mut RandomSource, output: &mut W,
+) -> Result<(), StreamError> {
+    if count == Some(0) {
+        return Ok(());
+    }
+    if lines.is_empty() {
+        return Err(StreamError::NoLines);
+    }
+
+    let mut written = 0;
+    while count != Some(written) {
+        let i = random.up_to(lines.len() as u64 - 1).map_err(StreamError::Random)?;
+        write_line(output, &lines[i as usize], delimiter).map_err(StreamError::Output)?;
+        written += 1;
+    }
+
+    Ok(())
+}
+
+/// Write `count` random numbers from `low` to `high`, or until the output is closed
+/// without a `count`.
+fn repeat_range<W: Write + ?Sized>(
+    low: u64, high: u64, count: Option<u64>, delimiter: u8, random: &mut RandomSource,
+    output: &mut W,
+) -> Result<(), StreamError> {
+    if count == Some(0) {
+        return Ok(());
+    }
+    if high < low {
+        return Err(StreamError::NoLines);
+    }
+
+    let mut written = 0;
+    while count != Some(written) {
+        let number = low + random.up_to(high - low).map_err(StreamError::Random)?;
+        write_line(output, number.to_string().as_bytes(), delimiter)
+            .map_err(StreamError::Output)?;
+        written += 1;
+    }
+
+    output.flush().map_err(StreamError::Output)
+}
+
+#[derive(Debug)]
+enum ShufError {
+    Read(String, io::Error),
+    Write(String, io::Error),
+    InvalidRange(String),
+    InvalidCount(String),
+    ExtraOperand(String),
+    EchoAndRange,
+    NoLines,
+}
+
+impl fmt::Display for ShufError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShufError::Read(path, err) => write!(f, "{}: {}", path, err),
+            ShufError::Write(path, err) => write!(f, "failed to write {}: {}", path, err),
+            ShufError::InvalidRange(range) => write!(f, "invalid input range: '{}'", range),
+            ShufError::InvalidCount(count) => write!(f, "invalid line count: '{}'", count),
+            ShufError::ExtraOperand(operand) => write!(f, "extra operand '{}'", operand),
+            ShufError::EchoAndRange => write!(f, "cannot combine -e and -i options"),
+            ShufError::NoLines => write!(f, "no lines to repeat"),
+        }
+    }
+}
+
+impl error::Error for ShufError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ShufError::Read(_, err) | ShufError::Write(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted<T: Ord + Clone>(items: &[T]) -> Vec<T> {
+        let mut items = items.to_vec();
+        items.sort();
+        items
+    }
+
+    #[test]
+    fn parse_ranges() {
+        assert_eq!(parse_range("1-10"), Some((1, 10)));
+        assert_eq!(parse_range("5-4"), Some((5, 4)));
+        assert_eq!(parse_range("5-3"), None);
+        assert_eq!(parse_range("0-18446744073709551614"), Some((0, u64::MAX - 1)));
+        assert_eq!(parse_range("0-18446744073709551615"), None);
+        assert_eq!(parse_range("1-"), None);
+        assert_eq!(parse_range("-1-2"), None);
+        assert_eq!(parse_range("10"), None);
+    }
+
+    #[test]
+    fn shuffle_permutation() {
+        let mut random = RandomSource::Generator(1);
+        let mut items: Vec<_> = (0..100).collect();
+
+        shuffle(&mut items, u64::MAX, &mut random).unwrap();
+        assert_eq!(sorted(&items), (0..100).collect::<Vec<_>>());
+        assert_ne!(items, (0..100).collect::<Vec<_>>());
+
+        shuffle(&mut items, 10, &mut random).unwrap();
+        assert_eq!(items.len(), 10);
+    }
+
+    #[test]
+    fn shuffle_huge_range() {
+        let mut random = RandomSource::Generator(2);
+
+        let numbers = shuffle_range(1, u64::MAX, 1000, &mut random).unwrap();
+        assert_eq!(numbers.len(), 1000);
+        assert!(numbers.iter().all(|&n| n >= 1));
+        let mut unique = sorted(&numbers);
+        unique.dedup();
+        assert_eq!(unique.len(), 1000);
+
+        let numbers = shuffle_range(5, 14, u64::MAX, &mut random).unwrap();
+        assert_eq!(sorted(&numbers), (5..15).collect::<Vec<_>>());
+        assert!(shuffle_range(5, 4, 10, &mut random).unwrap().is_empty());
+    }
+
+    #[test]
+    fn sample_reservoir() {
+        let mut random = RandomSource::Generator(3);
+        let input: String = (0..1000).map(|i| format!("{}\n", i)).collect();
+
+        let sample = sample_lines(input.as_bytes(), b'\n', 10, &mut random).unwrap();
+        assert_eq!(sample.len(), 10);
+        let mut unique = sorted(&sample);
+        unique.dedup();
+        assert_eq!(unique.len(), 10);
+
+        let sample = sample_lines(&b"a\0b\0c"[..], b'\0', 10, &mut random).unwrap();
+        assert_eq!(sorted(&sample), vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]);
+    }
+
+    #[test]
+    fn repeat_output() {
+        let mut random = RandomSource::Generator(4);
+        let lines = vec![b"a".to_vec(), b"b".to_vec()];
+        let mut output = Vec::new();
+
+        repeat_lines(&lines, Some(50), b'\n', &mut random, &mut output).unwrap();
+        assert_eq!(output.len(), 100);
+        assert!(output.chunks(2).all(|line| line == b"a\n" || line == b"b\n"));
+
+        let mut output = Vec::new();
+        repeat_range(7, 7, Some(3), b'\n', &mut random, &mut output).unwrap();
+        assert_eq!(output, b"7\n7\n7\n");
+
+        let mut output = Vec::new();
+        match repeat_lines(&[], Some(1), b'\n', &mut random, &mut output) {
+            Err(StreamError::NoLines) => {},
+            res => panic!("{:?}", res),
+        }
+    }
+}
@@ -28,6 +28,35 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .short("m")
                 .long("merge"),
         )
+        .arg(Arg::with_name("check").help("Check for sorted input; do not sort.").short("c"))
+        .arg(
+            Arg::with_name("check_word")
+                .value_name("WORD")
+                .help("Like -c, or -C if WORD is ‘quiet’ or ‘silent’.")
+                .long_help(
+                    "Check for sorted input; do not sort.\n\nWith WORD ‘quiet’ or ‘silent’ it \
+                     behaves like -C, with ‘diagnose-first’, the default, the first line out of \
+                     order is reported.",
+                )
+                .long("check")
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&["diagnose-first", "quiet", "silent"]),
+        )
+        .arg(
+            Arg::with_name("check_quiet")
+                .help("Like -c, but do not report the first bad line.")
+                .short("C"),
+        )
+        .arg(
+            Arg::with_name("unique")
+                .help("Output only the first of an equal run.")
+                .long_help(
+                    "Output only the first of an equal run.\n\nWith -c, check for strict ordering.",
+                )
+                .short("u")
+                .long("unique"),
+        )
         .arg(
             Arg::with_name("OUTPUT_FILE")
                 .value_name("FILE")
@@ -38,5 +67,140 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 )
                 .short("o"),
         )
+        .arg(
+            Arg::with_name("key")
+                .value_name("KEYDEF")
+                .help("Sort via a key, KEYDEF gives location and type.")
+                .long_help(
+                    "Sort via a key, KEYDEF gives location and type.\n\nKEYDEF is \
+                     F[.C][OPTS][,F[.C][OPTS]] for start and stop position, where F is a field \
+                     number and C a character position in the field; both are origin 1, and the \
+                     stop position defaults to the line's end. If neither -t nor -b is in effect, \
+                     characters in a field are counted from the beginning of the preceding \
+                     whitespace. OPTS is one or more single-letter ordering options [bdfhiMnRrV], \
+                     which override global ordering options for that key. If no key is given, use \
+                     the entire line as the key. This option can be used multiple times.",
+                )
+                .short("k")
+                .long("key")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("field_separator")
+                .value_name("SEP")
+                .help("Use SEP instead of non-blank to blank transition.")
+                .short("t")
+                .long("field-separator"),
+        )
+        .arg(
+            Arg::with_name("ignore_leading_blanks")
+                .help("Ignore leading blanks.")
+                .short("b")
+                .long("ignore-leading-blanks"),
+        )
+        .arg(
+            Arg::with_name("dictionary_order")
+                .help("Consider only blanks and alphanumeric characters.")
+                .short("d")
+                .long("dictionary-order"),
+        )
+        .arg(
+            Arg::with_name("ignore_case")
+                .help("Fold lower case to upper case characters.")
+                .short("f")
+                .long("ignore-case"),
+        )
+        .arg(
+            Arg::with_name("ignore_nonprinting")
+                .help("Consider only printable characters.")
+                .short("i")
+                .long("ignore-nonprinting"),
+        )
+        .arg(
+            Arg::with_name("human_numeric_sort")
+                .help("Compare human readable numbers (e.g., 2K 1G).")
+                .short("h")
+                .long("human-numeric-sort"),
+        )
+        .arg(
+            Arg::with_name("month_sort")
+                .help("Compare (unknown) < 'JAN' < ... < 'DEC'.")
+                .short("M")
+                .long("month-sort"),
+        )
+        .arg(
+            Arg::with_name("numeric_sort")
+                .help("Compare according to string numerical value.")
+                .short("n")
+                .long("numeric-sort"),
+        )
+        .arg(
+            Arg::with_name("random_sort")
+                .help("Shuffle, but group identical keys.")
+                .short("R")
+                .long("random-sort"),
+        )
+        .arg(
+            Arg::with_name("reverse")
+                .help("Reverse the result of comparisons.")
+                .short("r")
+                .long("reverse"),
+        )
+        .arg(
+            Arg::with_name("stable")
+                .help("Stabilize sort by disabling last-resort comparison.")
+                .short("s")
+                .long("stable"),
+        )
+        .arg(
+            Arg::with_name("version_sort")
+                .help("Natural sort of (version) numbers within text.")
+                .short("V")
+                .long("version-sort"),
+        )
+        .arg(
+            Arg::with_name("sort")
+                .value_name("WORD")
+                .help("Sort according to WORD.")
+                .long_help(
+                    "Sort according to WORD: human-numeric -h, month -M, numeric -n, random -R, \
+                     version -V.",
+                )
+                .long("sort")
+                .possible_values(&["human-numeric", "month", "numeric", "random", "version"]),
+        )
+        .arg(
+            Arg::with_name("buffer_size")
+                .value_name("SIZE")
+                .help("Use SIZE for main memory buffer.")
+                .long_help(
+                    "Use SIZE for main memory buffer.\n\nSIZE is a number of KiB, or a number \
+                     followed by one of the suffixes b for bytes, K, M, G, T, P or E. When the \
+                     input doesn't fit in the buffer, the sorted parts are written to temporary \
+                     files and merged at the end.",
+                )
+                .short("S")
+                .long("buffer-size"),
+        )
+        .arg(
+            Arg::with_name("temporary_directory")
+                .value_name("DIR")
+                .help("Use DIR for temporaries, not $TMPDIR or /tmp.")
+                .long_help(
+                    "Use DIR for temporaries, not $TMPDIR or /tmp.\n\nMultiple options specify \
+                     multiple directories, used in turns.",
+                )
+                .short("T")
+                .long("temporary-directory")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("compress_program")
+                .value_name("PROG")
+                .help("Compress temporaries with PROG; decompress them with PROG -d.")
+                .long("compress-program"),
+        )
     // Add args here
 }
@@ -0,0 +1,374 @@
+//! Comparison of the lines by their keys.
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::hash_map::{DefaultHasher, RandomState},
+    hash::{BuildHasher, Hasher},
+};
+
+use coreutils_core::version_sort;
+
+use crate::key::{is_blank, Key, KeyOptions};
+
+/// The month names abbreviations, in the order they are sorted.
+const MONTHS: [&[u8]; 12] = [
+    b"JAN", b"FEB", b"MAR", b"APR", b"MAY", b"JUN", b"JUL", b"AUG", b"SEP", b"OCT", b"NOV", b"DEC",
+];
+
+/// The human readable number suffixes, in the order they are sorted.
+const UNITS: &[u8] = b"KMGTPEZY";
+
+/// Compares the lines by the keys, using the whole line as the last resort.
+#[derive(Debug, Clone)]
+pub struct Comparator {
+    keys: Vec<Key>,
+    separator: Option<u8>,
+    /// Reverse the last resort comparison.
+    reverse: bool,
+    /// Don't do the last resort comparison, so the lines with equal keys keep the input
+    /// order.
+    stable: bool,
+    /// The seed of the random order hashes, different on each run.
+    seed: u64,
+}
+
+impl Comparator {
+    /// Creates a new [`Comparator`].
+    ///
+    /// The keys without options use the `global` options, if there are no keys, the whole
+    /// line is the key.
+    pub fn new(
+        mut keys: Vec<Key>, global: KeyOptions, separator: Option<u8>, stable: bool,
+    ) -> Self {
+        for key in keys.iter_mut().filter(|key| key.options.is_default()) {
+            key.options = global;
+        }
+
+        if keys.is_empty() && !global.is_default() {
+            keys.push(Key::whole_line(global));
+        }
+
+        let seed = RandomState::new().build_hasher().finish();
+
+        Comparator { keys, separator, reverse: global.reverse, stable, seed }
+    }
+
+    /// Compare the lines `a` and `b`.
+    pub fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        for key in &self.keys {
+            let ord = compare_keys(
+                key.extract(a, self.separator),
+                key.extract(b, self.separator),
+                &key.options,
+                self.seed,
+            );
+
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+
+        if self.stable && !self.keys.is_empty() {
+            return Ordering::Equal;
+        }
+
+        let ord = a.cmp(b);
+        if self.reverse {
+            ord.reverse()
+        } else {
+            ord
+        }
+    }
+}
+
+/// Compare the keys `a` and `b` using `options`.
+fn compare_keys(a: &[u8], b: &[u8], options: &KeyOptions, seed: u64) -> Ordering {
+    let (a, b) = if options.fold_case || options.dictionary || options.ignore_nonprinting {
+        (Cow::Owned(filter_key(a, options)), Cow::Owned(filter_key(b, options)))
+    } else {
+        (Cow::Borrowed(a), Cow::Borrowed(b))
+    };
+
+    let ord = if options.random {
+        hash_key(&a, seed).cmp(&hash_key(&b, seed)).then_with(|| a.cmp(&b))
+    } else if options.numeric {
+        compare_numeric(&a, &b)
+    } else if options.human_numeric {
+        compare_human_numeric(&a, &b)
+    } else if options.month {
+        month(&a).cmp(&month(&b))
+    } else if options.version {
+        version_sort::compare(&a, &b)
+    } else {
+        a.cmp(&b)
+    };
+
+    if options.reverse {
+        ord.reverse()
+    } else {
+        ord
+    }
+}
+
+/// Remove the ignored characters of `key` and fold it to upper case as indicated by
+/// `options`.
+fn filter_key(key: &[u8], options: &KeyOptions) -> Vec<u8> {
+    key.iter()
+        .filter(|&&byte| {
+            (!options.dictionary || byte.is_ascii_alphanumeric() || is_blank(byte))
+                && (!options.ignore_nonprinting || (b' '..=b'~').contains(&byte))
+        })
+        .map(|&byte| if options.fold_case { byte.to_ascii_uppercase() } else { byte })
+        .collect()
+}
+
+fn hash_key(key: &[u8], seed: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write_u64(seed);
+    hasher.write(key);
+    hasher.finish()
+}
+
+/// Get the month of `key`, from 1 to 12, or 0 if it's not a month name.
+fn month(key: &[u8]) -> usize {
+    let key = &key[key.iter().take_while(|&&b| is_blank(b)).count()..];
+
+    MONTHS
+        .iter()
+        .position(|name| key.len() >= name.len() && key[..name.len()].eq_ignore_ascii_case(name))
+        .map(|i| i + 1)
+        .unwrap_or(0)
+}
+
+/// A number on the format accepted by the numeric sort, split on its parts.
+struct Number<'a> {
+    negative: bool,
+    /// The digits of the integer part, without the leading zeros.
+    integer: &'a [u8],
+    /// The digits of the fractional part, without the trailing zeros.
+    fraction: &'a [u8],
+    /// The character after the number.
+    suffix: Option<u8>,
+}
+
+impl<'a> Number<'a> {
+    /// Parse the number at the start of `s`, after any leading blanks. If there is no
+    /// number it's 0.
+    fn parse(s: &'a [u8]) -> Self {
+        let s = &s[s.iter().take_while(|&&b| is_blank(b)).count()..];
+
+        let (negative, s) = match s.first() {
+            Some(b'-') => (true, &s[1..]),
+            _ => (false, s),
+        };
+
+        let int_len = s.iter().take_while(|b| b.is_ascii_digit()).count();
+        let integer = &s[..int_len];
+        let integer = &integer[integer.iter().take_while(|&&b| b == b'0').count()..];
+
+        let (fraction, suffix) = match s.get(int_len) {
+            Some(b'.') => {
+                let s = &s[int_len + 1..];
+                let frac_len = s.iter().take_while(|b| b.is_ascii_digit()).count();
+                let fraction = &s[..frac_len];
+                let zeros = fraction.iter().rev().take_while(|&&b| b == b'0').count();
+                (&fraction[..fraction.len() - zeros], s.get(frac_len).copied())
+            },
+            suffix => (&[][..], suffix.copied()),
+        };
+
+        Number { negative, integer, fraction, suffix }
+    }
+
+    /// The order of the number suffix: 0 without a known one, 1 for K, 2 for M, and so
+    /// on. Negative for the negative numbers.
+    fn unit_order(&self) -> i32 {
+        if self.is_zero() {
+            return 0;
+        }
+
+        let order = match self.suffix {
+            Some(b'k') => 1,
+            Some(unit) => UNITS.iter().position(|&u| u == unit).map(|i| i as i32 + 1).unwrap_or(0),
+            None => 0,
+        };
+
+        if self.negative {
+            -order
+        } else {
+            order
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.integer.is_empty() && self.fraction.is_empty()
+    }
+}
+
+/// Compare the numbers at the start of `a` and `b`, without converting them, so there is
+/// no size or precision limit.
+fn compare_numeric(a: &[u8], b: &[u8]) -> Ordering {
+    compare_numbers(&Number::parse(a), &Number::parse(b))
+}
+
+fn compare_numbers(a: &Number, b: &Number) -> Ordering {
+    // The negative zero is the same as zero
+    let a_negative = a.negative && !a.is_zero();
+    let b_negative = b.negative && !b.is_zero();
+
+    match (a_negative, b_negative) {
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        _ => {
+            let ord = a
+                .integer
+                .len()
+                .cmp(&b.integer.len())
+                .then_with(|| a.integer.cmp(b.integer))
+                .then_with(|| a.fraction.cmp(b.fraction));
+
+            if a_negative {
+                ord.reverse()
+            } else {
+                ord
+            }
+        },
+    }
+}
+
+/// Compare the human readable numbers at the start of `a` and `b`, first by the suffix,
+/// then by the number.
+fn compare_human_numeric(a: &[u8], b: &[u8]) -> Ordering {
+    let (a, b) = (Number::parse(a), Number::parse(b));
+
+    a.unit_order().cmp(&b.unit_order()).then_with(|| compare_numbers(&a, &b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(lines: &[&str], keys: &[&str], global: KeyOptions, sep: Option<u8>) -> Vec<String> {
+        let keys = keys.iter().map(|spec| Key::parse(spec).unwrap()).collect();
+        let comparator = Comparator::new(keys, global, sep, false);
+
+        let mut lines: Vec<_> = lines.iter().map(|line| line.as_bytes()).collect();
+        lines.sort_by(|a, b| comparator.compare(a, b));
+        lines.iter().map(|line| String::from_utf8_lossy(line).to_string()).collect()
+    }
+
+    #[test]
+    fn numeric_order() {
+        let cmp = |a: &str, b: &str| compare_numeric(a.as_bytes(), b.as_bytes());
+
+        assert_eq!(cmp("10", "9"), Ordering::Greater);
+        assert_eq!(cmp("-10", "-9"), Ordering::Less);
+        assert_eq!(cmp("  007", "7"), Ordering::Equal);
+        assert_eq!(cmp("1.50", "1.5"), Ordering::Equal);
+        assert_eq!(cmp("1.05", "1.5"), Ordering::Less);
+        assert_eq!(cmp("-0", "0.000"), Ordering::Equal);
+        assert_eq!(cmp("abc", "0"), Ordering::Equal);
+        assert_eq!(cmp("-.5", "0"), Ordering::Less);
+        assert_eq!(cmp("123456789012345678901234567890", "99"), Ordering::Greater);
+    }
+
+    #[test]
+    fn sort_keys() {
+        let lines = ["b 2", "a 10", "c 1"];
+        let numeric = KeyOptions { numeric: true, ..KeyOptions::default() };
+
+        assert_eq!(sorted(&lines, &["2"], KeyOptions::default(), None), ["c 1", "a 10", "b 2"]);
+        assert_eq!(sorted(&lines, &["2n"], KeyOptions::default(), None), ["c 1", "b 2", "a 10"]);
+        assert_eq!(sorted(&lines, &["2"], numeric, None), ["c 1", "b 2", "a 10"]);
+        assert_eq!(sorted(&lines, &["2nr"], KeyOptions::default(), None), ["a 10", "b 2", "c 1"]);
+    }
+
+    #[test]
+    fn sort_multiple_keys() {
+        let lines = ["x:2:b", "y:1:b", "z:3:a"];
+        let sep = Some(b':');
+
+        assert_eq!(sorted(&lines, &["3,3", "2n"], KeyOptions::default(), sep), [
+            "z:3:a", "y:1:b", "x:2:b"
+        ]);
+        assert_eq!(sorted(&lines, &["3,3r", "2n"], KeyOptions::default(), sep), [
+            "y:1:b", "x:2:b", "z:3:a"
+        ]);
+    }
+
+    #[test]
+    fn sort_last_resort() {
+        let lines = ["b a", "a a", "B a"];
+        let reverse = KeyOptions { reverse: true, ..KeyOptions::default() };
+        let fold = KeyOptions { fold_case: true, ..KeyOptions::default() };
+
+        assert_eq!(sorted(&lines, &["2"], KeyOptions::default(), None), ["B a", "a a", "b a"]);
+        assert_eq!(sorted(&lines, &["2"], reverse, None), ["b a", "a a", "B a"]);
+        assert_eq!(sorted(&lines, &[], fold, None), ["a a", "B a", "b a"]);
+
+        let keys = vec![Key::parse("2").unwrap()];
+        let comparator = Comparator::new(keys, KeyOptions::default(), None, true);
+        assert_eq!(comparator.compare(b"b a", b"a a"), Ordering::Equal);
+    }
+
+    #[test]
+    fn sort_filters() {
+        let dictionary = KeyOptions { dictionary: true, ..KeyOptions::default() };
+        let nonprinting = KeyOptions { ignore_nonprinting: true, ..KeyOptions::default() };
+        let blanks =
+            KeyOptions { skip_start_blanks: true, skip_end_blanks: true, ..KeyOptions::default() };
+
+        assert_eq!(sorted(&["a-c", "ab"], &[], dictionary, None), ["ab", "a-c"]);
+        assert_eq!(sorted(&["a\x01c", "ab"], &[], nonprinting, None), ["ab", "a\x01c"]);
+        assert_eq!(sorted(&["  b", " a", "c"], &[], blanks, None), [" a", "  b", "c"]);
+        assert_eq!(sorted(&["x  b", "x a"], &["2"], blanks, None), ["x a", "x  b"]);
+    }
+
+    #[test]
+    fn human_numeric_order() {
+        let cmp = |a: &str, b: &str| compare_human_numeric(a.as_bytes(), b.as_bytes());
+
+        assert_eq!(cmp("2K", "1G"), Ordering::Less);
+        assert_eq!(cmp("1k", "1K"), Ordering::Equal);
+        assert_eq!(cmp("1023", "1K"), Ordering::Less);
+        assert_eq!(cmp("1.5M", "1M"), Ordering::Greater);
+        assert_eq!(cmp("-1G", "-1K"), Ordering::Less);
+        assert_eq!(cmp("0G", "1"), Ordering::Less);
+        assert_eq!(cmp(" 3x", "3"), Ordering::Equal);
+    }
+
+    #[test]
+    fn month_order() {
+        assert_eq!(month(b"jan"), 1);
+        assert_eq!(month(b"  December"), 12);
+        assert_eq!(month(b"Ma"), 0);
+        assert_eq!(month(b"foo"), 0);
+    }
+
+
+    #[test]
+    fn sort_orders() {
+        let month = KeyOptions { month: true, ..KeyOptions::default() };
+        let version = KeyOptions { version: true, ..KeyOptions::default() };
+
+        assert_eq!(sorted(&["1K", "2", "1G"], &["1h"], KeyOptions::default(), None), [
+            "2", "1K", "1G"
+        ]);
+        assert_eq!(sorted(&["x mar", "y foo", "z JAN"], &["2"], month, None), [
+            "y foo", "z JAN", "x mar"
+        ]);
+        assert_eq!(sorted(&["a-1.10", "a-1.9", "a-1.09~"], &[], version, None), [
+            "a-1.09~", "a-1.9", "a-1.10"
+        ]);
+    }
+
+    #[test]
+    fn sort_random() {
+        let random = KeyOptions { random: true, ..KeyOptions::default() };
+        let lines = ["a", "b", "a", "c", "b", "a"];
+
+        let sorted = sorted(&lines, &[], random, None);
+        let groups = sorted.windows(2).filter(|pair| pair[0] != pair[1]).count();
+        assert_eq!(groups, 2);
+    }
+}
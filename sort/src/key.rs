@@ -0,0 +1,382 @@
+//! Sort keys: the parsing of the `-k` specifications and the extraction of the keys from
+//! the lines.
+use std::fmt;
+
+/// The ordering options, that can be set for all the lines or for each key.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KeyOptions {
+    /// Compare according to the string numerical value. (`n`)
+    pub numeric: bool,
+    /// Compare the human readable numbers, like 2K and 1G. (`h`)
+    pub human_numeric: bool,
+    /// Compare the month names, unknown < JAN < ... < DEC. (`M`)
+    pub month: bool,
+    /// Compare the version numbers within the text. (`V`)
+    pub version: bool,
+    /// Shuffle, but keep together the equal keys. (`R`)
+    pub random: bool,
+    /// Reverse the result of the comparisons. (`r`)
+    pub reverse: bool,
+    /// Fold the lower case characters to upper case. (`f`)
+    pub fold_case: bool,
+    /// Consider only blanks and alphanumeric characters. (`d`)
+    pub dictionary: bool,
+    /// Consider only printable characters. (`i`)
+    pub ignore_nonprinting: bool,
+    /// Ignore the leading blanks of the key start. (`b` on the start)
+    pub skip_start_blanks: bool,
+    /// Ignore the leading blanks of the key end field. (`b` on the end)
+    pub skip_end_blanks: bool,
+}
+
+impl KeyOptions {
+    /// Returns `true` if no option is set.
+    pub fn is_default(&self) -> bool {
+        *self == KeyOptions::default()
+    }
+
+    /// Check that the options can be used together, returning the letters of the
+    /// options otherwise.
+    ///
+    /// Only one of the numeric, human numeric and month orders can be used, and they
+    /// can't be used with the version and random orders or ignoring characters.
+    pub fn check_compatible(&self) -> Result<(), String> {
+        let orders = [
+            self.numeric,
+            self.human_numeric,
+            self.month,
+            self.version || self.random || self.dictionary || self.ignore_nonprinting,
+        ];
+
+        if orders.iter().filter(|&&order| order).count() <= 1 {
+            return Ok(());
+        }
+
+        let letters = [
+            (self.dictionary, 'd'),
+            (self.fold_case, 'f'),
+            (self.human_numeric, 'h'),
+            (self.ignore_nonprinting, 'i'),
+            (self.month, 'M'),
+            (self.numeric, 'n'),
+            (self.random, 'R'),
+            (self.version, 'V'),
+        ];
+        Err(letters.iter().filter(|(set, _)| *set).map(|(_, letter)| letter).collect())
+    }
+
+    /// Set the option of the letter `opt` of a key specification, returns `false` if
+    /// `opt` is not a option letter.
+    fn set(&mut self, opt: char, on_end: bool) -> bool {
+        match opt {
+            'b' if on_end => self.skip_end_blanks = true,
+            'b' => self.skip_start_blanks = true,
+            'd' => self.dictionary = true,
+            'f' => self.fold_case = true,
+            'h' => self.human_numeric = true,
+            'i' => self.ignore_nonprinting = true,
+            'M' => self.month = true,
+            'n' => self.numeric = true,
+            'R' => self.random = true,
+            'r' => self.reverse = true,
+            'V' => self.version = true,
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// The error of a invalid key specification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyError {
+    spec: String,
+    reason: &'static str,
+}
+
+impl KeyError {
+    fn new(spec: &str, reason: &'static str) -> Self {
+        KeyError { spec: spec.to_string(), reason }
+    }
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: invalid field specification '{}'", self.reason, self.spec)
+    }
+}
+
+impl std::error::Error for KeyError {}
+
+/// The end of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct KeyEnd {
+    /// Field where the key ends, starting from 0.
+    field: usize,
+    /// Last character of the key on the field, starting from 1. 0 means the end of the
+    /// field.
+    char: usize,
+}
+
+/// A sort key, the part of the line used on the comparisons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    /// Field where the key starts, starting from 0.
+    start_field: usize,
+    /// Character of the start field where the key starts, starting from 0.
+    start_char: usize,
+    /// The end of the key, `None` for the end of the line.
+    end: Option<KeyEnd>,
+    pub options: KeyOptions,
+}
+
+impl Key {
+    /// Creates a [`Key`] with the whole line.
+    pub fn whole_line(options: KeyOptions) -> Self {
+        Key { start_field: 0, start_char: 0, end: None, options }
+    }
+
+    /// Parse a key specification, in the `F[.C][OPTS][,F[.C][OPTS]]` format.
+    pub fn parse(spec: &str) -> Result<Self, KeyError> {
+        let mut options = KeyOptions::default();
+
+        let (field, rest) = parse_number(spec);
+        let field = field.ok_or_else(|| KeyError::new(spec, "invalid number at field start"))?;
+        if field == 0 {
+            return Err(KeyError::new(spec, "field number is zero"));
+        }
+
+        let (char, rest) = match rest.strip_prefix('.') {
+            Some(rest) => match parse_number(rest) {
+                (Some(0), _) => return Err(KeyError::new(spec, "character offset is zero")),
+                (Some(char), rest) => (char, rest),
+                (None, _) => return Err(KeyError::new(spec, "invalid number after '.'")),
+            },
+            None => (1, rest),
+        };
+
+        let rest = parse_options(spec, rest, &mut options, false)?;
+
+        let end = match rest.strip_prefix(',') {
+            Some(rest) => {
+                let (field, rest) = parse_number(rest);
+                let field = field.ok_or_else(|| KeyError::new(spec, "invalid number after ','"))?;
+                if field == 0 {
+                    return Err(KeyError::new(spec, "field number is zero"));
+                }
+
+                let (char, rest) = match rest.strip_prefix('.') {
+                    Some(rest) => match parse_number(rest) {
+                        (Some(char), rest) => (char, rest),
+                        (None, _) => return Err(KeyError::new(spec, "invalid number after '.'")),
+                    },
+                    None => (0, rest),
+                };
+
+                let rest = parse_options(spec, rest, &mut options, true)?;
+                if !rest.is_empty() {
+                    return Err(KeyError::new(spec, "stray character in field spec"));
+                }
+
+                Some(KeyEnd { field: field - 1, char })
+            },
+            None if rest.is_empty() => None,
+            None => return Err(KeyError::new(spec, "stray character in field spec")),
+        };
+
+        Ok(Key { start_field: field - 1, start_char: char - 1, end, options })
+    }
+
+    /// Get the key of `line`, where the fields are separated by `separator`, or by the
+    /// transition from blank to non blank characters if it's `None`.
+    pub fn extract<'a>(&self, line: &'a [u8], separator: Option<u8>) -> &'a [u8] {
+        let start = self.start(line, separator);
+        let end = match self.end {
+            Some(end) => self.end(end, line, separator),
+            None => line.len(),
+        };
+
+        if end <= start {
+            &[]
+        } else {
+            &line[start..end]
+        }
+    }
+
+    fn start(&self, line: &[u8], separator: Option<u8>) -> usize {
+        let mut pos = skip_fields(line, self.start_field, separator, true);
+
+        if self.options.skip_start_blanks {
+            pos = skip_blanks(line, pos);
+        }
+
+        line.len().min(pos.saturating_add(self.start_char))
+    }
+
+    fn end(&self, end: KeyEnd, line: &[u8], separator: Option<u8>) -> usize {
+        // Without a character, the whole end field is included
+        let fields = if end.char == 0 { end.field.saturating_add(1) } else { end.field };
+
+        // The separator after the last field is not part of the key
+        let mut pos = skip_fields(line, fields, separator, end.char != 0);
+
+        if end.char == 0 {
+            return pos;
+        }
+
+        if self.options.skip_end_blanks {
+            pos = skip_blanks(line, pos);
+        }
+
+        line.len().min(pos.saturating_add(end.char))
+    }
+}
+
+/// Returns `true` if `byte` separates the fields when there is no separator.
+pub fn is_blank(byte: u8) -> bool {
+    byte == b' ' || byte == b'\t'
+}
+
+fn skip_blanks(line: &[u8], pos: usize) -> usize {
+    pos + line[pos..].iter().take_while(|&&b| is_blank(b)).count()
+}
+
+/// Get the position after `fields` fields of `line`. If `past_last_separator` is `false`,
+/// the position of the separator after the last field is returned instead.
+fn skip_fields(
+    line: &[u8], fields: usize, separator: Option<u8>, past_last_separator: bool,
+) -> usize {
+    let mut pos = 0;
+
+    for i in 0..fields {
+        if pos >= line.len() {
+            break;
+        }
+
+        match separator {
+            Some(sep) => {
+                pos += line[pos..].iter().take_while(|&&b| b != sep).count();
+                if pos < line.len() && (past_last_separator || i + 1 < fields) {
+                    pos += 1;
+                }
+            },
+            None => {
+                pos = skip_blanks(line, pos);
+                pos += line[pos..].iter().take_while(|&&b| !is_blank(b)).count();
+            },
+        }
+    }
+
+    pos
+}
+
+/// Parse the leading digits of `s`, returning the number and the rest of `s`. Numbers too
+/// big are saturated.
+fn parse_number(s: &str) -> (Option<usize>, &str) {
+    let len = s.bytes().take_while(u8::is_ascii_digit).count();
+    if len == 0 {
+        return (None, s);
+    }
+
+    let number = s[..len]
+        .bytes()
+        .fold(0_usize, |acc, digit| acc.saturating_mul(10).saturating_add((digit - b'0') as usize));
+
+    (Some(number), &s[len..])
+}
+
+/// Parse the option letters at the start of `s`, returning the rest of `s`.
+fn parse_options<'a>(
+    spec: &str, s: &'a str, options: &mut KeyOptions, on_end: bool,
+) -> Result<&'a str, KeyError> {
+    let len = s.chars().take_while(char::is_ascii_alphabetic).count();
+
+    for opt in s[..len].chars() {
+        if !options.set(opt, on_end) {
+            return Err(KeyError::new(spec, "stray character in field spec"));
+        }
+    }
+
+    Ok(&s[len..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(spec: &str) -> Key {
+        Key::parse(spec).unwrap()
+    }
+
+    #[test]
+    fn parse_key() {
+        assert_eq!(key("2"), Key { start_field: 1, start_char: 0, end: None, ..key("1") });
+        assert_eq!(key("2.3,4").end, Some(KeyEnd { field: 3, char: 0 }));
+        assert_eq!(key("2.3,4.5").end, Some(KeyEnd { field: 3, char: 5 }));
+        assert_eq!(key("2.3,4.0").end, Some(KeyEnd { field: 3, char: 0 }));
+        assert_eq!(key("2.3").start_char, 2);
+
+        let options = key("1nr,2b").options;
+        assert!(options.numeric && options.reverse && options.skip_end_blanks);
+        assert!(!options.skip_start_blanks);
+        assert!(key("1.2b").options.skip_start_blanks);
+
+        let options = key("1hMVR").options;
+        assert!(options.human_numeric && options.month && options.version && options.random);
+    }
+
+    #[test]
+    fn compatible_options() {
+        assert_eq!(key("1nr").options.check_compatible(), Ok(()));
+        assert_eq!(key("1Vdf").options.check_compatible(), Ok(()));
+        assert_eq!(key("1Rbi").options.check_compatible(), Ok(()));
+        assert_eq!(key("1hn").options.check_compatible(), Err("hn".to_string()));
+        assert_eq!(key("1bnrd").options.check_compatible(), Err("dn".to_string()));
+        assert_eq!(key("1MfV").options.check_compatible(), Err("fMV".to_string()));
+    }
+
+    #[test]
+    fn parse_key_errors() {
+        let reason = |spec| Key::parse(spec).unwrap_err().reason;
+
+        assert_eq!(reason("0"), "field number is zero");
+        assert_eq!(reason("1,0"), "field number is zero");
+        assert_eq!(reason("1.0"), "character offset is zero");
+        assert_eq!(reason(""), "invalid number at field start");
+        assert_eq!(reason("a"), "invalid number at field start");
+        assert_eq!(reason("1."), "invalid number after '.'");
+        assert_eq!(reason("1,"), "invalid number after ','");
+        assert_eq!(reason("1x"), "stray character in field spec");
+        assert_eq!(reason("1,2;"), "stray character in field spec");
+        assert_eq!(reason("1n2"), "stray character in field spec");
+    }
+
+    #[test]
+    fn extract_blank_fields() {
+        let line = b"  foo bar\t baz";
+
+        assert_eq!(key("1").extract(line, None), line);
+        assert_eq!(key("1,1").extract(line, None), b"  foo");
+        assert_eq!(key("2,2").extract(line, None), b" bar");
+        assert_eq!(key("2b,2").extract(line, None), b"bar");
+        assert_eq!(key("3,3").extract(line, None), b"\t baz");
+        assert_eq!(key("2.2,3.2").extract(line, None), b"bar\t ");
+        assert_eq!(key("2.2b,3.2b").extract(line, None), b"ar\t ba");
+        assert_eq!(key("4").extract(line, None), b"");
+        assert_eq!(key("3,2").extract(line, None), b"");
+        assert_eq!(key("1.20").extract(line, None), b"");
+    }
+
+    #[test]
+    fn extract_separator_fields() {
+        let line = b"a:bc::d";
+        let sep = Some(b':');
+
+        assert_eq!(key("1,1").extract(line, sep), b"a");
+        assert_eq!(key("2,2").extract(line, sep), b"bc");
+        assert_eq!(key("2,3").extract(line, sep), b"bc:");
+        assert_eq!(key("3,3").extract(line, sep), b"");
+        assert_eq!(key("4").extract(line, sep), b"d");
+        assert_eq!(key("2.2,4.1").extract(line, sep), b"c::d");
+        assert_eq!(key("5").extract(line, sep), b"");
+    }
+}
@@ -1,87 +1,323 @@
 use std::{
-    error, fmt,
+    cmp::Ordering,
+    env, error, fmt,
     fs::File,
     io::{self, prelude::*, BufReader, BufWriter},
+    mem,
+    path::{Path, PathBuf},
 };
 
 use clap::ArgMatches;
 
 mod cli;
+mod compare;
+mod key;
+mod runs;
+
+use compare::Comparator;
+use key::{Key, KeyError, KeyOptions};
+use runs::Runs;
 
 type Buffer = Vec<u8>;
 
+/// The memory used for the lines before the sorted runs are written to temporary files.
+const DEFAULT_BUFFER_SIZE: usize = 512 * 1024 * 1024;
+
+/// The minimum buffer size, so the number of temporary files stays low.
+const MIN_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The maximum number of the runs merged at once.
+const MERGE_FAN_IN: usize = 16;
+
 fn main() {
     let matches = cli::create_app().get_matches();
 
-    main_sort(matches).unwrap_or_else(|err| {
+    let code = main_sort(matches).unwrap_or_else(|err| {
         eprintln!("sort: {}.", err);
         std::process::exit(2);
     });
+
+    std::process::exit(code);
 }
 
-fn main_sort(matches: clap::ArgMatches) -> Result<(), SortError> {
+/// Sort, merge or check the inputs, returning the exit code.
+fn main_sort(matches: clap::ArgMatches) -> Result<i32, SortError> {
     let mut flags = SortFlags::from_matches(&matches)?;
-    let inputs = get_inputs(&matches)?;
 
-    let lines = sort(&flags, inputs);
-    for line in lines {
-        print_line(line, &mut flags)?;
+    if let Some(check) = flags.check {
+        return main_check(&matches, &flags, check);
     }
 
-    Ok(())
+    if flags.merge_only {
+        let inputs = open_inputs(&matches)?;
+        let (output, output_name) = (&mut flags.output, &flags.output_name);
+        merge(&flags.comparator, flags.unique, inputs, |line| {
+            write_line(output, line).map_err(|err| SortError::write(output_name, err))
+        })?;
+    } else {
+        sort_inputs(&mut flags, open_inputs(&matches)?)?;
+    }
+
+    flags.output.flush().map_err(|err| SortError::write(&flags.output_name, err))?;
+
+    Ok(0)
 }
 
-fn get_inputs(matches: &clap::ArgMatches) -> Result<Vec<Buffer>, SortError> {
-    match matches.values_of("INPUT_FILES") {
-        Some(files) => {
-            let files: Vec<_> = files
-                .map(|path| {
-                    File::open(path)
-                        .map(BufReader::new)
-                        .map(|file| {
-                            file.split(b'\n')
-                                .map(move |res| res.map_err(|err| SortError::read(path, err)))
-                        })
-                        .map_err(|err| SortError::read(path, err))
-                })
-                .collect::<Result<_, _>>()?;
-
-            let cap = files.iter().fold(0_usize, |acc, v| acc + v.size_hint().0);
-            // let mut inputs = Vec::with_capacity(cap);
-
-            let inputs =
-                files.into_iter().try_fold(Vec::with_capacity(cap), |mut inputs, mut lines| {
-                    lines.try_for_each(|line| {
-                        inputs.push(line?);
-                        Ok(())
-                    })?;
-                    Ok(inputs)
-                })?;
+/// Check that the single input is sorted, returning the exit code.
+fn main_check(matches: &ArgMatches, flags: &SortFlags, check: Check) -> Result<i32, SortError> {
+    let mut paths = matches.values_of("INPUT_FILES").into_iter().flatten();
+    let path = paths.next().unwrap_or("-");
+
+    if let Some(extra) = paths.next() {
+        let option = if check == Check::Quiet { "-C" } else { "-c" };
+        return Err(SortError::extra_operand(extra, option));
+    }
 
-            Ok(inputs)
+    match check_sorted(flags, open_lines(path)?)? {
+        Some((number, line)) => {
+            if check == Check::Diagnose {
+                eprintln!(
+                    "sort: {}:{}: disorder: {}",
+                    path,
+                    number,
+                    String::from_utf8_lossy(&line)
+                );
+            }
+            Ok(1)
         },
-        None => BufReader::new(io::stdin())
+        None => Ok(0),
+    }
+}
+
+/// The lines of a input.
+type Lines<'a> = Box<dyn Iterator<Item = Result<Buffer, SortError>> + 'a>;
+
+/// Open the input on `path`, the standard input if it is `-`.
+fn open_lines(path: &str) -> Result<Lines<'_>, SortError> {
+    if path == "-" {
+        let lines = BufReader::new(io::stdin())
             .split(b'\n')
-            .map(|res| res.map_err(|err| SortError::read("stdin", err)))
-            .collect(),
+            .map(|res| res.map_err(|err| SortError::read("stdin", err)));
+        return Ok(Box::new(lines));
+    }
+
+    let file = File::open(path).map_err(|err| SortError::read(path, err))?;
+    let lines = BufReader::new(file)
+        .split(b'\n')
+        .map(move |res| res.map_err(|err| SortError::read(path, err)));
+    Ok(Box::new(lines))
+}
+
+/// Open all the inputs, the standard input if there are none.
+fn open_inputs<'a>(matches: &'a ArgMatches) -> Result<Vec<Lines<'a>>, SortError> {
+    match matches.values_of("INPUT_FILES") {
+        Some(files) => files.map(open_lines).collect(),
+        None => Ok(vec![open_lines("-")?]),
+    }
+}
+
+#[cfg(test)]
+fn get_inputs(matches: &clap::ArgMatches) -> Result<Vec<Buffer>, SortError> {
+    let mut lines = open_inputs(matches)?.into_iter().flatten();
+    read_chunk(&mut lines, usize::MAX).map(|(inputs, _)| inputs)
+}
+
+/// Read the `lines` until they take `size` bytes of memory. Returns the lines and if they
+/// ended.
+fn read_chunk<I>(lines: &mut I, size: usize) -> Result<(Vec<Buffer>, bool), SortError>
+where
+    I: Iterator<Item = Result<Buffer, SortError>>,
+{
+    let mut chunk = Vec::new();
+    let mut chunk_size = 0_usize;
+
+    while chunk_size < size {
+        match lines.next() {
+            Some(line) => {
+                let line = line?;
+                chunk_size = chunk_size.saturating_add(line.len() + mem::size_of::<Buffer>());
+                chunk.push(line);
+            },
+            None => return Ok((chunk, true)),
+        }
+    }
+
+    Ok((chunk, false))
+}
+
+/// Sort the `inputs` to the output. When they don't fit in the buffer, the sorted runs
+/// are written to temporary files and merged at the end.
+fn sort_inputs(flags: &mut SortFlags, inputs: Vec<Lines<'_>>) -> Result<(), SortError> {
+    let mut runs = Runs::new(flags.temp_dirs.clone(), flags.compress_program.clone());
+    let mut lines = inputs.into_iter().flatten();
+
+    loop {
+        let (chunk, ended) = read_chunk(&mut lines, flags.buffer_size)?;
+        let chunk = sort(flags, chunk);
+
+        if ended && runs.is_empty() {
+            for line in chunk {
+                print_line(&line, flags)?;
+            }
+            return Ok(());
+        }
+
+        if ended {
+            // Keep the number of the open files low, merging the first runs in one
+            while runs.len() >= MERGE_FAN_IN {
+                let dir = runs.dir().clone();
+                let temp_err = |err| SortError::temp(&dir, err);
+
+                let mut writer = runs.create().map_err(temp_err)?;
+                let inputs = run_lines(&runs, MERGE_FAN_IN)?;
+                merge(&flags.comparator, flags.unique, inputs, |line| {
+                    write_line(&mut writer, line).map_err(temp_err)
+                })?;
+                runs.replace_first(MERGE_FAN_IN, writer).map_err(temp_err)?;
+            }
+
+            let mut inputs = run_lines(&runs, runs.len())?;
+            inputs.push(Box::new(chunk.into_iter().map(Ok)));
+
+            let (output, output_name) = (&mut flags.output, &flags.output_name);
+            return merge(&flags.comparator, flags.unique, inputs, |line| {
+                write_line(output, line).map_err(|err| SortError::write(output_name, err))
+            });
+        }
+
+        let dir = runs.dir().clone();
+        let mut writer = runs.create().map_err(|err| SortError::temp(&dir, err))?;
+        for line in &chunk {
+            write_line(&mut writer, line).map_err(|err| SortError::temp(&dir, err))?;
+        }
+        runs.push(writer).map_err(|err| SortError::temp(&dir, err))?;
     }
 }
 
+/// Open the lines of the first `n` runs.
+fn run_lines(runs: &Runs, n: usize) -> Result<Vec<Lines<'static>>, SortError> {
+    let dir = runs.dir().clone();
+    let readers = runs.open_first(n).map_err(|err| SortError::temp(&dir, err))?;
+
+    Ok(readers
+        .into_iter()
+        .map(|reader| {
+            let dir = dir.clone();
+            let lines = BufReader::new(reader)
+                .split(b'\n')
+                .map(move |res| res.map_err(|err| SortError::temp(&dir, err)));
+            Box::new(lines) as Lines
+        })
+        .collect())
+}
+
 fn sort(flags: &SortFlags, mut inputs: Vec<Buffer>) -> Vec<Buffer> {
-    if !flags.merge_only {
-        inputs.sort_unstable();
+    inputs.sort_by(|a, b| flags.comparator.compare(a, b));
+
+    if flags.unique {
+        inputs.dedup_by(|line, kept| flags.comparator.compare(kept, line) == Ordering::Equal);
     }
 
     inputs
 }
 
-fn print_line(line: Buffer, flags: &mut SortFlags) -> Result<(), SortError> {
-    flags.output.write(&line).map_err(|err| SortError::write(&flags.output_name, err))?;
-    writeln!(flags.output).map_err(|err| SortError::write(&flags.output_name, err))
+/// Merge the already sorted `inputs` to `output`. The lines that compare equal are taken
+/// first from the first inputs.
+fn merge<F>(
+    comparator: &Comparator, unique: bool, mut inputs: Vec<Lines<'_>>, mut output: F,
+) -> Result<(), SortError>
+where
+    F: FnMut(&[u8]) -> Result<(), SortError>,
+{
+    let mut heads = Vec::with_capacity(inputs.len());
+    for input in &mut inputs {
+        heads.push(input.next().transpose()?);
+    }
+
+    let mut last: Option<Buffer> = None;
+
+    loop {
+        let mut min: Option<usize> = None;
+        for (i, head) in heads.iter().enumerate() {
+            let head = match head {
+                Some(head) => head,
+                None => continue,
+            };
+
+            match min {
+                Some(m)
+                    if comparator.compare(head, heads[m].as_ref().unwrap()) != Ordering::Less => {},
+                _ => min = Some(i),
+            }
+        }
+
+        let i = match min {
+            Some(i) => i,
+            None => return Ok(()),
+        };
+
+        let line = std::mem::replace(&mut heads[i], inputs[i].next().transpose()?).unwrap();
+
+        if unique {
+            if let Some(last) = &last {
+                if comparator.compare(last, &line) == Ordering::Equal {
+                    continue;
+                }
+            }
+            output(&line)?;
+            last = Some(line);
+        } else {
+            output(&line)?;
+        }
+    }
+}
+
+/// Check that the `lines` are sorted, strictly if the output is unique. Returns the first
+/// line out of order with its line number.
+fn check_sorted(flags: &SortFlags, lines: Lines<'_>) -> Result<Option<(usize, Buffer)>, SortError> {
+    let mut prev: Option<Buffer> = None;
+
+    for (i, line) in lines.enumerate() {
+        let line = line?;
+
+        if let Some(prev) = &prev {
+            let ord = flags.comparator.compare(prev, &line);
+            if ord == Ordering::Greater || (flags.unique && ord == Ordering::Equal) {
+                return Ok(Some((i + 1, line)));
+            }
+        }
+        prev = Some(line);
+    }
+
+    Ok(None)
+}
+
+fn print_line(line: &[u8], flags: &mut SortFlags) -> Result<(), SortError> {
+    write_line(&mut flags.output, line).map_err(|err| SortError::write(&flags.output_name, err))
+}
+
+fn write_line<W: Write>(output: &mut W, line: &[u8]) -> io::Result<()> {
+    output.write_all(line)?;
+    output.write_all(b"\n")
+}
+
+/// How the `--check` mode reports the disorder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Check {
+    /// Print the first line out of order.
+    Diagnose,
+    /// Only exit with failure.
+    Quiet,
 }
 
 struct SortFlags {
     merge_only: bool,
+    check: Option<Check>,
+    unique: bool,
+    comparator: Comparator,
+    buffer_size: usize,
+    temp_dirs: Vec<PathBuf>,
+    compress_program: Option<String>,
     output_name: String,
     output: Box<dyn Write>,
 }
@@ -89,6 +325,79 @@ struct SortFlags {
 impl SortFlags {
     pub fn from_matches(matches: &ArgMatches) -> Result<Self, SortError> {
         let merge_only = matches.is_present("merge_only");
+        let unique = matches.is_present("unique");
+
+        let quiet_word = matches!(matches.value_of("check_word"), Some("quiet") | Some("silent"));
+        let diagnose =
+            matches.is_present("check") || (matches.is_present("check_word") && !quiet_word);
+        let quiet = matches.is_present("check_quiet") || quiet_word;
+
+        let check = match (diagnose, quiet) {
+            (true, true) => return Err(SortError::incompatible("cC".to_string())),
+            (true, false) => Some(Check::Diagnose),
+            (false, true) => Some(Check::Quiet),
+            (false, false) => None,
+        };
+
+        if check.is_some() && matches.is_present("OUTPUT_FILE") {
+            let option = if check == Some(Check::Quiet) { "Co" } else { "co" };
+            return Err(SortError::incompatible(option.to_string()));
+        }
+
+        let separator = match matches.value_of("field_separator") {
+            Some(sep) => Some(parse_separator(sep)?),
+            None => None,
+        };
+
+        let keys = matches
+            .values_of("key")
+            .map(|specs| specs.map(Key::parse).collect::<Result<Vec<_>, _>>())
+            .transpose()
+            .map_err(SortError::key)?
+            .unwrap_or_default();
+
+        let blanks = matches.is_present("ignore_leading_blanks");
+        let sort_word = matches.value_of("sort");
+        let global = KeyOptions {
+            numeric: matches.is_present("numeric_sort") || sort_word == Some("numeric"),
+            human_numeric: matches.is_present("human_numeric_sort")
+                || sort_word == Some("human-numeric"),
+            month: matches.is_present("month_sort") || sort_word == Some("month"),
+            version: matches.is_present("version_sort") || sort_word == Some("version"),
+            random: matches.is_present("random_sort") || sort_word == Some("random"),
+            reverse: matches.is_present("reverse"),
+            fold_case: matches.is_present("ignore_case"),
+            dictionary: matches.is_present("dictionary_order"),
+            ignore_nonprinting: matches.is_present("ignore_nonprinting"),
+            skip_start_blanks: blanks,
+            skip_end_blanks: blanks,
+        };
+
+        // The global options are only used by the keys without options
+        if keys.is_empty() || keys.iter().any(|key| key.options.is_default()) {
+            global.check_compatible().map_err(SortError::incompatible)?;
+        }
+        for key in &keys {
+            key.options.check_compatible().map_err(SortError::incompatible)?;
+        }
+
+        // The unique output compares only the keys, so the first of the equal lines is kept
+        let stable = matches.is_present("stable") || unique;
+        let comparator = Comparator::new(keys, global, separator, stable);
+
+        let buffer_size = match matches.value_of("buffer_size") {
+            Some(size) => parse_buffer_size(size)
+                .ok_or_else(|| SortError::buffer_size(size))?
+                .max(MIN_BUFFER_SIZE),
+            None => DEFAULT_BUFFER_SIZE,
+        };
+
+        let temp_dirs = match matches.values_of("temporary_directory") {
+            Some(dirs) => dirs.map(PathBuf::from).collect(),
+            None => vec![env::temp_dir()],
+        };
+        let compress_program = matches.value_of("compress_program").map(String::from);
+
         let (output_name, output): (String, Box<dyn Write>) = match matches.value_of("OUTPUT_FILE")
         {
             Some(path) => match File::create(path) {
@@ -97,10 +406,50 @@ impl SortFlags {
             },
             None => ("stdout".to_string(), Box::new(BufWriter::new(io::stdout()))),
         };
-        Ok(SortFlags { merge_only, output_name, output })
+        Ok(SortFlags {
+            merge_only,
+            check,
+            unique,
+            comparator,
+            buffer_size,
+            temp_dirs,
+            compress_program,
+            output_name,
+            output,
+        })
+    }
+}
+
+/// Parse the `-t` separator, that must be a single byte. `\0` is the NUL byte.
+fn parse_separator(sep: &str) -> Result<u8, SortError> {
+    match sep.as_bytes() {
+        [byte] => Ok(*byte),
+        b"\\0" => Ok(0),
+        [] => Err(SortError::separator(sep, "empty tab")),
+        _ => Err(SortError::separator(sep, "multi-character tab")),
     }
 }
 
+/// Parse the `-S` buffer size, in KiB without a suffix.
+fn parse_buffer_size(size: &str) -> Option<usize> {
+    let digits_end = size.find(|c: char| !c.is_ascii_digit()).unwrap_or(size.len());
+    let (number, suffix) = size.split_at(digits_end);
+    let number: usize = number.parse().ok()?;
+
+    let exponent = match suffix {
+        "b" => 0,
+        "" | "k" | "K" => 1,
+        "m" | "M" => 2,
+        "g" | "G" => 3,
+        "t" | "T" => 4,
+        "p" | "P" => 5,
+        "e" | "E" => 6,
+        _ => return None,
+    };
+
+    number.checked_mul(1024_usize.checked_pow(exponent)?)
+}
+
 #[derive(Debug)]
 struct SortError {
     path: String,
@@ -115,12 +464,42 @@ impl SortError {
     fn write(path: &str, err: io::Error) -> Self {
         SortError { path: path.to_string(), ty: SortErrorTy::FileWriteError(err) }
     }
+
+    fn key(err: KeyError) -> Self {
+        SortError { path: String::new(), ty: SortErrorTy::InvalidKey(err) }
+    }
+
+    fn separator(sep: &str, reason: &'static str) -> Self {
+        SortError { path: sep.to_string(), ty: SortErrorTy::InvalidSeparator(reason) }
+    }
+
+    fn incompatible(options: String) -> Self {
+        SortError { path: String::new(), ty: SortErrorTy::IncompatibleOptions(options) }
+    }
+
+    fn temp(dir: &Path, err: io::Error) -> Self {
+        SortError { path: dir.display().to_string(), ty: SortErrorTy::TempFileError(err) }
+    }
+
+    fn buffer_size(size: &str) -> Self {
+        SortError { path: size.to_string(), ty: SortErrorTy::InvalidBufferSize }
+    }
+
+    fn extra_operand(path: &str, option: &'static str) -> Self {
+        SortError { path: path.to_string(), ty: SortErrorTy::ExtraOperand(option) }
+    }
 }
 
 #[derive(Debug)]
 enum SortErrorTy {
     FileReadError(io::Error),
     FileWriteError(io::Error),
+    TempFileError(io::Error),
+    InvalidKey(KeyError),
+    InvalidSeparator(&'static str),
+    IncompatibleOptions(String),
+    ExtraOperand(&'static str),
+    InvalidBufferSize,
 }
 
 impl fmt::Display for SortError {
@@ -132,6 +511,18 @@ impl fmt::Display for SortError {
             SortErrorTy::FileWriteError(ref err) => {
                 write!(f, "failed to write file {}: {}", self.path, err)
             },
+            SortErrorTy::TempFileError(ref err) => {
+                write!(f, "failed to use a temporary file in {}: {}", self.path, err)
+            },
+            SortErrorTy::InvalidKey(ref err) => write!(f, "{}", err),
+            SortErrorTy::InvalidSeparator(reason) => write!(f, "{} '{}'", reason, self.path),
+            SortErrorTy::IncompatibleOptions(ref options) => {
+                write!(f, "options '-{}' are incompatible", options)
+            },
+            SortErrorTy::ExtraOperand(option) => {
+                write!(f, "extra operand '{}' not allowed with {}", self.path, option)
+            },
+            SortErrorTy::InvalidBufferSize => write!(f, "invalid -S argument '{}'", self.path),
         }
     }
 }
@@ -141,6 +532,12 @@ impl error::Error for SortError {
         match self.ty {
             SortErrorTy::FileReadError(ref err) => Some(err),
             SortErrorTy::FileWriteError(ref err) => Some(err),
+            SortErrorTy::TempFileError(ref err) => Some(err),
+            SortErrorTy::InvalidKey(ref err) => Some(err),
+            SortErrorTy::InvalidSeparator(_)
+            | SortErrorTy::IncompatibleOptions(_)
+            | SortErrorTy::ExtraOperand(_)
+            | SortErrorTy::InvalidBufferSize => None,
         }
     }
 }
@@ -183,7 +580,7 @@ mod tests {
                 $file1.path().to_str().unwrap(),
                 $file2.path().to_str().unwrap(),
                 $file3.path().to_str().unwrap(),
-            ]);
+            ])
         };
         ($file1:ident, $file2:ident, $file3:ident, $output_path:ident) => {
             cli::create_app().get_matches_from(vec![
@@ -193,16 +590,22 @@ mod tests {
                 $file1.path().to_str().unwrap(),
                 $file2.path().to_str().unwrap(),
                 $file3.path().to_str().unwrap(),
-            ]);
+            ])
         };
         ($filePathAsString:ident) => {
-            cli::create_app().get_matches_from(vec!["sort", $filePathAsString]);
+            cli::create_app().get_matches_from(vec!["sort", $filePathAsString])
         };
     }
 
     fn default_flags() -> SortFlags {
         SortFlags {
             merge_only: false,
+            check: None,
+            unique: false,
+            comparator: Comparator::new(Vec::new(), KeyOptions::default(), None, false),
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            temp_dirs: vec![env::temp_dir()],
+            compress_program: None,
             output_name: "stdout".to_string(),
             output: Box::new(BufWriter::new(io::stdout())),
         }
@@ -321,4 +724,101 @@ mod tests {
             fs::read(output_file_path).unwrap()
         )
     }
+
+    #[test]
+    fn test_sort_unique() {
+        let keys = vec![Key::parse("2").unwrap()];
+        let flags = SortFlags {
+            unique: true,
+            comparator: Comparator::new(keys, KeyOptions::default(), None, true),
+            ..default_flags()
+        };
+        let inputs = vec![b"b 1".to_vec(), b"a 2".to_vec(), b"a 1".to_vec(), b"c 2".to_vec()];
+
+        assert_eq!(vec![b"b 1".to_vec(), b"a 2".to_vec()], sort(&flags, inputs));
+    }
+
+    #[test]
+    fn test_main_merge() {
+        let output_file = NamedTempFile::new().unwrap();
+        let output_path = output_file.path().to_str().unwrap();
+
+        let (file1, file2, file3) =
+            create_temp_files!("a 1\nc 1\ne 1", "b 2\nc 2\nd 2", "a 3\nf 3");
+        let paths: Vec<_> = [&file1, &file2, &file3]
+            .iter()
+            .map(|file| file.path().to_str().unwrap().to_string())
+            .collect();
+
+        let matches = cli::create_app().get_matches_from(
+            ["sort", "-m", "-k1,1", "-o", output_path]
+                .iter()
+                .copied()
+                .chain(paths.iter().map(|p| p.as_str())),
+        );
+        main_sort(matches).unwrap();
+        assert_eq!(
+            b"a 1\na 3\nb 2\nc 1\nc 2\nd 2\ne 1\nf 3\n".to_vec(),
+            fs::read(output_file.path()).unwrap()
+        );
+
+        let matches = cli::create_app().get_matches_from(
+            ["sort", "-mu", "-k1,1", "-o", output_path]
+                .iter()
+                .copied()
+                .chain(paths.iter().map(|p| p.as_str())),
+        );
+        main_sort(matches).unwrap();
+        assert_eq!(
+            b"a 1\nb 2\nc 1\nd 2\ne 1\nf 3\n".to_vec(),
+            fs::read(output_file.path()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_check_sorted() {
+        let (file1, file2, file3) = create_temp_files!("a\nb\nb\nc", "a\nc\nb\nd", "b 1\na 2");
+        let unique = SortFlags { unique: true, ..default_flags() };
+        let keys = vec![Key::parse("2").unwrap()];
+        let by_key = SortFlags {
+            comparator: Comparator::new(keys, KeyOptions::default(), None, false),
+            ..default_flags()
+        };
+        let check = |flags: &SortFlags, file: &NamedTempFile| {
+            check_sorted(flags, open_lines(file.path().to_str().unwrap()).unwrap()).unwrap()
+        };
+
+        assert_eq!(None, check(&default_flags(), &file1));
+        assert_eq!(Some((3, b"b".to_vec())), check(&unique, &file1));
+        assert_eq!(Some((3, b"b".to_vec())), check(&default_flags(), &file2));
+        assert_eq!(Some((2, b"a 2".to_vec())), check(&default_flags(), &file3));
+        assert_eq!(None, check(&by_key, &file3));
+    }
+
+    #[test]
+    fn test_sort_inputs_spilled() {
+        let output_file = NamedTempFile::new().unwrap();
+        let lines: Vec<_> = (0..100).map(|i| format!("{:03}", (i * 37) % 100)).collect();
+        let input = lines.iter().map(|line| Ok(line.as_bytes().to_vec()));
+
+        let mut flags = SortFlags {
+            buffer_size: 64,
+            output: Box::new(output_file.reopen().unwrap()),
+            ..default_flags()
+        };
+        sort_inputs(&mut flags, vec![Box::new(input)]).unwrap();
+        drop(flags);
+
+        let expected: String = (0..100).map(|i| format!("{:03}\n", i)).collect();
+        assert_eq!(expected.into_bytes(), fs::read(output_file.path()).unwrap());
+    }
+
+    #[test]
+    fn test_parse_buffer_size() {
+        assert_eq!(parse_buffer_size("10"), Some(10 * 1024));
+        assert_eq!(parse_buffer_size("100b"), Some(100));
+        assert_eq!(parse_buffer_size("2M"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_buffer_size("1x"), None);
+        assert_eq!(parse_buffer_size("M"), None);
+    }
 }
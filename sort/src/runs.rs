@@ -0,0 +1,193 @@
+//! Temporary files with the sorted runs of the inputs that don't fit in the buffer.
+use std::{
+    fs::File,
+    io::{self, BufWriter, Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+/// The sorted runs written to the temporary files, in the input order.
+#[derive(Debug)]
+pub struct Runs {
+    /// The directories where the temporary files are created, in turns.
+    dirs: Vec<PathBuf>,
+    /// The program that compresses the temporary files, and decompress them with `-d`.
+    compress_program: Option<String>,
+    files: Vec<File>,
+}
+
+impl Runs {
+    /// Creates a empty [`Runs`].
+    pub fn new(dirs: Vec<PathBuf>, compress_program: Option<String>) -> Self {
+        Runs { dirs, compress_program, files: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The directory where the next temporary file is created.
+    pub fn dir(&self) -> &PathBuf {
+        &self.dirs[self.files.len() % self.dirs.len()]
+    }
+
+    /// Creates a new temporary file for a run, that is added after the lines are written
+    /// with [`Runs::push`].
+    ///
+    /// # Errors
+    /// If the file can't be created or the compress program can't be executed, an error
+    /// variant will be returned.
+    pub fn create(&self) -> io::Result<RunWriter> {
+        let file = tempfile::tempfile_in(self.dir())?;
+
+        match self.compress_program {
+            Some(ref program) => {
+                let mut child = Command::new(program)
+                    .stdin(Stdio::piped())
+                    .stdout(file.try_clone()?)
+                    .spawn()
+                    .map_err(|err| spawn_error(program, "", err))?;
+
+                let stdin = BufWriter::new(child.stdin.take().unwrap());
+                Ok(RunWriter::Compressed { program: program.clone(), child, stdin, file })
+            },
+            None => Ok(RunWriter::Plain(BufWriter::new(file))),
+        }
+    }
+
+    /// Add the run written with `writer` after the existing ones.
+    ///
+    /// # Errors
+    /// If the lines can't be flushed to the file or the compress program fails, an error
+    /// variant will be returned.
+    pub fn push(&mut self, writer: RunWriter) -> io::Result<()> {
+        let file = writer.finish()?;
+        self.files.push(file);
+        Ok(())
+    }
+
+    /// Replace the first `n` runs by the run written with `writer`.
+    ///
+    /// # Errors
+    /// If the lines can't be flushed to the file or the compress program fails, an error
+    /// variant will be returned.
+    pub fn replace_first(&mut self, n: usize, writer: RunWriter) -> io::Result<()> {
+        let file = writer.finish()?;
+        self.files.splice(..n, Some(file));
+        Ok(())
+    }
+
+    /// Open the first `n` runs for reading, or all of them if there are less.
+    ///
+    /// # Errors
+    /// If the files can't be read or the compress program can't be executed, an error
+    /// variant will be returned.
+    pub fn open_first(&self, n: usize) -> io::Result<Vec<RunReader>> {
+        self.files
+            .iter()
+            .take(n)
+            .map(|file| {
+                let mut file = file.try_clone()?;
+                file.seek(SeekFrom::Start(0))?;
+
+                match self.compress_program {
+                    Some(ref program) => {
+                        let mut child = Command::new(program)
+                            .arg("-d")
+                            .stdin(file)
+                            .stdout(Stdio::piped())
+                            .spawn()
+                            .map_err(|err| spawn_error(program, " -d", err))?;
+
+                        let stdout = child.stdout.take().unwrap();
+                        Ok(RunReader::Decompressed { program: program.clone(), child, stdout })
+                    },
+                    None => Ok(RunReader::Plain(file)),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Writer of the lines of a new run.
+#[derive(Debug)]
+pub enum RunWriter {
+    Plain(BufWriter<File>),
+    Compressed { program: String, child: Child, stdin: BufWriter<ChildStdin>, file: File },
+}
+
+impl RunWriter {
+    /// Flush the lines and wait for the compress program to finish.
+    fn finish(self) -> io::Result<File> {
+        match self {
+            RunWriter::Plain(writer) => writer.into_inner().map_err(|err| err.into_error()),
+            RunWriter::Compressed { program, mut child, stdin, file } => {
+                // Close the pipe so the program knows the input ended
+                drop(stdin.into_inner().map_err(|err| err.into_error())?);
+                wait_success(&program, "", &mut child)?;
+                Ok(file)
+            },
+        }
+    }
+}
+
+impl Write for RunWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            RunWriter::Plain(writer) => writer.write(buf),
+            RunWriter::Compressed { stdin, .. } => stdin.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            RunWriter::Plain(writer) => writer.flush(),
+            RunWriter::Compressed { stdin, .. } => stdin.flush(),
+        }
+    }
+}
+
+/// Reader of the lines of a run.
+#[derive(Debug)]
+pub enum RunReader {
+    Plain(File),
+    Decompressed { program: String, child: Child, stdout: ChildStdout },
+}
+
+impl Read for RunReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            RunReader::Plain(file) => file.read(buf),
+            RunReader::Decompressed { program, child, stdout } => {
+                let read = stdout.read(buf)?;
+                if read == 0 && !buf.is_empty() {
+                    wait_success(program, " -d", child)?;
+                }
+                Ok(read)
+            },
+        }
+    }
+}
+
+fn spawn_error(program: &str, args: &str, err: io::Error) -> io::Error {
+    io::Error::new(err.kind(), format!("couldn't execute '{}{}': {}", program, args, err))
+}
+
+/// Wait for `child` to exit, failing if it didn't exit successfully.
+fn wait_success(program: &str, args: &str, child: &mut Child) -> io::Result<()> {
+    if child.wait()?.success() {
+        Ok(())
+    } else {
+        let msg = format!("'{}{}' terminated abnormally", program, args);
+
+        // `io::Error::other` is newer than the MSRV
+        #[allow(clippy::io_other_error)]
+        let err = io::Error::new(io::ErrorKind::Other, msg);
+
+        Err(err)
+    }
+}
@@ -0,0 +1,170 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("File to split, or the standard input if it is '-' or not given.")
+                .index(1),
+        )
+        .arg(
+            Arg::with_name("PREFIX")
+                .help("Prefix of the names of the pieces, that is 'x' if it is not given.")
+                .index(2),
+        )
+        .arg(
+            Arg::with_name("bytes")
+                .help("Put SIZE bytes in each piece.")
+                .long_help(
+                    "Put SIZE bytes in each piece.\n\nSIZE is a number with an optional unit: b \
+                     for 512, K, M, G, T, P, E, Z or Y for the powers of 1024, that can also be \
+                     written as KiB, MiB and so on, or KB, MB and so on for the powers of 1000.",
+                )
+                .long("bytes")
+                .short("b")
+                .value_name("SIZE")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("lineBytes")
+                .help("Put at most SIZE bytes of whole lines in each piece.")
+                .long_help(
+                    "Put at most SIZE bytes of whole lines in each piece.\n\nThe lines longer \
+                     than SIZE are split in pieces of SIZE bytes.",
+                )
+                .long("line-bytes")
+                .short("C")
+                .value_name("SIZE")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("lines")
+                .help("Put NUMBER lines in each piece (default: 1000).")
+                .long("lines")
+                .short("l")
+                .value_name("NUMBER")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("number")
+                .help("Split in CHUNKS pieces of the same size.")
+                .long_help(
+                    "Split in CHUNKS pieces of the same size.\n\nCHUNKS may be:\n  N      split \
+                     in N pieces of bytes\n  K/N    output the Kth of N pieces of bytes\n  l/N    \
+                     split in N pieces of whole lines\n  l/K/N  output the Kth of N pieces of \
+                     whole lines\n  r/N    split the lines in N pieces, one by one\n  r/K/N  \
+                     output the Kth of N pieces of the lines, one by one\n\nThe pieces that are \
+                     output are written to the standard output instead of a file.",
+                )
+                .long("number")
+                .short("n")
+                .value_name("CHUNKS")
+                .allow_hyphen_values(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("suffixLength")
+                .help("Use suffixes of length N (default: 2).")
+                .long_help(
+                    "Use suffixes of length N (default: 2).\n\nWithout it, the suffixes get \
+                     longer when they would run out, except with --number or a FROM of \
+                     --numeric-suffixes or --hex-suffixes.",
+                )
+                .long("suffix-length")
+                .short("a")
+                .value_name("N")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("numericSuffixes")
+                .help("Use numeric suffixes, starting at FROM (default: 0).")
+                .long_help(
+                    "Use numeric suffixes, starting at FROM (default: 0).\n\nWith FROM, the \
+                     suffixes don't get longer when they would run out.",
+                )
+                .long("numeric-suffixes")
+                .short("d")
+                .value_name("FROM")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .empty_values(true)
+                .overrides_with("hexSuffixes"),
+        )
+        .arg(
+            Arg::with_name("hexSuffixes")
+                .help("Use hexadecimal suffixes, starting at FROM (default: 0).")
+                .long_help(
+                    "Use hexadecimal suffixes, starting at FROM (default: 0).\n\nWith FROM, the \
+                     suffixes don't get longer when they would run out.",
+                )
+                .long("hex-suffixes")
+                .short("x")
+                .value_name("FROM")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .empty_values(true)
+                .overrides_with("numericSuffixes"),
+        )
+        .arg(
+            Arg::with_name("additionalSuffix")
+                .help("Append SUFFIX to the names of the pieces.")
+                .long("additional-suffix")
+                .value_name("SUFFIX")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("separator")
+                .help("Use SEP instead of the newline to end the lines, where '\\0' is NUL.")
+                .long("separator")
+                .short("t")
+                .value_name("SEP")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("filter")
+                .help("Write each piece to the input of COMMAND, with its name in $FILE.")
+                .long_help(
+                    "Write each piece to the input of COMMAND, with its name in $FILE.\n\nCOMMAND \
+                     is run by the shell in $SHELL, or /bin/sh if it is not set, once for each \
+                     piece, like in --filter='gzip > $FILE.gz'.",
+                )
+                .long("filter")
+                .value_name("COMMAND"),
+        )
+        .arg(
+            Arg::with_name("elideEmpty")
+                .help("Do not create the empty pieces with --number.")
+                .long("elide-empty-files")
+                .short("e"),
+        )
+        .arg(
+            Arg::with_name("unbuffered")
+                .help("Write each line at once with --number r/N.")
+                .long("unbuffered")
+                .short("u"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .help("Print a message for each piece created.")
+                .long("verbose"),
+        )
+}
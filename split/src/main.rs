@@ -0,0 +1,1122 @@
+use std::{
+    cmp, env,
+    ffi::{OsStr, OsString},
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Cursor, Read, Seek, SeekFrom, Write},
+    os::unix::{ffi::OsStrExt, fs::MetadataExt, io::FromRawFd, process::ExitStatusExt},
+    path::Path,
+    process::{self, Child, Command, Stdio},
+};
+
+use clap::ArgMatches;
+use coreutils_core::{
+    libc,
+    size::{parse_size, SizeError},
+};
+
+mod cli;
+mod name;
+
+use name::{Names, ALPHABETIC, HEXADECIMAL, NUMERIC, SUFFIX_LENGTH};
+
+/// The suffixes of the units of the sizes.
+const SUFFIXES: &str = "bEGKkMmPTYZ";
+
+/// The lines of each piece when the way to split isn't given.
+const LINES: u64 = 1000;
+
+/// The size of the buffer of the reads.
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// How the input is split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// In pieces of a number of bytes.
+    Bytes(u64),
+    /// In pieces of a number of lines.
+    Lines(u64),
+    /// In pieces of at most a number of bytes of whole lines.
+    LineBytes(u64),
+    /// In a number of pieces, or only the one of them that is output.
+    Chunks { kind: Chunk, number: u64, only: Option<u64> },
+}
+
+/// How the input is split in a number of pieces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Chunk {
+    /// In pieces of the same number of bytes.
+    Bytes,
+    /// In pieces of about the same number of bytes, of whole lines.
+    Lines,
+    /// In pieces of the lines one by one, like dealing cards.
+    RoundRobin,
+}
+
+/// The options of a split.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Options {
+    mode: Mode,
+    /// The characters of the suffixes, and the one that they start at.
+    alphabet: &'static [u8],
+    start: String,
+    /// The length of the suffixes, and whether they grow instead of being exhausted.
+    suffix_length: usize,
+    grow: bool,
+    /// The suffix after the one of the characters of the alphabet.
+    additional: OsString,
+    /// The byte that ends the lines.
+    separator: u8,
+    /// Whether the empty pieces are not created with [`Mode::Chunks`].
+    elide_empty: bool,
+    /// Whether each line is written at once with [`Chunk::RoundRobin`].
+    unbuffered: bool,
+    /// The command that each piece is written to instead of a file.
+    filter: Option<OsString>,
+    verbose: bool,
+}
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let options = Options::from_matches(&matches).unwrap_or_else(|err| {
+        eprintln!("split: {}", err);
+        process::exit(1);
+    });
+
+    let path = matches.value_of_os("FILE").filter(|&path| path != "-").map(Path::new);
+    let prefix = matches.value_of_os("PREFIX").unwrap_or_else(|| OsStr::new("x"));
+
+    if let Err(err) = split(path, prefix, &options) {
+        eprintln!("split: {}", err);
+        process::exit(1);
+    }
+}
+
+impl Options {
+    /// The options of the `matches`.
+    ///
+    /// # Errors
+    /// If there is more than a way to split, or an option has a value that is not valid,
+    /// an error message will be returned.
+    fn from_matches(matches: &ArgMatches) -> Result<Self, String> {
+        let ways: u64 = ["bytes", "lineBytes", "lines", "number"]
+            .iter()
+            .map(|&way| matches.occurrences_of(way))
+            .sum();
+
+        if ways > 1 {
+            return Err("cannot split in more than one way".to_string());
+        }
+
+        let mode = if let Some(size) = matches.value_of("bytes") {
+            Mode::Bytes(parse_number(size, SUFFIXES, "invalid number of bytes")?)
+        } else if let Some(size) = matches.value_of("lineBytes") {
+            Mode::LineBytes(parse_number(size, SUFFIXES, "invalid number of bytes")?)
+        } else if let Some(lines) = matches.value_of("lines") {
+            Mode::Lines(parse_number(lines, "", "invalid number of lines")?)
+        } else if let Some(chunks) = matches.value_of("number") {
+            parse_chunks(chunks)?
+        } else {
+            Mode::Lines(LINES)
+        };
+
+        let (alphabet, start) = if matches.is_present("numericSuffixes") {
+            let start = matches.value_of("numericSuffixes");
+            (NUMERIC, parse_start(start, NUMERIC, "numerical")?)
+        } else if matches.is_present("hexSuffixes") {
+            let start = matches.value_of("hexSuffixes");
+            (HEXADECIMAL, parse_start(start, HEXADECIMAL, "hexadecimal")?)
+        } else {
+            (ALPHABETIC, None)
+        };
+
+        let given_length = match matches.value_of("suffixLength") {
+            Some(length) => match length.parse() {
+                Ok(0) => None,
+                Ok(length) => Some(length),
+                Err(_) => return Err(format!("invalid suffix length: '{}'", length)),
+            },
+            None => None,
+        };
+
+        // The pieces of the chunks are all named at once, after the start if it is lower
+        // than their number
+        let needed = match mode {
+            Mode::Chunks { number, .. } => {
+                let start = start
+                    .and_then(|start| u64::from_str_radix(start, alphabet.len() as u32).ok())
+                    .filter(|&start| start < number)
+                    .unwrap_or(0);
+
+                Some(Names::length_for(number.saturating_add(start), alphabet))
+            },
+            _ => None,
+        };
+
+        let suffix_length = match (given_length, needed) {
+            (Some(length), Some(needed)) if length < needed => {
+                return Err(format!("the suffix length needs to be at least {}", needed));
+            },
+            (Some(length), _) => length,
+            (None, needed) => cmp::max(SUFFIX_LENGTH, needed.unwrap_or(0)),
+        };
+
+        if matches!(start, Some(start) if start.len() > suffix_length) {
+            return Err(
+                "numerical suffix start value is too large for the suffix length".to_string()
+            );
+        }
+
+        let additional = matches.value_of_os("additionalSuffix").unwrap_or_default();
+
+        if additional.as_bytes().contains(&b'/') {
+            return Err(format!(
+                "invalid suffix '{}', contains directory separator",
+                additional.to_string_lossy()
+            ));
+        }
+
+        let filter = matches.value_of_os("filter").map(OsStr::to_os_string);
+
+        if filter.is_some() && matches!(mode, Mode::Chunks { only: Some(_), .. }) {
+            return Err("--filter does not process a chunk extracted to stdout".to_string());
+        }
+
+        let separator = match matches.values_of("separator") {
+            Some(separators) => parse_separator(separators)?,
+            None => b'\n',
+        };
+
+        Ok(Options {
+            mode,
+            alphabet,
+            start: start.unwrap_or_default().to_string(),
+            suffix_length,
+            grow: given_length.is_none() && needed.is_none() && start.is_none(),
+            additional: additional.to_os_string(),
+            separator,
+            elide_empty: matches.is_present("elideEmpty"),
+            unbuffered: matches.is_present("unbuffered"),
+            filter,
+            verbose: matches.is_present("verbose"),
+        })
+    }
+}
+
+/// Parse the positive `number`, with the optional `suffixes` of its units, where `what`
+/// it is starts the error message.
+///
+/// # Errors
+/// If it is not a positive number that fits in 63 bits, an error message will be
+/// returned.
+fn parse_number(number: &str, suffixes: &str, what: &str) -> Result<u64, String> {
+    match parse_size(number, suffixes) {
+        Ok(0) => Err(format!("{}: '{}': Numerical result out of range", what, number)),
+        Ok(value) if value <= i64::MAX as u64 => Ok(value),
+        Ok(_) | Err(SizeError::TooLarge) => {
+            Err(format!("{}: '{}': {}", what, number, SizeError::TooLarge))
+        },
+        Err(SizeError::Invalid) => Err(format!("{}: '{}'", what, number)),
+    }
+}
+
+/// Parse the `start` of the suffixes of the characters of the `alphabet`, without its
+/// leading zeros, where `what` they are ends the error message.
+///
+/// # Errors
+/// If it has characters that are not in the `alphabet`, an error message will be
+/// returned.
+fn parse_start<'a>(
+    start: Option<&'a str>, alphabet: &[u8], what: &str,
+) -> Result<Option<&'a str>, String> {
+    match start {
+        Some(start) if start.bytes().all(|byte| alphabet.contains(&byte)) => {
+            // The last zero is kept, so the start is 0
+            let digits = start.trim_start_matches('0');
+            Ok(Some(if digits.is_empty() && !start.is_empty() { "0" } else { digits }))
+        },
+        Some(start) => Err(format!("'{}': invalid start value for {} suffix", start, what)),
+        None => Ok(None),
+    }
+}
+
+/// Parse the `chunks` of `--number`, that are `N`, `K/N`, `l/N`, `l/K/N`, `r/N` or
+/// `r/K/N`.
+///
+/// # Errors
+/// If the numbers are not positive, or the one of the chunk output is greater than the
+/// number of chunks, an error message will be returned.
+fn parse_chunks(chunks: &str) -> Result<Mode, String> {
+    let (kind, numbers) = if let Some(numbers) = chunks.strip_prefix("l/") {
+        (Chunk::Lines, numbers)
+    } else if let Some(numbers) = chunks.strip_prefix("r/") {
+        (Chunk::RoundRobin, numbers)
+    } else {
+        (Chunk::Bytes, chunks)
+    };
+
+    let (only, number) = match numbers.find('/') {
+        Some(slash) => (Some(&numbers[..slash]), &numbers[slash + 1..]),
+        None => (None, numbers),
+    };
+
+    let number = parse_number(number, "", "invalid number of chunks")?;
+
+    let only = match only.map(|only| (only, parse_number(only, "", "invalid chunk number"))) {
+        Some((only, Ok(chunk))) if chunk > number => {
+            return Err(format!("invalid chunk number: '{}': Numerical result out of range", only));
+        },
+        Some((_, chunk)) => Some(chunk?),
+        None => None,
+    };
+
+    Ok(Mode::Chunks { kind, number, only })
+}
+
+/// Parse the `separators` of the lines, that must be the same character, where `\0` is
+/// NUL.
+///
+/// # Errors
+/// If a separator is empty or has more than a character, or they are not the same, an
+/// error message will be returned.
+fn parse_separator<'a>(separators: impl Iterator<Item = &'a str>) -> Result<u8, String> {
+    let mut separator = None;
+
+    for text in separators {
+        let byte = match text.as_bytes() {
+            [] => return Err("empty record separator".to_string()),
+            [byte] => *byte,
+            b"\\0" => b'\0',
+            _ => return Err(format!("multi-character separator '{}'", text)),
+        };
+
+        if matches!(separator, Some(separator) if separator != byte) {
+            return Err("multiple separator characters specified".to_string());
+        }
+        separator = Some(byte);
+    }
+
+    Ok(separator.unwrap_or(b'\n'))
+}
+
+/// Split the file at `path`, or the standard input if there is none, into the pieces
+/// named after the `prefix`, as the `options` say.
+///
+/// # Errors
+/// If the input can't be read, or a piece can't be created or written, an error message
+/// will be returned.
+fn split(path: Option<&Path>, prefix: &OsStr, options: &Options) -> Result<(), String> {
+    let (mut input, name) = match path {
+        Some(path) => match File::open(path) {
+            Ok(file) => (file, path.display().to_string()),
+            Err(err) => {
+                return Err(format!("cannot open '{}' for reading: {}", path.display(), err));
+            },
+        },
+        // Only closed when split finishes
+        None => (unsafe { File::from_raw_fd(libc::STDIN_FILENO) }, "-".to_string()),
+    };
+
+    let metadata = input.metadata().map_err(|err| format!("{}: {}", name, err))?;
+
+    let mut pieces = Pieces {
+        names: Names::new(
+            prefix,
+            &options.additional,
+            options.alphabet,
+            options.suffix_length,
+            &options.start,
+            options.grow,
+        ),
+        input: (metadata.dev(), metadata.ino()),
+        filter: options.filter.clone(),
+        verbose: options.verbose,
+        current: None,
+    };
+
+    let mut reader = Reader { input: &mut input, name: &name, buffer: vec![0; BUFFER_SIZE] };
+    let separator = options.separator;
+
+    let split = match options.mode {
+        Mode::Bytes(size) => split_bytes(&mut reader, &mut pieces, size),
+        Mode::Lines(lines) => split_lines(&mut reader, &mut pieces, lines, separator),
+        Mode::LineBytes(size) => split_line_bytes(&mut reader, &mut pieces, size, separator),
+        Mode::Chunks { kind: Chunk::RoundRobin, number, only } => {
+            deal_lines(&mut reader, &mut pieces, number, only, options)
+        },
+        Mode::Chunks { kind, number, only } => {
+            // The size of the input must be known, so it's read to the end if it's not a file
+            if metadata.is_file() {
+                // `stream_position` is newer than the MSRV
+                #[allow(clippy::seek_from_current)]
+                let position = reader.input.seek(SeekFrom::Current(0)).unwrap_or(0);
+                let size = metadata.len().saturating_sub(position);
+                split_chunks(&mut reader, &mut pieces, kind, number, only, size, options)
+            } else {
+                let mut bytes = Vec::new();
+                reader.input.read_to_end(&mut bytes).map_err(|err| format!("{}: {}", name, err))?;
+
+                let size = bytes.len() as u64;
+                let mut input = Cursor::new(bytes);
+                let mut reader = Reader { input: &mut input, name: &name, buffer: reader.buffer };
+                split_chunks(&mut reader, &mut pieces, kind, number, only, size, options)
+            }
+        },
+    };
+
+    split.and_then(|_| pieces.finish())
+}
+
+/// Split the input of the `reader` into `pieces` of `size` bytes.
+///
+/// # Errors
+/// If the input can't be read, or a piece can't be created or written, an error message
+/// will be returned.
+fn split_bytes<R: Read>(
+    reader: &mut Reader<R>, pieces: &mut Pieces, size: u64,
+) -> Result<(), String> {
+    // The bytes left to write in the current piece
+    let mut left = 0;
+
+    reader.read_each(|mut bytes| {
+        while !bytes.is_empty() {
+            if left == 0 {
+                pieces.next()?;
+                left = size;
+            }
+
+            let taken = cmp::min(left, bytes.len() as u64) as usize;
+
+            pieces.write(&bytes[..taken])?;
+            left -= taken as u64;
+            bytes = &bytes[taken..];
+        }
+
+        Ok(())
+    })
+}
+
+/// Split the input of the `reader` into `pieces` of a number of `lines`, that end with
+/// the `separator`.
+///
+/// # Errors
+/// If the input can't be read, or a piece can't be created or written, an error message
+/// will be returned.
+fn split_lines<R: Read>(
+    reader: &mut Reader<R>, pieces: &mut Pieces, lines: u64, separator: u8,
+) -> Result<(), String> {
+    // The lines left to write in the current piece
+    let mut left = 0;
+
+    reader.read_each(|mut bytes| {
+        while !bytes.is_empty() {
+            if left == 0 {
+                pieces.next()?;
+                left = lines;
+            }
+
+            let mut end = bytes.len();
+
+            for (position, _) in bytes.iter().enumerate().filter(|(_, &byte)| byte == separator) {
+                left -= 1;
+
+                if left == 0 {
+                    end = position + 1;
+                    break;
+                }
+            }
+
+            pieces.write(&bytes[..end])?;
+            bytes = &bytes[end..];
+        }
+
+        Ok(())
+    })
+}
+
+/// Split the input of the `reader` into `pieces` of at most `size` bytes of whole lines,
+/// that end with the `separator`, where the lines longer than `size` are split in pieces
+/// of `size` bytes.
+///
+/// # Errors
+/// If the input can't be read, or a piece can't be created or written, an error message
+/// will be returned.
+fn split_line_bytes<R: Read>(
+    reader: &mut Reader<R>, pieces: &mut Pieces, size: u64, separator: u8,
+) -> Result<(), String> {
+    // The bytes written in the current piece
+    let mut written = 0;
+    // The bytes after the last line of the current piece, that are written once it's known
+    // if their line fits in it
+    let mut held = Vec::new();
+    // Whether a line was ended in the current piece, so the next one is not split
+    let mut ended = false;
+
+    let write = |pieces: &mut Pieces, written: &mut u64, bytes: &[u8]| {
+        if *written == 0 {
+            pieces.next()?;
+        }
+
+        *written += bytes.len() as u64;
+        pieces.write(bytes)
+    };
+
+    reader.read_each(|mut bytes| {
+        while !bytes.is_empty() {
+            // The bytes that are left in the current piece
+            let room = size - written - held.len() as u64;
+            let full = bytes.len() as u64 >= room;
+            let (window, rest) = bytes.split_at(cmp::min(room, bytes.len() as u64) as usize);
+
+            match window.iter().rposition(|&byte| byte == separator) {
+                Some(end) => {
+                    held.extend_from_slice(&window[..=end]);
+                    write(pieces, &mut written, &held)?;
+
+                    held.clear();
+                    held.extend_from_slice(&window[end + 1..]);
+                    ended = true;
+                },
+                // Split the line that doesn't fit in a piece
+                None if !ended => {
+                    held.extend_from_slice(window);
+                    write(pieces, &mut written, &held)?;
+                    held.clear();
+                },
+                None => held.extend_from_slice(window),
+            }
+
+            bytes = rest;
+
+            if full {
+                written = 0;
+                ended = false;
+            }
+        }
+
+        Ok(())
+    })?;
+
+    if !held.is_empty() {
+        write(pieces, &mut written, &held)?;
+    }
+
+    Ok(())
+}
+
+/// Split the input of the `reader`, of `size` bytes, into a `number` of `pieces` of the
+/// `kind`, or write the `only` one of them to the standard output.
+///
+/// # Errors
+/// If the input can't be read, or a piece can't be created or written, an error message
+/// will be returned.
+fn split_chunks<R: Read + Seek>(
+    reader: &mut Reader<R>, pieces: &mut Pieces, kind: Chunk, number: u64, only: Option<u64>,
+    size: u64, options: &Options,
+) -> Result<(), String> {
+    let chunk_size = cmp::max(1, size / number);
+    // The start of each piece, where the last one ends at the end of the input
+    let start = |index: u64| cmp::min(size, index.saturating_mul(chunk_size));
+    let end = |index: u64| if index == number - 1 { size } else { start(index + 1) };
+    // The index of the piece of the lines that start at an offset
+    let index_of = |offset: u64| cmp::min(offset / chunk_size, number - 1);
+
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+
+    let split = match (kind, only) {
+        (Chunk::Bytes, Some(only)) => {
+            reader
+                .input
+                .seek(SeekFrom::Current(start(only - 1) as i64))
+                .map_err(|err| format!("{}: {}", reader.name, err))?;
+
+            reader.copy(end(only - 1) - start(only - 1), |bytes| {
+                output.write_all(bytes).map_err(|err| format!("write error: {}", err))
+            })
+        },
+        (Chunk::Bytes, None) => {
+            for index in 0..number {
+                let length = end(index) - start(index);
+
+                if length > 0 || !options.elide_empty {
+                    pieces.next()?;
+                    reader.copy(length, |bytes| pieces.write(bytes))?;
+                }
+            }
+
+            Ok(())
+        },
+        (_, Some(only)) => {
+            let mut offset = 0;
+            // Whether the current line starts in the piece
+            let mut inside = false;
+
+            reader.read_lines(options.separator, |line, starts| {
+                if starts {
+                    let index = index_of(offset);
+
+                    if index > only - 1 {
+                        return Ok(false);
+                    }
+                    inside = index == only - 1;
+                }
+
+                if inside {
+                    output.write_all(line).map_err(|err| format!("write error: {}", err))?;
+                }
+
+                offset += line.len() as u64;
+                Ok(true)
+            })
+        },
+        (_, None) => {
+            // The index of the current piece, that has the lines that start in it
+            let mut current = None;
+            let mut offset = 0;
+
+            reader.read_lines(options.separator, |line, starts| {
+                if starts {
+                    let index = index_of(offset);
+                    let first = current.map_or(0, |current| current + 1);
+
+                    // The pieces without lines
+                    for _ in first..index {
+                        if !options.elide_empty {
+                            pieces.next()?;
+                        }
+                    }
+
+                    if current != Some(index) {
+                        pieces.next()?;
+                        current = Some(index);
+                    }
+                }
+
+                pieces.write(line)?;
+                offset += line.len() as u64;
+                Ok(true)
+            })?;
+
+            // The pieces after the last line
+            if !options.elide_empty {
+                let first = current.map_or(0, |current| current + 1);
+
+                for _ in first..number {
+                    pieces.next()?;
+                }
+            }
+
+            Ok(())
+        },
+    };
+
+    split.and_then(|_| output.flush().map_err(|err| format!("write error: {}", err)))
+}
+
+/// Deal the lines of the input of the `reader` one by one to a `number` of `pieces`, or
+/// write the lines of the `only` one of them to the standard output, as the `options`
+/// say.
+///
+/// # Errors
+/// If the input can't be read, or a piece can't be created or written, an error message
+/// will be returned.
+fn deal_lines<R: Read>(
+    reader: &mut Reader<R>, pieces: &mut Pieces, number: u64, only: Option<u64>, options: &Options,
+) -> Result<(), String> {
+    let stdout = io::stdout();
+
+    let mut outputs: Vec<Output> = match only {
+        Some(_) => vec![Output::Standard(stdout.lock())],
+        None => {
+            let mut outputs = Vec::new();
+
+            for _ in 0..number {
+                let name = pieces.next_name()?;
+
+                outputs.push(if options.elide_empty {
+                    Output::Closed(name)
+                } else {
+                    Output::Piece(BufWriter::new(pieces.create(name)?))
+                });
+            }
+
+            outputs
+        },
+    };
+
+    // The index of the piece of the current line
+    let mut index = 0;
+
+    reader.read_lines(options.separator, |line, _| {
+        let ended = line.last() == Some(&options.separator);
+        let flush = options.unbuffered && ended;
+
+        match only {
+            Some(only) if index == only - 1 => outputs[0].write(line, flush, pieces)?,
+            Some(_) => {},
+            None => outputs[index as usize].write(line, flush, pieces)?,
+        }
+
+        if ended {
+            index = (index + 1) % number;
+        }
+
+        Ok(true)
+    })?;
+
+    for output in outputs {
+        output.close(pieces)?;
+    }
+
+    Ok(())
+}
+
+/// An output of the lines dealt to a piece.
+enum Output<'a> {
+    Standard(io::StdoutLock<'a>),
+    /// A piece that is created when a line is written to it.
+    Closed(OsString),
+    Piece(BufWriter<Piece>),
+}
+
+impl Output<'_> {
+    /// Write the `bytes`, flushing them at once if `flush`.
+    ///
+    /// # Errors
+    /// If a piece can't be created or written, an error message will be returned.
+    fn write(&mut self, bytes: &[u8], flush: bool, pieces: &Pieces) -> Result<(), String> {
+        if let Output::Closed(name) = self {
+            *self = Output::Piece(BufWriter::new(pieces.create(name.clone())?));
+        }
+
+        let result = match self {
+            Output::Standard(stdout) => {
+                stdout.write_all(bytes).and_then(|_| if flush { stdout.flush() } else { Ok(()) })
+            },
+            Output::Piece(piece) => {
+                piece.write_all(bytes).and_then(|_| if flush { piece.flush() } else { Ok(()) })
+            },
+            Output::Closed(_) => Ok(()),
+        };
+
+        result.map_err(|err| self.error(err))
+    }
+
+    /// Flush the bytes written.
+    ///
+    /// # Errors
+    /// If they can't be written, an error message will be returned.
+    fn flush(&mut self) -> Result<(), String> {
+        let result = match self {
+            Output::Standard(stdout) => stdout.flush(),
+            Output::Piece(piece) => piece.flush(),
+            Output::Closed(_) => Ok(()),
+        };
+
+        result.map_err(|err| self.error(err))
+    }
+
+    /// Flush the bytes written, and close the piece.
+    ///
+    /// # Errors
+    /// If the bytes can't be written, or the filter of the piece fails, an error message
+    /// will be returned.
+    fn close(mut self, pieces: &Pieces) -> Result<(), String> {
+        self.flush()?;
+
+        match self {
+            Output::Piece(piece) => match piece.into_inner() {
+                Ok(piece) => pieces.close(piece),
+                Err(err) => Err(format!("write error: {}", err.error())),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn error(&self, err: io::Error) -> String {
+        match self {
+            Output::Piece(piece) => format!("{}: {}", piece.get_ref().name.to_string_lossy(), err),
+            _ => format!("write error: {}", err),
+        }
+    }
+}
+
+/// The reader of the input.
+struct Reader<'a, R> {
+    input: &'a mut R,
+    name: &'a str,
+    buffer: Vec<u8>,
+}
+
+impl<R: Read> Reader<'_, R> {
+    /// Read the input to its end, calling `each` with the bytes of each read.
+    ///
+    /// # Errors
+    /// If the input can't be read, or `each` fails, an error message will be returned.
+    fn read_each(
+        &mut self, mut each: impl FnMut(&[u8]) -> Result<(), String>,
+    ) -> Result<(), String> {
+        loop {
+            match self.input.read(&mut self.buffer) {
+                Ok(0) => return Ok(()),
+                Ok(read) => each(&self.buffer[..read])?,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {},
+                Err(err) => return Err(format!("{}: {}", self.name, err)),
+            }
+        }
+    }
+
+    /// Read the input to its end, or until `each` returns false, calling it with each
+    /// part of a line that ends with the `separator` in the reads, and whether it
+    /// starts the line.
+    ///
+    /// # Errors
+    /// If the input can't be read, or `each` fails, an error message will be returned.
+    fn read_lines(
+        &mut self, separator: u8, mut each: impl FnMut(&[u8], bool) -> Result<bool, String>,
+    ) -> Result<(), String> {
+        let mut starts = true;
+
+        loop {
+            let mut bytes = match self.input.read(&mut self.buffer) {
+                Ok(0) => return Ok(()),
+                Ok(read) => &self.buffer[..read],
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(format!("{}: {}", self.name, err)),
+            };
+
+            while !bytes.is_empty() {
+                let end = match bytes.iter().position(|&byte| byte == separator) {
+                    Some(position) => position + 1,
+                    None => bytes.len(),
+                };
+
+                if !each(&bytes[..end], starts)? {
+                    return Ok(());
+                }
+
+                starts = bytes[end - 1] == separator;
+                bytes = &bytes[end..];
+            }
+        }
+    }
+
+    /// Read at most `length` bytes of the input, calling `each` with the bytes of each
+    /// read.
+    ///
+    /// # Errors
+    /// If the input can't be read, or `each` fails, an error message will be returned.
+    fn copy(
+        &mut self, length: u64, mut each: impl FnMut(&[u8]) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let mut left = length;
+
+        while left > 0 {
+            let size = cmp::min(left, self.buffer.len() as u64) as usize;
+
+            match self.input.read(&mut self.buffer[..size]) {
+                Ok(0) => break,
+                Ok(read) => {
+                    each(&self.buffer[..read])?;
+                    left -= read as u64;
+                },
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {},
+                Err(err) => return Err(format!("{}: {}", self.name, err)),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A piece written to a file, or to the input of a filter.
+struct Piece {
+    sink: Sink,
+    name: OsString,
+}
+
+/// Where the bytes of a piece are written.
+enum Sink {
+    File(File),
+    /// The filter run for the piece, whose input is closed when it doesn't read it.
+    Filter(Child),
+}
+
+impl Write for Piece {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        match &mut self.sink {
+            Sink::File(file) => file.write(bytes),
+            Sink::Filter(child) => match &mut child.stdin {
+                Some(stdin) => match stdin.write(bytes) {
+                    // The rest of the piece is discarded when the filter stops reading it
+                    Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {
+                        child.stdin = None;
+                        Ok(bytes.len())
+                    },
+                    result => result,
+                },
+                None => Ok(bytes.len()),
+            },
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.sink {
+            Sink::File(file) => file.flush(),
+            Sink::Filter(_) => Ok(()),
+        }
+    }
+}
+
+/// The pieces, created one after the other.
+struct Pieces {
+    names: Names,
+    /// The device and inode of the input, that can't be overwritten by a piece.
+    input: (u64, u64),
+    /// The command that each piece is written to instead of a file.
+    filter: Option<OsString>,
+    /// Whether a message is printed for each piece created.
+    verbose: bool,
+    /// The piece that is written.
+    current: Option<Piece>,
+}
+
+impl Pieces {
+    /// The name of the next piece.
+    ///
+    /// # Errors
+    /// If the suffixes are exhausted, an error message will be returned.
+    fn next_name(&mut self) -> Result<OsString, String> {
+        self.names.next().ok_or_else(|| "output file suffixes exhausted".to_string())
+    }
+
+    /// Create the piece of `name`, truncating it if it exists, or run the filter for it.
+    ///
+    /// # Errors
+    /// If it can't be created, it is the input, or the filter can't be run, an error
+    /// message will be returned.
+    fn create(&self, name: OsString) -> Result<Piece, String> {
+        let path = Path::new(&name);
+        let failed = |err| format!("{}: {}", path.display(), err);
+
+        if let Some(filter) = &self.filter {
+            if self.verbose {
+                println!("executing with FILE={}", path.display());
+            }
+
+            let shell = env::var_os("SHELL").unwrap_or_else(|| OsString::from("/bin/sh"));
+            let child = Command::new(&shell)
+                .arg("-c")
+                .arg(filter)
+                .env("FILE", &name)
+                .stdin(Stdio::piped())
+                .spawn()
+                .map_err(|err| {
+                    format!(
+                        "failed to run command: \"{} -c {}\": {}",
+                        shell.to_string_lossy(),
+                        filter.to_string_lossy(),
+                        err
+                    )
+                })?;
+
+            return Ok(Piece { sink: Sink::Filter(child), name });
+        }
+
+        if self.verbose {
+            println!("creating file '{}'", path.display());
+        }
+
+        // Not truncated before it's known that it's not the input
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(failed)?;
+        let metadata = file.metadata().map_err(failed)?;
+
+        if (metadata.dev(), metadata.ino()) == self.input {
+            return Err(format!("'{}' would overwrite input; aborting", path.display()));
+        }
+
+        if metadata.is_file() {
+            file.set_len(0).map_err(failed)?;
+        }
+
+        Ok(Piece { sink: Sink::File(file), name })
+    }
+
+    /// Close the `piece`, waiting for its filter to finish.
+    ///
+    /// # Errors
+    /// If the filter exits with a failure, or is killed by a signal other than `SIGPIPE`,
+    /// an error message will be returned.
+    fn close(&self, piece: Piece) -> Result<(), String> {
+        let mut child = match piece.sink {
+            Sink::File(_) => return Ok(()),
+            Sink::Filter(child) => child,
+        };
+
+        // Closed so the filter knows that the piece ended
+        drop(child.stdin.take());
+
+        let status = child.wait().map_err(|err| format!("waiting for child process: {}", err))?;
+        let name = piece.name.to_string_lossy();
+        let filter = self.filter.as_deref().unwrap_or_default().to_string_lossy();
+
+        match (status.code(), status.signal()) {
+            (Some(code), _) if code != 0 => {
+                Err(format!("with FILE={}, exit {} from command: {}", name, code, filter))
+            },
+            (_, Some(signal)) if signal != libc::SIGPIPE => Err(format!(
+                "with FILE={}, signal {} from command: {}",
+                name,
+                signal_name(signal),
+                filter
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Create the next piece, that is written from now on.
+    ///
+    /// # Errors
+    /// If it can't be created, an error message will be returned.
+    fn next(&mut self) -> Result<(), String> {
+        self.finish()?;
+
+        let name = self.next_name()?;
+        self.current = Some(self.create(name)?);
+        Ok(())
+    }
+
+    /// Write the `bytes` to the current piece.
+    ///
+    /// # Errors
+    /// If they can't be written, an error message will be returned.
+    fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        match &mut self.current {
+            Some(piece) => piece
+                .write_all(bytes)
+                .map_err(|err| format!("{}: {}", piece.name.to_string_lossy(), err)),
+            None => Ok(()),
+        }
+    }
+
+    /// Close the current piece.
+    ///
+    /// # Errors
+    /// If its filter fails, an error message will be returned.
+    fn finish(&mut self) -> Result<(), String> {
+        match self.current.take() {
+            Some(piece) => self.close(piece),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The name of the `signal`, without the `SIG` prefix, or its number if it is not known.
+fn signal_name(signal: libc::c_int) -> String {
+    let name = match signal {
+        libc::SIGHUP => "HUP",
+        libc::SIGINT => "INT",
+        libc::SIGQUIT => "QUIT",
+        libc::SIGILL => "ILL",
+        libc::SIGTRAP => "TRAP",
+        libc::SIGABRT => "ABRT",
+        libc::SIGBUS => "BUS",
+        libc::SIGFPE => "FPE",
+        libc::SIGKILL => "KILL",
+        libc::SIGUSR1 => "USR1",
+        libc::SIGSEGV => "SEGV",
+        libc::SIGUSR2 => "USR2",
+        libc::SIGALRM => "ALRM",
+        libc::SIGTERM => "TERM",
+        libc::SIGXCPU => "XCPU",
+        libc::SIGXFSZ => "XFSZ",
+        _ => return signal.to_string(),
+    };
+
+    name.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        let bytes = |size| parse_number(size, SUFFIXES, "invalid number of bytes");
+
+        assert_eq!(bytes("10"), Ok(10));
+        assert_eq!(bytes("2b"), Ok(1024));
+        assert_eq!(bytes("1KB"), Ok(1000));
+        assert_eq!(bytes("K"), Ok(1024));
+        assert_eq!(bytes("1c"), Err("invalid number of bytes: '1c'".to_string()));
+        assert_eq!(
+            bytes("0"),
+            Err("invalid number of bytes: '0': Numerical result out of range".to_string())
+        );
+        assert_eq!(
+            bytes("10E"),
+            Err("invalid number of bytes: '10E': Value too large for defined data type".to_string())
+        );
+        assert_eq!(
+            parse_number("1K", "", "invalid number of lines"),
+            Err("invalid number of lines: '1K'".to_string())
+        );
+    }
+
+    #[test]
+    fn starts() {
+        assert_eq!(parse_start(None, NUMERIC, "numerical"), Ok(None));
+        assert_eq!(parse_start(Some("007"), NUMERIC, "numerical"), Ok(Some("7")));
+        assert_eq!(parse_start(Some("00"), NUMERIC, "numerical"), Ok(Some("0")));
+        assert_eq!(parse_start(Some("0ff"), HEXADECIMAL, "hexadecimal"), Ok(Some("ff")));
+        assert_eq!(
+            parse_start(Some("1a"), NUMERIC, "numerical"),
+            Err("'1a': invalid start value for numerical suffix".to_string())
+        );
+        assert_eq!(
+            parse_start(Some("F"), HEXADECIMAL, "hexadecimal"),
+            Err("'F': invalid start value for hexadecimal suffix".to_string())
+        );
+    }
+
+    #[test]
+    fn chunks() {
+        let chunks = |kind, number, only| Ok(Mode::Chunks { kind, number, only });
+
+        assert_eq!(parse_chunks("3"), chunks(Chunk::Bytes, 3, None));
+        assert_eq!(parse_chunks("2/3"), chunks(Chunk::Bytes, 3, Some(2)));
+        assert_eq!(parse_chunks("l/5"), chunks(Chunk::Lines, 5, None));
+        assert_eq!(parse_chunks("l/1/5"), chunks(Chunk::Lines, 5, Some(1)));
+        assert_eq!(parse_chunks("r/2/2"), chunks(Chunk::RoundRobin, 2, Some(2)));
+        assert_eq!(
+            parse_chunks("3/2"),
+            Err("invalid chunk number: '3': Numerical result out of range".to_string())
+        );
+        assert_eq!(parse_chunks("q/2"), Err("invalid chunk number: 'q'".to_string()));
+        assert_eq!(parse_chunks("l/2/"), Err("invalid number of chunks: ''".to_string()));
+    }
+
+    #[test]
+    fn separators() {
+        assert_eq!(parse_separator(["\\0"].iter().copied()), Ok(b'\0'));
+        assert_eq!(parse_separator([",", ","].iter().copied()), Ok(b','));
+        assert_eq!(
+            parse_separator(["a", "b"].iter().copied()),
+            Err("multiple separator characters specified".to_string())
+        );
+        assert_eq!(
+            parse_separator(["ab"].iter().copied()),
+            Err("multi-character separator 'ab'".to_string())
+        );
+        assert_eq!(
+            parse_separator([""].iter().copied()),
+            Err("empty record separator".to_string())
+        );
+    }
+}
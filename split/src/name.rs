@@ -0,0 +1,180 @@
+//! The names of the pieces, that are the prefix followed by a suffix of letters or
+//! digits, like `xaa`, `xab` and so on, and an additional suffix.
+use std::ffi::{OsStr, OsString};
+
+/// The characters of the alphabetic suffixes, in their order.
+pub(crate) const ALPHABETIC: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+/// The characters of the numeric suffixes, in their order.
+pub(crate) const NUMERIC: &[u8] = b"0123456789";
+
+/// The characters of the hexadecimal suffixes, in their order.
+pub(crate) const HEXADECIMAL: &[u8] = b"0123456789abcdef";
+
+/// The length of the suffixes when it isn't given.
+pub(crate) const SUFFIX_LENGTH: usize = 2;
+
+/// The names of the pieces, one after the other.
+#[derive(Debug, Clone)]
+pub(crate) struct Names {
+    prefix: OsString,
+    /// The characters of the suffixes.
+    alphabet: &'static [u8],
+    /// The indexes in the alphabet of the characters of the next suffix, or none if the
+    /// suffixes are exhausted.
+    suffix: Option<Vec<usize>>,
+    /// The suffix after the one of the characters of the alphabet.
+    additional: OsString,
+    /// Whether the suffixes get longer instead of being exhausted.
+    grow: bool,
+}
+
+impl Names {
+    /// The names of the `prefix` followed by the suffixes of `length` of the characters
+    /// of the `alphabet`, from the `start` padded with the first of them, and the
+    /// `additional` suffix, where the suffixes `grow` as `yz`, `zaaa`, `zaab` and so on
+    /// instead of being exhausted at `zz`.
+    ///
+    /// The `start` must have characters of the `alphabet` only, and at most `length` of
+    /// them.
+    pub(crate) fn new(
+        prefix: &OsStr, additional: &OsStr, alphabet: &'static [u8], length: usize, start: &str,
+        grow: bool,
+    ) -> Self {
+        let mut suffix = vec![0; length - start.len()];
+        suffix.extend(start.bytes().filter_map(|byte| alphabet.iter().position(|&c| c == byte)));
+
+        Names {
+            prefix: prefix.to_os_string(),
+            alphabet,
+            suffix: Some(suffix),
+            additional: additional.to_os_string(),
+            grow,
+        }
+    }
+
+    /// The length of the suffixes of the characters of the `alphabet` needed to name the
+    /// `count` of pieces.
+    pub(crate) fn length_for(count: u64, alphabet: &[u8]) -> usize {
+        let mut last = count.saturating_sub(1);
+        let mut length = 1;
+
+        while last >= alphabet.len() as u64 {
+            last /= alphabet.len() as u64;
+            length += 1;
+        }
+
+        length
+    }
+
+    /// Go past the `suffix` to the next one, that is longer when the first character
+    /// would be the last one of the alphabet and the suffixes grow.
+    fn advance(&mut self) {
+        let suffix = match &mut self.suffix {
+            Some(suffix) => suffix,
+            None => return,
+        };
+
+        let last = self.alphabet.len() - 1;
+
+        for position in (0..suffix.len()).rev() {
+            suffix[position] += 1;
+
+            if self.grow && position == 0 && suffix[0] == last {
+                // The last character is taken into the prefix, so the names stay sorted
+                self.prefix.push(char::from(self.alphabet[last]).to_string());
+                *suffix = vec![0; suffix.len() + 1];
+                return;
+            }
+
+            if suffix[position] < self.alphabet.len() {
+                return;
+            }
+
+            suffix[position] = 0;
+        }
+
+        self.suffix = None;
+    }
+}
+
+impl Iterator for Names {
+    type Item = OsString;
+
+    fn next(&mut self) -> Option<OsString> {
+        let suffix: String =
+            self.suffix.as_ref()?.iter().map(|&index| char::from(self.alphabet[index])).collect();
+
+        let mut name = self.prefix.clone();
+        name.push(suffix);
+        name.push(&self.additional);
+
+        self.advance();
+
+        Some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suffixes() {
+        let names: Vec<_> = Names::new(x(), empty(), ALPHABETIC, 1, "", false).collect();
+
+        assert_eq!(names.len(), 26);
+        assert_eq!(names[0], "xa");
+        assert_eq!(names[25], "xz");
+
+        let names: Vec<_> = Names::new(OsStr::new("p"), empty(), ALPHABETIC, 2, "", false)
+            .skip(26)
+            .take(2)
+            .collect();
+        assert_eq!(names, ["pba", "pbb"]);
+
+        let names: Vec<_> =
+            Names::new(x(), OsStr::new(".txt"), NUMERIC, 3, "98", false).skip(900).collect();
+        assert_eq!(names, ["x998.txt", "x999.txt"]);
+
+        let names: Vec<_> = Names::new(x(), empty(), HEXADECIMAL, 2, "e", false).take(3).collect();
+        assert_eq!(names, ["x0e", "x0f", "x10"]);
+    }
+
+    #[test]
+    fn growing_suffixes() {
+        let mut names = Names::new(x(), empty(), ALPHABETIC, 2, "", true).skip(649);
+
+        assert_eq!(names.next().unwrap(), "xyz");
+        assert_eq!(names.next().unwrap(), "xzaaa");
+
+        let mut names = names.skip(25 * 26 * 26 - 2);
+
+        assert_eq!(names.next().unwrap(), "xzyzz");
+        assert_eq!(names.next().unwrap(), "xzzaaaa");
+
+        let mut names = Names::new(x(), empty(), NUMERIC, 2, "", true).skip(89);
+
+        assert_eq!(names.next().unwrap(), "x89");
+        assert_eq!(names.next().unwrap(), "x9000");
+    }
+
+    #[test]
+    fn lengths() {
+        assert_eq!(Names::length_for(0, ALPHABETIC), 1);
+        assert_eq!(Names::length_for(26, ALPHABETIC), 1);
+        assert_eq!(Names::length_for(27, ALPHABETIC), 2);
+        assert_eq!(Names::length_for(676, ALPHABETIC), 2);
+        assert_eq!(Names::length_for(677, ALPHABETIC), 3);
+        assert_eq!(Names::length_for(100, NUMERIC), 2);
+        assert_eq!(Names::length_for(257, HEXADECIMAL), 3);
+    }
+
+    fn x() -> &'static OsStr {
+        OsStr::new("x")
+    }
+
+    fn empty() -> &'static OsStr {
+        OsStr::new("")
+    }
+}
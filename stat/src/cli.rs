@@ -0,0 +1,87 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .after_help(
+            "The valid format sequences for files are:\n\n  %a  access rights in octal\n  %A  \
+             access rights in human readable form\n  %b  number of blocks allocated (see %B)\n  \
+             %B  the size in bytes of each block reported by %b\n  %d  device number in decimal\n  \
+             %D  device number in hex\n  %f  raw mode in hex\n  %F  file type\n  %g  group ID of \
+             owner\n  %G  group name of owner\n  %h  number of hard links\n  %i  inode number\n  \
+             %m  mount point\n  %n  file name\n  %N  quoted file name with dereference if \
+             symbolic link\n  %o  optimal I/O transfer size hint\n  %s  total size, in bytes\n  \
+             %t  major device type in hex, for character/block device special files\n  %T  minor \
+             device type in hex, for character/block device special files\n  %u  user ID of \
+             owner\n  %U  user name of owner\n  %w  time of file birth, human readable; - if \
+             unknown\n  %W  time of file birth, seconds since Epoch; 0 if unknown\n  %x  time of \
+             last access, human readable\n  %X  time of last access, seconds since Epoch\n  %y  \
+             time of last data modification, human readable\n  %Y  time of last data \
+             modification, seconds since Epoch\n  %z  time of last status change, human \
+             readable\n  %Z  time of last status change, seconds since Epoch\n  %Hd %Ld  major \
+             and minor device number in decimal\n  %Hr %Lr  major and minor device type in \
+             decimal, for character/block device special files\n\nThe sequences take the flags, \
+             width and precision of printf(3), where the precision of %W, %X, %Y and %Z is the \
+             number of digits of the nanoseconds.\n\nThe valid format sequences for file systems \
+             are:\n\n  %a  free blocks available to non-superuser\n  %b  total data blocks in \
+             file system\n  %c  total file nodes in file system\n  %d  free file nodes in file \
+             system\n  %f  free blocks in file system\n  %i  file system ID in hex\n  %l  maximum \
+             length of filenames\n  %n  file name\n  %s  block size (for faster transfers)\n  %S  \
+             fundamental block size (for block counts)\n  %t  file system type in hex\n  %T  file \
+             system type in human readable form",
+        )
+        .arg(
+            Arg::with_name("FILE")
+                .help("File(s) to display the status.")
+                .multiple(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("dereference")
+                .help("Follow the symbolic links.")
+                .long("dereference")
+                .short("L"),
+        )
+        .arg(
+            Arg::with_name("file_system")
+                .help("Display the status of the file system of the files instead.")
+                .long("file-system")
+                .short("f"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("Use the FORMAT instead of the default, with a newline after each use.")
+                .long("format")
+                .short("c")
+                .value_name("FORMAT")
+                .allow_hyphen_values(true)
+                .overrides_with("printf"),
+        )
+        .arg(
+            Arg::with_name("printf")
+                .help("Like --format, but interpret the backslash escapes and without a newline.")
+                .long_help(
+                    "Like --format, but interpret the backslash escapes and without a \
+                     newline.\n\nThe escapes are \\\\, \\\", \\a, \\b, \\e, \\f, \\n, \\r, \\t, \
+                     \\v, \\NNN with 1 to 3 octal digits and \\xHH with 1 or 2 hex digits.",
+                )
+                .long("printf")
+                .value_name("FORMAT")
+                .allow_hyphen_values(true)
+                .overrides_with("format"),
+        )
+        .arg(
+            Arg::with_name("terse")
+                .help("Display the information in terse form.")
+                .long("terse")
+                .short("t"),
+        )
+}
@@ -0,0 +1,244 @@
+//! The status of the files and the values of their sequences.
+use std::{
+    fs::{self, Metadata},
+    io,
+    os::unix::fs::{FileTypeExt, MetadataExt},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use coreutils_core::{
+    datetime::{calendar::Fields, Zone},
+    libc,
+    os::{group::Group, passwd::Passwd},
+};
+
+use crate::format::Value;
+
+/// A file with its status.
+#[derive(Debug)]
+pub(crate) struct FileStatus<'a> {
+    name: &'a str,
+    metadata: Metadata,
+    /// Quote the names of `%N`.
+    quote: bool,
+}
+
+impl<'a> FileStatus<'a> {
+    /// The status of the file `name`, or of the file it points to if `dereference` and
+    /// it's a symbolic link.
+    pub(crate) fn new(name: &'a str, dereference: bool, quote: bool) -> io::Result<Self> {
+        let metadata = if dereference { fs::metadata(name)? } else { fs::symlink_metadata(name)? };
+
+        Ok(FileStatus { name, metadata, quote })
+    }
+
+    /// The file is a character or block device.
+    pub(crate) fn is_device(&self) -> bool {
+        let file_type = self.metadata.file_type();
+        file_type.is_char_device() || file_type.is_block_device()
+    }
+
+    /// The value of the sequence with the `modifier` and `conversion`, or [`None`] if
+    /// there is no such sequence.
+    pub(crate) fn directive(&self, modifier: Option<char>, conversion: char) -> Option<Value> {
+        let meta = &self.metadata;
+
+        let value = match (modifier, conversion) {
+            (_, 'a') => Value::Octal(u64::from(meta.mode() & 0o7777)),
+            (_, 'A') => Value::Str(unix_mode::to_string(meta.mode())),
+            (_, 'b') => Value::Unsigned(meta.blocks()),
+            // The blocks of `st_blocks` are always of 512 bytes
+            (_, 'B') => Value::Unsigned(512),
+            (None, 'd') => Value::Unsigned(meta.dev()),
+            (Some('H'), 'd') => Value::Unsigned(major(meta.dev())),
+            (Some(_), 'd') => Value::Unsigned(minor(meta.dev())),
+            (_, 'D') => Value::Hex(meta.dev()),
+            (_, 'f') => Value::Hex(u64::from(meta.mode())),
+            (_, 'F') => Value::Str(self.file_type().to_string()),
+            (_, 'g') => Value::Unsigned(u64::from(meta.gid())),
+            (_, 'G') => Value::Str(match Group::from_gid(meta.gid()) {
+                Ok(group) => group.name().to_string(),
+                Err(_) => "UNKNOWN".to_string(),
+            }),
+            (_, 'h') => Value::Unsigned(meta.nlink()),
+            (_, 'i') => Value::Unsigned(meta.ino()),
+            (_, 'm') => Value::Str(self.mount_point().ok()?.display().to_string()),
+            (_, 'n') => Value::Str(self.name.to_string()),
+            (_, 'N') => Value::Str(self.quoted_name()),
+            (_, 'o') => Value::Unsigned(meta.blksize()),
+            (Some('H'), 'r') => Value::Unsigned(major(meta.rdev())),
+            (Some(_), 'r') => Value::Unsigned(minor(meta.rdev())),
+            (_, 's') => Value::Unsigned(meta.size()),
+            (_, 't') => Value::Hex(major(meta.rdev())),
+            (_, 'T') => Value::Hex(minor(meta.rdev())),
+            (_, 'u') => Value::Unsigned(u64::from(meta.uid())),
+            (_, 'U') => Value::Str(match Passwd::from_uid(meta.uid()) {
+                Ok(passwd) => passwd.name().to_string(),
+                Err(_) => "UNKNOWN".to_string(),
+            }),
+            (_, 'w') => Value::Str(match self.birth() {
+                Some((seconds, nanos)) => human_time(seconds, nanos),
+                None => "-".to_string(),
+            }),
+            (_, 'W') => {
+                let (seconds, nanos) = self.birth().unwrap_or((0, 0));
+                Value::Epoch { seconds, nanos }
+            },
+            (_, 'x') => Value::Str(human_time(meta.atime(), meta.atime_nsec() as u32)),
+            (_, 'X') => Value::Epoch { seconds: meta.atime(), nanos: meta.atime_nsec() as u32 },
+            (_, 'y') => Value::Str(human_time(meta.mtime(), meta.mtime_nsec() as u32)),
+            (_, 'Y') => Value::Epoch { seconds: meta.mtime(), nanos: meta.mtime_nsec() as u32 },
+            (_, 'z') => Value::Str(human_time(meta.ctime(), meta.ctime_nsec() as u32)),
+            (_, 'Z') => Value::Epoch { seconds: meta.ctime(), nanos: meta.ctime_nsec() as u32 },
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    fn file_type(&self) -> &'static str {
+        let file_type = self.metadata.file_type();
+
+        if file_type.is_file() && self.metadata.len() == 0 {
+            "regular empty file"
+        } else if file_type.is_file() {
+            "regular file"
+        } else if file_type.is_dir() {
+            "directory"
+        } else if file_type.is_symlink() {
+            "symbolic link"
+        } else if file_type.is_fifo() {
+            "fifo"
+        } else if file_type.is_socket() {
+            "socket"
+        } else if file_type.is_char_device() {
+            "character special file"
+        } else if file_type.is_block_device() {
+            "block special file"
+        } else {
+            "weird file"
+        }
+    }
+
+    /// The name, with the file it points to if it's a symbolic link, like `'a' -> 'b'`.
+    fn quoted_name(&self) -> String {
+        let quote = |name: &str| if self.quote { shell_quote(name) } else { name.to_string() };
+
+        if self.metadata.file_type().is_symlink() {
+            if let Ok(target) = fs::read_link(self.name) {
+                return format!("{} -> {}", quote(self.name), quote(&target.to_string_lossy()));
+            }
+        }
+
+        quote(self.name)
+    }
+
+    /// The directory where the file system of the file is mounted, the last ancestor of
+    /// the file on its device.
+    fn mount_point(&self) -> io::Result<PathBuf> {
+        let path = Path::new(self.name);
+        let mut mount = if self.metadata.is_dir() {
+            fs::canonicalize(path)?
+        } else {
+            // Not the file a symbolic link points to, that may be in other device
+            match path.parent() {
+                Some(parent) if parent != Path::new("") => fs::canonicalize(parent)?,
+                _ => fs::canonicalize(".")?,
+            }
+        };
+
+        while let Some(parent) = mount.parent() {
+            if fs::metadata(parent)?.dev() != self.metadata.dev() {
+                break;
+            }
+            mount = parent.to_path_buf();
+        }
+
+        Ok(mount)
+    }
+
+    /// The time of the creation of the file, if the platform and the file system have it.
+    fn birth(&self) -> Option<(i64, u32)> {
+        let created = self.metadata.created().ok()?;
+
+        Some(match created.duration_since(UNIX_EPOCH) {
+            Ok(since) => (since.as_secs() as i64, since.subsec_nanos()),
+            Err(err) => {
+                let before = err.duration();
+                match before.subsec_nanos() {
+                    0 => (-(before.as_secs() as i64), 0),
+                    nanos => (-(before.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+                }
+            },
+        })
+    }
+}
+
+/// The time of `seconds` since the Epoch in the local time, like
+/// `2020-01-02 03:04:05.123456789 +0100`.
+fn human_time(seconds: i64, nanos: u32) -> String {
+    let offset = Zone::Local.offset_at(seconds);
+    let fields = Fields::from_seconds(seconds + offset);
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:09} {}{:02}{:02}",
+        fields.year,
+        fields.month,
+        fields.day,
+        fields.hour,
+        fields.minute,
+        fields.second,
+        nanos,
+        sign,
+        offset / 3600,
+        offset / 60 % 60
+    )
+}
+
+/// Quote `name` for the shell, with single quotes unless it has one of them.
+fn shell_quote(name: &str) -> String {
+    if !name.contains('\'') {
+        format!("'{}'", name)
+    } else if !name.contains(&['"', '$', '`', '\\', '!'][..]) {
+        format!("\"{}\"", name)
+    } else {
+        format!("'{}'", name.replace('\'', "'\\''"))
+    }
+}
+
+// The major and minor numbers of the device `dev`
+#[cfg(not(target_os = "haiku"))]
+fn major(dev: u64) -> u64 {
+    libc::major(dev as libc::dev_t) as u64
+}
+
+#[cfg(not(target_os = "haiku"))]
+fn minor(dev: u64) -> u64 {
+    libc::minor(dev as libc::dev_t) as u64
+}
+
+// Haiku doesn't split the device numbers
+#[cfg(target_os = "haiku")]
+fn major(dev: u64) -> u64 {
+    dev
+}
+
+#[cfg(target_os = "haiku")]
+fn minor(_dev: u64) -> u64 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote() {
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("it's"), "\"it's\"");
+        assert_eq!(shell_quote("it's $5"), "'it'\\''s $5'");
+    }
+}
@@ -0,0 +1,271 @@
+//! The status of the file systems of the files and the values of their sequences.
+use std::{ffi::CString, io, mem::MaybeUninit};
+
+use coreutils_core::libc;
+
+use crate::format::Value;
+
+/// The file system of a file with its status.
+pub(crate) struct FsStatus<'a> {
+    name: &'a str,
+    status: libc::statvfs,
+    fs_type: u64,
+    type_name: String,
+    id: u64,
+}
+
+impl<'a> FsStatus<'a> {
+    /// The status of file system of the file `name`.
+    pub(crate) fn new(name: &'a str) -> io::Result<Self> {
+        let path = CString::new(name)?;
+
+        let mut status = MaybeUninit::uninit();
+        let result = unsafe { libc::statvfs(path.as_ptr(), status.as_mut_ptr()) };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: Initialized by the successful statvfs
+        let status = unsafe { status.assume_init() };
+
+        let (fs_type, type_name, id) = fs_type(&path, &status)?;
+
+        Ok(FsStatus { name, status, fs_type, type_name, id })
+    }
+
+    /// The value of the sequence with the `modifier` and `conversion`, or [`None`] if
+    /// there is no such sequence.
+    pub(crate) fn directive(&self, modifier: Option<char>, conversion: char) -> Option<Value> {
+        let status = &self.status;
+
+        // The fields are of 64 bits or less, depending on the system
+        #[allow(clippy::useless_conversion)]
+        let value = match (modifier, conversion) {
+            (None, 'a') => Value::Unsigned(u64::from(status.f_bavail)),
+            (None, 'b') => Value::Unsigned(u64::from(status.f_blocks)),
+            (None, 'c') => Value::Unsigned(u64::from(status.f_files)),
+            (None, 'd') => Value::Unsigned(u64::from(status.f_ffree)),
+            (None, 'f') => Value::Unsigned(u64::from(status.f_bfree)),
+            (None, 'i') => Value::Hex(self.id),
+            (None, 'l') => Value::Unsigned(u64::from(status.f_namemax)),
+            (None, 'n') => Value::Str(self.name.to_string()),
+            (None, 's') => Value::Unsigned(u64::from(status.f_bsize)),
+            (None, 'S') => Value::Unsigned(u64::from(status.f_frsize)),
+            (None, 't') => Value::Hex(self.fs_type),
+            (None, 'T') => Value::Str(self.type_name.clone()),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+}
+
+/// The type number, type name and ID of the file system of `path`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn fs_type(path: &CString, _status: &libc::statvfs) -> io::Result<(u64, String, u64)> {
+    let mut status = MaybeUninit::uninit();
+    let result = unsafe { libc::statfs(path.as_ptr(), status.as_mut_ptr()) };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: Initialized by the successful statfs
+    let status = unsafe { status.assume_init() };
+
+    // The type numbers are of 32 bits, and the words of the ID are private
+    let fs_type = u64::from(status.f_type as u32);
+    let words: [u32; 2] = unsafe { std::mem::transmute(status.f_fsid) };
+    let id = u64::from(words[0]) << 32 | u64::from(words[1]);
+
+    Ok((fs_type, type_name(fs_type), id))
+}
+
+/// The type number, type name and ID of the file system of `path`.
+#[cfg(any(target_os = "macos", target_os = "freebsd"))]
+fn fs_type(path: &CString, status: &libc::statvfs) -> io::Result<(u64, String, u64)> {
+    use std::ffi::CStr;
+
+    let mut fs_status = MaybeUninit::uninit();
+    let result = unsafe { libc::statfs(path.as_ptr(), fs_status.as_mut_ptr()) };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: Initialized by the successful statfs
+    let fs_status = unsafe { fs_status.assume_init() };
+
+    // SAFETY: The name is a nul terminated string of the structure
+    let name = unsafe { CStr::from_ptr(fs_status.f_fstypename.as_ptr()) };
+
+    Ok((u64::from(fs_status.f_type), name.to_string_lossy().into_owned(), status.f_fsid as u64))
+}
+
+/// The type number, type name and ID of the file system of `path`.
+// The platforms without the type of the file systems
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "freebsd"
+)))]
+fn fs_type(_path: &CString, status: &libc::statvfs) -> io::Result<(u64, String, u64)> {
+    Ok((0, "?".to_string(), status.f_fsid as u64))
+}
+
+/// The name of the file system type of the magic number `fs_type`, like GNU.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn type_name(fs_type: u64) -> String {
+    let name = match fs_type {
+        0x5A3C_69F0 => "aafs",
+        0x6163_6673 => "acfs",
+        0xADF5 => "adfs",
+        0xADFF => "affs",
+        0x5346_414F => "afs",
+        0x0904_1934 => "anon-inode FS",
+        0x6175_6673 => "aufs",
+        0x0187 => "autofs",
+        0x1366_1366 => "balloon-kvm-fs",
+        0x4246_5331 => "befs",
+        0x6264_6576 => "bdevfs",
+        0x1BAD_FACE => "bfs",
+        0x6C6F_6F70 => "binderfs",
+        0xCAFE_4A11 => "bpf_fs",
+        0x4249_4E4D => "binfmt_misc",
+        0x9123_683E => "btrfs",
+        0x7372_7279 => "btrfs_test",
+        0x00C3_6400 => "ceph",
+        0x0027_E0EB => "cgroupfs",
+        0x6367_7270 => "cgroup2fs",
+        0xFF53_4D42 => "cifs",
+        0x7375_7245 => "coda",
+        0x012F_F7B7 => "coh",
+        0x6265_6570 => "configfs",
+        0x28CD_3D45 => "cramfs",
+        0x453D_CD28 => "cramfs-wend",
+        0x6464_6178 => "daxfs",
+        0x6462_6720 => "debugfs",
+        0x1373 => "devfs",
+        0x454D_444D => "devmem",
+        0x1CD1 => "devpts",
+        0x444D_4142 => "dma-buf-fs",
+        0xF15F => "ecryptfs",
+        0xDE5E_81E4 => "efivarfs",
+        0x0041_4A53 => "efs",
+        0xE0F5_E1E2 => "erofs",
+        0x2011_BAB0 => "exfat",
+        0x4558_4653 => "exfs",
+        0x5DF5 => "exofs",
+        0x137D => "ext",
+        0xEF53 => "ext2/ext3",
+        0xEF51 => "ext2",
+        0xF2F5_2010 => "f2fs",
+        0x4006 => "fat",
+        0x1983_0326 => "fhgfs",
+        0x6573_5546 => "fuseblk",
+        0x6573_5543 => "fusectl",
+        0x0BAD_1DEA => "futexfs",
+        0x0116_1970 => "gfs/gfs2",
+        0x4750_4653 => "gpfs",
+        0x4244 => "hfs",
+        0x482B => "hfs+",
+        0x4858 => "hfsx",
+        0x00C0_FFEE => "hostfs",
+        0xF995_E849 => "hpfs",
+        0x9584_58F6 => "hugetlbfs",
+        0x1130_7854 => "inodefs",
+        0x0131_11A8 => "ibrix",
+        0x2BAD_1DEA => "inotifyfs",
+        0x9660 | 0x4004 | 0x4000 => "isofs",
+        0x07C0 => "jffs",
+        0x72B6 => "jffs2",
+        0x3153_464A => "jfs",
+        0x6B41_4653 => "k-afs",
+        0xC97E_8168 => "logfs",
+        0x0BD0_0BD0 => "lustre",
+        0x5346_314D => "m1fs",
+        0x137F => "minix",
+        0x138F => "minix (30 char.)",
+        0x2468 => "minix v2",
+        0x2478 => "minix v2 (30 char.)",
+        0x4D5A => "minix3",
+        0x1980_0202 => "mqueue",
+        0x4D44 => "msdos",
+        0x564C => "novell",
+        0x6969 => "nfs",
+        0x6E66_7364 => "nfsd",
+        0x3434 => "nilfs",
+        0x6E73_6673 => "nsfs",
+        0x5346_544E => "ntfs",
+        0x9FA1 => "openprom",
+        0x7461_636F => "ocfs2",
+        0x794C_7630 => "overlayfs",
+        0xAAD7_AAEA => "panfs",
+        0x5049_5045 => "pipefs",
+        0xC757_1590 => "ppc-cmm-fs",
+        0x7C7C_6673 => "prl_fs",
+        0x9FA0 => "proc",
+        0x6165_676C => "pstorefs",
+        0x002F => "qnx4",
+        0x6819_1122 => "qnx6",
+        0x8584_58F6 => "ramfs",
+        0x0765_5821 => "rdt",
+        0x5265_4973 => "reiserfs",
+        0x7275 => "romfs",
+        0x6759_6969 => "rpc_pipefs",
+        0x5DCA_2DF5 => "sdcardfs",
+        0x5345_434D => "secretmem",
+        0x7363_6673 => "securityfs",
+        0xF97C_FF8C => "selinux",
+        0x4341_5D53 => "smackfs",
+        0x517B => "smb",
+        0xFE53_4D42 => "smb2",
+        0xBEEF_DEAD => "snfs",
+        0x534F_434B => "sockfs",
+        0x7371_7368 => "squashfs",
+        0x6265_6572 => "sysfs",
+        0x012F_F7B6 => "sysv2",
+        0x012F_F7B5 => "sysv4",
+        0x0102_1994 => "tmpfs",
+        0x7472_6163 => "tracefs",
+        0x2405_1905 => "ubifs",
+        0x1501_3346 => "udf",
+        0x0001_1954 | 0x5419_0100 => "ufs",
+        0x9FA2 => "usbdevfs",
+        0x0102_1997 => "v9fs",
+        0x786F_4256 => "vboxsf",
+        0xBACB_ACBC => "vmhgfs",
+        0xA501_FCF5 => "vxfs",
+        0x565A_4653 => "vzfs",
+        0x5346_4846 => "wslfs",
+        0xABBA_1974 => "xenfs",
+        0x012F_F7B4 => "xenix",
+        0x5846_5342 => "xfs",
+        0x012F_D16D => "xia",
+        0x0033 => "z3fold",
+        0x2FC1_2FC1 => "zfs",
+        0x5A4F_4653 => "zonefs",
+        0x5829_5829 => "zsmallocfs",
+        _ => return format!("UNKNOWN (0x{:x})", fs_type),
+    };
+
+    name.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_status() {
+        let status = FsStatus::new("/").unwrap();
+        assert_eq!(status.directive(None, 'n'), Some(Value::Str("/".to_string())));
+        assert!(matches!(status.directive(None, 'b'), Some(Value::Unsigned(_))));
+        assert_eq!(status.directive(None, 'Q'), None);
+        assert!(FsStatus::new("/nonexistent/file").is_err());
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn type_names() {
+        assert_eq!(type_name(0xEF53), "ext2/ext3");
+        assert_eq!(type_name(0x0102_1994), "tmpfs");
+        assert_eq!(type_name(0x1234), "UNKNOWN (0x1234)");
+    }
+}
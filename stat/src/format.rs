@@ -0,0 +1,335 @@
+//! The expansion of the `%` sequences of the formats, with the flags, width and precision
+//! of `printf(3)`, and of the backslash escapes of `--printf`.
+use std::{iter::Peekable, str::Chars};
+
+/// A value of a sequence, that decides how the flags are applied to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Value {
+    Unsigned(u64),
+    Octal(u64),
+    Hex(u64),
+    Str(String),
+    /// Seconds since the Epoch, where the precision is the digits of the nanoseconds.
+    Epoch {
+        seconds: i64,
+        nanos: u32,
+    },
+}
+
+/// The flags, width and precision of a sequence, like `-10` of `%-10s`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Spec {
+    left: bool,
+    zero: bool,
+    plus: bool,
+    space: bool,
+    alternate: bool,
+    width: usize,
+    precision: Option<usize>,
+    /// The precision was only a `.`, without digits.
+    bare_precision: bool,
+}
+
+/// Expand the `format` with the values given by `directive` for each sequence, that
+/// gets the `H` or `L` modifier and the conversion character, into the bytes to output.
+///
+/// The conversions without a value are replaced by `?`, and if `escapes`, the backslash
+/// escapes are interpreted outside of the sequences.
+///
+/// # Errors
+/// A sequence left without its conversion at the end of the `format`.
+pub(crate) fn expand(
+    format: &str, escapes: bool, mut directive: impl FnMut(Option<char>, char) -> Option<Value>,
+) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(format.len());
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if escapes => escape(&mut chars, &mut out),
+            '%' => {
+                let rest = chars.clone().collect::<String>();
+                let spec = parse_spec(&mut chars);
+                let modifier = match chars.peek() {
+                    Some(&m) if m == 'H' || m == 'L' => {
+                        chars.next();
+                        Some(m)
+                    },
+                    _ => None,
+                };
+
+                match (chars.next(), modifier) {
+                    (None, None) if rest.is_empty() => out.push(b'%'),
+                    (None, None) => return Err(format!("'%{}': invalid directive", rest)),
+                    (None, Some(_)) => out.push(b'?'),
+                    (Some('%'), None) if rest.starts_with('%') => out.push(b'%'),
+                    // Only `d` and `r` take the modifiers
+                    (Some(conv), Some(_)) if conv != 'd' && conv != 'r' => {
+                        out.push(b'?');
+                        push_char(&mut out, conv);
+                    },
+                    (Some(conv), _) => match directive(modifier, conv) {
+                        Some(value) => out.extend_from_slice(apply(&spec, &value).as_bytes()),
+                        None => out.push(b'?'),
+                    },
+                }
+            },
+            _ => push_char(&mut out, c),
+        }
+    }
+
+    Ok(out)
+}
+
+fn parse_spec(chars: &mut Peekable<Chars>) -> Spec {
+    let mut spec = Spec::default();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            '-' => spec.left = true,
+            '0' => spec.zero = true,
+            '+' => spec.plus = true,
+            ' ' => spec.space = true,
+            '#' => spec.alternate = true,
+            // The thousands grouping, that has no separator in the C locale
+            '\'' => {},
+            _ => break,
+        }
+        chars.next();
+    }
+
+    spec.width = digits(chars).unwrap_or(0);
+    if chars.peek() == Some(&'.') {
+        chars.next();
+        let precision = digits(chars);
+        spec.bare_precision = precision.is_none();
+        spec.precision = Some(precision.unwrap_or(0));
+    }
+
+    spec
+}
+
+fn digits(chars: &mut Peekable<Chars>) -> Option<usize> {
+    let mut number = None;
+    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+        number = Some(number.unwrap_or(0usize).saturating_mul(10).saturating_add(digit as usize));
+        chars.next();
+    }
+    number
+}
+
+/// Format `value` with the flags, width and precision of `spec`.
+fn apply(spec: &Spec, value: &Value) -> String {
+    match value {
+        Value::Unsigned(n) => integer(spec, "", &n.to_string(), ""),
+        Value::Octal(n) => {
+            let digits = format!("{:o}", n);
+            let prefix = if spec.alternate && *n != 0 { "0" } else { "" };
+            integer(spec, "", &digits, prefix)
+        },
+        Value::Hex(n) => {
+            let prefix = if spec.alternate && *n != 0 { "0x" } else { "" };
+            integer(spec, "", &format!("{:x}", n), prefix)
+        },
+        Value::Str(s) => {
+            let s: String = match spec.precision {
+                Some(precision) => s.chars().take(precision).collect(),
+                None => s.clone(),
+            };
+            pad(spec, "", &s, false)
+        },
+        Value::Epoch { seconds, nanos } => epoch(spec, *seconds, *nanos),
+    }
+}
+
+/// Format the digits of a integer, where the precision is the minimum number of digits.
+fn integer(spec: &Spec, sign: &str, digits: &str, prefix: &str) -> String {
+    let digits = match spec.precision {
+        Some(0) if digits == "0" => String::new(),
+        Some(precision) => {
+            format!("{:0>width$}", digits, width = precision.saturating_sub(prefix.len()))
+        },
+        None => digits.to_string(),
+    };
+
+    pad(spec, sign, &format!("{}{}", prefix, digits), spec.precision.is_none())
+}
+
+/// Pad `text` after the `sign` up to the width of `spec`, with zeros if `zeros` and the
+/// `0` flag.
+fn pad(spec: &Spec, sign: &str, text: &str, zeros: bool) -> String {
+    let len = sign.chars().count() + text.chars().count();
+    let fill = spec.width.saturating_sub(len);
+
+    if spec.left {
+        format!("{}{}{}", sign, text, " ".repeat(fill))
+    } else if spec.zero && zeros {
+        format!("{}{}{}", sign, "0".repeat(fill), text)
+    } else {
+        format!("{}{}{}", " ".repeat(fill), sign, text)
+    }
+}
+
+/// Format seconds since the Epoch with the nanoseconds of the precision, like GNU.
+///
+/// The width counts the fraction, that is not padded with zeros, so the integer part is
+/// padded to the width without it. If it doesn't fit, the spaces are after the fraction.
+fn epoch(spec: &Spec, seconds: i64, nanos: u32) -> String {
+    let precision = match spec.precision {
+        Some(_) if spec.bare_precision => 9,
+        Some(precision) if precision > 0 => precision,
+        _ => {
+            let spec = Spec { precision: None, ..*spec };
+            return signed(&spec, seconds < 0, seconds.to_string().trim_start_matches('-'));
+        },
+    };
+    let digits = precision.min(9);
+    let divisor = 10u32.pow(9 - digits as u32);
+
+    // The fraction of the negative times is counted towards the Epoch
+    let (whole, fraction) = if seconds < 0 && nanos > 0 {
+        let fraction = (1_000_000_000 - nanos) / divisor;
+        (seconds + (fraction != 0) as i64, fraction)
+    } else {
+        (seconds, nanos / divisor)
+    };
+
+    let width = match spec.width.checked_sub(precision + 1) {
+        Some(width) if !spec.left && width > 1 => width,
+        _ => 0,
+    };
+    let whole_spec = Spec { left: false, width, precision: None, ..*spec };
+    let whole = signed(&whole_spec, seconds < 0, whole.to_string().trim_start_matches('-'));
+
+    // The spaces of the width left, that are also the ones the fraction goes over it
+    let len = whole.chars().count();
+    let trailing = match spec.width.checked_sub(len + 1) {
+        Some(room) if len < spec.width && room > 0 => room.max(digits) - room.min(digits),
+        _ => 0,
+    };
+    let zeros = "0".repeat(precision - digits);
+
+    format!(
+        "{}.{:0digits$}{:<trailing$}",
+        whole,
+        fraction,
+        zeros,
+        digits = digits,
+        trailing = trailing
+    )
+}
+
+/// Format the `digits` of a signed integer.
+fn signed(spec: &Spec, negative: bool, digits: &str) -> String {
+    let sign = if negative {
+        "-"
+    } else if spec.plus {
+        "+"
+    } else if spec.space {
+        " "
+    } else {
+        ""
+    };
+
+    integer(spec, sign, digits, "")
+}
+
+/// Interpret the escape after a backslash of `chars` into `out`.
+fn escape(chars: &mut Peekable<Chars>, out: &mut Vec<u8>) {
+    let c = match chars.next() {
+        Some(c) => c,
+        None => {
+            eprintln!("stat: warning: backslash at end of format");
+            out.push(b'\\');
+            return;
+        },
+    };
+
+    match c {
+        '\\' => out.push(b'\\'),
+        '"' => out.push(b'"'),
+        'a' => out.push(0x07),
+        'b' => out.push(0x08),
+        'e' => out.push(0x1b),
+        'f' => out.push(0x0c),
+        'n' => out.push(b'\n'),
+        'r' => out.push(b'\r'),
+        't' => out.push(b'\t'),
+        'v' => out.push(0x0b),
+        '0'..='7' => out.push(escape_code(chars, 8, c.to_digit(8).unwrap_or(0))),
+        'x' if matches!(chars.peek(), Some(c) if c.is_ascii_hexdigit()) => {
+            out.push(escape_code(chars, 16, 0))
+        },
+        _ => {
+            eprintln!("stat: warning: unrecognized escape '\\{}'", c);
+            push_char(out, c);
+        },
+    }
+}
+
+/// The byte of a escape, from the `code` of the digits already read and up to 2 more
+/// digits of `radix` in `chars`.
+fn escape_code(chars: &mut Peekable<Chars>, radix: u32, mut code: u32) -> u8 {
+    for _ in 0..2 {
+        match chars.peek().and_then(|c| c.to_digit(radix)) {
+            Some(digit) => {
+                code = code * radix + digit;
+                chars.next();
+            },
+            None => break,
+        }
+    }
+    (code & 0xff) as u8
+}
+
+fn push_char(out: &mut Vec<u8>, c: char) {
+    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_with(format: &str, escapes: bool) -> Result<String, String> {
+        let out = expand(format, escapes, |modifier, conversion| match (modifier, conversion) {
+            (None, 's') => Some(Value::Unsigned(42)),
+            (None, 'a') => Some(Value::Octal(0o644)),
+            (None, 'f') => Some(Value::Hex(0x81a4)),
+            (None, 'n') => Some(Value::Str("file".to_string())),
+            (Some('H'), 'd') => Some(Value::Unsigned(254)),
+            (None, 'Y') => Some(Value::Epoch { seconds: 1_600_000_000, nanos: 123_456_789 }),
+            (None, 'Z') => Some(Value::Epoch { seconds: -2, nanos: 500_000_000 }),
+            _ => None,
+        })?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn flags() {
+        assert_eq!(expand_with("%5s|%-5s|%05s|%.3s", false).unwrap(), "   42|42   |00042|042");
+        assert_eq!(expand_with("%a|%#a|%04a|%f|%#f", false).unwrap(), "644|0644|0644|81a4|0x81a4");
+        assert_eq!(expand_with("%6n|%-6n|%.2n", false).unwrap(), "  file|file  |fi");
+        assert_eq!(expand_with("%Hd|%Hs|%Q|%%|%", false).unwrap(), "254|?s|?|%|%");
+        assert_eq!(expand_with("%5", false), Err("'%5': invalid directive".to_string()));
+    }
+
+    #[test]
+    fn epoch_precision() {
+        assert_eq!(
+            expand_with("%Y|%.Y|%.3Y|%.12Y", false).unwrap(),
+            "1600000000|1600000000.123456789|1600000000.123|1600000000.123456789000"
+        );
+        assert_eq!(
+            expand_with("%16.3Y|%-16.3Y|%016.3Y", false).unwrap(),
+            "  1600000000.123|1600000000.123  |001600000000.123"
+        );
+        assert_eq!(expand_with("%-12.9Y|", false).unwrap(), "1600000000.123456789        |");
+        assert_eq!(expand_with("%Z|%.1Z|%+Y", false).unwrap(), "-2|-1.5|+1600000000");
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(expand_with("%n\\t\\x41\\101\\\\\\n", true).unwrap(), "file\tAA\\\n");
+        assert_eq!(expand_with("%n\\t", false).unwrap(), "file\\t");
+    }
+}
@@ -0,0 +1,113 @@
+use std::{
+    io::{self, Write},
+    process,
+};
+
+use coreutils_core::error::strerror;
+
+mod cli;
+mod file;
+mod filesystem;
+mod format;
+
+use file::FileStatus;
+use filesystem::FsStatus;
+
+const DEFAULT_FMT: &str = "  File: %N\n  Size: %-10s\tBlocks: %-10b IO Block: %-6o %F\nDevice: \
+                           %Hd,%Ld\tInode: %-10i  Links: %h\nAccess: (%04a/%10.10A)  Uid: \
+                           (%5u/%8U)   Gid: (%5g/%8G)\nAccess: %x\nModify: %y\nChange: %z\n \
+                           Birth: %w\n";
+const DEFAULT_DEVICE_FMT: &str = "  File: %N\n  Size: %-10s\tBlocks: %-10b IO Block: %-6o \
+                                  %F\nDevice: %Hd,%Ld\tInode: %-10i  Links: %-5h Device type: \
+                                  %Hr,%Lr\nAccess: (%04a/%10.10A)  Uid: (%5u/%8U)   Gid: \
+                                  (%5g/%8G)\nAccess: %x\nModify: %y\nChange: %z\n Birth: %w\n";
+const TERSE_FMT: &str = "%n %s %b %f %u %g %D %i %h %t %T %X %Y %Z %W %o\n";
+const FS_DEFAULT_FMT: &str = "  File: \"%n\"\n    ID: %-8i Namelen: %-7l Type: %T\nBlock size: \
+                              %-10s Fundamental block size: %S\nBlocks: Total: %-10b Free: %-10f \
+                              Available: %a\nInodes: Total: %-10c Free: %d\n";
+const FS_TERSE_FMT: &str = "%n %i %l %t %s %S %b %f %a %c %d\n";
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let dereference = matches.is_present("dereference");
+    let file_system = matches.is_present("file_system");
+    let terse = matches.is_present("terse");
+    // The `--format` has a newline after it, and `--printf` has the escapes
+    let custom = match (matches.value_of("format"), matches.value_of("printf")) {
+        (Some(format), _) => Some((format, false)),
+        (None, Some(format)) => Some((format, true)),
+        (None, None) => None,
+    };
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut exit_code = 0;
+
+    // Required argument, ok to unwrap and not check if is supplied.
+    for name in matches.values_of("FILE").unwrap() {
+        let output = if file_system {
+            FsStatus::new(name)
+                .map(|status| {
+                    let (format, escapes) = match custom {
+                        Some(custom) => custom,
+                        None if terse => (FS_TERSE_FMT, false),
+                        None => (FS_DEFAULT_FMT, false),
+                    };
+                    format::expand(format, escapes, |modifier, conversion| {
+                        status.directive(modifier, conversion)
+                    })
+                })
+                .map_err(|err| {
+                    format!(
+                        "cannot read file system information for '{}': {}",
+                        name,
+                        strerror(&err)
+                    )
+                })
+        } else {
+            FileStatus::new(name, dereference, custom.is_some())
+                .map(|status| {
+                    let (format, escapes) = match custom {
+                        Some(custom) => custom,
+                        None if terse => (TERSE_FMT, false),
+                        None if status.is_device() => (DEFAULT_DEVICE_FMT, false),
+                        None => (DEFAULT_FMT, false),
+                    };
+                    format::expand(format, escapes, |modifier, conversion| {
+                        status.directive(modifier, conversion)
+                    })
+                })
+                .map_err(|err| format!("cannot stat '{}': {}", name, strerror(&err)))
+        };
+
+        let mut output = match output {
+            Ok(Ok(output)) => output,
+            // The format is the same for every file
+            Ok(Err(err)) => {
+                eprintln!("stat: {}", err);
+                process::exit(1);
+            },
+            Err(err) => {
+                eprintln!("stat: {}", err);
+                exit_code = 1;
+                continue;
+            },
+        };
+
+        if matches.is_present("format") {
+            output.push(b'\n');
+        }
+
+        if let Err(err) = stdout.write_all(&output) {
+            eprintln!("stat: {}", strerror(&err));
+            process::exit(1);
+        }
+    }
+
+    if let Err(err) = stdout.flush() {
+        eprintln!("stat: {}", strerror(&err));
+        exit_code = 1;
+    }
+    process::exit(exit_code);
+}
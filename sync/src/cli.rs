@@ -0,0 +1,35 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("Files to synchronize.")
+                .long_help(
+                    "Files to synchronize.\n\nWithout files, the cached writes of all the file \
+                     systems are synchronized.",
+                )
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("data")
+                .help("Synchronize only the data of the files, and the metadata needed to read it.")
+                .long("data")
+                .short("d"),
+        )
+        .arg(
+            Arg::with_name("fileSystem")
+                .help("Synchronize the file systems where the files are.")
+                .long("file-system")
+                .short("f"),
+        )
+}
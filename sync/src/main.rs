@@ -0,0 +1,135 @@
+use std::{
+    fs::{File, OpenOptions},
+    io,
+    os::unix::{fs::OpenOptionsExt, io::AsRawFd},
+    path::Path,
+    process,
+};
+
+use coreutils_core::libc;
+
+mod cli;
+
+/// What is synchronized of the files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// The data and metadata of the files.
+    File,
+    /// The data of the files, and the metadata needed to read it.
+    Data,
+    /// The file systems where the files are.
+    FileSystem,
+}
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let mode = match (matches.is_present("data"), matches.is_present("fileSystem")) {
+        (true, true) => {
+            eprintln!("sync: cannot specify both --data and --file-system");
+            process::exit(1);
+        },
+        (true, false) => Mode::Data,
+        (false, true) => Mode::FileSystem,
+        (false, false) => Mode::File,
+    };
+
+    let files = match matches.values_of_os("FILE") {
+        Some(files) => files,
+        None if mode == Mode::Data => {
+            eprintln!("sync: --data needs at least one argument");
+            process::exit(1);
+        },
+        None => {
+            unsafe { libc::sync() };
+            return;
+        },
+    };
+
+    let mut success = true;
+
+    for file in files {
+        if let Err(err) = sync(Path::new(file), mode) {
+            eprintln!("sync: {}", err);
+            success = false;
+        }
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// Synchronize the file at `path` as the `mode` asks.
+///
+/// # Errors
+/// If the file can't be opened or synchronized, an error message will be returned.
+fn sync(path: &Path, mode: Mode) -> Result<(), String> {
+    let file = open(path).map_err(|err| format!("error opening '{}': {}", path.display(), err))?;
+
+    // The FIFOs are opened without blocking, but are synchronized blocking
+    reset_nonblocking(&file)
+        .map_err(|err| format!("couldn't reset non-blocking mode '{}': {}", path.display(), err))?;
+
+    let synced = match mode {
+        Mode::File => file.sync_all(),
+        Mode::Data => file.sync_data(),
+        Mode::FileSystem => sync_file_system(&file),
+    };
+
+    synced.map_err(|err| format!("error syncing '{}': {}", path.display(), err))
+}
+
+/// Open the file at `path` without blocking, to read or else to write it.
+///
+/// # Errors
+/// If it can't be opened either way, the error of opening it to read is returned, since
+/// it is the one that matters for the directories.
+fn open(path: &Path) -> io::Result<File> {
+    OpenOptions::new().read(true).custom_flags(libc::O_NONBLOCK).open(path).or_else(|err| {
+        OpenOptions::new().write(true).custom_flags(libc::O_NONBLOCK).open(path).map_err(|_| err)
+    })
+}
+
+fn reset_nonblocking(file: &File) -> io::Result<()> {
+    let fd = file.as_raw_fd();
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+
+    if flags == -1 || unsafe { libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_NONBLOCK) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn sync_file_system(file: &File) -> io::Result<()> {
+    match unsafe { libc::syncfs(file.as_raw_fd()) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Without a call to synchronize a single file system, all of them are synchronized.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn sync_file_system(_file: &File) -> io::Result<()> {
+    unsafe { libc::sync() };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        for &mode in &[Mode::File, Mode::Data, Mode::FileSystem] {
+            assert_eq!(sync(&dir.join("Cargo.toml"), mode), Ok(()));
+            assert_eq!(sync(dir, mode), Ok(()));
+        }
+
+        assert!(sync(&dir.join("nowhere"), Mode::File).unwrap_err().starts_with("error opening"));
+    }
+}
@@ -0,0 +1,39 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("File(s) to use, or '-' to use from standard input.")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("before")
+                .help("Attach the separator before each line, instead of after it.")
+                .long("before")
+                .short("b"),
+        )
+        .arg(
+            Arg::with_name("regex")
+                .help("Interpret the separator as a regular expression, in the GNU syntax.")
+                .long("regex")
+                .short("r"),
+        )
+        .arg(
+            Arg::with_name("separator")
+                .help("Use STRING as the separator of the lines, instead of a newline.")
+                .long("separator")
+                .short("s")
+                .value_name("STRING")
+                .allow_hyphen_values(true),
+        )
+}
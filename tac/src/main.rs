@@ -0,0 +1,331 @@
+use std::{
+    ffi::OsStr,
+    fmt,
+    fs::File,
+    io::{self, stdin, stdout, BufWriter, Read, Seek, SeekFrom, Write},
+    process,
+};
+
+use clap::ArgMatches;
+use coreutils_core::{bre::BreError, emacs};
+use regex_automata::{
+    meta::{self, Regex},
+    util::syntax,
+    Anchored,
+};
+
+mod cli;
+
+/// The number of bytes read at once from the end of the files.
+const BLOCK_SIZE: usize = 64 * 1024;
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let tac = Tac::from_matches(&matches).unwrap_or_else(|err| {
+        eprintln!("tac: {}", err);
+        process::exit(1);
+    });
+
+    let files: Vec<&OsStr> = match matches.values_of_os("FILE") {
+        Some(files) => files.collect(),
+        None => vec![OsStr::new("-")],
+    };
+
+    let stdout = stdout();
+    let mut output = BufWriter::new(stdout.lock());
+    let mut success = true;
+
+    for file in files {
+        let result = Input::open(file).and_then(|mut input| tac.reverse(&mut input, &mut output));
+
+        match result {
+            Ok(()) => {},
+            Err(Error::Write(err)) => {
+                eprintln!("tac: write error: {}", err);
+                process::exit(1);
+            },
+            Err(err) => {
+                let name =
+                    if file == "-" { "'standard input'".into() } else { file.to_string_lossy() };
+                eprintln!("tac: {}", err.with_name(&name));
+                success = false;
+            },
+        }
+    }
+
+    if let Err(err) = output.flush() {
+        eprintln!("tac: write error: {}", err);
+        process::exit(1);
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// The separator of the lines.
+#[derive(Debug)]
+enum Separator {
+    /// A string, that is a newline by default.
+    String(Vec<u8>),
+    Regex(Regex),
+}
+
+impl Separator {
+    /// Compile the regular expression `pattern` of the separator.
+    ///
+    /// Like GNU, the syntax is the one of Emacs, and the anchors also match at the
+    /// newlines.
+    fn regex(pattern: &str) -> Result<Self, BreError> {
+        let translated = emacs::translate(pattern.as_bytes())?;
+
+        Regex::builder()
+            .configure(meta::Config::new().utf8_empty(false))
+            .syntax(syntax::Config::new().unicode(false).utf8(false).multi_line(true))
+            .build(&translated)
+            .map(Separator::Regex)
+            .map_err(|_| BreError::Invalid)
+    }
+
+    /// The start and the end of the match of the separator that starts the last in
+    /// `text`, ending at most at its end, if there is one.
+    ///
+    /// Like GNU, the regular expressions are tried at each start from the end of the
+    /// `text`, and the first one that matches is the last match, that doesn't need to
+    /// find all the matches that overlap it.
+    fn find_last(&self, text: &[u8]) -> Option<(usize, usize)> {
+        match self {
+            Separator::String(string) => text
+                .windows(string.len())
+                .rposition(|window| window == &string[..])
+                .map(|start| (start, start + string.len())),
+            Separator::Regex(regex) => (0..text.len()).rev().find_map(|start| {
+                let input = regex_automata::Input::new(text).range(start..).anchored(Anchored::Yes);
+                regex.search(&input).map(|found| (found.start(), found.end()))
+            }),
+        }
+    }
+}
+
+/// The writer of the lines of the files in reverse order.
+#[derive(Debug)]
+struct Tac {
+    separator: Separator,
+    /// Whether the separator is at the start of the line that follows it, instead of at
+    /// the end of the one before it.
+    before: bool,
+}
+
+impl Tac {
+    fn from_matches(matches: &ArgMatches) -> Result<Self, String> {
+        let separator = matches.value_of("separator").unwrap_or("\n");
+
+        let separator = if matches.is_present("regex") {
+            if separator.is_empty() {
+                return Err("separator cannot be empty".to_string());
+            }
+
+            Separator::regex(separator).map_err(|err| err.to_string())?
+        } else if separator.is_empty() {
+            // Like GNU, a empty string is the end of a C string
+            Separator::String(vec![b'\0'])
+        } else {
+            Separator::String(separator.as_bytes().to_vec())
+        };
+
+        Ok(Tac { separator, before: matches.is_present("before") })
+    }
+
+    /// Write the lines of the `input` to the `output`, with the last one first.
+    ///
+    /// The input is read backwards from its end, keeping only the line that is not
+    /// complete yet.
+    fn reverse<W: Write>(&self, input: &mut Input, output: &mut W) -> Result<(), Error> {
+        // The bytes that are not written yet, from the position of the input
+        let mut data = Vec::new();
+        // The end of the separators that can still be matched in the data, that is the
+        // start of the last one matched
+        let mut limit = 0;
+
+        loop {
+            if input.position() > 0 {
+                // Doubling the size when a line is longer than a block keeps the search
+                // for its separator linear
+                let size = BLOCK_SIZE.max(data.len()) as u64;
+                let size = size.min(input.position()) as usize;
+
+                let mut block = input.read_before(size).map_err(Error::Read)?;
+                block.extend_from_slice(&data);
+                data = block;
+                limit += size;
+            }
+
+            let mut end = data.len();
+
+            // Like GNU, the separators end before the start of the last one
+            while let Some((start, separator_end)) = self.separator.find_last(&data[..limit]) {
+                // The context of a regular expression at the start of the data, like a
+                // `^`, is only known with the bytes before it
+                if start == 0 && input.position() > 0 {
+                    break;
+                }
+
+                let line_start = if self.before { start } else { separator_end };
+                output.write_all(&data[line_start..end]).map_err(Error::Write)?;
+
+                end = line_start;
+                limit = start;
+            }
+
+            data.truncate(end);
+
+            if input.position() == 0 {
+                return output.write_all(&data).map_err(Error::Write);
+            }
+        }
+    }
+}
+
+/// A file to read backwards.
+#[derive(Debug)]
+enum Input {
+    /// A regular file, with the position of the bytes read.
+    File(File, u64),
+    /// The bytes of the other files, that can't be read backwards, without the ones read.
+    Memory(Vec<u8>),
+}
+
+impl Input {
+    /// Open the `file`, or standard input for `-`.
+    fn open(file: &OsStr) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+
+        if file == "-" {
+            stdin().lock().read_to_end(&mut bytes).map_err(Error::Read)?;
+            return Ok(Input::Memory(bytes));
+        }
+
+        let mut opened = File::open(file).map_err(Error::Open)?;
+
+        if opened.metadata().map(|metadata| metadata.is_file()).unwrap_or(false) {
+            let len = opened.seek(SeekFrom::End(0)).map_err(Error::Read)?;
+            Ok(Input::File(opened, len))
+        } else {
+            opened.read_to_end(&mut bytes).map_err(Error::Read)?;
+            Ok(Input::Memory(bytes))
+        }
+    }
+
+    /// The number of bytes before the ones read.
+    fn position(&self) -> u64 {
+        match self {
+            Input::File(_, position) => *position,
+            Input::Memory(bytes) => bytes.len() as u64,
+        }
+    }
+
+    /// Read the `size` bytes before the ones read.
+    fn read_before(&mut self, size: usize) -> io::Result<Vec<u8>> {
+        match self {
+            Input::File(file, position) => {
+                *position -= size as u64;
+
+                let mut block = vec![0; size];
+                file.seek(SeekFrom::Start(*position))?;
+                file.read_exact(&mut block)?;
+
+                Ok(block)
+            },
+            Input::Memory(bytes) => Ok(bytes.split_off(bytes.len() - size)),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Error {
+    Open(io::Error),
+    Read(io::Error),
+    Write(io::Error),
+}
+
+impl Error {
+    /// The error with the `name` of its file.
+    fn with_name<'a>(&'a self, name: &'a str) -> NamedError<'a> {
+        NamedError { error: self, name }
+    }
+}
+
+/// A error with the name of its file.
+struct NamedError<'a> {
+    error: &'a Error,
+    name: &'a str,
+}
+
+impl fmt::Display for NamedError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.error {
+            Error::Open(err) => write!(f, "failed to open '{}' for reading: {}", self.name, err),
+            Error::Read(err) => write!(f, "{}: read error: {}", self.name, err),
+            Error::Write(err) => write!(f, "write error: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reverse(tac: &Tac, text: &str) -> String {
+        let mut output = Vec::new();
+        tac.reverse(&mut Input::Memory(text.as_bytes().to_vec()), &mut output).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    fn string(separator: &str, before: bool) -> Tac {
+        Tac { separator: Separator::String(separator.as_bytes().to_vec()), before }
+    }
+
+    fn regex(separator: &str, before: bool) -> Tac {
+        Tac { separator: Separator::regex(separator).unwrap(), before }
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(reverse(&string("\n", false), "a\nb\nc\n"), "c\nb\na\n");
+        assert_eq!(reverse(&string("\n", false), "a\nb\nc"), "cb\na\n");
+        assert_eq!(reverse(&string("\n", false), "\n\na"), "a\n\n");
+        assert_eq!(reverse(&string("\n", false), ""), "");
+        assert_eq!(reverse(&string("\n", true), "a\nb\n"), "\n\nba");
+    }
+
+    #[test]
+    fn separators() {
+        assert_eq!(reverse(&string(", ", false), "a, b, c"), "cb, a, ");
+        assert_eq!(reverse(&string("aa", false), "aaab"), "baaa");
+        assert_eq!(reverse(&string("aa", true), "aaab"), "aaba");
+        assert_eq!(reverse(&string("bc", true), "abcabc"), "bcbcaa");
+    }
+
+    #[test]
+    fn regexes() {
+        assert_eq!(reverse(&regex("[0-9][0-9]*", false), "a1b22c"), "c2b2a1");
+        assert_eq!(reverse(&regex("\\(1\\|2\\)", true), "a1b22c"), "2c21ba");
+        assert_eq!(reverse(&regex("^x", false), "xaxbx"), "axbxx");
+        assert_eq!(reverse(&regex("x*", false), "abc"), "cba");
+        assert_eq!(reverse(&regex("^x", false), "xa\nxb\n"), "b\na\nxx");
+        assert_eq!(reverse(&regex("x$", false), "ax\nbx\n"), "\n\nbxax");
+    }
+
+    #[test]
+    fn blocks() {
+        let text: String = (0..50_000).map(|i| format!("{}\n", i)).collect();
+        let expected: String = (0..50_000).rev().map(|i| format!("{}\n", i)).collect();
+        let long = "x".repeat(3 * BLOCK_SIZE);
+
+        assert_eq!(reverse(&string("\n", false), &text), expected);
+        assert_eq!(reverse(&regex("^[0-9]", true), &text), expected);
+        assert_eq!(reverse(&string("--", false), &format!("{}--a", long)), format!("a{}--", long));
+    }
+}
@@ -14,18 +14,79 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
         .arg(Arg::with_name("FILE").help("File(s) to use.").multiple(true))
         .arg(
             Arg::with_name("bytes")
-                .help("The total number of bytes to display from the end of the file.")
+                .help(
+                    "The total number of bytes to display from the end of the file, or with a \
+                     leading '+', the byte to start the output with.",
+                )
+                .long_help(
+                    "The total number of bytes to display from the end of the file, or with a \
+                     leading '+', the byte to start the output with.\n\nN may have a multiplier \
+                     suffix: b 512, kB 1000, K 1024, MB 1000*1000, M 1024*1024, and so on for G, \
+                     T, P, E. Binary prefixes can be used too: KiB=K, MiB=M, and so on.",
+                )
                 .long("bytes")
                 .short("c")
                 .value_name("N")
+                .allow_hyphen_values(true)
                 .conflicts_with("lines"),
         )
         .arg(
             Arg::with_name("lines")
-                .help("The total number of lines to display from the end of the file.")
+                .help(
+                    "The total number of lines to display from the end of the file, or with a \
+                     leading '+', the line to start the output with.",
+                )
+                .long_help(
+                    "The total number of lines to display from the end of the file, or with a \
+                     leading '+', the line to start the output with.\n\nN may have the same \
+                     multiplier suffixes as --bytes.",
+                )
                 .long("lines")
                 .short("n")
                 .value_name("N")
+                .allow_hyphen_values(true)
                 .default_value("10"),
         )
+        .arg(
+            Arg::with_name("follow")
+                .help("Output appended data as the file grows.")
+                .long_help(
+                    "Output appended data as the file grows.\n\nHOW may be 'name' or 'descriptor' \
+                     (the default). With 'descriptor' the open file is followed even if it's \
+                     renamed or removed. With 'name' the file name is followed, so the file is \
+                     opened again when it's replaced, like on log rotation.",
+                )
+                .long("follow")
+                .short("f")
+                .value_name("HOW")
+                .takes_value(true)
+                .min_values(0)
+                .max_values(1)
+                .require_equals(true)
+                .possible_values(&["name", "descriptor"]),
+        )
+        .arg(Arg::with_name("follow_name_retry").help("Same as --follow=name --retry.").short("F"))
+        .arg(
+            Arg::with_name("retry")
+                .help("Keep trying to open a file if it is inaccessible.")
+                .long("retry"),
+        )
+        .arg(
+            Arg::with_name("sleep_interval")
+                .help("With -f, check the file for changes at most every N (default 1.0) seconds.")
+                .long_help(
+                    "With -f, check the file for changes at most every N (default 1.0) \
+                     seconds.\n\nWhere the system can notify file changes, the change is output \
+                     as soon as it happens, otherwise the file is polled every N seconds.",
+                )
+                .long("sleep-interval")
+                .short("s")
+                .value_name("N"),
+        )
+        .arg(
+            Arg::with_name("pid")
+                .help("With -f, terminate after the process with ID PID dies.")
+                .long("pid")
+                .value_name("PID"),
+        )
 }
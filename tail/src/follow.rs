@@ -0,0 +1,512 @@
+//! Follow mode: output the data appended to the files as they grow.
+//!
+//! The files are watched with `inotify(7)` on Linux and `kqueue(2)` on the BSDs and
+//! macOS. If the watch can't be set up, the files are polled every sleep interval.
+//!
+//! When following until a process exits, the process is checked every sleep interval, on
+//! Linux a `pidfd` is used instead to know it as soon as it happens.
+use std::{
+    fs::{self, File, Metadata},
+    io::{self, Read, Seek, SeekFrom, Write},
+    time::Duration,
+};
+
+use coreutils_core::error::strerror;
+#[cfg(unix)]
+pub use coreutils_core::os::Pid;
+
+/// Process ID Type.
+#[cfg(not(unix))]
+pub type Pid = u32;
+
+/// How the files are followed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FollowMode {
+    /// Keep reading the open file, even if it's renamed or removed.
+    Descriptor,
+    /// Follow the file name, reopening it when other file takes its name, like on log
+    /// rotation.
+    Name,
+}
+
+/// Device and inode number of a file.
+type FileId = (u64, u64);
+
+#[cfg(unix)]
+fn file_id(metadata: &Metadata) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &Metadata) -> Option<FileId> {
+    None
+}
+
+/// A file being followed.
+#[derive(Debug)]
+pub struct FollowedFile {
+    /// The name used on the headers and diagnostics.
+    name: String,
+    /// The open file, `None` while the file can't be opened.
+    file: Option<File>,
+    /// The identity of the open file, so it's known when the name refers to other file.
+    id: Option<FileId>,
+    /// How much of the file was already written.
+    pos: u64,
+    /// The file became inaccessible and is no longer followed.
+    gone: bool,
+}
+
+impl FollowedFile {
+    /// Creates a new [`FollowedFile`], where `pos` bytes of `file` were already written.
+    pub fn new(name: String, file: File, pos: u64) -> Self {
+        let id = file.metadata().ok().as_ref().and_then(file_id);
+        FollowedFile { name, file: Some(file), id, pos, gone: false }
+    }
+
+    /// Creates a new [`FollowedFile`] for a file that could not be opened yet.
+    pub fn missing(name: String) -> Self {
+        FollowedFile { name, file: None, id: None, pos: 0, gone: false }
+    }
+
+    /// Write the data appended to the file since the last call, returning if any data was
+    /// written. If the file got truncated, it's read again from the start.
+    fn write_appended<W: Write>(&mut self, writer: &mut W, header: bool) -> io::Result<bool> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => return Ok(false),
+        };
+
+        let len = file.metadata()?.len();
+
+        if len < self.pos {
+            eprintln!("tail: {}: file truncated", self.name);
+            self.pos = file.seek(SeekFrom::Start(0))?;
+        }
+
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer)?;
+
+        if buffer.is_empty() {
+            return Ok(false);
+        }
+
+        if header {
+            writeln!(writer, "\n==> {} <==", self.name)?;
+        }
+        writer.write_all(&buffer)?;
+        self.pos += buffer.len() as u64;
+
+        Ok(true)
+    }
+
+    /// Check if the file name still refers to the open file, opening the file that has
+    /// the name now if it doesn't. Returns if the file was opened again.
+    ///
+    /// If the name is inaccessible and `retry` is not set, the file is no longer
+    /// followed.
+    fn reopen_if_changed(&mut self, retry: bool) -> bool {
+        let metadata = match fs::metadata(&self.name) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                if self.file.is_some() {
+                    eprintln!("tail: '{}' has become inaccessible: {}", self.name, strerror(&err));
+                    self.file = None;
+                    self.id = None;
+                }
+                if !retry {
+                    self.gone = true;
+                }
+                return false;
+            },
+        };
+
+        let id = file_id(&metadata);
+        if self.file.is_some() && id == self.id {
+            return false;
+        }
+
+        let file = match File::open(&self.name) {
+            Ok(file) => file,
+            // Can happen if the file is removed again or its permissions changed, the
+            // next check will tell
+            Err(_) => return false,
+        };
+
+        if self.file.is_some() {
+            eprintln!("tail: '{}' has been replaced;  following new file", self.name);
+        } else {
+            eprintln!("tail: '{}' has appeared;  following new file", self.name);
+        }
+
+        self.id = file.metadata().ok().as_ref().and_then(file_id);
+        self.file = Some(file);
+        self.pos = 0;
+
+        true
+    }
+}
+
+/// Options of the follow mode.
+#[derive(Debug, Clone, Copy)]
+pub struct FollowOptions {
+    /// How the files are followed.
+    pub mode: FollowMode,
+    /// Keep trying to open the files that are inaccessible.
+    pub retry: bool,
+    /// Time between the checks for new data, when no watcher is available it's the time
+    /// between each poll.
+    pub sleep_interval: Duration,
+    /// Print a header when the followed file with new data changes.
+    pub headers: bool,
+    /// Stop following after the process with this ID exits.
+    pub pid: Option<Pid>,
+}
+
+/// A process whose exit ends the follow.
+struct ProcessWatch {
+    pid: Pid,
+    /// The `pidfd` of the process, if it could be opened.
+    #[cfg(target_os = "linux")]
+    fd: Option<std::os::unix::io::RawFd>,
+}
+
+impl ProcessWatch {
+    #[cfg(target_os = "linux")]
+    fn new(pid: Pid) -> Self {
+        let fd = coreutils_core::os::process::pidfd_open(pid).ok();
+        ProcessWatch { pid, fd }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn new(pid: Pid) -> Self {
+        ProcessWatch { pid }
+    }
+
+    /// The file descriptor to wait on along with the file changes.
+    #[cfg(target_os = "linux")]
+    fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        self.fd
+    }
+
+    /// Check if the process exited.
+    #[cfg(target_os = "linux")]
+    fn exited(&self) -> bool {
+        use coreutils_core::libc::{poll, pollfd, POLLIN};
+
+        match self.fd {
+            Some(fd) => {
+                let mut fds = pollfd { fd, events: POLLIN, revents: 0 };
+                unsafe { poll(&mut fds, 1, 0) > 0 }
+            },
+            None => !coreutils_core::os::process::exists(self.pid),
+        }
+    }
+
+    /// Check if the process exited.
+    #[cfg(all(unix, not(target_os = "linux")))]
+    fn exited(&self) -> bool {
+        !coreutils_core::os::process::exists(self.pid)
+    }
+
+    /// Check if the process exited. Not supported, so it's never known.
+    #[cfg(not(unix))]
+    fn exited(&self) -> bool {
+        let _ = self.pid;
+        false
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for ProcessWatch {
+    fn drop(&mut self) {
+        if let Some(fd) = self.fd {
+            unsafe { coreutils_core::libc::close(fd) };
+        }
+    }
+}
+
+/// Follow `files`, writing on `writer` the data appended to them. Never returns unless an
+/// error happens, no file can be followed anymore or the process of `options.pid` exits.
+///
+/// The last file on `files` is assumed to be the last one written, so no header is
+/// printed until other file has new data.
+pub fn follow<W: Write>(
+    mut files: Vec<FollowedFile>, options: FollowOptions, writer: &mut W,
+) -> io::Result<()> {
+    if files.is_empty() {
+        return Ok(());
+    }
+
+    let process = options.pid.map(ProcessWatch::new);
+    let mut watcher = Watcher::new(&files);
+    let mut last_written = files.len() - 1;
+
+    loop {
+        // Checked before reading, so what was written before the exit is still output
+        let exited = process.as_ref().map(ProcessWatch::exited).unwrap_or(false);
+        let mut rewatch = false;
+
+        for (i, file) in files.iter_mut().enumerate() {
+            if file.gone {
+                continue;
+            }
+
+            if write_file(file, writer, options.headers && i != last_written)? {
+                last_written = i;
+            }
+
+            // On the descriptor mode, the name is only used until the file is opened
+            let check_name = options.mode == FollowMode::Name || file.file.is_none();
+
+            if check_name && file.reopen_if_changed(options.retry) {
+                rewatch = true;
+                if write_file(file, writer, options.headers && i != last_written)? {
+                    last_written = i;
+                }
+            }
+        }
+
+        if files.iter().all(|file| file.gone) {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "no files remaining"));
+        }
+
+        if exited {
+            return Ok(());
+        }
+
+        if rewatch {
+            watcher = Watcher::new(&files);
+        }
+
+        match &mut watcher {
+            Some(w) => w.wait(options.sleep_interval, process.as_ref()),
+            None => std::thread::sleep(options.sleep_interval),
+        }
+    }
+}
+
+/// Write the data appended to `file`, the read errors are reported and ignored.
+///
+/// The data is flushed, so it comes before any diagnostic of the next checks.
+fn write_file<W: Write>(file: &mut FollowedFile, writer: &mut W, header: bool) -> io::Result<bool> {
+    match file.write_appended(writer, header) {
+        Ok(written) => {
+            writer.flush()?;
+            Ok(written)
+        },
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Err(err),
+        Err(err) => {
+            eprintln!("tail: error reading '{}': {}", file.name, strerror(&err));
+            Ok(false)
+        },
+    }
+}
+
+#[cfg(target_os = "linux")]
+use self::inotify::Watcher;
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+))]
+use self::kqueue::Watcher;
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+)))]
+use self::poll::Watcher;
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use std::{ffi::CString, time::Duration};
+
+    use coreutils_core::libc::{
+        c_int, c_void, close, inotify_add_watch, inotify_init1, poll, pollfd, read, IN_ATTRIB,
+        IN_CLOEXEC, IN_DELETE_SELF, IN_MODIFY, IN_MOVE_SELF, IN_NONBLOCK, POLLIN,
+    };
+
+    use super::{FollowedFile, ProcessWatch};
+
+    /// Watch the files for changes with `inotify(7)`.
+    pub struct Watcher {
+        fd: c_int,
+    }
+
+    impl Watcher {
+        /// Creates a watcher for `files`, returns `None` if any of them can't be watched.
+        pub fn new(files: &[FollowedFile]) -> Option<Self> {
+            let fd = unsafe { inotify_init1(IN_CLOEXEC | IN_NONBLOCK) };
+            if fd == -1 {
+                return None;
+            }
+            let watcher = Watcher { fd };
+
+            let mask = IN_MODIFY | IN_ATTRIB | IN_DELETE_SELF | IN_MOVE_SELF;
+            for file in files.iter().filter(|file| file.file.is_some()) {
+                let path = CString::new(file.name.as_bytes()).ok()?;
+                if unsafe { inotify_add_watch(watcher.fd, path.as_ptr(), mask) } == -1 {
+                    return None;
+                }
+            }
+
+            Some(watcher)
+        }
+
+        /// Wait until a file changes, the `process` exits or `timeout` passes.
+        pub fn wait(&mut self, timeout: Duration, process: Option<&ProcessWatch>) {
+            let mut fds = [pollfd { fd: self.fd, events: POLLIN, revents: 0 }; 2];
+            let mut nfds = 1;
+            if let Some(fd) = process.and_then(ProcessWatch::fd) {
+                fds[1] = pollfd { fd, events: POLLIN, revents: 0 };
+                nfds = 2;
+            }
+            let timeout = timeout.as_millis().min(c_int::MAX as u128) as c_int;
+
+            if unsafe { poll(fds.as_mut_ptr(), nfds, timeout) } <= 0 || fds[0].revents == 0 {
+                return;
+            }
+
+            // Drain the events, only knowing that something changed is needed
+            let mut buffer = [0u8; 4096];
+            while unsafe { read(self.fd, buffer.as_mut_ptr() as *mut c_void, buffer.len()) } > 0 {}
+        }
+    }
+
+    impl Drop for Watcher {
+        fn drop(&mut self) {
+            unsafe { close(self.fd) };
+        }
+    }
+}
+
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+))]
+mod kqueue {
+    use std::{mem, os::unix::io::AsRawFd, ptr, thread, time::Duration};
+
+    use coreutils_core::libc::{
+        c_int, close, kevent, kqueue, timespec, EVFILT_VNODE, EV_ADD, EV_CLEAR, NOTE_ATTRIB,
+        NOTE_DELETE, NOTE_EXTEND, NOTE_RENAME, NOTE_WRITE,
+    };
+
+    use super::{FollowedFile, ProcessWatch};
+
+    /// Watch the files for changes with `kqueue(2)`.
+    pub struct Watcher {
+        kq: c_int,
+        events: Vec<kevent>,
+    }
+
+    impl Watcher {
+        /// Creates a watcher for `files`, returns `None` if any of them can't be watched.
+        pub fn new(files: &[FollowedFile]) -> Option<Self> {
+            let kq = unsafe { kqueue() };
+            if kq == -1 {
+                return None;
+            }
+
+            let changes: Vec<kevent> = files
+                .iter()
+                .filter_map(|file| file.file.as_ref())
+                .map(|file| {
+                    let mut event: kevent = unsafe { mem::zeroed() };
+                    event.ident = file.as_raw_fd() as _;
+                    event.filter = EVFILT_VNODE;
+                    event.flags = EV_ADD | EV_CLEAR;
+                    event.fflags =
+                        NOTE_WRITE | NOTE_EXTEND | NOTE_DELETE | NOTE_RENAME | NOTE_ATTRIB;
+                    event
+                })
+                .collect();
+
+            let watcher = Watcher { kq, events: changes.clone() };
+
+            let res = unsafe {
+                kevent(
+                    watcher.kq,
+                    changes.as_ptr(),
+                    changes.len() as _,
+                    ptr::null_mut(),
+                    0,
+                    ptr::null(),
+                )
+            };
+
+            if res == -1 {
+                return None;
+            }
+
+            Some(watcher)
+        }
+
+        /// Wait until a file changes or `timeout` passes.
+        pub fn wait(&mut self, timeout: Duration, _process: Option<&ProcessWatch>) {
+            // Without events `kevent` returns right away
+            if self.events.is_empty() {
+                thread::sleep(timeout);
+                return;
+            }
+
+            let timeout =
+                timespec { tv_sec: timeout.as_secs() as _, tv_nsec: timeout.subsec_nanos() as _ };
+
+            unsafe {
+                kevent(
+                    self.kq,
+                    ptr::null(),
+                    0,
+                    self.events.as_mut_ptr(),
+                    self.events.len() as _,
+                    &timeout,
+                )
+            };
+        }
+    }
+
+    impl Drop for Watcher {
+        fn drop(&mut self) {
+            unsafe { close(self.kq) };
+        }
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+)))]
+mod poll {
+    use std::{thread, time::Duration};
+
+    use super::{FollowedFile, ProcessWatch};
+
+    /// Poll the files every sleep interval.
+    pub struct Watcher;
+
+    impl Watcher {
+        pub fn new(_files: &[FollowedFile]) -> Option<Self> {
+            Some(Watcher)
+        }
+
+        /// Wait for `timeout`.
+        pub fn wait(&mut self, timeout: Duration, _process: Option<&ProcessWatch>) {
+            thread::sleep(timeout);
+        }
+    }
+}
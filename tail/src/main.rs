@@ -1,30 +1,129 @@
 use std::{
+    convert::TryFrom,
     fs::File,
-    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    process,
+    time::Duration,
 };
 
-use clap::{value_t, ArgMatches, ErrorKind};
+use clap::ArgMatches;
+use coreutils_core::{error::strerror, size::parse_size};
 
 mod cli;
+mod follow;
+
+use follow::{FollowMode, FollowOptions, FollowedFile, Pid};
 
 const DEFAULT_LINES_COUNT: usize = 10;
+const NEW_LINE: u8 = b'\n';
+const DEFAULT_SLEEP_INTERVAL: Duration = Duration::from_secs(1);
+/// The suffixes of the multipliers the counts can have.
+const COUNT_SUFFIXES: &str = "bkKmMgGtTPE";
 
 fn main() {
     let matches = cli::create_app().get_matches();
 
     let flags = Flags::from_matches(&matches);
     let input_list = Input::from_matches(&matches);
+    let headers = input_list.len() > 1;
+    let follow = follow_options(&matches, headers);
+
+    let mut writer = BufWriter::new(io::stdout());
+
+    let retry = follow.map(|f| f.retry).unwrap_or(false);
+
+    let (success, files) =
+        tail(&flags, input_list, &mut writer, headers, retry).unwrap_or_else(|err| {
+            eprintln!("tail: {}", strerror(&err));
+            process::exit(1);
+        });
+
+    if let Some(options) = follow {
+        if let Err(err) = follow::follow(files, options, &mut writer) {
+            if err.kind() != io::ErrorKind::BrokenPipe {
+                eprintln!("tail: {}", strerror(&err));
+            }
+            process::exit(1);
+        }
+    }
 
-    tail(&flags, input_list).unwrap_or_else(|err| {
-        eprintln!("tail: {}", err);
-        std::process::exit(1);
-    });
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// Parse the follow mode arguments, returns `None` if not following.
+///
+/// This will exit the program early on invalid args
+fn follow_options(matches: &ArgMatches, headers: bool) -> Option<FollowOptions> {
+    let name_retry = matches.is_present("follow_name_retry");
+
+    if !matches.is_present("follow") && !name_retry {
+        if matches.is_present("pid") {
+            eprintln!("tail: warning: PID ignored; --pid=PID is useful only when following");
+        }
+        return None;
+    }
+
+    // `--follow` without a value is the same as `--follow=descriptor`
+    let mode = match matches.value_of("follow") {
+        Some("name") => FollowMode::Name,
+        _ if name_retry => FollowMode::Name,
+        _ => FollowMode::Descriptor,
+    };
+
+    let sleep_interval = match matches.value_of("sleep_interval") {
+        Some(value) => match value.parse::<f64>() {
+            Ok(secs) if secs >= 0.0 && secs.is_finite() => Duration::from_secs_f64(secs),
+            _ => {
+                eprintln!("tail: invalid number of seconds: '{}'", value);
+                process::exit(1);
+            },
+        },
+        None => DEFAULT_SLEEP_INTERVAL,
+    };
+
+    let retry = matches.is_present("retry") || name_retry;
+
+    if retry && mode == FollowMode::Descriptor {
+        eprintln!("tail: warning: --retry only effective for the initial open");
+    }
+
+    let pid = matches.value_of("pid").and_then(parse_pid);
+
+    Some(FollowOptions { mode, retry, sleep_interval, headers, pid })
+}
+
+/// Parse the `--pid` value.
+///
+/// This will exit the program early on invalid values
+#[cfg(unix)]
+fn parse_pid(value: &str) -> Option<Pid> {
+    match value.parse::<Pid>() {
+        Ok(pid) if pid >= 0 => Some(pid),
+        _ => {
+            eprintln!("tail: invalid PID: '{}'", value);
+            process::exit(1);
+        },
+    }
 }
 
-/// We truncate the input at either some number of lines or bytes
+/// Parse the `--pid` value, not supported on this system.
+#[cfg(not(unix))]
+fn parse_pid(_value: &str) -> Option<Pid> {
+    eprintln!("tail: warning: --pid=PID is not supported on this system");
+    None
+}
+
+/// We truncate the input at either some number of lines or bytes from the end, or start
+/// the output at some line or byte from the start
 enum Flags {
     LinesCount(usize),
     BytesCount(usize),
+    /// Output starting with the line number, the first line is 1
+    LinesFrom(usize),
+    /// Output starting with the byte number, the first byte is 1
+    BytesFrom(usize),
 }
 
 impl Flags {
@@ -32,22 +131,46 @@ impl Flags {
     ///
     /// This will exit the program early on invalid args
     fn from_matches(matches: &ArgMatches) -> Self {
-        match value_t!(matches, "bytes", usize) {
-            Ok(bytes_count) => Flags::BytesCount(bytes_count),
-            Err(e) => match e.kind {
-                ErrorKind::ValueValidation => e.exit(),
-                _ => match value_t!(matches, "lines", usize) {
-                    Ok(l) => Flags::LinesCount(l),
-                    Err(e) => match e.kind {
-                        ErrorKind::ValueValidation => e.exit(),
-                        _ => Flags::LinesCount(DEFAULT_LINES_COUNT),
-                    },
+        if let Some(bytes) = matches.value_of("bytes") {
+            match parse_count(bytes) {
+                Some((true, count)) => Flags::BytesFrom(count),
+                Some((false, count)) => Flags::BytesCount(count),
+                None => {
+                    eprintln!("tail: invalid number of bytes: '{}'", bytes);
+                    process::exit(1);
                 },
-            },
+            }
+        } else if let Some(lines) = matches.value_of("lines") {
+            match parse_count(lines) {
+                Some((true, count)) => Flags::LinesFrom(count),
+                Some((false, count)) => Flags::LinesCount(count),
+                None => {
+                    eprintln!("tail: invalid number of lines: '{}'", lines);
+                    process::exit(1);
+                },
+            }
+        } else {
+            Flags::LinesCount(DEFAULT_LINES_COUNT)
         }
     }
 }
 
+/// Parse a count argument, returning if it has a leading '+' and the count.
+///
+/// The count may have a multiplier suffix: `b` 512, `kB` 1000, `K` 1024, `MB` 1000*1000,
+/// `M` 1024*1024, and so on for `G`, `T`, `P` and `E`. The binary suffixes can also be
+/// written as `KiB`, `MiB`, ...
+fn parse_count(value: &str) -> Option<(bool, usize)> {
+    let (from_start, value) = match value.strip_prefix('+') {
+        Some(value) => (true, value),
+        None => (false, value.strip_prefix('-').unwrap_or(value)),
+    };
+
+    let count = parse_size(value, COUNT_SUFFIXES).ok()?;
+
+    Some((from_start, usize::try_from(count).ok()?))
+}
+
 /// Input is either a file, or STDIN
 enum Input {
     File(String),
@@ -68,43 +191,75 @@ impl Input {
 }
 
 /// Return the tail of our input, truncated at a number of lines or bytes
-fn tail(flags: &Flags, input_list: Vec<Input>) -> io::Result<()> {
-    let files_count = input_list.len();
-    let mut writer = BufWriter::new(io::stdout());
-
-    for (i, input) in input_list.iter().enumerate() {
-        if i > 0 {
-            writeln!(writer)?;
-        }
-
-        let buffer = match input {
+///
+/// Returns if all the inputs were read successfully and the opened files, so they can be
+/// followed. With `retry`, the files that could not be opened are returned as well.
+fn tail<W: Write>(
+    flags: &Flags, input_list: Vec<Input>, writer: &mut W, headers: bool, retry: bool,
+) -> io::Result<(bool, Vec<FollowedFile>)> {
+    let mut success = true;
+    let mut files = Vec::new();
+    let mut first_output = true;
+
+    for input in input_list {
+        let (name, buffer) = match input {
             Input::File(file) => {
-                if files_count > 1 {
-                    writeln!(writer, "==> {} <==", file)?;
+                let mut f = match File::open(&file) {
+                    Ok(f) => f,
+                    Err(err) => {
+                        eprintln!("tail: cannot open '{}' for reading: {}", file, strerror(&err));
+                        success = false;
+                        if retry {
+                            files.push(FollowedFile::missing(file));
+                        }
+                        continue;
+                    },
+                };
+
+                print_header(writer, headers, &mut first_output, &file)?;
+
+                // Regular files are read only where the output starts
+                if f.metadata().map(|m| m.is_file()).unwrap_or(false) {
+                    match tail_file(flags, &mut f, writer) {
+                        Ok(pos) => files.push(FollowedFile::new(file, f, pos)),
+                        Err(err) => {
+                            eprintln!("tail: error reading '{}': {}", file, strerror(&err));
+                            success = false;
+                        },
+                    }
+                    continue;
                 }
 
                 // Move the contents of the file into a cloneable buffer so the
                 // buffer's bytes or lines of the file can be counted without
                 // clearing the original buffer.
                 let mut buffer: Vec<u8> = Vec::new();
-                let mut f = File::open(file)?;
-                f.read_to_end(&mut buffer)?;
+                if let Err(err) = f.read_to_end(&mut buffer) {
+                    eprintln!("tail: error reading '{}': {}", file, strerror(&err));
+                    success = false;
+                    continue;
+                }
+
+                files.push(FollowedFile::new(file.clone(), f, buffer.len() as u64));
 
-                buffer
+                (file, buffer)
             },
             Input::Stdin => {
-                if files_count > 1 {
-                    writeln!(writer, "==> standard input <==")?;
-                }
+                let name = String::from("standard input");
+                print_header(writer, headers, &mut first_output, &name)?;
 
                 // Move the contents of the standard input into a cloneable
                 // buffer so the buffer's bytes or lines of the file can be
                 // counted without clearing the original buffer.
                 let mut buffer: Vec<u8> = Vec::new();
                 let mut stdin = io::stdin();
-                stdin.read_to_end(&mut buffer)?;
+                if let Err(err) = stdin.read_to_end(&mut buffer) {
+                    eprintln!("tail: error reading '{}': {}", name, strerror(&err));
+                    success = false;
+                    continue;
+                }
 
-                buffer
+                (name, buffer)
             },
         };
 
@@ -115,12 +270,121 @@ fn tail(flags: &Flags, input_list: Vec<Input>) -> io::Result<()> {
         let count = match flags {
             Flags::LinesCount(_) => line_count(buffer.clone()),
             Flags::BytesCount(_) => byte_count(buffer.clone()),
+            Flags::LinesFrom(_) | Flags::BytesFrom(_) => 0,
         };
 
-        read_stream(flags, reader, &mut writer, count)?;
+        if let Err(err) = read_stream(flags, reader, writer, count) {
+            eprintln!("tail: error reading '{}': {}", name, strerror(&err));
+            success = false;
+        }
     }
 
-    Ok(())
+    writer.flush()?;
+
+    Ok((success, files))
+}
+
+/// Print the header for the input `name` if `headers` is set, separating it from the the
+/// previous output with a empty line
+fn print_header<W: Write>(
+    writer: &mut W, headers: bool, first_output: &mut bool, name: &str,
+) -> io::Result<()> {
+    if !headers {
+        return Ok(());
+    }
+
+    if !*first_output {
+        writeln!(writer)?;
+    }
+    *first_output = false;
+
+    writeln!(writer, "==> {} <==", name)
+}
+
+/// Write the tail of a regular file, seeking to where the output starts. Returns the
+/// offset of the end of the output.
+fn tail_file<W: Write>(flags: &Flags, file: &mut File, writer: &mut W) -> io::Result<u64> {
+    let end = file.seek(SeekFrom::End(0))?;
+
+    let start = match flags {
+        Flags::BytesCount(bytes_count) => end.saturating_sub(*bytes_count as u64),
+        Flags::BytesFrom(byte) => end.min(byte.saturating_sub(1) as u64),
+        Flags::LinesCount(lines_count) => find_lines_from_end(file, end, *lines_count)?,
+        Flags::LinesFrom(line) => {
+            file.seek(SeekFrom::Start(0))?;
+            let mut reader = BufReader::new(file);
+            let skipped = skip_lines(&mut reader, *line)?;
+            let copied = io::copy(&mut reader, writer)?;
+            return Ok(skipped + copied);
+        },
+    };
+
+    file.seek(SeekFrom::Start(start))?;
+    let copied = io::copy(file, writer)?;
+
+    Ok(start + copied)
+}
+
+/// Find the offset in `file` where the last `lines_count` lines start, reading it
+/// backwards from `end`.
+fn find_lines_from_end(file: &mut File, end: u64, lines_count: usize) -> io::Result<u64> {
+    const CHUNK_SIZE: u64 = 8192;
+
+    if lines_count == 0 {
+        return Ok(end);
+    }
+
+    let mut buffer = vec![0u8; CHUNK_SIZE as usize];
+    let mut pos = end;
+    let mut newlines_to_find = lines_count;
+    let mut is_last_byte = true;
+
+    while pos > 0 {
+        let read_size = CHUNK_SIZE.min(pos);
+        pos -= read_size;
+        file.seek(SeekFrom::Start(pos))?;
+
+        let chunk = &mut buffer[..read_size as usize];
+        file.read_exact(chunk)?;
+
+        for (i, &byte) in chunk.iter().enumerate().rev() {
+            if byte != NEW_LINE {
+                is_last_byte = false;
+                continue;
+            }
+
+            // The line terminator of the last line does not start a line
+            if is_last_byte {
+                is_last_byte = false;
+                continue;
+            }
+
+            newlines_to_find -= 1;
+            if newlines_to_find == 0 {
+                return Ok(pos + i as u64 + 1);
+            }
+        }
+    }
+
+    // The file does not have more lines than `lines_count`
+    Ok(0)
+}
+
+/// Skip the lines before the `line` line, returning the number of bytes skipped.
+fn skip_lines<R: BufRead>(reader: &mut R, line: usize) -> io::Result<u64> {
+    let mut skipped = 0;
+    let mut buffer = Vec::new();
+
+    for _ in 1..line {
+        buffer.clear();
+        let bytes_read = reader.read_until(NEW_LINE, &mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        skipped += bytes_read as u64;
+    }
+
+    Ok(skipped)
 }
 
 /// Read from a stream, truncated at a number of lines or bytes from the end,
@@ -130,7 +394,7 @@ fn read_stream<R: Read, W: Write>(
 ) -> Result<(), io::Error> {
     match flags {
         Flags::LinesCount(lines_count) => {
-            let difference = count - lines_count.to_owned();
+            let difference = count.saturating_sub(*lines_count);
 
             for line in reader.lines().skip(difference) {
                 let line = match line {
@@ -144,17 +408,25 @@ fn read_stream<R: Read, W: Write>(
             }
         },
         Flags::BytesCount(bytes_count) => {
-            let difference = count - bytes_count.to_owned();
+            let difference = count.saturating_sub(*bytes_count);
 
             let mut buffer = Vec::new();
 
-            reader.fill_buf()?;
-            reader.consume(difference);
+            // Consuming the buffer directly would only skip what is buffered
+            io::copy(&mut reader.by_ref().take(difference as u64), &mut io::sink())?;
 
             reader.read_to_end(&mut buffer)?;
 
             writer.write_all(&buffer)?;
         },
+        Flags::LinesFrom(line) => {
+            skip_lines(&mut reader, *line)?;
+            io::copy(&mut reader, writer)?;
+        },
+        Flags::BytesFrom(byte) => {
+            io::copy(&mut reader.by_ref().take(byte.saturating_sub(1) as u64), &mut io::sink())?;
+            io::copy(&mut reader, writer)?;
+        },
     }
 
     Ok(())
@@ -201,4 +473,35 @@ mod tests {
 
         assert_eq!(String::from_utf8(out).unwrap(), "az".to_string());
     }
+
+    #[test]
+    fn read_stream_lines_from() {
+        let buffer = b"foo\nbar\nbaz";
+        let flags = Flags::LinesFrom(2);
+        let mut out = Vec::new();
+
+        read_stream(&flags, BufReader::new(&buffer[..]), &mut out, 0).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "bar\nbaz".to_string());
+    }
+
+    #[test]
+    fn read_stream_bytes_from() {
+        let buffer = b"foo\nbar\nbaz";
+        let flags = Flags::BytesFrom(3);
+        let mut out = Vec::new();
+
+        read_stream(&flags, BufReader::new(&buffer[..]), &mut out, 0).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "o\nbar\nbaz".to_string());
+    }
+
+    #[test]
+    fn parse_count_sign() {
+        assert_eq!(parse_count("10"), Some((false, 10)));
+        assert_eq!(parse_count("-10"), Some((false, 10)));
+        assert_eq!(parse_count("+10"), Some((true, 10)));
+        assert_eq!(parse_count("+1K"), Some((true, 1024)));
+        assert_eq!(parse_count("1x"), None);
+    }
 }
@@ -0,0 +1,58 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .usage("test EXPRESSION\n    test\n    [ EXPRESSION ]\n    [ ]\n    [ OPTION")
+        .arg(
+            Arg::with_name("EXPRESSION")
+                .help("The expression to evaluate, with each token as a separate argument.")
+                .long_help(
+                    "The expression to evaluate, with each token as a separate argument. An \
+                     omitted EXPRESSION is false.\n\nThe options are only recognized as the single \
+                     argument of [, test takes them as strings.\n\n  ( EXPRESSION \
+                     )              EXPRESSION is true\n  ! EXPRESSION                EXPRESSION \
+                     is false\n  EXPRESSION1 -a EXPRESSION2  both EXPRESSION1 and EXPRESSION2 are \
+                     true\n  EXPRESSION1 -o EXPRESSION2  either EXPRESSION1 or EXPRESSION2 is \
+                     true\n\n  -n STRING                   the length of STRING is \
+                     nonzero\n  STRING                      same as -n STRING\n  -z \
+                     STRING                   the length of STRING is zero\n  STRING1 = \
+                     STRING2           the strings are equal, also ==\n  STRING1 != \
+                     STRING2          the strings are not equal\n\n  INTEGER1 -eq \
+                     INTEGER2       INTEGER1 is equal to INTEGER2, also -ne, -lt, -le, -gt and \
+                     -ge\n  -l STRING                   the length of STRING, as an INTEGER \
+                     operand\n\n  FILE1 -ef FILE2             FILE1 and FILE2 have the same device \
+                     and inode numbers\n  FILE1 -nt FILE2             FILE1 is newer (modification \
+                     date) than FILE2\n  FILE1 -ot FILE2             FILE1 is older than \
+                     FILE2\n\n  -b FILE                     FILE exists and is block special\n  -c \
+                     FILE                     FILE exists and is character special\n  -d \
+                     FILE                     FILE exists and is a directory\n  -e \
+                     FILE                     FILE exists\n  -f FILE                     FILE \
+                     exists and is a regular file\n  -g FILE                     FILE exists and \
+                     is set-group-ID\n  -G FILE                     FILE exists and is owned by \
+                     the effective group ID\n  -h FILE                     FILE exists and is a \
+                     symbolic link, also -L\n  -k FILE                     FILE exists and has its \
+                     sticky bit set\n  -N FILE                     FILE exists and has been \
+                     modified since it was last read\n  -O FILE                     FILE exists \
+                     and is owned by the effective user ID\n  -p FILE                     FILE \
+                     exists and is a named pipe\n  -r FILE                     FILE exists and \
+                     read permission is granted\n  -s FILE                     FILE exists and has \
+                     a size greater than zero\n  -S FILE                     FILE exists and is a \
+                     socket\n  -t FD                       file descriptor FD is opened on a \
+                     terminal\n  -u FILE                     FILE exists and its set-user-ID bit \
+                     is set\n  -w FILE                     FILE exists and write permission is \
+                     granted\n  -x FILE                     FILE exists and execute (or search) \
+                     permission is granted\n\nThe exit status is 0 if EXPRESSION is true, 1 if it \
+                     is false and 2 if it is invalid.",
+                )
+                .multiple(true),
+        )
+}
@@ -0,0 +1,448 @@
+//! The parser and evaluator of the expressions, with the POSIX rules for the expressions
+//! of up to 4 arguments.
+use std::{
+    cmp::Ordering,
+    ffi::{OsStr, OsString},
+    fmt,
+    os::unix::ffi::OsStrExt,
+};
+
+use crate::file;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestError {
+    MissingArgument(String),
+    UnaryExpected(String),
+    BinaryExpected(String),
+    CloseParenExpected,
+    CloseParenExpectedFound(String),
+    InvalidInteger(String),
+    ExtraArgument(String),
+    NoLength(&'static str),
+}
+
+impl fmt::Display for TestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestError::MissingArgument(arg) => write!(f, "missing argument after '{}'", arg),
+            TestError::UnaryExpected(arg) => write!(f, "'{}': unary operator expected", arg),
+            TestError::BinaryExpected(arg) => write!(f, "'{}': binary operator expected", arg),
+            TestError::CloseParenExpected => write!(f, "')' expected"),
+            TestError::CloseParenExpectedFound(arg) => {
+                write!(f, "')' expected, found '{}'", arg)
+            },
+            TestError::InvalidInteger(arg) => write!(f, "invalid integer '{}'", arg),
+            TestError::ExtraArgument(arg) => write!(f, "extra argument '{}'", arg),
+            TestError::NoLength(op) => write!(f, "{} does not accept -l", op),
+        }
+    }
+}
+
+/// The binary operators.
+const BINARY_OPERATORS: [&str; 12] =
+    ["=", "==", "!=", "-eq", "-ne", "-lt", "-le", "-gt", "-ge", "-nt", "-ot", "-ef"];
+
+/// The letters of the unary operators.
+const UNARY_OPERATORS: &[u8] = b"bcdefgGhkLnNOprsStuwxz";
+
+/// Evaluate the expression of the `args`. No arguments are false.
+pub fn evaluate(args: &[OsString]) -> Result<bool, TestError> {
+    if args.is_empty() {
+        return Ok(false);
+    }
+
+    let mut parser = Parser { args, pos: 0 };
+    let value = parser.posix(args.len())?;
+
+    match args.get(parser.pos) {
+        Some(arg) => Err(TestError::ExtraArgument(lossy(arg))),
+        None => Ok(value),
+    }
+}
+
+/// A recursive descent parser, with a method for each precedence level.
+///
+/// Like the other implementations, the operands of `-a` and `-o` are always evaluated.
+struct Parser<'a> {
+    args: &'a [OsString],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    /// The argument at `pos` after the current one.
+    fn peek(&self, pos: usize) -> &[u8] {
+        self.args.get(self.pos + pos).map_or(&[], |arg| arg.as_bytes())
+    }
+
+    fn remaining(&self) -> usize {
+        self.args.len() - self.pos
+    }
+
+    /// Advance over the current argument, failing if it's the last and `more` are needed.
+    fn advance(&mut self, more: bool) -> Result<(), TestError> {
+        self.pos += 1;
+        if more && self.pos >= self.args.len() {
+            return Err(self.beyond());
+        }
+        Ok(())
+    }
+
+    fn beyond(&self) -> TestError {
+        TestError::MissingArgument(self.args.last().map(|arg| lossy(arg)).unwrap_or_default())
+    }
+
+    /// Evaluate the next `count` arguments with the POSIX rules, or as a expression if
+    /// they are more than 4.
+    fn posix(&mut self, count: usize) -> Result<bool, TestError> {
+        match count {
+            1 => Ok(self.one_argument()),
+            2 => self.two_arguments(),
+            3 => self.three_arguments(),
+            4 if self.peek(0) == b"!" => {
+                self.advance(true)?;
+                Ok(!self.three_arguments()?)
+            },
+            4 if self.peek(0) == b"(" && self.peek(3) == b")" => {
+                self.advance(false)?;
+                let value = self.two_arguments()?;
+                self.advance(false)?;
+                Ok(value)
+            },
+            _ => self.expression(),
+        }
+    }
+
+    /// `STRING`
+    fn one_argument(&mut self) -> bool {
+        let value = !self.peek(0).is_empty();
+        self.pos += 1;
+        value
+    }
+
+    /// `! STRING` and `-OP ARG`
+    fn two_arguments(&mut self) -> Result<bool, TestError> {
+        if self.peek(0) == b"!" {
+            self.advance(false)?;
+            return Ok(!self.one_argument());
+        }
+        if is_option(self.peek(0)) {
+            return self.unary_operator();
+        }
+        Err(self.beyond())
+    }
+
+    /// `ARG1 OP ARG2`, `! -OP ARG` and `( STRING )`
+    fn three_arguments(&mut self) -> Result<bool, TestError> {
+        if is_binary_operator(self.peek(1)) {
+            return self.binary_operator(false);
+        }
+        if self.peek(0) == b"!" {
+            self.advance(true)?;
+            return Ok(!self.two_arguments()?);
+        }
+        if self.peek(0) == b"(" && self.peek(2) == b")" {
+            self.advance(false)?;
+            let value = self.one_argument();
+            self.advance(false)?;
+            return Ok(value);
+        }
+        if self.peek(1) == b"-a" || self.peek(1) == b"-o" {
+            return self.expression();
+        }
+        Err(TestError::BinaryExpected(lossy(&self.args[self.pos + 1])))
+    }
+
+    fn expression(&mut self) -> Result<bool, TestError> {
+        if self.remaining() == 0 {
+            return Err(self.beyond());
+        }
+        self.or()
+    }
+
+    /// `EXPRESSION1 -o EXPRESSION2`
+    fn or(&mut self) -> Result<bool, TestError> {
+        let mut value = self.and()?;
+        while self.peek(0) == b"-o" {
+            self.advance(false)?;
+            value |= self.and()?;
+        }
+        Ok(value)
+    }
+
+    /// `EXPRESSION1 -a EXPRESSION2`
+    fn and(&mut self) -> Result<bool, TestError> {
+        let mut value = self.term()?;
+        while self.peek(0) == b"-a" {
+            self.advance(false)?;
+            value &= self.term()?;
+        }
+        Ok(value)
+    }
+
+    /// `! EXPRESSION`, `( EXPRESSION )`, the operators and the strings
+    fn term(&mut self) -> Result<bool, TestError> {
+        let mut negated = false;
+        while self.remaining() > 0 && self.peek(0) == b"!" {
+            self.advance(true)?;
+            negated = !negated;
+        }
+
+        if self.remaining() == 0 {
+            return Err(self.beyond());
+        }
+
+        let value = if self.peek(0) == b"(" {
+            self.advance(true)?;
+
+            // The POSIX rules apply to the arguments until the `)`
+            let mut count = 1;
+            while count < self.remaining() && self.peek(count) != b")" {
+                if count == 4 {
+                    count = self.remaining();
+                    break;
+                }
+                count += 1;
+            }
+            let value = self.posix(count)?;
+
+            match self.args.get(self.pos) {
+                Some(arg) if arg == ")" => {},
+                Some(arg) => return Err(TestError::CloseParenExpectedFound(lossy(arg))),
+                None => return Err(TestError::CloseParenExpected),
+            }
+            self.advance(false)?;
+            value
+        } else if self.remaining() >= 4 && self.peek(0) == b"-l" && is_binary_operator(self.peek(2))
+        {
+            self.binary_operator(true)?
+        } else if self.remaining() >= 3 && is_binary_operator(self.peek(1)) {
+            self.binary_operator(false)?
+        } else if is_option(self.peek(0)) {
+            self.unary_operator()?
+        } else {
+            self.one_argument()
+        };
+
+        Ok(negated ^ value)
+    }
+
+    /// `-OP ARG`, where `-OP` is the current argument
+    fn unary_operator(&mut self) -> Result<bool, TestError> {
+        let op = self.peek(0)[1];
+        if !UNARY_OPERATORS.contains(&op) {
+            return Err(TestError::UnaryExpected(lossy(&self.args[self.pos])));
+        }
+        self.advance(true)?;
+
+        let arg = &self.args[self.pos];
+        let value = match op {
+            b'n' => !arg.is_empty(),
+            b'z' => arg.is_empty(),
+            b't' => file::is_terminal(&find_integer(arg)?),
+            _ => file::unary(op, arg),
+        };
+        self.pos += 1;
+        Ok(value)
+    }
+
+    /// `ARG1 OP ARG2`, where `ARG1` is the current argument, or `-l ARG1 OP ARG2` if
+    /// `left_length`
+    fn binary_operator(&mut self, left_length: bool) -> Result<bool, TestError> {
+        if left_length {
+            self.advance(false)?;
+        }
+
+        // The operator is after the left operand, the right one may have a `-l` before
+        let op_pos = self.pos + 1;
+        let right_length = op_pos + 2 < self.args.len() && self.args[op_pos + 1] == "-l";
+        let left = &self.args[op_pos - 1];
+        let right = &self.args[op_pos + 1 + right_length as usize];
+        let op = self.args[op_pos].as_bytes();
+        self.pos = op_pos + 2 + right_length as usize;
+
+        let value = match op {
+            b"=" | b"==" => left == right,
+            b"!=" => left != right,
+            b"-nt" | b"-ot" | b"-ef" if left_length || right_length => {
+                return Err(TestError::NoLength(match op {
+                    b"-nt" => "-nt",
+                    b"-ot" => "-ot",
+                    _ => "-ef",
+                }));
+            },
+            b"-nt" => file::newer(left, right),
+            b"-ot" => file::newer(right, left),
+            b"-ef" => file::same_file(left, right),
+            _ => {
+                let integer = |arg: &OsStr, length: bool| {
+                    if length {
+                        Ok(arg.len().to_string())
+                    } else {
+                        find_integer(arg)
+                    }
+                };
+                let ordering =
+                    compare_integers(&integer(left, left_length)?, &integer(right, right_length)?);
+
+                match op {
+                    b"-eq" => ordering == Ordering::Equal,
+                    b"-ne" => ordering != Ordering::Equal,
+                    b"-lt" => ordering == Ordering::Less,
+                    b"-le" => ordering != Ordering::Greater,
+                    b"-gt" => ordering == Ordering::Greater,
+                    _ => ordering != Ordering::Less,
+                }
+            },
+        };
+        Ok(value)
+    }
+}
+
+/// Whether `arg` may be a unary operator, a `-` and a character.
+fn is_option(arg: &[u8]) -> bool {
+    arg.len() == 2 && arg[0] == b'-'
+}
+
+fn is_binary_operator(arg: &[u8]) -> bool {
+    BINARY_OPERATORS.iter().any(|op| op.as_bytes() == arg)
+}
+
+/// The integer of `arg`, that may be surrounded by blanks and start with a sign.
+fn find_integer(arg: &OsStr) -> Result<String, TestError> {
+    let is_blank = |c: char| c == ' ' || c == '\t';
+    let invalid = || TestError::InvalidInteger(lossy(arg));
+
+    let integer = arg.to_str().ok_or_else(invalid)?.trim_matches(is_blank);
+    let integer = integer.strip_prefix('+').unwrap_or(integer);
+    let digits = integer.strip_prefix('-').unwrap_or(integer);
+
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    Ok(integer.to_string())
+}
+
+/// Compare the integers `left` and `right` by their digits, so they can be of any size.
+fn compare_integers(left: &str, right: &str) -> Ordering {
+    let split = |s: &str| -> (bool, String) {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        let digits = digits.trim_start_matches('0');
+        (negative && !digits.is_empty(), digits.to_string())
+    };
+    let (left_negative, left) = split(left);
+    let (right_negative, right) = split(right);
+
+    let magnitude = left.len().cmp(&right.len()).then_with(|| left.cmp(&right));
+    match (left_negative, right_negative) {
+        (false, false) => magnitude,
+        (true, true) => magnitude.reverse(),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+    }
+}
+
+fn lossy(arg: &OsStr) -> String {
+    arg.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use filetime::{set_file_mtime, FileTime};
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn eval(args: &[&str]) -> Result<bool, TestError> {
+        let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+        evaluate(&args)
+    }
+
+    #[test]
+    fn posix_rules() {
+        assert_eq!(eval(&[]), Ok(false));
+        assert_eq!(eval(&[""]), Ok(false));
+        assert_eq!(eval(&["-f"]), Ok(true));
+        assert_eq!(eval(&["!", ""]), Ok(true));
+        assert_eq!(eval(&["-n", ""]), Ok(false));
+        assert_eq!(eval(&["-z", ""]), Ok(true));
+        assert_eq!(eval(&["!", "!", "x"]), Ok(true));
+        assert_eq!(eval(&["(", "", ")"]), Ok(false));
+        assert_eq!(eval(&["(", "-n", "x", ")"]), Ok(true));
+        assert_eq!(eval(&["!", "a", "=", "b"]), Ok(true));
+        assert_eq!(eval(&["-a", "=", "-a"]), Ok(true));
+        assert_eq!(eval(&["!", "=", "!"]), Ok(true));
+    }
+
+    #[test]
+    fn connectives() {
+        assert_eq!(eval(&["a", "-a", ""]), Ok(false));
+        assert_eq!(eval(&["a", "-o", ""]), Ok(true));
+        assert_eq!(eval(&["", "-o", "", "-o", "a"]), Ok(true));
+        assert_eq!(eval(&["a", "-o", "b", "-a", ""]), Ok(true));
+        assert_eq!(eval(&["!", "(", "a", "=", "a", ")", "-a", "b"]), Ok(false));
+        assert_eq!(eval(&["(", "a", "-a", "(", "", "-o", "b", ")", ")"]), Ok(true));
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(eval(&[" 1 ", "-eq", "+1"]), Ok(true));
+        assert_eq!(eval(&["-0", "-eq", "0"]), Ok(true));
+        assert_eq!(eval(&["-2", "-lt", "-1"]), Ok(true));
+        assert_eq!(eval(&["10", "-gt", "9"]), Ok(true));
+        assert_eq!(eval(&["3", "-le", "3"]), Ok(true));
+        assert_eq!(eval(&["3", "-ne", "3"]), Ok(false));
+        assert_eq!(eval(&["99999999999999999999999", "-ge", "99999999999999999999998"]), Ok(true));
+        assert_eq!(eval(&["-l", "abc", "-eq", "3"]), Ok(true));
+        assert_eq!(eval(&["1", "-eq", "a"]), Err(TestError::InvalidInteger("a".to_string())));
+        assert_eq!(eval(&["1", "-eq", "1 2"]), Err(TestError::InvalidInteger("1 2".to_string())));
+    }
+
+    #[test]
+    fn files() {
+        let dir = tempdir().unwrap();
+        let old = dir.path().join("old");
+        let new = dir.path().join("new");
+        fs::write(&old, "").unwrap();
+        fs::write(&new, "new").unwrap();
+        set_file_mtime(&old, FileTime::from_unix_time(0, 0)).unwrap();
+
+        let (dir, old, new) =
+            (dir.path().to_str().unwrap(), old.to_str().unwrap(), new.to_str().unwrap());
+        assert_eq!(eval(&["-d", dir]), Ok(true));
+        assert_eq!(eval(&["-f", dir]), Ok(false));
+        assert_eq!(eval(&["-e", old]), Ok(true));
+        assert_eq!(eval(&["-s", old]), Ok(false));
+        assert_eq!(eval(&["-s", new]), Ok(true));
+        assert_eq!(eval(&["-e", "/nonexistent/file"]), Ok(false));
+        assert_eq!(eval(&[new, "-nt", old]), Ok(true));
+        assert_eq!(eval(&[new, "-ot", old]), Ok(false));
+        assert_eq!(eval(&[new, "-nt", "/nonexistent/file"]), Ok(true));
+        assert_eq!(eval(&[new, "-ef", new]), Ok(true));
+        assert_eq!(eval(&[new, "-ef", old]), Ok(false));
+    }
+
+    #[test]
+    fn errors() {
+        let missing = |arg: &str| Err(TestError::MissingArgument(arg.to_string()));
+        assert_eq!(eval(&["x", "y"]), missing("y"));
+        assert_eq!(eval(&["x", "-a"]), missing("-a"));
+        assert_eq!(eval(&["(", ")"]), missing(")"));
+        assert_eq!(eval(&["-q", "y"]), Err(TestError::UnaryExpected("-q".to_string())));
+        assert_eq!(eval(&["!", "-a", "x"]), Err(TestError::UnaryExpected("-a".to_string())));
+        assert_eq!(eval(&["a", "-q", "b"]), Err(TestError::BinaryExpected("-q".to_string())));
+        assert_eq!(eval(&["a", "<", "b"]), Err(TestError::BinaryExpected("<".to_string())));
+        assert_eq!(eval(&["(", "a", "b", "c"]), Err(TestError::BinaryExpected("b".to_string())));
+        assert_eq!(eval(&["a", "b", "c", "d"]), Err(TestError::ExtraArgument("b".to_string())));
+        assert_eq!(
+            eval(&["(", "a", "-a", "b", "c"]),
+            Err(TestError::CloseParenExpectedFound("c".to_string()))
+        );
+        assert_eq!(eval(&["(", "a", "-a", "b"]), Err(TestError::CloseParenExpected));
+        assert_eq!(eval(&["-l", "a", "-nt", "b"]), Err(TestError::NoLength("-nt")));
+    }
+}
@@ -0,0 +1,98 @@
+//! The predicates of the files.
+use std::{
+    cmp::Ordering,
+    ffi::{CString, OsStr},
+    fs::{self, Metadata},
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{FileTypeExt, MetadataExt},
+    },
+};
+
+use coreutils_core::{libc, os::tty::is_tty_raw};
+
+// The mode bits, as `u32` for the platforms with a smaller `mode_t`
+const SET_USER_ID: u32 = 0o4000;
+const SET_GROUP_ID: u32 = 0o2000;
+const STICKY: u32 = 0o1000;
+
+/// Evaluate the unary operator `op`, the letter after the `-`, on the file `path`.
+///
+/// The `-n` and `-z` operators check strings and `-t` a file descriptor, so they are not
+/// evaluated here.
+pub fn unary(op: u8, path: &OsStr) -> bool {
+    match op {
+        b'h' | b'L' => {
+            return fs::symlink_metadata(path).map(|m| m.file_type().is_symlink()).unwrap_or(false)
+        },
+        b'r' => return access(path, libc::R_OK),
+        b'w' => return access(path, libc::W_OK),
+        b'x' => return access(path, libc::X_OK),
+        _ => {},
+    }
+
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return false,
+    };
+    let file_type = metadata.file_type();
+
+    match op {
+        b'e' => true,
+        b'f' => file_type.is_file(),
+        b'd' => file_type.is_dir(),
+        b'b' => file_type.is_block_device(),
+        b'c' => file_type.is_char_device(),
+        b'p' => file_type.is_fifo(),
+        b'S' => file_type.is_socket(),
+        b's' => metadata.len() > 0,
+        b'u' => metadata.mode() & SET_USER_ID != 0,
+        b'g' => metadata.mode() & SET_GROUP_ID != 0,
+        b'k' => metadata.mode() & STICKY != 0,
+        b'O' => metadata.uid() == unsafe { libc::geteuid() },
+        b'G' => metadata.gid() == unsafe { libc::getegid() },
+        b'N' => accessed_before_modified(&metadata),
+        _ => false,
+    }
+}
+
+/// Whether the file descriptor `fd`, the digits of a integer, is opened on a terminal.
+pub fn is_terminal(fd: &str) -> bool {
+    match fd.parse::<i32>() {
+        Ok(fd) if fd >= 0 => is_tty_raw(fd),
+        _ => false,
+    }
+}
+
+/// Whether `path` is newer than `other`, or it exists and `other` doesn't.
+pub fn newer(path: &OsStr, other: &OsStr) -> bool {
+    match (fs::metadata(path), fs::metadata(other)) {
+        (Ok(metadata), Ok(other)) => compare_modified(&metadata, &other) == Ordering::Greater,
+        (Ok(_), Err(_)) => true,
+        _ => false,
+    }
+}
+
+/// Whether `path` and `other` are the same file, with the same device and inode numbers.
+pub fn same_file(path: &OsStr, other: &OsStr) -> bool {
+    match (fs::metadata(path), fs::metadata(other)) {
+        (Ok(metadata), Ok(other)) => metadata.dev() == other.dev() && metadata.ino() == other.ino(),
+        _ => false,
+    }
+}
+
+fn compare_modified(metadata: &Metadata, other: &Metadata) -> Ordering {
+    (metadata.mtime(), metadata.mtime_nsec()).cmp(&(other.mtime(), other.mtime_nsec()))
+}
+
+fn accessed_before_modified(metadata: &Metadata) -> bool {
+    (metadata.mtime(), metadata.mtime_nsec()) > (metadata.atime(), metadata.atime_nsec())
+}
+
+/// Whether the permissions in `mode` are granted to `path`.
+fn access(path: &OsStr, mode: libc::c_int) -> bool {
+    match CString::new(path.as_bytes()) {
+        Ok(path) => unsafe { libc::access(path.as_ptr(), mode) == 0 },
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,47 @@
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    path::Path,
+    process,
+};
+
+mod cli;
+mod eval;
+mod file;
+
+use eval::TestError;
+
+fn main() {
+    let mut args: Vec<OsString> = env::args_os().collect();
+    let name = args.first().and_then(|arg| Path::new(arg).file_name()).map(OsString::from);
+
+    // Only `[` has options, since `test --help` must test the "--help" string
+    let name = if name.as_deref() == Some(OsStr::new("[")) {
+        if args.len() == 2 && (args[1] == "--help" || args[1] == "--version") {
+            cli::create_app().get_matches_from(&args);
+        }
+
+        if args.len() < 2 || args[args.len() - 1] != "]" {
+            eprintln!("[: missing ']'");
+            process::exit(2);
+        }
+        args.pop();
+        "["
+    } else {
+        "test"
+    };
+
+    match eval::evaluate(&args[1..]) {
+        Ok(true) => {},
+        Ok(false) => process::exit(1),
+        // Like the other implementations, the `]` is found instead of the `)`
+        Err(TestError::CloseParenExpected) if name == "[" => {
+            eprintln!("[: {}", TestError::CloseParenExpectedFound("]".to_string()));
+            process::exit(2);
+        },
+        Err(err) => {
+            eprintln!("{}: {}", name, err);
+            process::exit(2);
+        },
+    }
+}
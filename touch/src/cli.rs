@@ -84,13 +84,17 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
         )
         .arg(
             Arg::with_name("date")
-                .help(
-                    "Parse STRING (date format [Y-M-D h:m:s]) and use it instead of current time.",
+                .help("Parse STRING (a free-form date) and use it instead of current time.")
+                .long_help(
+                    "Parse STRING (a free-form date) and use it instead of current time.\n\nThe \
+                     STRING may be a date and time like '2020-01-02 03:04:05', a relative date \
+                     like '2 hours ago' or '@SECONDS' since the Epoch. With -r the relative dates \
+                     are relative to the times of OTHER_FILE.",
                 )
                 .long("date")
                 .short("d")
-                .conflicts_with("reference")
-                .value_name("STRING"),
+                .value_name("STRING")
+                .allow_hyphen_values(true),
         )
         .arg(
             Arg::with_name("timestamp")
@@ -1,12 +1,20 @@
 use std::{
     fs::{self, File, Metadata},
-    process,
+    io, process,
     time::SystemTime,
 };
 
 use clap::ArgMatches;
-use filetime::{set_file_atime, set_file_mtime, set_file_times, set_symlink_file_times, FileTime};
-use time::PrimitiveDateTime;
+use coreutils_core::datetime::{
+    self,
+    calendar::{days_in_month, Fields},
+    Timestamp, Zone,
+};
+#[cfg(unix)]
+use coreutils_core::os::{time::set_file_times, Time, TimeSpec};
+use filetime::FileTime;
+#[cfg(not(unix))]
+use filetime::{set_file_times, set_symlink_file_times};
 
 mod cli;
 // TODO: add Unit tests for touch
@@ -95,15 +103,7 @@ impl<'a> TouchFlags<'a> {
 
 /// Returns the correct `(atime, mtime)` acording to the `flags`.
 fn new_filetimes(flags: TouchFlags) -> Result<(FileTime, FileTime), String> {
-    if let Some(flags_date) = flags.date {
-        let date = match PrimitiveDateTime::parse(&flags_date, "%Y-%m-%d %H:%M:%S") {
-            Ok(dt) => dt.assume_utc(),
-            Err(err) => return Err(format!("Problem parsing date arguments: {}", err)),
-        };
-        let time = FileTime::from_unix_time(date.timestamp(), date.microsecond());
-
-        Ok((time, time))
-    } else if let Some(flags_reference) = flags.reference_path {
+    if let Some(flags_reference) = flags.reference_path {
         let file_meta = match fs::metadata(flags_reference) {
             Ok(m) => m,
             Err(err) => {
@@ -113,37 +113,22 @@ fn new_filetimes(flags: TouchFlags) -> Result<(FileTime, FileTime), String> {
                 ));
             },
         };
+        let atime = FileTime::from_last_access_time(&file_meta);
+        let mtime = FileTime::from_last_modification_time(&file_meta);
 
-        Ok((
-            FileTime::from_last_access_time(&file_meta),
-            FileTime::from_last_modification_time(&file_meta),
-        ))
+        // The relative dates, like "-2 hours", are relative to the times of OTHER_FILE
+        match flags.date {
+            Some(flags_date) => {
+                Ok((date_filetime(flags_date, atime)?, date_filetime(flags_date, mtime)?))
+            },
+            None => Ok((atime, mtime)),
+        }
+    } else if let Some(flags_date) = flags.date {
+        let time = date_filetime(flags_date, FileTime::from_system_time(SystemTime::now()))?;
+
+        Ok((time, time))
     } else if let Some(flags_timestamp) = flags.timestamp {
-        // PrimitiveDateTime::parse doesn't handle missing %C and %C%y in format,
-        // thus, based on the length of the input, the input string will be
-        // completed up to the "%C%y%m%d%H%M.%S" format
-        let current_date: PrimitiveDateTime = SystemTime::now().into();
-        let input = flags_timestamp.trim_start();
-        let input = match input.len() {
-            // CCYYMMDDhhmm.ss format, nothing to add
-            15 => input.to_owned(),
-            // YYMMDDhhmm.ss format, add century
-            13 => format!("{}{}", current_date.format("%C"), input),
-            // CCYYMMDDhhmm format, add .00 for seconds
-            12 => format!("{}.00", input),
-            // MMDDhhmm.ss format, add year
-            11 => format!("{}{}", current_date.format("%Y"), input),
-            // YYMMDDhhmm format, add century and .00 for seconds
-            10 => format!("{}{}.00", current_date.format("%C"), input),
-            // YYMMDDhhmm format, add year and .00 for seconds
-            8 => format!("{}{}.00", current_date.format("%Y"), input),
-            _ => return Err(format!("Unhandled timestamp format '{}'", input)),
-        };
-        let date = match PrimitiveDateTime::parse(&input, "%Y%m%d%H%M.%S") {
-            Ok(dt) => dt.assume_utc(),
-            Err(err) => return Err(format!("Problem parsing timestamp argument: {}", err)),
-        };
-        let time = FileTime::from_unix_time(date.timestamp(), date.microsecond());
+        let time = posix_filetime(flags_timestamp, FileTime::from_system_time(SystemTime::now()))?;
 
         Ok((time, time))
     } else {
@@ -153,52 +138,110 @@ fn new_filetimes(flags: TouchFlags) -> Result<(FileTime, FileTime), String> {
     }
 }
 
+/// Parse the free-form `date` of `--date` relative to `base`, in the local time.
+fn date_filetime(date: &str, base: FileTime) -> Result<FileTime, String> {
+    let base = Timestamp { seconds: base.unix_seconds(), nanos: base.nanoseconds() };
+    let time = datetime::parse_date(date, base, Zone::Local, false)
+        .map_err(|_| format!("invalid date format '{}'", date))?;
+
+    Ok(FileTime::from_unix_time(time.seconds, time.nanos))
+}
+
+/// Parse the `timestamp` of `-t`, in the POSIX format `[[CC]YY]MMDDhhmm[.ss]` and the
+/// local time, where the missing year is the one of `now`.
+fn posix_filetime(timestamp: &str, now: FileTime) -> Result<FileTime, String> {
+    let invalid = || format!("invalid date format '{}'", timestamp);
+
+    let input = timestamp.trim_start();
+    let (digits, second) = match input.find('.') {
+        Some(index) => match &input[index + 1..] {
+            sec if sec.len() == 2 => (&input[..index], sec),
+            _ => return Err(invalid()),
+        },
+        None => (input, "00"),
+    };
+    if !digits.bytes().chain(second.bytes()).all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    // Every field has 2 digits
+    let field = |index: usize| digits[index..index + 2].parse::<i64>().unwrap_or_default();
+    let (year, rest) = match digits.len() {
+        8 => {
+            let local = now.unix_seconds() + Zone::Local.offset_at(now.unix_seconds());
+            (Fields::from_seconds(local).year, 0)
+        },
+        // The years 69 to 99 are of the 20th century, like POSIX says
+        10 if field(0) >= 69 => (1900 + field(0), 2),
+        10 => (2000 + field(0), 2),
+        12 => (field(0) * 100 + field(2), 4),
+        _ => return Err(invalid()),
+    };
+
+    let fields = Fields {
+        year,
+        month: field(rest),
+        day: field(rest + 2),
+        hour: field(rest + 4),
+        minute: field(rest + 6),
+        second: second.parse().unwrap_or_default(),
+    };
+    // The 60 seconds is a leap second, taken as the next minute
+    if !(1..=12).contains(&fields.month)
+        || !(1..=days_in_month(fields.year, fields.month)).contains(&fields.day)
+        || fields.hour > 23
+        || fields.minute > 59
+        || fields.second > 60
+    {
+        return Err(invalid());
+    }
+
+    Ok(FileTime::from_unix_time(Zone::Local.local_to_utc(fields.to_seconds()), 0))
+}
+
 /// Update the times of the `path` acording with the `flags`.
 fn update_time(
     path: &str, new_atime: FileTime, new_mtime: FileTime, meta: &Metadata, flags: TouchFlags,
 ) {
-    match (flags.access_time, flags.mod_time) {
-        (true, false) => update_access_time(&path, new_atime, meta, flags.no_deref),
-        (false, true) => update_modification_time(&path, new_mtime, meta, flags.no_deref),
-        (true, true) => update_both_time(&path, new_atime, new_mtime, flags.no_deref),
-
-        // Unreachable because when creating `TouchFlags` if both are false, we change both to true
-        // since de default behaviour is to change both. So (false, false) will never happen, and if
-        // happen, it's a bug.
-        _ => unreachable!(),
+    let atime = if flags.access_time { Some(new_atime) } else { None };
+    let mtime = if flags.mod_time { Some(new_mtime) } else { None };
+
+    if let Err(err) = set_times(path, atime, mtime, meta, flags.no_deref) {
+        let which = match (atime, mtime) {
+            (Some(_), None) => "access time",
+            (None, Some(_)) => "modification time",
+            _ => "time",
+        };
+        eprintln!("touch: Failed to update {} {}: {}", path, which, err);
     }
 }
 
-fn update_access_time(path: &str, new_atime: FileTime, meta: &Metadata, no_deref: bool) {
-    if no_deref {
-        let mtime = FileTime::from_last_modification_time(meta);
-
-        if let Err(err) = set_symlink_file_times(&path, new_atime, mtime) {
-            eprintln!("touch: Failed to update {} access time: {}", &path, err);
-        }
-    } else if let Err(err) = set_file_atime(&path, new_atime) {
-        eprintln!("touch: Failed to update {} access time: {}", &path, err);
-    }
+/// Set the times of `path` that are not [`None`] in a single call, leaving the others
+/// unchanged, and the ones of the symbolic link itself if `no_deref`.
+#[cfg(unix)]
+fn set_times(
+    path: &str, atime: Option<FileTime>, mtime: Option<FileTime>, _meta: &Metadata, no_deref: bool,
+) -> io::Result<()> {
+    let to_timespec = |time: FileTime| TimeSpec {
+        tv_sec: time.unix_seconds() as Time,
+        tv_nsec: time.nanoseconds() as _,
+    };
+
+    set_file_times(path, atime.map(to_timespec), mtime.map(to_timespec), no_deref)
 }
 
-fn update_modification_time(path: &str, new_mtime: FileTime, meta: &Metadata, no_deref: bool) {
-    if no_deref {
-        let atime = FileTime::from_last_access_time(meta);
+/// Set the times of `path` that are not [`None`], the others set as they are in `meta`,
+/// and the ones of the symbolic link itself if `no_deref`.
+#[cfg(not(unix))]
+fn set_times(
+    path: &str, atime: Option<FileTime>, mtime: Option<FileTime>, meta: &Metadata, no_deref: bool,
+) -> io::Result<()> {
+    let atime = atime.unwrap_or_else(|| FileTime::from_last_access_time(meta));
+    let mtime = mtime.unwrap_or_else(|| FileTime::from_last_modification_time(meta));
 
-        if let Err(err) = set_symlink_file_times(&path, atime, new_mtime) {
-            eprintln!("touch: Failed to update {} modification time: {}", &path, err);
-        }
-    } else if let Err(err) = set_file_mtime(&path, new_mtime) {
-        eprintln!("touch: Failed to update {} modification time: {}", &path, err);
-    }
-}
-
-fn update_both_time(path: &str, new_atime: FileTime, new_mtime: FileTime, no_deref: bool) {
     if no_deref {
-        if let Err(err) = set_symlink_file_times(&path, new_atime, new_mtime) {
-            eprintln!("touch: Failed to update {} time: {}", &path, err);
-        }
-    } else if let Err(err) = set_file_times(&path, new_atime, new_mtime) {
-        eprintln!("touch: Failed to update {} time: {}", &path, err);
+        set_symlink_file_times(path, atime, mtime)
+    } else {
+        set_file_times(path, atime, mtime)
     }
 }
@@ -1,5 +1,5 @@
 use std::{
-    fs::{metadata, remove_file},
+    fs::{metadata, remove_file, symlink_metadata},
     io,
 };
 
@@ -115,15 +115,8 @@ fn touch_update_time_with_date() {
         let file1_metadata = metadata(curr_file).unwrap();
         let file1_mtime = FileTime::from_last_modification_time(&file1_metadata);
 
-        let time = time::OffsetDateTime::from_unix_timestamp(file1_mtime.unix_seconds());
-
         // check modification and access time is equal 2009-01-03 03:13:00
-        assert_eq!(
-            time,
-            PrimitiveDateTime::parse("2009-01-03 03:13:00", "%Y-%m-%d %H:%M:%S")
-                .unwrap()
-                .assume_utc()
-        );
+        assert_eq!(file1_mtime.unix_seconds(), local_seconds(2009, 1, 3, 3, 13, 0));
     }
     remove_test_files(&files).unwrap();
 }
@@ -150,19 +143,128 @@ fn touch_update_time_with_timestamp() {
         let file1_metadata = metadata(curr_file).unwrap();
         let file1_mtime = FileTime::from_last_modification_time(&file1_metadata);
 
-        let time = time::OffsetDateTime::from_unix_timestamp(file1_mtime.unix_seconds());
-
         // check modification and access time is equal 2009-01-03 03:13:00
-        assert_eq!(
-            time,
-            PrimitiveDateTime::parse("2009-01-03 03:13:00", "%Y-%m-%d %H:%M:%S")
-                .unwrap()
-                .assume_utc()
-        );
+        assert_eq!(file1_mtime.unix_seconds(), local_seconds(2009, 1, 3, 3, 13, 0));
     }
     remove_test_files(&files).unwrap();
 }
 
+#[test]
+fn touch_update_time_with_reference_and_date() {
+    let matches = cli::create_app().get_matches_from(vec![
+        "touch",
+        "-r",
+        "file13.rs",
+        "--date=-2 hours",
+        "file14.rs",
+    ]);
+
+    let flags = TouchFlags::from_matches(&matches);
+    let files = vec!["file13.rs", "file14.rs"];
+
+    File::create(files[0]).unwrap();
+    let reference = FileTime::from_unix_time(1_600_000_000, 123_456_789);
+    filetime::set_file_times(files[0], reference, reference).unwrap();
+
+    touch(&files[1..], flags);
+
+    let file_metadata = metadata(files[1]).unwrap();
+    let expected = FileTime::from_unix_time(1_600_000_000 - 7200, 123_456_789);
+    assert_eq!(FileTime::from_last_modification_time(&file_metadata), expected);
+    assert_eq!(FileTime::from_last_access_time(&file_metadata), expected);
+
+    remove_test_files(&files).unwrap();
+}
+
+#[test]
+fn touch_update_only_access_time_keeps_modification_time() {
+    let matches = cli::create_app().get_matches_from(vec!["touch", "-a", "file15.rs"]);
+    let flags = TouchFlags::from_matches(&matches);
+    let files = vec!["file15.rs"];
+
+    File::create(files[0]).unwrap();
+    let old = FileTime::from_unix_time(1_600_000_000, 123_456_789);
+    filetime::set_file_times(files[0], old, old).unwrap();
+
+    touch(&files, flags);
+
+    let file_metadata = metadata(files[0]).unwrap();
+    assert_ne!(FileTime::from_last_access_time(&file_metadata), old);
+    assert_eq!(FileTime::from_last_modification_time(&file_metadata), old);
+
+    remove_test_files(&files).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn touch_no_deref_symlink() {
+    let matches = cli::create_app().get_matches_from(vec![
+        "touch",
+        "-h",
+        "-d",
+        "@1600000000",
+        "file16.rs",
+        "file17.rs",
+    ]);
+    let flags = TouchFlags::from_matches(&matches);
+    let files = vec!["file16.rs", "file17.rs"];
+
+    File::create(files[0]).unwrap();
+    std::os::unix::fs::symlink(files[0], files[1]).unwrap();
+    let target_mtime = FileTime::from_last_modification_time(&metadata(files[0]).unwrap());
+
+    touch(&files[1..], flags);
+
+    let link_metadata = symlink_metadata(files[1]).unwrap();
+    let expected = FileTime::from_unix_time(1_600_000_000, 0);
+    assert_eq!(FileTime::from_last_modification_time(&link_metadata), expected);
+    assert_eq!(FileTime::from_last_modification_time(&metadata(files[0]).unwrap()), target_mtime);
+
+    remove_test_files(&files).unwrap();
+}
+
+#[test]
+fn touch_no_create() {
+    let matches = cli::create_app().get_matches_from(vec!["touch", "-c", "file18.rs"]);
+    let flags = TouchFlags::from_matches(&matches);
+
+    touch(&["file18.rs"], flags);
+
+    assert!(metadata("file18.rs").is_err());
+}
+
+#[test]
+fn touch_date_nanoseconds() {
+    let time = date_filetime("@1600000000.5", FileTime::zero()).unwrap();
+    assert_eq!(time, FileTime::from_unix_time(1_600_000_000, 500_000_000));
+
+    assert!(date_filetime("not a date", FileTime::zero()).is_err());
+}
+
+#[test]
+fn touch_posix_timestamp() {
+    let now = FileTime::from_unix_time(local_seconds(2020, 6, 1, 0, 0, 0), 0);
+    let times = [
+        ("06021530", local_seconds(2020, 6, 2, 15, 30, 0)),
+        ("06021530.45", local_seconds(2020, 6, 2, 15, 30, 45)),
+        ("6906021530", local_seconds(1969, 6, 2, 15, 30, 0)),
+        ("6806021530", local_seconds(2068, 6, 2, 15, 30, 0)),
+        ("199906021530.05", local_seconds(1999, 6, 2, 15, 30, 5)),
+    ];
+    for (timestamp, seconds) in &times {
+        assert_eq!(posix_filetime(timestamp, now).unwrap().unix_seconds(), *seconds);
+    }
+
+    for timestamp in &["0602153", "06021530.5", "13021530", "02301530", "06022530", "0602x530"] {
+        assert!(posix_filetime(timestamp, now).is_err());
+    }
+}
+
+/// The seconds since the Epoch of a local date and time.
+fn local_seconds(year: i64, month: i64, day: i64, hour: i64, minute: i64, second: i64) -> i64 {
+    Zone::Local.local_to_utc(Fields { year, month, day, hour, minute, second }.to_seconds())
+}
+
 fn remove_test_files(files: &[&str]) -> io::Result<()> {
     for filename in files {
         remove_file(&filename)?;
@@ -0,0 +1,78 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("SET")
+                .help(
+                    "The set of the characters to translate, delete or squeeze, and the one to \
+                     translate them to.",
+                )
+                .multiple(true)
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("complement")
+                .help("Use the complement of SET1.")
+                .long("complement")
+                .short("c"),
+        )
+        .arg(
+            Arg::with_name("complementChars")
+                .help("Use the complement of SET1, like -c.")
+                .short("C"),
+        )
+        .arg(
+            Arg::with_name("delete")
+                .help("Delete the characters of SET1, instead of translating them.")
+                .long("delete")
+                .short("d"),
+        )
+        .arg(
+            Arg::with_name("squeeze")
+                .help(
+                    "Replace each sequence of a repeated character of the last SET with a single \
+                     occurrence of that character.",
+                )
+                .long_help(
+                    "Replace each sequence of a repeated character of the last SET with a single \
+                     occurrence of that character.\n\nThe characters are squeezed after they are \
+                     translated or deleted.",
+                )
+                .long("squeeze-repeats")
+                .short("s"),
+        )
+        .arg(
+            Arg::with_name("truncate")
+                .help("Truncate SET1 to the length of SET2, instead of extending SET2.")
+                .long_help(
+                    "Truncate SET1 to the length of SET2, instead of extending SET2.\n\nBy \
+                     default, SET2 is extended to the length of SET1 by repeating its last \
+                     character.",
+                )
+                .long("truncate-set1")
+                .short("t"),
+        )
+        .arg(
+            Arg::with_name("utf8")
+                .help("Read the SETs and standard input as UTF-8 characters, instead of bytes.")
+                .long_help(
+                    "Read the SETs and standard input as UTF-8 characters, instead of \
+                     bytes.\n\nThe bytes of the input that are not valid UTF-8 are written \
+                     unchanged, and are neither in a SET nor in its complement. The classes are \
+                     the Unicode ones, and the equivalence classes have the accented Latin \
+                     letters with the letter they are based on.",
+                )
+                .long("utf8")
+                .short("U"),
+        )
+}
@@ -0,0 +1,524 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, stdin, stdout, BufWriter, ErrorKind, Read, Write},
+    process,
+};
+
+use clap::{ArgMatches, Values};
+
+mod cli;
+mod set;
+
+use set::{Class, Element, Encoding, Members, Set};
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+    let options = Options::from_matches(&matches);
+
+    let sets: Vec<_> = matches.values_of("SET").map_or_else(Vec::new, Values::collect);
+    // Like GNU, SET2 is needed to translate and to both delete and squeeze, and only
+    // SET1 is allowed to only delete
+    let min = if options.delete == options.squeeze { 2 } else { 1 };
+    let max = if options.delete && !options.squeeze { 1 } else { 2 };
+    if sets.is_empty() {
+        fail("missing operand");
+    } else if sets.len() < min {
+        eprintln!("tr: missing operand after '{}'", sets[sets.len() - 1]);
+        if options.squeeze {
+            fail_with("Two strings must be given when both deleting and squeezing repeats.");
+        }
+        fail_with("Two strings must be given when translating.");
+    } else if sets.len() > max {
+        eprintln!("tr: extra operand '{}'", sets[max]);
+        if sets.len() == 2 {
+            fail_with("Only one string may be given when deleting without squeezing repeats.");
+        }
+        process::exit(1);
+    }
+
+    let parse = |set: &str| Set::parse(set, options.encoding).unwrap_or_else(|err| fail(&err));
+    let set1 = parse(sets[0]);
+    let set2 = sets.get(1).map(|set| parse(set));
+    let tr = Tr::new(&options, set1, set2).unwrap_or_else(|err| fail(&err));
+
+    let stdin = stdin();
+    let stdout = stdout();
+    let mut output = BufWriter::new(stdout.lock());
+    match tr.filter(&mut stdin.lock(), &mut output) {
+        Ok(()) => {},
+        Err(Error::Read(err)) => fail(&format!("read error: {}", err)),
+        Err(Error::Write(err)) => fail(&format!("write error: {}", err)),
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("tr: {}", message);
+    process::exit(1);
+}
+
+/// Print the `message` of the usage error, without the name of the program.
+fn fail_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+#[derive(Debug)]
+enum Error {
+    Read(io::Error),
+    Write(io::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Options {
+    complement: bool,
+    delete: bool,
+    squeeze: bool,
+    truncate: bool,
+    encoding: Encoding,
+}
+
+impl Options {
+    fn from_matches(matches: &ArgMatches) -> Self {
+        Options {
+            complement: matches.is_present("complement") || matches.is_present("complementChars"),
+            delete: matches.is_present("delete"),
+            squeeze: matches.is_present("squeeze"),
+            truncate: matches.is_present("truncate"),
+            encoding: if matches.is_present("utf8") { Encoding::Utf8 } else { Encoding::Bytes },
+        }
+    }
+}
+
+/// The filter of the characters of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tr {
+    encoding: Encoding,
+    /// The character written for each translated character.
+    table: HashMap<u32, u32>,
+    /// The characters deleted.
+    delete: Members,
+    /// The characters written that are squeezed.
+    squeeze: Members,
+}
+
+impl Tr {
+    /// Create the filter of the `options` and of the sets, checking them like GNU.
+    ///
+    /// # Errors
+    /// If the sets can't be used together, an error message is returned.
+    fn new(options: &Options, set1: Set, mut set2: Option<Set>) -> Result<Self, String> {
+        let translating = set2.is_some() && !options.delete;
+
+        let mut stats1 = set1.stats()?;
+        if options.complement {
+            let chars: HashSet<_> = set1.chars().collect();
+            stats1.len = options.encoding.len() - chars.len() as u64;
+        }
+        if !stats1.indefinite.is_empty() {
+            return Err("the [c*] repeat construct may not appear in string1".to_string());
+        }
+
+        if let Some(set2) = &mut set2 {
+            let mut stats2 = set2.stats()?;
+            // The repeat of SET2 fills it up to the length of SET1
+            if let [index] = stats2.indefinite[..] {
+                if stats1.len >= stats2.len {
+                    if let Element::Repeat(c, _) = set2.elements[index] {
+                        set2.elements[index] = Element::Repeat(c, stats1.len - stats2.len);
+                    }
+                    stats2.len = stats1.len;
+                }
+            }
+            if stats2.indefinite.len() > 1 {
+                return Err("only one [c*] repeat construct may appear in string2".to_string());
+            }
+
+            if translating {
+                if stats2.has_equiv {
+                    return Err(
+                        "[=c=] expressions may not appear in string2 when translating".to_string()
+                    );
+                }
+                if stats2.has_restricted_class {
+                    return Err("when translating, the only character classes that may appear \
+                                in\nstring2 are 'upper' and 'lower'"
+                        .to_string());
+                }
+                if stats2.has_class {
+                    check_cases(&set1, set2, &mut stats1.len, &mut stats2.len)?;
+                }
+
+                if stats1.len > stats2.len && !options.truncate {
+                    let c = match set2.elements.last() {
+                        None => {
+                            return Err(
+                                "when not truncating set1, string2 must be non-empty".to_string()
+                            );
+                        },
+                        Some(Element::Class(_)) | Some(Element::Equiv(_)) => {
+                            return Err("when translating with string1 longer than string2,\nthe \
+                                        latter string must not end with a character class"
+                                .to_string());
+                        },
+                        Some(&Element::Char(c))
+                        | Some(&Element::Range(_, c))
+                        | Some(&Element::Repeat(c, _)) => c,
+                    };
+                    set2.elements.push(Element::Repeat(c, stats1.len - stats2.len));
+                    stats2.len = stats1.len;
+                }
+
+                if options.complement
+                    && stats1.has_class
+                    && !(stats2.len == stats1.len && set2.is_homogeneous())
+                {
+                    return Err("when translating with complemented character classes,\nstring2 \
+                                must map all characters in the domain to one"
+                        .to_string());
+                }
+            } else if !stats2.indefinite.is_empty() {
+                return Err(
+                    "the [c*] construct may appear in string2 only when translating".to_string()
+                );
+            }
+        }
+
+        let encoding = options.encoding;
+        let mut table = HashMap::new();
+        match &set2 {
+            Some(set2) if translating && options.complement => {
+                // The characters that are not in SET1 are translated in order
+                let members = set1.members(true);
+                let mut chars2 = set2.chars();
+                for from in encoding.chars().filter(|&c| members.contains(c)) {
+                    match chars2.next() {
+                        Some(to) => table.insert(from, to),
+                        None => break,
+                    };
+                }
+            },
+            Some(set2) if translating => {
+                let mut chars1 = set1.chars();
+                let mut chars2 = set2.chars();
+                loop {
+                    let (next1, next2) = (chars1.next_cased(), chars2.next_cased());
+                    match (next1, next2) {
+                        (Some((_, Some(from))), Some((_, Some(to)))) if from != to => {
+                            table.extend(case_mappings(from, encoding));
+                        },
+                        (Some((from, _)), Some((to, _))) => {
+                            table.insert(from, to);
+                        },
+                        _ => break,
+                    }
+
+                    // The classes of the cases are translated at once
+                    if let Some((_, Some(_))) = next2 {
+                        chars1.skip_element();
+                        chars2.skip_element();
+                    }
+                }
+            },
+            _ => {},
+        }
+
+        let delete =
+            if options.delete { set1.members(options.complement) } else { Members::none() };
+        let squeeze = match &set2 {
+            _ if !options.squeeze => Members::none(),
+            Some(set2) => set2.members(false),
+            None => set1.members(options.complement),
+        };
+
+        Ok(Tr { encoding, table, delete, squeeze })
+    }
+
+    /// Write the `input` to the `output`, with its characters deleted, translated, and
+    /// then squeezed.
+    fn filter<R: Read, W: Write>(&self, input: &mut R, output: &mut W) -> Result<(), Error> {
+        match self.encoding {
+            Encoding::Bytes => self.filter_bytes(input, output),
+            Encoding::Utf8 => self.filter_utf8(input, output),
+        }
+    }
+
+    /// Filter the bytes of the `input`.
+    fn filter_bytes<R: Read, W: Write>(&self, input: &mut R, output: &mut W) -> Result<(), Error> {
+        let mut table = [0; 256];
+        let mut delete = [false; 256];
+        let mut squeeze = [false; 256];
+        for c in 0..=255 {
+            let i = usize::from(c);
+            table[i] = self.table.get(&u32::from(c)).map_or(c, |&to| to as u8);
+            delete[i] = self.delete.contains(u32::from(c));
+            squeeze[i] = self.squeeze.contains(u32::from(c));
+        }
+
+        let mut buffer = vec![0; 8192];
+        let mut last = None;
+
+        loop {
+            let read = match input.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(Error::Read(err)),
+            };
+
+            // The bytes are filtered in place, since they are never longer
+            let mut written = 0;
+            for i in 0..read {
+                let byte = buffer[i];
+                if delete[usize::from(byte)] {
+                    continue;
+                }
+
+                let byte = table[usize::from(byte)];
+                if squeeze[usize::from(byte)] && last == Some(byte) {
+                    continue;
+                }
+                last = Some(byte);
+                buffer[written] = byte;
+                written += 1;
+            }
+
+            output.write_all(&buffer[..written]).map_err(Error::Write)?;
+        }
+
+        output.flush().map_err(Error::Write)
+    }
+
+    /// Filter the UTF-8 characters of the `input`, where the bytes that are not valid are
+    /// written unchanged.
+    fn filter_utf8<R: Read, W: Write>(&self, input: &mut R, output: &mut W) -> Result<(), Error> {
+        let mut buffer = vec![0; 8192];
+        let mut filtered = Vec::with_capacity(buffer.len());
+        // The count of the bytes of an incomplete character kept at the start of the buffer
+        let mut kept = 0;
+        let mut last = None;
+
+        loop {
+            let read = match input.read(&mut buffer[kept..]) {
+                Ok(read) => read,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(Error::Read(err)),
+            };
+            let end = kept + read;
+            if end == 0 {
+                break;
+            }
+
+            let mut i = 0;
+            while i < end {
+                let len = set::utf8_len(buffer[i]);
+                // The last character may continue in the next read
+                if len > 0 && i + len > end && read > 0 {
+                    break;
+                }
+
+                let c = buffer[..end]
+                    .get(i..i + len)
+                    .filter(|_| len > 0)
+                    .and_then(|bytes| std::str::from_utf8(bytes).ok())
+                    .and_then(|text| text.chars().next());
+                let c = match c {
+                    Some(c) => c,
+                    None => {
+                        filtered.push(buffer[i]);
+                        last = None;
+                        i += 1;
+                        continue;
+                    },
+                };
+                i += len;
+
+                let c = u32::from(c);
+                if self.delete.contains(c) {
+                    continue;
+                }
+
+                let c = self.table.get(&c).copied().unwrap_or(c);
+                if self.squeeze.contains(c) && last == Some(c) {
+                    continue;
+                }
+                last = Some(c);
+                let c = std::char::from_u32(c).unwrap_or_default();
+                filtered.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+            }
+
+            output.write_all(&filtered).map_err(Error::Write)?;
+            filtered.clear();
+            buffer.copy_within(i..end, 0);
+            kept = end - i;
+            if read == 0 && kept == 0 {
+                break;
+            }
+        }
+
+        output.flush().map_err(Error::Write)
+    }
+}
+
+/// The characters of the class `from` of the `encoding`, with the ones of the other case
+/// they are translated to.
+fn case_mappings(from: Class, encoding: Encoding) -> impl Iterator<Item = (u32, u32)> {
+    encoding.chars().filter(move |&c| from.contains(c, encoding)).filter_map(move |c| {
+        let c = std::char::from_u32(c)?;
+        let mut mapped = if from == Class::Lower {
+            c.to_uppercase().collect::<Vec<_>>()
+        } else {
+            c.to_lowercase().collect::<Vec<_>>()
+        };
+        match mapped.pop() {
+            // Like glibc, only the characters with a single one of the other case are
+            // translated, and only to ones of the encoding
+            Some(to) if mapped.is_empty() && (encoding == Encoding::Utf8 || to.is_ascii()) => {
+                Some((u32::from(c), u32::from(to)))
+            },
+            _ => None,
+        }
+    })
+}
+
+/// Check that the classes of the cases of `set2` are in the same positions as ones of
+/// `set1`, updating the lengths `len1` and `len2` of the sets, like GNU.
+fn check_cases(set1: &Set, set2: &Set, len1: &mut u64, len2: &mut u64) -> Result<(), String> {
+    let mut chars1 = set1.chars();
+    let mut chars2 = set2.chars();
+    let mut starts1 = true;
+
+    loop {
+        let (next1, next2) = (chars1.next_cased(), chars2.next_cased());
+        match (next1.and_then(|(_, case)| case), next2.and_then(|(_, case)| case)) {
+            (_, None) => {},
+            (Some(case1), Some(case2)) if starts1 => {
+                chars1.skip_element();
+                chars2.skip_element();
+                // Like GNU, a class counts as a single character
+                *len1 -= case1.len(set1.encoding) - 1;
+                *len2 -= case2.len(set2.encoding) - 1;
+            },
+            _ => return Err("misaligned [:upper:] and/or [:lower:] construct".to_string()),
+        }
+
+        if next1.is_none() || next2.is_none() {
+            return Ok(());
+        }
+        starts1 = chars1.at_element_start();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tr(args: &[&str], input: &str) -> Result<String, String> {
+        let matches = cli::create_app().get_matches_from(["tr"].iter().chain(args));
+        let sets: Vec<_> = matches.values_of("SET").unwrap().collect();
+        let options = Options::from_matches(&matches);
+        let set1 = Set::parse(sets[0], options.encoding)?;
+        let set2 = sets.get(1).map(|set| Set::parse(set, options.encoding)).transpose()?;
+        let tr = Tr::new(&options, set1, set2)?;
+
+        let mut output = Vec::new();
+        tr.filter(&mut input.as_bytes(), &mut output).unwrap();
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn translate() {
+        assert_eq!(tr(&["abc", "xyz"], "aabbcd").unwrap(), "xxyyzd");
+        assert_eq!(tr(&["a-z", "A-Z"], "Hello, World").unwrap(), "HELLO, WORLD");
+        assert_eq!(tr(&["abc", "x"], "abcd").unwrap(), "xxxd");
+        assert_eq!(tr(&["-t", "abc", "x"], "abcd").unwrap(), "xbcd");
+        assert_eq!(tr(&["aa", "xy"], "a").unwrap(), "y");
+        assert_eq!(tr(&["a-f", "[x*2]y[z*]"], "abcdef").unwrap(), "xxyzzz");
+        assert_eq!(tr(&["a-c", "[x*]Y"], "abc").unwrap(), "xxY");
+        assert_eq!(tr(&["-c", "a", "x"], "abc\n").unwrap(), "axxx");
+        assert_eq!(tr(&["-c", "\\000-\\377", "x"], "abc").unwrap(), "abc");
+        assert_eq!(tr(&["-C", "[:alpha:]", "[_*]"], "a b!").unwrap(), "a_b_");
+    }
+
+    #[test]
+    fn cases() {
+        assert_eq!(tr(&["[:lower:]", "[:upper:]"], "Hello").unwrap(), "HELLO");
+        assert_eq!(tr(&["[:upper:]", "[:lower:]"], "Hello").unwrap(), "hello");
+        assert_eq!(tr(&["x[:lower:]", "y[:upper:]"], "xab").unwrap(), "XAB");
+        assert_eq!(tr(&["[:lower:]", "[:upper:]x"], "az").unwrap(), "AZ");
+        assert_eq!(tr(&["[:upper:]", "ab"], "ABC").unwrap(), "abb");
+        assert_eq!(tr(&["[:digit:]", "[x*]"], "a1b2").unwrap(), "axbx");
+    }
+
+    #[test]
+    fn delete_and_squeeze() {
+        assert_eq!(tr(&["-d", "a-c"], "abcdcba").unwrap(), "d");
+        assert_eq!(tr(&["-cd", "[:digit:]"], "a1b2\n").unwrap(), "12");
+        assert_eq!(tr(&["-s", "a-c"], "aabbccdd").unwrap(), "abcdd");
+        assert_eq!(tr(&["-cs", "a"], "aabbcc").unwrap(), "aabc");
+        assert_eq!(tr(&["-s", "ab", "x"], "aabbcc").unwrap(), "xcc");
+        assert_eq!(tr(&["-ds", "a", "b"], "abba bb").unwrap(), "b b");
+        assert_eq!(tr(&["-s", " "], "a  b   c").unwrap(), "a b c");
+    }
+
+    #[test]
+    fn utf8() {
+        assert_eq!(tr(&["-U", "а-я", "А-Я"], "привет, мир").unwrap(), "ПРИВЕТ, МИР");
+        assert_eq!(tr(&["-U", "[:lower:]", "[:upper:]"], "straße é").unwrap(), "STRAßE É");
+        assert_eq!(tr(&["-U", "[=e=]", "e"], "élève").unwrap(), "eleve");
+        assert_eq!(tr(&["-U", "-d", "[:alpha:]"], "ж1é2").unwrap(), "12");
+        assert_eq!(tr(&["-U", "-s", "ё"], "ёёё\n").unwrap(), "ё\n");
+        assert_eq!(tr(&["-U", "-c", "а-я", "x"], "яz€").unwrap(), "яxx");
+        assert_eq!(tr(&["[=a=]", "b"], "aá").unwrap(), "bá");
+
+        let tr = |args: &[&str], input: &[u8]| {
+            let matches = cli::create_app().get_matches_from(["tr", "-U"].iter().chain(args));
+            let options = Options::from_matches(&matches);
+            let set = Set::parse(matches.value_of("SET").unwrap(), options.encoding).unwrap();
+            let mut output = Vec::new();
+            Tr::new(&options, set, None).unwrap().filter(&mut &input[..], &mut output).unwrap();
+            output
+        };
+        assert_eq!(tr(&["-d", "é"], b"a\xc3\xa9\xc3b\xff\xc3"), b"a\xc3b\xff\xc3");
+        assert_eq!(tr(&["-cd", "a"], b"ab\xffa"), b"a\xffa");
+
+        let mut input = "é".repeat(5000).into_bytes();
+        input.push(b'x');
+        assert_eq!(tr(&["-d", "x"], &input), "é".repeat(5000).as_bytes());
+    }
+
+    #[test]
+    fn errors() {
+        let error = |args: &[&str]| tr(args, "").unwrap_err();
+        assert_eq!(error(&["[a*]", "b"]), "the [c*] repeat construct may not appear in string1");
+        assert_eq!(
+            error(&["a", "[b*][c*]"]),
+            "only one [c*] repeat construct may appear in string2"
+        );
+        assert_eq!(
+            error(&["-ds", "a", "[b*]"]),
+            "the [c*] construct may appear in string2 only when translating"
+        );
+        assert_eq!(error(&["a-z", ""]), "when not truncating set1, string2 must be non-empty");
+        assert_eq!(error(&["a-z", "[:upper:]"]), "misaligned [:upper:] and/or [:lower:] construct");
+        assert_eq!(error(&["a", "x[:upper:]"]), "misaligned [:upper:] and/or [:lower:] construct");
+        assert_eq!(
+            error(&["a", "[=b=]"]),
+            "[=c=] expressions may not appear in string2 when translating"
+        );
+        assert_eq!(
+            error(&["a", "[:digit:]"]),
+            "when translating, the only character classes that may appear in\nstring2 are 'upper' \
+             and 'lower'"
+        );
+        assert_eq!(
+            error(&["[:upper:][:digit:]", "[:lower:]"]),
+            "when translating with string1 longer than string2,\nthe latter string must not end \
+             with a character class"
+        );
+        assert_eq!(
+            error(&["-c", "[:upper:]", "ab"]),
+            "when translating with complemented character classes,\nstring2 must map all \
+             characters in the domain to one"
+        );
+    }
+}
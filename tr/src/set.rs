@@ -0,0 +1,812 @@
+//! The sets of characters of the operands, with their escapes, ranges, classes,
+//! equivalence classes and repeats.
+use std::collections::HashSet;
+
+/// How the characters of the sets and of the input are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    /// Each byte is a character of the "C" locale.
+    Bytes,
+    /// The characters are the Unicode scalar values encoded in UTF-8.
+    Utf8,
+}
+
+impl Encoding {
+    /// The count of all the characters.
+    pub(crate) fn len(self) -> u64 {
+        match self {
+            Encoding::Bytes => 256,
+            Encoding::Utf8 => 0x11_0000 - 0x800,
+        }
+    }
+
+    /// All the characters, in increasing order.
+    pub(crate) fn chars(self) -> impl Iterator<Item = u32> {
+        self.chars_from(0)
+    }
+
+    /// The characters from the `first`, in increasing order.
+    fn chars_from(self, first: u32) -> impl Iterator<Item = u32> {
+        let last = match self {
+            Encoding::Bytes => 0xff,
+            Encoding::Utf8 => 0x10_ffff,
+        };
+        (first..=last).filter(|&c| !is_surrogate(c))
+    }
+}
+
+/// Whether the value `c` is a surrogate, that is not a Unicode scalar value.
+fn is_surrogate(c: u32) -> bool {
+    (0xd800..=0xdfff).contains(&c)
+}
+
+/// A class of characters, given as `[:name:]`.
+///
+/// With bytes, the classes are the ones of the "C" locale, and with UTF-8 they are the
+/// Unicode ones, except for the digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Class {
+    Alnum,
+    Alpha,
+    Blank,
+    Cntrl,
+    Digit,
+    Graph,
+    Lower,
+    Print,
+    Punct,
+    Space,
+    Upper,
+    Xdigit,
+}
+
+impl Class {
+    fn from_name(name: &[u32]) -> Option<Self> {
+        let name: String = name.iter().filter_map(|&c| std::char::from_u32(c)).collect();
+        let class = match name.as_str() {
+            "alnum" => Class::Alnum,
+            "alpha" => Class::Alpha,
+            "blank" => Class::Blank,
+            "cntrl" => Class::Cntrl,
+            "digit" => Class::Digit,
+            "graph" => Class::Graph,
+            "lower" => Class::Lower,
+            "print" => Class::Print,
+            "punct" => Class::Punct,
+            "space" => Class::Space,
+            "upper" => Class::Upper,
+            "xdigit" => Class::Xdigit,
+            _ => return None,
+        };
+        Some(class)
+    }
+
+    /// Whether the character `c` of the `encoding` is in the class.
+    pub(crate) fn contains(self, c: u32, encoding: Encoding) -> bool {
+        if encoding == Encoding::Utf8 && c >= 0x80 {
+            return matches!(std::char::from_u32(c), Some(c) if self.contains_char(c));
+        }
+
+        let byte = match c {
+            0..=0x7f => c as u8,
+            _ => return false,
+        };
+        match self {
+            Class::Alnum => byte.is_ascii_alphanumeric(),
+            Class::Alpha => byte.is_ascii_alphabetic(),
+            Class::Blank => byte == b' ' || byte == b'\t',
+            Class::Cntrl => byte.is_ascii_control(),
+            Class::Digit => byte.is_ascii_digit(),
+            Class::Graph => byte.is_ascii_graphic(),
+            Class::Lower => byte.is_ascii_lowercase(),
+            Class::Print => byte.is_ascii_graphic() || byte == b' ',
+            Class::Punct => byte.is_ascii_punctuation(),
+            // Unlike `u8::is_ascii_whitespace`, with the vertical tab
+            Class::Space => matches!(byte, b' ' | b'\t'..=b'\r'),
+            Class::Upper => byte.is_ascii_uppercase(),
+            Class::Xdigit => byte.is_ascii_hexdigit(),
+        }
+    }
+
+    /// Whether the character `c`, which is not ASCII, is in the class.
+    fn contains_char(self, c: char) -> bool {
+        // Like glibc, the spaces that do not break are not blanks
+        let is_blank = || {
+            c.is_whitespace()
+                && !matches!(c, '\u{85}' | '\u{a0}' | '\u{2007}' | '\u{2028}'..='\u{202f}')
+        };
+        let is_graph = || !c.is_whitespace() && !c.is_control();
+
+        match self {
+            Class::Alnum => c.is_alphanumeric(),
+            Class::Alpha => c.is_alphabetic(),
+            Class::Blank => is_blank(),
+            Class::Cntrl => c.is_control(),
+            Class::Digit | Class::Xdigit => false,
+            Class::Graph => is_graph(),
+            Class::Lower => c.is_lowercase(),
+            Class::Print => is_graph() || is_blank(),
+            Class::Punct => is_graph() && !c.is_alphanumeric(),
+            Class::Space => c.is_whitespace(),
+            Class::Upper => c.is_uppercase(),
+        }
+    }
+
+    /// The first member of the class after `c`, or the first one if `None`.
+    fn next_member(self, c: Option<u32>, encoding: Encoding) -> Option<u32> {
+        let start = c.map_or(0, |c| c + 1);
+        encoding.chars_from(start).find(|&c| self.contains(c, encoding))
+    }
+
+    /// The count of the members of the class.
+    pub(crate) fn len(self, encoding: Encoding) -> u64 {
+        encoding.chars().filter(|&c| self.contains(c, encoding)).count() as u64
+    }
+
+    /// Whether the class is one of the cases, the only ones allowed in the second set
+    /// when translating.
+    pub(crate) fn is_case(self) -> bool {
+        self == Class::Lower || self == Class::Upper
+    }
+}
+
+/// The letters of Latin-1 and Latin Extended-A with diacritics, after the letter they
+/// are based on.
+const EQUIVALENTS: &[&str] = &[
+    "AÀÁÂÃÄÅĀĂĄ",
+    "CÇĆĈĊČ",
+    "DĎ",
+    "EÈÉÊËĒĔĖĘĚ",
+    "GĜĞĠĢ",
+    "HĤ",
+    "IÌÍÎÏĨĪĬĮİ",
+    "JĴ",
+    "KĶ",
+    "LĹĻĽ",
+    "NÑŃŅŇ",
+    "OÒÓÔÕÖŌŎŐ",
+    "RŔŖŘ",
+    "SŚŜŞŠ",
+    "TŢŤ",
+    "UÙÚÛÜŨŪŬŮŰŲ",
+    "WŴ",
+    "YÝŶŸ",
+    "ZŹŻŽ",
+    "aàáâãäåāăą",
+    "cçćĉċč",
+    "dď",
+    "eèéêëēĕėęě",
+    "gĝğġģ",
+    "hĥ",
+    "iìíîïĩīĭį",
+    "jĵ",
+    "kķ",
+    "lĺļľ",
+    "nñńņň",
+    "oòóôõöōŏő",
+    "rŕŗř",
+    "sśŝşš",
+    "tţť",
+    "uùúûüũūŭůűų",
+    "wŵ",
+    "yýÿŷ",
+    "zźżž",
+];
+
+/// The members of the equivalence class of `c` for the `encoding`, in increasing
+/// order.
+///
+/// Like GNU, with bytes each character is alone in its class, and with UTF-8 the letters
+/// with diacritics are with the letter they are based on.
+fn equivalents(c: u32, encoding: Encoding) -> Vec<u32> {
+    let class = std::char::from_u32(c)
+        .filter(|_| encoding == Encoding::Utf8)
+        .and_then(|c| EQUIVALENTS.iter().find(|class| class.contains(c)));
+
+    match class {
+        Some(class) => {
+            let mut members: Vec<_> = class.chars().map(u32::from).collect();
+            members.sort_unstable();
+            members
+        },
+        None => vec![c],
+    }
+}
+
+/// An element of a set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Element {
+    Char(u32),
+    /// The characters from the first to the last.
+    Range(u32, u32),
+    Class(Class),
+    /// The equivalence class of the character, given as `[=c=]`.
+    Equiv(u32),
+    /// The character repeated the count times, where 0 is as many times as needed when
+    /// it is given as `[c*]` or `[c*0]`.
+    Repeat(u32, u64),
+}
+
+/// The largest count of the characters of a set, and of a `[c*n]` repeat, like GNU.
+const MAX_COUNT: u64 = u64::MAX - 1;
+
+/// A set of characters, as given on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Set {
+    pub(crate) elements: Vec<Element>,
+    pub(crate) encoding: Encoding,
+}
+
+/// The facts about a set that are checked before using it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Stats {
+    /// The count of the characters of the set, with its elements in turn.
+    pub(crate) len: u64,
+    pub(crate) has_class: bool,
+    /// Whether it has a class other than the cases.
+    pub(crate) has_restricted_class: bool,
+    pub(crate) has_equiv: bool,
+    /// The indices of the `[c*]` repeats.
+    pub(crate) indefinite: Vec<usize>,
+}
+
+/// The characters of a set, or of its complement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Members {
+    chars: HashSet<u32>,
+    complement: bool,
+}
+
+impl Members {
+    /// The empty set.
+    pub(crate) fn none() -> Self {
+        Members { chars: HashSet::new(), complement: false }
+    }
+
+    pub(crate) fn contains(&self, c: u32) -> bool {
+        self.chars.contains(&c) != self.complement
+    }
+}
+
+impl Set {
+    /// Parse the `operand` of a set of the `encoding`, like GNU.
+    ///
+    /// A warning is printed for the escapes that are not portable.
+    ///
+    /// # Errors
+    /// If a class, a repeat or a range is not valid, or the escapes are not valid UTF-8
+    /// with that encoding, an error message is returned.
+    pub(crate) fn parse(operand: &str, encoding: Encoding) -> Result<Self, String> {
+        let s = match encoding {
+            Encoding::Bytes => {
+                unescape(operand).into_iter().map(|(c, e)| (u32::from(c), e)).collect()
+            },
+            Encoding::Utf8 => decode(&unescape(operand))
+                .ok_or_else(|| format!("invalid UTF-8 escapes in '{}'", operand))?,
+        };
+        let len = s.len();
+        // Whether the character `i` is the special `c`, and not escaped
+        let is = |i: usize, c: u8| i < len && s[i] == (u32::from(c), false);
+        let mut elements = Vec::new();
+
+        // The constructs are at least 3 characters long
+        let mut i = 0;
+        while i + 2 < len {
+            if is(i, b'[') {
+                if is(i + 1, b':') || is(i + 1, b'=') {
+                    let delimiter = s[i + 1].0;
+                    if let Some(close) = find_closing(&s, i + 2, delimiter) {
+                        let operand: Vec<_> = s[i + 2..close].iter().map(|&(c, _)| c).collect();
+                        let element = if delimiter == u32::from(b':') {
+                            if operand.is_empty() {
+                                return Err("missing character class name '[::]'".to_string());
+                            }
+                            Class::from_name(&operand).map(Element::Class)
+                        } else {
+                            if operand.is_empty() {
+                                return Err(
+                                    "missing equivalence class character '[==]'".to_string()
+                                );
+                            }
+                            Some(Element::Equiv(operand[0])).filter(|_| operand.len() == 1)
+                        };
+
+                        match element {
+                            Some(element) => {
+                                elements.push(element);
+                                i = close + 2;
+                                continue;
+                            },
+                            // This may be a repeat of ':' or '='
+                            None if is_star_digits_bracket(&s, i + 2) => {},
+                            None if delimiter == u32::from(b':') => {
+                                return Err(format!(
+                                    "invalid character class '{}'",
+                                    printable(&operand, encoding)
+                                ));
+                            },
+                            None => {
+                                return Err(format!(
+                                    "{}: equivalence class operand must be a single character",
+                                    printable(&operand, encoding)
+                                ));
+                            },
+                        }
+                    }
+                }
+
+                if let Some((repeat, close)) = find_repeat(&s, i + 1, encoding)? {
+                    elements.push(repeat);
+                    i = close + 1;
+                    continue;
+                }
+            }
+
+            if is(i + 1, b'-') {
+                let (first, last) = (s[i].0, s[i + 2].0);
+                if last < first {
+                    return Err(format!(
+                        "range-endpoints of '{}-{}' are in reverse collating sequence order",
+                        printable(&[first], encoding),
+                        printable(&[last], encoding)
+                    ));
+                }
+                elements.push(Element::Range(first, last));
+                i += 3;
+            } else {
+                elements.push(Element::Char(s[i].0));
+                i += 1;
+            }
+        }
+        elements.extend(s[i..].iter().map(|&(c, _)| Element::Char(c)));
+
+        Ok(Set { elements, encoding })
+    }
+
+    /// The characters of the set, in turn.
+    pub(crate) fn chars(&self) -> Chars<'_> {
+        Chars { elements: &self.elements, encoding: self.encoding, index: 0, state: None }
+    }
+
+    /// The facts about the set.
+    ///
+    /// # Errors
+    /// If the count of the characters is too large, an error message is returned.
+    pub(crate) fn stats(&self) -> Result<Stats, String> {
+        let mut stats = Stats {
+            len: 0,
+            has_class: false,
+            has_restricted_class: false,
+            has_equiv: false,
+            indefinite: Vec::new(),
+        };
+
+        for (index, element) in self.elements.iter().enumerate() {
+            let len = match *element {
+                Element::Char(_) => 1,
+                Element::Range(first, last) => {
+                    let surrogates = (first.max(0xd800)..=last.min(0xdfff)).count() as u64;
+                    u64::from(last - first) + 1 - surrogates
+                },
+                Element::Class(class) => {
+                    stats.has_class = true;
+                    stats.has_restricted_class |= !class.is_case();
+                    class.len(self.encoding)
+                },
+                Element::Equiv(c) => {
+                    stats.has_equiv = true;
+                    equivalents(c, self.encoding).len() as u64
+                },
+                Element::Repeat(_, 0) => {
+                    stats.indefinite.push(index);
+                    0
+                },
+                Element::Repeat(_, count) => count,
+            };
+            stats.len = stats
+                .len
+                .checked_add(len)
+                .filter(|&len| len <= MAX_COUNT)
+                .ok_or_else(|| "too many characters in set".to_string())?;
+        }
+
+        Ok(stats)
+    }
+
+    /// The characters of the set, or of its complement if `complement`.
+    pub(crate) fn members(&self, complement: bool) -> Members {
+        Members { chars: self.chars().collect(), complement }
+    }
+
+    /// Whether the set has at least one character, and no other character than the first.
+    pub(crate) fn is_homogeneous(&self) -> bool {
+        let mut chars = self.chars();
+        match chars.next() {
+            Some(first) => chars.all(|c| c == first),
+            None => false,
+        }
+    }
+}
+
+/// An iterator over the characters of a set, which also tells their case classes.
+#[derive(Debug, Clone)]
+pub(crate) struct Chars<'a> {
+    elements: &'a [Element],
+    encoding: Encoding,
+    /// The index of the current element.
+    index: usize,
+    /// The state in the current element, or `None` if it was not started: the last
+    /// character of the ranges, the next one of the classes, or the count of the
+    /// characters of the equivalence classes and of the repeats.
+    state: Option<u64>,
+}
+
+impl Chars<'_> {
+    /// The next character, with the case of the class it is in, if any.
+    pub(crate) fn next_cased(&mut self) -> Option<(u32, Option<Class>)> {
+        loop {
+            let (c, case) = match *self.elements.get(self.index)? {
+                Element::Char(c) => {
+                    self.skip_element();
+                    (c, None)
+                },
+                Element::Range(first, last) => {
+                    let c = match self.state {
+                        Some(0xd7ff) => 0xe000,
+                        Some(state) => state as u32 + 1,
+                        None => first,
+                    };
+                    if c == last {
+                        self.skip_element();
+                    } else {
+                        self.state = Some(u64::from(c));
+                    }
+                    (c, None)
+                },
+                Element::Class(class) => {
+                    let c = match self.state {
+                        Some(state) => state as u32,
+                        None => match class.next_member(None, self.encoding) {
+                            Some(c) => c,
+                            // The classes are not empty, but the Unicode ones may be
+                            None => {
+                                self.skip_element();
+                                continue;
+                            },
+                        },
+                    };
+                    match class.next_member(Some(c), self.encoding) {
+                        Some(next) => self.state = Some(u64::from(next)),
+                        None => self.skip_element(),
+                    }
+                    (c, Some(class).filter(|class| class.is_case()))
+                },
+                Element::Equiv(c) => {
+                    let members = equivalents(c, self.encoding);
+                    let index = self.state.unwrap_or(0);
+                    if index as usize + 1 == members.len() {
+                        self.skip_element();
+                    } else {
+                        self.state = Some(index + 1);
+                    }
+                    (members[index as usize], None)
+                },
+                Element::Repeat(_, 0) => {
+                    self.skip_element();
+                    continue;
+                },
+                Element::Repeat(c, count) => {
+                    let repeated = self.state.map_or(1, |state| state + 1);
+                    if repeated == count {
+                        self.skip_element();
+                    } else {
+                        self.state = Some(repeated);
+                    }
+                    (c, None)
+                },
+            };
+
+            return Some((c, case));
+        }
+    }
+
+    /// Whether the next character is the first of its element.
+    pub(crate) fn at_element_start(&self) -> bool {
+        self.state.is_none()
+    }
+
+    /// Skip the rest of the current element.
+    pub(crate) fn skip_element(&mut self) {
+        self.index += 1;
+        self.state = None;
+    }
+}
+
+impl Iterator for Chars<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_cased().map(|(c, _)| c)
+    }
+}
+
+/// Replace the escapes of the `operand` by the bytes they stand for, which are marked to
+/// not be special.
+fn unescape(operand: &str) -> Vec<(u8, bool)> {
+    let s = operand.as_bytes();
+    let octal = |i: usize| s.get(i).filter(|&&c| (b'0'..=b'7').contains(&c)).map(|c| c - b'0');
+    let mut unescaped = Vec::with_capacity(s.len());
+
+    let mut i = 0;
+    while i < s.len() {
+        if s[i] != b'\\' {
+            unescaped.push((s[i], false));
+            i += 1;
+            continue;
+        }
+
+        let c = match s.get(i + 1) {
+            Some(b'a') => b'\x07',
+            Some(b'b') => b'\x08',
+            Some(b'f') => b'\x0c',
+            Some(b'n') => b'\n',
+            Some(b'r') => b'\r',
+            Some(b't') => b'\t',
+            Some(b'v') => b'\x0b',
+            Some(b'0'..=b'7') => {
+                let mut c = u16::from(s[i + 1] - b'0');
+                if let Some(second) = octal(i + 2) {
+                    c = 8 * c + u16::from(second);
+                    i += 1;
+                    if let Some(third) = octal(i + 2) {
+                        if 8 * c + u16::from(third) < 256 {
+                            c = 8 * c + u16::from(third);
+                            i += 1;
+                        } else {
+                            let (d1, d2, d3) =
+                                (char::from(s[i]), char::from(s[i + 1]), char::from(s[i + 2]));
+                            eprintln!(
+                                "tr: warning: the ambiguous octal escape \\{}{}{} is \
+                                 being\n\tinterpreted as the 2-byte sequence \\0{}{}, {}",
+                                d1, d2, d3, d1, d2, d3
+                            );
+                        }
+                    }
+                }
+                c as u8
+            },
+            Some(&c) => c,
+            None => {
+                eprintln!("tr: warning: an unescaped backslash at end of string is not portable");
+                b'\\'
+            },
+        };
+
+        unescaped.push((c, true));
+        i += 2;
+    }
+
+    unescaped
+}
+
+/// The length of the UTF-8 sequence starting with the `byte`, or 0 if it can't start one.
+pub(crate) fn utf8_len(byte: u8) -> usize {
+    match byte {
+        0x00..=0x7f => 1,
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => 0,
+    }
+}
+
+/// Decode the `unescaped` bytes of a set as UTF-8, where the characters are special only
+/// if none of their bytes were escaped.
+fn decode(unescaped: &[(u8, bool)]) -> Option<Vec<(u32, bool)>> {
+    let mut decoded = Vec::with_capacity(unescaped.len());
+
+    let mut i = 0;
+    while i < unescaped.len() {
+        let len = utf8_len(unescaped[i].0);
+        let sequence = unescaped.get(i..i + len).filter(|_| len > 0)?;
+        let bytes: Vec<_> = sequence.iter().map(|&(byte, _)| byte).collect();
+        let c = std::str::from_utf8(&bytes).ok()?.chars().next()?;
+
+        decoded.push((u32::from(c), sequence.iter().any(|&(_, escaped)| escaped)));
+        i += len;
+    }
+
+    Some(decoded)
+}
+
+/// The index of the `delimiter` that is followed by ']', from the `start` of the set `s`.
+fn find_closing(s: &[(u32, bool)], start: usize, delimiter: u32) -> Option<usize> {
+    (start..s.len().saturating_sub(1))
+        .find(|&i| s[i] == (delimiter, false) && s[i + 1] == (u32::from(b']'), false))
+}
+
+/// Whether the set `s` has a `*` at the `start`, followed by digits and ']'.
+fn is_star_digits_bracket(s: &[(u32, bool)], start: usize) -> bool {
+    if s.get(start) != Some(&(u32::from(b'*'), false)) {
+        return false;
+    }
+
+    s[start + 1..]
+        .iter()
+        .find(|&&(c, escaped)| !(u32::from(b'0')..=u32::from(b'9')).contains(&c) || escaped)
+        == Some(&(u32::from(b']'), false))
+}
+
+/// Find the `[c*n]` repeat of the character at the `start` of the set `s`, after the '['.
+///
+/// Returns the repeat and the index of its ']', or `None` if there is no repeat.
+///
+/// # Errors
+/// If the count of the repeat is not valid, an error message is returned.
+fn find_repeat(
+    s: &[(u32, bool)], start: usize, encoding: Encoding,
+) -> Result<Option<(Element, usize)>, String> {
+    if s.get(start + 1) != Some(&(u32::from(b'*'), false)) {
+        return Ok(None);
+    }
+
+    let close = (start + 2..s.len()).take_while(|&i| !s[i].1).find(|&i| s[i].0 == u32::from(b']'));
+    let close = match close {
+        Some(close) => close,
+        None => return Ok(None),
+    };
+
+    let digits: Vec<_> = s[start + 2..close].iter().map(|&(c, _)| c).collect();
+    let count = if digits.is_empty() {
+        0
+    } else {
+        // Like GNU, the counts starting with 0 are octal
+        let radix = if digits[0] == u32::from(b'0') { 8 } else { 10 };
+        let text: String = digits.iter().filter_map(|&c| std::char::from_u32(c)).collect();
+        match u64::from_str_radix(text.trim_start(), radix) {
+            Ok(count) if count <= MAX_COUNT && text.chars().count() == digits.len() => count,
+            _ => {
+                return Err(format!(
+                    "invalid repeat count '{}' in [c*n] construct",
+                    printable(&digits, encoding)
+                ));
+            },
+        }
+    };
+
+    Ok(Some((Element::Repeat(s[start].0, count), close)))
+}
+
+/// The `text` with its special characters escaped, for the error messages.
+fn printable(text: &[u32], encoding: Encoding) -> String {
+    let mut printable = String::new();
+    for &c in text {
+        match std::char::from_u32(c) {
+            Some('\x07') => printable.push_str("\\a"),
+            Some('\x08') => printable.push_str("\\b"),
+            Some('\x0c') => printable.push_str("\\f"),
+            Some('\n') => printable.push_str("\\n"),
+            Some('\r') => printable.push_str("\\r"),
+            Some('\t') => printable.push_str("\\t"),
+            Some('\x0b') => printable.push_str("\\v"),
+            Some(printed) if Class::Print.contains(c, encoding) => printable.push(printed),
+            _ => printable.push_str(&format!("\\{:03o}", c)),
+        }
+    }
+    printable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(operand: &str) -> Set {
+        Set::parse(operand, Encoding::Bytes).unwrap()
+    }
+
+    fn chars(operand: &str) -> Vec<u8> {
+        set(operand).chars().map(|c| c as u8).collect()
+    }
+
+    fn utf8_chars(operand: &str) -> String {
+        let set = Set::parse(operand, Encoding::Utf8).unwrap();
+        set.chars().map(|c| std::char::from_u32(c).unwrap()).collect()
+    }
+
+    #[test]
+    fn constructs() {
+        assert_eq!(chars("a-d"), b"abcd");
+        assert_eq!(chars("[:digit:]x"), b"0123456789x");
+        assert_eq!(chars("[:xdigit:]"), b"0123456789ABCDEFabcdef");
+        assert_eq!(chars("[:space:]"), b"\t\n\x0b\x0c\r ");
+        assert_eq!(chars("[a*3]b[c*0]"), b"aaab");
+        assert_eq!(chars("[a*010]"), b"aaaaaaaa");
+        assert_eq!(chars("a-a"), b"a");
+        assert_eq!(chars("[:alpha"), b"[:alpha");
+        assert_eq!(chars("[:*2]"), b"::");
+        assert_eq!(chars("[=*2]"), b"==");
+        assert_eq!(chars("[=a=]b"), b"ab");
+        assert_eq!(chars("a-"), b"a-");
+        assert_eq!(chars("-a-c"), b"-abc");
+        assert_eq!(chars("[a-c]"), b"[abc]");
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(chars("\\n\\t\\\\\\q"), b"\n\t\\q");
+        assert_eq!(chars("\\0\\101\\1011"), b"\0AA1");
+        assert_eq!(chars("\\400"), b" 0");
+        assert_eq!(chars("a\\-c"), b"a-c");
+        assert_eq!(chars("\\[:digit:]"), b"[:digit:]");
+        assert_eq!(chars("\\101-C"), b"ABC");
+        assert_eq!(chars("a\\"), b"a\\");
+    }
+
+    #[test]
+    fn utf8() {
+        assert_eq!(utf8_chars("а-е"), "абвгде");
+        assert_eq!(utf8_chars("[=e=]"), "eèéêëēĕėęě");
+        assert_eq!(utf8_chars("[=É=]x"), "EÈÉÊËĒĔĖĘĚx");
+        assert_eq!(utf8_chars("[=ж=]"), "ж");
+        assert_eq!(utf8_chars("[é*3]"), "ééé");
+        assert_eq!(utf8_chars("\\303\\251-\\303\\253"), "éêë");
+        assert_eq!(utf8_chars("\\uD7FF"), "uD7FF");
+        assert_eq!(utf8_chars("\u{d7fe}-\u{e001}"), "\u{d7fe}\u{d7ff}\u{e000}\u{e001}");
+        assert!(Set::parse("[:upper:]", Encoding::Utf8).unwrap().chars().any(|c| c == 0x416));
+        assert_eq!(
+            Set::parse("\\303", Encoding::Utf8).unwrap_err(),
+            "invalid UTF-8 escapes in '\\303'"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = |operand: &str| Set::parse(operand, Encoding::Bytes).unwrap_err();
+        assert_eq!(error("[:foo:]"), "invalid character class 'foo'");
+        assert_eq!(error("[::]"), "missing character class name '[::]'");
+        assert_eq!(error("[==]"), "missing equivalence class character '[==]'");
+        assert_eq!(error("[=ab=]"), "ab: equivalence class operand must be a single character");
+        assert_eq!(
+            error("[=é=]"),
+            "\\303\\251: equivalence class operand must be a single character"
+        );
+        assert_eq!(error("[a*2x]"), "invalid repeat count '2x' in [c*n] construct");
+        assert_eq!(error("[a*09]"), "invalid repeat count '09' in [c*n] construct");
+        assert_eq!(
+            error("\\n-\\001"),
+            "range-endpoints of '\\n-\\001' are in reverse collating sequence order"
+        );
+        assert_eq!(
+            Set::parse("я-а", Encoding::Utf8).unwrap_err(),
+            "range-endpoints of 'я-а' are in reverse collating sequence order"
+        );
+    }
+
+    #[test]
+    fn stats() {
+        let stats = set("a-c[:upper:][x*][y*4][=z=]").stats().unwrap();
+        assert_eq!(stats.len, 34);
+        assert!(stats.has_class);
+        assert!(!stats.has_restricted_class);
+        assert!(stats.has_equiv);
+        assert_eq!(stats.indefinite, vec![2]);
+
+        let stats = Set::parse("\u{d000}-\u{e000}", Encoding::Utf8).unwrap().stats().unwrap();
+        assert_eq!(stats.len, 0x1001 - 0x800);
+
+        let set = set("[a*18446744073709551614]b");
+        assert_eq!(set.stats().unwrap_err(), "too many characters in set");
+    }
+
+    #[test]
+    fn cases() {
+        let set = set("a[:upper:]");
+        let mut chars = set.chars();
+        assert_eq!(chars.next_cased(), Some((u32::from(b'a'), None)));
+        assert!(chars.at_element_start());
+        assert_eq!(chars.next_cased(), Some((u32::from(b'A'), Some(Class::Upper))));
+        assert!(!chars.at_element_start());
+        chars.skip_element();
+        assert_eq!(chars.next_cased(), None);
+    }
+}
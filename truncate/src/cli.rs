@@ -0,0 +1,60 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("Files to change, that are created if they don't exist.")
+                .required(true)
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("noCreate")
+                .help("Do not create the files that don't exist.")
+                .long("no-create")
+                .short("c")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("ioBlocks")
+                .help("Take SIZE as a number of blocks of I/O of each file, instead of bytes.")
+                .long("io-blocks")
+                .short("o")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("reference")
+                .help("Set the size of RFILE, or change it with a relative SIZE.")
+                .long("reference")
+                .short("r")
+                .value_name("RFILE")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("size")
+                .help("Set or change the size by SIZE bytes.")
+                .long_help(
+                    "Set or change the size by SIZE bytes.\n\nSIZE is a number with an optional \
+                     unit: K, M, G, T, P, E, Z or Y for the powers of 1024, that can also be \
+                     written as KiB, MiB and so on, or KB, MB and so on for the powers of \
+                     1000.\n\nIt may start with a change of the size: '+' to extend it by SIZE, \
+                     '-' to shrink it by SIZE, '<' to shrink it to at most SIZE, '>' to extend it \
+                     to at least SIZE, '/' to round it down to a multiple of SIZE, or '%' to \
+                     round it up to a multiple of SIZE.",
+                )
+                .long("size")
+                .short("s")
+                .value_name("SIZE")
+                .allow_hyphen_values(true)
+                .takes_value(true),
+        )
+}
@@ -0,0 +1,302 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Seek, SeekFrom},
+    os::unix::fs::MetadataExt,
+    path::Path,
+    process,
+};
+
+use coreutils_core::{
+    error::strerror,
+    size::{parse_size, SizeError},
+};
+
+mod cli;
+
+/// The suffixes of the units of the sizes.
+const SUFFIXES: &str = "EgGkKmMPtTYZ";
+
+/// How the size given changes the size of the files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Set it.
+    Set,
+    /// Add it, to extend or shrink the size.
+    Adjust,
+    /// Shrink the size to at most it.
+    AtMost,
+    /// Extend the size to at least it.
+    AtLeast,
+    /// Round the size down to a multiple of it.
+    RoundDown,
+    /// Round the size up to a multiple of it.
+    RoundUp,
+}
+
+/// The change of the size of the files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Change {
+    mode: Mode,
+    size: i64,
+    /// The size that is changed instead of the one of the files, if any.
+    reference: Option<i64>,
+    /// Whether the `size` is a number of the I/O blocks of the files.
+    blocks: bool,
+}
+
+fn main() {
+    let matches = cli::create_app().get_matches();
+
+    let size = matches.value_of("size").map(parse_change).transpose().unwrap_or_else(|err| {
+        eprintln!("truncate: {}", err);
+        process::exit(1);
+    });
+
+    let reference = matches.value_of_os("reference").map(|reference| {
+        reference_size(Path::new(reference)).unwrap_or_else(|err| {
+            eprintln!("truncate: {}", err);
+            process::exit(1);
+        })
+    });
+
+    let blocks = matches.is_present("ioBlocks");
+
+    let change = match (size, reference) {
+        (None, None) => {
+            eprintln!("truncate: you must specify either '--size' or '--reference'");
+            process::exit(1);
+        },
+        (Some((Mode::Set, _)), Some(_)) => {
+            eprintln!("truncate: you must specify a relative '--size' with '--reference'");
+            process::exit(1);
+        },
+        (None, Some(_)) if blocks => {
+            eprintln!("truncate: '--io-blocks' was specified but '--size' was not");
+            process::exit(1);
+        },
+        (Some((mode, size)), reference) => Change { mode, size, reference, blocks },
+        (None, Some(reference)) => {
+            Change { mode: Mode::Set, size: reference, reference: None, blocks }
+        },
+    };
+
+    let no_create = matches.is_present("noCreate");
+    let mut success = true;
+
+    // FILE is required, so it's ok to unwrap
+    for file in matches.values_of_os("FILE").unwrap() {
+        if let Err(err) = truncate(Path::new(file), change, no_create) {
+            eprintln!("truncate: {}", err);
+            success = false;
+        }
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// Parse the `--size`, with the optional change of the size before it.
+///
+/// # Errors
+/// If there is more than a change, the size is not a size with one of the [`SUFFIXES`] or
+/// it is 0 to round to its multiples, an error message will be returned.
+fn parse_change(size: &str) -> Result<(Mode, i64), String> {
+    let invalid = |err| match err {
+        SizeError::Invalid => format!("Invalid number: '{}'", size),
+        SizeError::TooLarge => format!("Invalid number: '{}': {}", size, err),
+    };
+
+    let trimmed = size.trim_start();
+
+    let (mut mode, mut number) = match trimmed.chars().next() {
+        Some('<') => (Mode::AtMost, &trimmed[1..]),
+        Some('>') => (Mode::AtLeast, &trimmed[1..]),
+        Some('/') => (Mode::RoundDown, &trimmed[1..]),
+        Some('%') => (Mode::RoundUp, &trimmed[1..]),
+        _ => (Mode::Set, trimmed),
+    };
+
+    number = number.trim_start();
+
+    let negative = number.starts_with('-');
+
+    if negative || number.starts_with('+') {
+        if mode != Mode::Set {
+            return Err("multiple relative modifiers specified".to_string());
+        }
+        mode = Mode::Adjust;
+        number = &number[1..];
+    }
+
+    let bytes = parse_size(number, SUFFIXES).map_err(invalid)?;
+
+    if bytes > i64::MAX as u64 {
+        return Err(invalid(SizeError::TooLarge));
+    }
+
+    if bytes == 0 && (mode == Mode::RoundDown || mode == Mode::RoundUp) {
+        return Err("division by zero".to_string());
+    }
+
+    Ok((mode, if negative { -(bytes as i64) } else { bytes as i64 }))
+}
+
+/// Get the size of the file at `path` given with `--reference`.
+///
+/// # Errors
+/// If the metadata or the size of the file can't be read, an error message will be
+/// returned.
+fn reference_size(path: &Path) -> Result<i64, String> {
+    let metadata = fs::metadata(path)
+        .map_err(|err| format!("cannot stat '{}': {}", path.display(), strerror(&err)))?;
+
+    if metadata.is_file() {
+        return Ok(metadata.len() as i64);
+    }
+
+    File::open(path)
+        .and_then(|mut file| file.seek(SeekFrom::End(0)))
+        .map(|end| end as i64)
+        .map_err(|err| format!("cannot get the size of '{}': {}", path.display(), strerror(&err)))
+}
+
+/// Change the size of the file at `path`, creating it unless there is `no_create`.
+///
+/// # Errors
+/// If the file can't be opened, its size can't be read or the new size can't be set, an
+/// error message will be returned.
+fn truncate(path: &Path, change: Change, no_create: bool) -> Result<(), String> {
+    let mut file = match OpenOptions::new().write(true).create(!no_create).open(path) {
+        Ok(file) => file,
+        Err(err) if no_create && err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => {
+            return Err(format!(
+                "cannot open '{}' for writing: {}",
+                path.display(),
+                strerror(&err)
+            ));
+        },
+    };
+
+    let mut size = change.size;
+
+    if change.blocks {
+        let blocks = file
+            .metadata()
+            .map_err(|err| format!("cannot fstat '{}': {}", path.display(), strerror(&err)))?
+            .blksize() as i64;
+
+        size = size.checked_mul(blocks).ok_or_else(|| {
+            format!(
+                "overflow in {} * {} byte blocks for file '{}'",
+                change.size,
+                blocks,
+                path.display()
+            )
+        })?;
+    }
+
+    let new_size = if change.mode == Mode::Set {
+        size
+    } else {
+        let current = match change.reference {
+            Some(reference) => reference,
+            None => current_size(&mut file).map_err(|err| {
+                format!("cannot get the size of '{}': {}", path.display(), strerror(&err))
+            })?,
+        };
+
+        match change.mode {
+            Mode::AtMost => current.min(size),
+            Mode::AtLeast => current.max(size),
+            Mode::RoundDown => current - current % size,
+            mode => {
+                let added = match mode {
+                    Mode::RoundUp if current % size == 0 => 0,
+                    Mode::RoundUp => size - current % size,
+                    _ => size,
+                };
+
+                current.checked_add(added).ok_or_else(|| {
+                    format!("overflow extending size of file '{}'", path.display())
+                })?
+            },
+        }
+    };
+
+    let new_size = new_size.max(0) as u64;
+
+    file.set_len(new_size).map_err(|err| {
+        format!("failed to truncate '{}' at {} bytes: {}", path.display(), new_size, strerror(&err))
+    })
+}
+
+/// Get the size of a open `file`, that is where it ends when it is not a regular file.
+fn current_size(file: &mut File) -> io::Result<i64> {
+    let metadata = file.metadata()?;
+
+    if metadata.is_file() {
+        Ok(metadata.len() as i64)
+    } else {
+        file.seek(SeekFrom::End(0)).map(|end| end as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes() {
+        assert_eq!(parse_change("5K"), Ok((Mode::Set, 5 * 1024)));
+        assert_eq!(parse_change(" +10"), Ok((Mode::Adjust, 10)));
+        assert_eq!(parse_change("-5"), Ok((Mode::Adjust, -5)));
+        assert_eq!(parse_change("< 2KB"), Ok((Mode::AtMost, 2000)));
+        assert_eq!(parse_change(">1"), Ok((Mode::AtLeast, 1)));
+        assert_eq!(parse_change("/300"), Ok((Mode::RoundDown, 300)));
+        assert_eq!(parse_change("%K"), Ok((Mode::RoundUp, 1024)));
+    }
+
+    #[test]
+    fn invalid_changes() {
+        assert_eq!(parse_change("+-5"), Err("Invalid number: '+-5'".to_string()));
+        assert_eq!(parse_change("+ 5"), Err("Invalid number: '+ 5'".to_string()));
+        assert_eq!(parse_change("<-5"), Err("multiple relative modifiers specified".to_string()));
+        assert_eq!(parse_change("%0"), Err("division by zero".to_string()));
+        assert!(parse_change("9E").unwrap_err().ends_with("Value too large for defined data type"));
+    }
+
+    #[test]
+    fn repeated_flags() {
+        let args = vec!["truncate", "-o", "-c", "-s", "2", "--io-blocks", "-c", "file"];
+        let matches = cli::create_app().get_matches_from_safe(args).unwrap();
+        assert!(matches.is_present("ioBlocks") && matches.is_present("noCreate"));
+    }
+
+    #[test]
+    fn sizes() {
+        let path = std::env::temp_dir().join(format!("truncate.{}", process::id()));
+        let change = |mode, size| Change { mode, size, reference: None, blocks: false };
+
+        fs::write(&path, b"0123456789").unwrap();
+        let changes = [
+            change(Mode::Adjust, 10),
+            change(Mode::AtMost, 15),
+            change(Mode::RoundDown, 4),
+            change(Mode::RoundUp, 5),
+            change(Mode::Adjust, -20),
+            Change { reference: Some(3), ..change(Mode::AtLeast, 7) },
+        ];
+        let sizes: Vec<_> = changes
+            .iter()
+            .map(|&change| {
+                truncate(&path, change, true).unwrap();
+                fs::metadata(&path).unwrap().len()
+            })
+            .collect();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(sizes, [20, 15, 12, 15, 0, 7]);
+    }
+}
@@ -50,4 +50,44 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("N"),
         )
         .arg(Arg::with_name("unique").help("Only display unique lines.").short("u").long("unique"))
+        .arg(Arg::with_name("all-repeated").help("Print all duplicate lines.").short("D"))
+        .arg(
+            Arg::with_name("all-repeated-method")
+                .help("Like -D, but allow separating groups with an empty line.")
+                .long_help(
+                    "Like -D, but allow separating groups with an empty line.\n\nMETHOD is one of \
+                     none (default), prepend or separate.",
+                )
+                .long("all-repeated")
+                .value_name("METHOD")
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&["none", "prepend", "separate"]),
+        )
+        .arg(
+            Arg::with_name("group")
+                .help("Show all items, separating groups with an empty line.")
+                .long_help(
+                    "Show all items, separating groups with an empty line.\n\nMETHOD is one of \
+                     separate (default), prepend, append or both.",
+                )
+                .long("group")
+                .value_name("METHOD")
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&["separate", "prepend", "append", "both"]),
+        )
+        .arg(
+            Arg::with_name("ignore-case")
+                .help("Ignore differences in case when comparing.")
+                .short("i")
+                .long("ignore-case"),
+        )
+        .arg(
+            Arg::with_name("check-chars")
+                .help("Compare no more than N characters in lines.")
+                .short("w")
+                .long("check-chars")
+                .value_name("N"),
+        )
 }
@@ -2,7 +2,7 @@ use std::{
     cmp,
     fs::File,
     io::{self, BufRead, BufReader, BufWriter, Write},
-    process,
+    mem, process,
 };
 
 mod cli;
@@ -31,26 +31,49 @@ fn main() {
         })))
     };
 
-    uniq(&mut reader, &mut writer, flags).unwrap_or_else(|err| {
+    uniq(&mut reader, &mut writer, flags).and_then(|_| writer.flush()).unwrap_or_else(|err| {
         eprintln!("uniq: {}.", err);
         process::exit(1);
     });
 }
 
-// -f is ignored before -s
+/// How the groups of lines are delimited with `--all-repeated`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Delimit {
+    None,
+    /// A empty line before each group.
+    Prepend,
+    /// A empty line between the groups.
+    Separate,
+}
+
+/// Where the empty lines are printed with `--group`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Group {
+    Separate,
+    Prepend,
+    Append,
+    Both,
+}
+
+// -f is applied before -s
 #[derive(Default)]
 struct Flags {
-    show_count: bool,           // -c | --show_count
-    supress_unique: bool,       // -d | --repeated
-    supress_repeated: bool,     // -u | --unique
-    skip_bytes: Option<usize>,  // -s | --skip-chars=N
-    skip_fields: Option<usize>, // -f | --skip-fields=N
+    show_count: bool,              // -c | --count
+    supress_unique: bool,          // -d | --repeated
+    supress_repeated: bool,        // -u | --unique
+    all_repeated: Option<Delimit>, // -D | --all-repeated[=METHOD]
+    group: Option<Group>,          // --group[=METHOD]
+    ignore_case: bool,             // -i | --ignore-case
+    skip_bytes: Option<usize>,     // -s | --skip-chars=N
+    skip_fields: Option<usize>,    // -f | --skip-fields=N
+    check_bytes: Option<usize>,    // -w | --check-chars=N
 }
 // skip_utf8_chars:  Option<usize>, // --skip-utf8=N
 
 impl Flags {
     fn from_matches(matches: &clap::ArgMatches) -> Self {
-        // Used to capture skip_bytes and skip_fields
+        // Used to capture skip_bytes, skip_fields and check_bytes
         let try_parse_arg_to_usize = |arg: Option<&str>, error_msg| {
             if let Some(arg) = arg {
                 let number = arg.parse::<usize>().unwrap_or_else(|_| {
@@ -63,153 +86,210 @@ impl Flags {
             }
         };
 
-        Flags {
-            show_count: matches.is_present("show_count"),
+        let all_repeated = match matches.value_of("all-repeated-method") {
+            Some("prepend") => Some(Delimit::Prepend),
+            Some("separate") => Some(Delimit::Separate),
+            Some(_) => Some(Delimit::None),
+            None if matches.is_present("all-repeated")
+                || matches.is_present("all-repeated-method") =>
+            {
+                Some(Delimit::None)
+            },
+            None => None,
+        };
+
+        let group = match matches.value_of("group") {
+            Some("prepend") => Some(Group::Prepend),
+            Some("append") => Some(Group::Append),
+            Some("both") => Some(Group::Both),
+            Some(_) => Some(Group::Separate),
+            None if matches.is_present("group") => Some(Group::Separate),
+            None => None,
+        };
+
+        let flags = Flags {
+            show_count: matches.is_present("count"),
             supress_unique: matches.is_present("repeated"),
             supress_repeated: matches.is_present("unique"),
+            all_repeated,
+            group,
+            ignore_case: matches.is_present("ignore-case"),
             skip_bytes: try_parse_arg_to_usize(
-                matches.value_of("skip-chars"),
+                matches.value_of("skip-bytes"),
                 "--skip-chars: Invalid number of bytes to skip",
             ),
             skip_fields: try_parse_arg_to_usize(
                 matches.value_of("skip-fields"),
                 "--skip-fields: Invalid number of fields to skip",
             ),
+            check_bytes: try_parse_arg_to_usize(
+                matches.value_of("check-chars"),
+                "--check-chars: Invalid number of bytes to compare",
+            ),
+        };
+
+        if flags.group.is_some()
+            && (flags.show_count
+                || flags.supress_unique
+                || flags.supress_repeated
+                || flags.all_repeated.is_some())
+        {
+            eprintln!("uniq: --group is mutually exclusive with -c/-d/-D/-u.");
+            process::exit(1);
+        }
+
+        if flags.show_count && flags.all_repeated.is_some() {
+            eprintln!("uniq: printing all duplicated lines and repeat counts is meaningless.");
+            process::exit(1);
         }
+
+        flags
     }
+
+    /// The part of `line` that is compared, after the skipped fields and bytes and with
+    /// at most the checked bytes.
+    fn compared<'a>(&self, line: &'a [u8]) -> &'a [u8] {
+        let skipped = skip_fields_and_bytes(
+            line,
+            self.skip_fields.unwrap_or(0),
+            self.skip_bytes.unwrap_or(0),
+        );
+        let line = &line[skipped..];
+
+        match self.check_bytes {
+            Some(check) => &line[..cmp::min(check, line.len())],
+            None => line,
+        }
+    }
+
+    /// Returns `true` if the lines `a` and `b` are in the same group.
+    fn matches(&self, a: &[u8], b: &[u8]) -> bool {
+        let (a, b) = (self.compared(a), self.compared(b));
+
+        if self.ignore_case {
+            a.eq_ignore_ascii_case(b)
+        } else {
+            a == b
+        }
+    }
+}
+
+fn is_blank(byte: u8) -> bool {
+    byte == b' ' || byte == b'\t'
 }
 
 // Return the total of bytes skipped
-fn skip_fields_and_bytes(string: &str, fields: usize, bytes: usize) -> usize {
-    let mut iter = string.char_indices().peekable();
+fn skip_fields_and_bytes(line: &[u8], fields: usize, bytes: usize) -> usize {
     let mut skipped = 0;
 
     // Skip fields, regex is "\s*\S*"
     for _ in 0..fields {
-        while let Some((char_bytes, character)) = iter.peek() {
-            if character.is_whitespace() {
-                break;
-            }
-            skipped += char_bytes;
-            iter.next();
-        }
-
-        while let Some((char_bytes, character)) = iter.peek() {
-            if !character.is_whitespace() {
-                break;
-            }
-            skipped += char_bytes;
-            iter.next();
-        }
+        skipped += line[skipped..].iter().take_while(|&&b| is_blank(b)).count();
+        skipped += line[skipped..].iter().take_while(|&&b| !is_blank(b)).count();
     }
 
     // Skip bytes
-    // Try to skip them, but don't allow to overflow string.as_bytes().len()
-    let skipped = cmp::min(string.as_bytes().len(), skipped + bytes);
+    // Try to skip them, but don't allow to overflow line.len()
+    cmp::min(line.len(), skipped + bytes)
+}
 
-    skipped
+fn write_line<W: Write>(writer: &mut W, line: &[u8]) -> io::Result<()> {
+    writer.write_all(line)?;
+    writer.write_all(b"\n")
 }
 
 fn uniq<R: BufRead, W: Write>(
     reader: &mut R, writer: &mut W, flags: Flags,
 ) -> Result<(), io::Error> {
-    // If -s and -f are unset, last_line is guaranteed to be equals to the previous line,
-    // else, last_line is the first line in the last set of lines that with each other,
-    // considering the ignored bytes or fields.
-    let mut last_line = String::new();
-    let mut last_skipped_bytes = 0;
-
-    // Number of times that `last_line` matched
-    let mut last_count = 0;
-
-    // After reaching EOF, don't exit immediately, still process the `last_line`
-    let mut reached_eof = false;
-
-    // Loop for each line read
-    while !reached_eof {
-        let mut new_line = String::new();
-
-        // Using `reader.read_line()` to capture the line break characters
-        let bytes_read = reader.read_line(&mut new_line)?;
-        reached_eof = bytes_read == 0;
-
-        // Only happens on the last line of the input, when reaching EOF
-        // Add \n at the end if necessary
-        if bytes_read > 0 && new_line.bytes().next_back() != Some(b'\n') {
-            new_line.reserve_exact(1);
-            new_line.push('\n');
-        }
+    // The first line of the current group, that is the one printed by default, and the
+    // last one read of it, printed with -D when the next one matches
+    let mut first_line = Vec::new();
+    let mut last_line = Vec::new();
 
-        let skipped_bytes = skip_fields_and_bytes(
-            new_line.as_str(),
-            flags.skip_fields.unwrap_or(0),
-            flags.skip_bytes.unwrap_or(0),
-        );
+    // Number of lines in the current group
+    let mut count: u64 = 0;
 
-        let new_slice = &new_line.as_bytes()[skipped_bytes..];
-        let last_slice = &last_line.as_bytes()[last_skipped_bytes..];
+    // Number of the groups with repeated lines and of the groups, used for the delimiters
+    let mut repeated_groups = 0;
+    let mut groups = 0;
 
-        let line_changed = new_slice != last_slice;
-        let current_line_match_count = if line_changed { 1 } else { last_count + 1 };
+    let mut new_line = Vec::new();
 
-        // The combination of these two flags supress all output
-        if flags.supress_repeated && flags.supress_unique {
-            continue;
+    loop {
+        new_line.clear();
+        let reached_eof = reader.read_until(b'\n', &mut new_line)? == 0;
+        if new_line.last() == Some(&b'\n') {
+            new_line.pop();
         }
 
-        // The following block decides if new_line or last_line should be shown
-        // The lines are always shown as early as possible
-        // Formatting changes based on flags.show_count
-        if flags.show_count {
-            if line_changed {
-                let mut should_show_last_line = false;
-
-                if flags.supress_unique {
-                    if last_count > 1 {
-                        should_show_last_line = true;
-                    }
-                } else if flags.supress_repeated {
-                    if last_count == 1 {
-                        should_show_last_line = true;
-                    }
-                } else {
-                    should_show_last_line = true;
-                }
+        if !reached_eof && count > 0 && flags.matches(&first_line, &new_line) {
+            count += 1;
 
-                if should_show_last_line && last_count > 0 {
-                    write!(writer, "{:7} ", last_count)?;
-                    writer.write_all(last_line.as_bytes())?;
+            if let Some(delimit) = flags.all_repeated {
+                let first_repeated = delimit == Delimit::Separate && repeated_groups == 0;
+                if count == 2 && delimit != Delimit::None && !first_repeated {
+                    writer.write_all(b"\n")?;
                 }
+                write_line(writer, &last_line)?;
+                mem::swap(&mut last_line, &mut new_line);
+            } else if flags.group.is_some() {
+                write_line(writer, &new_line)?;
             }
-        } else {
-            let mut line_to_show: Option<&str> = None;
 
-            if flags.supress_unique {
-                if current_line_match_count == 2 {
-                    line_to_show = Some(&new_line);
-                }
-            } else if flags.supress_repeated {
-                if line_changed && last_count == 1 {
-                    line_to_show = Some(&last_line);
+            continue;
+        }
+
+        // The current group ended
+        if count > 0 && flags.group.is_none() {
+            let show = if count == 1 {
+                flags.all_repeated.is_none() && !flags.supress_unique
+            } else {
+                !flags.supress_repeated
+            };
+
+            if show {
+                if flags.show_count {
+                    write!(writer, "{:7} ", count)?;
                 }
-            } else if line_changed {
-                line_to_show = Some(&new_line);
+                let line = if flags.all_repeated.is_some() { &last_line } else { &first_line };
+                write_line(writer, line)?;
             }
 
-            if let Some(line) = line_to_show {
-                writer.write_all(line.as_bytes())?;
+            if count > 1 {
+                repeated_groups += 1;
             }
         }
 
-        last_line = new_line;
-        last_count = current_line_match_count;
-        last_skipped_bytes = skipped_bytes;
+        if reached_eof {
+            break;
+        }
+
+        if let Some(group) = flags.group {
+            let delimit = match group {
+                Group::Prepend | Group::Both => true,
+                Group::Separate | Group::Append => groups > 0,
+            };
+            if delimit {
+                writer.write_all(b"\n")?;
+            }
+            write_line(writer, &new_line)?;
+            groups += 1;
+        }
+
+        count = 1;
+        if flags.all_repeated.is_some() {
+            last_line.clone_from(&new_line);
+        }
+        mem::swap(&mut first_line, &mut new_line);
+    }
+
+    if groups > 0 && matches!(flags.group, Some(Group::Append) | Some(Group::Both)) {
+        writer.write_all(b"\n")?;
     }
 
     Ok(())
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -323,4 +403,54 @@ mod tests {
         let flags = Flags { supress_unique: true, supress_repeated: true, ..flags_none() };
         assert_eq!(expected, test_uniq(input, flags));
     }
+
+    #[test]
+    fn test_uniq_flag_all_repeated() {
+        let input = "A\nA\nB\nC\nC\nC\nD";
+        let flags = Flags { all_repeated: Some(Delimit::None), ..flags_none() };
+        assert_eq!("A\nA\nC\nC\nC\n", test_uniq(input, flags));
+
+        let flags = Flags { all_repeated: Some(Delimit::Prepend), ..flags_none() };
+        assert_eq!("\nA\nA\n\nC\nC\nC\n", test_uniq(input, flags));
+
+        let flags = Flags { all_repeated: Some(Delimit::Separate), ..flags_none() };
+        assert_eq!("A\nA\n\nC\nC\nC\n", test_uniq(input, flags));
+    }
+
+    #[test]
+    fn test_uniq_flag_group() {
+        let input = "A\nA\nB\nC";
+        let flags = Flags { group: Some(Group::Separate), ..flags_none() };
+        assert_eq!("A\nA\n\nB\n\nC\n", test_uniq(input, flags));
+
+        let flags = Flags { group: Some(Group::Both), ..flags_none() };
+        assert_eq!("\nA\nA\n\nB\n\nC\n\n", test_uniq(input, flags));
+
+        let flags = Flags { group: Some(Group::Append), ..flags_none() };
+        assert_eq!("", test_uniq("", flags));
+    }
+
+    #[test]
+    fn test_uniq_flag_ignore_case() {
+        let input = "a\nA\nb\nB\nc";
+        let flags = Flags { ignore_case: true, show_count: true, ..flags_none() };
+        assert_eq!("      2 a\n      2 b\n      1 c\n", test_uniq(input, flags));
+    }
+
+    #[test]
+    fn test_uniq_flag_check_chars() {
+        let input = "abc\nabd\nacd\nb";
+        let flags = Flags { check_bytes: Some(2), ..flags_none() };
+        assert_eq!("abc\nacd\nb\n", test_uniq(input, flags));
+
+        let flags = Flags { check_bytes: Some(1), skip_bytes: Some(1), ..flags_none() };
+        assert_eq!("abc\nacd\nb\n", test_uniq(input, flags));
+    }
+
+    #[test]
+    fn test_uniq_skip_fields_blanks() {
+        let input = "1 \ta x\n2 b x\n  3\t\tb x\n4 c";
+        let flags = Flags { skip_fields: Some(2), ..flags_none() };
+        assert_eq!("1 \ta x\n4 c\n", test_uniq(input, flags));
+    }
 }
@@ -26,8 +26,9 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("chars")
                 .help("Display the character counts.")
                 .long_help(
-                    "Display the character counts.\n\nIf the current locale does not support \
-                     multibyte characters, this is equivalent to the -c option.",
+                    "Display the character counts.\n\nWith a UTF-8 locale, the input is decoded \
+                     as UTF-8 and the invalid bytes are not counted. If the current locale does \
+                     not support multibyte characters, this is equivalent to the -c option.",
                 )
                 .long("chars")
                 .short("m"),
@@ -37,11 +38,11 @@ pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("max-line-length")
                 .help("Display the maximum display width.")
                 .long_help(
-                    "Display the maximum display width.\n\nWrite the length of the line \
-                     containing the most bytes (default) or characters (when -m is provided) to \
-                     standard output.\n\nWhen more than one file argument is specified, the \
-                     longest input line of all files is reported as the value of the final \
-                     \"total\".",
+                    "Display the maximum display width.\n\nWrite the display width of the widest \
+                     line to standard output. Tabs advance to the next multiple of 8 columns, \
+                     wide characters take 2 columns and non printable characters take \
+                     none.\n\nWhen more than one file argument is specified, the widest input \
+                     line of all files is reported as the value of the final \"total\".",
                 )
                 .long("max-line-length")
                 .short("L"),
@@ -1,9 +1,13 @@
 use std::{
     fs::File,
-    io::{self, prelude::*, BufReader},
+    io::{self, prelude::*, ErrorKind, SeekFrom},
+    panic, thread,
 };
 
 use clap::ArgMatches;
+use coreutils_core::{bstr::decode_utf8, env::is_utf8_locale};
+use memchr::memchr_iter;
+use unicode_width::UnicodeWidthChar;
 
 mod cli;
 
@@ -25,12 +29,9 @@ fn main() {
     for filename in &filenames {
         let result = if filename == "-" {
             let stdin = io::stdin();
-            wc(stdin.lock())
+            wc(stdin.lock(), flags)
         } else {
-            match File::open(filename) {
-                Ok(file) => wc(file),
-                Err(err) => Err(err),
-            }
+            wc_file(filename, flags)
         };
 
         match result {
@@ -55,6 +56,8 @@ struct WcFlags {
     print_words: bool,
     print_max_line_len: bool,
     pretty: bool,
+    /// The locale character encoding is UTF-8.
+    utf8: bool,
 }
 
 impl WcFlags {
@@ -66,6 +69,7 @@ impl WcFlags {
             print_words: true,
             print_max_line_len: false,
             pretty: false,
+            utf8: false,
         }
     }
 
@@ -76,6 +80,7 @@ impl WcFlags {
         let print_words = matches.is_present("words");
         let print_max_line_len = matches.is_present("max-line-length");
         let pretty = matches.is_present("pretty");
+        let utf8 = is_utf8_locale();
 
         if !print_bytes
             && !print_chars
@@ -84,7 +89,7 @@ impl WcFlags {
             && !print_max_line_len
             && !pretty
         {
-            return Self::new();
+            return WcFlags { utf8, ..Self::new() };
         }
 
         if pretty
@@ -94,12 +99,18 @@ impl WcFlags {
             && !print_max_line_len
             && !print_bytes
         {
-            let mut flags = Self::new();
-            flags.pretty = true;
-            return flags;
+            return WcFlags { pretty, utf8, ..Self::new() };
         }
 
-        WcFlags { print_bytes, print_chars, print_lines, print_words, print_max_line_len, pretty }
+        WcFlags {
+            print_bytes,
+            print_chars,
+            print_lines,
+            print_words,
+            print_max_line_len,
+            pretty,
+            utf8,
+        }
     }
 }
 
@@ -109,7 +120,7 @@ struct WcResult {
     words: u64,
     chars: u64,
     bytes: u64,
-    max_line_len: u32,
+    max_line_len: u64,
 }
 
 impl WcResult {
@@ -124,41 +135,312 @@ impl WcResult {
     }
 }
 
-fn wc<R: Read>(stream: R) -> Result<WcResult, io::Error> {
-    let reader = BufReader::new(stream);
+/// Size of the buffer used to read the input.
+const BUFFER_SIZE: usize = 256 * 1024;
+
+/// Minimum number of bytes counted by each thread when a file is split across threads.
+const PARALLEL_CHUNK_SIZE: u64 = 32 * 1024 * 1024;
+
+/// Counts the input incrementally, keeping the state between the buffers so the words,
+/// lines and characters can span them.
+#[derive(Default, Clone, Copy)]
+struct Counter {
+    result: WcResult,
+    /// Scan each character, needed for the words, chars and max line length.
+    scan: bool,
+    /// Decode the input as UTF-8, otherwise each byte is a character.
+    utf8: bool,
+    /// A whitespace or word character was counted.
+    delimited: bool,
+    /// The first whitespace or word character counted was a word character.
+    starts_in_word: bool,
+    /// The last whitespace or word character counted was a word character.
+    in_word: bool,
+    /// Display width of the current line.
+    line_width: u64,
+    /// Bytes of a UTF-8 character that continues on the next buffer.
+    pending: [u8; 4],
+    pending_len: usize,
+}
+
+impl Counter {
+    fn new(flags: WcFlags) -> Self {
+        Counter {
+            scan: flags.print_words || flags.print_chars || flags.print_max_line_len,
+            utf8: flags.utf8,
+            ..Counter::default()
+        }
+    }
+
+    /// Count the bytes of `buffer`, continuing from the previous calls.
+    fn update(&mut self, mut buffer: &[u8]) {
+        self.result.bytes += buffer.len() as u64;
+
+        if !self.scan {
+            // memchr is SIMD accelerated, bytes and lines don't need anything else
+            self.result.lines += memchr_iter(b'\n', buffer).count() as u64;
+            return;
+        }
+
+        if !self.utf8 {
+            self.result.chars += buffer.len() as u64;
+            for &byte in buffer {
+                if byte.is_ascii() {
+                    self.count_char(byte as char);
+                }
+            }
+            return;
+        }
+
+        if self.pending_len > 0 {
+            buffer = self.complete_pending(buffer);
+        }
 
-    let mut result = WcResult::default();
+        while let Some(&byte) = buffer.first() {
+            if byte.is_ascii() {
+                self.result.chars += 1;
+                self.count_char(byte as char);
+                buffer = &buffer[1..];
+                continue;
+            }
 
-    for line in reader.lines() {
-        let line = line?;
+            match decode_utf8(buffer) {
+                (Some(ch), size) => {
+                    self.result.chars += 1;
+                    self.count_char(ch);
+                    buffer = &buffer[size..];
+                },
+                // The character continues on the next buffer
+                (None, size) if size == buffer.len() && is_utf8_lead(byte) => {
+                    self.pending[..size].copy_from_slice(buffer);
+                    self.pending_len = size;
+                    return;
+                },
+                // The invalid bytes are not characters and don't start or end words
+                (None, size) => buffer = &buffer[size..],
+            }
+        }
+    }
 
-        result.lines += 1;
+    /// Count the character whose start was on the previous buffer, returning the rest of
+    /// `buffer`.
+    fn complete_pending<'a>(&mut self, buffer: &'a [u8]) -> &'a [u8] {
+        let pending_len = self.pending_len;
+        let mut bytes = self.pending;
+        let taken = buffer.len().min(4 - pending_len);
+        bytes[pending_len..pending_len + taken].copy_from_slice(&buffer[..taken]);
+        let bytes = &bytes[..pending_len + taken];
+
+        self.pending_len = 0;
+        match decode_utf8(bytes) {
+            (Some(ch), size) => {
+                self.result.chars += 1;
+                self.count_char(ch);
+                &buffer[size - pending_len..]
+            },
+            (None, size) if size == bytes.len() && bytes.len() < 4 => {
+                self.pending[..size].copy_from_slice(bytes);
+                self.pending_len = size;
+                &[]
+            },
+            // Only the pending bytes are invalid, the buffer is counted from the start
+            (None, _) => buffer,
+        }
+    }
 
-        let mut last_was_whitespace = true;
-        let mut n_chars_excluding_newline = 0;
-        for chr in line.chars() {
-            n_chars_excluding_newline += 1;
+    /// Count the words, lines and line width of `ch`.
+    fn count_char(&mut self, ch: char) {
+        let word = match ch {
+            '\n' => {
+                self.result.lines += 1;
+                self.end_line();
+                Some(false)
+            },
+            '\r' | '\x0C' => {
+                self.end_line();
+                Some(false)
+            },
+            '\t' => {
+                self.line_width += 8 - self.line_width % 8;
+                Some(false)
+            },
+            '\x0B' => Some(false),
+            // Control characters don't start or end words
+            _ if ch.is_control() => None,
+            _ => {
+                self.line_width += ch.width().unwrap_or(0) as u64;
+                Some(!is_word_separator(ch))
+            },
+        };
 
-            let is_whitespace = chr.is_whitespace();
-            if is_whitespace && !last_was_whitespace {
-                result.words += 1;
+        if let Some(word) = word {
+            if !self.delimited {
+                self.delimited = true;
+                self.starts_in_word = word;
+            }
+            if word && !self.in_word {
+                self.result.words += 1;
             }
-            last_was_whitespace = is_whitespace;
+            self.in_word = word;
         }
+    }
 
-        // The max line length considers characters, not bytes.
-        result.max_line_len = result.max_line_len.max(n_chars_excluding_newline);
+    fn end_line(&mut self) {
+        self.result.max_line_len = self.result.max_line_len.max(self.line_width);
+        self.line_width = 0;
+    }
 
-        result.chars += n_chars_excluding_newline as u64 + 1;
+    /// Combine with the `next` counter, that counted the bytes that came right after the
+    /// ones of `self`, that must not end in the middle of a character. The bytes of a
+    /// character cut at the end of `self` are dropped, so the chunks start at a
+    /// [`char_boundary`].
+    ///
+    /// The max line length is not exact when a line spans both.
+    fn combine(self, next: Counter) -> Self {
+        let mut result = self.result.combine(next.result);
+        if self.in_word && next.starts_in_word {
+            // A word spans both
+            result.words -= 1;
+        }
 
-        if !last_was_whitespace {
-            result.words += 1;
+        Counter {
+            result,
+            delimited: self.delimited || next.delimited,
+            starts_in_word: if self.delimited { self.starts_in_word } else { next.starts_in_word },
+            in_word: if next.delimited { next.in_word } else { self.in_word },
+            ..next
         }
+    }
 
-        result.bytes += line.len() as u64 + 1;
+    fn finish(mut self) -> WcResult {
+        self.end_line();
+        self.result
     }
+}
+
+/// Check if `byte` can start a multibyte UTF-8 character.
+fn is_utf8_lead(byte: u8) -> bool {
+    (0xC2..=0xF4).contains(&byte)
+}
+
+/// Check if `ch` separates words. The no-break spaces separate them as well.
+fn is_word_separator(ch: char) -> bool {
+    match ch {
+        '\u{A0}' | '\u{2007}' | '\u{202F}' | '\u{2060}' => true,
+        _ => ch.is_whitespace(),
+    }
+}
+
+/// Count the data of `stream` until EOF, starting from a counter state.
+fn count<R: Read>(mut stream: R, mut counter: Counter) -> io::Result<Counter> {
+    let mut buffer = vec![0; BUFFER_SIZE];
+
+    loop {
+        match stream.read(&mut buffer) {
+            Ok(0) => return Ok(counter),
+            Ok(n) => counter.update(&buffer[..n]),
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn wc<R: Read>(stream: R, flags: WcFlags) -> io::Result<WcResult> {
+    count(stream, Counter::new(flags)).map(Counter::finish)
+}
+
+/// Count the file `filename`, avoiding to read it if only the bytes are needed and
+/// splitting the counting across threads for large files that must be scanned.
+fn wc_file(filename: &str, flags: WcFlags) -> io::Result<WcResult> {
+    let file = File::open(filename)?;
+    let metadata = file.metadata()?;
+    let counter = Counter::new(flags);
+
+    // Some pseudo files report a size of 0, so those are read
+    if metadata.is_file() && metadata.len() > 0 {
+        let len = metadata.len();
+
+        if !counter.scan && !flags.print_lines {
+            return Ok(WcResult { bytes: len, ..WcResult::default() });
+        }
+
+        let threads = available_threads().min((len / PARALLEL_CHUNK_SIZE) as usize);
+
+        // The max line length can't be split, the lengths can span several chunks
+        if counter.scan && !flags.print_max_line_len && threads > 1 {
+            return wc_parallel(filename, len, threads, counter);
+        }
+    }
+
+    wc(file, flags)
+}
+
+/// Count `file` splitting it on `threads` chunks, counted at the same time.
+fn wc_parallel(filename: &str, len: u64, threads: usize, counter: Counter) -> io::Result<WcResult> {
+    let chunk_size = len / threads as u64;
+
+    // The chunks can't split the characters
+    let mut starts = vec![0];
+    let mut file = File::open(filename)?;
+    for i in 1..threads as u64 {
+        let start =
+            if counter.utf8 { char_boundary(&mut file, i * chunk_size)? } else { i * chunk_size };
+        starts.push(start.max(*starts.last().unwrap_or(&0)));
+    }
+
+    let handles: Vec<_> = (0..threads)
+        .map(|i| {
+            let start = starts[i];
+            // The last chunk takes the remainder and whatever was appended meanwhile
+            let size = starts.get(i + 1).map(|end| end - start).unwrap_or(u64::MAX);
+
+            // Each thread opens the file, so it has its own offset
+            let filename = filename.to_string();
+            thread::spawn(move || -> io::Result<Counter> {
+                let mut file = File::open(filename)?;
+                file.seek(SeekFrom::Start(start))?;
+                count(file.take(size), counter)
+            })
+        })
+        .collect();
+
+    let mut total = counter;
+    for handle in handles {
+        let chunk = handle.join().unwrap_or_else(|err| panic::resume_unwind(err))?;
+        total = total.combine(chunk);
+    }
+
+    Ok(total.finish())
+}
+
+/// Get the first offset of `file`, from `pos`, that is not in the middle of a UTF-8
+/// character.
+fn char_boundary(file: &mut File, pos: u64) -> io::Result<u64> {
+    let mut buffer = [0; 3];
+    file.seek(SeekFrom::Start(pos))?;
+    let read = file.read(&mut buffer)?;
+
+    // A character has at most 3 continuation bytes, if there are more they are invalid on
+    // both chunks
+    let continuation = buffer[..read].iter().take_while(|&&b| b & 0xC0 == 0x80).count();
+    Ok(pos + continuation as u64)
+}
+
+/// Number of threads that can run at the same time.
+#[cfg(unix)]
+fn available_threads() -> usize {
+    use coreutils_core::libc::{sysconf, _SC_NPROCESSORS_ONLN};
+
+    match unsafe { sysconf(_SC_NPROCESSORS_ONLN) } {
+        n if n > 0 => n as usize,
+        _ => 1,
+    }
+}
 
-    Ok(result)
+/// Number of threads that can run at the same time.
+#[cfg(not(unix))]
+fn available_threads() -> usize {
+    std::env::var("NUMBER_OF_PROCESSORS").ok().and_then(|n| n.parse().ok()).unwrap_or(1)
 }
 
 fn get_formatted_result(filename: &str, result: &WcResult, flags: WcFlags) -> String {
@@ -195,7 +477,7 @@ fn get_formatted_result(filename: &str, result: &WcResult, flags: WcFlags) -> St
         push("bytes", &mut s, result.bytes);
     }
     if flags.print_max_line_len {
-        push("max line length", &mut s, result.max_line_len.into());
+        push("max line length", &mut s, result.max_line_len);
     }
 
     if filename != "-" && !flags.pretty {
@@ -35,9 +35,10 @@ fn wc_stdin() {
         print_words: true,
         print_max_line_len: true,
         pretty: false,
+        utf8: true,
     };
-    let res = get_formatted_result("-", &wc(test_str).unwrap(), flags);
-    assert_eq!(res, String::from("1 5 22 22 21 "));
+    let res = get_formatted_result("-", &wc(test_str, flags).unwrap(), flags);
+    assert_eq!(res, String::from("0 5 21 21 21 "));
 }
 
 #[test]
@@ -50,14 +51,153 @@ fn wc_pretty_print() {
         print_words: false,
         print_max_line_len: false,
         pretty: true,
+        utf8: true,
     };
-    let res = get_formatted_result("test", &wc(test_str).unwrap(), flags);
+    let res = get_formatted_result("test", &wc(test_str, flags).unwrap(), flags);
     assert_eq!(
         res,
         String::from(
             "test
-  lines: 1
-  bytes: 22"
+  lines: 0
+  bytes: 21"
         )
     );
 }
+
+#[test]
+fn wc_lines_and_words() {
+    let test_str = TestReader::new("one two\n\nthree  four\tfive\n");
+    let flags = WcFlags::new();
+    let res = wc(test_str, flags).unwrap();
+    assert_eq!((res.lines, res.words, res.chars), (3, 5, 26));
+}
+
+#[test]
+fn wc_invalid_utf8() {
+    let input = &b"caf\xc3\xa9 \xff\xfe\n\x01\x02 x"[..];
+
+    let flags = WcFlags { utf8: true, ..WcFlags::new() };
+    let res = wc(input, flags).unwrap();
+    assert_eq!((res.lines, res.words, res.chars, res.bytes), (1, 2, 10, 13));
+
+    let flags = WcFlags { utf8: false, ..WcFlags::new() };
+    let res = wc(input, flags).unwrap();
+    assert_eq!((res.lines, res.words, res.chars, res.bytes), (1, 2, 13, 13));
+}
+
+#[test]
+fn wc_max_line_len() {
+    let flags = WcFlags { print_max_line_len: true, utf8: true, ..WcFlags::new() };
+    assert_eq!(wc(&b"a\tb\n\xe6\x97\xa5\xe6\x9c\xac\n"[..], flags).unwrap().max_line_len, 9);
+    assert_eq!(wc(&b"abc\rde\n1234567\t\x0cx"[..], flags).unwrap().max_line_len, 8);
+    assert_eq!(wc(&b"e\xcc\x81\xe3\x80\x80\xe6\x97\xa5"[..], flags).unwrap().max_line_len, 5);
+
+    let flags = WcFlags { utf8: false, ..flags };
+    assert_eq!(wc(&b"\xe6\x97\xa5\xe6\x9c\xac x"[..], flags).unwrap().max_line_len, 2);
+}
+
+#[test]
+fn wc_multibyte_words() {
+    let flags = WcFlags { utf8: true, ..WcFlags::new() };
+    let res = wc("日本\u{3000}語 a\u{a0}b\u{85}c".as_bytes(), flags).unwrap();
+    assert_eq!((res.words, res.chars), (4, 10));
+}
+
+#[test]
+fn wc_split_characters() {
+    let input = "ação – 日本 🦀\n\u{a0}x".as_bytes();
+    let flags = WcFlags { print_max_line_len: true, utf8: true, ..WcFlags::new() };
+    let whole = wc(input, flags).unwrap();
+
+    for i in 0..=input.len() {
+        for j in i..=input.len() {
+            let mut counter = Counter::new(flags);
+            counter.update(&input[..i]);
+            counter.update(&input[i..j]);
+            counter.update(&input[j..]);
+            let res = counter.finish();
+
+            assert_eq!(
+                (res.lines, res.words, res.chars, res.max_line_len),
+                (whole.lines, whole.words, whole.chars, whole.max_line_len)
+            );
+        }
+    }
+}
+
+#[test]
+fn wc_combine_split() {
+    let input =
+        "lorem ipsum\n dolor\x01sit amet,\x00 consectetur\n\nadipiscing elit — ação".as_bytes();
+    let flags = WcFlags { print_words: true, utf8: true, ..WcFlags::new() };
+
+    let mut whole = Counter::new(flags);
+    whole.update(input);
+    let whole = whole.finish();
+
+    let boundaries =
+        (0..=input.len()).filter(|&i| input.get(i).map(|b| b & 0xC0 != 0x80).unwrap_or(true));
+    for i in boundaries.clone() {
+        for j in boundaries.clone().filter(|&j| j >= i) {
+            let mut counters = [Counter::new(flags); 3];
+            counters[0].update(&input[..i]);
+            counters[1].update(&input[i..j]);
+            counters[2].update(&input[j..]);
+            let res = counters[0].combine(counters[1]).combine(counters[2]).finish();
+
+            assert_eq!(
+                (res.lines, res.words, res.chars, res.bytes),
+                (whole.lines, whole.words, whole.chars, whole.bytes)
+            );
+        }
+    }
+}
+
+#[test]
+fn wc_parallel_file() {
+    let path = std::env::temp_dir().join(format!("wc_parallel_{}", std::process::id()));
+    let content = "a few wörds\n  and\tsome 日本\n".repeat(1000);
+    std::fs::write(&path, &content).unwrap();
+
+    let flags = WcFlags { utf8: true, ..WcFlags::new() };
+    let filename = path.to_str().unwrap();
+    let seq = wc(content.as_bytes(), flags).unwrap();
+    let par = wc_parallel(filename, content.len() as u64, 7, Counter::new(flags)).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        (par.lines, par.words, par.chars, par.bytes),
+        (seq.lines, seq.words, seq.chars, seq.bytes)
+    );
+}
+
+#[test]
+fn wc_parallel_split_characters() {
+    let path = std::env::temp_dir().join(format!("wc_parallel_split_{}", std::process::id()));
+    // The chunks of most of the thread counts start in the middle of a character
+    let content = "🦀 ação\u{a0}日本語 x\n".repeat(97);
+    std::fs::write(&path, &content).unwrap();
+
+    let flags = WcFlags { utf8: true, ..WcFlags::new() };
+    let filename = path.to_str().unwrap();
+    let len = content.len() as u64;
+    let seq = wc(content.as_bytes(), flags).unwrap();
+
+    let mut split = 0;
+    for threads in 2..=32 {
+        let chunk_size = len / threads;
+        split +=
+            (1..threads).filter(|i| !content.is_char_boundary((i * chunk_size) as usize)).count();
+
+        let par = wc_parallel(filename, len, threads as usize, Counter::new(flags)).unwrap();
+        assert_eq!(
+            (par.lines, par.words, par.chars, par.bytes),
+            (seq.lines, seq.words, seq.chars, seq.bytes),
+            "{} threads",
+            threads
+        );
+    }
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(split > 0);
+}
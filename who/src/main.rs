@@ -247,7 +247,7 @@ fn print_info(uts: &[&Utmpx], flags: WhoFlags) {
             let (msg, _) = def_status(u);
             println!(
                 "{:<12} {:<3} {:<10} {:<18}   {:<10}",
-                u.user(),
+                entry_name(u),
                 if flags.message { msg } else { ' ' },
                 u.device_name(),
                 u.login_time().format("%Y-%m-%d %H:%M"),
@@ -259,7 +259,7 @@ fn print_info(uts: &[&Utmpx], flags: WhoFlags) {
             let (msg, _) = def_status(u);
             println!(
                 "{:<}{:^3}{:-^}{:-^18}",
-                u.user(),
+                entry_name(u),
                 if flags.message { msg } else { ' ' },
                 // format!("{}", u.device_name()),
                 u.device_name(),
@@ -272,7 +272,7 @@ fn print_info(uts: &[&Utmpx], flags: WhoFlags) {
             let (msg, idle) = def_status(u);
             println!(
                 "{:<12} {:<3} {:<10} {:<18}    {:<10} {:<10}",
-                u.user(),
+                entry_name(u),
                 if flags.message { msg } else { ' ' },
                 u.device_name(),
                 u.login_time().format("%Y-%m-%d %H:%M"),
@@ -285,18 +285,48 @@ fn print_info(uts: &[&Utmpx], flags: WhoFlags) {
             let (msg, idle) = def_status(u);
             println!(
                 "{:<12} {:<3} {:<10} {:<10} {:<18}    {:<10} {:<10}",
-                u.user(),
+                entry_name(u),
                 if flags.message { msg } else { ' ' },
                 u.device_name(),
                 u.process_id(),
                 u.login_time().format("%Y-%m-%d %H:%M"),
                 idle,
-                format!("({})", u.host())
+                entry_comment(u)
             )
         });
     }
 }
 
+/// The name of a entry, special records use a description instead of the user name.
+#[cfg(not(target_os = "openbsd"))]
+fn entry_name(utmpx: &Utmpx) -> String {
+    if utmpx.is_boot_time() {
+        "system boot".to_string()
+    } else if let Some(run_level) = utmpx.run_level() {
+        run_level.to_string()
+    } else {
+        utmpx.user().to_string()
+    }
+}
+
+/// The comment of a entry, dead processes show how they exited.
+#[cfg(not(target_os = "openbsd"))]
+fn entry_comment(utmpx: &Utmpx) -> String {
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "solaris",
+        target_os = "illumos"
+    ))]
+    {
+        if let Some(exit) = utmpx.process_exit() {
+            return exit.to_string();
+        }
+    }
+
+    format!("({})", utmpx.host())
+}
+
 #[cfg(target_os = "openbsd")]
 fn print_info(uts: &[&Utmp], flags: WhoFlags) {
     if flags.is_all_false() {